@@ -58,6 +58,7 @@ fn load_motion_file_data(
             path,
             duration: zmo.get_duration(),
             total_attack_frames: zmo.total_attack_frames,
+            attack_frame_times: zmo.get_attack_frame_times(),
         })
     } else {
         Some(MotionFileData {
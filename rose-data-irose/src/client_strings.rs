@@ -6,19 +6,17 @@ use arrayvec::ArrayString;
 use rose_data::{ClientStrings, StringDatabase};
 
 pub fn get_client_strings(
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<Arc<ClientStrings>, anyhow::Error> {
-    let get_string = |id: u16| -> &'static str {
+    let get_string = |id: u16| -> Arc<str> {
         let mut key = ArrayString::<16>::new();
         write!(&mut key, "{}", id).ok();
-        unsafe {
-            std::mem::transmute(
-                string_database
-                    .client_strings
-                    .get_text_string(string_database.language, &key)
-                    .unwrap_or(""),
-            )
-        }
+        Arc::from(
+            string_database
+                .client_strings
+                .get_text_string(string_database.language, &key)
+                .unwrap_or(""),
+        )
     };
 
     Ok(Arc::new(ClientStrings {
@@ -97,7 +95,5 @@ pub fn get_client_strings(
         clan_create_conditions: get_string(98),
         clan_create_error_slogan: get_string(78),
         clan_error_permission: get_string(76),
-
-        _string_database: string_database,
     }))
 }
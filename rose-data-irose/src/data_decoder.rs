@@ -4,9 +4,10 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 
 use rose_data::{
-    AbilityType, AmmoIndex, ClanMemberPosition, DataDecoder, EffectBulletMoveType, EquipmentIndex,
-    ItemClass, ItemReference, ItemType, SkillActionMode, SkillBasicCommand, SkillTargetFilter,
-    SkillType, StatusEffectClearedByType, StatusEffectType, VehiclePartIndex, VehicleType,
+    AbilityType, AmmoIndex, ClanMemberPosition, DataDecoder, EffectBulletMoveType, Element,
+    EquipmentIndex, ItemClass, ItemReference, ItemType, SkillActionMode, SkillBasicCommand,
+    SkillTargetFilter, SkillType, StatusEffectClearedByType, StatusEffectType, VehiclePartIndex,
+    VehicleType,
 };
 
 macro_rules! impl_conversions {
@@ -139,6 +140,11 @@ pub enum IroseAbilityType {
     PassiveAvoid = 101,
     PassiveShieldDefence = 102,
     PassiveImmunity = 103,
+
+    ResistFire = 104,
+    ResistIce = 105,
+    ResistLightning = 106,
+    ResistEarth = 107,
 }
 impl_conversions!(IroseAbilityType, AbilityType, decode_ability_type);
 
@@ -321,6 +327,15 @@ pub enum IroseAmmoIndex {
 }
 impl_conversions!(IroseAmmoIndex, AmmoIndex, decode_ammo_index);
 
+#[derive(FromPrimitive)]
+pub enum IroseElement {
+    Fire = 1,
+    Ice = 2,
+    Lightning = 3,
+    Earth = 4,
+}
+impl_conversions!(IroseElement, Element, decode_element);
+
 #[derive(FromPrimitive)]
 pub enum IroseStatusEffectType {
     IncreaseHp = 1,
@@ -659,6 +674,10 @@ pub fn decode_ability_type(id: usize) -> Option<AbilityType> {
         IroseAbilityType::PassiveAvoid => Some(AbilityType::PassiveAvoid),
         IroseAbilityType::PassiveShieldDefence => Some(AbilityType::PassiveShieldDefence),
         IroseAbilityType::PassiveImmunity => Some(AbilityType::PassiveImmunity),
+        IroseAbilityType::ResistFire => Some(AbilityType::ResistFire),
+        IroseAbilityType::ResistIce => Some(AbilityType::ResistIce),
+        IroseAbilityType::ResistLightning => Some(AbilityType::ResistLightning),
+        IroseAbilityType::ResistEarth => Some(AbilityType::ResistEarth),
     }
 }
 
@@ -769,6 +788,10 @@ pub fn encode_ability_type(from: AbilityType) -> Option<usize> {
         AbilityType::PassiveAvoid => IroseAbilityType::PassiveAvoid.to_usize(),
         AbilityType::PassiveShieldDefence => IroseAbilityType::PassiveShieldDefence.to_usize(),
         AbilityType::PassiveImmunity => IroseAbilityType::PassiveImmunity.to_usize(),
+        AbilityType::ResistFire => IroseAbilityType::ResistFire.to_usize(),
+        AbilityType::ResistIce => IroseAbilityType::ResistIce.to_usize(),
+        AbilityType::ResistLightning => IroseAbilityType::ResistLightning.to_usize(),
+        AbilityType::ResistEarth => IroseAbilityType::ResistEarth.to_usize(),
     }
 }
 
@@ -1020,6 +1043,15 @@ pub fn decode_ammo_index(id: usize) -> Option<AmmoIndex> {
     }
 }
 
+pub fn decode_element(id: usize) -> Option<Element> {
+    match FromPrimitive::from_usize(id)? {
+        IroseElement::Fire => Some(Element::Fire),
+        IroseElement::Ice => Some(Element::Ice),
+        IroseElement::Lightning => Some(Element::Lightning),
+        IroseElement::Earth => Some(Element::Earth),
+    }
+}
+
 pub fn encode_equipment_index(id: EquipmentIndex) -> Option<usize> {
     match id {
         EquipmentIndex::Face => IroseEquipmentIndex::Face.to_usize(),
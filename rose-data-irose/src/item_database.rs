@@ -291,16 +291,14 @@ fn load_base_item(
     if check_valid && icon_index == 0 {
         return None;
     }
-    let item_strings = string_database.get_item(item_type, data.0.get(id, data.0.columns() - 1));
+    let string_id = data.0.get(id, data.0.columns() - 1);
+    let item_strings = string_database.get_item(item_type, string_id);
 
     Some(BaseItemData {
         id: ItemReference::new(item_type, id),
-        name: item_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.name) }),
-        description: item_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.description) }),
+        string_id: string_id.to_string(),
+        name: Arc::from(item_strings.as_ref().map_or("", |x| x.name)),
+        description: Arc::from(item_strings.as_ref().map_or("", |x| x.description)),
         class: data
             .get_item_class(id)
             .unwrap_or(IroseItemClass::Unknown)
@@ -540,9 +538,8 @@ macro_rules! load_items {
 
 pub fn get_item_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    strings: &StringDatabase,
 ) -> Result<ItemDatabase, anyhow::Error> {
-    let strings = &*string_database;
     let face = load_items! { vfs, strings, "3DDATA/STB/LIST_FACEITEM.STB", load_base_item, ItemType::Face, FaceItemData };
     let head =
         load_items! { vfs, strings, "3DDATA/STB/LIST_CAP.STB", load_head_item, HeadItemData };
@@ -597,7 +594,6 @@ pub fn get_item_database(
             + item_grades.len()
     );
     Ok(ItemDatabase::new(
-        string_database,
         face,
         head,
         body,
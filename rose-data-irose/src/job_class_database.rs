@@ -12,7 +12,7 @@ impl StbJobClass {
 
 pub fn get_job_class_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<JobClassDatabase, anyhow::Error> {
     let stb = StbJobClass(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_CLASS.STB")?);
     let mut job_classes = Vec::with_capacity(stb.0.rows());
@@ -27,13 +27,13 @@ pub fn get_job_class_database(
         let name = stb
             .0
             .try_get(row, stb.0.columns() - 1)
-            .map(|key| string_database.get_job_class_name(key));
+            .map_or("", |key| string_database.get_job_class_name(key));
         job_classes.push(Some(JobClassData {
             id: JobClassId::new(row as u16).unwrap(),
-            name: name.map_or("", |x| unsafe { std::mem::transmute(x) }),
+            name: Arc::from(name),
             jobs,
         }));
     }
 
-    Ok(JobClassDatabase::new(string_database, job_classes))
+    Ok(JobClassDatabase::new(job_classes))
 }
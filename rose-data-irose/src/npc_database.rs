@@ -1,19 +1,62 @@
 use enum_map::enum_map;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     num::{NonZeroU16, NonZeroUsize},
     sync::Arc,
 };
 
 use rose_data::{
     EffectFileId, EffectId, MotionFileData, MotionId, NpcConversationData, NpcData, NpcDatabase,
-    NpcDatabaseOptions, NpcId, NpcMotionAction, NpcStoreTabData, NpcStoreTabId, SoundId,
-    StringDatabase,
+    NpcDatabaseOptions, NpcId, NpcMotionAction, NpcStoreTabData, NpcStoreTabId, QuestTriggerHash,
+    SoundId, StringDatabase,
+};
+use rose_file_readers::{
+    stb_column, ChrFile, ConFile, LuaFunction, StbFile, VfsPathBuf, VirtualFilesystem, ZmoFile,
 };
-use rose_file_readers::{stb_column, ChrFile, StbFile, VfsPathBuf, VirtualFilesystem, ZmoFile};
 
 use crate::data_decoder::decode_item_base1000;
 
+// The conversation scripts are compiled lua, so we cannot run them. Instead we
+// collect the constant trigger name of every QF_doQuestTrigger call in the script.
+fn load_conversation_quest_triggers(
+    vfs: &VirtualFilesystem,
+    filename: &str,
+) -> Option<HashSet<QuestTriggerHash>> {
+    let con_file = match vfs.read_file::<ConFile, _>(filename) {
+        Ok(con_file) => con_file,
+        Err(error) => {
+            log::warn!(
+                "Failed to read conversation file {}, rejecting its quest triggers: {}",
+                filename,
+                error
+            );
+            return None;
+        }
+    };
+
+    if con_file.script_binary.is_empty() {
+        return Some(HashSet::new());
+    }
+
+    match LuaFunction::read(&con_file.script_binary) {
+        Ok(script) => Some(
+            script
+                .find_global_call_string_arguments("QF_doQuestTrigger")
+                .iter()
+                .map(|name| QuestTriggerHash::from(name.as_str()))
+                .collect(),
+        ),
+        Err(error) => {
+            log::warn!(
+                "Failed to read conversation script in {}, rejecting its quest triggers: {}",
+                filename,
+                error
+            );
+            None
+        }
+    }
+}
+
 struct StbNpc(StbFile);
 
 impl StbNpc {
@@ -117,6 +160,7 @@ fn load_motion_file_data(
             path,
             duration: zmo.get_duration(),
             total_attack_frames: zmo.total_attack_frames,
+            attack_frame_times: zmo.get_attack_frame_times(),
         })
     } else {
         Some(MotionFileData {
@@ -128,7 +172,7 @@ fn load_motion_file_data(
 
 pub fn get_npc_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
     options: &NpcDatabaseOptions,
 ) -> Result<NpcDatabase, anyhow::Error> {
     let model_data = vfs.read_file::<ChrFile, _>("3DDATA/NPC/LIST_NPC.CHR")?;
@@ -166,15 +210,16 @@ pub fn get_npc_database(
             }
         }
 
-        let name = if let Some(entry) = npc_string_id.and_then(|key| string_database.get_npc(key)) {
-            unsafe { std::mem::transmute(entry.text) }
-        } else {
-            ""
-        };
+        let name = Arc::from(
+            npc_string_id
+                .and_then(|key| string_database.get_npc(key))
+                .map_or("", |entry| entry.text),
+        );
 
         npc_count += 1;
         npcs.push(Some(NpcData {
             id: NpcId::new(id as u16).unwrap(),
+            string_id: npc_string_id.map(String::from),
             name,
             walk_speed: data.get_walk_speed(id).unwrap_or(0),
             run_speed: data.get_run_speed(id).unwrap_or(0),
@@ -232,6 +277,7 @@ pub fn get_npc_database(
         if key.is_none() || filename.is_none() {
             continue;
         }
+        let filename = filename.unwrap();
         conversation_files.insert(
             key.unwrap().to_string(),
             NpcConversationData {
@@ -239,7 +285,8 @@ pub fn get_npc_database(
                 name: data.get_name(id).unwrap_or("").to_string(),
                 _type: data.get_type(id).unwrap_or("").to_string(),
                 description: data.get_description(id).unwrap_or("").to_string(),
-                filename: filename.unwrap().to_string(),
+                filename: filename.to_string(),
+                quest_triggers: load_conversation_quest_triggers(vfs, filename),
             },
         );
     }
@@ -255,11 +302,11 @@ pub fn get_npc_database(
         }
 
         if !items.is_empty() {
-            let name = if let Some(entry) = string_database.get_npc_store_tab(data.get(id, 1)) {
-                unsafe { std::mem::transmute(entry.text) }
-            } else {
-                ""
-            };
+            let name = Arc::from(
+                string_database
+                    .get_npc_store_tab(data.get(id, 1))
+                    .map_or("", |entry| entry.text),
+            );
 
             store_tabs.insert(
                 NpcStoreTabId::new(id as u16).unwrap(),
@@ -290,7 +337,6 @@ pub fn get_npc_database(
         store_tabs.len()
     );
     Ok(NpcDatabase::new(
-        string_database,
         npcs,
         conversation_files,
         store_tabs,
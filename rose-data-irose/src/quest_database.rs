@@ -12,7 +12,7 @@ impl StbQuest {
 
 pub fn get_quest_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<QuestDatabase, anyhow::Error> {
     let quest_s_stb = vfs.read_file_with::<StbFile, _>(
         "3DDATA/QUESTDATA/QUEST_S.STB",
@@ -40,18 +40,10 @@ pub fn get_quest_database(
             let quest_strings = string_database.get_quest(string_id);
             quests.push(Some(QuestData {
                 id: row,
-                name: quest_strings
-                    .as_ref()
-                    .map_or("", |x| unsafe { std::mem::transmute(x.name) }),
-                description: quest_strings
-                    .as_ref()
-                    .map_or("", |x| unsafe { std::mem::transmute(x.description) }),
-                start_message: quest_strings
-                    .as_ref()
-                    .map_or("", |x| unsafe { std::mem::transmute(x.start_message) }),
-                end_message: quest_strings
-                    .as_ref()
-                    .map_or("", |x| unsafe { std::mem::transmute(x.end_message) }),
+                name: Arc::from(quest_strings.as_ref().map_or("", |x| x.name)),
+                description: Arc::from(quest_strings.as_ref().map_or("", |x| x.description)),
+                start_message: Arc::from(quest_strings.as_ref().map_or("", |x| x.start_message)),
+                end_message: Arc::from(quest_strings.as_ref().map_or("", |x| x.end_message)),
                 time_limit,
             }));
         } else {
@@ -81,7 +73,6 @@ pub fn get_quest_database(
 
     debug!("Loaded {} QSD triggers", triggers.len());
     Ok(QuestDatabase {
-        _string_database: string_database,
         quests,
         strings,
         triggers,
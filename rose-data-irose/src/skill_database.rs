@@ -14,8 +14,8 @@ use rose_data::{
 use rose_file_readers::{stb_column, StbFile, VirtualFilesystem};
 
 use crate::data_decoder::{
-    decode_item_class, IroseAbilityType, IroseSkillActionMode, IroseSkillBasicCommand,
-    IroseSkillPageType, IroseSkillTargetFilter, IroseSkillType,
+    decode_item_class, IroseAbilityType, IroseElement, IroseSkillActionMode,
+    IroseSkillBasicCommand, IroseSkillPageType, IroseSkillTargetFilter, IroseSkillType,
 };
 
 pub const SKILL_PAGE_SIZE: usize = 30;
@@ -182,7 +182,7 @@ impl StbSkill {
     stb_column! { 83, get_area_hit_effect, i32 }
     stb_column! { 84, get_area_hit_sound, i32 }
     stb_column! { 85, get_learn_money_cost, u32 }
-    stb_column! { 86, get_attribute, i32 }
+    stb_column! { 86, get_element, IroseElement }
 
     pub fn get_cooldown(&self, id: usize) -> SkillCooldown {
         let duration =
@@ -202,12 +202,8 @@ fn load_skill(data: &StbSkill, string_database: &StringDatabase, id: usize) -> O
 
     Some(SkillData {
         id: skill_id,
-        name: skill_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.name) }),
-        description: skill_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.description) }),
+        name: Arc::from(skill_strings.as_ref().map_or("", |x| x.name)),
+        description: Arc::from(skill_strings.as_ref().map_or("", |x| x.description)),
         base_skill_id: data.get_base_skill_id(id),
         action_mode: data
             .get_action_mode(id)
@@ -239,6 +235,7 @@ fn load_skill(data: &StbSkill, string_database: &StringDatabase, id: usize) -> O
         casting_effects: data.get_casting_effects(id),
         cooldown: data.get_cooldown(id),
         damage_type: data.get_damage_type(id).unwrap_or(0),
+        element: data.get_element(id).and_then(|x| x.try_into().ok()),
         harm: data.get_harm(id).unwrap_or(0),
         hit_effect_file_id: data.get_hit_effect_id(id),
         hit_link_dummy_bone_id: data
@@ -282,15 +279,15 @@ fn load_skill(data: &StbSkill, string_database: &StringDatabase, id: usize) -> O
 
 pub fn get_skill_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<SkillDatabase, anyhow::Error> {
     let data = StbSkill(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_SKILL.STB")?);
     let mut skills = Vec::with_capacity(data.rows());
     skills.push(None); // SkillId 0
     for id in 1..data.rows() {
-        skills.push(load_skill(&data, &string_database, id));
+        skills.push(load_skill(&data, string_database, id));
     }
 
     debug!("Loaded {} skills", skills.len());
-    Ok(SkillDatabase::new(string_database, skills))
+    Ok(SkillDatabase::new(skills))
 }
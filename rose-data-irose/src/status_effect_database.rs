@@ -58,18 +58,14 @@ fn load_status_effect(
 
     Some(StatusEffectData {
         id,
-        name: status_effect_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.name) }),
-        description: status_effect_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.description) }),
-        start_message: status_effect_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.start_message) }),
-        end_message: status_effect_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.end_message) }),
+        name: Arc::from(status_effect_strings.as_ref().map_or("", |x| x.name)),
+        description: Arc::from(status_effect_strings.as_ref().map_or("", |x| x.description)),
+        start_message: Arc::from(
+            status_effect_strings
+                .as_ref()
+                .map_or("", |x| x.start_message),
+        ),
+        end_message: Arc::from(status_effect_strings.as_ref().map_or("", |x| x.end_message)),
         status_effect_type,
         can_be_reapplied: data.get_can_be_reapplied(row).unwrap_or(false),
         cleared_by_type: data
@@ -85,20 +81,19 @@ fn load_status_effect(
 
 pub fn get_status_effect_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<StatusEffectDatabase, anyhow::Error> {
     let data = StbStatus(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_STATUS.STB")?);
     let mut status_effects = HashMap::new();
 
     for row in 1..data.0.rows() {
-        if let Some(status_effect_data) = load_status_effect(&data, &string_database, row) {
+        if let Some(status_effect_data) = load_status_effect(&data, string_database, row) {
             status_effects.insert(row as u16, status_effect_data);
         }
     }
 
     debug!("Loaded {} status effects", status_effects.len());
     Ok(StatusEffectDatabase::new(
-        string_database,
         status_effects,
         StatusEffectId::new(43).unwrap(),
     ))
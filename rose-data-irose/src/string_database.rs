@@ -10,16 +10,26 @@ use crate::{
     encode_skill_target_filter, encode_skill_type,
 };
 
+/// Loads the strings of `language`, `fallback_language` which is used for any
+/// missing strings, and `additional_languages` which can be looked up by name.
 pub fn get_string_database(
     vfs: &VirtualFilesystem,
     language: usize,
+    fallback_language: usize,
+    additional_languages: &[usize],
 ) -> Result<Arc<StringDatabase>, anyhow::Error> {
+    let mut languages = vec![language, fallback_language];
+    languages.extend_from_slice(additional_languages);
+    languages.sort_unstable();
+    languages.dedup();
     let stl_read_options = StlReadOptions {
-        language_filter: Some(vec![language]),
+        language_filter: Some(languages.clone()),
     };
 
     Ok(Arc::new(StringDatabase {
         language,
+        fallback_language,
+        languages,
         encode_ability_type,
         encode_clan_member_position,
         encode_item_class,
@@ -1,20 +1,25 @@
-use std::sync::Arc;
+use std::{collections::BTreeSet, path::Path, sync::Arc};
 
 use bevy::math::{Quat, Vec2, Vec3, Vec3Swizzles};
-use log::debug;
+use log::{debug, warn};
 
 use rose_data::{
-    NpcConversationId, NpcId, SkyboxId, StringDatabase, ZoneData, ZoneDatabase, ZoneEventObject,
-    ZoneId, ZoneList, ZoneListEntry, ZoneMonsterSpawnPoint, ZoneNpcSpawn, WORLD_TICKS_PER_DAY,
+    NpcConversationId, NpcId, SkyboxId, StringDatabase, WarpGateId, ZoneData, ZoneDatabase,
+    ZoneEventObject, ZoneId, ZoneList, ZoneListEntry, ZoneMonsterSpawnPoint, ZoneNavGrid,
+    ZoneNavGridDatabase, ZoneNpcSpawn, ZoneWarpGate, WORLD_TICKS_PER_DAY,
 };
 use rose_file_readers::{
-    stb_column, IfoEventObject, IfoFile, IfoMonsterSpawn, IfoMonsterSpawnPoint, IfoNpc,
-    IfoReadOptions, StbFile, VfsPath, VfsPathBuf, VirtualFilesystem, ZonFile, ZonReadOptions,
+    stb_column, HimFile, IfoEventObject, IfoFile, IfoMonsterSpawn, IfoMonsterSpawnPoint, IfoNpc,
+    IfoObject, IfoReadOptions, StbFile, VfsPath, VfsPathBuf, VirtualFilesystem, ZonFile,
+    ZonReadOptions,
 };
 
 const MIN_SECTOR_SIZE: u32 = 5000;
 const MAX_SECTOR_SIZE: u32 = 12000;
 
+// Terrain steeper than this, as a ratio of height change to cell size, is treated as not movable
+const NAV_GRID_MAX_WALKABLE_SLOPE: f32 = 2.0;
+
 pub struct StbZone(pub StbFile);
 
 #[allow(dead_code)]
@@ -64,6 +69,125 @@ pub enum LoadZoneError {
     ZonFileNotFound,
 }
 
+/// Returns the (x, y) of the zone blocks which have a file with any of the
+/// extensions, ordered by row. When the game data cannot be listed every block
+/// of the 64x64 grid is returned, and the caller must try to read each one.
+pub(crate) fn find_zone_blocks(
+    vfs: &VirtualFilesystem,
+    zone_base_directory: &Path,
+    extensions: &[&str],
+) -> Vec<(u32, u32)> {
+    let mut blocks = BTreeSet::new();
+
+    for extension in extensions {
+        let Some(files) = vfs.glob(&format!(
+            "{}/*_*.{}",
+            zone_base_directory.to_string_lossy(),
+            extension
+        )) else {
+            return (0..64u32)
+                .flat_map(|block_y| (0..64u32).map(move |block_x| (block_x, block_y)))
+                .collect();
+        };
+
+        blocks.extend(files.iter().filter_map(|path| {
+            let (block_x, block_y) = path.path().file_stem()?.to_str()?.split_once('_')?;
+            let (block_x, block_y) = (block_x.parse::<u32>().ok()?, block_y.parse::<u32>().ok()?);
+            (block_x < 64 && block_y < 64).then_some((block_y, block_x))
+        }));
+    }
+
+    blocks
+        .into_iter()
+        .map(|(block_y, block_x)| (block_x, block_y))
+        .collect()
+}
+
+/// The terrain heightmaps of a zone, every heightmap has the same size.
+pub(crate) struct ZoneHeightmaps {
+    pub blocks: Vec<(u32, u32, HimFile)>,
+    pub min_block_x: u32,
+    pub min_block_y: u32,
+    pub max_block_x: u32,
+    pub max_block_y: u32,
+    pub cells_per_block: usize,
+}
+
+impl ZoneHeightmaps {
+    /// Reads the heightmaps of every block of a zone, heightmaps which do not
+    /// match the size of the first are ignored. Returns None if the zone has
+    /// no heightmaps.
+    pub fn load(vfs: &VirtualFilesystem, zone_base_directory: &Path, id: usize) -> Option<Self> {
+        let mut blocks: Vec<(u32, u32, HimFile)> = Vec::new();
+        for (block_x, block_y) in find_zone_blocks(vfs, zone_base_directory, &["HIM"]) {
+            let Ok(him_file) = vfs.read_file::<HimFile, _>(
+                zone_base_directory.join(format!("{}_{}.HIM", block_x, block_y)),
+            ) else {
+                continue;
+            };
+            if him_file.width <= 1 || him_file.width != him_file.height {
+                continue;
+            }
+
+            if let Some((_, _, first)) = blocks.first() {
+                if first.width != him_file.width {
+                    warn!(
+                        "Ignoring zone {} heightmap {}_{} with size {} which does not match size {}",
+                        id, block_x, block_y, him_file.width, first.width
+                    );
+                    continue;
+                }
+            }
+
+            blocks.push((block_x, block_y, him_file));
+        }
+
+        let cells_per_block = (blocks.first()?.2.width - 1) as usize;
+        Some(Self {
+            min_block_x: blocks.iter().map(|(x, _, _)| *x).min()?,
+            min_block_y: blocks.iter().map(|(_, y, _)| *y).min()?,
+            max_block_x: blocks.iter().map(|(x, _, _)| *x).max()?,
+            max_block_y: blocks.iter().map(|(_, y, _)| *y).max()?,
+            cells_per_block,
+            blocks,
+        })
+    }
+
+    /// Returns the world position of the lowest x and y corner of the
+    /// heightmaps. Block y increases in the opposite direction to world y,
+    /// with block 65 at world y 0.
+    pub fn get_origin(&self, block_size: f32) -> Vec2 {
+        Vec2::new(
+            self.min_block_x as f32 * block_size,
+            (64 - self.max_block_y) as f32 * block_size,
+        )
+    }
+
+    /// Returns the number of cells covered by the heightmaps in x and y
+    pub fn get_num_cells(&self) -> (usize, usize) {
+        (
+            (self.max_block_x - self.min_block_x + 1) as usize * self.cells_per_block,
+            (self.max_block_y - self.min_block_y + 1) as usize * self.cells_per_block,
+        )
+    }
+
+    /// Returns the cell of the whole grid for a vertex of a block's heightmap,
+    /// with y flipped to increase in the same direction as world y
+    pub fn get_grid_position(
+        &self,
+        block_x: u32,
+        block_y: u32,
+        vertex_x: usize,
+        vertex_y: usize,
+    ) -> (usize, usize) {
+        (
+            (block_x - self.min_block_x) as usize * self.cells_per_block + vertex_x,
+            (self.max_block_y - block_y) as usize * self.cells_per_block
+                + (self.cells_per_block - vertex_y),
+        )
+    }
+}
+
 fn create_monster_spawn(
     spawn: &IfoMonsterSpawnPoint,
     object_offset: Vec3,
@@ -129,6 +253,17 @@ fn create_event_object(
     }
 }
 
+fn create_warp_gate(warp_object: &IfoObject, object_offset: Vec3) -> ZoneWarpGate {
+    ZoneWarpGate {
+        warp_gate_id: WarpGateId::new(warp_object.warp_id),
+        position: Vec3::new(
+            warp_object.position.x,
+            warp_object.position.y,
+            warp_object.position.z,
+        ) + object_offset,
+    }
+}
+
 fn load_zone(
     vfs: &VirtualFilesystem,
     data: &StbZone,
@@ -156,6 +291,7 @@ fn load_zone(
     let mut monster_spawns = Vec::new();
     let mut npcs = Vec::new();
     let mut event_objects = Vec::new();
+    let mut warp_gates = Vec::new();
 
     let mut num_blocks = 0;
     let mut min_block_x = None;
@@ -182,42 +318,41 @@ fn load_zone(
         skip_effect_objects: true,
         skip_sound_objects: true,
         skip_water_planes: true,
-        skip_warp_objects: true,
+        skip_warp_objects: false,
     };
 
-    for block_y in 0..64u32 {
-        for block_x in 0..64u32 {
-            if let Ok(ifo_file) = vfs.read_file_with::<IfoFile, _>(
-                zone_base_directory.join(format!("{}_{}.IFO", block_x, block_y)),
-                &ifo_read_options,
-            ) {
-                monster_spawns.extend(
-                    ifo_file
-                        .monster_spawns
-                        .iter()
-                        .map(|x| create_monster_spawn(x, objects_offset)),
-                );
-                npcs.extend(
-                    ifo_file
-                        .npcs
-                        .iter()
-                        .map(|x| create_npc_spawn(x, objects_offset)),
-                );
-                event_objects.extend(ifo_file.event_objects.iter().map(|event_object| {
-                    create_event_object(
-                        event_object,
-                        objects_offset,
-                        block_x as i32,
-                        block_y as i32,
-                    )
-                }));
-                num_blocks += 1;
-
-                min_block_x = Some(min_block_x.map_or(block_x, |value| u32::min(value, block_x)));
-                min_block_y = Some(min_block_y.map_or(block_y, |value| u32::min(value, block_y)));
-                max_block_x = Some(max_block_x.map_or(block_x, |value| u32::max(value, block_x)));
-                max_block_y = Some(max_block_y.map_or(block_y, |value| u32::max(value, block_y)));
-            }
+    for (block_x, block_y) in find_zone_blocks(vfs, zone_base_directory, &["IFO"]) {
+        if let Ok(ifo_file) = vfs.read_file_with::<IfoFile, _>(
+            zone_base_directory.join(format!("{}_{}.IFO", block_x, block_y)),
+            &ifo_read_options,
+        ) {
+            monster_spawns.extend(
+                ifo_file
+                    .monster_spawns
+                    .iter()
+                    .map(|x| create_monster_spawn(x, objects_offset)),
+            );
+            npcs.extend(
+                ifo_file
+                    .npcs
+                    .iter()
+                    .map(|x| create_npc_spawn(x, objects_offset)),
+            );
+            event_objects.extend(ifo_file.event_objects.iter().map(|event_object| {
+                create_event_object(event_object, objects_offset, block_x as i32, block_y as i32)
+            }));
+            warp_gates.extend(
+                ifo_file
+                    .warps
+                    .iter()
+                    .map(|x| create_warp_gate(x, objects_offset)),
+            );
+            num_blocks += 1;
+
+            min_block_x = Some(min_block_x.map_or(block_x, |value| u32::min(value, block_x)));
+            min_block_y = Some(min_block_y.map_or(block_y, |value| u32::min(value, block_y)));
+            max_block_x = Some(max_block_x.map_or(block_x, |value| u32::max(value, block_x)));
+            max_block_y = Some(max_block_y.map_or(block_y, |value| u32::max(value, block_y)));
         }
     }
 
@@ -261,12 +396,8 @@ fn load_zone(
     }
 
     let zone_strings = string_database.get_zone(data.get_zone_string_id(id).unwrap_or(""));
-    let name = zone_strings
-        .as_ref()
-        .map_or("", |x| unsafe { std::mem::transmute(x.name) });
-    let description = zone_strings
-        .as_ref()
-        .map_or("", |x| unsafe { std::mem::transmute(x.description) });
+    let name = zone_strings.as_ref().map_or("", |x| x.name);
+    let description = zone_strings.as_ref().map_or("", |x| x.description);
     debug!(
         "Loaded zone {} {} blocks: {}, spawns: {}, npcs: {}, sectors ({}, {}), start: {}",
         id,
@@ -280,14 +411,15 @@ fn load_zone(
     );
     Ok(ZoneData {
         id: ZoneId::new(id as u16).unwrap(),
-        name,
-        description,
+        name: Arc::from(name),
+        description: Arc::from(description),
         sector_size,
         grid_per_patch: zon_file.grid_per_patch,
         grid_size: zon_file.grid_size,
         event_objects,
         monster_spawns,
         npcs,
+        warp_gates,
         sectors_base_position: Vec2::new((min_x as f32) * block_size, (min_y as f32) * block_size),
         num_sectors_x,
         num_sectors_y,
@@ -324,16 +456,103 @@ fn load_zone(
 
 pub fn get_zone_database(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<ZoneDatabase, anyhow::Error> {
     let data = StbZone(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_ZONE.STB")?);
     let mut zones = Vec::with_capacity(data.rows());
     zones.push(None); // Zone ID 0
     for id in 1..data.rows() {
-        zones.push(load_zone(vfs, &data, &string_database, id).ok());
+        zones.push(load_zone(vfs, &data, string_database, id).ok());
+    }
+
+    Ok(ZoneDatabase::new(zones))
+}
+
+fn load_zone_nav_grid(
+    vfs: &VirtualFilesystem,
+    data: &StbZone,
+    id: usize,
+) -> Result<ZoneNavGrid, LoadZoneError> {
+    let zone_file = VfsPath::from(data.get_zone_file(id).ok_or(LoadZoneError::NotExists)?);
+    let zone_base_directory = zone_file
+        .path()
+        .parent()
+        .ok_or(LoadZoneError::ZonFileInvalidPath)?;
+
+    let zon_file: ZonFile = vfs
+        .read_file_with(
+            &zone_file,
+            &ZonReadOptions {
+                skip_zone_info: false,
+                skip_event_positions: true,
+                skip_textures: true,
+                skip_tiles: true,
+            },
+        )
+        .map_err(|_| LoadZoneError::ZonFileNotFound)?;
+
+    let heightmaps =
+        ZoneHeightmaps::load(vfs, zone_base_directory, id).ok_or(LoadZoneError::NotExists)?;
+
+    // Each cell is the area between 4 heights
+    let block_size = 16.0 * zon_file.grid_per_patch * zon_file.grid_size;
+    let cells_per_block = heightmaps.cells_per_block;
+    let cell_size = block_size / cells_per_block as f32;
+    let max_height_delta = cell_size * NAV_GRID_MAX_WALKABLE_SLOPE;
+    let (width, height) = heightmaps.get_num_cells();
+    let origin = heightmaps.get_origin(block_size);
+    let mut walkable = vec![false; width * height];
+
+    for (block_x, block_y, him_file) in heightmaps.blocks.iter() {
+        for tile_y in 0..cells_per_block {
+            for tile_x in 0..cells_per_block {
+                let corners = [
+                    him_file.get(tile_x as u32, tile_y as u32),
+                    him_file.get(tile_x as u32 + 1, tile_y as u32),
+                    him_file.get(tile_x as u32, tile_y as u32 + 1),
+                    him_file.get(tile_x as u32 + 1, tile_y as u32 + 1),
+                ];
+                let Some(corners) = corners.into_iter().collect::<Option<Vec<f32>>>() else {
+                    continue;
+                };
+                let min_height = corners.iter().copied().fold(f32::MAX, f32::min);
+                let max_height = corners.iter().copied().fold(f32::MIN, f32::max);
+
+                // The cell is below the tile's top left vertex
+                let (cell_x, cell_y) =
+                    heightmaps.get_grid_position(*block_x, *block_y, tile_x, tile_y + 1);
+                walkable[cell_y * width + cell_x] = max_height - min_height <= max_height_delta;
+            }
+        }
+    }
+
+    debug!(
+        "Loaded zone {} nav grid {}x{} cells, walkable: {}",
+        id,
+        width,
+        height,
+        walkable.iter().filter(|walkable| **walkable).count(),
+    );
+    Ok(ZoneNavGrid {
+        origin,
+        cell_size,
+        width,
+        height,
+        walkable,
+    })
+}
+
+pub fn get_zone_nav_grid_database(
+    vfs: &VirtualFilesystem,
+) -> Result<ZoneNavGridDatabase, anyhow::Error> {
+    let data = StbZone(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_ZONE.STB")?);
+    let mut nav_grids = Vec::with_capacity(data.rows());
+    nav_grids.push(None); // Zone ID 0
+    for id in 1..data.rows() {
+        nav_grids.push(load_zone_nav_grid(vfs, &data, id).ok());
     }
 
-    Ok(ZoneDatabase::new(string_database, zones))
+    Ok(ZoneNavGridDatabase::new(nav_grids))
 }
 
 fn load_zone_list_entry(
@@ -345,12 +564,8 @@ fn load_zone_list_entry(
 
     Ok(ZoneListEntry {
         id: ZoneId::new(id as u16).unwrap(),
-        name: zone_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.name) }),
-        description: zone_strings
-            .as_ref()
-            .map_or("", |x| unsafe { std::mem::transmute(x.description) }),
+        name: Arc::from(zone_strings.as_ref().map_or("", |x| x.name)),
+        description: Arc::from(zone_strings.as_ref().map_or("", |x| x.description)),
         minimap_path: data.get_zone_minimap_filename(id).map(VfsPathBuf::new),
         minimap_start_x: data.get_zone_minimap_start_x(id).unwrap_or(0),
         minimap_start_y: data.get_zone_minimap_start_y(id).unwrap_or(0),
@@ -383,14 +598,14 @@ fn load_zone_list_entry(
 
 pub fn get_zone_list(
     vfs: &VirtualFilesystem,
-    string_database: Arc<StringDatabase>,
+    string_database: &StringDatabase,
 ) -> Result<ZoneList, anyhow::Error> {
     let data = StbZone(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_ZONE.STB")?);
     let mut zones = Vec::with_capacity(data.rows());
     zones.push(None); // Zone ID 0
     for id in 1..data.rows() {
-        zones.push(load_zone_list_entry(&data, &string_database, id).ok());
+        zones.push(load_zone_list_entry(&data, string_database, id).ok());
     }
 
-    Ok(ZoneList::new(string_database, zones))
+    Ok(ZoneList::new(zones))
 }
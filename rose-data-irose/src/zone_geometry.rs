@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use bevy::math::{Affine3A, BVec3, Quat, Vec3};
+use log::debug;
+
+use rose_data::{ZoneGeometryData, ZoneGeometryDatabase, ZoneObstacle};
+use rose_file_readers::{
+    types, IfoFile, IfoObject, IfoReadOptions, StbFile, VfsPath, VirtualFilesystem, ZmsFile,
+    ZonFile, ZonReadOptions, ZscCollisionFlags, ZscFile,
+};
+
+use crate::zone_database::{find_zone_blocks, LoadZoneError, StbZone, ZoneHeightmaps};
+
+fn to_quat(x: f32, y: f32, z: f32, w: f32) -> Quat {
+    let rotation = Quat::from_xyzw(x, y, z, w);
+    if rotation.length_squared() < f32::EPSILON {
+        Quat::IDENTITY
+    } else {
+        rotation.normalize()
+    }
+}
+
+fn to_vec3(value: &types::Vec3<f32>) -> Vec3 {
+    Vec3::new(value.x, value.y, value.z)
+}
+
+fn load_mesh_bounds(vfs: &VirtualFilesystem, zsc: &ZscFile, mesh_id: u16) -> Option<(Vec3, Vec3)> {
+    let zms_file = vfs
+        .read_file::<ZmsFile, _>(zsc.meshes.get(mesh_id as usize)?)
+        .ok()?;
+
+    // Mesh vertex positions are in metres, whereas zone positions are in centimetres
+    let mut vertices = zms_file
+        .position
+        .iter()
+        .map(|&[x, y, z]| Vec3::new(x, y, z) * 100.0);
+    let first = vertices.next()?;
+    Some(vertices.fold((first, first), |(min, max), vertex| {
+        (min.min(vertex), max.max(vertex))
+    }))
+}
+
+fn create_object_obstacles(
+    vfs: &VirtualFilesystem,
+    zsc: &ZscFile,
+    mesh_bounds_cache: &mut HashMap<u16, Option<(Vec3, Vec3)>>,
+    object: &IfoObject,
+    objects_offset: Vec3,
+    obstacles: &mut Vec<ZoneObstacle>,
+) {
+    let Some(zsc_object) = zsc.objects.get(object.object_id as usize) else {
+        return;
+    };
+
+    let object_transform = Affine3A::from_scale_rotation_translation(
+        to_vec3(&object.scale),
+        to_quat(
+            object.rotation.x,
+            object.rotation.y,
+            object.rotation.z,
+            object.rotation.w,
+        ),
+        to_vec3(&object.position) + objects_offset,
+    );
+
+    let mut part_transforms: Vec<Affine3A> = Vec::with_capacity(zsc_object.parts.len());
+    for part in zsc_object.parts.iter() {
+        let local_transform = Affine3A::from_scale_rotation_translation(
+            to_vec3(&part.scale),
+            to_quat(
+                part.rotation.x,
+                part.rotation.y,
+                part.rotation.z,
+                part.rotation.w,
+            ),
+            to_vec3(&part.position),
+        );
+        let part_transform = part
+            .parent
+            .and_then(|parent| part_transforms.get(parent as usize))
+            .map_or(local_transform, |parent_transform| {
+                *parent_transform * local_transform
+            });
+        part_transforms.push(part_transform);
+
+        if part.collision_shape.is_none()
+            || part
+                .collision_flags
+                .contains(ZscCollisionFlags::PASSTHROUGH)
+        {
+            continue;
+        }
+
+        let Some((mesh_min, mesh_max)) = *mesh_bounds_cache
+            .entry(part.mesh_id)
+            .or_insert_with(|| load_mesh_bounds(vfs, zsc, part.mesh_id))
+        else {
+            continue;
+        };
+
+        let transform = object_transform * part_transform;
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for corner in 0..8 {
+            let point = transform.transform_point3(Vec3::select(
+                BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                mesh_max,
+                mesh_min,
+            ));
+            min = min.min(point);
+            max = max.max(point);
+        }
+        obstacles.push(ZoneObstacle { min, max });
+    }
+}
+
+fn load_zone_geometry(
+    vfs: &VirtualFilesystem,
+    data: &StbZone,
+    id: usize,
+) -> Result<ZoneGeometryData, LoadZoneError> {
+    let zone_file = VfsPath::from(data.get_zone_file(id).ok_or(LoadZoneError::NotExists)?);
+    let zone_base_directory = zone_file
+        .path()
+        .parent()
+        .ok_or(LoadZoneError::ZonFileInvalidPath)?;
+
+    let zon_file: ZonFile = vfs
+        .read_file_with(
+            &zone_file,
+            &ZonReadOptions {
+                skip_zone_info: false,
+                skip_event_positions: true,
+                skip_textures: true,
+                skip_tiles: true,
+            },
+        )
+        .map_err(|_| LoadZoneError::ZonFileNotFound)?;
+
+    let deco_zsc = data
+        .get_zone_deco_table(id)
+        .and_then(|path| vfs.read_file::<ZscFile, _>(path).ok());
+    let cnst_zsc = data
+        .get_zone_cnst_table(id)
+        .and_then(|path| vfs.read_file::<ZscFile, _>(path).ok());
+
+    let ifo_read_options = IfoReadOptions {
+        skip_event_objects: true,
+        skip_monster_spawns: true,
+        skip_npcs: true,
+        skip_animated_objects: true,
+        skip_collision_objects: true,
+        skip_cnst_objects: cnst_zsc.is_none(),
+        skip_deco_objects: deco_zsc.is_none(),
+        skip_effect_objects: true,
+        skip_sound_objects: true,
+        skip_water_planes: true,
+        skip_warp_objects: true,
+    };
+
+    let block_size = 16.0 * zon_file.grid_per_patch * zon_file.grid_size;
+    let objects_offset = Vec3::new(
+        (64.0 / 2.0) * block_size + block_size / 2.0,
+        (64.0 / 2.0) * block_size + block_size / 2.0,
+        0.0,
+    );
+
+    let heightmaps =
+        ZoneHeightmaps::load(vfs, zone_base_directory, id).ok_or(LoadZoneError::NotExists)?;
+    let mut obstacles = Vec::new();
+    let mut deco_mesh_bounds = HashMap::new();
+    let mut cnst_mesh_bounds = HashMap::new();
+
+    for (block_x, block_y) in find_zone_blocks(vfs, zone_base_directory, &["IFO"]) {
+        if let Ok(ifo_file) = vfs.read_file_with::<IfoFile, _>(
+            zone_base_directory.join(format!("{}_{}.IFO", block_x, block_y)),
+            &ifo_read_options,
+        ) {
+            if let Some(deco_zsc) = deco_zsc.as_ref() {
+                for object in ifo_file.deco_objects.iter() {
+                    create_object_obstacles(
+                        vfs,
+                        deco_zsc,
+                        &mut deco_mesh_bounds,
+                        object,
+                        objects_offset,
+                        &mut obstacles,
+                    );
+                }
+            }
+
+            if let Some(cnst_zsc) = cnst_zsc.as_ref() {
+                for object in ifo_file.cnst_objects.iter() {
+                    create_object_obstacles(
+                        vfs,
+                        cnst_zsc,
+                        &mut cnst_mesh_bounds,
+                        object,
+                        objects_offset,
+                        &mut obstacles,
+                    );
+                }
+            }
+        }
+    }
+
+    // Adjacent heightmaps share their edge vertices
+    let cells_per_block = heightmaps.cells_per_block;
+    let cell_size = block_size / cells_per_block as f32;
+    let (num_cells_x, num_cells_y) = heightmaps.get_num_cells();
+    let (width, height) = (num_cells_x + 1, num_cells_y + 1);
+    let origin = heightmaps.get_origin(block_size);
+    let mut heights = vec![None; width * height];
+
+    for (block_x, block_y, him_file) in heightmaps.blocks.iter() {
+        for vertex_y in 0..=cells_per_block {
+            for vertex_x in 0..=cells_per_block {
+                let (x, y) = heightmaps.get_grid_position(*block_x, *block_y, vertex_x, vertex_y);
+                heights[y * width + x] = him_file.get(vertex_x as u32, vertex_y as u32);
+            }
+        }
+    }
+
+    debug!(
+        "Loaded zone {} geometry {}x{} vertices, obstacles: {}",
+        id,
+        width,
+        height,
+        obstacles.len(),
+    );
+    Ok(ZoneGeometryData::new(
+        origin, cell_size, width, height, heights, obstacles,
+    ))
+}
+
+pub fn get_zone_geometry_database(
+    vfs: &VirtualFilesystem,
+) -> Result<ZoneGeometryDatabase, anyhow::Error> {
+    let data = StbZone(vfs.read_file::<StbFile, _>("3DDATA/STB/LIST_ZONE.STB")?);
+    let mut zones = Vec::with_capacity(data.rows());
+    zones.push(None); // Zone ID 0
+    for id in 1..data.rows() {
+        zones.push(load_zone_geometry(vfs, &data, id).ok());
+    }
+
+    Ok(ZoneGeometryDatabase::new(zones))
+}
@@ -1,3 +1,4 @@
+use enum_map::Enum;
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -107,4 +108,30 @@ pub enum AbilityType {
     PassiveAvoid,
     PassiveShieldDefence,
     PassiveImmunity,
+
+    ResistFire,
+    ResistIce,
+    ResistLightning,
+    ResistEarth,
+}
+
+impl AbilityType {
+    /// The element resisted by an elemental resistance ability.
+    pub fn resisted_element(&self) -> Option<Element> {
+        match *self {
+            AbilityType::ResistFire => Some(Element::Fire),
+            AbilityType::ResistIce => Some(Element::Ice),
+            AbilityType::ResistLightning => Some(Element::Lightning),
+            AbilityType::ResistEarth => Some(Element::Earth),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Enum)]
+pub enum Element {
+    Fire,
+    Ice,
+    Lightning,
+    Earth,
 }
@@ -4,7 +4,7 @@ use std::cmp::Ordering;
 
 use crate::{BaseItemData, ItemClass, ItemReference, ItemType};
 
-const MAX_STACKABLE_ITEM_QUANTITY: u32 = 999;
+pub const MAX_STACKABLE_ITEM_QUANTITY: u32 = 999;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Enum, Serialize, Deserialize)]
 pub enum EquipmentIndex {
@@ -122,6 +122,13 @@ pub struct EquipmentItem {
     pub is_crafted: bool,
     pub has_socket: bool,
     pub is_appraised: bool,
+    #[serde(default)]
+    pub is_bound: bool,
+
+    /// Assigned by the server when the item is created, so a single item can
+    /// be traced through trades, banks and drops
+    #[serde(default)]
+    pub instance_id: Option<u64>,
 }
 
 impl EquipmentItem {
@@ -136,6 +143,8 @@ impl EquipmentItem {
                 is_crafted: false,
                 has_socket: false,
                 is_appraised: false,
+                is_bound: false,
+                instance_id: None,
             })
         } else {
             None
@@ -345,6 +354,22 @@ impl Item {
         }
     }
 
+    pub fn is_bound(&self) -> bool {
+        match self {
+            Item::Equipment(equipment) => equipment.is_bound,
+            Item::Stackable(_) => false,
+        }
+    }
+
+    /// Stackable items merge together, so only equipment items have an
+    /// instance id.
+    pub fn instance_id(&self) -> Option<u64> {
+        match self {
+            Item::Equipment(equipment) => equipment.instance_id,
+            Item::Stackable(_) => None,
+        }
+    }
+
     pub fn is_same_item_reference(&self, item_reference: ItemReference) -> bool {
         match self {
             Item::Equipment(item) => item.item == item_reference,
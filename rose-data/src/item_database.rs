@@ -4,8 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
 use crate::{
-    AbilityType, EffectFileId, EffectId, JobClassId, SkillId, SoundId, StatusEffectId,
-    StringDatabase, VehiclePartIndex,
+    AbilityType, AmmoIndex, EffectFileId, EffectId, JobClassId, SkillId, SoundId, StatusEffectId,
+    VehiclePartIndex,
 };
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -288,13 +288,35 @@ impl ItemClass {
                 | ItemClass::DualGuns
         )
     }
+
+    /// The ammo slot consumed when attacking with a weapon of this class.
+    pub fn weapon_ammo_index(&self) -> Option<AmmoIndex> {
+        match *self {
+            ItemClass::Bow | ItemClass::Crossbow => Some(AmmoIndex::Arrow),
+            ItemClass::Gun | ItemClass::DualGuns => Some(AmmoIndex::Bullet),
+            ItemClass::Launcher => Some(AmmoIndex::Throw),
+            _ => None,
+        }
+    }
+
+    /// The ammo slot an item of this class can be equipped in.
+    pub fn ammo_index(&self) -> Option<AmmoIndex> {
+        match *self {
+            ItemClass::Arrow => Some(AmmoIndex::Arrow),
+            ItemClass::Bullet => Some(AmmoIndex::Bullet),
+            ItemClass::Shell => Some(AmmoIndex::Throw),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct BaseItemData {
     pub id: ItemReference,
-    pub name: &'static str,
-    pub description: &'static str,
+    /// Key of the item strings in the string database
+    pub string_id: String,
+    pub name: Arc<str>,
+    pub description: Arc<str>,
     pub class: ItemClass,
     pub base_price: u32,
     pub price_rate: u32,
@@ -482,7 +504,6 @@ pub enum ItemData<'a> {
 }
 
 pub struct ItemDatabase {
-    _string_database: Arc<StringDatabase>,
     face: Vec<Option<FaceItemData>>,
     head: Vec<Option<HeadItemData>>,
     body: Vec<Option<BodyItemData>>,
@@ -504,7 +525,6 @@ pub struct ItemDatabase {
 impl ItemDatabase {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        string_database: Arc<StringDatabase>,
         face: Vec<Option<FaceItemData>>,
         head: Vec<Option<HeadItemData>>,
         body: Vec<Option<BodyItemData>>,
@@ -522,7 +542,6 @@ impl ItemDatabase {
         item_grades: Vec<ItemGradeData>,
     ) -> Self {
         Self {
-            _string_database: string_database,
             face,
             head,
             body,
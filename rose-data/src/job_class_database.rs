@@ -2,8 +2,6 @@ use arrayvec::ArrayVec;
 use serde::{Deserialize, Serialize};
 use std::{num::NonZeroU16, str::FromStr, sync::Arc};
 
-use crate::StringDatabase;
-
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct JobId(u16);
 
@@ -16,24 +14,17 @@ id_wrapper_impl!(JobClassId, NonZeroU16, u16);
 
 pub struct JobClassData {
     pub id: JobClassId,
-    pub name: &'static str,
+    pub name: Arc<str>,
     pub jobs: ArrayVec<JobId, 8>,
 }
 
 pub struct JobClassDatabase {
-    _string_database: Arc<StringDatabase>,
     job_classes: Vec<Option<JobClassData>>,
 }
 
 impl JobClassDatabase {
-    pub fn new(
-        string_database: Arc<StringDatabase>,
-        job_classes: Vec<Option<JobClassData>>,
-    ) -> Self {
-        Self {
-            _string_database: string_database,
-            job_classes,
-        }
+    pub fn new(job_classes: Vec<Option<JobClassData>>) -> Self {
+        Self { job_classes }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &JobClassData> {
@@ -80,9 +80,11 @@ mod string_database;
 mod warp_gate_database;
 mod world;
 mod zone_database;
+mod zone_geometry;
 mod zone_list;
+mod zone_nav_grid;
 
-pub use ability::AbilityType;
+pub use ability::{AbilityType, Element};
 pub use ai_database::AiDatabase;
 pub use animation_event_flags::AnimationEventFlags;
 pub use character_motion_database::{
@@ -97,7 +99,7 @@ pub use effect_database::{
 };
 pub use item::{
     AmmoIndex, EquipmentIndex, EquipmentItem, Item, ItemSlotBehaviour, ItemWeaponType, StackError,
-    StackableItem, StackableSlotBehaviour, VehiclePartIndex,
+    StackableItem, StackableSlotBehaviour, VehiclePartIndex, MAX_STACKABLE_ITEM_QUANTITY,
 };
 pub use item_database::{
     BackItemData, BaseItemData, BodyItemData, ConsumableItemData, FaceItemData, FeetItemData,
@@ -131,5 +133,8 @@ pub use world::{
 };
 pub use zone_database::{
     ZoneData, ZoneDatabase, ZoneEventObject, ZoneId, ZoneMonsterSpawnPoint, ZoneNpcSpawn,
+    ZoneTimeOfDay, ZoneWarpGate, ZoneWeather,
 };
+pub use zone_geometry::{ZoneGeometryData, ZoneGeometryDatabase, ZoneObstacle};
 pub use zone_list::{ZoneList, ZoneListEntry};
+pub use zone_nav_grid::{ZoneNavGrid, ZoneNavGridDatabase};
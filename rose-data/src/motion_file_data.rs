@@ -14,4 +14,15 @@ pub struct MotionFileData {
     pub path: VfsPathBuf,
     pub duration: Duration,
     pub total_attack_frames: usize,
+
+    /// The time from the start of the motion of each attack frame
+    pub attack_frame_times: Vec<Duration>,
+}
+
+impl MotionFileData {
+    /// Returns when the first attack of the motion hits, or None if the motion
+    /// has no attack frames.
+    pub fn get_first_attack_frame_time(&self) -> Option<Duration> {
+        self.attack_frame_times.first().copied()
+    }
 }
@@ -2,14 +2,14 @@ use bevy::reflect::Reflect;
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     num::{NonZeroU16, NonZeroUsize},
     str::FromStr,
     sync::Arc,
 };
 
 use crate::{
-    EffectFileId, EffectId, ItemReference, MotionFileData, MotionId, SoundId, StringDatabase,
+    EffectFileId, EffectId, ItemReference, MotionFileData, MotionId, QuestTriggerHash, SoundId,
 };
 
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize, Reflect)]
@@ -44,7 +44,9 @@ pub enum NpcMotionAction {
 
 pub struct NpcData {
     pub id: NpcId,
-    pub name: &'static str,
+    /// Key of the npc strings in the string database
+    pub string_id: Option<String>,
+    pub name: Arc<str>,
     pub walk_speed: i32,
     pub run_speed: i32,
     pub scale: f32,
@@ -95,10 +97,14 @@ pub struct NpcConversationData {
     pub _type: String,
     pub description: String,
     pub filename: String,
+
+    /// Quest triggers which can be invoked by the conversation script, or None
+    /// if the script could not be read and no quest trigger is allowed
+    pub quest_triggers: Option<HashSet<QuestTriggerHash>>,
 }
 
 pub struct NpcStoreTabData {
-    pub name: &'static str,
+    pub name: Arc<str>,
     pub items: HashMap<u16, ItemReference>,
 }
 
@@ -107,25 +113,38 @@ pub struct NpcDatabaseOptions {
 }
 
 pub struct NpcDatabase {
-    _string_database: Arc<StringDatabase>,
     npcs: Vec<Option<NpcData>>,
     conversation_files: HashMap<String, NpcConversationData>,
+    conversation_quest_triggers: HashSet<QuestTriggerHash>,
+    death_quest_triggers: HashSet<QuestTriggerHash>,
     store_tabs: HashMap<NpcStoreTabId, NpcStoreTabData>,
     action_map: EnumMap<NpcMotionAction, MotionId>,
 }
 
 impl NpcDatabase {
     pub fn new(
-        string_database: Arc<StringDatabase>,
         npcs: Vec<Option<NpcData>>,
         conversation_files: HashMap<String, NpcConversationData>,
         store_tabs: HashMap<NpcStoreTabId, NpcStoreTabData>,
         action_map: EnumMap<NpcMotionAction, MotionId>,
     ) -> Self {
+        let conversation_quest_triggers = conversation_files
+            .values()
+            .filter_map(|conversation| conversation.quest_triggers.as_ref())
+            .flat_map(|quest_triggers| quest_triggers.iter().copied())
+            .collect();
+        let death_quest_triggers = npcs
+            .iter()
+            .flatten()
+            .filter(|npc| !npc.death_quest_trigger_name.is_empty())
+            .map(|npc| QuestTriggerHash::from(npc.death_quest_trigger_name.as_str()))
+            .collect();
+
         Self {
-            _string_database: string_database,
             npcs,
             conversation_files,
+            conversation_quest_triggers,
+            death_quest_triggers,
             store_tabs,
             action_map,
         }
@@ -149,6 +168,15 @@ impl NpcDatabase {
             .map(|(_, conv)| conv)
     }
 
+    /// Returns true if the quest trigger can be invoked by any conversation script
+    pub fn is_conversation_quest_trigger(&self, hash: QuestTriggerHash) -> bool {
+        self.conversation_quest_triggers.contains(&hash)
+    }
+
+    pub fn is_death_quest_trigger(&self, hash: QuestTriggerHash) -> bool {
+        self.death_quest_triggers.contains(&hash)
+    }
+
     pub fn get_npc_motion(&self, npc_id: NpcId, motion_id: MotionId) -> Option<&MotionFileData> {
         let npc_data = self.get_npc(npc_id)?;
         npc_data
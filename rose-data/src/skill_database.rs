@@ -9,8 +9,8 @@ use std::{
 };
 
 use crate::{
-    effect_database::EffectId, AbilityType, EffectFileId, ItemClass, JobClassId, MotionId, NpcId,
-    SoundId, StatusEffectId, StringDatabase, ZoneId,
+    effect_database::EffectId, AbilityType, EffectFileId, Element, ItemClass, JobClassId, MotionId,
+    NpcId, SoundId, StatusEffectId, ZoneId,
 };
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Reflect)]
@@ -116,6 +116,11 @@ impl SkillType {
                 | SkillType::Resurrection
         )
     }
+
+    /// Skills which fire the equipped ammo, every other skill saves it.
+    pub fn uses_ammo(&self) -> bool {
+        matches!(self, SkillType::EnforceBullet | SkillType::FireBullet)
+    }
 }
 
 pub type SkillCooldownGroup = NonZeroUsize;
@@ -141,8 +146,8 @@ pub struct SkillCastingEffect {
 #[derive(Debug)]
 pub struct SkillData {
     pub id: SkillId,
-    pub name: &'static str,
-    pub description: &'static str,
+    pub name: Arc<str>,
+    pub description: Arc<str>,
 
     pub base_skill_id: Option<SkillId>,
     pub level: u32,
@@ -176,6 +181,7 @@ pub struct SkillData {
     pub casting_effects: [Option<SkillCastingEffect>; 4],
     pub cooldown: SkillCooldown,
     pub damage_type: i32,
+    pub element: Option<Element>,
     pub harm: u32,
     pub hit_effect_file_id: Option<EffectFileId>,
     pub hit_link_dummy_bone_id: Option<usize>,
@@ -196,16 +202,12 @@ pub struct SkillData {
 }
 
 pub struct SkillDatabase {
-    _string_database: Arc<StringDatabase>,
     skills: Vec<Option<SkillData>>,
 }
 
 impl SkillDatabase {
-    pub fn new(string_database: Arc<StringDatabase>, skills: Vec<Option<SkillData>>) -> Self {
-        Self {
-            _string_database: string_database,
-            skills,
-        }
+    pub fn new(skills: Vec<Option<SkillData>>) -> Self {
+        Self { skills }
     }
 
     pub fn get_skill(&self, id: SkillId) -> Option<&SkillData> {
@@ -3,7 +3,7 @@ use enum_map::Enum;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, num::NonZeroU16, str::FromStr, sync::Arc};
 
-use crate::{EffectFileId, StringDatabase};
+use crate::EffectFileId;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Debug, Serialize, Deserialize)]
 pub struct StatusEffectId(NonZeroU16);
@@ -105,10 +105,10 @@ pub enum StatusEffectClearedByType {
 #[derive(Debug)]
 pub struct StatusEffectData {
     pub id: StatusEffectId,
-    pub name: &'static str,
-    pub description: &'static str,
-    pub start_message: &'static str,
-    pub end_message: &'static str,
+    pub name: Arc<str>,
+    pub description: Arc<str>,
+    pub start_message: Arc<str>,
+    pub end_message: Arc<str>,
     pub status_effect_type: StatusEffectType,
     pub can_be_reapplied: bool,
     pub cleared_by_type: StatusEffectClearedByType,
@@ -119,19 +119,16 @@ pub struct StatusEffectData {
 }
 
 pub struct StatusEffectDatabase {
-    _string_database: Arc<StringDatabase>,
     status_effects: HashMap<u16, StatusEffectData>,
     decrease_summon_life_status_effect_id: StatusEffectId,
 }
 
 impl StatusEffectDatabase {
     pub fn new(
-        string_database: Arc<StringDatabase>,
         status_effects: HashMap<u16, StatusEffectData>,
         decrease_summon_life_status_effect_id: StatusEffectId,
     ) -> Self {
         Self {
-            _string_database: string_database,
             status_effects,
             decrease_summon_life_status_effect_id,
         }
@@ -11,6 +11,13 @@ use crate::{AbilityType, ClanMemberPosition, ItemClass, ItemType, SkillTargetFil
 pub struct StringDatabase {
     pub language: usize,
 
+    /// Used for any string which is missing or empty in `language`
+    pub fallback_language: usize,
+
+    /// Every language which was loaded, including `language` and
+    /// `fallback_language`
+    pub languages: Vec<usize>,
+
     pub encode_ability_type: fn(AbilityType) -> Option<usize>,
     pub encode_clan_member_position: fn(ClanMemberPosition) -> Option<usize>,
     pub encode_item_class: fn(ItemClass) -> Option<usize>,
@@ -38,115 +45,142 @@ pub struct StringDatabase {
 }
 
 impl StringDatabase {
-    pub fn get_ability_type(&self, ability_type: AbilityType) -> &str {
-        let index = if let Some(index) = (self.encode_ability_type)(ability_type) {
-            index as u16
-        } else {
-            return "";
-        };
+    /// Returns the first non-empty result of `get` for `language` and then
+    /// the fallback language.
+    fn with_fallback<T>(
+        &self,
+        language: usize,
+        get: impl Fn(usize) -> Option<T>,
+        is_empty: impl Fn(&T) -> bool,
+    ) -> Option<T> {
+        get(language)
+            .filter(|value| !is_empty(value))
+            .or_else(|| get(self.fallback_language))
+    }
 
-        let mut key = ArrayString::<16>::new();
-        write!(&mut key, "{}", index).ok();
-        self.ability
-            .get_text_string(self.language, &key)
-            .unwrap_or("")
+    fn get_text<'a>(&self, stl: &'a StlFile, language: usize, key: &str) -> &'a str {
+        self.with_fallback(
+            language,
+            |language| stl.get_text_string(language, key),
+            |text| text.is_empty(),
+        )
+        .unwrap_or("")
     }
 
-    pub fn get_clan_member_position(&self, position: ClanMemberPosition) -> &str {
-        let index = if let Some(index) = (self.encode_clan_member_position)(position) {
-            index as u16
-        } else {
+    fn get_text_by_index<'a>(&self, stl: &'a StlFile, index: Option<usize>) -> &'a str {
+        let Some(index) = index else {
             return "";
         };
 
         let mut key = ArrayString::<16>::new();
-        write!(&mut key, "{}", index).ok();
-        self.clan.get_text_string(self.language, &key).unwrap_or("")
+        write!(&mut key, "{}", index as u16).ok();
+        self.get_text(stl, self.language, &key)
+    }
+
+    pub fn get_ability_type(&self, ability_type: AbilityType) -> &str {
+        self.get_text_by_index(&self.ability, (self.encode_ability_type)(ability_type))
+    }
+
+    pub fn get_clan_member_position(&self, position: ClanMemberPosition) -> &str {
+        self.get_text_by_index(&self.clan, (self.encode_clan_member_position)(position))
     }
 
     pub fn get_item(&self, item_type: ItemType, key: &str) -> Option<StlItemEntry> {
-        let index = self.item[item_type].lookup_key(key)?;
-        self.item[item_type].get_item_entry(self.language, index)
+        let stl = &self.item[item_type];
+        let index = stl.lookup_key(key)?;
+        self.with_fallback(
+            self.language,
+            |language| stl.get_item_entry(language, index),
+            |entry| entry.name.is_empty(),
+        )
+    }
+
+    /// Returns the name of an item in `language`, which can be any of the
+    /// languages loaded as well as the server language.
+    pub fn get_item_name(&self, language: usize, item_type: ItemType, key: &str) -> &str {
+        self.get_text(&self.item[item_type], language, key)
     }
 
     pub fn get_item_class(&self, item_class: ItemClass) -> &str {
-        let index = if let Some(index) = (self.encode_item_class)(item_class) {
-            index as u16
-        } else {
-            return "";
-        };
-        let mut key = ArrayString::<16>::new();
-        write!(&mut key, "{}", index).ok();
-        self.item_class
-            .get_text_string(self.language, &key)
-            .unwrap_or("")
+        self.get_text_by_index(&self.item_class, (self.encode_item_class)(item_class))
     }
 
     pub fn get_job_name(&self, job: u16) -> &str {
-        let mut key = ArrayString::<16>::new();
-        write!(&mut key, "{}", job).ok();
-        self.job.get_text_string(self.language, &key).unwrap_or("")
+        self.get_text_by_index(&self.job, Some(job as usize))
     }
 
     pub fn get_job_class_name(&self, key: &str) -> &str {
-        self.job_class
-            .get_text_string(self.language, key)
-            .unwrap_or("")
+        self.get_text(&self.job_class, self.language, key)
     }
 
     pub fn get_npc(&self, key: &str) -> Option<StlNormalEntry> {
         let index = self.npc.lookup_key(key)?;
-        self.npc.get_normal_entry(self.language, index)
+        self.with_fallback(
+            self.language,
+            |language| self.npc.get_normal_entry(language, index),
+            |entry| entry.text.is_empty(),
+        )
+    }
+
+    /// Returns the name of an npc in `language`, which can be any of the
+    /// languages loaded as well as the server language.
+    pub fn get_npc_name(&self, language: usize, key: &str) -> &str {
+        self.get_text(&self.npc, language, key)
     }
 
     pub fn get_npc_store_tab(&self, key: &str) -> Option<StlNormalEntry> {
         let index = self.npc_store_tabs.lookup_key(key)?;
-        self.npc_store_tabs.get_normal_entry(self.language, index)
+        self.with_fallback(
+            self.language,
+            |language| self.npc_store_tabs.get_normal_entry(language, index),
+            |entry| entry.text.is_empty(),
+        )
     }
 
     pub fn get_quest(&self, key: &str) -> Option<StlQuestEntry> {
         let index = self.quest.lookup_key(key)?;
-        self.quest.get_quest_entry(self.language, index)
+        self.with_fallback(
+            self.language,
+            |language| self.quest.get_quest_entry(language, index),
+            |entry| entry.name.is_empty(),
+        )
     }
 
     pub fn get_skill(&self, key: &str) -> Option<StlItemEntry> {
         let index = self.skill.lookup_key(key)?;
-        self.skill.get_item_entry(self.language, index)
+        self.with_fallback(
+            self.language,
+            |language| self.skill.get_item_entry(language, index),
+            |entry| entry.name.is_empty(),
+        )
     }
 
     pub fn get_skill_target_filter(&self, skill_target_filter: SkillTargetFilter) -> &str {
-        let index = if let Some(index) = (self.encode_skill_target_filter)(skill_target_filter) {
-            index as u16
-        } else {
-            return "";
-        };
-        let mut key = ArrayString::<16>::new();
-        write!(&mut key, "{}", index).ok();
-        self.skill_target
-            .get_text_string(self.language, &key)
-            .unwrap_or("")
+        self.get_text_by_index(
+            &self.skill_target,
+            (self.encode_skill_target_filter)(skill_target_filter),
+        )
     }
 
     pub fn get_skill_type(&self, skill_type: SkillType) -> &str {
-        let index = if let Some(index) = (self.encode_skill_type)(skill_type) {
-            index as u16
-        } else {
-            return "";
-        };
-        let mut key = ArrayString::<16>::new();
-        write!(&mut key, "{}", index).ok();
-        self.skill_type
-            .get_text_string(self.language, &key)
-            .unwrap_or("")
+        self.get_text_by_index(&self.skill_type, (self.encode_skill_type)(skill_type))
     }
 
     pub fn get_status_effect(&self, key: &str) -> Option<StlQuestEntry> {
         let index = self.status_effect.lookup_key(key)?;
-        self.status_effect.get_quest_entry(self.language, index)
+        self.with_fallback(
+            self.language,
+            |language| self.status_effect.get_quest_entry(language, index),
+            |entry| entry.name.is_empty(),
+        )
     }
 
     pub fn get_zone(&self, key: &str) -> Option<StlItemEntry> {
         let index = self.zone.lookup_key(key)?;
-        self.zone.get_item_entry(self.language, index)
+        self.with_fallback(
+            self.language,
+            |language| self.zone.get_item_entry(language, index),
+            |entry| entry.name.is_empty(),
+        )
     }
 }
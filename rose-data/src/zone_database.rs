@@ -5,13 +5,30 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, num::NonZeroU16, str::FromStr, sync::Arc};
 
-use crate::{NpcConversationId, NpcId, SkyboxId, StringDatabase};
+use crate::{NpcConversationId, NpcId, SkyboxId, WarpGateId};
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Reflect)]
 pub struct ZoneId(pub NonZeroU16);
 
 id_wrapper_impl!(ZoneId, NonZeroU16, u16);
 
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub enum ZoneTimeOfDay {
+    Morning,
+    Day,
+    Evening,
+    Night,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
+pub enum ZoneWeather {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+    Fog,
+}
+
 pub struct ZoneMonsterSpawnPoint {
     pub position: Vec3,
     pub basic_spawns: Vec<(NpcId, usize)>,
@@ -36,16 +53,22 @@ pub struct ZoneEventObject {
     pub position: Vec3,
 }
 
+pub struct ZoneWarpGate {
+    pub warp_gate_id: WarpGateId,
+    pub position: Vec3,
+}
+
 pub struct ZoneData {
     pub id: ZoneId,
-    pub name: &'static str,
-    pub description: &'static str,
+    pub name: Arc<str>,
+    pub description: Arc<str>,
     pub sector_size: u32,
     pub grid_per_patch: f32,
     pub grid_size: f32,
     pub event_objects: Vec<ZoneEventObject>,
     pub monster_spawns: Vec<ZoneMonsterSpawnPoint>,
     pub npcs: Vec<ZoneNpcSpawn>,
+    pub warp_gates: Vec<ZoneWarpGate>,
     pub sectors_base_position: Vec2,
     pub num_sectors_x: u32,
     pub num_sectors_y: u32,
@@ -61,6 +84,22 @@ pub struct ZoneData {
 }
 
 impl ZoneData {
+    pub fn get_time_of_day(&self, world_time: u32) -> ZoneTimeOfDay {
+        let zone_time = world_time % self.day_cycle.max(1);
+
+        if zone_time >= self.night_time {
+            ZoneTimeOfDay::Night
+        } else if zone_time >= self.evening_time {
+            ZoneTimeOfDay::Evening
+        } else if zone_time >= self.day_time {
+            ZoneTimeOfDay::Day
+        } else if zone_time >= self.morning_time {
+            ZoneTimeOfDay::Morning
+        } else {
+            ZoneTimeOfDay::Night
+        }
+    }
+
     pub fn get_closest_revive_position(&self, origin: Vec3) -> Option<Vec3> {
         let mut closest = None;
 
@@ -77,16 +116,12 @@ impl ZoneData {
 }
 
 pub struct ZoneDatabase {
-    _string_database: Arc<StringDatabase>,
     zones: Vec<Option<ZoneData>>,
 }
 
 impl ZoneDatabase {
-    pub fn new(string_database: Arc<StringDatabase>, zones: Vec<Option<ZoneData>>) -> Self {
-        Self {
-            _string_database: string_database,
-            zones,
-        }
+    pub fn new(zones: Vec<Option<ZoneData>>) -> Self {
+        Self { zones }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &ZoneData> {
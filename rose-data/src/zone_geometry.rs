@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec2, Vec2, Vec3, Vec3Swizzles};
+use serde::{Deserialize, Serialize};
+
+use crate::ZoneId;
+
+// Obstacles are indexed by the cells of this size which they overlap, so a
+// raycast only has to test the obstacles which are near to it
+const OBSTACLE_GRID_CELL_SIZE: f32 = 1000.0;
+
+/// An axis aligned box around a zone object which blocks line of sight.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct ZoneObstacle {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl ZoneObstacle {
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
+    /// Returns the fraction along the segment from start to end where it first
+    /// intersects this obstacle.
+    pub fn intersect_segment(&self, start: Vec3, end: Vec3) -> Option<f32> {
+        let direction = end - start;
+        let mut t_min = 0.0f32;
+        let mut t_max = 1.0f32;
+
+        for axis in 0..3 {
+            if direction[axis].abs() < f32::EPSILON {
+                if start[axis] < self.min[axis] || start[axis] > self.max[axis] {
+                    return None;
+                }
+            } else {
+                let t0 = (self.min[axis] - start[axis]) / direction[axis];
+                let t1 = (self.max[axis] - start[axis]) / direction[axis];
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+
+        Some(t_min)
+    }
+}
+
+/// The terrain heights and obstacles of a zone, heights are stored for each
+/// vertex of the terrain grid with y increasing in the same direction as world y.
+#[derive(Serialize, Deserialize)]
+pub struct ZoneGeometryData {
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub width: usize,
+    pub height: usize,
+    pub heights: Vec<Option<f32>>,
+    pub obstacles: Vec<ZoneObstacle>,
+    obstacle_grid: HashMap<IVec2, Vec<u32>>,
+}
+
+fn get_obstacle_grid_cell(position: Vec2) -> IVec2 {
+    (position / OBSTACLE_GRID_CELL_SIZE).floor().as_ivec2()
+}
+
+impl ZoneGeometryData {
+    pub fn new(
+        origin: Vec2,
+        cell_size: f32,
+        width: usize,
+        height: usize,
+        heights: Vec<Option<f32>>,
+        obstacles: Vec<ZoneObstacle>,
+    ) -> Self {
+        let mut obstacle_grid: HashMap<IVec2, Vec<u32>> = HashMap::new();
+        for (index, obstacle) in obstacles.iter().enumerate() {
+            let min = get_obstacle_grid_cell(obstacle.min.xy());
+            let max = get_obstacle_grid_cell(obstacle.max.xy());
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    obstacle_grid
+                        .entry(IVec2::new(x, y))
+                        .or_default()
+                        .push(index as u32);
+                }
+            }
+        }
+
+        Self {
+            origin,
+            cell_size,
+            width,
+            height,
+            heights,
+            obstacles,
+            obstacle_grid,
+        }
+    }
+
+    /// Returns the obstacles in the grid cells overlapped by the bounds of the
+    /// segment from start to end.
+    fn get_nearby_obstacles(&self, start: Vec2, end: Vec2) -> impl Iterator<Item = &ZoneObstacle> {
+        let min = get_obstacle_grid_cell(start.min(end));
+        let max = get_obstacle_grid_cell(start.max(end));
+        let mut indices: Vec<u32> = (min.y..=max.y)
+            .flat_map(|y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.obstacle_grid.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+            .into_iter()
+            .map(|index| &self.obstacles[index as usize])
+    }
+
+    fn get_vertex_height(&self, x: i32, y: i32) -> Option<f32> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            None
+        } else {
+            self.heights[y as usize * self.width + x as usize]
+        }
+    }
+
+    /// Returns the terrain height at position, or None if there is no terrain there.
+    pub fn get_terrain_height(&self, position: Vec2) -> Option<f32> {
+        let grid_position = (position - self.origin) / self.cell_size;
+        let x = grid_position.x.floor() as i32;
+        let y = grid_position.y.floor() as i32;
+        let fraction_x = grid_position.x - x as f32;
+        let fraction_y = grid_position.y - y as f32;
+
+        let height00 = self.get_vertex_height(x, y)?;
+        let height10 = self.get_vertex_height(x + 1, y)?;
+        let height01 = self.get_vertex_height(x, y + 1)?;
+        let height11 = self.get_vertex_height(x + 1, y + 1)?;
+
+        let height0 = height00 + (height10 - height00) * fraction_x;
+        let height1 = height01 + (height11 - height01) * fraction_x;
+        Some(height0 + (height1 - height0) * fraction_y)
+    }
+
+    /// Returns the first position along the segment from start to end which
+    /// hits the terrain or an obstacle. Obstacles which contain either end of
+    /// the segment are ignored, so entities standing inside an obstacle's
+    /// bounds are not blocked by it.
+    pub fn raycast(&self, start: Vec3, end: Vec3) -> Option<Vec3> {
+        let mut hit_fraction = self
+            .get_nearby_obstacles(start.xy(), end.xy())
+            .filter(|obstacle| !obstacle.contains(start) && !obstacle.contains(end))
+            .filter_map(|obstacle| obstacle.intersect_segment(start, end))
+            .fold(None, |closest: Option<f32>, fraction| {
+                Some(closest.map_or(fraction, |closest| closest.min(fraction)))
+            });
+
+        let step_size = self.cell_size / 2.0;
+        let num_steps = (start.xy().distance(end.xy()) / step_size).ceil() as usize;
+        for step in 1..num_steps {
+            let fraction = step as f32 / num_steps as f32;
+            if hit_fraction.map_or(false, |hit_fraction| hit_fraction <= fraction) {
+                break;
+            }
+
+            let position = start.lerp(end, fraction);
+            if self
+                .get_terrain_height(position.xy())
+                .map_or(false, |height| height > position.z)
+            {
+                hit_fraction = Some(fraction);
+                break;
+            }
+        }
+
+        hit_fraction.map(|fraction| start.lerp(end, fraction))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZoneGeometryDatabase {
+    zones: Vec<Option<ZoneGeometryData>>,
+}
+
+impl ZoneGeometryDatabase {
+    pub fn new(zones: Vec<Option<ZoneGeometryData>>) -> Self {
+        Self { zones }
+    }
+
+    pub fn get_zone_geometry(&self, id: ZoneId) -> Option<&ZoneGeometryData> {
+        match self.zones.get(id.get() as usize) {
+            Some(inner) => inner.as_ref(),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use bevy::math::{IVec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::ZoneId;
+
+const PATH_COST_STRAIGHT: u32 = 10;
+const PATH_COST_DIAGONAL: u32 = 14;
+const PATH_MAX_SEARCH_NODES: usize = 10000;
+
+/// A grid of movable cells covering a zone, cells are indexed with y
+/// increasing in the same direction as world y.
+#[derive(Serialize, Deserialize)]
+pub struct ZoneNavGrid {
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub width: usize,
+    pub height: usize,
+    pub walkable: Vec<bool>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct PathNode {
+    cost: u32,
+    index: usize,
+}
+
+impl Ord for PathNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap acts as a min-heap on cost
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for PathNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ZoneNavGrid {
+    fn get_cell(&self, position: Vec2) -> IVec2 {
+        ((position - self.origin) / self.cell_size)
+            .floor()
+            .as_ivec2()
+    }
+
+    fn get_cell_center(&self, cell: IVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + Vec2::splat(0.5)) * self.cell_size
+    }
+
+    fn get_cell_index(&self, cell: IVec2) -> Option<usize> {
+        if cell.x < 0 || cell.y < 0 || cell.x >= self.width as i32 || cell.y >= self.height as i32 {
+            None
+        } else {
+            Some(cell.y as usize * self.width + cell.x as usize)
+        }
+    }
+
+    fn is_cell_walkable(&self, cell: IVec2) -> bool {
+        self.get_cell_index(cell)
+            .map_or(false, |index| self.walkable[index])
+    }
+
+    /// Returns true if position is within the area covered by the grid
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.get_cell_index(self.get_cell(position)).is_some()
+    }
+
+    pub fn is_walkable(&self, position: Vec2) -> bool {
+        self.is_cell_walkable(self.get_cell(position))
+    }
+
+    pub fn has_line_of_sight(&self, start: Vec2, end: Vec2) -> bool {
+        let step_size = self.cell_size / 2.0;
+        let num_steps = (start.distance(end) / step_size).ceil() as usize;
+
+        (0..=num_steps).all(|step| {
+            let t = if num_steps == 0 {
+                1.0
+            } else {
+                step as f32 / num_steps as f32
+            };
+            self.is_walkable(start.lerp(end, t))
+        })
+    }
+
+    /// Returns the furthest position along the line from start to end which can
+    /// be reached, if start itself is not walkable then end is returned unchanged.
+    pub fn clamp_destination(&self, start: Vec2, end: Vec2) -> Vec2 {
+        if !self.is_walkable(start) || self.is_walkable(end) {
+            return end;
+        }
+
+        let step_size = self.cell_size / 2.0;
+        let num_steps = (start.distance(end) / step_size).ceil() as usize;
+        let mut clamped = start;
+        for step in 1..=num_steps {
+            let position = start.lerp(end, step as f32 / num_steps as f32);
+            if !self.is_walkable(position) {
+                break;
+            }
+            clamped = position;
+        }
+        clamped
+    }
+
+    /// Finds a path from start to end, returning the waypoints to move through
+    /// with the last waypoint being end. Returns None if either end is not
+    /// walkable or no path could be found within the search limit.
+    pub fn find_path(&self, start: Vec2, end: Vec2) -> Option<Vec<Vec2>> {
+        let start_cell = self.get_cell(start);
+        let end_cell = self.get_cell(end);
+        let start_index = self.get_cell_index(start_cell)?;
+        let end_index = self.get_cell_index(end_cell)?;
+        if !self.walkable[start_index] || !self.walkable[end_index] {
+            return None;
+        }
+
+        if self.has_line_of_sight(start, end) {
+            return Some(vec![end]);
+        }
+
+        let heuristic = |cell: IVec2| {
+            let delta = (end_cell - cell).abs();
+            let diagonal = delta.x.min(delta.y) as u32;
+            let straight = delta.x.max(delta.y) as u32 - diagonal;
+            diagonal * PATH_COST_DIAGONAL + straight * PATH_COST_STRAIGHT
+        };
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut path_cost: HashMap<usize, u32> = HashMap::new();
+        path_cost.insert(start_index, 0);
+        open.push(PathNode {
+            cost: heuristic(start_cell),
+            index: start_index,
+        });
+
+        let mut num_searched = 0;
+        let mut found = false;
+        while let Some(PathNode { index, .. }) = open.pop() {
+            if index == end_index {
+                found = true;
+                break;
+            }
+
+            num_searched += 1;
+            if num_searched > PATH_MAX_SEARCH_NODES {
+                break;
+            }
+
+            let cell = IVec2::new((index % self.width) as i32, (index / self.width) as i32);
+            let cell_cost = path_cost[&index];
+
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+
+                    let neighbour = cell + IVec2::new(dx, dy);
+                    let Some(neighbour_index) = self.get_cell_index(neighbour) else {
+                        continue;
+                    };
+                    if !self.walkable[neighbour_index] {
+                        continue;
+                    }
+
+                    let is_diagonal = dx != 0 && dy != 0;
+                    if is_diagonal
+                        && (!self.is_cell_walkable(cell + IVec2::new(dx, 0))
+                            || !self.is_cell_walkable(cell + IVec2::new(0, dy)))
+                    {
+                        // Do not allow cutting corners
+                        continue;
+                    }
+
+                    let neighbour_cost = cell_cost
+                        + if is_diagonal {
+                            PATH_COST_DIAGONAL
+                        } else {
+                            PATH_COST_STRAIGHT
+                        };
+                    if path_cost
+                        .get(&neighbour_index)
+                        .map_or(true, |&cost| neighbour_cost < cost)
+                    {
+                        path_cost.insert(neighbour_index, neighbour_cost);
+                        came_from.insert(neighbour_index, index);
+                        open.push(PathNode {
+                            cost: neighbour_cost + heuristic(neighbour),
+                            index: neighbour_index,
+                        });
+                    }
+                }
+            }
+        }
+
+        if !found {
+            return None;
+        }
+
+        let mut cells = vec![end_index];
+        let mut current = end_index;
+        while let Some(&previous) = came_from.get(&current) {
+            cells.push(previous);
+            current = previous;
+        }
+        cells.reverse();
+
+        let mut points: Vec<Vec2> = cells
+            .iter()
+            .map(|&index| {
+                self.get_cell_center(IVec2::new(
+                    (index % self.width) as i32,
+                    (index / self.width) as i32,
+                ))
+            })
+            .collect();
+        points[0] = start;
+        *points.last_mut().unwrap() = end;
+
+        // Remove any waypoints which can be skipped by moving in a straight line
+        let mut waypoints = Vec::new();
+        let mut current = 0;
+        while current < points.len() - 1 {
+            let mut next = points.len() - 1;
+            while next > current + 1 && !self.has_line_of_sight(points[current], points[next]) {
+                next -= 1;
+            }
+            waypoints.push(points[next]);
+            current = next;
+        }
+
+        Some(waypoints)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZoneNavGridDatabase {
+    nav_grids: Vec<Option<ZoneNavGrid>>,
+}
+
+impl ZoneNavGridDatabase {
+    pub fn new(nav_grids: Vec<Option<ZoneNavGrid>>) -> Self {
+        Self { nav_grids }
+    }
+
+    pub fn get_zone_nav_grid(&self, id: ZoneId) -> Option<&ZoneNavGrid> {
+        match self.nav_grids.get(id.get() as usize) {
+            Some(inner) => inner.as_ref(),
+            None => None,
+        }
+    }
+}
@@ -8,6 +8,13 @@ pub struct HimFile {
 }
 
 impl HimFile {
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.heights.get((y * self.width + x) as usize).copied()
+    }
+
     pub fn get_clamped(&self, x: i32, y: i32) -> f32 {
         let x = i32::clamp(x, 0, self.width as i32 - 1) as usize;
         let y = i32::clamp(y, 0, self.height as i32 - 1) as usize;
@@ -35,6 +35,7 @@ mod ifo;
 mod irosephvfs;
 mod lit;
 mod ltb;
+mod luac;
 mod ptl;
 mod qsd;
 mod stl;
@@ -65,6 +66,7 @@ pub use ifo::{
 pub use irosephvfs::IrosePhVfsIndex;
 pub use lit::{LitFile, LitObject, LitObjectPart};
 pub use ltb::LtbFile;
+pub use luac::{LuaConstant, LuaFunction};
 pub use ptl::{PtlFile, PtlKeyframe, PtlKeyframeData, PtlSequence, PtlUpdateCoords};
 pub use qsd::*;
 pub use stb::{StbFile, StbReadOptions};
@@ -0,0 +1,316 @@
+use anyhow::{anyhow, bail};
+
+use crate::reader::RoseFileReader;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LuaVersion {
+    Lua50,
+    Lua51,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LuaConstant {
+    Nil,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+/// A function from a compiled Lua 5.0 or 5.1 chunk, only the parts which are
+/// required to find the arguments of calls are kept.
+#[derive(Clone, Debug)]
+pub struct LuaFunction {
+    pub constants: Vec<LuaConstant>,
+    pub functions: Vec<LuaFunction>,
+    pub code: Vec<u32>,
+    version: LuaVersion,
+}
+
+struct LuaHeader {
+    version: LuaVersion,
+    size_int: u8,
+    size_size_t: u8,
+    size_number: u8,
+}
+
+impl LuaHeader {
+    fn read(reader: &mut RoseFileReader) -> Result<Self, anyhow::Error> {
+        if reader.read_fixed_length_bytes(4)? != b"\x1bLua" {
+            bail!("Invalid lua chunk signature");
+        }
+
+        let version = match reader.read_u8()? {
+            0x50 => LuaVersion::Lua50,
+            0x51 => LuaVersion::Lua51,
+            version => bail!("Unsupported lua version {:02X}", version),
+        };
+
+        if version == LuaVersion::Lua51 && reader.read_u8()? != 0 {
+            bail!("Unsupported lua chunk format");
+        }
+
+        if reader.read_u8()? != 1 {
+            bail!("Unsupported big endian lua chunk");
+        }
+
+        let size_int = reader.read_u8()?;
+        let size_size_t = reader.read_u8()?;
+        let size_instruction = reader.read_u8()?;
+        if version == LuaVersion::Lua50 {
+            // The instruction layout is fixed for 5.1, and must match the 5.0 default
+            let field_sizes = reader.read_fixed_length_bytes(4)?;
+            if field_sizes != [6, 8, 9, 9] {
+                bail!("Unsupported lua instruction layout {:?}", field_sizes);
+            }
+        }
+        let size_number = reader.read_u8()?;
+
+        match version {
+            LuaVersion::Lua50 => {
+                // Test number 3.14159265358979323846E7
+                reader.skip(size_number as u64);
+            }
+            LuaVersion::Lua51 => {
+                // Integral number flag
+                reader.skip(1);
+            }
+        }
+
+        if !matches!(size_int, 4 | 8)
+            || !matches!(size_size_t, 4 | 8)
+            || size_instruction != 4
+            || !matches!(size_number, 4 | 8)
+        {
+            bail!("Unsupported lua type sizes");
+        }
+
+        Ok(Self {
+            version,
+            size_int,
+            size_size_t,
+            size_number,
+        })
+    }
+
+    fn read_int(&self, reader: &mut RoseFileReader) -> Result<usize, anyhow::Error> {
+        Ok(match self.size_int {
+            8 => reader.read_u64()? as usize,
+            _ => reader.read_u32()? as usize,
+        })
+    }
+
+    fn read_count(&self, reader: &mut RoseFileReader) -> Result<usize, anyhow::Error> {
+        let count = self.read_int(reader)?;
+
+        // Every element takes at least one byte
+        if count > reader.remaining() {
+            bail!("Invalid lua chunk count {}", count);
+        }
+        Ok(count)
+    }
+
+    fn read_string(&self, reader: &mut RoseFileReader) -> Result<Option<String>, anyhow::Error> {
+        let size = match self.size_size_t {
+            8 => reader.read_u64()? as usize,
+            _ => reader.read_u32()? as usize,
+        };
+        if size == 0 {
+            return Ok(None);
+        }
+
+        // The size includes the trailing 0
+        let bytes = reader.read_fixed_length_bytes(size)?;
+        Ok(Some(
+            String::from_utf8_lossy(&bytes[..size - 1]).into_owned(),
+        ))
+    }
+
+    fn read_number(&self, reader: &mut RoseFileReader) -> Result<f64, anyhow::Error> {
+        Ok(match self.size_number {
+            4 => reader.read_f32()? as f64,
+            _ => reader.read_f64()?,
+        })
+    }
+
+    fn read_code(&self, reader: &mut RoseFileReader) -> Result<Vec<u32>, anyhow::Error> {
+        let count = self.read_count(reader)?;
+        let mut code = Vec::with_capacity(count);
+        for _ in 0..count {
+            code.push(reader.read_u32()?);
+        }
+        Ok(code)
+    }
+
+    fn read_constants(
+        &self,
+        reader: &mut RoseFileReader,
+    ) -> Result<Vec<LuaConstant>, anyhow::Error> {
+        let count = self.read_count(reader)?;
+        let mut constants = Vec::with_capacity(count);
+        for _ in 0..count {
+            constants.push(match reader.read_u8()? {
+                0 => LuaConstant::Nil,
+                1 => LuaConstant::Boolean(reader.read_u8()? != 0),
+                3 => LuaConstant::Number(self.read_number(reader)?),
+                4 => LuaConstant::String(self.read_string(reader)?.unwrap_or_default()),
+                constant_type => bail!("Invalid lua constant type {}", constant_type),
+            });
+        }
+        Ok(constants)
+    }
+
+    fn read_functions(
+        &self,
+        reader: &mut RoseFileReader,
+        depth: usize,
+    ) -> Result<Vec<LuaFunction>, anyhow::Error> {
+        let count = self.read_count(reader)?;
+        let mut functions = Vec::with_capacity(count);
+        for _ in 0..count {
+            functions.push(self.read_function(reader, depth + 1)?);
+        }
+        Ok(functions)
+    }
+
+    fn skip_debug_info(&self, reader: &mut RoseFileReader) -> Result<(), anyhow::Error> {
+        // Line info
+        let num_lines = self.read_count(reader)?;
+        reader.skip((num_lines * self.size_int as usize) as u64);
+
+        // Local variables
+        for _ in 0..self.read_count(reader)? {
+            self.read_string(reader)?;
+            self.read_int(reader)?;
+            self.read_int(reader)?;
+        }
+
+        // Upvalue names
+        for _ in 0..self.read_count(reader)? {
+            self.read_string(reader)?;
+        }
+        Ok(())
+    }
+
+    fn read_function(
+        &self,
+        reader: &mut RoseFileReader,
+        depth: usize,
+    ) -> Result<LuaFunction, anyhow::Error> {
+        if depth > 200 {
+            bail!("Lua functions are nested too deeply");
+        }
+
+        self.read_string(reader)?; // Source name
+        self.read_int(reader)?; // Line defined
+        if self.version == LuaVersion::Lua51 {
+            self.read_int(reader)?; // Last line defined
+        }
+        reader.skip(4); // Upvalues, parameters, is vararg, max stack size
+
+        match self.version {
+            LuaVersion::Lua50 => {
+                self.skip_debug_info(reader)?;
+                let constants = self.read_constants(reader)?;
+                let functions = self.read_functions(reader, depth)?;
+                let code = self.read_code(reader)?;
+                Ok(LuaFunction {
+                    constants,
+                    functions,
+                    code,
+                    version: self.version,
+                })
+            }
+            LuaVersion::Lua51 => {
+                let code = self.read_code(reader)?;
+                let constants = self.read_constants(reader)?;
+                let functions = self.read_functions(reader, depth)?;
+                self.skip_debug_info(reader)?;
+                Ok(LuaFunction {
+                    constants,
+                    functions,
+                    code,
+                    version: self.version,
+                })
+            }
+        }
+    }
+}
+
+struct LuaInstruction {
+    opcode: u32,
+    a: u32,
+    bx: u32,
+}
+
+impl LuaFunction {
+    /// Reads the main function of a compiled Lua 5.0 or 5.1 chunk
+    pub fn read(data: &[u8]) -> Result<Self, anyhow::Error> {
+        let mut reader = RoseFileReader::from(data);
+        let header = LuaHeader::read(&mut reader)?;
+        header
+            .read_function(&mut reader, 0)
+            .map_err(|error| anyhow!("Failed to read lua function: {}", error))
+    }
+
+    fn decode(&self, instruction: u32) -> LuaInstruction {
+        match self.version {
+            LuaVersion::Lua50 => LuaInstruction {
+                opcode: instruction & 0x3f,
+                a: instruction >> 24,
+                bx: (instruction >> 6) & 0x3ffff,
+            },
+            LuaVersion::Lua51 => LuaInstruction {
+                opcode: instruction & 0x3f,
+                a: (instruction >> 6) & 0xff,
+                bx: instruction >> 14,
+            },
+        }
+    }
+
+    fn get_string_constant(&self, index: u32) -> Option<&str> {
+        match self.constants.get(index as usize) {
+            Some(LuaConstant::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the constant string first argument of every call to the global
+    /// function `name` in this function and the functions nested in it.
+    pub fn find_global_call_string_arguments(&self, name: &str) -> Vec<String> {
+        let (op_loadk, op_getglobal, op_call, op_tailcall) = match self.version {
+            LuaVersion::Lua50 => (1, 5, 24, 25),
+            LuaVersion::Lua51 => (1, 5, 28, 29),
+        };
+
+        let mut arguments = Vec::new();
+        let mut pending_call: Option<(u32, Option<&str>)> = None;
+        for instruction in self.code.iter().map(|&code| self.decode(code)) {
+            if instruction.opcode == op_getglobal
+                && self.get_string_constant(instruction.bx) == Some(name)
+            {
+                pending_call = Some((instruction.a, None));
+                continue;
+            }
+
+            let Some((function_register, argument)) = pending_call.as_mut() else {
+                continue;
+            };
+
+            if instruction.opcode == op_loadk && instruction.a == *function_register + 1 {
+                *argument = self.get_string_constant(instruction.bx);
+            } else if (instruction.opcode == op_call || instruction.opcode == op_tailcall)
+                && instruction.a == *function_register
+            {
+                if let Some(argument) = argument {
+                    arguments.push(argument.to_string());
+                }
+                pending_call = None;
+            }
+        }
+
+        for function in self.functions.iter() {
+            arguments.extend(function.find_global_call_string_arguments(name));
+        }
+        arguments
+    }
+}
@@ -9,6 +9,8 @@ use memmap::{Mmap, MmapOptions};
 use crate::{reader::RoseFileReader, VfsError, VfsFile, VfsPath, VirtualFilesystemDevice};
 
 /// VFS format used by TitanROSE.
+///
+/// The index only stores a hash of each path, so its files cannot be listed.
 #[derive(Debug)]
 pub struct TitanVfsIndex {
     pub version: u32,
@@ -6,7 +6,9 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{reader::RoseFileReader, VfsError, VfsFile, VfsPath, VirtualFilesystemDevice};
+use crate::{
+    reader::RoseFileReader, VfsError, VfsFile, VfsPath, VfsPathBuf, VirtualFilesystemDevice,
+};
 
 struct FileEntry {
     offset: usize,
@@ -118,4 +120,18 @@ impl VirtualFilesystemDevice for VfsIndex {
 
         false
     }
+
+    fn list_files(&self, prefix: &VfsPath) -> Option<Vec<VfsPathBuf>> {
+        let prefix = prefix.path().to_string_lossy();
+
+        Some(
+            self.storages
+                .iter()
+                .flat_map(|storage| storage.files.keys())
+                .map(|path| path.to_string_lossy())
+                .filter(|path| path.starts_with(&*prefix))
+                .map(|path| VfsPathBuf::new(&path))
+                .collect(),
+        )
+    }
 }
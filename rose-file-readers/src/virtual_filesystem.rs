@@ -1,7 +1,9 @@
 use anyhow::Context;
 use std::{
     borrow::Cow,
+    collections::BTreeSet,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 use thiserror::Error;
 
@@ -16,6 +18,8 @@ pub enum VfsFile<'a> {
 pub enum VfsError {
     #[error("File {0} not found")]
     FileNotFound(PathBuf),
+    #[error("A device is already mounted as {0}")]
+    MountExists(String),
 }
 
 impl<'a> From<&'a VfsFile<'a>> for RoseFileReader<'a> {
@@ -125,6 +129,40 @@ impl<'a> From<&'a VfsPathBuf> for VfsPath<'a> {
 pub trait VirtualFilesystemDevice {
     fn open_file(&self, path: &VfsPath) -> Result<VfsFile, anyhow::Error>;
     fn exists(&self, path: &VfsPath) -> bool;
+
+    /// Returns the paths of the files which start with `prefix`, or None if
+    /// the device cannot list its files, such as an index which only stores
+    /// a hash of each path.
+    fn list_files(&self, _prefix: &VfsPath) -> Option<Vec<VfsPathBuf>> {
+        None
+    }
+}
+
+/// Matches a normalised path against a pattern where `*` matches any
+/// characters within a directory and `?` matches a single character.
+fn glob_matches(pattern: &[char], path: &[char]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some(('*', pattern)) => {
+            for index in 0..=path.len() {
+                if glob_matches(pattern, &path[index..]) {
+                    return true;
+                }
+                if path.get(index) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some(('?', pattern)) => matches!(
+            path.split_first(),
+            Some((c, path)) if *c != '/' && glob_matches(pattern, path)
+        ),
+        Some((pattern_c, pattern)) => matches!(
+            path.split_first(),
+            Some((c, path)) if c == pattern_c && glob_matches(pattern, path)
+        ),
+    }
 }
 
 pub struct HostFilesystemDevice {
@@ -147,39 +185,217 @@ impl VirtualFilesystemDevice for HostFilesystemDevice {
     fn exists(&self, vfs_path: &VfsPath) -> bool {
         self.root_path.join(vfs_path.path()).exists()
     }
+
+    fn list_files(&self, prefix: &VfsPath) -> Option<Vec<VfsPathBuf>> {
+        let prefix = prefix.path().to_string_lossy();
+        let mut files = Vec::new();
+        let mut directories = vec![(self.root_path.clone(), String::new())];
+
+        while let Some((directory, vfs_directory)) = directories.pop() {
+            let Ok(entries) = std::fs::read_dir(&directory) else {
+                continue;
+            };
+
+            for entry in entries.flatten() {
+                let vfs_path = VfsPath::normalise_path(&format!(
+                    "{}{}",
+                    vfs_directory,
+                    entry.file_name().to_string_lossy()
+                ))
+                .to_string_lossy()
+                .into_owned();
+
+                if entry.path().is_dir() {
+                    // Host paths may not match the case of the normalised
+                    // prefix, so only descend into directories which could
+                    // contain it
+                    let vfs_path = vfs_path + "/";
+                    if vfs_path.starts_with(&*prefix) || prefix.starts_with(&vfs_path) {
+                        directories.push((entry.path(), vfs_path));
+                    }
+                } else if vfs_path.starts_with(&*prefix) {
+                    files.push(VfsPathBuf::new(&vfs_path));
+                }
+            }
+        }
+
+        Some(files)
+    }
+}
+
+struct VfsMount {
+    name: String,
+    priority: i32,
+    device: Box<dyn VirtualFilesystemDevice + Send + Sync>,
 }
 
 pub struct VirtualFilesystem {
-    pub devices: Vec<Box<dyn VirtualFilesystemDevice + Send + Sync>>,
+    /// Sorted by descending priority, mounts with the same priority are kept
+    /// in the order they were mounted
+    mounts: Vec<VfsMount>,
+
+    /// The paths of the files opened since `start_recording`
+    recorded_paths: Mutex<Option<BTreeSet<PathBuf>>>,
 }
 
 impl VirtualFilesystem {
+    /// Creates a filesystem with the devices mounted at the same priority,
+    /// so they are searched in order. The devices are named by their index.
     pub fn new(devices: Vec<Box<dyn VirtualFilesystemDevice + Send + Sync>>) -> Self {
-        Self { devices }
+        Self {
+            mounts: devices
+                .into_iter()
+                .enumerate()
+                .map(|(index, device)| VfsMount {
+                    name: index.to_string(),
+                    priority: 0,
+                    device,
+                })
+                .collect(),
+            recorded_paths: Mutex::new(None),
+        }
     }
 
-    pub fn exists<'a, P: Into<VfsPath<'a>>>(&self, path: P) -> bool {
+    /// Mounts a device, files are read from the device with the highest
+    /// priority which contains them.
+    pub fn mount(
+        &mut self,
+        name: &str,
+        priority: i32,
+        device: Box<dyn VirtualFilesystemDevice + Send + Sync>,
+    ) -> Result<(), VfsError> {
+        if self.mounts.iter().any(|mount| mount.name == name) {
+            return Err(VfsError::MountExists(name.to_string()));
+        }
+
+        let index = self
+            .mounts
+            .partition_point(|mount| mount.priority >= priority);
+        self.mounts.insert(
+            index,
+            VfsMount {
+                name: name.to_string(),
+                priority,
+                device,
+            },
+        );
+        Ok(())
+    }
+
+    /// Unmounts a device, returning it or None if there is no device mounted
+    /// with the name.
+    pub fn unmount(
+        &mut self,
+        name: &str,
+    ) -> Option<Box<dyn VirtualFilesystemDevice + Send + Sync>> {
+        let index = self.mounts.iter().position(|mount| mount.name == name)?;
+        Some(self.mounts.remove(index).device)
+    }
+
+    /// Returns the name and priority of each mounted device, in the order
+    /// they are searched.
+    pub fn mounts(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.mounts
+            .iter()
+            .map(|mount| (mount.name.as_str(), mount.priority))
+    }
+
+    /// Returns the names of the mounted devices which contain the path, in
+    /// the order they are searched. The file is read from the first device,
+    /// which is useful to find which device is overriding a file.
+    pub fn find_mounts<'a>(&self, path: impl Into<VfsPath<'a>>) -> Vec<&str> {
         let vfs_path: VfsPath = path.into();
+        self.mounts
+            .iter()
+            .filter(|mount| mount.device.exists(&vfs_path))
+            .map(|mount| mount.name.as_str())
+            .collect()
+    }
 
-        for device in &self.devices {
-            if device.exists(&vfs_path) {
-                return true;
-            }
+    /// Start recording the path of every file which is opened, used to find
+    /// which files some data was read from.
+    pub fn start_recording(&self) {
+        *self.recorded_paths.lock().unwrap() = Some(BTreeSet::new());
+    }
+
+    /// Stop recording and return the paths of the files which were opened
+    /// since `start_recording`, in sorted order.
+    pub fn finish_recording(&self) -> Vec<PathBuf> {
+        self.recorded_paths
+            .lock()
+            .unwrap()
+            .take()
+            .map(|paths| paths.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the paths of the files in all mounted devices which start with
+    /// `prefix`, in sorted order. Returns None if any device cannot list its
+    /// files, as the result would be incomplete.
+    pub fn list_files<'a>(&self, prefix: impl Into<VfsPath<'a>>) -> Option<Vec<VfsPathBuf>> {
+        let prefix: VfsPath = prefix.into();
+        let mut files = BTreeSet::new();
+
+        for mount in &self.mounts {
+            files.extend(
+                mount
+                    .device
+                    .list_files(&prefix)?
+                    .into_iter()
+                    .map(|path| path.path),
+            );
         }
 
-        false
+        Some(files.into_iter().map(|path| VfsPathBuf { path }).collect())
+    }
+
+    /// Returns the paths of the files in all mounted devices which match
+    /// `pattern`, such as `3DDATA/MAPS/JUNON/JDT01/*.IFO`. A `*` matches any
+    /// characters within a directory and `?` matches a single character.
+    pub fn glob(&self, pattern: &str) -> Option<Vec<VfsPathBuf>> {
+        let pattern: Vec<char> = VfsPath::normalise_path(pattern)
+            .to_string_lossy()
+            .chars()
+            .collect();
+        let prefix: String = pattern
+            .iter()
+            .take_while(|c| **c != '*' && **c != '?')
+            .collect();
+
+        Some(
+            self.list_files(prefix.as_str())?
+                .into_iter()
+                .filter(|path| {
+                    let path: Vec<char> = path.path().to_string_lossy().chars().collect();
+                    glob_matches(&pattern, &path)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn exists<'a, P: Into<VfsPath<'a>>>(&self, path: P) -> bool {
+        let vfs_path: VfsPath = path.into();
+
+        self.mounts
+            .iter()
+            .any(|mount| mount.device.exists(&vfs_path))
     }
 
     pub fn open_file<'a>(&self, path: impl Into<VfsPath<'a>>) -> Result<VfsFile, anyhow::Error> {
         let vfs_path: VfsPath = path.into();
 
-        for device in &self.devices {
-            match device.open_file(&vfs_path) {
-                Ok(file) => return Ok(file),
+        for mount in &self.mounts {
+            match mount.device.open_file(&vfs_path) {
+                Ok(file) => {
+                    if let Some(recorded_paths) = self.recorded_paths.lock().unwrap().as_mut() {
+                        recorded_paths.insert(vfs_path.path().to_path_buf());
+                    }
+                    return Ok(file);
+                }
                 Err(error) => {
                     match error.downcast_ref::<VfsError>() {
                         Some(VfsError::FileNotFound(_)) => continue,
-                        None => return Err(error),
+                        _ => return Err(error),
                     };
                 }
             }
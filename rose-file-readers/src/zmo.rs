@@ -22,9 +22,28 @@ pub struct ZmoReadOptions {
     pub skip_animation: bool,
 }
 
+fn is_attack_frame_event(frame_event: u16) -> bool {
+    matches!(frame_event, 10 | 20..=28 | 56..=57 | 66..=67)
+}
+
 impl ZmoFile {
     pub fn get_duration(&self) -> Duration {
-        Duration::from_nanos((self.num_frames as u64 * 1_000_000_000) / self.fps as u64)
+        self.get_frame_time(self.num_frames)
+    }
+
+    pub fn get_frame_time(&self, frame: usize) -> Duration {
+        Duration::from_nanos((frame as u64 * 1_000_000_000) / self.fps as u64)
+    }
+
+    /// Returns the time from the start of the motion of every frame with an
+    /// attack event, which is when the attack hits its target.
+    pub fn get_attack_frame_times(&self) -> Vec<Duration> {
+        self.frame_events
+            .iter()
+            .enumerate()
+            .filter(|(_, &frame_event)| is_attack_frame_event(frame_event))
+            .map(|(frame, _)| self.get_frame_time(frame))
+            .collect()
     }
 }
 
@@ -123,9 +142,8 @@ impl RoseFile for ZmoFile {
                     let frame_event = reader.read_u16()?;
                     frame_events.push(frame_event);
 
-                    match frame_event {
-                        10 | 20..=28 | 56..=57 | 66..=67 => total_attack_frames += 1,
-                        _ => {}
+                    if is_attack_frame_event(frame_event) {
+                        total_attack_frames += 1;
                     }
                 }
 
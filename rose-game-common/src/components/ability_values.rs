@@ -1,6 +1,7 @@
 use bevy::{ecs::prelude::Component, reflect::Reflect};
+use enum_map::EnumMap;
 
-use rose_data::StatusEffectType;
+use rose_data::{Element, StatusEffectType};
 
 use crate::components::{MoveMode, StatusEffects};
 
@@ -121,7 +122,10 @@ pub struct AbilityValues {
     pub attack_range: i32,
     pub hit: i32,
     pub defence: i32,
+    pub block_rate: i32,
     pub resistance: i32,
+    #[reflect(ignore)]
+    pub elemental_resistance: EnumMap<Element, i32>,
     pub critical: i32,
     pub avoid: i32,
     pub vehicle_attack_power: i32,
@@ -296,6 +300,20 @@ impl AbilityValues {
         self.vehicle_move_speed + self.adjust.run_speed
     }
 
+    /// Percentage of damage from the element which is resisted, negative for a weakness
+    pub fn get_elemental_resistance(&self, element: Element) -> i32 {
+        self.elemental_resistance[element]
+    }
+
+    /// Percentage chance to block an attack with a shield
+    pub fn get_block_rate(&self) -> i32 {
+        if self.is_driving {
+            0
+        } else {
+            self.block_rate
+        }
+    }
+
     pub fn get_save_mana(&self) -> i32 {
         self.save_mana
     }
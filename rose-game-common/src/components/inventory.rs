@@ -8,7 +8,7 @@ use std::{
 
 use rose_data::{
     AmmoIndex, EquipmentIndex, EquipmentItem, Item, ItemReference, ItemSlotBehaviour, ItemType,
-    StackableItem, VehiclePartIndex,
+    StackError, StackableItem, VehiclePartIndex,
 };
 
 pub const INVENTORY_PAGE_SIZE: usize = 5 * 6;
@@ -198,6 +198,62 @@ impl InventoryPage {
         None
     }
 
+    pub fn find_empty_slot(&self) -> Option<ItemSlot> {
+        self.slots
+            .iter()
+            .position(|slot| slot.is_none())
+            .map(|index| ItemSlot::Inventory(self.page_type, index))
+    }
+
+    /// Merges partial stacks of the same item together and then orders the
+    /// items by item type and number, leaving all empty slots at the end.
+    pub fn sort(&mut self) {
+        let mut items: Vec<Item> = Vec::with_capacity(self.slots.len());
+
+        for item in self.slots.iter_mut().filter_map(|slot| slot.take()) {
+            match item {
+                Item::Stackable(mut stackable) => {
+                    for sorted_item in items.iter_mut() {
+                        let merge_quantity = match sorted_item.can_stack_with(&stackable) {
+                            Ok(_) => stackable.quantity,
+                            Err(StackError::PartialStack(quantity)) => quantity,
+                            Err(_) => 0,
+                        };
+
+                        if merge_quantity > 0 {
+                            if let Some(merge_item) = stackable.try_take_subquantity(merge_quantity)
+                            {
+                                sorted_item.try_stack_with(merge_item).ok();
+                            }
+                        }
+
+                        if stackable.quantity == 0 {
+                            break;
+                        }
+                    }
+
+                    if stackable.quantity > 0 {
+                        items.push(Item::Stackable(stackable));
+                    }
+                }
+                item => items.push(item),
+            }
+        }
+
+        items.sort_by_key(|item| {
+            let item_reference = item.get_item_reference();
+            (
+                item_reference.item_type as usize,
+                item_reference.item_number,
+                std::cmp::Reverse(item.get_quantity()),
+            )
+        });
+
+        for (slot, item) in self.slots.iter_mut().zip(items.into_iter()) {
+            *slot = Some(item);
+        }
+    }
+
     pub fn find_item(&self, item_reference: ItemReference) -> Option<ItemSlot> {
         for i in 0..self.slots.len() {
             if let Some(slot_item) = &self.slots[i] {
@@ -243,6 +299,9 @@ impl Default for Inventory {
 #[derive(Debug)]
 pub enum InventoryError {
     NotEnoughMoney,
+    InvalidItemSlot,
+    InvalidQuantity,
+    NoEmptySlot,
 }
 
 #[allow(dead_code)]
@@ -387,6 +446,99 @@ impl Inventory {
             .find_item(item_reference)
     }
 
+    /// Splits `quantity` from the stack in `slot` into the first empty slot of the same page.
+    pub fn try_split_item(
+        &mut self,
+        slot: ItemSlot,
+        quantity: u32,
+    ) -> Result<ItemSlot, InventoryError> {
+        let ItemSlot::Inventory(page_type, _) = slot else {
+            return Err(InventoryError::InvalidItemSlot);
+        };
+
+        let item = self.get_item(slot).ok_or(InventoryError::InvalidItemSlot)?;
+        if quantity == 0 || quantity >= item.get_quantity() {
+            return Err(InventoryError::InvalidQuantity);
+        }
+
+        let empty_slot = self
+            .get_page(page_type)
+            .find_empty_slot()
+            .ok_or(InventoryError::NoEmptySlot)?;
+
+        let split_item = self
+            .get_item_mut(slot)
+            .and_then(|item| item.try_take_subquantity(quantity))
+            .ok_or(InventoryError::InvalidQuantity)?;
+        *self.get_item_slot_mut(empty_slot).unwrap() = Some(split_item);
+        Ok(empty_slot)
+    }
+
+    /// Moves the item in `from_slot` to `to_slot` within the same page. If both slots
+    /// contain the same stackable item then as much as possible is merged into
+    /// `to_slot`, otherwise the two slots are swapped.
+    pub fn try_move_item(
+        &mut self,
+        from_slot: ItemSlot,
+        to_slot: ItemSlot,
+    ) -> Result<(), InventoryError> {
+        let (
+            ItemSlot::Inventory(from_page_type, from_index),
+            ItemSlot::Inventory(to_page_type, to_index),
+        ) = (from_slot, to_slot)
+        else {
+            return Err(InventoryError::InvalidItemSlot);
+        };
+
+        if from_page_type != to_page_type
+            || from_index == to_index
+            || from_index >= INVENTORY_PAGE_SIZE
+            || to_index >= INVENTORY_PAGE_SIZE
+        {
+            return Err(InventoryError::InvalidItemSlot);
+        }
+
+        let page = self.get_page_mut(from_page_type);
+        let from_item = page.slots[from_index]
+            .as_ref()
+            .ok_or(InventoryError::InvalidItemSlot)?;
+
+        let merge_quantity = match (from_item, page.slots[to_index].as_ref()) {
+            (Item::Stackable(from_stackable), Some(to_item)) => {
+                match to_item.can_stack_with(from_stackable) {
+                    Ok(_) => Some(from_stackable.quantity),
+                    Err(StackError::PartialStack(quantity)) => Some(quantity),
+                    Err(_) => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(merge_quantity) = merge_quantity {
+            if merge_quantity == 0 {
+                return Err(InventoryError::InvalidQuantity);
+            }
+
+            let merge_item = page.slots[from_index]
+                .try_take_quantity(merge_quantity)
+                .ok_or(InventoryError::InvalidQuantity)?;
+            page.slots[to_index]
+                .try_stack_with_item(merge_item)
+                .map_err(|_| InventoryError::InvalidQuantity)?;
+            return Ok(());
+        }
+
+        page.slots.swap(from_index, to_index);
+        Ok(())
+    }
+
+    pub fn sort(&mut self) {
+        self.equipment.sort();
+        self.consumables.sort();
+        self.materials.sort();
+        self.vehicles.sort();
+    }
+
     pub fn has_empty_slot(&self, page_type: InventoryPageType) -> bool {
         self.get_page(page_type)
             .slots
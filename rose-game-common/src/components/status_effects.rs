@@ -1,4 +1,4 @@
-use bevy::ecs::prelude::Component;
+use bevy::ecs::prelude::{Component, Entity};
 use enum_map::EnumMap;
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
@@ -15,6 +15,9 @@ pub struct ActiveStatusEffect {
 pub struct StatusEffects {
     pub active: EnumMap<StatusEffectType, Option<ActiveStatusEffect>>,
     pub expire_times: EnumMap<StatusEffectType, Option<Instant>>,
+    // The entity which applied each status effect, so that damage over time can be
+    // attributed to them
+    pub casters: EnumMap<StatusEffectType, Option<Entity>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -78,6 +81,7 @@ impl StatusEffects {
                     value,
                 });
                 self.expire_times[status_effect_type] = Some(expire_time);
+                self.casters[status_effect_type] = None;
                 true
             }
         }
@@ -16,6 +16,7 @@ pub struct Damage {
     pub amount: u32,
     pub is_critical: bool,
     pub apply_hit_stun: bool,
+    pub is_blocked: bool,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -1,4 +1,4 @@
-use rose_data::{NpcId, ZoneId};
+use rose_data::{ItemReference, NpcId, ZoneId};
 
 use crate::components::DroppedItem;
 
@@ -14,4 +14,8 @@ pub trait DropTable {
         character_drop_rate: i32,
         character_charm: i32,
     ) -> Option<DroppedItem>;
+
+    /// Returns every item which can drop from the npc's own drop table, not
+    /// including the drop table of the zone it is in.
+    fn get_npc_drop_items(&self, npc_id: NpcId) -> Vec<ItemReference>;
 }
@@ -4,13 +4,14 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::{
         BasicStatType, CharacterGender, CharacterUniqueId, ClanMark, HotbarSlot, ItemSlot, Level,
-        SkillSlot,
+        Money, SkillSlot,
     },
     data::Password,
     messages::{ClientEntityId, PartyItemSharing, PartyRejectInviteReason, PartyXpSharing},
 };
 use rose_data::{
-    AmmoIndex, EquipmentIndex, Item, MotionId, QuestTriggerHash, VehiclePartIndex, WarpGateId,
+    AmmoIndex, EquipmentIndex, Item, ItemType, MotionId, QuestTriggerHash, VehiclePartIndex,
+    WarpGateId,
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -61,6 +62,9 @@ pub enum ClientMessage {
         slot: u8,
         name: String,
     },
+    SecondaryPin {
+        pin: String,
+    },
     GameConnectionRequest {
         login_token: u32,
         password: Password,
@@ -69,6 +73,9 @@ pub enum ClientMessage {
     Chat {
         text: String,
     },
+    ShoutChat {
+        text: String,
+    },
     Move {
         target_entity_id: Option<ClientEntityId>,
         x: f32,
@@ -110,6 +117,10 @@ pub enum ClientMessage {
     SetReviveSaveZone,
     QuestTrigger {
         trigger: QuestTriggerHash,
+
+        /// The index of the reward chosen by the player, for quests which
+        /// offer a choice of rewards
+        reward_choice: Option<usize>,
     },
     QuestDelete {
         slot: usize,
@@ -118,11 +129,20 @@ pub enum ClientMessage {
     PersonalStoreListItems {
         store_entity_id: ClientEntityId,
     },
+    PersonalStoreSearch {
+        item_type: Option<ItemType>,
+        item_name: Option<String>,
+    },
     PersonalStoreBuyItem {
         store_entity_id: ClientEntityId,
         store_slot_index: usize,
         buy_item: Item,
     },
+    PersonalStoreOpen {
+        title: String,
+        sell_items: Vec<(ItemSlot, Item, Money)>,
+        buy_items: Vec<(Item, Money)>,
+    },
     DropItem {
         item_slot: ItemSlot,
         quantity: usize,
@@ -212,6 +232,15 @@ pub enum ClientMessage {
         item_slot: ItemSlot,
         ingredients: [ItemSlot; 3],
     },
+    InventorySplitItem {
+        item_slot: ItemSlot,
+        quantity: u32,
+    },
+    InventoryMoveItem {
+        from_slot: ItemSlot,
+        to_slot: ItemSlot,
+    },
+    InventorySort,
     BankOpen,
     BankDepositItem {
         item_slot: ItemSlot,
@@ -231,6 +260,13 @@ pub enum ClientMessage {
         npc_entity_id: ClientEntityId,
         item_slot: ItemSlot,
     },
+    BarbershopPreview {
+        npc_entity_id: ClientEntityId,
+        face: u8,
+        hair: u8,
+    },
+    BarbershopConfirm,
+    BarbershopCancel,
     ClanCreate {
         name: String,
         description: String,
@@ -241,4 +277,32 @@ pub enum ClientMessage {
         level: Level,
         job: u16,
     },
+    ClanUpdateDescription {
+        description: String,
+    },
+    ClanUpdateNotice {
+        notice: String,
+    },
+    ClanUpdateMark {
+        mark: ClanMark,
+    },
+    ClanDeclareWar {
+        clan_name: String,
+    },
+    ClanAcceptWar {
+        clan_name: String,
+    },
+    ClanBankOpen,
+    ClanBankDepositItem {
+        item_slot: ItemSlot,
+        item: Item,
+    },
+    ClanBankWithdrawItem {
+        bank_slot: usize,
+        item: Item,
+    },
+    ClanBankGetLog,
+    ClientIntegrityResponse {
+        hashes: Vec<[u8; 32]>,
+    },
 }
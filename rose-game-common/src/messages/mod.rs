@@ -23,4 +23,6 @@ pub enum PartyXpSharing {
 pub enum PartyItemSharing {
     EqualLootDistribution,
     AcquisitionOrder,
+    /// Every item and all money is given to the party owner
+    PartyLeader,
 }
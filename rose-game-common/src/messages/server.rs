@@ -7,7 +7,7 @@ use thiserror::Error;
 use rose_data::{
     AbilityType, AmmoIndex, ClanMemberPosition, EquipmentIndex, EquipmentItem, Item, ItemReference,
     MotionId, NpcId, QuestTriggerHash, SkillId, StackableItem, StatusEffectType, VehiclePartIndex,
-    WorldTicks, ZoneId,
+    WorldTicks, ZoneId, ZoneTimeOfDay, ZoneWeather,
 };
 
 use crate::{
@@ -32,6 +32,14 @@ pub enum ConnectionRequestError {
     InvalidPassword,
 }
 
+#[derive(Copy, Clone, Debug, Error, Serialize, Deserialize)]
+pub enum SecondaryPinError {
+    #[error("Invalid secondary PIN, {attempts_remaining} attempts remaining")]
+    InvalidPin { attempts_remaining: u32 },
+    #[error("Secondary PIN is locked for {seconds_remaining} seconds")]
+    Locked { seconds_remaining: u32 },
+}
+
 #[derive(Copy, Clone, Debug, Error, Serialize, Deserialize)]
 pub enum LoginError {
     #[error("Login failed")]
@@ -42,6 +50,10 @@ pub enum LoginError {
     InvalidPassword,
     #[error("Already logged in")]
     AlreadyLoggedIn,
+    #[error("Server is under maintenance")]
+    Maintenance,
+    #[error("Not available in your region")]
+    OutsideRegion,
 }
 
 #[derive(Copy, Clone, Debug, Error, Serialize, Deserialize)]
@@ -56,6 +68,17 @@ pub enum JoinServerError {
     InvalidServerId,
     #[error("Invalid channel id")]
     InvalidChannelId,
+    #[error("Channel is full")]
+    ChannelFull,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChannelListItem {
+    pub id: u8,
+    pub name: String,
+
+    /// How crowded the channel is, from 0 to 100
+    pub percent_full: u8,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -167,6 +190,7 @@ pub struct SpawnEntityCharacter {
     pub team: Team,
     pub personal_store_info: Option<(i32, String)>,
     pub clan_membership: Option<CharacterClanMembership>,
+    pub rebirth_count: u32,
 }
 
 #[allow(dead_code)]
@@ -218,6 +242,17 @@ pub enum NpcStoreTransactionError {
     NotEnoughMoney,
     NotSameUnion,
     NotEnoughUnionPoints,
+    StoreClosed,
+    OutOfStock,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PersonalStoreSearchResult {
+    pub store_entity_id: ClientEntityId,
+    pub seller_name: String,
+    pub title: String,
+    pub position: Vec3,
+    pub sell_items: Vec<(u8, Item, Money)>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -240,6 +275,14 @@ pub struct PartyMemberInfoOffline {
     pub name: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartyMemberMapMarker {
+    pub character_id: CharacterUniqueId,
+    pub name: String,
+    pub zone_id: ZoneId,
+    pub position: Vec2,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PartyMemberInfo {
@@ -292,6 +335,73 @@ pub enum ClanCreateError {
     UnmetCondition,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClanUpdateError {
+    NoPermission,
+    InvalidDescription,
+    InvalidNotice,
+    InvalidMark,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClanBankError {
+    NotInClan,
+    NoPermission,
+    InvalidItem,
+    BankFull,
+    InventoryFull,
+    ItemBound,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClanBankAction {
+    Deposit,
+    Withdraw,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClanBankLogEntry {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub name: String,
+    pub action: ClanBankAction,
+    pub item: Item,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClanWarError {
+    NoPermission,
+    InvalidClan,
+    AlreadyAtWar,
+    NotDeclared,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClanWarResult {
+    Won,
+    Lost,
+    Draw,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum WarpGateError {
+    NotFound,
+    TooFarAway,
+    LevelTooLow,
+    QuestNotCompleted,
+    NotEnoughMoney,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum BarbershopError {
+    NpcTooFarAway,
+    InvalidAppearance,
+    NotEnoughMoney,
+    NoPreview,
+    PreviewExpired,
+    ZoneChanged,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClanMemberInfo {
     pub name: String,
@@ -318,7 +428,7 @@ pub enum ServerMessage {
     },
     ChannelList {
         server_id: usize,
-        channels: Vec<(u8, String)>,
+        channels: Vec<ChannelListItem>,
     },
     ChannelListError {
         error: ChannelListError,
@@ -361,6 +471,9 @@ pub enum ServerMessage {
         port: u16,
     },
     SelectCharacterError,
+    SecondaryPinRequest {
+        error: Option<SecondaryPinError>,
+    },
     CharacterData {
         data: Box<CharacterData>,
     },
@@ -457,6 +570,7 @@ pub enum ServerMessage {
         spawn_command_state: SpawnCommandState,
         move_mode: MoveMode,
         status_effects: ActiveStatusEffects,
+        is_elite: bool,
     },
     SpawnEntityNpc {
         entity_id: ClientEntityId,
@@ -524,6 +638,10 @@ pub enum ServerMessage {
     UpdateMoney {
         money: Money,
     },
+    UpdateRebirthCount {
+        entity_id: ClientEntityId,
+        rebirth_count: u32,
+    },
     UpdateStatusEffects {
         entity_id: ClientEntityId,
         status_effects: ActiveStatusEffects,
@@ -533,6 +651,7 @@ pub enum ServerMessage {
         entity_id: ClientEntityId,
         run_speed: i32,
         passive_attack_speed: i32,
+        weight_rate: u8,
     },
     UpdateXpStamina {
         xp: u64,
@@ -543,6 +662,10 @@ pub enum ServerMessage {
         item_slot: ItemSlot,
         life: u16,
     },
+    UpdateZoneEnvironment {
+        time_of_day: ZoneTimeOfDay,
+        weather: ZoneWeather,
+    },
     RewardItems {
         items: Vec<(ItemSlot, Option<Item>)>,
     },
@@ -599,6 +722,9 @@ pub enum ServerMessage {
         sell_items: Vec<(u8, Item, Money)>,
         buy_items: Vec<(u8, Item, Money)>,
     },
+    PersonalStoreSearchResults {
+        results: Vec<PersonalStoreSearchResult>,
+    },
     PersonalStoreTransaction {
         status: PersonalStoreTransactionStatus,
         store_entity_id: ClientEntityId,
@@ -713,6 +839,9 @@ pub enum ServerMessage {
     PartyMemberUpdateInfo {
         member_info: PartyMemberInfoOnline,
     },
+    PartyMemberMapMarkers {
+        markers: Vec<PartyMemberMapMarker>,
+    },
     PartyMemberRewardItem {
         client_entity_id: ClientEntityId,
         item: Item,
@@ -757,6 +886,17 @@ pub enum ServerMessage {
         item: Item,
         updated_money: Money,
     },
+    BarbershopPreview {
+        face: u8,
+        hair: u8,
+        price: Money,
+    },
+    BarbershopError {
+        error: BarbershopError,
+    },
+    WarpGateError {
+        error: WarpGateError,
+    },
     ClanInfo {
         id: ClanUniqueId,
         mark: ClanMark,
@@ -798,4 +938,54 @@ pub enum ServerMessage {
     ClanMemberList {
         members: Vec<ClanMemberInfo>,
     },
+    ClanNotice {
+        notice: String,
+    },
+    ClanUpdateError {
+        error: ClanUpdateError,
+    },
+    ClanWarDeclared {
+        clan_name: String,
+    },
+    ClanWarStarted {
+        clan_name: String,
+        duration: Duration,
+    },
+    ClanWarScore {
+        score: u32,
+        opponent_score: u32,
+    },
+    ClanWarEnded {
+        clan_name: String,
+        result: ClanWarResult,
+        score: u32,
+        opponent_score: u32,
+    },
+    ClanWarError {
+        error: ClanWarError,
+    },
+    ClanBankOpen {
+        items: Vec<(usize, Option<Item>)>,
+        can_deposit: bool,
+        can_withdraw: bool,
+    },
+    ClanBankUpdateItems {
+        items: Vec<(usize, Option<Item>)>,
+    },
+    ClanBankTransaction {
+        inventory_item_slot: ItemSlot,
+        inventory_item: Option<Item>,
+        bank_slot: usize,
+        bank_item: Option<Item>,
+    },
+    ClanBankLog {
+        entries: Vec<ClanBankLogEntry>,
+    },
+    ClanBankError {
+        error: ClanBankError,
+    },
+    /// The client must respond with the SHA-256 hash of each of these files
+    ClientIntegrityChallenge {
+        paths: Vec<String>,
+    },
 }
@@ -1,11 +1,12 @@
 use core::f32;
+use enum_map::EnumMap;
 use log::error;
 use rand::Rng;
 use rose_data_irose::IroseSkillPageType;
 use std::{num::NonZeroU32, sync::Arc};
 
 use rose_data::{
-    AbilityType, AmmoIndex, EquipmentIndex, EquipmentItem, Item, ItemClass, ItemDatabase,
+    AbilityType, AmmoIndex, Element, EquipmentIndex, EquipmentItem, Item, ItemClass, ItemDatabase,
     ItemReference, ItemType, ItemWeaponType, NpcDatabase, NpcId, SkillAddAbility, SkillData,
     SkillDatabase, VehiclePartIndex,
 };
@@ -95,7 +96,9 @@ impl AbilityValueCalculator for AbilityValuesData {
             attack_range: npc_data.attack_range,
             hit,
             defence,
+            block_rate: 0,
             resistance,
+            elemental_resistance: Default::default(),
             critical: (npc_data.level as f32 * 2.5) as i32,
             avoid,
             vehicle_attack_power: 0,
@@ -180,6 +183,16 @@ impl AbilityValueCalculator for AbilityValuesData {
                 _ => (0, 0, 0, 0),
             };
 
+        let defence = calculate_defence(
+            &self.item_database,
+            &basic_stats,
+            level,
+            &equipment_ability_values,
+            equipment,
+            &passive_ability_values,
+            false,
+        ) + job_add_defence;
+
         AbilityValues {
             is_driving: false,
             damage_category: DamageCategory::Character,
@@ -254,15 +267,13 @@ impl AbilityValueCalculator for AbilityValuesData {
                 equipment,
                 &passive_ability_values,
             ),
-            defence: calculate_defence(
+            defence,
+            block_rate: calculate_block_rate(
                 &self.item_database,
-                &basic_stats,
-                level,
-                &equipment_ability_values,
                 equipment,
                 &passive_ability_values,
-                false,
-            ) + job_add_defence,
+                defence,
+            ),
             resistance: calculate_resistance(
                 &self.item_database,
                 &basic_stats,
@@ -271,6 +282,10 @@ impl AbilityValueCalculator for AbilityValuesData {
                 equipment,
                 &passive_ability_values,
             ) + job_add_resistance,
+            elemental_resistance: calculate_elemental_resistance(
+                &equipment_ability_values,
+                &passive_ability_values,
+            ),
             critical: calculate_critical(
                 &basic_stats,
                 &equipment_ability_values,
@@ -362,6 +377,7 @@ impl AbilityValueCalculator for AbilityValuesData {
                 amount: 0,
                 apply_hit_stun: false,
                 is_critical: false,
+                is_blocked: false,
             }
         } else {
             match attacker.get_attack_damage_type() {
@@ -638,6 +654,7 @@ impl AbilityValueCalculator for AbilityValuesData {
             amount: damage as u32,
             is_critical: false,
             apply_hit_stun,
+            is_blocked: false,
         }
     }
 
@@ -699,7 +716,7 @@ impl AbilityValueCalculator for AbilityValuesData {
             BasicStatType::Sense => basic_stats.sense,
         };
 
-        if current > MAX_BASIC_STAT_VALUE {
+        if current >= MAX_BASIC_STAT_VALUE {
             None
         } else {
             Some((current as f32 * 0.2) as u32)
@@ -1138,20 +1155,23 @@ fn calculate_attack_damage_physical(
         };
 
         damage *= attacker.get_additional_damage_multipler();
-        damage = f32::max(damage * hit_count as f32, 10.0);
 
+        // Each hit of a multi-hit attack, such as a katar or dual sword pair, is
+        // capped individually rather than capping the combined damage
         if attacker.get_damage_category() == DamageCategory::Character
             && defender.get_damage_category() == DamageCategory::Character
         {
             damage = f32::min(damage, defender.get_max_health() as f32 * 0.35);
         }
 
+        damage = f32::max(damage * hit_count as f32, 10.0);
         damage = f32::min(damage, 2047.0);
 
         Damage {
             amount: damage as u32,
             is_critical: true,
             apply_hit_stun,
+            is_blocked: false,
         }
     } else {
         // Normal physical damage
@@ -1177,7 +1197,6 @@ fn calculate_attack_damage_physical(
         };
 
         damage *= attacker.get_additional_damage_multipler();
-        damage = f32::max(damage * hit_count as f32, 5.0);
 
         if attacker.get_damage_category() == DamageCategory::Character
             && defender.get_damage_category() == DamageCategory::Character
@@ -1185,12 +1204,14 @@ fn calculate_attack_damage_physical(
             damage = f32::min(damage, defender.get_max_health() as f32 * 0.25);
         }
 
+        damage = f32::max(damage * hit_count as f32, 5.0);
         damage = f32::min(damage, 2047.0);
 
         Damage {
             amount: damage as u32,
             is_critical: false,
             apply_hit_stun,
+            is_blocked: false,
         }
     }
 }
@@ -1233,7 +1254,6 @@ fn calculate_attack_damage_magic(
         };
 
         damage *= attacker.get_additional_damage_multipler();
-        damage = f32::max(damage * hit_count as f32, 10.0);
 
         if attacker.get_damage_category() == DamageCategory::Character
             && defender.get_damage_category() == DamageCategory::Character
@@ -1241,12 +1261,14 @@ fn calculate_attack_damage_magic(
             damage = f32::min(damage, defender.get_max_health() as f32 * 0.35);
         }
 
+        damage = f32::max(damage * hit_count as f32, 10.0);
         damage = f32::min(damage, 2047.0);
 
         Damage {
             amount: damage as u32,
             is_critical: true,
             apply_hit_stun,
+            is_blocked: false,
         }
     } else {
         // Normal magic damage
@@ -1274,7 +1296,6 @@ fn calculate_attack_damage_magic(
         };
 
         damage *= attacker.get_additional_damage_multipler();
-        damage = f32::max(damage * hit_count as f32, 5.0);
 
         if attacker.get_damage_category() == DamageCategory::Character
             && defender.get_damage_category() == DamageCategory::Character
@@ -1282,12 +1303,14 @@ fn calculate_attack_damage_magic(
             damage = f32::min(damage, defender.get_max_health() as f32 * 0.25);
         }
 
+        damage = f32::max(damage * hit_count as f32, 5.0);
         damage = f32::min(damage, 2047.0);
 
         Damage {
             amount: damage as u32,
             is_critical: false,
             apply_hit_stun,
+            is_blocked: false,
         }
     }
 }
@@ -1358,6 +1381,7 @@ struct EquipmentAbilityValue {
     pub bank_addon: i32,
     pub store_skin: i32,
     pub vehicle_health: i32,
+    pub elemental_resistance: EnumMap<Element, i32>,
 }
 
 impl EquipmentAbilityValue {
@@ -1431,6 +1455,14 @@ impl EquipmentAbilityValue {
             AbilityType::BankAddon => self.bank_addon += value,
             AbilityType::StoreSkin => self.store_skin += value,
             AbilityType::VehicleHealth => self.vehicle_health += value,
+            AbilityType::ResistFire
+            | AbilityType::ResistIce
+            | AbilityType::ResistLightning
+            | AbilityType::ResistEarth => {
+                if let Some(element) = ability_type.resisted_element() {
+                    self.elemental_resistance[element] += value;
+                }
+            }
             _ => {
                 error!("Item has unimplemented ability type {:?}", ability_type)
             }
@@ -1527,6 +1559,7 @@ struct PassiveSkillAbilities {
     avoid: i32,
     shield_defence: i32,
     immunity: i32,
+    elemental_resistance: EnumMap<Element, i32>,
 }
 
 impl PassiveSkillAbilities {
@@ -1604,6 +1637,14 @@ impl PassiveSkillAbilityValues {
             AbilityType::PassiveAvoid => abilities.avoid += value,
             AbilityType::PassiveShieldDefence => abilities.shield_defence += value,
             AbilityType::PassiveImmunity => abilities.immunity += value,
+            AbilityType::ResistFire
+            | AbilityType::ResistIce
+            | AbilityType::ResistLightning
+            | AbilityType::ResistEarth => {
+                if let Some(element) = ability_type.resisted_element() {
+                    abilities.elemental_resistance[element] += value;
+                }
+            }
             _ => {
                 error!(
                     "Passive skill has unimplemented ability type {:?}",
@@ -1703,8 +1744,6 @@ fn calculate_vehicle_move_speed(
             / 10.0
     };
 
-    // TODO: Limit speed to 300 if > max weight
-
     item_speed + vehicle_ability_values.move_speed as f32
 }
 
@@ -1797,10 +1836,12 @@ fn calculate_attack_power(
     let sense = basic_stats.sense as f32;
     let level = level.level as f32;
 
+    // Only ammo which matches the ammo slot contributes to attack power
     let get_ammo_quality = |item_database: &ItemDatabase, equipment: &Equipment, ammo_index| {
         equipment
             .get_ammo_item(ammo_index)
             .and_then(|item| item_database.get_material_item(item.item.item_number))
+            .filter(|item| item.item_data.class.ammo_index() == Some(ammo_index))
             .map(|item| item.item_data.quality)
             .unwrap_or(0) as f32
     };
@@ -2139,6 +2180,44 @@ fn calculate_defence(
     defence
 }
 
+fn calculate_elemental_resistance(
+    equipment_ability_values: &EquipmentAbilityValue,
+    passive_ability_values: &PassiveSkillAbilityValues,
+) -> EnumMap<Element, i32> {
+    let mut elemental_resistance = equipment_ability_values.elemental_resistance;
+
+    for (element, resistance) in elemental_resistance.iter_mut() {
+        *resistance += passive_ability_values.value.elemental_resistance[element];
+    }
+
+    elemental_resistance
+}
+
+fn calculate_block_rate(
+    item_database: &ItemDatabase,
+    equipment: &Equipment,
+    passive_ability_values: &PassiveSkillAbilityValues,
+    defence: i32,
+) -> i32 {
+    let has_shield = equipment
+        .get_equipment_item(EquipmentIndex::SubWeapon)
+        .filter(|item| !item.is_broken())
+        .and_then(|item| item_database.get_base_item(item.item))
+        .map_or(false, |item_data| {
+            matches!(item_data.class, ItemClass::Shield)
+        });
+    if !has_shield {
+        return 0;
+    }
+
+    let block_rate = 5.0
+        + defence as f32 * 0.02
+        + passive_ability_values.value.shield_defence as f32 * 0.1
+        + passive_ability_values.rate.shield_defence as f32 * 0.5;
+
+    i32::min(block_rate as i32, 40)
+}
+
 fn calculate_resistance(
     item_database: &ItemDatabase,
     basic_stats: &BasicStats,
@@ -2,7 +2,9 @@ use rand::Rng;
 use rose_file_readers::{StbFile, VirtualFilesystem};
 use std::sync::Arc;
 
-use rose_data::{EquipmentItem, ItemDatabase, ItemType, NpcDatabase, NpcId, StackableItem, ZoneId};
+use rose_data::{
+    EquipmentItem, ItemDatabase, ItemReference, ItemType, NpcDatabase, NpcId, StackableItem, ZoneId,
+};
 use rose_data_irose::decode_item_base1000;
 use rose_game_common::{
     components::{DroppedItem, Money},
@@ -182,6 +184,38 @@ impl DropTable for DropTableData {
             }
         }
     }
+
+    fn get_npc_drop_items(&self, npc_id: NpcId) -> Vec<ItemReference> {
+        let Some(npc_data) = self.npc_database.get_npc(npc_id) else {
+            return Vec::new();
+        };
+        let drop_table_row = npc_data.drop_table_index as usize;
+
+        let mut items = Vec::new();
+        for drop_table_column in 0..30 {
+            let drop_value = self
+                .lookup_drop(drop_table_row, drop_table_column)
+                .unwrap_or(0);
+            let drop_values = if (1..=4).contains(&drop_value) {
+                // Values 1 to 4 choose randomly from one of the sub tables
+                let first_column = (26 + drop_value * 5) as usize;
+                (first_column..first_column + 5)
+                    .map(|column| self.lookup_drop(drop_table_row, column).unwrap_or(0))
+                    .collect()
+            } else {
+                vec![drop_value]
+            };
+
+            for drop_value in drop_values {
+                if let Some(item_reference) = decode_item_base1000(drop_value as usize) {
+                    if !items.contains(&item_reference) {
+                        items.push(item_reference);
+                    }
+                }
+            }
+        }
+        items
+    }
 }
 
 pub fn get_drop_table(
@@ -6,7 +6,7 @@ use tokio::{
     net::TcpStream,
 };
 
-use crate::{Packet, PacketCodec};
+use crate::{Packet, PacketCodec, PacketDirection, PacketDump, WebSocketStream};
 
 #[derive(Debug, Error)]
 pub enum ConnectionError {
@@ -20,50 +20,209 @@ pub enum ConnectionError {
     DecryptBodyFailed,
 }
 
+/// The transport used to send and receive encrypted packets.
+enum ConnectionStream {
+    Tcp(BufWriter<TcpStream>),
+    WebSocket(WebSocketStream),
+}
+
+impl ConnectionStream {
+    async fn read_buf(&mut self, buffer: &mut BytesMut) -> Result<usize, ConnectionError> {
+        match self {
+            ConnectionStream::Tcp(stream) => stream
+                .read_buf(buffer)
+                .await
+                .map_err(|_| ConnectionError::ConnectionLost),
+            ConnectionStream::WebSocket(stream) => stream
+                .read_buf(buffer)
+                .await
+                .map_err(|_| ConnectionError::ConnectionLost),
+        }
+    }
+
+    async fn write_all(&mut self, data: &[u8]) -> Result<(), ConnectionError> {
+        match self {
+            ConnectionStream::Tcp(stream) => {
+                stream
+                    .write_all(data)
+                    .await
+                    .map_err(|_| ConnectionError::ConnectionLost)?;
+                stream
+                    .flush()
+                    .await
+                    .map_err(|_| ConnectionError::ConnectionLost)
+            }
+            ConnectionStream::WebSocket(stream) => stream
+                .write_all(data)
+                .await
+                .map_err(|_| ConnectionError::ConnectionLost),
+        }
+    }
+
+    async fn shutdown(&mut self) {
+        match self {
+            ConnectionStream::Tcp(stream) => {
+                let _ = stream.shutdown().await;
+            }
+            ConnectionStream::WebSocket(stream) => stream.shutdown().await,
+        }
+    }
+}
+
+// Largest packet we will attempt to decrypt whilst selecting a packet codec
+const MAX_PACKET_SIZE: usize = 0x7FF;
+
 pub struct Connection<'a> {
-    stream: BufWriter<TcpStream>,
+    stream: ConnectionStream,
     buffer: BytesMut,
     packet_codec: &'a (dyn PacketCodec + Send + Sync),
+    connection_packet_codec: Option<Box<dyn PacketCodec + Send + Sync>>,
+    packet_dump: Option<PacketDump>,
 }
 
 impl<'a> Connection<'a> {
     pub fn new(socket: TcpStream, packet_codec: &'a (dyn PacketCodec + Send + Sync)) -> Self {
         Self {
-            stream: BufWriter::new(socket),
+            stream: ConnectionStream::Tcp(BufWriter::new(socket)),
             buffer: BytesMut::with_capacity(4 * 1024),
             packet_codec,
+            connection_packet_codec: None,
+            packet_dump: None,
+        }
+    }
+
+    /// Creates a connection which sends and receives packets in WebSocket binary frames.
+    pub fn new_websocket(
+        stream: WebSocketStream,
+        packet_codec: &'a (dyn PacketCodec + Send + Sync),
+    ) -> Self {
+        Self {
+            stream: ConnectionStream::WebSocket(stream),
+            buffer: BytesMut::with_capacity(4 * 1024),
+            packet_codec,
+            connection_packet_codec: None,
+            packet_dump: None,
+        }
+    }
+
+    pub fn get_packet_codec_seed(&self) -> u32 {
+        self.connection_packet_codec
+            .as_deref()
+            .unwrap_or(self.packet_codec)
+            .get_seed()
+    }
+
+    /// Waits until the header of the first packet sent by the client has been
+    /// received.
+    pub async fn wait_for_first_packet(&mut self) -> Result<(), anyhow::Error> {
+        while self.buffer.len() < 6 {
+            self.stream.read_buf(&mut self.buffer).await?;
+            if self.buffer.is_empty() {
+                return Err(ConnectionError::ConnectionLost.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Selects the packet codec for this connection by finding which of
+    /// `packet_codecs` can decrypt the first packet sent by the client.
+    ///
+    /// Returns the index of the selected packet codec, or None if the first
+    /// packet can only be decrypted by the default packet codec.
+    pub async fn select_packet_codec(
+        &mut self,
+        packet_codecs: Vec<Box<dyn PacketCodec + Send + Sync>>,
+    ) -> Result<Option<usize>, anyhow::Error> {
+        if packet_codecs.is_empty() {
+            return Ok(None);
         }
+
+        let is_valid_first_packet =
+            |packet_codec: &dyn PacketCodec, buffer: &BytesMut| -> Option<bool> {
+                let mut header = BytesMut::from(&buffer[..6]);
+                let read_length = packet_codec.decrypt_packet_header(&mut header);
+                if !(6..=MAX_PACKET_SIZE).contains(&read_length) {
+                    return Some(false);
+                }
+
+                if buffer.len() < read_length {
+                    // Need to wait for more data before we can validate the packet
+                    return None;
+                }
+
+                let mut packet = BytesMut::from(&buffer[..read_length]);
+                packet_codec.decrypt_packet_header(&mut packet);
+                Some(packet_codec.decrypt_packet_body(&mut packet))
+            };
+
+        loop {
+            self.wait_for_first_packet().await?;
+
+            let mut needs_more_data = false;
+            for (index, packet_codec) in packet_codecs.iter().enumerate() {
+                match is_valid_first_packet(packet_codec.as_ref(), &self.buffer) {
+                    Some(true) => {
+                        self.connection_packet_codec = packet_codecs.into_iter().nth(index);
+                        return Ok(Some(index));
+                    }
+                    Some(false) => {}
+                    None => needs_more_data = true,
+                }
+            }
+
+            match is_valid_first_packet(self.packet_codec, &self.buffer) {
+                Some(true) => return Ok(None),
+                Some(false) => {}
+                None => needs_more_data = true,
+            }
+
+            if !needs_more_data {
+                return Err(ConnectionError::DecryptHeaderFailed.into());
+            }
+
+            self.stream.read_buf(&mut self.buffer).await?;
+        }
+    }
+
+    pub fn set_packet_dump(&mut self, mut packet_dump: PacketDump) {
+        packet_dump.write_header(
+            "codec_seed",
+            &format!("{:08X}", self.get_packet_codec_seed()),
+        );
+        self.packet_dump = Some(packet_dump);
+    }
+
+    pub fn packet_dump_mut(&mut self) -> Option<&mut PacketDump> {
+        self.packet_dump.as_mut()
     }
 
     pub async fn shutdown(&mut self) {
-        let _ = self.stream.shutdown().await;
+        self.stream.shutdown().await;
     }
 
     pub async fn read_packet(&mut self) -> Result<Packet, anyhow::Error> {
+        let packet_codec = self
+            .connection_packet_codec
+            .as_deref()
+            .unwrap_or(self.packet_codec);
         let mut read_length = 6usize;
         let mut have_read_header = false;
 
         loop {
             while self.buffer.len() < read_length {
-                match self.stream.read_buf(&mut self.buffer).await {
-                    Ok(_) => {
-                        if self.buffer.is_empty() {
-                            return Err(ConnectionError::ConnectionLost.into());
-                        }
-                    }
-                    Err(_) => {
-                        return Err(ConnectionError::ConnectionLost.into());
-                    }
+                self.stream.read_buf(&mut self.buffer).await?;
+                if self.buffer.is_empty() {
+                    return Err(ConnectionError::ConnectionLost.into());
                 }
             }
 
             if !have_read_header {
-                read_length = self.packet_codec.decrypt_packet_header(&mut self.buffer);
+                read_length = packet_codec.decrypt_packet_header(&mut self.buffer);
                 if read_length == 0 {
                     return Err(ConnectionError::DecryptHeaderFailed.into());
                 }
                 have_read_header = true;
-            } else if self.packet_codec.decrypt_packet_body(&mut self.buffer) {
+            } else if packet_codec.decrypt_packet_body(&mut self.buffer) {
                 // Read packet into size, command, data
                 let size = self.buffer.get_u16_le() as usize;
                 let command = self.buffer.get_u16_le();
@@ -79,6 +238,9 @@ impl<'a> Connection<'a> {
                 self.buffer.advance(read_length - size);
 
                 trace!(target: "packets", "RECV [{:03X}] {:02x?}", command, &data[..]);
+                if let Some(packet_dump) = self.packet_dump.as_mut() {
+                    packet_dump.write_packet(PacketDirection::Recv, command, &data);
+                }
                 return Ok(Packet { command, data });
             } else {
                 return Err(ConnectionError::DecryptBodyFailed.into());
@@ -88,6 +250,9 @@ impl<'a> Connection<'a> {
 
     pub async fn write_packet(&mut self, packet: Packet) -> Result<(), anyhow::Error> {
         trace!(target: "packets", "SEND [{:03X}] {:02x?}", packet.command, &packet.data[..]);
+        if let Some(packet_dump) = self.packet_dump.as_mut() {
+            packet_dump.write_packet(PacketDirection::Send, packet.command, &packet.data);
+        }
 
         let size = packet.data.len() + 6;
         let mut buffer = BytesMut::with_capacity(size);
@@ -95,18 +260,12 @@ impl<'a> Connection<'a> {
         buffer.put_u16_le(packet.command);
         buffer.put_u16_le(0);
         buffer.put(packet.data);
-        self.packet_codec.encrypt_packet(&mut buffer);
-
-        self.stream
-            .write_all(&buffer)
-            .await
-            .map_err(|_| ConnectionError::ConnectionLost)?;
-
-        self.stream
-            .flush()
-            .await
-            .map_err(|_| ConnectionError::ConnectionLost)?;
+        self.connection_packet_codec
+            .as_deref()
+            .unwrap_or(self.packet_codec)
+            .encrypt_packet(&mut buffer);
 
+        self.stream.write_all(&buffer).await?;
         Ok(())
     }
 }
@@ -1,5 +1,9 @@
 mod connection;
 mod packet;
+mod packet_dump;
+mod websocket;
 
 pub use connection::{Connection, ConnectionError};
 pub use packet::{Packet, PacketCodec, PacketError, PacketReader, PacketWriter};
+pub use packet_dump::{PacketDirection, PacketDump, PacketDumpEntry, PacketDumpError};
+pub use websocket::{WebSocketError, WebSocketStream};
@@ -0,0 +1,139 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{LineWriter, Write},
+    path::Path,
+    time::Instant,
+};
+
+use thiserror::Error;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PacketDirection {
+    Recv,
+    Send,
+}
+
+impl PacketDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PacketDirection::Recv => "RECV",
+            PacketDirection::Send => "SEND",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PacketDumpError {
+    #[error("invalid packet dump line")]
+    InvalidLine,
+}
+
+/// A single decrypted packet from a packet dump.
+///
+/// Each packet is written as one line of the form:
+/// `<milliseconds since connect> <RECV|SEND> <command hex> <payload hex>`
+#[derive(Clone, Debug)]
+pub struct PacketDumpEntry {
+    pub time_ms: u128,
+    pub direction: PacketDirection,
+    pub command: u16,
+    pub data: Vec<u8>,
+}
+
+impl PacketDumpEntry {
+    /// Parses a packet line, returns None for comments and empty lines.
+    pub fn parse(line: &str) -> Result<Option<PacketDumpEntry>, PacketDumpError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let mut parts = line.split_whitespace();
+        let time_ms = parts
+            .next()
+            .and_then(|value| value.parse::<u128>().ok())
+            .ok_or(PacketDumpError::InvalidLine)?;
+        let direction = match parts.next() {
+            Some("RECV") => PacketDirection::Recv,
+            Some("SEND") => PacketDirection::Send,
+            _ => return Err(PacketDumpError::InvalidLine),
+        };
+        let command = parts
+            .next()
+            .and_then(|value| u16::from_str_radix(value, 16).ok())
+            .ok_or(PacketDumpError::InvalidLine)?;
+        let payload = parts.next().unwrap_or("");
+        if payload.len() % 2 != 0 {
+            return Err(PacketDumpError::InvalidLine);
+        }
+        let data = (0..payload.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&payload[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| PacketDumpError::InvalidLine)?;
+
+        Ok(Some(PacketDumpEntry {
+            time_ms,
+            direction,
+            command,
+            data,
+        }))
+    }
+
+    /// Parses the value of a `# key value` header comment line.
+    pub fn parse_header<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+        let mut parts = line.trim().strip_prefix('#')?.split_whitespace();
+        if parts.next()? == key {
+            parts.next()
+        } else {
+            None
+        }
+    }
+}
+
+/// Writes the decrypted packets of a single connection to a file.
+pub struct PacketDump {
+    writer: LineWriter<File>,
+    start_time: Instant,
+}
+
+impl PacketDump {
+    pub fn create(path: &Path) -> Result<Self, std::io::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            writer: LineWriter::new(File::create(path)?),
+            start_time: Instant::now(),
+        })
+    }
+
+    pub fn write_header(&mut self, key: &str, value: &str) {
+        writeln!(self.writer, "# {} {}", key, value).ok();
+    }
+
+    pub fn write_note(&mut self, note: &str) {
+        for line in note.lines() {
+            writeln!(self.writer, "#   {}", line).ok();
+        }
+    }
+
+    pub fn write_packet(&mut self, direction: PacketDirection, command: u16, data: &[u8]) {
+        let mut payload = String::with_capacity(data.len() * 2);
+        for byte in data {
+            write!(payload, "{:02x}", byte).ok();
+        }
+
+        writeln!(
+            self.writer,
+            "{} {} {:03X} {}",
+            self.start_time.elapsed().as_millis(),
+            direction.as_str(),
+            command,
+            payload
+        )
+        .ok();
+    }
+}
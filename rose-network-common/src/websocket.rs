@@ -0,0 +1,82 @@
+use bytes::{BufMut, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, protocol::WebSocketConfig, Message};
+
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum WebSocketError {
+    #[error("connection lost")]
+    ConnectionLost,
+
+    #[error("invalid websocket handshake")]
+    InvalidHandshake,
+
+    #[error("invalid websocket frame")]
+    InvalidFrame,
+
+    #[error("websocket connection closed")]
+    Closed,
+}
+
+/// Wraps a TCP stream in the WebSocket protocol, packets are sent and
+/// received as the payload of binary messages.
+pub struct WebSocketStream {
+    stream: tokio_tungstenite::WebSocketStream<TcpStream>,
+}
+
+impl WebSocketStream {
+    /// Performs the server side of the WebSocket opening handshake, which
+    /// rejects requests without valid upgrade headers. Frames from the client
+    /// must be masked as required by RFC 6455.
+    pub async fn accept(socket: TcpStream) -> Result<Self, WebSocketError> {
+        let config = WebSocketConfig {
+            max_message_size: Some(MAX_MESSAGE_SIZE),
+            max_frame_size: Some(MAX_MESSAGE_SIZE),
+            accept_unmasked_frames: false,
+            ..Default::default()
+        };
+        let stream = tokio_tungstenite::accept_async_with_config(socket, Some(config))
+            .await
+            .map_err(|_| WebSocketError::InvalidHandshake)?;
+        Ok(Self { stream })
+    }
+
+    /// Reads the payload of the next binary message into buffer, pings are
+    /// answered whilst waiting for it.
+    pub async fn read_buf(&mut self, buffer: &mut BytesMut) -> Result<usize, WebSocketError> {
+        loop {
+            match self.stream.next().await {
+                Some(Ok(Message::Binary(payload))) => {
+                    if !payload.is_empty() {
+                        buffer.put(payload.as_slice());
+                        return Ok(payload.len());
+                    }
+                }
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+                Some(Ok(Message::Close(_))) => return Err(WebSocketError::Closed),
+                Some(Ok(Message::Text(_))) => return Err(WebSocketError::InvalidFrame),
+                Some(Err(
+                    tungstenite::Error::ConnectionClosed
+                    | tungstenite::Error::AlreadyClosed
+                    | tungstenite::Error::Io(_),
+                ))
+                | None => return Err(WebSocketError::ConnectionLost),
+                Some(Err(_)) => return Err(WebSocketError::InvalidFrame),
+            }
+        }
+    }
+
+    pub async fn write_all(&mut self, data: &[u8]) -> Result<(), WebSocketError> {
+        self.stream
+            .send(Message::Binary(data.to_vec()))
+            .await
+            .map_err(|_| WebSocketError::ConnectionLost)
+    }
+
+    pub async fn shutdown(&mut self) {
+        self.stream.close(None).await.ok();
+    }
+}
@@ -873,6 +873,7 @@ impl<'a> PacketReadDamage for PacketReader<'a> {
                 amount: server_damage.amount() as u32,
                 is_critical,
                 apply_hit_stun,
+                is_blocked: false,
             },
             is_killed,
             is_immediate,
@@ -904,6 +905,7 @@ impl PacketWriteDamage for PacketWriter {
             action |= 0x10;
         }
 
+        // The iROSE client has no block flag, the game server whispers blocks instead
         let damage = PacketServerDamage::new()
             .with_amount(damage.amount.min(2047) as u16)
             .with_action(action);
@@ -1211,7 +1213,12 @@ impl PacketWritePartyRules for PacketWriter {
     fn write_party_rules(&mut self, item_sharing: &PartyItemSharing, xp_sharing: &PartyXpSharing) {
         let mut bits = 0;
 
-        if matches!(item_sharing, PartyItemSharing::AcquisitionOrder) {
+        // The irose client has no party leader item sharing, so it is shown as acquisition
+        // order which is the only rule where the picked up item can go to another member
+        if matches!(
+            item_sharing,
+            PartyItemSharing::AcquisitionOrder | PartyItemSharing::PartyLeader
+        ) {
             bits |= 0x80;
         }
 
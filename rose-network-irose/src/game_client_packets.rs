@@ -10,7 +10,9 @@ use num_derive::FromPrimitive;
 use rose_data::{AmmoIndex, EquipmentIndex, Item, MotionId, SkillId, VehiclePartIndex, WarpGateId};
 use rose_data_irose::{decode_ammo_index, encode_ammo_index};
 use rose_game_common::{
-    components::{BasicStatType, CharacterUniqueId, ClanMark, HotbarSlot, ItemSlot, SkillSlot},
+    components::{
+        BasicStatType, CharacterUniqueId, ClanMark, HotbarSlot, ItemSlot, Money, SkillSlot,
+    },
     messages::{
         client::NpcStoreBuyItem, ClientEntityId, PartyItemSharing, PartyRejectInviteReason,
         PartyXpSharing,
@@ -26,7 +28,7 @@ use crate::common_packets::{
     PacketWriteItems, PacketWritePartyRules, PacketWriteSkillSlot, PacketWriteVehiclePartIndex,
 };
 
-#[derive(FromPrimitive)]
+#[derive(Debug, FromPrimitive)]
 pub enum ClientPackets {
     LogoutRequest = 0x707,
     ConnectRequest = 0x70b,
@@ -39,6 +41,7 @@ pub enum ClientPackets {
     Emote = 0x781,
     MoveToggle = 0x782,
     Chat = 0x783,
+    ShoutChat = 0x785,
     StopMove = 0x796,
     Attack = 0x798,
     Move = 0x79a,
@@ -59,6 +62,7 @@ pub enum ClientPackets {
     CastSkillTargetPosition = 0x7b4,
     CraftItem = 0x7bc,
     ChangeVehiclePart = 0x7ca,
+    PersonalStoreOpen = 0x7c2,
     PersonalStoreListItems = 0x7c4,
     PersonalStoreBuyItem = 0x7c5,
     RepairItemUsingItem = 0x7cb,
@@ -67,6 +71,7 @@ pub enum ClientPackets {
     PartyReply = 0x7d1,
     PartyUpdateRules = 0x7d7,
     ClanCommand = 0x7e0,
+    ClientIntegrityResponse = 0x7f0,
 }
 
 #[derive(Debug)]
@@ -231,6 +236,33 @@ impl<'a> TryFrom<&'a Packet> for PacketClientChat<'a> {
     }
 }
 
+#[derive(Debug)]
+pub struct PacketClientShoutChat<'a> {
+    pub text: &'a str,
+}
+
+impl<'a> From<&'a PacketClientShoutChat<'a>> for Packet {
+    fn from(packet: &'a PacketClientShoutChat<'a>) -> Self {
+        let mut writer = PacketWriter::new(ClientPackets::ShoutChat as u16);
+        writer.write_null_terminated_utf8(packet.text);
+        writer.into()
+    }
+}
+
+impl<'a> TryFrom<&'a Packet> for PacketClientShoutChat<'a> {
+    type Error = PacketError;
+
+    fn try_from(packet: &'a Packet) -> Result<Self, Self::Error> {
+        if packet.command != ClientPackets::ShoutChat as u16 {
+            return Err(PacketError::InvalidPacket);
+        }
+
+        let mut reader = PacketReader::from(packet);
+        let text = reader.read_null_terminated_utf8()?;
+        Ok(PacketClientShoutChat { text })
+    }
+}
+
 #[derive(Debug)]
 pub struct PacketClientSetHotbarSlot {
     pub slot_index: usize,
@@ -578,6 +610,74 @@ impl From<&PacketClientPersonalStoreBuyItem> for Packet {
     }
 }
 
+#[derive(Debug)]
+pub struct PacketClientPersonalStoreOpen<'a> {
+    pub title: &'a str,
+    pub sell_items: Vec<(ItemSlot, Item, Money)>,
+    pub buy_items: Vec<(Item, Money)>,
+}
+
+impl<'a> TryFrom<&'a Packet> for PacketClientPersonalStoreOpen<'a> {
+    type Error = PacketError;
+
+    fn try_from(packet: &'a Packet) -> Result<Self, Self::Error> {
+        if packet.command != ClientPackets::PersonalStoreOpen as u16 {
+            return Err(PacketError::InvalidPacket);
+        }
+
+        let mut reader = PacketReader::from(packet);
+        let num_sell_items = reader.read_u8()? as usize;
+        let num_buy_items = reader.read_u8()? as usize;
+        let mut sell_items = Vec::with_capacity(num_sell_items);
+        let mut buy_items = Vec::with_capacity(num_buy_items);
+
+        for _ in 0..num_sell_items {
+            let item_slot = reader.read_item_slot_u8()?;
+            let item = reader.read_item_full()?.ok_or(PacketError::InvalidPacket)?;
+            let price = Money(reader.read_u32()? as i64);
+            sell_items.push((item_slot, item, price));
+        }
+
+        for _ in 0..num_buy_items {
+            let _slot = reader.read_u8()?;
+            let item = reader.read_item_full()?.ok_or(PacketError::InvalidPacket)?;
+            let price = Money(reader.read_u32()? as i64);
+            buy_items.push((item, price));
+        }
+
+        let title = reader.read_null_terminated_utf8()?;
+
+        Ok(PacketClientPersonalStoreOpen {
+            title,
+            sell_items,
+            buy_items,
+        })
+    }
+}
+
+impl<'a> From<&'a PacketClientPersonalStoreOpen<'a>> for Packet {
+    fn from(packet: &'a PacketClientPersonalStoreOpen<'a>) -> Self {
+        let mut writer = PacketWriter::new(ClientPackets::PersonalStoreOpen as u16);
+        writer.write_u8(packet.sell_items.len() as u8);
+        writer.write_u8(packet.buy_items.len() as u8);
+
+        for (item_slot, item, price) in packet.sell_items.iter() {
+            writer.write_item_slot_u8(*item_slot);
+            writer.write_item_full(Some(item));
+            writer.write_u32(price.0 as u32);
+        }
+
+        for (slot_index, (item, price)) in packet.buy_items.iter().enumerate() {
+            writer.write_u8(slot_index as u8);
+            writer.write_item_full(Some(item));
+            writer.write_u32(price.0 as u32);
+        }
+
+        writer.write_null_terminated_utf8(packet.title);
+        writer.into()
+    }
+}
+
 #[derive(Debug)]
 pub struct PacketClientRepairItemUsingItem {
     pub use_item_slot: ItemSlot,
@@ -1572,3 +1672,41 @@ impl From<&PacketClientClanCommand> for Packet {
         writer.into()
     }
 }
+
+/// Not part of the official protocol, only clients which support the client
+/// integrity check respond to its challenge
+#[derive(Debug)]
+pub struct PacketClientClientIntegrityResponse {
+    pub hashes: Vec<[u8; 32]>,
+}
+
+impl TryFrom<&Packet> for PacketClientClientIntegrityResponse {
+    type Error = PacketError;
+
+    fn try_from(packet: &Packet) -> Result<Self, Self::Error> {
+        if packet.command != ClientPackets::ClientIntegrityResponse as u16 {
+            return Err(PacketError::InvalidPacket);
+        }
+
+        let mut reader = PacketReader::from(packet);
+        let count = reader.read_u8()? as usize;
+        let mut hashes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(reader.read_fixed_length_bytes(32)?);
+            hashes.push(hash);
+        }
+        Ok(PacketClientClientIntegrityResponse { hashes })
+    }
+}
+
+impl From<&PacketClientClientIntegrityResponse> for Packet {
+    fn from(packet: &PacketClientClientIntegrityResponse) -> Self {
+        let mut writer = PacketWriter::new(ClientPackets::ClientIntegrityResponse as u16);
+        writer.write_u8(packet.hashes.len() as u8);
+        for hash in packet.hashes.iter() {
+            writer.write_bytes(hash);
+        }
+        writer.into()
+    }
+}
@@ -49,7 +49,7 @@ use crate::common_packets::{
     PacketWriteStatusEffects, PacketWriteVehiclePartIndex,
 };
 
-#[derive(FromPrimitive)]
+#[derive(Debug, FromPrimitive)]
 pub enum ServerPackets {
     AnnounceChat = 0x702,
     LogoutResult = 0x707,
@@ -125,6 +125,7 @@ pub enum ServerPackets {
     PartyMemberUpdateInfo = 0x7d5,
     PartyUpdateRules = 0x7d7,
     ClanCommand = 0x7e0,
+    ClientIntegrityChallenge = 0x7f0,
 }
 
 #[allow(dead_code)]
@@ -3267,6 +3268,7 @@ pub struct PacketServerUpdateSpeed {
     pub entity_id: ClientEntityId,
     pub run_speed: i32,
     pub passive_attack_speed: i32,
+    pub weight_rate: u8,
 }
 
 impl TryFrom<&Packet> for PacketServerUpdateSpeed {
@@ -3281,12 +3283,13 @@ impl TryFrom<&Packet> for PacketServerUpdateSpeed {
         let entity_id = reader.read_entity_id()?;
         let run_speed = reader.read_u16()? as i32;
         let passive_attack_speed = reader.read_u16()? as i32;
-        let _weight_rate = reader.read_u8()?;
+        let weight_rate = reader.read_u8()?;
 
         Ok(Self {
             entity_id,
             run_speed,
             passive_attack_speed,
+            weight_rate,
         })
     }
 }
@@ -3297,7 +3300,7 @@ impl From<&PacketServerUpdateSpeed> for Packet {
         writer.write_entity_id(packet.entity_id);
         writer.write_u16(packet.run_speed as u16);
         writer.write_u16(packet.passive_attack_speed as u16);
-        writer.write_u8(0); // TODO: Weight rate
+        writer.write_u8(packet.weight_rate);
         writer.into()
     }
 }
@@ -3389,6 +3392,9 @@ impl From<&PacketServerNpcStoreTransactionError> for Packet {
             NpcStoreTransactionError::NotEnoughMoney => 4,
             NpcStoreTransactionError::NotSameUnion => 5,
             NpcStoreTransactionError::NotEnoughUnionPoints => 6,
+            // The irose client has no closed store or out of stock errors, the
+            // game server sends them as whispers instead of this packet
+            NpcStoreTransactionError::StoreClosed | NpcStoreTransactionError::OutOfStock => 2,
         };
 
         writer.write_u8(error);
@@ -4463,3 +4469,38 @@ impl From<&PacketServerClanCommand> for Packet {
         writer.into()
     }
 }
+
+/// Not part of the official protocol, asks the client for the SHA-256 hash of
+/// each of these files
+pub struct PacketServerClientIntegrityChallenge<'a> {
+    pub paths: Vec<&'a str>,
+}
+
+impl<'a> TryFrom<&'a Packet> for PacketServerClientIntegrityChallenge<'a> {
+    type Error = PacketError;
+
+    fn try_from(packet: &'a Packet) -> Result<Self, Self::Error> {
+        if packet.command != ServerPackets::ClientIntegrityChallenge as u16 {
+            return Err(PacketError::InvalidPacket);
+        }
+
+        let mut reader = PacketReader::from(packet);
+        let count = reader.read_u8()? as usize;
+        let mut paths = Vec::with_capacity(count);
+        for _ in 0..count {
+            paths.push(reader.read_null_terminated_utf8()?);
+        }
+        Ok(PacketServerClientIntegrityChallenge { paths })
+    }
+}
+
+impl<'a> From<&'a PacketServerClientIntegrityChallenge<'a>> for Packet {
+    fn from(packet: &'a PacketServerClientIntegrityChallenge<'a>) -> Self {
+        let mut writer = PacketWriter::new(ServerPackets::ClientIntegrityChallenge as u16);
+        writer.write_u8(packet.paths.len() as u8);
+        for path in packet.paths.iter() {
+            writer.write_null_terminated_utf8(path);
+        }
+        writer.into()
+    }
+}
@@ -3,7 +3,7 @@ use std::convert::TryFrom;
 
 use rose_network_common::{Packet, PacketError, PacketReader, PacketWriter};
 
-#[derive(FromPrimitive)]
+#[derive(Debug, FromPrimitive)]
 pub enum ClientPackets {
     Connect = 0x703,
     ChannelList = 0x704,
@@ -2,7 +2,7 @@ use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use rose_network_common::{Packet, PacketError, PacketReader, PacketWriter};
 
-#[derive(FromPrimitive)]
+#[derive(Debug, FromPrimitive)]
 pub enum ServerPackets {
     ChannelList = 0x704,
     LoginReply = 0x708,
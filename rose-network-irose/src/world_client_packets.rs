@@ -6,7 +6,7 @@ use rose_network_common::{Packet, PacketError, PacketReader, PacketWriter};
 
 use crate::common_packets::{PacketReadCharacterGender, PacketWriteCharacterGender};
 
-#[derive(FromPrimitive)]
+#[derive(Debug, FromPrimitive)]
 pub enum ClientPackets {
     ConnectRequest = 0x70b,
     CharacterListRequest = 0x712,
@@ -14,6 +14,9 @@ pub enum ClientPackets {
     DeleteCharacter = 0x714,
     SelectCharacter = 0x715,
     ClanCommand = 0x7e0,
+
+    // Extension which is not sent by the official client
+    SecondaryPin = 0x7f0,
 }
 
 #[derive(Debug)]
@@ -76,6 +79,7 @@ pub struct PacketClientCreateCharacter<'a> {
     pub birth_stone: u8,
     pub hair: u8,
     pub face: u8,
+    pub weapon_type: u8,
     pub start_point: u16,
     pub name: &'a str,
 }
@@ -92,7 +96,7 @@ impl<'a> TryFrom<&'a Packet> for PacketClientCreateCharacter<'a> {
         let birth_stone = reader.read_u8()?;
         let hair = reader.read_u8()?;
         let face = reader.read_u8()?;
-        let _weapon_type = reader.read_u8()?;
+        let weapon_type = reader.read_u8()?;
         let start_point = reader.read_u16()?;
         let name = reader.read_null_terminated_utf8()?;
         Ok(PacketClientCreateCharacter {
@@ -100,6 +104,7 @@ impl<'a> TryFrom<&'a Packet> for PacketClientCreateCharacter<'a> {
             birth_stone,
             hair,
             face,
+            weapon_type,
             start_point,
             name,
         })
@@ -113,7 +118,7 @@ impl<'a> From<&'a PacketClientCreateCharacter<'a>> for Packet {
         writer.write_u8(packet.birth_stone);
         writer.write_u8(packet.hair);
         writer.write_u8(packet.face);
-        writer.write_u8(0);
+        writer.write_u8(packet.weapon_type);
         writer.write_u16(packet.start_point);
         writer.write_null_terminated_utf8(packet.name);
         writer.into()
@@ -233,3 +238,30 @@ impl From<&PacketClientClanCommand> for Packet {
         writer.into()
     }
 }
+
+#[derive(Debug)]
+pub struct PacketClientSecondaryPin<'a> {
+    pub pin: &'a str,
+}
+
+impl<'a> TryFrom<&'a Packet> for PacketClientSecondaryPin<'a> {
+    type Error = PacketError;
+
+    fn try_from(packet: &'a Packet) -> Result<Self, Self::Error> {
+        if packet.command != ClientPackets::SecondaryPin as u16 {
+            return Err(PacketError::InvalidPacket);
+        }
+
+        let mut reader = PacketReader::from(packet);
+        let pin = reader.read_null_terminated_utf8()?;
+        Ok(PacketClientSecondaryPin { pin })
+    }
+}
+
+impl<'a> From<&'a PacketClientSecondaryPin<'a>> for Packet {
+    fn from(packet: &'a PacketClientSecondaryPin<'a>) -> Self {
+        let mut writer = PacketWriter::new(ClientPackets::SecondaryPin as u16);
+        writer.write_null_terminated_utf8(packet.pin);
+        writer.into()
+    }
+}
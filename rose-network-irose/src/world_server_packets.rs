@@ -5,13 +5,13 @@ use num_traits::FromPrimitive;
 use rose_data::{EquipmentIndex, EquipmentItem, ItemReference, ZoneId};
 use rose_game_common::{
     components::{CharacterDeleteTime, CharacterInfo, Equipment, Level},
-    messages::server::CharacterListItem,
+    messages::server::{CharacterListItem, SecondaryPinError},
 };
 use rose_network_common::{Packet, PacketError, PacketReader, PacketWriter};
 
 use crate::common_packets::{PacketReadCharacterGender, PacketWriteCharacterGender};
 
-#[derive(FromPrimitive)]
+#[derive(Debug, FromPrimitive)]
 pub enum ServerPackets {
     ConnectReply = 0x70c,
     CharacterListReply = 0x712,
@@ -19,6 +19,9 @@ pub enum ServerPackets {
     DeleteCharacterReply = 0x714,
     MoveServer = 0x711,
     ReturnToCharacterSelect = 0x71c,
+
+    // Extension which is not sent by the official server
+    SecondaryPinRequest = 0x7f0,
 }
 
 #[allow(dead_code)]
@@ -334,3 +337,53 @@ impl From<&PacketServerReturnToCharacterSelect> for Packet {
         writer.into()
     }
 }
+
+pub struct PacketServerSecondaryPinRequest {
+    pub error: Option<SecondaryPinError>,
+}
+
+impl TryFrom<&Packet> for PacketServerSecondaryPinRequest {
+    type Error = PacketError;
+
+    fn try_from(packet: &Packet) -> Result<Self, Self::Error> {
+        if packet.command != ServerPackets::SecondaryPinRequest as u16 {
+            return Err(PacketError::InvalidPacket);
+        }
+
+        let mut reader = PacketReader::from(packet);
+        let result = reader.read_u8()?;
+        let value = reader.read_u32()?;
+        let error = match result {
+            0 => None,
+            1 => Some(SecondaryPinError::InvalidPin {
+                attempts_remaining: value,
+            }),
+            2 => Some(SecondaryPinError::Locked {
+                seconds_remaining: value,
+            }),
+            _ => return Err(PacketError::InvalidPacket),
+        };
+        Ok(PacketServerSecondaryPinRequest { error })
+    }
+}
+
+impl From<&PacketServerSecondaryPinRequest> for Packet {
+    fn from(packet: &PacketServerSecondaryPinRequest) -> Self {
+        let mut writer = PacketWriter::new(ServerPackets::SecondaryPinRequest as u16);
+        match packet.error {
+            None => {
+                writer.write_u8(0);
+                writer.write_u32(0);
+            }
+            Some(SecondaryPinError::InvalidPin { attempts_remaining }) => {
+                writer.write_u8(1);
+                writer.write_u32(attempts_remaining);
+            }
+            Some(SecondaryPinError::Locked { seconds_remaining }) => {
+                writer.write_u8(2);
+                writer.write_u32(seconds_remaining);
+            }
+        }
+        writer.into()
+    }
+}
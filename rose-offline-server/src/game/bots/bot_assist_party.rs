@@ -0,0 +1,128 @@
+use bevy::prelude::{Commands, Component, Entity, Query, With};
+use big_brain::{
+    prelude::{ActionBuilder, ActionState, ScorerBuilder},
+    scorers::Score,
+    thinker::Actor,
+};
+
+use crate::game::{
+    bots::IDLE_DURATION,
+    components::{
+        ClientEntity, Command, CommandData, HealthPoints, NextCommand, Party, PartyMembership,
+        Position, Team,
+    },
+};
+
+use super::{BotCombatTarget, BotQueryFilterAlive, BotQueryFilterAliveNoTarget};
+
+#[derive(Clone, Component, Debug, ScorerBuilder)]
+pub struct PartyMemberHasTarget {
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Component, ActionBuilder)]
+pub struct AssistPartyMember;
+
+/// Returns the first living enemy which an online party member in the same
+/// zone is attacking.
+fn find_party_target(
+    entity: Entity,
+    query_bot: &Query<(&PartyMembership, &Position, &Team), BotQueryFilterAliveNoTarget>,
+    query_party: &Query<&Party>,
+    query_member: &Query<(&Command, &Position)>,
+    query_target: &Query<(&Team, &HealthPoints), With<ClientEntity>>,
+) -> Option<Entity> {
+    let (party_membership, position, team) = query_bot.get(entity).ok()?;
+    let party = query_party.get(party_membership.party?).ok()?;
+
+    party
+        .members
+        .iter()
+        .filter_map(|party_member| party_member.get_entity())
+        .filter(|member_entity| *member_entity != entity)
+        .filter_map(|member_entity| query_member.get(member_entity).ok())
+        .filter(|(_, member_position)| member_position.zone_id == position.zone_id)
+        .filter_map(|(member_command, _)| match member_command.command {
+            CommandData::Attack { target } => Some(target),
+            _ => None,
+        })
+        .find(|target| {
+            query_target
+                .get(*target)
+                .ok()
+                .map_or(false, |(target_team, target_health_points)| {
+                    target_team.id != team.id && target_health_points.hp > 0
+                })
+        })
+}
+
+pub fn score_party_member_has_target(
+    mut query: Query<(&PartyMemberHasTarget, &Actor, &mut Score)>,
+    query_bot: Query<(&PartyMembership, &Position, &Team), BotQueryFilterAliveNoTarget>,
+    query_party: Query<&Party>,
+    query_member: Query<(&Command, &Position)>,
+    query_target: Query<(&Team, &HealthPoints), With<ClientEntity>>,
+) {
+    for (scorer, &Actor(entity), mut score) in query.iter_mut() {
+        score.set(0.0);
+
+        if find_party_target(
+            entity,
+            &query_bot,
+            &query_party,
+            &query_member,
+            &query_target,
+        )
+        .is_some()
+        {
+            score.set(scorer.score);
+        }
+    }
+}
+
+pub fn action_assist_party_member(
+    mut commands: Commands,
+    mut query: Query<(&Actor, &mut ActionState), With<AssistPartyMember>>,
+    query_bot: Query<(&PartyMembership, &Position, &Team), BotQueryFilterAliveNoTarget>,
+    query_party: Query<&Party>,
+    query_member: Query<(&Command, &Position)>,
+    query_target: Query<(&Team, &HealthPoints), With<ClientEntity>>,
+    query_command: Query<&Command, BotQueryFilterAlive>,
+) {
+    for (&Actor(entity), mut state) in query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                let Some(target) = find_party_target(
+                    entity,
+                    &query_bot,
+                    &query_party,
+                    &query_member,
+                    &query_target,
+                ) else {
+                    *state = ActionState::Failure;
+                    continue;
+                };
+
+                commands
+                    .entity(entity)
+                    .insert(NextCommand::with_attack(target))
+                    .insert(BotCombatTarget { entity: target });
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Ok(command) = query_command.get(entity) else {
+                    *state = ActionState::Failure;
+                    continue;
+                };
+
+                if command.is_stop_for(IDLE_DURATION) {
+                    *state = ActionState::Success;
+                }
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::{
+    ecs::query::WorldQuery,
+    math::Vec3Swizzles,
+    prelude::{Commands, Component, Entity, Query, Res, With},
+};
+use big_brain::{
+    prelude::{ActionBuilder, ActionState, ScorerBuilder},
+    scorers::Score,
+    thinker::Actor,
+};
+
+use crate::game::{
+    bots::{BotProfile, IDLE_DURATION},
+    components::{ClientEntityType, Command, HealthPoints, NextCommand, Position, Team},
+    resources::ClientEntityList,
+};
+
+use super::{BotCombatTarget, BotQueryFilterAlive, BotQueryFilterAliveNoTarget};
+
+const DUELIST_SEARCH_DISTANCE: f32 = 1500.0f32;
+
+#[derive(Debug, Clone, Component, ScorerBuilder)]
+pub struct FindNearbyDuelist {
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Component, ActionBuilder)]
+pub struct ChallengeNearbyDuelist;
+
+#[derive(WorldQuery)]
+pub struct BotQuery<'w> {
+    entity: Entity,
+    command: &'w Command,
+    position: &'w Position,
+    team: &'w Team,
+}
+
+/// Returns the nearest living duelist bot which is not on our team.
+fn find_nearest_duelist(
+    bot: &BotQueryItem,
+    query_duelist: &Query<(&BotProfile, &Team, &HealthPoints)>,
+    client_entity_list: &ClientEntityList,
+) -> Option<Entity> {
+    let zone_entities = client_entity_list.get_zone(bot.position.zone_id)?;
+
+    zone_entities
+        .iter_entity_type_within_distance(
+            bot.position.position.xy(),
+            DUELIST_SEARCH_DISTANCE,
+            &[ClientEntityType::Character],
+        )
+        .filter(|(nearby_entity, _)| *nearby_entity != bot.entity)
+        .filter(|(nearby_entity, _)| {
+            query_duelist.get(*nearby_entity).ok().map_or(
+                false,
+                |(nearby_profile, nearby_team, nearby_health_points)| {
+                    *nearby_profile == BotProfile::Duelist
+                        && nearby_team.id != bot.team.id
+                        && nearby_health_points.hp > 0
+                },
+            )
+        })
+        .min_by(|(_, a), (_, b)| {
+            let distance_a = bot.position.position.xy().distance_squared(a.xy());
+            let distance_b = bot.position.position.xy().distance_squared(b.xy());
+            distance_a.total_cmp(&distance_b)
+        })
+        .map(|(nearby_entity, _)| nearby_entity)
+}
+
+pub fn score_find_nearby_duelist(
+    mut query: Query<(&FindNearbyDuelist, &Actor, &mut Score)>,
+    query_bot: Query<BotQuery, BotQueryFilterAliveNoTarget>,
+    query_duelist: Query<(&BotProfile, &Team, &HealthPoints)>,
+    client_entity_list: Res<ClientEntityList>,
+) {
+    for (scorer, &Actor(entity), mut score) in query.iter_mut() {
+        score.set(0.0);
+
+        let Ok(bot) = query_bot.get(entity) else {
+            continue;
+        };
+
+        if find_nearest_duelist(&bot, &query_duelist, &client_entity_list).is_some() {
+            score.set(scorer.score);
+        }
+    }
+}
+
+pub fn action_challenge_nearby_duelist(
+    mut commands: Commands,
+    mut query: Query<(&Actor, &mut ActionState), With<ChallengeNearbyDuelist>>,
+    query_bot: Query<BotQuery, BotQueryFilterAlive>,
+    query_duelist: Query<(&BotProfile, &Team, &HealthPoints)>,
+    client_entity_list: Res<ClientEntityList>,
+) {
+    for (&Actor(entity), mut state) in query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                let Some(duelist_entity) = query_bot.get(entity).ok().and_then(|bot| {
+                    find_nearest_duelist(&bot, &query_duelist, &client_entity_list)
+                }) else {
+                    *state = ActionState::Failure;
+                    continue;
+                };
+
+                commands
+                    .entity(entity)
+                    .insert(NextCommand::with_attack(duelist_entity))
+                    .insert(BotCombatTarget {
+                        entity: duelist_entity,
+                    });
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                let Ok(bot) = query_bot.get(entity) else {
+                    *state = ActionState::Failure;
+                    continue;
+                };
+
+                if bot.command.is_stop_for(IDLE_DURATION) {
+                    *state = ActionState::Success;
+                }
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::Component;
+use big_brain::{
+    prelude::Highest,
+    thinker::{Thinker, ThinkerBuilder},
+};
+use serde::Deserialize;
+
+use super::{
+    bot_thinker, AcceptPartyInvite, ActionAttackTarget, AssistPartyMember,
+    AttackRandomNearbyTarget, AttackThreat, CanPartyInviteNearbyBot, ChallengeNearbyDuelist,
+    FindMonsterSpawns, FindNearbyDuelist, FindNearbyItemDrop, FindNearbyTarget, HasPartyInvite,
+    IsDead, IsTeleporting, JoinZone, PartyInviteNearbyBot, PartyMemberHasTarget,
+    PickupNearestItemDrop, ReviveCurrentZone, ShouldAttackTarget, ShouldSitRecoverHp,
+    ShouldUseAttackSkill, ShouldUseBuffSkill, SitRecoverHp, ThreatIsNotTarget, UseAttackSkill,
+    UseBuffSkill, WanderTown,
+};
+
+/// The behaviour of a bot, each profile composes a different thinker from the
+/// same scorers and actions.
+#[derive(Clone, Copy, Component, Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BotProfile {
+    /// Hunts monsters at spawns which match its level and picks up their drops
+    Farmer,
+
+    /// Wanders around town challenging other duelist bots
+    Duelist,
+
+    /// Forms parties with nearby bots and attacks whatever its party is fighting
+    PackHunter,
+
+    /// Walks around town and only fights back when attacked
+    TownIdler,
+}
+
+impl BotProfile {
+    pub const ALL: [BotProfile; 4] = [
+        BotProfile::Farmer,
+        BotProfile::Duelist,
+        BotProfile::PackHunter,
+        BotProfile::TownIdler,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotProfile::Farmer => "farmer",
+            BotProfile::Duelist => "duelist",
+            BotProfile::PackHunter => "pack_hunter",
+            BotProfile::TownIdler => "town_idler",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn thinker(&self) -> ThinkerBuilder {
+        match self {
+            BotProfile::Farmer => bot_thinker(),
+            BotProfile::Duelist => Thinker::build()
+                .picker(Highest)
+                .when(IsDead { score: 1.0 }, ReviveCurrentZone)
+                .when(IsTeleporting { score: 1.0 }, JoinZone)
+                .when(ThreatIsNotTarget { score: 0.9 }, AttackThreat)
+                .when(ShouldUseAttackSkill { score: 0.85 }, UseAttackSkill)
+                .when(
+                    ShouldAttackTarget {
+                        min_score: 0.6,
+                        max_score: 0.8,
+                    },
+                    ActionAttackTarget,
+                )
+                .when(ShouldSitRecoverHp { score: 0.4 }, SitRecoverHp)
+                .when(ShouldUseBuffSkill { score: 0.3 }, UseBuffSkill)
+                .when(FindNearbyDuelist { score: 0.2 }, ChallengeNearbyDuelist)
+                .otherwise(WanderTown),
+            BotProfile::PackHunter => Thinker::build()
+                .picker(Highest)
+                .when(IsDead { score: 1.0 }, ReviveCurrentZone)
+                .when(IsTeleporting { score: 1.0 }, JoinZone)
+                .when(HasPartyInvite { score: 1.0 }, AcceptPartyInvite)
+                .when(ThreatIsNotTarget { score: 0.9 }, AttackThreat)
+                .when(ShouldUseAttackSkill { score: 0.85 }, UseAttackSkill)
+                .when(
+                    ShouldAttackTarget {
+                        min_score: 0.6,
+                        max_score: 0.8,
+                    },
+                    ActionAttackTarget,
+                )
+                .when(CanPartyInviteNearbyBot { score: 0.7 }, PartyInviteNearbyBot)
+                .when(PartyMemberHasTarget { score: 0.65 }, AssistPartyMember)
+                .when(FindNearbyItemDrop { score: 0.5 }, PickupNearestItemDrop)
+                .when(ShouldSitRecoverHp { score: 0.4 }, SitRecoverHp)
+                .when(ShouldUseBuffSkill { score: 0.3 }, UseBuffSkill)
+                .when(FindNearbyTarget { score: 0.2 }, AttackRandomNearbyTarget)
+                .otherwise(FindMonsterSpawns),
+            BotProfile::TownIdler => Thinker::build()
+                .picker(Highest)
+                .when(IsDead { score: 1.0 }, ReviveCurrentZone)
+                .when(IsTeleporting { score: 1.0 }, JoinZone)
+                .when(ThreatIsNotTarget { score: 0.9 }, AttackThreat)
+                .when(
+                    ShouldAttackTarget {
+                        min_score: 0.6,
+                        max_score: 0.8,
+                    },
+                    ActionAttackTarget,
+                )
+                .when(ShouldSitRecoverHp { score: 0.4 }, SitRecoverHp)
+                .otherwise(WanderTown),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use bevy::prelude::{Commands, Component, Query, Res, Vec3, With};
+use big_brain::{
+    prelude::{ActionBuilder, ActionState},
+    thinker::Actor,
+};
+use rand::Rng;
+
+use crate::game::{
+    components::{Command, MoveMode, NextCommand, Position},
+    GameData,
+};
+
+use super::BotQueryFilterAlive;
+
+/// How far from the zone start position bots wander
+const TOWN_WANDER_DISTANCE: f32 = 1500.0;
+
+/// How long bots stand still between each walk
+const TOWN_WANDER_PAUSE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default, Clone, Component, ActionBuilder)]
+pub struct WanderTown;
+
+pub fn action_wander_town(
+    mut commands: Commands,
+    mut query: Query<(&Actor, &mut ActionState), With<WanderTown>>,
+    query_entity: Query<(&Command, &Position), BotQueryFilterAlive>,
+    game_data: Res<GameData>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (&Actor(entity), mut state) in query.iter_mut() {
+        let Ok((command, position)) = query_entity.get(entity) else {
+            continue;
+        };
+
+        match *state {
+            ActionState::Requested => {
+                let Some(zone_data) = game_data.zones.get_zone(position.zone_id) else {
+                    *state = ActionState::Failure;
+                    continue;
+                };
+
+                commands.entity(entity).insert(NextCommand::with_move(
+                    zone_data.start_position
+                        + Vec3::new(
+                            rng.gen_range(-TOWN_WANDER_DISTANCE..TOWN_WANDER_DISTANCE),
+                            rng.gen_range(-TOWN_WANDER_DISTANCE..TOWN_WANDER_DISTANCE),
+                            0.0,
+                        ),
+                    None,
+                    Some(MoveMode::Walk),
+                ));
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                if command.is_stop_for(TOWN_WANDER_PAUSE) {
+                    *state = ActionState::Success;
+                }
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Success;
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,34 +1,49 @@
 mod bot_accept_party_invite;
+mod bot_assist_party;
 mod bot_attack_target;
 mod bot_attack_threat;
+mod bot_duel;
 mod bot_find_monster_spawn;
 mod bot_find_nearby_target;
 mod bot_join_zone;
 mod bot_pickup_item;
+mod bot_profile;
 mod bot_revive;
 mod bot_send_party_invite;
 mod bot_sit_recover_hp;
 mod bot_snowball_fight;
 mod bot_use_attack_skill;
 mod bot_use_buff_skill;
+mod bot_wander_town;
 
 mod create_bot;
+mod spawn_bot;
 
+pub use bot_profile::BotProfile;
 pub use create_bot::{
     bot_build_artisan, bot_build_bourgeois, bot_build_champion, bot_build_cleric, bot_build_knight,
     bot_build_mage, bot_build_raider, bot_build_scout, bot_create_random_build,
     bot_create_with_build, BotBuild,
 };
+pub use spawn_bot::{bot_level_range, bot_spawn};
 
 use bot_accept_party_invite::{
     action_accept_party_invite, score_has_party_invite, AcceptPartyInvite, HasPartyInvite,
 };
+use bot_assist_party::{
+    action_assist_party_member, score_party_member_has_target, AssistPartyMember,
+    PartyMemberHasTarget,
+};
 use bot_attack_target::{
     action_attack_target, score_should_attack_target, ActionAttackTarget, ShouldAttackTarget,
 };
 use bot_attack_threat::{
     action_attack_threat, score_threat_is_not_target, AttackThreat, ThreatIsNotTarget,
 };
+use bot_duel::{
+    action_challenge_nearby_duelist, score_find_nearby_duelist, ChallengeNearbyDuelist,
+    FindNearbyDuelist,
+};
 use bot_find_monster_spawn::{action_find_monster_spawn, FindMonsterSpawns};
 use bot_find_nearby_target::{
     action_attack_random_nearby_target, score_find_nearby_target, AttackRandomNearbyTarget,
@@ -55,6 +70,8 @@ use bot_use_buff_skill::{
     action_use_buff_skill, score_should_use_buff_skill, ShouldUseBuffSkill, UseBuffSkill,
 };
 
+use bot_wander_town::{action_wander_town, WanderTown};
+
 use bevy::prelude::{Component, Entity, IntoSystemConfigs, Plugin, PreUpdate, With, Without};
 use big_brain::{
     prelude::Highest,
@@ -84,9 +101,11 @@ impl Plugin for BotPlugin {
             (
                 (
                     action_accept_party_invite,
+                    action_assist_party_member,
                     action_attack_random_nearby_target,
                     action_attack_target,
                     action_attack_threat,
+                    action_challenge_nearby_duelist,
                     action_find_monster_spawn,
                     action_join_zone,
                     action_party_invite_nearby_bot,
@@ -96,15 +115,18 @@ impl Plugin for BotPlugin {
                     action_snowball_fight,
                     action_use_attack_skill,
                     action_use_buff_skill,
+                    action_wander_town,
                 )
                     .in_set(BigBrainSet::Actions),
                 (
                     score_can_party_invite_nearby_bot,
+                    score_find_nearby_duelist,
                     score_find_nearby_item_drop_system,
                     score_find_nearby_target,
                     score_has_party_invite,
                     score_is_dead,
                     score_is_teleporting,
+                    score_party_member_has_target,
                     score_should_attack_target,
                     score_should_sit_recover_hp,
                     score_should_use_attack_skill,
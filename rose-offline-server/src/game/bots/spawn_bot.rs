@@ -0,0 +1,154 @@
+use std::ops::RangeInclusive;
+
+use bevy::{
+    prelude::{Commands, Entity},
+    utils::HashSet,
+};
+
+use rose_data::{EquipmentIndex, ZoneId};
+
+use crate::game::{
+    bundles::CharacterBundle,
+    components::{
+        ClanMembership, Command, Cooldowns, DamageSources, EquipmentItemDatabase, MotionData,
+        MoveMode, MoveSpeed, MovementImpairment, NextCommand, PartyMembership, PassiveRecoveryTime,
+        Position, StatusEffects, StatusEffectsRegen, Team,
+    },
+    GameData,
+};
+
+use super::{bot_create_random_build, BotProfile};
+
+/// Returns a level range for bots in the zone, based on the median level of
+/// the monsters which spawn there.
+pub fn bot_level_range(game_data: &GameData, zone_id: ZoneId) -> RangeInclusive<u32> {
+    let Some(zone_data) = game_data.zones.get_zone(zone_id) else {
+        return 1..=170;
+    };
+
+    // Find all monsters which spawn in this zone
+    let mut monster_ids = HashSet::new();
+    for spawn in zone_data.monster_spawns.iter() {
+        for npc_id in spawn.basic_spawns.iter().map(|(npc_id, _)| *npc_id) {
+            monster_ids.insert(npc_id);
+        }
+
+        for npc_id in spawn.tactic_spawns.iter().map(|(npc_id, _)| *npc_id) {
+            monster_ids.insert(npc_id);
+        }
+    }
+
+    // Create a sorted list of the levels of the monsters in this zone
+    let mut monster_levels: Vec<_> = monster_ids
+        .iter()
+        .filter_map(|npc_id| game_data.npcs.get_npc(*npc_id))
+        .map(|npc_data| npc_data.level)
+        .collect();
+    monster_levels.sort();
+
+    // Calculate a bot level range based on the median monster level +/- 20%
+    let Some(median_monster_level) = monster_levels.get((monster_levels.len() + 1) / 2) else {
+        return 1..=170;
+    };
+
+    // Ensure delta_level is mininum of 2 to ensure at least some variation in bot levels
+    let delta_level = (*median_monster_level / 5).max(2);
+    (median_monster_level - delta_level).max(1) as u32
+        ..=(median_monster_level + delta_level).min(170) as u32
+}
+
+/// Spawns a bot with a random build, it joins the zone at `position` once its
+/// thinker runs.
+pub fn bot_spawn(
+    commands: &mut Commands,
+    game_data: &GameData,
+    name: String,
+    position: Position,
+    level: u32,
+    profile: BotProfile,
+) -> Entity {
+    let (bot_build, mut bot_data) = bot_create_random_build(game_data, name, level);
+
+    let status_effects = StatusEffects::new();
+    let status_effects_regen = StatusEffectsRegen::new();
+
+    let ability_values = game_data.ability_value_calculator.calculate(
+        &bot_data.info,
+        &bot_data.level,
+        &bot_data.equipment,
+        &bot_data.basic_stats,
+        &bot_data.skill_list,
+        &status_effects,
+    );
+
+    let move_mode = MoveMode::Run;
+    let move_speed = MoveSpeed::new(ability_values.get_move_speed(&move_mode));
+
+    let weapon_motion_type = game_data
+        .items
+        .get_equipped_weapon_item_data(&bot_data.equipment, EquipmentIndex::Weapon)
+        .map(|item_data| item_data.motion_type)
+        .unwrap_or(0) as usize;
+
+    let motion_data = MotionData::from_character(
+        game_data.motions.as_ref(),
+        weapon_motion_type,
+        bot_data.info.gender,
+    );
+
+    bot_data.position = position;
+    bot_data.health_points.hp = ability_values.get_max_health();
+    bot_data.mana_points.mp = ability_values.get_max_mana();
+
+    let mut entity_commands = commands.spawn((
+        bot_build,
+        profile,
+        profile.thinker(),
+        CharacterBundle {
+            ability_values,
+            achievements: bot_data.achievements,
+            basic_stats: bot_data.basic_stats,
+            bank: Default::default(),
+            cooldowns: Cooldowns::default(),
+            command: Command::default(),
+            damage_sources: DamageSources::default_character(),
+            equipment: bot_data.equipment,
+            experience_points: bot_data.experience_points,
+            health_points: bot_data.health_points,
+            honor_points: bot_data.honor_points,
+            hotbar: bot_data.hotbar,
+            info: bot_data.info,
+            inventory: bot_data.inventory,
+            level: bot_data.level,
+            mana_points: bot_data.mana_points,
+            motion_data,
+            move_mode,
+            move_speed,
+            movement_impairment: MovementImpairment::new(),
+            next_command: NextCommand::default(),
+            party_membership: PartyMembership::default(),
+            passive_recovery_time: PassiveRecoveryTime::default(),
+            position: bot_data.position,
+            quest_state: bot_data.quest_state,
+            rebirth: bot_data.rebirth,
+            skill_list: bot_data.skill_list,
+            skill_points: bot_data.skill_points,
+            stamina: bot_data.stamina,
+            stat_points: bot_data.stat_points,
+            statistics: bot_data.statistics,
+            status_effects,
+            status_effects_regen,
+            team: Team::default_character(),
+            union_membership: bot_data.union_membership,
+            clan_membership: ClanMembership::default(),
+        },
+    ));
+
+    // Duelists must be on their own team to be able to attack each other
+    if profile == BotProfile::Duelist {
+        let team = Team::with_unique_id(entity_commands.id().index());
+        entity_commands.insert(team);
+    }
+
+    entity_commands.id()
+}
@@ -0,0 +1,128 @@
+use rose_data::AbilityType;
+use rose_game_common::components::{BasicStatType, BasicStats, CharacterGender, StatPoints};
+
+use crate::game::{components::GameClient, messages::server::ServerMessage, GameData};
+
+const ALL_BASIC_STAT_TYPES: [BasicStatType; 6] = [
+    BasicStatType::Strength,
+    BasicStatType::Dexterity,
+    BasicStatType::Intelligence,
+    BasicStatType::Concentration,
+    BasicStatType::Charm,
+    BasicStatType::Sense,
+];
+
+#[derive(Debug)]
+pub enum BasicStatIncreaseError {
+    MaxValue,
+    NotEnoughPoints,
+}
+
+pub fn basic_stats_try_increase(
+    game_data: &GameData,
+    basic_stats: &mut BasicStats,
+    stat_points: &mut StatPoints,
+    basic_stat_type: BasicStatType,
+) -> Result<i32, BasicStatIncreaseError> {
+    let cost = game_data
+        .ability_value_calculator
+        .calculate_basic_stat_increase_cost(basic_stats, basic_stat_type)
+        .ok_or(BasicStatIncreaseError::MaxValue)?;
+
+    if cost > stat_points.points {
+        return Err(BasicStatIncreaseError::NotEnoughPoints);
+    }
+
+    let value = basic_stats.get(basic_stat_type) + 1;
+    stat_points.points -= cost;
+    basic_stats.set(basic_stat_type, value);
+    Ok(value)
+}
+
+/// Calculates how many stat points were spent raising `basic_stats` from
+/// `initial_basic_stats`, following the same cost curve as basic_stats_try_increase.
+fn calculate_spent_stat_points(
+    game_data: &GameData,
+    initial_basic_stats: &BasicStats,
+    basic_stats: &BasicStats,
+) -> u32 {
+    let mut spent_points = 0;
+
+    for basic_stat_type in ALL_BASIC_STAT_TYPES {
+        let mut current = initial_basic_stats.clone();
+        while current.get(basic_stat_type) < basic_stats.get(basic_stat_type) {
+            let Some(cost) = game_data
+                .ability_value_calculator
+                .calculate_basic_stat_increase_cost(&current, basic_stat_type)
+            else {
+                break;
+            };
+
+            spent_points += cost;
+            current.set(basic_stat_type, current.get(basic_stat_type) + 1);
+        }
+    }
+
+    spent_points
+}
+
+/// Calculates how many stat points a character has been rewarded for levelling
+/// up to `level`, following the same table as the experience points system.
+fn calculate_earned_stat_points(game_data: &GameData, level: u32) -> u32 {
+    (2..=level)
+        .map(|level| {
+            game_data
+                .ability_value_calculator
+                .calculate_levelup_reward_stat_points(level)
+        })
+        .sum()
+}
+
+/// Sends the value of every basic stat to the client, after they were changed
+/// without the client requesting it.
+pub fn basic_stats_send_update(game_client: &GameClient, basic_stats: &BasicStats) {
+    for basic_stat_type in ALL_BASIC_STAT_TYPES {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::UpdateBasicStat {
+                basic_stat_type,
+                value: basic_stats.get(basic_stat_type),
+            })
+            .ok();
+    }
+}
+
+pub fn basic_stats_reset(
+    game_data: &GameData,
+    gender: CharacterGender,
+    level: u32,
+    basic_stats: &mut BasicStats,
+    stat_points: &mut StatPoints,
+    game_client: Option<&GameClient>,
+) -> bool {
+    let Ok(initial_basic_stats) = game_data.character_creator.get_basic_stats(gender) else {
+        return false;
+    };
+
+    // Stats can also be raised by quest rewards without spending points, so
+    // the refund can never give back more than was earned from levelling up
+    let earned_points = calculate_earned_stat_points(game_data, level);
+    let refund_points = calculate_spent_stat_points(game_data, &initial_basic_stats, basic_stats)
+        .min(earned_points.saturating_sub(stat_points.points));
+    *basic_stats = initial_basic_stats;
+    stat_points.points = stat_points.points.saturating_add(refund_points);
+
+    if let Some(game_client) = game_client {
+        basic_stats_send_update(game_client, basic_stats);
+
+        game_client
+            .server_message_tx
+            .send(ServerMessage::UpdateAbilityValueSet {
+                ability_type: AbilityType::BonusPoint,
+                value: stat_points.points as i32,
+            })
+            .ok();
+    }
+
+    true
+}
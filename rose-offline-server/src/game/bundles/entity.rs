@@ -9,14 +9,16 @@ use std::time::Duration;
 use rose_data::{NpcId, ZoneId};
 
 use crate::game::{
+    bundles::item_assign_instance_id,
     components::{
-        AbilityValues, Bank, BasicStats, CharacterInfo, ClanMembership, ClientEntity,
+        AbilityValues, Achievements, Bank, BasicStats, CharacterInfo, ClanMembership, ClientEntity,
         ClientEntityId, ClientEntitySector, ClientEntityType, ClientEntityVisibility, Command,
-        Cooldowns, DamageSources, DroppedItem, EntityExpireTime, Equipment, ExperiencePoints,
-        GameClient, HealthPoints, Hotbar, Inventory, ItemDrop, Level, ManaPoints, MotionData,
-        MoveMode, MoveSpeed, NextCommand, Npc, NpcAi, NpcStandingDirection, ObjectVariables, Owner,
-        OwnerExpireTime, PartyMembership, PartyOwner, PassiveRecoveryTime, Position, QuestState,
-        SkillList, SkillPoints, SpawnOrigin, Stamina, StatPoints, StatusEffects,
+        Cooldowns, DamageSources, DefaultExpireTime, DroppedItem, EliteMonster, EntityExpireTime,
+        Equipment, ExperiencePoints, GameClient, HealthPoints, HonorPoints, Hotbar, Inventory,
+        ItemDrop, Level, ManaPoints, MotionData, MoveMode, MoveSpeed, MovementImpairment,
+        NextCommand, Npc, NpcAi, NpcStandingDirection, ObjectVariables, Owner, OwnerExpireTime,
+        PartyMembership, PartyOwner, PassiveRecoveryTime, Position, QuestState, Rebirth, SkillList,
+        SkillPoints, SpawnOrigin, Stamina, StatPoints, Statistics, StatusEffects,
         StatusEffectsRegen, Team, UnionMembership,
     },
     messages::server::ServerMessage,
@@ -28,7 +30,6 @@ pub const EVENT_OBJECT_VARIABLES_COUNT: usize = 20;
 pub const NPC_OBJECT_VARIABLES_COUNT: usize = 20;
 pub const MONSTER_OBJECT_VARIABLES_COUNT: usize = 5;
 pub const ITEM_DROP_ENTITY_EXPIRE_TIME: Duration = Duration::from_secs(120);
-pub const ITEM_DROP_OWNER_EXPIRE_TIME: Duration = Duration::from_secs(60);
 pub const ITEM_DROP_RADIUS: i32 = 200;
 
 #[derive(Bundle)]
@@ -54,6 +55,7 @@ pub struct NpcBundle {
 #[derive(Bundle)]
 pub struct CharacterBundle {
     pub ability_values: AbilityValues,
+    pub achievements: Achievements,
     pub basic_stats: BasicStats,
     pub bank: Bank,
     pub cooldowns: Cooldowns,
@@ -62,6 +64,7 @@ pub struct CharacterBundle {
     pub equipment: Equipment,
     pub experience_points: ExperiencePoints,
     pub health_points: HealthPoints,
+    pub honor_points: HonorPoints,
     pub hotbar: Hotbar,
     pub info: CharacterInfo,
     pub inventory: Inventory,
@@ -70,15 +73,18 @@ pub struct CharacterBundle {
     pub motion_data: MotionData,
     pub move_mode: MoveMode,
     pub move_speed: MoveSpeed,
+    pub movement_impairment: MovementImpairment,
     pub next_command: NextCommand,
     pub party_membership: PartyMembership,
     pub passive_recovery_time: PassiveRecoveryTime,
     pub position: Position,
     pub quest_state: QuestState,
+    pub rebirth: Rebirth,
     pub skill_list: SkillList,
     pub skill_points: SkillPoints,
     pub stamina: Stamina,
     pub stat_points: StatPoints,
+    pub statistics: Statistics,
     pub status_effects: StatusEffects,
     pub status_effects_regen: StatusEffectsRegen,
     pub team: Team,
@@ -96,6 +102,7 @@ pub struct MonsterBundle {
     pub motion_data: MotionData,
     pub move_mode: MoveMode,
     pub move_speed: MoveSpeed,
+    pub movement_impairment: MovementImpairment,
     pub next_command: NextCommand,
     pub npc: Npc,
     //pub npc_ai: Option<NpcAi>,
@@ -119,6 +126,7 @@ impl MonsterBundle {
         team: Team,
         owner: Option<(Entity, &Level)>,
         summon_skill_level: Option<i32>,
+        elite_monster: Option<EliteMonster>,
     ) -> Option<Entity> {
         let npc_data = game_data.npcs.get_npc(npc_id)?;
         let npc_ai = Some(npc_data.ai_file_index)
@@ -128,12 +136,15 @@ impl MonsterBundle {
         let status_effects = StatusEffects::new();
         let status_effects_regen = StatusEffectsRegen::new();
 
-        let ability_values = game_data.ability_value_calculator.calculate_npc(
+        let mut ability_values = game_data.ability_value_calculator.calculate_npc(
             npc_id,
             &status_effects,
             owner.map(|(_, owner_level)| owner_level.level as i32),
             summon_skill_level,
         )?;
+        if let Some(elite_monster) = elite_monster.as_ref() {
+            elite_monster.apply_ability_values(&mut ability_values);
+        }
 
         let damage_sources = Some(ability_values.get_max_damage_sources())
             .filter(|max_damage_sources| *max_damage_sources > 0)
@@ -147,6 +158,7 @@ impl MonsterBundle {
             SpawnOrigin::Summoned(_, spawn_position) => spawn_position,
             SpawnOrigin::MonsterSpawnPoint(_, spawn_position) => spawn_position,
             SpawnOrigin::Quest(_, spawn_position) => spawn_position,
+            SpawnOrigin::Invasion(spawn_position) => spawn_position,
         };
 
         let position = Position::new(
@@ -166,6 +178,7 @@ impl MonsterBundle {
             motion_data: MotionData::from_npc(&game_data.npcs, npc_id),
             move_mode,
             move_speed,
+            movement_impairment: MovementImpairment::new(),
             next_command: NextCommand::default(),
             npc: Npc::new(npc_id, 0),
             object_variables: ObjectVariables::new(MONSTER_OBJECT_VARIABLES_COUNT),
@@ -189,6 +202,10 @@ impl MonsterBundle {
             entity_commands.insert(Owner::new(owner_entity));
         }
 
+        if let Some(elite_monster) = elite_monster {
+            entity_commands.insert(elite_monster);
+        }
+
         client_entity_join_zone(
             commands,
             client_entity_list,
@@ -202,6 +219,14 @@ impl MonsterBundle {
     }
 }
 
+/// Only the owner, or members of the owner's party, can pick up an item drop
+/// until its ownership expires and it becomes free for all.
+pub struct ItemDropOwner {
+    pub entity: Entity,
+    pub party_entity: Option<Entity>,
+    pub duration: Duration,
+}
+
 #[derive(Bundle)]
 pub struct ItemDropBundle {
     pub drop: ItemDrop,
@@ -215,14 +240,20 @@ impl ItemDropBundle {
     pub fn spawn(
         commands: &mut Commands,
         client_entity_list: &mut ClientEntityList,
-        item: DroppedItem,
+        mut item: DroppedItem,
         position: &Position,
-        owner_entity: Option<Entity>,
-        party_owner_entity: Option<Entity>,
+        owner: Option<ItemDropOwner>,
         time: &Time,
     ) -> Option<Entity> {
         let mut rng = rand::thread_rng();
 
+        if let DroppedItem::Item(item) = &mut item {
+            item_assign_instance_id(
+                item,
+                &format!("item drop in zone {}", position.zone_id.get()),
+            );
+        }
+
         let drop_point = Vec3::new(
             position.position.x + rng.gen_range(-ITEM_DROP_RADIUS..=ITEM_DROP_RADIUS) as f32,
             position.position.y + rng.gen_range(-ITEM_DROP_RADIUS..=ITEM_DROP_RADIUS) as f32,
@@ -231,24 +262,27 @@ impl ItemDropBundle {
 
         let drop_position = Position::new(drop_point, position.zone_id);
 
-        let mut entity_commands = commands.spawn(ItemDropBundle {
-            drop: ItemDrop::with_dropped_item(item),
-            position: drop_position.clone(),
-            entity_expire_time: EntityExpireTime::new(
-                time.last_update().unwrap() + ITEM_DROP_ENTITY_EXPIRE_TIME,
-            ),
-        });
+        let mut entity_commands = commands.spawn((
+            ItemDropBundle {
+                drop: ItemDrop::with_dropped_item(item),
+                position: drop_position.clone(),
+                entity_expire_time: EntityExpireTime::new(
+                    time.last_update().unwrap() + ITEM_DROP_ENTITY_EXPIRE_TIME,
+                ),
+            },
+            DefaultExpireTime,
+        ));
         let entity = entity_commands.id();
 
-        if let Some(owner_entity) = owner_entity {
+        if let Some(owner) = owner {
             entity_commands.insert((
-                Owner::new(owner_entity),
-                OwnerExpireTime::new(time.last_update().unwrap() + ITEM_DROP_OWNER_EXPIRE_TIME),
+                Owner::new(owner.entity),
+                OwnerExpireTime::new(time.last_update().unwrap() + owner.duration),
             ));
-        }
 
-        if let Some(party_owner_entity) = party_owner_entity {
-            entity_commands.insert(PartyOwner::new(party_owner_entity));
+            if let Some(party_entity) = owner.party_entity {
+                entity_commands.insert(PartyOwner::new(party_entity));
+            }
         }
 
         client_entity_join_zone(
@@ -0,0 +1,94 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::info;
+
+use rose_data::{EquipmentItem, Item};
+use rose_game_common::components::{Equipment, Inventory};
+
+/// Item instance ids are snowflakes, the milliseconds since 2020-01-01 in the
+/// high bits followed by a sequence number
+const ITEM_INSTANCE_ID_EPOCH_MILLIS: u64 = 1_577_836_800_000;
+const ITEM_INSTANCE_ID_SEQUENCE_BITS: u32 = 22;
+
+static LAST_ITEM_INSTANCE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a new unique item instance id. The timestamp keeps ids unique
+/// across server restarts, and the sequence number within a millisecond.
+pub fn item_instance_id_new() -> u64 {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+        .saturating_sub(ITEM_INSTANCE_ID_EPOCH_MILLIS);
+    let timestamp_id = millis << ITEM_INSTANCE_ID_SEQUENCE_BITS;
+
+    let mut last_id = LAST_ITEM_INSTANCE_ID.load(Ordering::Relaxed);
+    loop {
+        let id = timestamp_id.max(last_id + 1);
+        match LAST_ITEM_INSTANCE_ID.compare_exchange_weak(
+            last_id,
+            id,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => return id,
+            Err(actual) => last_id = actual,
+        }
+    }
+}
+
+/// Assigns an instance id to an equipment item which does not have one yet,
+/// writing the new instance to the economy log with where it came from.
+pub fn item_assign_instance_id(item: &mut Item, source: &str) {
+    if let Some(equipment_item) = item.as_equipment_mut() {
+        equipment_item_assign_instance_id(equipment_item, source);
+    }
+}
+
+fn equipment_item_assign_instance_id(equipment_item: &mut EquipmentItem, source: &str) {
+    if equipment_item.instance_id.is_some() {
+        return;
+    }
+
+    let instance_id = item_instance_id_new();
+    equipment_item.instance_id = Some(instance_id);
+    info!(
+        target: "economy",
+        "Created item instance {} {:?} {} from {}",
+        instance_id,
+        equipment_item.item.item_type,
+        equipment_item.item.item_number,
+        source
+    );
+}
+
+/// Assigns instance ids to the items a character already owned before
+/// instance ids existed.
+pub fn item_assign_character_instance_ids(
+    inventory: &mut Inventory,
+    equipment: &mut Equipment,
+    character_name: &str,
+) {
+    let source = format!("existing item of character {}", character_name);
+    for page in [
+        &mut inventory.equipment,
+        &mut inventory.consumables,
+        &mut inventory.materials,
+        &mut inventory.vehicles,
+    ] {
+        for item in page.slots.iter_mut().flatten() {
+            item_assign_instance_id(item, &source);
+        }
+    }
+
+    for equipment_item in equipment
+        .equipped_items
+        .values_mut()
+        .chain(equipment.equipped_vehicle.values_mut())
+        .flatten()
+    {
+        equipment_item_assign_instance_id(equipment_item, &source);
+    }
+}
@@ -0,0 +1,132 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use rose_data::{Item, ItemReference, MAX_STACKABLE_ITEM_QUANTITY};
+
+use crate::game::{bundles::item_assign_instance_id, GameData};
+
+/// Gems above this number are socketed gem items, below it the gem is an
+/// appraisal option of the item.
+const SOCKET_GEM_MIN: u16 = 300;
+
+#[derive(Debug, Error)]
+pub enum ItemSpawnError {
+    #[error("invalid item type {0}")]
+    InvalidItemType(usize),
+    #[error("invalid item {0:?}")]
+    InvalidItem(ItemReference),
+    #[error("quantity must be between 1 and {0}")]
+    InvalidQuantity(u32),
+    #[error("invalid grade {0}")]
+    InvalidGrade(u8),
+    #[error("invalid gem {0}")]
+    InvalidGem(u16),
+    #[error("gem {0} requires the item to have a socket")]
+    GemWithoutSocket(u16),
+    #[error("only equipment items can have a grade, socket, gem, durability or be bound")]
+    NotEquipment,
+}
+
+/// An item created by a GM with the `/item` chat command or the `spawn-item`
+/// admin command. The item type is the id used by the game data, as typed by
+/// the GM.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ItemSpawn {
+    pub item_type: usize,
+    pub item_number: usize,
+    pub quantity: u32,
+    pub grade: u8,
+    pub has_socket: bool,
+    pub gem: u16,
+    /// Defaults to the durability of the item data
+    pub durability: Option<u8>,
+    pub is_bound: bool,
+}
+
+impl ItemSpawn {
+    /// Creates the item after validating every attribute against the item
+    /// database, the item is given an instance id and written to the economy
+    /// log with who spawned it.
+    pub fn create_item(
+        &self,
+        game_data: &GameData,
+        spawned_by: &str,
+    ) -> Result<Item, ItemSpawnError> {
+        let item_type = game_data
+            .data_decoder
+            .decode_item_type(self.item_type)
+            .ok_or(ItemSpawnError::InvalidItemType(self.item_type))?;
+        let item_reference = ItemReference::new(item_type, self.item_number);
+        let item_data = game_data
+            .items
+            .get_base_item(item_reference)
+            .ok_or(ItemSpawnError::InvalidItem(item_reference))?;
+
+        let mut item = match Item::from_item_data(item_data, self.quantity) {
+            Some(Item::Stackable(stackable_item)) => {
+                if self.quantity > MAX_STACKABLE_ITEM_QUANTITY {
+                    return Err(ItemSpawnError::InvalidQuantity(MAX_STACKABLE_ITEM_QUANTITY));
+                }
+
+                if self.grade != 0
+                    || self.has_socket
+                    || self.gem != 0
+                    || self.durability.is_some()
+                    || self.is_bound
+                {
+                    return Err(ItemSpawnError::NotEquipment);
+                }
+
+                Item::Stackable(stackable_item)
+            }
+            Some(Item::Equipment(mut equipment_item)) => {
+                if self.quantity != 1 {
+                    return Err(ItemSpawnError::InvalidQuantity(1));
+                }
+
+                if game_data.items.get_item_grade(self.grade).is_none() {
+                    return Err(ItemSpawnError::InvalidGrade(self.grade));
+                }
+
+                if self.gem > SOCKET_GEM_MIN {
+                    if game_data.items.get_gem_item(self.gem as usize).is_none() {
+                        return Err(ItemSpawnError::InvalidGem(self.gem));
+                    }
+
+                    if !self.has_socket {
+                        return Err(ItemSpawnError::GemWithoutSocket(self.gem));
+                    }
+                } else if self.gem != 0 {
+                    equipment_item.is_appraised = true;
+                }
+
+                equipment_item.grade = self.grade;
+                equipment_item.has_socket = self.has_socket;
+                equipment_item.gem = self.gem;
+                equipment_item.is_bound = self.is_bound;
+                if let Some(durability) = self.durability {
+                    equipment_item.durability = durability;
+                }
+
+                Item::Equipment(equipment_item)
+            }
+            None => return Err(ItemSpawnError::InvalidQuantity(MAX_STACKABLE_ITEM_QUANTITY)),
+        };
+
+        item_assign_instance_id(&mut item, &format!("GM item spawn by {}", spawned_by));
+        info!(
+            target: "economy",
+            "GM {} spawned item {:?} {} x{} grade {} socket {} gem {} bound {}",
+            spawned_by,
+            item_reference.item_type,
+            item_reference.item_number,
+            item.get_quantity(),
+            self.grade,
+            self.has_socket,
+            self.gem,
+            self.is_bound
+        );
+        Ok(item)
+    }
+}
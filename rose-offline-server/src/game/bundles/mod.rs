@@ -1,16 +1,22 @@
 mod ability_values;
+mod basic_stats;
 mod entity;
+mod item_instance;
+mod item_spawn;
 mod skill_list;
 mod skill_use;
 
 pub use ability_values::{
     ability_values_add_value, ability_values_get_value, ability_values_set_value,
 };
+pub use basic_stats::{basic_stats_reset, basic_stats_send_update, basic_stats_try_increase};
 pub use entity::{
     client_entity_join_zone, client_entity_leave_zone, client_entity_teleport_zone,
-    CharacterBundle, ItemDropBundle, MonsterBundle, NpcBundle, EVENT_OBJECT_VARIABLES_COUNT,
-    MONSTER_OBJECT_VARIABLES_COUNT, NPC_OBJECT_VARIABLES_COUNT,
+    CharacterBundle, ItemDropBundle, ItemDropOwner, MonsterBundle, NpcBundle,
+    EVENT_OBJECT_VARIABLES_COUNT, MONSTER_OBJECT_VARIABLES_COUNT, NPC_OBJECT_VARIABLES_COUNT,
 };
+pub use item_instance::{item_assign_character_instance_ids, item_assign_instance_id};
+pub use item_spawn::{ItemSpawn, ItemSpawnError};
 pub use skill_list::{
     can_learn_skill, can_level_up_skill, skill_list_try_learn_skill, skill_list_try_level_up_skill,
     SkillListBundle,
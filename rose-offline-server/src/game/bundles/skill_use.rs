@@ -12,6 +12,7 @@ use crate::game::{
         ExperiencePoints, HealthPoints, Inventory, ManaPoints, MoveMode, PartyMembership, Stamina,
         Team,
     },
+    resources::ClanWars,
     GameData,
 };
 
@@ -106,6 +107,7 @@ fn check_skill_target_filter(
     skill_caster: &SkillCasterBundleItem,
     skill_target: &SkillTargetBundleItem,
     skill_data: &SkillData,
+    is_clan_war_target: bool,
 ) -> bool {
     let target_is_alive = skill_target.health_points.hp > 0;
     let target_is_caster = skill_caster.entity == skill_target.entity;
@@ -145,11 +147,11 @@ fn check_skill_target_filter(
         SkillTargetFilter::Enemy => {
             target_is_alive
                 && skill_target.team.id != Team::DEFAULT_NPC_TEAM_ID
-                && skill_caster.team.id != skill_target.team.id
+                && (skill_caster.team.id != skill_target.team.id || is_clan_war_target)
         }
         SkillTargetFilter::EnemyCharacter => {
             target_is_alive
-                && skill_caster.team.id != skill_target.team.id
+                && (skill_caster.team.id != skill_target.team.id || is_clan_war_target)
                 && matches!(
                     skill_target.client_entity.entity_type,
                     ClientEntityType::Character
@@ -299,6 +301,32 @@ fn check_equipment(
     false
 }
 
+fn check_ammo(
+    game_data: &GameData,
+    skill_caster: &SkillCasterBundleItem,
+    skill_data: &SkillData,
+) -> bool {
+    let Some(equipment) = skill_caster.equipment else {
+        return true;
+    };
+
+    if !skill_data.skill_type.uses_ammo() {
+        return true;
+    }
+
+    let Some(ammo_index) = equipment
+        .get_equipment_item(EquipmentIndex::Weapon)
+        .and_then(|item| game_data.items.get_base_item(item.item))
+        .and_then(|item_data| item_data.class.weapon_ammo_index())
+    else {
+        return true;
+    };
+
+    equipment
+        .get_ammo_item(ammo_index)
+        .map_or(false, |ammo_item| ammo_item.quantity > 0)
+}
+
 pub fn skill_can_use(
     now: Instant,
     game_data: &GameData,
@@ -338,6 +366,10 @@ pub fn skill_can_use(
         return false;
     }
 
+    if !check_ammo(game_data, skill_caster, skill_data) {
+        return false;
+    }
+
     true
 }
 
@@ -345,8 +377,18 @@ pub fn skill_can_target_entity(
     skill_caster: &SkillCasterBundleItem,
     skill_target: &SkillTargetBundleItem,
     skill_data: &SkillData,
+    clan_wars: &ClanWars,
 ) -> bool {
-    if !check_skill_target_filter(skill_caster, skill_target, skill_data) {
+    let is_clan_war_target = clan_wars.is_at_war(
+        skill_caster
+            .clan_membership
+            .and_then(|clan_membership| clan_membership.clan()),
+        skill_target
+            .clan_membership
+            .and_then(|clan_membership| clan_membership.clan()),
+    );
+
+    if !check_skill_target_filter(skill_caster, skill_target, skill_data, is_clan_war_target) {
         return false;
     }
 
@@ -365,6 +407,7 @@ pub fn skill_can_target_self(skill_caster: &SkillCasterBundleItem, skill_data: &
             team: skill_caster.team,
         },
         skill_data,
+        false,
     ) {
         return false;
     }
@@ -1,12 +1,22 @@
 use bevy::ecs::prelude::Component;
 
-use crate::game::storage::account::AccountStorage;
+use chrono::{DateTime, Utc};
+
+use crate::game::storage::account::{AccountStorage, AccountToken};
 
 #[derive(Component)]
 pub struct Account {
     pub name: String,
     pub password_md5_sha256: String,
     pub character_names: Vec<String>,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub email_verification_token: Option<AccountToken>,
+    pub password_reset_token: Option<AccountToken>,
+    pub secondary_pin_sha256: Option<String>,
+    pub secondary_pin_failures: u32,
+    pub secondary_pin_locked_until: Option<DateTime<Utc>>,
+    pub language: Option<usize>,
 }
 
 impl From<&Account> for AccountStorage {
@@ -15,6 +25,14 @@ impl From<&Account> for AccountStorage {
             name: account.name.clone(),
             password_md5_sha256: account.password_md5_sha256.clone(),
             character_names: account.character_names.clone(),
+            email: account.email.clone(),
+            email_verified: account.email_verified,
+            email_verification_token: account.email_verification_token.clone(),
+            password_reset_token: account.password_reset_token.clone(),
+            secondary_pin_sha256: account.secondary_pin_sha256.clone(),
+            secondary_pin_failures: account.secondary_pin_failures,
+            secondary_pin_locked_until: account.secondary_pin_locked_until,
+            language: account.language,
         }
     }
 }
@@ -25,6 +43,14 @@ impl From<AccountStorage> for Account {
             name: storage.name,
             password_md5_sha256: storage.password_md5_sha256,
             character_names: storage.character_names,
+            email: storage.email,
+            email_verified: storage.email_verified,
+            email_verification_token: storage.email_verification_token,
+            password_reset_token: storage.password_reset_token,
+            secondary_pin_sha256: storage.secondary_pin_sha256,
+            secondary_pin_failures: storage.secondary_pin_failures,
+            secondary_pin_locked_until: storage.secondary_pin_locked_until,
+            language: storage.language,
         }
     }
 }
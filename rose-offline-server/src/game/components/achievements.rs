@@ -0,0 +1,52 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::ecs::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// The achievements of a character, by the id of the achievement in the
+/// achievements config.
+#[derive(Component, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Achievements {
+    /// Progress towards the achievements which are not yet unlocked
+    #[serde(default)]
+    pub progress: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub unlocked: BTreeSet<String>,
+
+    /// The achievement whose title is equipped
+    #[serde(default)]
+    pub equipped_title: Option<String>,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    pub fn get_progress(&self, id: &str) -> u32 {
+        self.progress.get(id).copied().unwrap_or(0)
+    }
+
+    /// Returns true if the achievement was unlocked, or false if it was
+    /// already unlocked.
+    pub fn unlock(&mut self, id: &str) -> bool {
+        self.progress.remove(id);
+        self.unlocked.insert(id.to_string())
+    }
+
+    /// Adds to the progress of an achievement, returning true if it reached
+    /// the required progress and was unlocked.
+    pub fn add_progress(&mut self, id: &str, amount: u32, required_progress: u32) -> bool {
+        if self.is_unlocked(id) {
+            return false;
+        }
+
+        let progress = self.progress.entry(id.to_string()).or_insert(0);
+        *progress = progress.saturating_add(amount);
+        if *progress >= required_progress {
+            self.unlock(id)
+        } else {
+            false
+        }
+    }
+}
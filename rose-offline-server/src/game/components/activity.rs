@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+use bevy::ecs::prelude::Component;
+
+/// Tracks when the player of a connected character last sent a message, used
+/// to detect players who are AFK.
+#[derive(Component)]
+pub struct Activity {
+    pub last_activity: Instant,
+
+    /// When the player was warned they will be disconnected for being AFK
+    pub afk_warned_time: Option<Instant>,
+}
+
+impl Activity {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_activity: now,
+            afk_warned_time: None,
+        }
+    }
+
+    pub fn record_activity(&mut self, now: Instant) {
+        self.last_activity = now;
+        self.afk_warned_time = None;
+    }
+}
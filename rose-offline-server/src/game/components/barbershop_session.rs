@@ -0,0 +1,18 @@
+use std::time::Instant;
+
+use bevy::ecs::prelude::{Component, Entity};
+
+use rose_data::ZoneId;
+
+use crate::game::components::Money;
+
+/// A pending appearance change which is only applied once the client confirms it
+#[derive(Component)]
+pub struct BarbershopSession {
+    pub npc_entity: Entity,
+    pub zone_id: ZoneId,
+    pub face: u8,
+    pub hair: u8,
+    pub price: Money,
+    pub expire_time: Instant,
+}
@@ -1,8 +1,19 @@
 use bevy::prelude::{Component, Deref, DerefMut};
 
-use crate::game::storage::character::CharacterStorage;
+use crate::game::{messages::server::CharacterListItem, storage::character::CharacterStorage};
 
 #[derive(Component, Default, Deref, DerefMut)]
 pub struct CharacterList {
-    pub characters: Vec<CharacterStorage>,
+    pub characters: Vec<CharacterListItem>,
+}
+
+impl From<&CharacterStorage> for CharacterListItem {
+    fn from(character: &CharacterStorage) -> Self {
+        Self {
+            info: character.info.clone(),
+            level: character.level,
+            delete_time: character.delete_time,
+            equipment: character.equipment.clone(),
+        }
+    }
 }
@@ -53,6 +53,7 @@ pub struct Clan {
     pub unique_id: ClanUniqueId,
     pub name: String,
     pub description: String,
+    pub notice: String,
     pub money: Money,
     pub points: ClanPoints,
     pub level: ClanLevel,
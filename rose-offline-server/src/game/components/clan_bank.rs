@@ -0,0 +1,95 @@
+use bevy::ecs::prelude::Component;
+
+use rose_data::{ClanMemberPosition, Item};
+use rose_game_common::messages::server::ClanBankLogEntry;
+
+use crate::game::storage::clan_bank::ClanBankStorage;
+
+pub const CLAN_BANK_MAX_SLOTS: usize = 60;
+pub const CLAN_BANK_MAX_LOG_ENTRIES: usize = 200;
+
+#[derive(Component)]
+pub struct ClanBank {
+    pub slots: Vec<Option<Item>>,
+    pub log: Vec<ClanBankLogEntry>,
+}
+
+impl Default for ClanBank {
+    fn default() -> Self {
+        Self {
+            slots: vec![None; CLAN_BANK_MAX_SLOTS],
+            log: Vec::new(),
+        }
+    }
+}
+
+impl From<&ClanBank> for ClanBankStorage {
+    fn from(clan_bank: &ClanBank) -> Self {
+        Self {
+            slots: clan_bank.slots.clone(),
+            log: clan_bank.log.clone(),
+        }
+    }
+}
+
+impl From<ClanBankStorage> for ClanBank {
+    fn from(storage: ClanBankStorage) -> Self {
+        let mut slots = storage.slots;
+        slots.resize(CLAN_BANK_MAX_SLOTS, None);
+
+        Self {
+            slots,
+            log: storage.log,
+        }
+    }
+}
+
+impl ClanBank {
+    pub fn can_deposit(position: ClanMemberPosition) -> bool {
+        !matches!(position, ClanMemberPosition::Penalty)
+    }
+
+    pub fn can_withdraw(position: ClanMemberPosition) -> bool {
+        matches!(
+            position,
+            ClanMemberPosition::Commander
+                | ClanMemberPosition::DeputyMaster
+                | ClanMemberPosition::Master
+        )
+    }
+
+    pub fn try_add_item(&mut self, item: Item) -> Result<(usize, &Item), Item> {
+        // First try find an existing item slot we can stack with, else the first empty slot
+        let stack_index = match &item {
+            Item::Stackable(stackable) => self.slots.iter().position(|slot| {
+                slot.as_ref().map_or(false, |slot_item| {
+                    slot_item.can_stack_with(stackable).is_ok()
+                })
+            }),
+            Item::Equipment(_) => None,
+        };
+
+        let Some(index) = stack_index.or_else(|| self.slots.iter().position(|slot| slot.is_none()))
+        else {
+            return Err(item);
+        };
+
+        match self.slots[index].as_mut() {
+            Some(slot_item) => slot_item
+                .try_stack_with_item(item)
+                .expect("how did we get here"),
+            None => self.slots[index] = Some(item),
+        }
+
+        Ok((index, self.slots[index].as_ref().unwrap()))
+    }
+
+    pub fn add_log_entry(&mut self, entry: ClanBankLogEntry) {
+        self.log.push(entry);
+
+        if self.log.len() > CLAN_BANK_MAX_LOG_ENTRIES {
+            let remove_count = self.log.len() - CLAN_BANK_MAX_LOG_ENTRIES;
+            self.log.drain(..remove_count);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use bevy::ecs::prelude::Component;
+use serde::{Deserialize, Serialize};
 
 use rose_data::ZoneId;
 
 pub use rose_game_common::messages::ClientEntityId;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ClientEntityType {
     Character,
     Monster,
@@ -1,10 +1,16 @@
-use bevy::prelude::{Component, Deref, DerefMut};
+use std::ops::{Deref, DerefMut};
+
+use bevy::{math::Vec3, prelude::Component};
 
 use crate::game::resources::ClientEntitySet;
 
-#[derive(Component, Default, Deref, DerefMut)]
+#[derive(Component, Default)]
 pub struct ClientEntityVisibility {
     pub entities: ClientEntitySet,
+
+    /// The zone visibility version and client position when visibility was
+    /// last updated, or None when it must be updated
+    pub last_update: Option<(u64, Vec3)>,
 }
 
 impl ClientEntityVisibility {
@@ -12,3 +18,17 @@ impl ClientEntityVisibility {
         Default::default()
     }
 }
+
+impl Deref for ClientEntityVisibility {
+    type Target = ClientEntitySet;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entities
+    }
+}
+
+impl DerefMut for ClientEntityVisibility {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.entities
+    }
+}
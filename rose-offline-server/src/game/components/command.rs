@@ -82,6 +82,10 @@ pub struct Command {
 
     // The duration required to complete this command, if None then the command is immediately interruptible
     pub required_duration: Option<Duration>,
+
+    // How long into an attack command its damage is applied, before scaling by attack speed.
+    // None once the damage has been applied or the attack was interrupted.
+    pub attack_hit_duration: Option<Duration>,
 }
 
 impl Default for Command {
@@ -96,6 +100,7 @@ impl Command {
             command,
             duration: Duration::new(0, 0),
             required_duration,
+            attack_hit_duration: None,
         }
     }
 
@@ -178,8 +183,11 @@ impl Command {
         )
     }
 
-    pub fn with_attack(target: Entity, duration: Duration) -> Self {
-        Self::new(CommandData::Attack { target }, Some(duration))
+    pub fn with_attack(target: Entity, duration: Duration, hit_duration: Duration) -> Self {
+        Self {
+            attack_hit_duration: Some(hit_duration),
+            ..Self::new(CommandData::Attack { target }, Some(duration))
+        }
     }
 
     pub fn with_pickup_item_drop(target: Entity, duration: Duration) -> Self {
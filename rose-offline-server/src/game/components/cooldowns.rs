@@ -11,4 +11,7 @@ pub struct Cooldowns {
     pub skill: HashMap<SkillId, Instant>,
     pub skill_global: Option<Instant>,
     pub skill_group: [Option<Instant>; MAX_SKILL_COOLDOWN_GROUPS],
+
+    /// When items of each cooldown type can next be used
+    pub item: HashMap<usize, Instant>,
 }
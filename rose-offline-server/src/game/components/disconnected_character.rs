@@ -0,0 +1,16 @@
+use std::time::Instant;
+
+use bevy::ecs::prelude::Component;
+
+/// Marks a character whose game client has disconnected, the character stays
+/// in the world until its player reconnects or the grace period expires.
+#[derive(Component)]
+pub struct DisconnectedCharacter {
+    pub expire_time: Instant,
+}
+
+impl DisconnectedCharacter {
+    pub fn new(expire_time: Instant) -> Self {
+        Self { expire_time }
+    }
+}
@@ -0,0 +1,25 @@
+use bevy::ecs::prelude::Component;
+
+use rose_game_common::components::AbilityValues;
+
+/// A monster which has been promoted to an elite variant when spawned.
+#[derive(Component, Clone, Debug)]
+pub struct EliteMonster {
+    pub health_multiplier: f32,
+    pub attack_multiplier: f32,
+    pub defence_multiplier: f32,
+    pub experience_multiplier: f32,
+    pub drop_count: usize,
+}
+
+impl EliteMonster {
+    pub fn apply_ability_values(&self, ability_values: &mut AbilityValues) {
+        ability_values.max_health =
+            (ability_values.max_health as f32 * self.health_multiplier) as i32;
+        ability_values.attack_power =
+            (ability_values.attack_power as f32 * self.attack_multiplier) as i32;
+        ability_values.defence = (ability_values.defence as f32 * self.defence_multiplier) as i32;
+        ability_values.resistance =
+            (ability_values.resistance as f32 * self.defence_multiplier) as i32;
+    }
+}
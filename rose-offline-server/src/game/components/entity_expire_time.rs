@@ -12,3 +12,8 @@ impl EntityExpireTime {
         Self { when }
     }
 }
+
+/// Marks an entity which has the default expire time of its bundle, so it can
+/// be replaced once without changing an expire time which was already set
+#[derive(Component)]
+pub struct DefaultExpireTime;
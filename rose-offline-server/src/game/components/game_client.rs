@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use bevy::{ecs::prelude::Component, prelude::Entity};
 use crossbeam_channel::Receiver;
 use tokio::sync::mpsc::UnboundedSender;
@@ -10,18 +12,28 @@ pub struct GameClient {
     pub server_message_tx: UnboundedSender<ServerMessage>,
     pub login_token: u32,
     pub world_client_entity: Option<Entity>,
+
+    /// The address the client connects from
+    pub ip: IpAddr,
+
+    /// The login token bound to the packet codec seed of this connection
+    pub packet_codec_login_token: Option<u32>,
 }
 
 impl GameClient {
     pub fn new(
         client_message_rx: Receiver<ClientMessage>,
         server_message_tx: UnboundedSender<ServerMessage>,
+        ip: IpAddr,
+        packet_codec_login_token: Option<u32>,
     ) -> Self {
         Self {
             client_message_rx,
             server_message_tx,
             login_token: 0u32,
             world_client_entity: None,
+            ip,
+            packet_codec_login_token,
         }
     }
 }
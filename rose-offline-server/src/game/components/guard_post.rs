@@ -0,0 +1,13 @@
+use bevy::{ecs::prelude::Component, math::Vec3};
+
+/// Where a guard npc returns to once it has no target.
+#[derive(Component)]
+pub struct GuardPost {
+    pub position: Vec3,
+}
+
+impl GuardPost {
+    pub fn new(position: Vec3) -> Self {
+        Self { position }
+    }
+}
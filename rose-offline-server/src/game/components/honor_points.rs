@@ -0,0 +1,16 @@
+use bevy::ecs::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Earned by winning event zones and repelling invasions, and spent in NPC
+/// store tabs which are priced in honor points.
+#[derive(Component, Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct HonorPoints {
+    #[serde(default)]
+    pub points: u32,
+}
+
+impl HonorPoints {
+    pub fn add(&mut self, points: u32) {
+        self.points = self.points.saturating_add(points);
+    }
+}
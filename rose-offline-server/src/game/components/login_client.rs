@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use bevy::ecs::prelude::Component;
 use crossbeam_channel::Receiver;
 use tokio::sync::mpsc::UnboundedSender;
@@ -9,17 +11,27 @@ pub struct LoginClient {
     pub client_message_rx: Receiver<ClientMessage>,
     pub server_message_tx: UnboundedSender<ServerMessage>,
     pub login_token: u32,
+
+    /// The country the client connects from, if it is known
+    pub country: Option<String>,
+
+    /// The address the client connects from
+    pub ip: IpAddr,
 }
 
 impl LoginClient {
     pub fn new(
         client_message_rx: Receiver<ClientMessage>,
         server_message_tx: UnboundedSender<ServerMessage>,
+        ip: IpAddr,
+        country: Option<String>,
     ) -> Self {
         Self {
             client_message_rx,
             server_message_tx,
             login_token: 0u32,
+            country,
+            ip,
         }
     }
 }
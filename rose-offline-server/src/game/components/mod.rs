@@ -1,7 +1,11 @@
 mod account;
+mod achievements;
+mod activity;
 mod bank;
+mod barbershop_session;
 mod character_list;
 mod clan;
+mod clan_bank;
 mod client_entity;
 mod client_entity_sector;
 mod client_entity_visibility;
@@ -9,29 +13,46 @@ mod command;
 mod cooldowns;
 mod damage_sources;
 mod dead;
+mod disconnected_character;
 mod driving_time;
+mod elite_monster;
 mod entity_expire_time;
 mod event_object;
 mod game_client;
+mod guard_post;
+mod honor_points;
 mod login_client;
 mod monster_spawn_point;
 mod motion_data;
+mod move_path;
+mod movement_impairment;
 mod next_command;
 mod npc_ai;
 mod npc_standing_direction;
 mod object_variables;
+mod offline_vendor;
 mod owner;
 mod owner_expire_time;
 mod party;
+mod party_map_marker_hidden;
 mod party_membership;
 mod party_owner;
 mod passive_recovery_time;
+mod persistent_item_drop;
 mod personal_store;
+mod pk_flag;
 mod position;
+mod position_history;
+mod rebirth;
+mod reward_calendar;
 mod server_info;
 mod spawn_origin;
+mod spectator;
+mod statistics;
+mod teleport_gate;
 mod weight;
 mod world_client;
+mod zone_transition;
 
 pub use rose_game_common::components::{
     AbilityValues, ActiveQuest, ActiveStatusEffect, ActiveStatusEffectRegen, BasicStatType,
@@ -44,9 +65,13 @@ pub use rose_game_common::components::{
 };
 
 pub use account::Account;
+pub use achievements::Achievements;
+pub use activity::Activity;
 pub use bank::Bank;
+pub use barbershop_session::BarbershopSession;
 pub use character_list::CharacterList;
 pub use clan::{Clan, ClanMember, ClanMembership};
+pub use clan_bank::ClanBank;
 pub use client_entity::{ClientEntity, ClientEntityId, ClientEntityType};
 pub use client_entity_sector::ClientEntitySector;
 pub use client_entity_visibility::ClientEntityVisibility;
@@ -54,26 +79,46 @@ pub use command::{Command, CommandCastSkillTarget, CommandData};
 pub use cooldowns::Cooldowns;
 pub use damage_sources::{DamageSource, DamageSources};
 pub use dead::Dead;
+pub use disconnected_character::DisconnectedCharacter;
 pub use driving_time::DrivingTime;
-pub use entity_expire_time::EntityExpireTime;
+pub use elite_monster::EliteMonster;
+pub use entity_expire_time::{DefaultExpireTime, EntityExpireTime};
 pub use event_object::EventObject;
 pub use game_client::GameClient;
+pub use guard_post::GuardPost;
+pub use honor_points::HonorPoints;
 pub use login_client::LoginClient;
 pub use monster_spawn_point::MonsterSpawnPoint;
 pub use motion_data::{MotionData, MotionDataCharacter, MotionDataNpc};
+pub use move_path::MovePath;
+pub use movement_impairment::{MovementImpairment, MovementImpairmentType};
 pub use next_command::NextCommand;
 pub use npc_ai::NpcAi;
 pub use npc_standing_direction::NpcStandingDirection;
 pub use object_variables::ObjectVariables;
+pub use offline_vendor::{OfflineVendor, OfflineVendorProceeds};
 pub use owner::Owner;
 pub use owner_expire_time::OwnerExpireTime;
-pub use party::{Party, PartyMember};
+pub use party::{Party, PartyMember, PartyUniqueId};
+pub use party_map_marker_hidden::PartyMapMarkerHidden;
 pub use party_membership::PartyMembership;
 pub use party_owner::PartyOwner;
 pub use passive_recovery_time::PassiveRecoveryTime;
-pub use personal_store::{PersonalStore, PERSONAL_STORE_ITEM_SLOTS};
+pub use persistent_item_drop::PersistentItemDrop;
+pub use personal_store::{
+    PersonalStore, PERSONAL_STORE_ITEM_SLOTS, PERSONAL_STORE_MAX_PRICE,
+    PERSONAL_STORE_MAX_TITLE_LENGTH,
+};
+pub use pk_flag::PkFlag;
 pub use position::Position;
+pub use position_history::PositionHistory;
+pub use rebirth::Rebirth;
+pub use reward_calendar::RewardCalendar;
 pub use server_info::ServerInfo;
 pub use spawn_origin::SpawnOrigin;
+pub use spectator::Spectator;
+pub use statistics::Statistics;
+pub use teleport_gate::TeleportGate;
 pub use weight::Weight;
 pub use world_client::WorldClient;
+pub use zone_transition::{ZoneTransition, ZoneTransitionSummon};
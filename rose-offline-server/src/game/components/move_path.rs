@@ -0,0 +1,35 @@
+use bevy::ecs::prelude::Component;
+use bevy::math::{Vec2, Vec3};
+
+use rose_data::ZoneId;
+
+/// The path an entity is following to reach the destination of its move
+/// command, waypoints are removed as they are reached and once empty the
+/// entity moves directly to the destination.
+#[derive(Component, Clone, Debug)]
+pub struct MovePath {
+    pub zone_id: ZoneId,
+    pub destination: Vec3,
+    pub waypoints: Vec<Vec2>,
+}
+
+impl MovePath {
+    pub fn new(zone_id: ZoneId, destination: Vec3, mut waypoints: Vec<Vec2>) -> Self {
+        // The final waypoint is the destination itself
+        waypoints.pop();
+        waypoints.reverse();
+
+        Self {
+            zone_id,
+            destination,
+            waypoints,
+        }
+    }
+
+    /// Returns the next position to move towards.
+    pub fn get_next_waypoint(&self) -> Vec3 {
+        self.waypoints.last().map_or(self.destination, |waypoint| {
+            Vec3::new(waypoint.x, waypoint.y, self.destination.z)
+        })
+    }
+}
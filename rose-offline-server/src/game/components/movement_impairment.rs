@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use bevy::ecs::prelude::Component;
+use enum_map::{Enum, EnumMap};
+
+/// How long after an impairment is applied that another of the same type has
+/// a reduced effect
+const DIMINISHING_RETURNS_DURATION: Duration = Duration::from_secs(15);
+
+/// The effect of each consecutive impairment of the same type within
+/// `DIMINISHING_RETURNS_DURATION`, after which the entity is immune
+const DIMINISHING_RETURNS_MULTIPLIERS: [f32; 3] = [1.0, 0.5, 0.25];
+
+#[derive(Copy, Clone, Debug, Enum, PartialEq, Eq)]
+pub enum MovementImpairmentType {
+    Stun,
+    Root,
+    Knockback,
+}
+
+#[derive(Copy, Clone)]
+struct DiminishingReturns {
+    count: usize,
+    reset_time: Instant,
+}
+
+/// Stuns and roots which are not represented by a status effect, and the
+/// diminishing returns of every movement impairing effect.
+#[derive(Component, Default)]
+pub struct MovementImpairment {
+    pub stunned_until: Option<Instant>,
+    pub rooted_until: Option<Instant>,
+    diminishing_returns: EnumMap<MovementImpairmentType, Option<DiminishingReturns>>,
+}
+
+impl MovementImpairment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_stunned(&self, now: Instant) -> bool {
+        self.stunned_until
+            .map_or(false, |stunned_until| now < stunned_until)
+    }
+
+    /// Stunned entities are also unable to move.
+    pub fn is_rooted(&self, now: Instant) -> bool {
+        self.is_stunned(now)
+            || self
+                .rooted_until
+                .map_or(false, |rooted_until| now < rooted_until)
+    }
+
+    /// Records an impairment being applied, returning the multiplier for its
+    /// duration or distance, which is 0 when the entity is immune.
+    pub fn apply_diminishing_returns(
+        &mut self,
+        impairment_type: MovementImpairmentType,
+        now: Instant,
+    ) -> f32 {
+        let count = self.diminishing_returns[impairment_type]
+            .filter(|diminishing_returns| now < diminishing_returns.reset_time)
+            .map_or(0, |diminishing_returns| diminishing_returns.count);
+
+        let Some(multiplier) = DIMINISHING_RETURNS_MULTIPLIERS.get(count) else {
+            return 0.0;
+        };
+
+        self.diminishing_returns[impairment_type] = Some(DiminishingReturns {
+            count: count + 1,
+            reset_time: now + DIMINISHING_RETURNS_DURATION,
+        });
+        *multiplier
+    }
+
+    pub fn stun(&mut self, until: Instant) {
+        self.stunned_until = self.stunned_until.max(Some(until));
+    }
+
+    pub fn root(&mut self, until: Instant) {
+        self.rooted_until = self.rooted_until.max(Some(until));
+    }
+}
@@ -0,0 +1,39 @@
+use std::time::Instant;
+
+use bevy::ecs::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+use crate::game::components::Money;
+
+/// The sales made by a personal store whilst its owner was offline, which are
+/// reported to the owner when they next join the game.
+#[derive(Component, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct OfflineVendorProceeds {
+    pub items_sold: u32,
+    pub money: Money,
+}
+
+impl OfflineVendorProceeds {
+    pub fn add_sale(&mut self, quantity: u32, money: Money) {
+        self.items_sold = self.items_sold.saturating_add(quantity);
+        self.money = Money(self.money.0.saturating_add(money.0));
+    }
+}
+
+/// Marks a character whose personal store stays open after its game client
+/// disconnected, until the owner joins the game with it again or the store
+/// expires.
+#[derive(Component)]
+pub struct OfflineVendor {
+    pub expire_time: Instant,
+    pub proceeds: OfflineVendorProceeds,
+}
+
+impl OfflineVendor {
+    pub fn new(expire_time: Instant, proceeds: OfflineVendorProceeds) -> Self {
+        Self {
+            expire_time,
+            proceeds,
+        }
+    }
+}
@@ -7,7 +7,9 @@ use rose_game_common::{
     messages::{PartyItemSharing, PartyXpSharing},
 };
 
-use crate::game::components::CharacterUniqueId;
+use crate::game::{components::CharacterUniqueId, storage::party::PartyStorage};
+
+pub type PartyUniqueId = u32;
 
 #[derive(Clone)]
 pub enum PartyMember {
@@ -26,6 +28,7 @@ impl PartyMember {
 
 #[derive(Component)]
 pub struct Party {
+    pub unique_id: PartyUniqueId,
     pub owner: Entity,
     pub members: ArrayVec<PartyMember, 5>,
     pub item_sharing: PartyItemSharing,
@@ -37,7 +40,7 @@ pub struct Party {
 }
 
 impl Party {
-    pub fn new(owner: Entity, party_members: &[PartyMember]) -> Self {
+    pub fn new(unique_id: PartyUniqueId, owner: Entity, party_members: &[PartyMember]) -> Self {
         let mut members = ArrayVec::new();
 
         for member in party_members {
@@ -45,6 +48,7 @@ impl Party {
         }
 
         Self {
+            unique_id,
             owner,
             members,
             item_sharing: PartyItemSharing::EqualLootDistribution,
@@ -58,3 +62,19 @@ impl Party {
         }
     }
 }
+
+impl From<PartyStorage> for Party {
+    fn from(storage: PartyStorage) -> Self {
+        let members: Vec<PartyMember> = storage
+            .members
+            .into_iter()
+            .map(|member| PartyMember::Offline(member.character_id, member.name))
+            .collect();
+
+        // All members are offline, the owner is assigned when the first member reconnects
+        let mut party = Party::new(storage.unique_id, Entity::PLACEHOLDER, &members);
+        party.item_sharing = storage.item_sharing;
+        party.xp_sharing = storage.xp_sharing;
+        party
+    }
+}
@@ -0,0 +1,5 @@
+use bevy::ecs::prelude::Component;
+
+/// Marks a character whose position should not be shared with their party's world map
+#[derive(Component, Default)]
+pub struct PartyMapMarkerHidden;
@@ -0,0 +1,5 @@
+use bevy::ecs::prelude::Component;
+
+/// Marks an item drop which is saved to storage so it survives a server restart
+#[derive(Component, Default)]
+pub struct PersistentItemDrop;
@@ -5,6 +5,10 @@ use rose_data::Item;
 use crate::game::components::{ItemSlot, Money};
 
 pub const PERSONAL_STORE_ITEM_SLOTS: usize = 30;
+pub const PERSONAL_STORE_MAX_TITLE_LENGTH: usize = 64;
+
+// The irose protocol sends personal store prices as u32
+pub const PERSONAL_STORE_MAX_PRICE: Money = Money(u32::MAX as i64);
 
 #[derive(Clone, Component)]
 pub struct PersonalStore {
@@ -42,4 +46,22 @@ impl PersonalStore {
 
         Err(PersonalStoreError::Full)
     }
+
+    pub fn add_buy_item(&mut self, item: Item, price: Money) -> Result<(), PersonalStoreError> {
+        for slot in self.buy_items.iter_mut() {
+            if slot.is_none() {
+                *slot = Some((item, price));
+                return Ok(());
+            }
+        }
+
+        Err(PersonalStoreError::Full)
+    }
+
+    pub fn is_item_slot_reserved(&self, item_slot: ItemSlot) -> bool {
+        self.sell_items
+            .iter()
+            .flatten()
+            .any(|(sell_item_slot, _)| *sell_item_slot == item_slot)
+    }
 }
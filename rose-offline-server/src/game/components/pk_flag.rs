@@ -0,0 +1,16 @@
+use std::time::Instant;
+
+use bevy::ecs::prelude::Component;
+
+/// Set on a character which killed an ordinary player, guards attack
+/// characters with a pk flag until it expires.
+#[derive(Component)]
+pub struct PkFlag {
+    pub expire_time: Instant,
+}
+
+impl PkFlag {
+    pub fn new(expire_time: Instant) -> Self {
+        Self { expire_time }
+    }
+}
@@ -0,0 +1,51 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    ecs::prelude::Component,
+    math::{Vec2, Vec3, Vec3Swizzles},
+};
+
+use rose_data::ZoneId;
+
+/// The recent positions of an entity, used to compensate for client latency
+/// when checking if an entity is within range of an attack or skill.
+#[derive(Component, Default)]
+pub struct PositionHistory {
+    zone_id: Option<ZoneId>,
+    positions: VecDeque<(Instant, Vec3)>,
+}
+
+impl PositionHistory {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records the entity position at `time` and removes any positions older than `max_age`
+    pub fn push(&mut self, time: Instant, position: Vec3, zone_id: ZoneId, max_age: Duration) {
+        if self.zone_id != Some(zone_id) {
+            self.zone_id = Some(zone_id);
+            self.positions.clear();
+        }
+
+        while self.positions.front().map_or(false, |(front_time, _)| {
+            time.saturating_duration_since(*front_time) > max_age
+        }) {
+            self.positions.pop_front();
+        }
+
+        self.positions.push_back((time, position));
+    }
+
+    /// Returns the closest distance between `from` and the entity's `current`
+    /// position or any position it has been at since `since`.
+    pub fn get_min_distance_xy(&self, from: Vec2, current: Vec3, since: Instant) -> f32 {
+        self.positions
+            .iter()
+            .filter(|(time, _)| *time >= since)
+            .map(|(_, position)| from.distance(position.xy()))
+            .fold(from.distance(current.xy()), f32::min)
+    }
+}
@@ -0,0 +1,10 @@
+use bevy::ecs::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// How many times a character has been reborn at level 1 after reaching the
+/// level cap.
+#[derive(Component, Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Rebirth {
+    #[serde(default)]
+    pub count: u32,
+}
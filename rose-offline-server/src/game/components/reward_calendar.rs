@@ -0,0 +1,45 @@
+use bevy::ecs::prelude::Component;
+
+use crate::game::storage::reward_calendar::RewardCalendarStorage;
+
+#[derive(Component, Default)]
+pub struct RewardCalendar {
+    pub last_claim_day: Option<i32>,
+    pub streak: u32,
+    pub prompted: bool,
+}
+
+impl From<&RewardCalendar> for RewardCalendarStorage {
+    fn from(reward_calendar: &RewardCalendar) -> Self {
+        Self {
+            last_claim_day: reward_calendar.last_claim_day,
+            streak: reward_calendar.streak,
+        }
+    }
+}
+
+impl From<RewardCalendarStorage> for RewardCalendar {
+    fn from(storage: RewardCalendarStorage) -> Self {
+        Self {
+            last_claim_day: storage.last_claim_day,
+            streak: storage.streak,
+            prompted: false,
+        }
+    }
+}
+
+impl RewardCalendar {
+    pub fn can_claim(&self, today: i32) -> bool {
+        self.last_claim_day.map_or(true, |day| day < today)
+    }
+
+    /// Returns the streak the account would have if it claimed a reward today,
+    /// a streak is only continued when the previous claim was yesterday.
+    pub fn get_claim_streak(&self, today: i32) -> u32 {
+        if self.last_claim_day == Some(today - 1) {
+            self.streak.saturating_add(1)
+        } else {
+            1
+        }
+    }
+}
@@ -6,4 +6,8 @@ pub enum SpawnOrigin {
     Summoned(Entity, Vec3),
     MonsterSpawnPoint(Entity, Vec3),
     Quest(Entity, Vec3),
+
+    /// Invading monsters and the guards defending against them, the position
+    /// is where the monster returns to when idle
+    Invasion(Vec3),
 }
@@ -0,0 +1,8 @@
+use bevy::ecs::prelude::Component;
+
+/// Marks a GM who is hidden from other clients and ignored by monsters
+#[derive(Component, Default)]
+pub struct Spectator {
+    /// The name of the character the spectator follows, if any
+    pub follow_character: Option<String>,
+}
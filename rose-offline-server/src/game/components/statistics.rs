@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+
+use bevy::ecs::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+use rose_data::NpcId;
+
+/// Lifetime statistics of a character, used for the server leaderboards.
+#[derive(Component, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Statistics {
+    /// The number of kills of each monster, by npc id
+    #[serde(default)]
+    pub monster_kills: BTreeMap<u16, u32>,
+    #[serde(default)]
+    pub pvp_kills: u32,
+    #[serde(default)]
+    pub deaths: u32,
+    #[serde(default)]
+    pub playtime_secs: u64,
+
+    /// The playtime of the current session, or of the last session whilst
+    /// the character is offline
+    #[serde(default)]
+    pub session_playtime_secs: u64,
+    #[serde(default)]
+    pub longest_session_playtime_secs: u64,
+
+    /// The total zuly earned from drops, quests and selling items
+    #[serde(default)]
+    pub money_earned: u64,
+}
+
+impl Statistics {
+    pub fn add_monster_kill(&mut self, npc_id: NpcId) {
+        let kills = self.monster_kills.entry(npc_id.get()).or_insert(0);
+        *kills = kills.saturating_add(1);
+    }
+
+    pub fn get_monster_kills(&self, npc_id: NpcId) -> u32 {
+        self.monster_kills.get(&npc_id.get()).copied().unwrap_or(0)
+    }
+
+    pub fn start_session(&mut self) {
+        self.session_playtime_secs = 0;
+    }
+
+    pub fn add_playtime(&mut self, secs: u64) {
+        self.playtime_secs = self.playtime_secs.saturating_add(secs);
+        self.session_playtime_secs = self.session_playtime_secs.saturating_add(secs);
+        self.longest_session_playtime_secs = self
+            .longest_session_playtime_secs
+            .max(self.session_playtime_secs);
+    }
+
+    pub fn total_monster_kills(&self) -> u64 {
+        self.monster_kills.values().map(|&kills| kills as u64).sum()
+    }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashSet;
+
+use bevy::ecs::prelude::{Component, Entity};
+
+use crate::game::components::Position;
+
+/// A temporary gate which teleports any character which walks within radius
+/// of it to destination.
+#[derive(Component, Clone, Debug)]
+pub struct TeleportGate {
+    pub destination: Position,
+    pub radius: f32,
+
+    /// Characters which were within radius on the last update, only characters
+    /// which enter the gate are teleported so the characters standing on it
+    /// when it was created are not.
+    pub occupants: HashSet<Entity>,
+}
+
+impl TeleportGate {
+    pub fn new(destination: Position, radius: f32) -> Self {
+        Self {
+            destination,
+            radius,
+            occupants: HashSet::new(),
+        }
+    }
+}
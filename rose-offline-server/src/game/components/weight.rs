@@ -1,12 +1,55 @@
 use bevy::ecs::prelude::Component;
 
+use crate::game::components::{AbilityValues, MoveMode};
+
+// Weight rate is the current weight as a percentage of max weight
+pub const WEIGHT_RATE_WALK: u32 = 100;
+pub const WEIGHT_RATE_CANNOT_ATTACK: u32 = 110;
+
+pub const WEIGHT_LIMITED_VEHICLE_SPEED: f32 = 300.0;
+
 #[derive(Component)]
 pub struct Weight {
     pub weight: u32,
+    pub weight_rate: u32,
 }
 
 impl Weight {
-    pub fn new(weight: u32) -> Self {
-        Self { weight }
+    pub fn new(weight: u32, max_weight: i32) -> Self {
+        let weight_rate = if max_weight > 0 {
+            (weight as u64 * 100 / max_weight as u64) as u32
+        } else {
+            0
+        };
+
+        Self {
+            weight,
+            weight_rate,
+        }
+    }
+
+    pub fn is_walk_only(&self) -> bool {
+        self.weight_rate >= WEIGHT_RATE_WALK
+    }
+
+    pub fn can_attack(&self) -> bool {
+        self.weight_rate < WEIGHT_RATE_CANNOT_ATTACK
+    }
+
+    /// The weight rate as sent to the client, which uses it to display the over weight warnings
+    pub fn get_client_weight_rate(&self) -> u8 {
+        self.weight_rate.min(u8::MAX as u32) as u8
+    }
+
+    pub fn get_move_speed(&self, ability_values: &AbilityValues, move_mode: &MoveMode) -> f32 {
+        let move_speed = ability_values.get_move_speed(move_mode);
+        if !self.is_walk_only() {
+            return move_speed;
+        }
+
+        match move_mode {
+            MoveMode::Walk | MoveMode::Run => move_speed.min(ability_values.get_walk_speed()),
+            MoveMode::Drive => move_speed.min(WEIGHT_LIMITED_VEHICLE_SPEED),
+        }
     }
 }
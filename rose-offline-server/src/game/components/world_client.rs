@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use bevy::ecs::prelude::{Component, Entity};
 use crossbeam_channel::Receiver;
 use tokio::sync::mpsc::UnboundedSender;
@@ -11,12 +13,26 @@ pub struct WorldClient {
     pub login_token: u32,
     pub selected_game_server: Option<Entity>,
     pub game_client_entity: Option<Entity>,
+
+    /// Set once the account's secondary PIN has been entered on this connection
+    pub secondary_pin_verified: bool,
+
+    /// The character select which is waiting for the secondary PIN
+    pub pending_select_character: Option<(u8, String)>,
+
+    /// The address the client connects from
+    pub ip: IpAddr,
+
+    /// The login token bound to the packet codec seed of this connection
+    pub packet_codec_login_token: Option<u32>,
 }
 
 impl WorldClient {
     pub fn new(
         client_message_rx: Receiver<ClientMessage>,
         server_message_tx: UnboundedSender<ServerMessage>,
+        ip: IpAddr,
+        packet_codec_login_token: Option<u32>,
     ) -> Self {
         Self {
             client_message_rx,
@@ -24,6 +40,10 @@ impl WorldClient {
             login_token: 0u32,
             selected_game_server: None,
             game_client_entity: None,
+            secondary_pin_verified: false,
+            pending_select_character: None,
+            ip,
+            packet_codec_login_token,
         }
     }
 }
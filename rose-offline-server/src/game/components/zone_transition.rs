@@ -0,0 +1,26 @@
+use bevy::ecs::prelude::Component;
+
+use rose_data::{NpcId, ZoneId};
+
+use crate::game::components::{HealthPoints, StatusEffects, StatusEffectsRegen};
+
+/// A summon which follows its owner to a new zone.
+pub struct ZoneTransitionSummon {
+    pub npc_id: NpcId,
+    pub summon_skill_level: Option<i32>,
+    pub health_points: HealthPoints,
+    pub status_effects: StatusEffects,
+    pub status_effects_regen: StatusEffectsRegen,
+}
+
+/// State which must be recreated next to a character after it changes zone,
+/// as it does not belong to the character's entity. It is removed once the
+/// character has joined `zone_id`.
+///
+/// Status effects such as party buffs stay on the character's entity, so they
+/// already carry over without being stored here.
+#[derive(Component)]
+pub struct ZoneTransition {
+    pub zone_id: ZoneId,
+    pub summons: Vec<ZoneTransitionSummon>,
+}
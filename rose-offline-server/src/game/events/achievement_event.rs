@@ -0,0 +1,21 @@
+use bevy::prelude::{Entity, Event};
+
+use rose_data::NpcId;
+
+#[derive(Event)]
+pub enum AchievementEvent {
+    Kill {
+        entity: Entity,
+        npc_id: NpcId,
+    },
+    CompleteQuest {
+        entity: Entity,
+        quest_id: usize,
+    },
+
+    /// Equip the title of an unlocked achievement, or None to unequip the title
+    EquipTitle {
+        entity: Entity,
+        achievement_id: Option<String>,
+    },
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::{Entity, Event};
+
+#[derive(Event)]
+pub enum BarbershopEvent {
+    Preview {
+        entity: Entity,
+        npc_entity: Entity,
+        face: u8,
+        hair: u8,
+    },
+    Confirm {
+        entity: Entity,
+    },
+    Cancel {
+        entity: Entity,
+    },
+}
@@ -0,0 +1,18 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+/// Starts or removes groups of bots, `entity` is the GM who is told the
+/// result, or None when the server started the scenario.
+#[derive(Event)]
+pub enum BotScenarioEvent {
+    Start {
+        name: String,
+        entity: Option<Entity>,
+    },
+    Stop {
+        name: String,
+        entity: Option<Entity>,
+    },
+    Clear {
+        entity: Option<Entity>,
+    },
+}
@@ -0,0 +1,9 @@
+use bevy::prelude::{Entity, Event};
+
+#[derive(Event)]
+pub enum CharacterInspectEvent {
+    Search { entity: Entity, pattern: String },
+    Inspect { entity: Entity, name: String },
+    Dump { entity: Entity, name: String },
+    FindItem { entity: Entity, instance_id: u64 },
+}
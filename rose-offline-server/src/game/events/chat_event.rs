@@ -0,0 +1,10 @@
+use bevy::prelude::{Entity, Event};
+
+use crate::game::resources::ChatChannel;
+
+#[derive(Event)]
+pub struct ChatEvent {
+    pub entity: Entity,
+    pub channel: ChatChannel,
+    pub text: String,
+}
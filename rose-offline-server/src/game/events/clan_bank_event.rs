@@ -0,0 +1,25 @@
+use bevy::prelude::{Entity, Event};
+
+use rose_data::Item;
+
+use crate::game::components::ItemSlot;
+
+#[derive(Event)]
+pub enum ClanBankEvent {
+    Open {
+        entity: Entity,
+    },
+    DepositItem {
+        entity: Entity,
+        item_slot: ItemSlot,
+        item: Item,
+    },
+    WithdrawItem {
+        entity: Entity,
+        bank_slot: usize,
+        item: Item,
+    },
+    GetLog {
+        entity: Entity,
+    },
+}
@@ -55,4 +55,28 @@ pub enum ClanEvent {
         clan_entity: Entity,
         skill_id: SkillId,
     },
+    UpdateDescription {
+        entity: Entity,
+        description: String,
+    },
+    UpdateNotice {
+        entity: Entity,
+        notice: String,
+    },
+    UpdateMark {
+        entity: Entity,
+        mark: ClanMark,
+    },
+    DeclareWar {
+        declarer: Entity,
+        clan_name: String,
+    },
+    AcceptWar {
+        accepter: Entity,
+        clan_name: String,
+    },
+    WarKill {
+        killer: Entity,
+        killed: Entity,
+    },
 }
@@ -0,0 +1,8 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+/// A game client's response to its client integrity challenge.
+#[derive(Event)]
+pub struct ClientIntegrityEvent {
+    pub entity: Entity,
+    pub hashes: Vec<[u8; 32]>,
+}
@@ -1,6 +1,6 @@
 use bevy::{ecs::prelude::Entity, prelude::Event};
 
-use rose_data::SkillId;
+use rose_data::{Element, SkillId};
 use rose_game_common::data::Damage;
 
 #[derive(Event)]
@@ -20,6 +20,7 @@ pub enum DamageEvent {
         defender: Entity,
         damage: Damage,
         skill_id: SkillId,
+        element: Option<Element>,
         attacker_intelligence: i32,
     },
     // For aggressive events which do no damage, such as applying a debuff
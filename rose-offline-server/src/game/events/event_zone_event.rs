@@ -0,0 +1,20 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+/// Joins, leaves, opens or stops an event zone. `entity` is the character
+/// which is told the result, or None when the server started the event.
+#[derive(Event)]
+pub enum EventZoneEvent {
+    Join {
+        entity: Entity,
+    },
+    Leave {
+        entity: Entity,
+    },
+    Start {
+        name: String,
+        entity: Option<Entity>,
+    },
+    Stop {
+        entity: Option<Entity>,
+    },
+}
@@ -0,0 +1,14 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+/// Starts or stops an invasion. `entity` is the character which is told the
+/// result, or None when the server started the invasion.
+#[derive(Event)]
+pub enum InvasionEvent {
+    Start {
+        name: String,
+        entity: Option<Entity>,
+    },
+    Stop {
+        entity: Option<Entity>,
+    },
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::{Entity, Event};
+
+use crate::game::components::ItemSlot;
+
+#[derive(Event)]
+pub enum InventoryEvent {
+    SplitItem {
+        entity: Entity,
+        item_slot: ItemSlot,
+        quantity: u32,
+    },
+    MoveItem {
+        entity: Entity,
+        from_slot: ItemSlot,
+        to_slot: ItemSlot,
+    },
+    Sort {
+        entity: Entity,
+    },
+}
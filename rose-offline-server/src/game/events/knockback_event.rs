@@ -0,0 +1,9 @@
+use bevy::{ecs::prelude::Entity, math::Vec2, prelude::Event};
+
+/// Pushes an entity directly away from `origin` by up to `distance`.
+#[derive(Event)]
+pub struct KnockbackEvent {
+    pub entity: Entity,
+    pub origin: Vec2,
+    pub distance: f32,
+}
@@ -1,37 +1,75 @@
+mod achievement_event;
 mod bank_event;
+mod barbershop_event;
+mod bot_scenario_event;
+mod character_inspect_event;
 mod chat_command_event;
+mod chat_event;
+mod clan_bank_event;
 mod clan_event;
+mod client_integrity_event;
 mod damage_event;
 mod equipment_event;
+mod event_zone_event;
+mod invasion_event;
+mod inventory_event;
 mod item_life_event;
+mod knockback_event;
+mod npc_conversation_event;
 mod npc_store_event;
 mod party_event;
 mod personal_store_event;
 mod pickup_item_event;
 mod quest_trigger_event;
+mod rebirth_event;
 mod revive_event;
+mod reward_calendar_event;
 mod reward_item_event;
 mod reward_xp_event;
 mod save_event;
 mod skill_event;
+mod statistics_event;
+mod suspicion_event;
+mod teleport_event;
 mod use_ammo_event;
 mod use_item_event;
+mod warp_gate_event;
+mod zone_snapshot_event;
 
+pub use achievement_event::AchievementEvent;
 pub use bank_event::BankEvent;
+pub use barbershop_event::BarbershopEvent;
+pub use bot_scenario_event::BotScenarioEvent;
+pub use character_inspect_event::CharacterInspectEvent;
 pub use chat_command_event::ChatCommandEvent;
+pub use chat_event::ChatEvent;
+pub use clan_bank_event::ClanBankEvent;
 pub use clan_event::ClanEvent;
+pub use client_integrity_event::ClientIntegrityEvent;
 pub use damage_event::DamageEvent;
 pub use equipment_event::EquipmentEvent;
+pub use event_zone_event::EventZoneEvent;
+pub use invasion_event::InvasionEvent;
+pub use inventory_event::InventoryEvent;
 pub use item_life_event::ItemLifeEvent;
+pub use knockback_event::KnockbackEvent;
+pub use npc_conversation_event::NpcConversationEvent;
 pub use npc_store_event::NpcStoreEvent;
 pub use party_event::{PartyEvent, PartyMemberEvent};
 pub use personal_store_event::PersonalStoreEvent;
 pub use pickup_item_event::PickupItemEvent;
 pub use quest_trigger_event::QuestTriggerEvent;
+pub use rebirth_event::RebirthEvent;
 pub use revive_event::{ReviveEvent, RevivePosition};
+pub use reward_calendar_event::RewardCalendarEvent;
 pub use reward_item_event::RewardItemEvent;
 pub use reward_xp_event::RewardXpEvent;
 pub use save_event::SaveEvent;
 pub use skill_event::{SkillEvent, SkillEventTarget};
+pub use statistics_event::StatisticsEvent;
+pub use suspicion_event::{SuspicionEvent, SuspicionSignal};
+pub use teleport_event::TeleportEvent;
 pub use use_ammo_event::UseAmmoEvent;
 pub use use_item_event::UseItemEvent;
+pub use warp_gate_event::WarpGateEvent;
+pub use zone_snapshot_event::ZoneSnapshotEvent;
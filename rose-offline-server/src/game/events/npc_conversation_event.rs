@@ -0,0 +1,12 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+use rose_data::QuestTriggerHash;
+
+#[derive(Event)]
+pub enum NpcConversationEvent {
+    QuestTrigger {
+        entity: Entity,
+        trigger_hash: QuestTriggerHash,
+        reward_choice: Option<usize>,
+    },
+}
@@ -1,13 +1,26 @@
 use bevy::{ecs::prelude::Entity, prelude::Event};
 
-use rose_data::Item;
+use rose_data::{Item, ItemType};
+
+use crate::game::components::{ItemSlot, Money};
 
 #[derive(Event)]
 pub enum PersonalStoreEvent {
+    Open {
+        entity: Entity,
+        title: String,
+        sell_items: Vec<(ItemSlot, Item, Money)>,
+        buy_items: Vec<(Item, Money)>,
+    },
     ListItems {
         store_entity: Entity,
         list_entity: Entity,
     },
+    Search {
+        entity: Entity,
+        item_type: Option<ItemType>,
+        item_name: Option<String>,
+    },
     BuyItem {
         store_entity: Entity,
         buyer_entity: Entity,
@@ -6,4 +6,8 @@ use rose_data::QuestTriggerHash;
 pub struct QuestTriggerEvent {
     pub trigger_entity: Entity,
     pub trigger_hash: QuestTriggerHash,
+
+    /// The reward chosen by the player, which must be valid for quests that
+    /// offer a choice of rewards
+    pub reward_choice: Option<usize>,
 }
@@ -0,0 +1,6 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+#[derive(Event)]
+pub struct RebirthEvent {
+    pub entity: Entity,
+}
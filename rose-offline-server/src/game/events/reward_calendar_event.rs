@@ -0,0 +1,7 @@
+use bevy::prelude::{Entity, Event};
+
+#[derive(Event)]
+pub enum RewardCalendarEvent {
+    Prompt { entity: Entity },
+    Claim { entity: Entity },
+}
@@ -0,0 +1,11 @@
+use bevy::prelude::{Entity, Event};
+
+use rose_data::NpcId;
+
+#[derive(Event)]
+pub enum StatisticsEvent {
+    MonsterKill { entity: Entity, npc_id: NpcId },
+    PvpKill { entity: Entity },
+    Death { entity: Entity },
+    EarnMoney { entity: Entity, amount: i64 },
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::{Entity, Event};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuspicionSignal {
+    /// More attack requests than the character's attack speed allows
+    AttackRate,
+
+    /// An item was used before its cooldown finished
+    ItemUseRate,
+
+    /// The client reported a position further away than the character could
+    /// have moved
+    MovementSpeed,
+
+    /// A packet which could not be decrypted or parsed
+    MalformedPacket,
+
+    /// The client's hashes of its game data files did not match the server's
+    ClientIntegrity,
+}
+
+/// Something a client did which a legitimate client should never do, the
+/// cheat detection system adds it to the account's suspicion score.
+#[derive(Event)]
+pub struct SuspicionEvent {
+    pub entity: Entity,
+    pub signal: SuspicionSignal,
+}
+
+impl SuspicionEvent {
+    pub fn new(entity: Entity, signal: SuspicionSignal) -> Self {
+        Self { entity, signal }
+    }
+}
@@ -0,0 +1,9 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+use crate::game::components::Position;
+
+#[derive(Event)]
+pub struct TeleportEvent {
+    pub entity: Entity,
+    pub position: Position,
+}
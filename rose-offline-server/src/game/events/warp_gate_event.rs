@@ -0,0 +1,9 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+use rose_data::WarpGateId;
+
+#[derive(Event)]
+pub struct WarpGateEvent {
+    pub entity: Entity,
+    pub warp_gate_id: WarpGateId,
+}
@@ -0,0 +1,7 @@
+use bevy::{ecs::prelude::Entity, prelude::Event};
+
+/// Saves a snapshot of every entity in the zone the requesting entity is in.
+#[derive(Event)]
+pub struct ZoneSnapshotEvent {
+    pub entity: Entity,
+}
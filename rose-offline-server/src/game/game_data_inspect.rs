@@ -0,0 +1,312 @@
+use enum_map::Enum;
+use thiserror::Error;
+
+use rose_data::{BaseItemData, ItemReference, ItemType, NpcData, SkillData, StatusEffectId};
+
+use crate::game::GameData;
+
+#[derive(Debug, Error)]
+pub enum GameDataInspectError {
+    #[error("No {0} found matching {1}")]
+    NotFound(&'static str, String),
+}
+
+/// Finds the entries whose id is `query`, or whose name contains `query`
+/// ignoring case.
+fn find_by_id_or_name<'a, T>(
+    entries: impl Iterator<Item = &'a T>,
+    query: &str,
+    id: impl Fn(&T) -> usize,
+    name: impl Fn(&T) -> &str,
+) -> Vec<&'a T>
+where
+    T: 'a,
+{
+    if let Ok(query_id) = query.parse::<usize>() {
+        return entries.filter(|entry| id(entry) == query_id).collect();
+    }
+
+    let query = query.to_lowercase();
+    entries
+        .filter(|entry| name(entry).to_lowercase().contains(&query))
+        .collect()
+}
+
+fn format_item_name(game_data: &GameData, item_reference: ItemReference) -> String {
+    format!(
+        "{:?} {} {}",
+        item_reference.item_type,
+        item_reference.item_number,
+        game_data
+            .items
+            .get_base_item(item_reference)
+            .map_or("unknown", |item_data| item_data.name.as_ref())
+    )
+}
+
+fn format_item(item_data: &BaseItemData) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "{:?} {}: {}",
+            item_data.id.item_type, item_data.id.item_number, item_data.name
+        ),
+        format!("  description: {}", item_data.description),
+        format!("  class: {:?}", item_data.class),
+        format!(
+            "  price: {} price rate: {} weight: {} quality: {}",
+            item_data.base_price, item_data.price_rate, item_data.weight, item_data.quality
+        ),
+    ];
+
+    if item_data.id.item_type.is_equipment_item() {
+        lines.push(format!(
+            "  durability: {} defence: {} resistance: {} rare type: {}",
+            item_data.durability, item_data.defence, item_data.resistance, item_data.rare_type
+        ));
+    }
+    if let Some(job_class) = item_data.equip_job_class_requirement {
+        lines.push(format!("  equip job class: {}", job_class.get()));
+    }
+    for (ability_type, value) in item_data.equip_ability_requirement.iter() {
+        lines.push(format!("  equip requires: {:?} {}", ability_type, value));
+    }
+    for (ability_type, value) in item_data.add_ability.iter() {
+        lines.push(format!("  add ability: {:?} {}", ability_type, value));
+    }
+    lines
+}
+
+/// Finds items by `<type> <number>`, using the same item type ids as the
+/// `/item` chat command, or by name.
+pub fn inspect_item(
+    game_data: &GameData,
+    query: &str,
+) -> Result<Vec<String>, GameDataInspectError> {
+    let items: Vec<&BaseItemData> = match query.split_once(' ').and_then(|(item_type, number)| {
+        Some((
+            item_type.trim().parse::<usize>().ok()?,
+            number.trim().parse::<usize>().ok()?,
+        ))
+    }) {
+        Some((item_type, item_number)) => game_data
+            .data_decoder
+            .decode_item_reference(item_number, item_type)
+            .and_then(|item_reference| game_data.items.get_base_item(item_reference))
+            .into_iter()
+            .collect(),
+        None => {
+            let query = query.to_lowercase();
+            (0..ItemType::LENGTH)
+                .map(ItemType::from_usize)
+                .flat_map(|item_type| game_data.items.iter_items(item_type))
+                .filter_map(|item_reference| game_data.items.get_base_item(item_reference))
+                .filter(|item_data| item_data.name.to_lowercase().contains(&query))
+                .collect()
+        }
+    };
+
+    if items.is_empty() {
+        return Err(GameDataInspectError::NotFound("item", query.to_string()));
+    }
+    Ok(items.into_iter().flat_map(format_item).collect())
+}
+
+fn find_npcs<'a>(
+    game_data: &'a GameData,
+    query: &str,
+) -> Result<Vec<&'a NpcData>, GameDataInspectError> {
+    let npcs = find_by_id_or_name(
+        game_data.npcs.iter(),
+        query,
+        |npc_data| npc_data.id.get() as usize,
+        |npc_data| npc_data.name.as_ref(),
+    );
+
+    if npcs.is_empty() {
+        return Err(GameDataInspectError::NotFound("npc", query.to_string()));
+    }
+    Ok(npcs)
+}
+
+/// Prints the stats and drop table of the monsters found by id or name.
+pub fn inspect_monster(
+    game_data: &GameData,
+    query: &str,
+) -> Result<Vec<String>, GameDataInspectError> {
+    let mut lines = Vec::new();
+
+    for npc_data in find_npcs(game_data, query)? {
+        lines.push(format!("{}: {}", npc_data.id.get(), npc_data.name));
+        lines.push(format!(
+            "  level: {} health: {} reward xp: {}",
+            npc_data.level, npc_data.health_points, npc_data.reward_xp
+        ));
+        lines.push(format!(
+            "  attack: {} hit: {} defence: {} resistance: {} avoid: {} attack speed: {} attack range: {}",
+            npc_data.attack,
+            npc_data.hit,
+            npc_data.defence,
+            npc_data.resistance,
+            npc_data.avoid,
+            npc_data.attack_speed,
+            npc_data.attack_range
+        ));
+        lines.push(format!(
+            "  walk speed: {} run speed: {} ai file: {}",
+            npc_data.walk_speed, npc_data.run_speed, npc_data.ai_file_index
+        ));
+        lines.push(format!(
+            "  drop table: {} item rate: {} money rate: {}",
+            npc_data.drop_table_index, npc_data.drop_item_rate, npc_data.drop_money_rate
+        ));
+
+        for item_reference in game_data.drop_table.get_npc_drop_items(npc_data.id) {
+            lines.push(format!(
+                "    {}",
+                format_item_name(game_data, item_reference)
+            ));
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Lists the store tabs of the npcs found by id or name.
+pub fn inspect_store(
+    game_data: &GameData,
+    query: &str,
+) -> Result<Vec<String>, GameDataInspectError> {
+    let mut lines = Vec::new();
+
+    for npc_data in find_npcs(game_data, query)? {
+        let store_tabs: Vec<_> = npc_data
+            .store_tabs
+            .iter()
+            .flatten()
+            .filter_map(|store_tab_id| game_data.npcs.get_store_tab(*store_tab_id))
+            .collect();
+        if store_tabs.is_empty() {
+            continue;
+        }
+
+        lines.push(format!("{}: {}", npc_data.id.get(), npc_data.name));
+        if let Some(store_union_number) = npc_data.store_union_number {
+            lines.push(format!("  union: {}", store_union_number));
+        }
+
+        for store_tab in store_tabs {
+            lines.push(format!("  {}:", store_tab.name));
+
+            let mut items: Vec<_> = store_tab.items.iter().collect();
+            items.sort_by_key(|(slot, _)| **slot);
+            for (slot, item_reference) in items {
+                lines.push(format!(
+                    "    {}: {}",
+                    slot,
+                    format_item_name(game_data, *item_reference)
+                ));
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        return Err(GameDataInspectError::NotFound("store", query.to_string()));
+    }
+    Ok(lines)
+}
+
+fn format_status_effect(game_data: &GameData, status_effect_id: StatusEffectId) -> String {
+    format!(
+        "{} {}",
+        status_effect_id.get(),
+        game_data
+            .status_effects
+            .get_status_effect(status_effect_id)
+            .map_or("unknown", |status_effect| status_effect.name.as_ref())
+    )
+}
+
+fn format_skill(game_data: &GameData, skill_data: &SkillData) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "{}: {} level {}",
+            skill_data.id.get(),
+            skill_data.name,
+            skill_data.level
+        ),
+        format!("  description: {}", skill_data.description),
+        format!(
+            "  type: {:?} target: {:?} action: {:?}",
+            skill_data.skill_type, skill_data.target_filter, skill_data.action_mode
+        ),
+        format!(
+            "  power: {} harm: {} damage type: {} element: {:?} success ratio: {}",
+            skill_data.power,
+            skill_data.harm,
+            skill_data.damage_type,
+            skill_data.element,
+            skill_data.success_ratio
+        ),
+        format!(
+            "  cast range: {} scope: {} cooldown: {:?}",
+            skill_data.cast_range, skill_data.scope, skill_data.cooldown
+        ),
+    ];
+
+    for (ability_type, value) in skill_data.use_ability.iter() {
+        lines.push(format!("  uses: {:?} {}", ability_type, value));
+    }
+    for add_ability in skill_data.add_ability.iter().flatten() {
+        lines.push(format!(
+            "  add ability: {:?} value {} rate {}",
+            add_ability.ability_type, add_ability.value, add_ability.rate
+        ));
+    }
+    for status_effect_id in skill_data.status_effects.iter().flatten() {
+        lines.push(format!(
+            "  status effect: {} for {:?}",
+            format_status_effect(game_data, *status_effect_id),
+            skill_data.status_effect_duration
+        ));
+    }
+    if let Some(summon_npc_id) = skill_data.summon_npc_id {
+        lines.push(format!(
+            "  summons: {} {}",
+            summon_npc_id.get(),
+            game_data
+                .npcs
+                .get_npc(summon_npc_id)
+                .map_or("unknown", |npc_data| npc_data.name.as_ref())
+        ));
+    }
+    if let Some(warp_zone_id) = skill_data.warp_zone_id {
+        lines.push(format!(
+            "  warps to: zone {} ({}, {})",
+            warp_zone_id.get(),
+            skill_data.warp_zone_x,
+            skill_data.warp_zone_y
+        ));
+    }
+    lines
+}
+
+/// Prints the effects of the skills found by id or name.
+pub fn inspect_skill(
+    game_data: &GameData,
+    query: &str,
+) -> Result<Vec<String>, GameDataInspectError> {
+    let skills = find_by_id_or_name(
+        game_data.skills.iter(),
+        query,
+        |skill_data| skill_data.id.get() as usize,
+        |skill_data| skill_data.name.as_ref(),
+    );
+
+    if skills.is_empty() {
+        return Err(GameDataInspectError::NotFound("skill", query.to_string()));
+    }
+    Ok(skills
+        .into_iter()
+        .flat_map(|skill_data| format_skill(game_data, skill_data))
+        .collect())
+}
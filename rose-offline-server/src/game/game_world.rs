@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use bevy::{
     app::ScheduleRunnerPlugin,
@@ -13,41 +13,67 @@ use crossbeam_channel::Receiver;
 use crate::game::{
     bots::BotPlugin,
     events::{
-        BankEvent, ChatCommandEvent, ClanEvent, DamageEvent, EquipmentEvent, ItemLifeEvent,
-        NpcStoreEvent, PartyEvent, PartyMemberEvent, PersonalStoreEvent, PickupItemEvent,
-        QuestTriggerEvent, ReviveEvent, RewardItemEvent, RewardXpEvent, SaveEvent, SkillEvent,
-        UseAmmoEvent, UseItemEvent,
+        AchievementEvent, BankEvent, BarbershopEvent, BotScenarioEvent, CharacterInspectEvent,
+        ChatCommandEvent, ChatEvent, ClanBankEvent, ClanEvent, ClientIntegrityEvent, DamageEvent,
+        EquipmentEvent, EventZoneEvent, InvasionEvent, InventoryEvent, ItemLifeEvent,
+        KnockbackEvent, NpcConversationEvent, NpcStoreEvent, PartyEvent, PartyMemberEvent,
+        PersonalStoreEvent, PickupItemEvent, QuestTriggerEvent, RebirthEvent, ReviveEvent,
+        RewardCalendarEvent, RewardItemEvent, RewardXpEvent, SaveEvent, SkillEvent,
+        StatisticsEvent, SuspicionEvent, TeleportEvent, UseAmmoEvent, UseItemEvent, WarpGateEvent,
+        ZoneSnapshotEvent,
     },
     messages::control::ControlMessage,
     resources::{
-        BotList, ClientEntityList, ControlChannel, GameConfig, GameData, LoginTokens, ServerList,
-        ServerMessages, WorldRates, WorldTime, ZoneList,
+        AccountSessions, BotList, CharacterListCache, ChatChannels, ChatModeration, CheatDetection,
+        ClanWars, ClientEntityList, ClientIntegrity, ControlChannel, EmailSender, EventZones,
+        GameConfig, GameData, Invasions, LeaderboardCache, LoginTokens, Maintenance, NameFilter,
+        NpcStoreStock, PacketCodecSeeds, PersonalStoreList, ServerList, ServerMessages,
+        StorageService, TickProfiler, WorldTime, ZoneGeometry, ZoneList,
+    },
+    storage::{
+        chat_mute::ChatMuteStorage, index_account_character_names,
+        item_transaction::recover_item_transactions, journal::recover_storage_journals,
+        migrate_account_character_names, npc_store_stock::NpcStoreStockStorage,
     },
     systems::{
         ability_values_changed_system, ability_values_update_character_system,
-        ability_values_update_npc_system, bank_system, chat_commands_system, clan_system,
-        client_entity_visibility_system, command_system, control_server_system, damage_system,
-        driving_time_system, equipment_event_system, experience_points_system, expire_time_system,
+        ability_values_update_npc_system, achievement_system, activity_system, bank_system,
+        barbershop_system, bot_scenario_system, character_inspect_system, chat_commands_system,
+        chat_system, cheat_detection_system, clan_bank_system, clan_system,
+        client_entity_visibility_system, client_integrity_system, command_system,
+        control_server_system, damage_system, driving_time_system, equipment_event_system,
+        event_zone_system, experience_points_system, expire_time_system,
         game_server_authentication_system, game_server_join_system, game_server_main_system,
-        item_life_system, login_server_authentication_system, login_server_system,
-        monster_spawn_system, npc_ai_system, npc_store_system, party_member_event_system,
+        invasion_system, inventory_system, item_drop_persist_system, item_drop_system,
+        item_life_system, knockback_system, leaderboard_system, login_server_authentication_system,
+        login_server_system, login_token_expire_system, maintenance_system, monster_spawn_system,
+        npc_ai_system, npc_conversation_system, npc_store_restock_system, npc_store_system,
+        party_member_event_system, party_member_map_markers_system,
         party_member_update_info_system, party_system, party_update_average_level_system,
-        passive_recovery_system, personal_store_system, pickup_item_system, quest_system,
-        revive_event_system, reward_item_system, save_system, server_messages_system,
-        skill_effect_system, startup_clans_system, startup_zones_system, status_effect_system,
-        update_character_motion_data_system, update_npc_motion_data_system, update_position_system,
-        use_ammo_system, use_item_system, weight_system, world_server_authentication_system,
-        world_server_system, world_time_system,
+        passive_recovery_system, personal_store_list_system, personal_store_system,
+        pickup_item_system, position_history_system, quest_system, rebirth_system,
+        revive_event_system, reward_calendar_system, reward_item_system, save_system,
+        server_messages_system, skill_effect_system, spectator_system, startup_clans_system,
+        startup_item_drops_system, startup_parties_system, startup_zones_system, statistics_system,
+        status_effect_system, storage_service_system, teleport_event_system, teleport_system,
+        tick_profiler_system, update_character_motion_data_system, update_npc_motion_data_system,
+        update_position_system, use_ammo_system, use_item_system, weight_system,
+        world_server_authentication_system, world_server_system, world_time_system,
+        zone_environment_system, zone_snapshot_system, zone_transition_system,
     },
 };
 
 pub struct GameWorld {
     control_rx: Receiver<ControlMessage>,
+    packet_codec_seeds: PacketCodecSeeds,
 }
 
 impl GameWorld {
-    pub fn new(control_rx: Receiver<ControlMessage>) -> Self {
-        Self { control_rx }
+    pub fn new(control_rx: Receiver<ControlMessage>, packet_codec_seeds: PacketCodecSeeds) -> Self {
+        Self {
+            control_rx,
+            packet_codec_seeds,
+        }
     }
 
     pub fn run(&mut self, game_config: GameConfig, game_data: GameData) {
@@ -57,37 +83,134 @@ impl GameWorld {
         )));
         app.add_plugins(BotPlugin);
 
+        app.insert_resource(AccountSessions::new());
         app.insert_resource(BotList::new());
+        app.insert_resource(CharacterListCache::new());
+        app.insert_resource(ChatChannels::new(game_config.chat_channels.clone()));
+        let chat_mutes =
+            ChatMuteStorage::try_load_active(chrono::Utc::now()).unwrap_or_else(|error| {
+                log::error!("Failed to load chat mutes: {:?}", error);
+                Vec::new()
+            });
+        app.insert_resource(ChatModeration::new(
+            &game_config.chat_moderation,
+            chat_mutes,
+        ));
+        app.insert_resource(CheatDetection::new(&game_config.cheat_detection));
+        app.insert_resource(ClanWars::new());
         app.insert_resource(ClientEntityList::new(&game_data.zones));
+        if let Some(client_integrity) = game_config.client_integrity.as_ref() {
+            app.insert_resource(ClientIntegrity::new(client_integrity));
+        }
         app.insert_resource(ControlChannel::new(self.control_rx.clone()));
-        app.insert_resource(LoginTokens::new());
+        app.insert_resource(EventZones::default());
+        app.insert_resource(Invasions::default());
+        if let Some(refresh_interval) = game_config.leaderboard_refresh_interval {
+            app.insert_resource(LeaderboardCache::new(refresh_interval, Instant::now()));
+        }
+        app.insert_resource(LoginTokens::new(self.packet_codec_seeds.clone()));
+        app.insert_resource(Maintenance::default());
+        app.insert_resource(NameFilter::new(
+            &game_config.name_filter,
+            &game_data.npcs,
+            &game_data.zones,
+        ));
+        let npc_store_stock = NpcStoreStockStorage::try_load().unwrap_or_else(|error| {
+            log::error!("Failed to load npc store stock: {:?}", error);
+            None
+        });
+        app.insert_resource(NpcStoreStock::new(
+            &game_config.npc_store_stock,
+            npc_store_stock.as_ref(),
+            Instant::now(),
+            chrono::Utc::now(),
+        ));
+        app.insert_resource(PersonalStoreList::new());
         app.insert_resource(ServerList::new());
         app.insert_resource(ServerMessages::new());
-        app.insert_resource(WorldRates::new());
+        match migrate_account_character_names() {
+            Ok(0) => {}
+            Ok(count) => log::info!("Renamed {} account and character documents", count),
+            Err(error) => panic!("Failed to migrate account and character names: {:?}", error),
+        }
+        log::info!(
+            "Indexed {} account and character documents",
+            index_account_character_names()
+        );
+        // Interrupted item transactions must complete before any character loads
+        match recover_item_transactions() {
+            Ok(0) => {}
+            Ok(count) => log::info!("Recovered {} interrupted item transactions", count),
+            Err(error) => log::error!("Failed to recover item transactions: {:?}", error),
+        }
+        match recover_storage_journals() {
+            Ok(0) => {}
+            Ok(count) => log::info!("Recovered {} interrupted storage journals", count),
+            Err(error) => log::error!("Failed to recover storage journals: {:?}", error),
+        }
+        let mut storage_service = StorageService::new();
+        if let Some(storage_backup) = game_config.storage_backup.clone() {
+            storage_service.set_backup_config(storage_backup, Instant::now());
+        }
+        app.insert_resource(storage_service);
+        if let Some(smtp) = game_config.smtp.clone() {
+            app.insert_resource(EmailSender::new(smtp));
+        }
+        app.insert_resource(game_config.world_rates.clone());
         app.insert_resource(WorldTime::new());
+        app.insert_resource(ZoneGeometry::new(game_data.zone_geometry.clone()));
         app.insert_resource(ZoneList::new());
+
+        if let Some(budget) = game_config.tick_profiler_budget {
+            match TickProfiler::install(budget) {
+                Ok(tick_profiler) => {
+                    app.insert_resource(tick_profiler);
+                }
+                Err(error) => log::warn!("Failed to enable tick profiler: {}", error),
+            }
+        }
+
         app.insert_resource(game_config);
         app.insert_resource(game_data);
 
-        app.add_event::<BankEvent>()
+        app.add_event::<AchievementEvent>()
+            .add_event::<BankEvent>()
+            .add_event::<BarbershopEvent>()
+            .add_event::<BotScenarioEvent>()
+            .add_event::<CharacterInspectEvent>()
             .add_event::<ChatCommandEvent>()
+            .add_event::<ChatEvent>()
+            .add_event::<ClanBankEvent>()
             .add_event::<ClanEvent>()
+            .add_event::<ClientIntegrityEvent>()
             .add_event::<DamageEvent>()
             .add_event::<EquipmentEvent>()
+            .add_event::<EventZoneEvent>()
+            .add_event::<InvasionEvent>()
+            .add_event::<InventoryEvent>()
             .add_event::<ItemLifeEvent>()
+            .add_event::<KnockbackEvent>()
+            .add_event::<NpcConversationEvent>()
             .add_event::<NpcStoreEvent>()
             .add_event::<PartyEvent>()
             .add_event::<PartyMemberEvent>()
             .add_event::<PersonalStoreEvent>()
             .add_event::<PickupItemEvent>()
             .add_event::<QuestTriggerEvent>()
+            .add_event::<RebirthEvent>()
             .add_event::<ReviveEvent>()
+            .add_event::<RewardCalendarEvent>()
             .add_event::<RewardItemEvent>()
             .add_event::<RewardXpEvent>()
             .add_event::<SaveEvent>()
             .add_event::<SkillEvent>()
+            .add_event::<StatisticsEvent>()
+            .add_event::<SuspicionEvent>()
+            .add_event::<TeleportEvent>()
             .add_event::<UseAmmoEvent>()
-            .add_event::<UseItemEvent>();
+            .add_event::<UseItemEvent>()
+            .add_event::<WarpGateEvent>()
+            .add_event::<ZoneSnapshotEvent>();
 
         /*
         Stage order:
@@ -98,14 +221,24 @@ impl GameWorld {
         - CoreSet::PostUpdate
         - CoreSet::Last
         */
-        app.add_systems(Startup, (startup_clans_system, startup_zones_system));
+        app.add_systems(
+            Startup,
+            (
+                startup_clans_system,
+                startup_parties_system,
+                startup_zones_system,
+                startup_item_drops_system.after(startup_zones_system),
+            ),
+        );
 
         app.add_systems(
             PreUpdate,
             (
                 (
-                    world_time_system,
+                    (world_time_system, zone_environment_system).chain(),
                     control_server_system,
+                    login_token_expire_system,
+                    maintenance_system,
                     login_server_authentication_system,
                     login_server_system,
                     world_server_authentication_system,
@@ -129,6 +262,7 @@ impl GameWorld {
                             update_npc_motion_data_system,
                             update_position_system,
                         ),
+                        position_history_system,
                         command_system,
                         (use_ammo_system, pickup_item_system),
                     )
@@ -137,6 +271,7 @@ impl GameWorld {
                         party_member_event_system,
                         party_system,
                         party_member_update_info_system,
+                        party_member_map_markers_system,
                     )
                         .chain(),
                     clan_system,
@@ -149,13 +284,23 @@ impl GameWorld {
             Update,
             (
                 bank_system,
+                barbershop_system,
+                character_inspect_system,
+                chat_system,
+                clan_bank_system,
+                inventory_system,
                 personal_store_system,
                 npc_store_system,
+                npc_store_restock_system.after(npc_store_system),
+                npc_conversation_system.before(quest_system),
                 quest_system,
+                (teleport_system, spectator_system),
                 use_item_system,
+                reward_calendar_system,
                 reward_item_system,
                 damage_system.before(item_life_system),
                 skill_effect_system.before(item_life_system),
+                knockback_system.after(skill_effect_system),
                 item_life_system,
                 equipment_event_system.after(item_life_system),
             ),
@@ -165,8 +310,24 @@ impl GameWorld {
             PostUpdate,
             (
                 weight_system,
+                personal_store_list_system,
                 experience_points_system,
+                rebirth_system.after(experience_points_system),
+                achievement_system.after(experience_points_system),
+                statistics_system,
+                activity_system,
+                client_integrity_system.before(cheat_detection_system),
+                cheat_detection_system,
+                leaderboard_system.after(statistics_system),
                 party_update_average_level_system.after(experience_points_system),
+                event_zone_system.before(teleport_event_system),
+                invasion_system.before(client_entity_visibility_system),
+                teleport_event_system.before(client_entity_visibility_system),
+                zone_transition_system.before(client_entity_visibility_system),
+                item_drop_system.before(client_entity_visibility_system),
+                item_drop_persist_system.after(item_drop_system),
+                zone_snapshot_system,
+                bot_scenario_system,
                 client_entity_visibility_system,
             ),
         );
@@ -179,6 +340,8 @@ impl GameWorld {
                 ability_values_changed_system,
                 server_messages_system,
                 save_system,
+                storage_service_system.after(save_system),
+                tick_profiler_system,
             ),
         );
 
@@ -1,11 +1,17 @@
 use bevy::ecs::prelude::Entity;
 use crossbeam_channel::Receiver;
+use serde::{Deserialize, Serialize};
+use std::{net::IpAddr, time::Duration};
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
 
-use crate::game::messages::{client::ClientMessage, server::ServerMessage};
+use crate::game::{
+    bundles::ItemSpawn,
+    messages::{client::ClientMessage, server::ServerMessage},
+    storage::leaderboard::Leaderboards,
+};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientType {
     Login,
     World,
@@ -15,6 +21,15 @@ pub enum ClientType {
 pub enum ControlMessage {
     AddClient {
         client_type: ClientType,
+        /// The address of the client, or the address given by the load
+        /// balancer when using the PROXY protocol
+        ip: IpAddr,
+        /// The country the client connects from, only looked up for login
+        /// clients when a GeoIP database is configured
+        country: Option<String>,
+        /// The login token bound to the packet codec seed used by the
+        /// connection, the client must connect with this token
+        packet_codec_login_token: Option<u32>,
         client_message_rx: Receiver<ClientMessage>,
         server_message_tx: UnboundedSender<ServerMessage>,
         response_tx: oneshot::Sender<Entity>,
@@ -23,6 +38,11 @@ pub enum ControlMessage {
         client_type: ClientType,
         entity: Entity,
     },
+    /// Sent before `RemoveClient` when a game client is disconnected for
+    /// sending a packet which could not be decrypted or parsed
+    MalformedPacket {
+        entity: Entity,
+    },
     AddWorldServer {
         name: String,
         ip: String,
@@ -41,4 +61,27 @@ pub enum ControlMessage {
     RemoveServer {
         entity: Entity,
     },
+    /// Sent by a game server whenever a client connects or disconnects
+    UpdateChannelPopulation {
+        game_server: Entity,
+        num_clients: usize,
+    },
+    /// Only GM accounts can log in until the server shuts down at the end of
+    /// the countdown
+    StartMaintenance {
+        countdown: Duration,
+        reason: String,
+    },
+    CancelMaintenance,
+    /// Responds with None if leaderboards are disabled or have not been built yet
+    GetLeaderboards {
+        response_tx: oneshot::Sender<Option<Leaderboards>>,
+    },
+    /// Gives the spawned item to an online character, responds with an error
+    /// message if the character is not online or the item is not valid
+    SpawnItem {
+        character_name: String,
+        item_spawn: ItemSpawn,
+        response_tx: oneshot::Sender<Result<(), String>>,
+    },
 }
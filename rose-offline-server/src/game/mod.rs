@@ -6,8 +6,14 @@ mod resources;
 mod systems;
 
 pub mod components;
+pub mod game_data_inspect;
 pub mod messages;
 pub mod storage;
 
+pub use bundles::ItemSpawn;
 pub use game_world::GameWorld;
-pub use resources::{GameConfig, GameData};
+pub use resources::{
+    AfkConfig, ChannelCapacityConfig, GameConfig, GameData, OfflineVendorConfig,
+    PacketCodecSeedUpdate, PacketCodecSeeds, PendingPacketCodecSeed, SmtpConfig,
+    StorageBackupConfig, WorldRates,
+};
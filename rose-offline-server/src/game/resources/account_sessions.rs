@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::prelude::Entity, prelude::Resource};
+
+/// The clients and login token which belong to an account's session, a
+/// session is held until all of them have been released.
+#[derive(Default)]
+pub struct AccountSession {
+    pub login_client: Option<Entity>,
+    pub world_client: Option<Entity>,
+    pub game_client: Option<Entity>,
+    pub login_token: Option<u32>,
+}
+
+impl AccountSession {
+    fn is_released(&self) -> bool {
+        self.login_client.is_none()
+            && self.world_client.is_none()
+            && self.game_client.is_none()
+            && self.login_token.is_none()
+    }
+
+    fn release_client(&mut self, entity: Entity) {
+        if self.login_client == Some(entity) {
+            self.login_client = None;
+        }
+
+        if self.world_client == Some(entity) {
+            self.world_client = None;
+        }
+
+        if self.game_client == Some(entity) {
+            self.game_client = None;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AccountSessionError {
+    AlreadyClaimed,
+    InvalidToken,
+}
+
+/// The authoritative record of which accounts are logged in, an account can
+/// only ever have a single session across the login, world and game servers.
+#[derive(Default, Resource)]
+pub struct AccountSessions {
+    sessions: HashMap<String, AccountSession>,
+
+    /// Game clients which have left their session, mapped to their account
+    /// name, their character must be saved and despawned before the account
+    /// can join the game again
+    disconnecting_game_clients: HashMap<Entity, String>,
+
+    /// Game clients which reconnected to a disconnected character, mapped to
+    /// the character entity which they now control
+    reconnected_game_clients: HashMap<Entity, Entity>,
+}
+
+impl AccountSessions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Claims a new session for the account, which fails if the account
+    /// already has a session.
+    pub fn try_claim(
+        &mut self,
+        account_name: &str,
+        login_client: Entity,
+    ) -> Result<(), AccountSessionError> {
+        if self.sessions.contains_key(account_name) {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        self.sessions.insert(
+            account_name.to_string(),
+            AccountSession {
+                login_client: Some(login_client),
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    /// Removes an account's session so that its clients can be disconnected
+    /// and the account claimed by a new login.
+    pub fn take_over(&mut self, account_name: &str) -> Option<AccountSession> {
+        let session = self.sessions.remove(account_name)?;
+        if let Some(game_client) = session.game_client {
+            self.disconnecting_game_clients
+                .insert(game_client, account_name.to_string());
+        }
+        Some(session)
+    }
+
+    /// Sets the login token for the session, returning the previous token
+    /// which is no longer valid.
+    pub fn set_login_token(&mut self, account_name: &str, token_id: u32) -> Option<u32> {
+        self.sessions
+            .get_mut(account_name)
+            .and_then(|session| session.login_token.replace(token_id))
+    }
+
+    pub fn claim_world_client(
+        &mut self,
+        account_name: &str,
+        token_id: u32,
+        world_client: Entity,
+    ) -> Result<(), AccountSessionError> {
+        let session = self
+            .sessions
+            .get_mut(account_name)
+            .filter(|session| session.login_token == Some(token_id))
+            .ok_or(AccountSessionError::InvalidToken)?;
+        if session.world_client.is_some() {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        session.world_client = Some(world_client);
+        Ok(())
+    }
+
+    /// Claims the session's game client, which fails whilst the account has a
+    /// character in game, including one from a session which was taken over.
+    pub fn claim_game_client(
+        &mut self,
+        account_name: &str,
+        token_id: u32,
+        game_client: Entity,
+    ) -> Result<(), AccountSessionError> {
+        if self
+            .disconnecting_game_clients
+            .values()
+            .any(|name| name == account_name)
+        {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        let session = self
+            .sessions
+            .get_mut(account_name)
+            .filter(|session| session.login_token == Some(token_id))
+            .ok_or(AccountSessionError::InvalidToken)?;
+        if session.game_client.is_some() {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        session.game_client = Some(game_client);
+        Ok(())
+    }
+
+    /// Claims the session's game client for a character which is still in the
+    /// world after its previous game client disconnected.
+    pub fn reconnect_game_client(
+        &mut self,
+        account_name: &str,
+        token_id: u32,
+        game_client: Entity,
+        character: Entity,
+    ) -> Result<(), AccountSessionError> {
+        if self
+            .disconnecting_game_clients
+            .get(&character)
+            .map(String::as_str)
+            != Some(account_name)
+        {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        self.claim_character(account_name, token_id, game_client, character)?;
+        self.disconnecting_game_clients.remove(&character);
+        Ok(())
+    }
+
+    /// Claims the session's game client for a character which was left in the
+    /// world as an offline vendor, which fails whilst the account has another
+    /// character in game.
+    pub fn claim_offline_vendor(
+        &mut self,
+        account_name: &str,
+        token_id: u32,
+        game_client: Entity,
+        character: Entity,
+    ) -> Result<(), AccountSessionError> {
+        if self
+            .disconnecting_game_clients
+            .values()
+            .any(|name| name == account_name)
+        {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        self.claim_character(account_name, token_id, game_client, character)
+    }
+
+    fn claim_character(
+        &mut self,
+        account_name: &str,
+        token_id: u32,
+        game_client: Entity,
+        character: Entity,
+    ) -> Result<(), AccountSessionError> {
+        let session = self
+            .sessions
+            .get_mut(account_name)
+            .filter(|session| session.login_token == Some(token_id))
+            .ok_or(AccountSessionError::InvalidToken)?;
+        if session.game_client.is_some() {
+            return Err(AccountSessionError::AlreadyClaimed);
+        }
+
+        session.game_client = Some(character);
+        self.reconnected_game_clients.insert(game_client, character);
+        Ok(())
+    }
+
+    /// Returns the character entity which a reconnected game client controls,
+    /// once the game client has disconnected.
+    pub fn remove_reconnected_game_client(&mut self, game_client: Entity) -> Option<Entity> {
+        self.reconnected_game_clients.remove(&game_client)
+    }
+
+    /// Removes a disconnected game client from its session, it is not released
+    /// until its character has been saved and despawned.
+    pub fn disconnect_game_client(&mut self, entity: Entity) {
+        for (account_name, session) in self.sessions.iter_mut() {
+            if session.game_client == Some(entity) {
+                session.game_client = None;
+                self.disconnecting_game_clients
+                    .insert(entity, account_name.clone());
+            }
+        }
+        self.sessions.retain(|_, session| !session.is_released());
+    }
+
+    /// Releases a client which has disconnected from whichever session it
+    /// belongs to.
+    pub fn release_client(&mut self, entity: Entity) {
+        self.disconnecting_game_clients.remove(&entity);
+        self.sessions.retain(|_, session| {
+            session.release_client(entity);
+            !session.is_released()
+        });
+    }
+
+    /// Releases a login token which has expired or been removed.
+    pub fn release_login_token(&mut self, token_id: u32) {
+        self.sessions.retain(|_, session| {
+            if session.login_token == Some(token_id) {
+                session.login_token = None;
+            }
+            !session.is_released()
+        });
+    }
+}
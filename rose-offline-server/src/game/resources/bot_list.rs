@@ -5,11 +5,24 @@ use bevy::{
 
 pub struct BotListEntry {
     pub entity: Entity,
+
+    /// The bot scenario which spawned this bot, if any
+    pub scenario: Option<String>,
 }
 
 impl BotListEntry {
     pub fn new(entity: Entity) -> Self {
-        Self { entity }
+        Self {
+            entity,
+            scenario: None,
+        }
+    }
+
+    pub fn with_scenario(entity: Entity, scenario: String) -> Self {
+        Self {
+            entity,
+            scenario: Some(scenario),
+        }
     }
 }
 
@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::Resource;
+
+use crate::game::messages::server::CharacterListItem;
+
+/// How long a character list stays cached after it was last used.
+const CHARACTER_LIST_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum number of accounts to cache, the least recently used account
+/// is evicted first.
+const CHARACTER_LIST_CACHE_MAX_ACCOUNTS: usize = 1024;
+
+struct CachedCharacterList {
+    characters: Vec<CharacterListItem>,
+    last_used: Instant,
+}
+
+/// Caches the character list for each account so repeated world server
+/// connections do not have to load every character from storage. Accounts
+/// are evicted once they have not been used for a while.
+#[derive(Default, Resource)]
+pub struct CharacterListCache {
+    accounts: HashMap<String, CachedCharacterList>,
+}
+
+impl CharacterListCache {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the cached character list only if it still matches the
+    /// account's character names.
+    pub fn get(
+        &mut self,
+        account_name: &str,
+        character_names: &[String],
+        now: Instant,
+    ) -> Option<&[CharacterListItem]> {
+        self.accounts
+            .get_mut(account_name)
+            .filter(|cached| {
+                cached.characters.len() == character_names.len()
+                    && cached
+                        .characters
+                        .iter()
+                        .zip(character_names.iter())
+                        .all(|(character, name)| &character.info.name == name)
+            })
+            .map(|cached| {
+                cached.last_used = now;
+                cached.characters.as_slice()
+            })
+    }
+
+    pub fn insert(
+        &mut self,
+        account_name: String,
+        characters: Vec<CharacterListItem>,
+        now: Instant,
+    ) {
+        self.accounts.retain(|_, cached| {
+            now.duration_since(cached.last_used) < CHARACTER_LIST_CACHE_DURATION
+        });
+
+        if !self.accounts.contains_key(&account_name)
+            && self.accounts.len() >= CHARACTER_LIST_CACHE_MAX_ACCOUNTS
+        {
+            if let Some(least_recently_used) = self
+                .accounts
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(account_name, _)| account_name.clone())
+            {
+                self.accounts.remove(&least_recently_used);
+            }
+        }
+
+        self.accounts.insert(
+            account_name,
+            CachedCharacterList {
+                characters,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Replaces a character in the account's cached list with its latest
+    /// saved state, does nothing if the account or character is not cached.
+    pub fn update(&mut self, account_name: &str, character: CharacterListItem) {
+        if let Some(cached) = self.accounts.get_mut(account_name).and_then(|cached| {
+            cached
+                .characters
+                .iter_mut()
+                .find(|cached| cached.info.name == character.info.name)
+        }) {
+            *cached = character;
+        }
+    }
+
+    pub fn invalidate(&mut self, account_name: &str) {
+        self.accounts.remove(account_name);
+    }
+}
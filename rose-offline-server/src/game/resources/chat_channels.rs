@@ -0,0 +1,83 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::{Entity, Resource};
+
+use crate::game::resources::{ChatChannelConfig, ChatChannelsConfig};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChatChannel {
+    Local,
+    Shout,
+    Trade,
+    Announce,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ChatChannelError {
+    Disabled,
+    LevelTooLow { min_level: u32 },
+    Cooldown { remaining: Duration },
+}
+
+/// Tracks the cooldown of each chat channel for each character.
+#[derive(Resource)]
+pub struct ChatChannels {
+    config: ChatChannelsConfig,
+    cooldowns: HashMap<(Entity, ChatChannel), Instant>,
+}
+
+impl ChatChannels {
+    pub fn new(config: ChatChannelsConfig) -> Self {
+        Self {
+            config,
+            cooldowns: HashMap::new(),
+        }
+    }
+
+    pub fn get_config(&self, channel: ChatChannel) -> &ChatChannelConfig {
+        match channel {
+            ChatChannel::Local => &self.config.local,
+            ChatChannel::Shout => &self.config.shout,
+            ChatChannel::Trade => &self.config.trade,
+            ChatChannel::Announce => &self.config.announce,
+        }
+    }
+
+    /// Checks the character is allowed to send a message to the channel and
+    /// starts the channel's cooldown for the character.
+    pub fn try_send(
+        &mut self,
+        entity: Entity,
+        level: u32,
+        channel: ChatChannel,
+        now: Instant,
+    ) -> Result<(), ChatChannelError> {
+        self.cooldowns.retain(|_, until| *until > now);
+
+        let config = self.get_config(channel);
+        if !config.enabled {
+            return Err(ChatChannelError::Disabled);
+        }
+
+        if level < config.min_level {
+            return Err(ChatChannelError::LevelTooLow {
+                min_level: config.min_level,
+            });
+        }
+
+        let cooldown = config.get_cooldown();
+        if let Some(&until) = self.cooldowns.get(&(entity, channel)) {
+            return Err(ChatChannelError::Cooldown {
+                remaining: until - now,
+            });
+        }
+
+        if !cooldown.is_zero() {
+            self.cooldowns.insert((entity, channel), now + cooldown);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::{Entity, Resource};
+use chrono::{DateTime, Utc};
+
+use crate::game::{
+    resources::{name_filter::normalize_name, BannedWordAction, ChatModerationConfig},
+    storage::chat_mute::ChatMuteStorage,
+};
+
+struct RecentMessage {
+    text: String,
+    count: u32,
+    first_sent: Instant,
+}
+
+/// Applies mutes, the banned word filter and spam detection to chat before it
+/// is sent to any channel.
+#[derive(Resource)]
+pub struct ChatModeration {
+    banned_words: Vec<String>,
+    banned_word_action: BannedWordAction,
+    spam_repeat_count: u32,
+    spam_repeat_window: Duration,
+    spam_mute_duration: Duration,
+    mutes: HashMap<String, ChatMuteStorage>,
+    recent_messages: HashMap<Entity, RecentMessage>,
+}
+
+impl ChatModeration {
+    pub fn new(config: &ChatModerationConfig, mutes: Vec<ChatMuteStorage>) -> Self {
+        Self {
+            banned_words: config
+                .banned_words
+                .iter()
+                .map(|word| normalize_name(word))
+                .filter(|word| !word.is_empty())
+                .collect(),
+            banned_word_action: config.banned_word_action,
+            spam_repeat_count: config.spam_repeat_count,
+            spam_repeat_window: Duration::from_secs(config.spam_repeat_window_secs),
+            spam_mute_duration: Duration::from_secs(config.spam_mute_secs),
+            mutes: mutes
+                .into_iter()
+                .map(|mute| (mute.character_name.to_lowercase(), mute))
+                .collect(),
+            recent_messages: HashMap::new(),
+        }
+    }
+
+    pub fn get_mute(&self, character_name: &str, now: DateTime<Utc>) -> Option<&ChatMuteStorage> {
+        self.mutes
+            .get(&character_name.to_lowercase())
+            .filter(|mute| mute.until > now)
+    }
+
+    /// Returns every mute which has not expired, ordered by when they expire.
+    pub fn get_active_mutes(&self, now: DateTime<Utc>) -> Vec<&ChatMuteStorage> {
+        let mut mutes: Vec<&ChatMuteStorage> = self
+            .mutes
+            .values()
+            .filter(|mute| mute.until > now)
+            .collect();
+        mutes.sort_by_key(|mute| mute.until);
+        mutes
+    }
+
+    /// Replaces any existing mute for the character.
+    pub fn mute(&mut self, mute: ChatMuteStorage) {
+        self.mutes.insert(mute.character_name.to_lowercase(), mute);
+    }
+
+    /// Returns the removed mute, or None if the character was not muted.
+    pub fn unmute(&mut self, character_name: &str) -> Option<ChatMuteStorage> {
+        self.mutes.remove(&character_name.to_lowercase())
+    }
+
+    pub fn get_spam_mute_duration(&self) -> Duration {
+        self.spam_mute_duration
+    }
+
+    /// Masks each word which contains a banned word, or returns None if the
+    /// message should be blocked.
+    pub fn filter_text(&self, text: &str) -> Option<String> {
+        if self.banned_words.is_empty() {
+            return Some(text.to_string());
+        }
+
+        let mut contains_banned_word = false;
+        let filtered: Vec<String> = text
+            .split(' ')
+            .map(|word| {
+                let normalized_word = normalize_name(word);
+                if self
+                    .banned_words
+                    .iter()
+                    .any(|banned_word| normalized_word.contains(banned_word.as_str()))
+                {
+                    contains_banned_word = true;
+                    "*".repeat(word.chars().count())
+                } else {
+                    word.to_string()
+                }
+            })
+            .collect();
+
+        match (contains_banned_word, self.banned_word_action) {
+            (true, BannedWordAction::Block) => None,
+            _ => Some(filtered.join(" ")),
+        }
+    }
+
+    /// Records a message sent by the character, returning true when it is the
+    /// same message repeated too many times within the spam window.
+    pub fn is_spam(&mut self, entity: Entity, text: &str, now: Instant) -> bool {
+        if self.spam_repeat_count == 0 {
+            return false;
+        }
+
+        let window = self.spam_repeat_window;
+        self.recent_messages
+            .retain(|_, recent| now.duration_since(recent.first_sent) < window);
+
+        let text = normalize_name(text.trim());
+        let recent = self
+            .recent_messages
+            .entry(entity)
+            .or_insert_with(|| RecentMessage {
+                text: text.clone(),
+                count: 0,
+                first_sent: now,
+            });
+        if recent.text != text {
+            *recent = RecentMessage {
+                text,
+                count: 0,
+                first_sent: now,
+            };
+        }
+        recent.count += 1;
+
+        let is_spam = recent.count >= self.spam_repeat_count;
+        if is_spam {
+            self.recent_messages.remove(&entity);
+        }
+        is_spam
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    math::{Vec3, Vec3Swizzles},
+    prelude::{Entity, Resource},
+};
+use chrono::{DateTime, Utc};
+
+use crate::game::{
+    events::SuspicionSignal,
+    resources::CheatDetectionConfig,
+    storage::{
+        storage_name_key,
+        suspicion::{SuspicionStorage, SuspicionStorageError},
+    },
+};
+
+/// Reported positions within this distance are always accepted, so a
+/// character which is slowed or stopped can still correct small differences
+const MIN_MOVE_COLLISION_DISTANCE: f32 = 200.0;
+
+struct RecentAttackRequests {
+    first_request: Instant,
+    count: u32,
+}
+
+struct SuspicionSession {
+    account_key: String,
+    score: u32,
+}
+
+/// Checks client actions for cheating and keeps the suspicion scores, both
+/// for the current session of each client and the stored score of their
+/// account.
+#[derive(Resource)]
+pub struct CheatDetection {
+    config: CheatDetectionConfig,
+    attack_requests: HashMap<Entity, RecentAttackRequests>,
+    sessions: HashMap<Entity, SuspicionSession>,
+
+    /// Suspicion of accounts with a session or an unsaved change, by the
+    /// storage key of the account name
+    accounts: HashMap<String, SuspicionStorage>,
+}
+
+impl CheatDetection {
+    pub fn new(config: &CheatDetectionConfig) -> Self {
+        Self {
+            config: config.clone(),
+            attack_requests: HashMap::new(),
+            sessions: HashMap::new(),
+            accounts: HashMap::new(),
+        }
+    }
+
+    /// Returns false when the client has sent more attack requests than it
+    /// could attack within the time of one attack.
+    pub fn check_attack_request(
+        &mut self,
+        entity: Entity,
+        now: Instant,
+        attack_speed: i32,
+    ) -> bool {
+        let attack_duration = Duration::from_secs_f32(100.0 / attack_speed.max(30) as f32);
+        let recent = self
+            .attack_requests
+            .entry(entity)
+            .or_insert(RecentAttackRequests {
+                first_request: now,
+                count: 0,
+            });
+
+        if now.saturating_duration_since(recent.first_request) >= attack_duration {
+            recent.first_request = now;
+            recent.count = 0;
+        }
+
+        recent.count += 1;
+        recent.count <= self.config.max_attack_requests
+    }
+
+    /// Returns false when a position reported by the client is further from
+    /// the server's position than the character could have moved.
+    pub fn check_move_collision(&self, position: Vec3, reported: Vec3, move_speed: f32) -> bool {
+        let max_distance =
+            (move_speed * self.config.movement_tolerance_secs).max(MIN_MOVE_COLLISION_DISTANCE);
+        position.xy().distance(reported.xy()) <= max_distance
+    }
+
+    /// Returns how long after using an item it can be used again without
+    /// being suspicious.
+    pub fn get_item_cooldown(&self, cooldown: Duration) -> Duration {
+        cooldown.mul_f32(self.config.item_cooldown_tolerance)
+    }
+
+    pub fn audit_score(&self) -> u32 {
+        self.config.audit_score
+    }
+
+    pub fn kick_score(&self) -> Option<u32> {
+        self.config.kick_score
+    }
+
+    fn get_weight(&self, signal: SuspicionSignal) -> u32 {
+        match signal {
+            SuspicionSignal::AttackRate => self.config.attack_rate_weight,
+            SuspicionSignal::ItemUseRate => self.config.item_use_rate_weight,
+            SuspicionSignal::MovementSpeed => self.config.movement_speed_weight,
+            SuspicionSignal::MalformedPacket => self.config.malformed_packet_weight,
+            SuspicionSignal::ClientIntegrity => self.config.client_integrity_weight,
+        }
+    }
+
+    fn get_account_mut(&mut self, account_name: &str) -> &mut SuspicionStorage {
+        self.accounts
+            .entry(storage_name_key(account_name))
+            .or_insert_with(|| load_suspicion(account_name))
+    }
+
+    /// Adds a signal to the session and account scores, returning the new
+    /// session score.
+    pub fn add_signal(
+        &mut self,
+        entity: Entity,
+        account_name: &str,
+        signal: SuspicionSignal,
+        now: DateTime<Utc>,
+    ) -> u32 {
+        let weight = self.get_weight(signal);
+        self.get_account_mut(account_name)
+            .add_signal(signal, weight, now);
+
+        let session = self
+            .sessions
+            .entry(entity)
+            .or_insert_with(|| SuspicionSession {
+                account_key: storage_name_key(account_name),
+                score: 0,
+            });
+        session.score = session.score.saturating_add(weight);
+        session.score
+    }
+
+    /// Records that the client was kicked, the next session starts from a
+    /// score of zero.
+    pub fn add_kick(&mut self, entity: Entity, account_name: &str) {
+        self.get_account_mut(account_name).kicks += 1;
+        self.sessions.remove(&entity);
+        self.attack_requests.remove(&entity);
+    }
+
+    pub fn get_account(&self, account_name: &str) -> SuspicionStorage {
+        self.accounts
+            .get(&storage_name_key(account_name))
+            .cloned()
+            .unwrap_or_else(|| load_suspicion(account_name))
+    }
+
+    pub fn clear_account(&mut self, account_name: &str) -> SuspicionStorage {
+        let suspicion = SuspicionStorage::default();
+        self.accounts
+            .insert(storage_name_key(account_name), suspicion.clone());
+        suspicion
+    }
+
+    /// Removes the sessions of clients which have disconnected, and accounts
+    /// which no longer have a session once `is_saved` says their suspicion
+    /// has been written to storage. `is_saved` is given the account's storage
+    /// key.
+    pub fn retain(
+        &mut self,
+        is_connected: impl Fn(Entity) -> bool,
+        is_saved: impl Fn(&str) -> bool,
+    ) {
+        self.attack_requests
+            .retain(|entity, _| is_connected(*entity));
+        self.sessions.retain(|entity, _| is_connected(*entity));
+
+        let sessions = &self.sessions;
+        self.accounts.retain(|account_key, _| {
+            sessions
+                .values()
+                .any(|session| &session.account_key == account_key)
+                || !is_saved(account_key)
+        });
+    }
+}
+
+fn load_suspicion(account_name: &str) -> SuspicionStorage {
+    match SuspicionStorage::try_load(account_name) {
+        Ok(suspicion) => suspicion,
+        Err(error) => {
+            if !matches!(
+                error.downcast_ref::<SuspicionStorageError>(),
+                Some(SuspicionStorageError::NotFound)
+            ) {
+                log::error!(
+                    "Failed to load suspicion for account {} with error {:?}",
+                    account_name,
+                    error
+                );
+            }
+            SuspicionStorage::default()
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use bevy::{ecs::prelude::Entity, prelude::Resource};
+
+pub struct ClanWarDeclaration {
+    pub declaring_clan: Entity,
+    pub target_clan: Entity,
+    pub expire_time: Instant,
+}
+
+pub struct ClanWar {
+    pub clans: [Entity; 2],
+    pub scores: [u32; 2],
+    pub end_time: Instant,
+}
+
+impl ClanWar {
+    pub fn clan_index(&self, clan: Entity) -> Option<usize> {
+        self.clans.iter().position(|war_clan| *war_clan == clan)
+    }
+}
+
+/// Pending war declarations and the currently active clan wars, a clan can only
+/// be in one active war at a time.
+#[derive(Resource)]
+pub struct ClanWars {
+    pub declarations: Vec<ClanWarDeclaration>,
+    pub wars: Vec<ClanWar>,
+}
+
+impl ClanWars {
+    pub fn new() -> Self {
+        Self {
+            declarations: Vec::new(),
+            wars: Vec::new(),
+        }
+    }
+
+    pub fn find_war(&self, clan: Entity) -> Option<&ClanWar> {
+        self.wars.iter().find(|war| war.clan_index(clan).is_some())
+    }
+
+    pub fn find_war_mut(&mut self, clan: Entity) -> Option<&mut ClanWar> {
+        self.wars
+            .iter_mut()
+            .find(|war| war.clan_index(clan).is_some())
+    }
+
+    pub fn is_at_war(&self, clan: Option<Entity>, other_clan: Option<Entity>) -> bool {
+        let (Some(clan), Some(other_clan)) = (clan, other_clan) else {
+            return false;
+        };
+
+        clan != other_clan
+            && self
+                .find_war(clan)
+                .map_or(false, |war| war.clan_index(other_clan).is_some())
+    }
+}
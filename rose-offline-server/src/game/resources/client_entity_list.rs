@@ -10,6 +10,23 @@ use crate::game::components::{ClientEntity, ClientEntityId, ClientEntitySector,
 
 const MAX_CLIENT_ENTITY_ID: usize = 4096;
 
+// Item drops are only visible within this distance, rather than from every
+// adjacent sector, as a client has no interest in drops it cannot reach
+const ITEM_DROP_INTEREST_RADIUS: f32 = 4000.0;
+
+// A visible entity with an interest radius stays visible until it is this much
+// further away, so entities at the edge are not repeatedly spawned and removed
+const INTEREST_RADIUS_LEAVE_MULTIPLIER: f32 = 1.25;
+
+// Only entity types which never move can have an interest radius, as moving
+// within a sector does not change the zone's visibility version
+fn get_interest_radius(entity_type: ClientEntityType) -> Option<f32> {
+    match entity_type {
+        ClientEntityType::ItemDrop => Some(ITEM_DROP_INTEREST_RADIUS),
+        ClientEntityType::Character | ClientEntityType::Monster | ClientEntityType::Npc => None,
+    }
+}
+
 pub type ClientEntitySet = BitArr!(for MAX_CLIENT_ENTITY_ID);
 
 #[derive(Clone, Default)]
@@ -69,6 +86,16 @@ pub struct ClientEntityZone {
     // The list of entities leaving the zone, this is so we can process any
     // visibility changes before freeing the entity id
     leaving_entities: Vec<ClientEntityId>,
+
+    // The list of entities whose entity type has an interest radius
+    interest_radius_entities: ClientEntitySet,
+
+    // The list of entities which are not visible to other clients
+    hidden_entities: ClientEntitySet,
+
+    // Incremented whenever an entity joins or leaves a sector, visibility only
+    // needs to be updated when this or the client's position has changed
+    visibility_version: u64,
 }
 
 impl ClientEntityZone {
@@ -88,6 +115,9 @@ impl ClientEntityZone {
             ],
             entities: vec![None; MAX_CLIENT_ENTITY_ID],
             leaving_entities: Vec::new(),
+            interest_radius_entities: Default::default(),
+            hidden_entities: Default::default(),
+            visibility_version: 0,
         }
     }
 
@@ -122,10 +152,62 @@ impl ClientEntityZone {
         self.get_sector(sector).get_visible_entities()
     }
 
+    pub fn get_visibility_version(&self) -> u64 {
+        self.visibility_version
+    }
+
+    /// Hides the entity from every other client, hidden entities are not
+    /// returned by `get_visible_entities` but can still see other entities.
+    pub fn set_entity_hidden(&mut self, id: ClientEntityId, hidden: bool) {
+        if self.hidden_entities[id.0] != hidden {
+            self.hidden_entities.set(id.0, hidden);
+            self.visibility_version = self.visibility_version.wrapping_add(1);
+        }
+    }
+
+    /// Returns the entities visible from `position`, which are the entities in
+    /// adjacent sectors that are also within their entity type's interest
+    /// radius. Entities in `visible_entities` are kept until they are further
+    /// than the interest radius, to avoid flickering at the edge. Hidden
+    /// entities are never visible.
+    pub fn get_visible_entities(
+        &self,
+        sector: UVec2,
+        position: Vec3,
+        visible_entities: &ClientEntitySet,
+    ) -> ClientEntitySet {
+        let mut result = *self.get_sector_visible_entities(sector) & !self.hidden_entities;
+
+        for index in (result & self.interest_radius_entities).iter_ones() {
+            let Some((_, client_entity, entity_position)) = self.entities[index].as_ref() else {
+                continue;
+            };
+            let Some(mut interest_radius) = get_interest_radius(client_entity.entity_type) else {
+                continue;
+            };
+
+            if visible_entities[index] {
+                interest_radius *= INTEREST_RADIUS_LEAVE_MULTIPLIER;
+            }
+
+            if position.xy().distance_squared(entity_position.xy())
+                > interest_radius * interest_radius
+            {
+                result.set(index, false);
+            }
+        }
+
+        result
+    }
+
     pub fn get_entity(&self, id: ClientEntityId) -> Option<&(Entity, ClientEntity, Vec3)> {
         self.entities[id.0].as_ref()
     }
 
+    pub fn is_entity_leaving(&self, id: ClientEntityId) -> bool {
+        self.leaving_entities.contains(&id)
+    }
+
     fn for_each_visible_sector<F>(&mut self, sector: UVec2, mut f: F)
     where
         F: FnMut(&mut ClientEntityZoneSector),
@@ -143,6 +225,8 @@ impl ClientEntityZone {
     }
 
     fn join_sector(&mut self, sector: UVec2, id: ClientEntityId) {
+        self.visibility_version = self.visibility_version.wrapping_add(1);
+
         // Join the sector
         self.get_sector_mut(sector).join_sector(id);
 
@@ -151,6 +235,8 @@ impl ClientEntityZone {
     }
 
     fn leave_sector(&mut self, sector: UVec2, id: ClientEntityId) {
+        self.visibility_version = self.visibility_version.wrapping_add(1);
+
         // Leave the sector
         self.get_sector_mut(sector).leave_sector(id);
 
@@ -179,6 +265,9 @@ impl ClientEntityZone {
 
         // Join zone
         *free_slot = Some((entity, client_entity.clone(), position));
+        if get_interest_radius(entity_type).is_some() {
+            self.interest_radius_entities.set(free_index, true);
+        }
 
         // Join sector
         self.join_sector(sector, client_entity_id);
@@ -236,6 +325,8 @@ impl ClientEntityZone {
         // Free the entity id
         for id in self.leaving_entities.iter() {
             self.entities[id.0] = None;
+            self.interest_radius_entities.set(id.0, false);
+            self.hidden_entities.set(id.0, false);
         }
 
         self.leaving_entities.clear();
@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::{Entity, Resource};
+use rand::seq::index;
+
+use crate::game::resources::ClientIntegrityConfig;
+
+struct PendingChallenge {
+    /// Index into `file_hashes` of each file the client was asked to hash
+    files: Vec<usize>,
+    deadline: Instant,
+}
+
+/// The integrity challenges sent to game clients which have not responded yet.
+#[derive(Resource)]
+pub struct ClientIntegrity {
+    sample_size: usize,
+    response_timeout: Duration,
+    kick_on_mismatch: bool,
+    file_hashes: Vec<(String, [u8; 32])>,
+    pending: HashMap<Entity, PendingChallenge>,
+}
+
+impl ClientIntegrity {
+    pub fn new(config: &ClientIntegrityConfig) -> Self {
+        Self {
+            sample_size: config.sample_size,
+            response_timeout: Duration::from_secs(config.response_timeout_secs),
+            kick_on_mismatch: config.kick_on_mismatch,
+            file_hashes: config.file_hashes.clone(),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn kick_on_mismatch(&self) -> bool {
+        self.kick_on_mismatch
+    }
+
+    /// Chooses a random sample of files for the client to hash, returns their
+    /// paths to send to the client.
+    pub fn start_challenge(&mut self, entity: Entity, now: Instant) -> Vec<String> {
+        let amount = self.sample_size.min(self.file_hashes.len());
+        if amount == 0 {
+            return Vec::new();
+        }
+
+        let files =
+            index::sample(&mut rand::thread_rng(), self.file_hashes.len(), amount).into_vec();
+        let paths = files
+            .iter()
+            .map(|&index| self.file_hashes[index].0.clone())
+            .collect();
+
+        self.pending.insert(
+            entity,
+            PendingChallenge {
+                files,
+                deadline: now + self.response_timeout,
+            },
+        );
+        paths
+    }
+
+    /// Returns the paths of the files whose hash did not match, or None if
+    /// the client did not have a challenge to respond to.
+    pub fn check_response(&mut self, entity: Entity, hashes: &[[u8; 32]]) -> Option<Vec<String>> {
+        let challenge = self.pending.remove(&entity)?;
+        Some(
+            challenge
+                .files
+                .iter()
+                .enumerate()
+                .filter(|&(response_index, &file_index)| {
+                    hashes.get(response_index) != Some(&self.file_hashes[file_index].1)
+                })
+                .map(|(_, &file_index)| self.file_hashes[file_index].0.clone())
+                .collect(),
+        )
+    }
+
+    /// Removes the challenges which were not responded to in time, returning
+    /// each client with the paths it did not respond for.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<(Entity, Vec<String>)> {
+        let expired: Vec<Entity> = self
+            .pending
+            .iter()
+            .filter(|(_, challenge)| challenge.deadline <= now)
+            .map(|(entity, _)| *entity)
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|entity| {
+                let challenge = self.pending.remove(&entity)?;
+                Some((
+                    entity,
+                    challenge
+                        .files
+                        .iter()
+                        .map(|&index| self.file_hashes[index].0.clone())
+                        .collect(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Forgets the challenges of clients which have disconnected.
+    pub fn retain(&mut self, is_connected: impl Fn(Entity) -> bool) {
+        self.pending.retain(|entity, _| is_connected(*entity));
+    }
+}
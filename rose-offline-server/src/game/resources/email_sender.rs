@@ -0,0 +1,124 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context};
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An SMTP relay which accepts mail without authentication or TLS, such as a
+/// mail server running on the same host.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpConfig {
+    /// Address of the SMTP server, e.g. 127.0.0.1:25
+    pub server: String,
+    pub from: String,
+}
+
+/// Sends emails to players on a background thread so that a slow mail server
+/// does not stall the game world.
+#[derive(Resource)]
+pub struct EmailSender {
+    config: SmtpConfig,
+}
+
+impl EmailSender {
+    pub fn new(config: SmtpConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn send(&self, to: String, subject: String, body: String) {
+        let config = self.config.clone();
+        std::thread::spawn(move || {
+            if let Err(error) = send_smtp(&config, &to, &subject, &body) {
+                log::error!("Failed to send email to {} with error {:?}", to, error);
+            } else {
+                log::info!("Sent email \"{}\" to {}", subject, to);
+            }
+        });
+    }
+}
+
+struct SmtpConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl SmtpConnection {
+    /// Reads a possibly multi-line reply and checks it has the expected code
+    fn expect_reply(&mut self, expected_code: u16) -> Result<(), anyhow::Error> {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(anyhow!("SMTP connection closed"));
+            }
+
+            let code = line
+                .get(..3)
+                .and_then(|code| code.parse::<u16>().ok())
+                .ok_or_else(|| anyhow!("Invalid SMTP reply {}", line.trim_end()))?;
+            if code != expected_code {
+                return Err(anyhow!("Unexpected SMTP reply {}", line.trim_end()));
+            }
+
+            // A dash after the code means the reply continues on the next line
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+
+    fn command(&mut self, command: &str, expected_code: u16) -> Result<(), anyhow::Error> {
+        self.writer.write_all(command.as_bytes())?;
+        self.writer.write_all(b"\r\n")?;
+        self.expect_reply(expected_code)
+            .with_context(|| format!("SMTP command {} failed", command))
+    }
+}
+
+fn send_smtp(
+    config: &SmtpConfig,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), anyhow::Error> {
+    let stream = TcpStream::connect(&config.server)
+        .with_context(|| format!("Failed to connect to SMTP server {}", config.server))?;
+    stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(SMTP_TIMEOUT))?;
+
+    let mut connection = SmtpConnection {
+        reader: BufReader::new(stream.try_clone()?),
+        writer: stream,
+    };
+    connection.expect_reply(220)?;
+    connection.command("HELO rose-offline", 250)?;
+    connection.command(&format!("MAIL FROM:<{}>", config.from), 250)?;
+    connection.command(&format!("RCPT TO:<{}>", to), 250)?;
+    connection.command("DATA", 354)?;
+
+    let mut message = format!(
+        "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\n\r\n",
+        config.from, to, subject
+    );
+    for line in body.lines() {
+        // Lines starting with a dot must be escaped so they do not end the message
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push_str(".\r\n");
+    connection.writer.write_all(message.as_bytes())?;
+    connection
+        .expect_reply(250)
+        .context("SMTP server did not accept the message")?;
+    connection.command("QUIT", 221)?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::{collections::HashMap, time::Instant};
+
+use bevy::{ecs::prelude::Entity, prelude::Resource};
+
+use crate::game::components::Position;
+
+#[derive(Copy, Clone, Debug)]
+pub enum EventZoneState {
+    /// Players can join until `start_time`
+    Signup { start_time: Instant },
+
+    /// The event ends at `end_time`, or when a team reaches the winning score
+    Running { end_time: Instant },
+}
+
+pub struct EventZoneParticipant {
+    pub entity: Entity,
+    pub team_index: usize,
+
+    /// Where the participant is returned to when the event ends
+    pub return_position: Option<Position>,
+
+    /// True once the participant has arrived in the event zone, after which
+    /// leaving the zone also leaves the event
+    pub has_entered_zone: bool,
+}
+
+/// The event which is currently open for signup or running.
+pub struct ActiveEventZone {
+    pub name: String,
+    pub state: EventZoneState,
+    pub participants: Vec<EventZoneParticipant>,
+    pub scores: Vec<u32>,
+
+    /// The participant carrying each team's flag in capture the flag
+    pub flag_carriers: Vec<Option<Entity>>,
+
+    /// The team which last held the hill in king of the hill, and when it was
+    /// last given a point
+    pub hill_holder: Option<usize>,
+    pub last_hill_score_time: Option<Instant>,
+}
+
+impl ActiveEventZone {
+    pub fn new(name: String, num_teams: usize, start_time: Instant) -> Self {
+        Self {
+            name,
+            state: EventZoneState::Signup { start_time },
+            participants: Vec::new(),
+            scores: vec![0; num_teams],
+            flag_carriers: vec![None; num_teams],
+            hill_holder: None,
+            last_hill_score_time: None,
+        }
+    }
+
+    pub fn find_participant(&self, entity: Entity) -> Option<&EventZoneParticipant> {
+        self.participants
+            .iter()
+            .find(|participant| participant.entity == entity)
+    }
+
+    /// Returns the index of the team with the highest score, or None if the
+    /// highest score is shared or no team has scored.
+    pub fn winning_team(&self) -> Option<usize> {
+        let best_score = self.scores.iter().copied().max().unwrap_or(0);
+        if best_score == 0
+            || self
+                .scores
+                .iter()
+                .filter(|score| **score == best_score)
+                .count()
+                > 1
+        {
+            return None;
+        }
+
+        self.scores.iter().position(|score| *score == best_score)
+    }
+}
+
+/// Only one event can be open or running at a time.
+#[derive(Default, Resource)]
+pub struct EventZones {
+    pub active: Option<ActiveEventZone>,
+
+    /// When each scheduled event will next open for signup
+    pub next_scheduled: HashMap<String, Instant>,
+}
@@ -1,9 +1,1974 @@
-use bevy::prelude::Resource;
+use bevy::{math::Vec3, prelude::Resource};
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
+
+use rand::Rng;
+
+use rose_data::{
+    ItemDatabase, ItemReference, NpcId, SkillData, SkillId, WarpGateId, ZoneId, ZoneTimeOfDay,
+    ZoneWeather,
+};
+use rose_game_common::components::{AbilityValues, CharacterGender, ClanMark, DroppedItem, Money};
+
+use crate::game::{
+    bots::BotProfile,
+    resources::{SmtpConfig, StorageBackupConfig, WorldRates},
+};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RewardCalendarItem {
+    pub item: ItemReference,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RewardCalendarReward {
+    #[serde(default)]
+    pub items: Vec<RewardCalendarItem>,
+    #[serde(default)]
+    pub money: Money,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RewardCalendarConfig {
+    /// Rewards for each consecutive day, the track repeats once the end is reached
+    pub days: Vec<RewardCalendarReward>,
+
+    /// Every streak_bonus_interval consecutive days the streak_bonus is also awarded
+    #[serde(default)]
+    pub streak_bonus_interval: u32,
+    #[serde(default)]
+    pub streak_bonus: RewardCalendarReward,
+}
+
+/// Items which become bound to a character, bound items can not be traded,
+/// dropped, or sold in a personal store.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ItemBindingConfig {
+    #[serde(default)]
+    pub bind_on_equip: Vec<ItemReference>,
+    #[serde(default)]
+    pub bind_on_pickup: Vec<ItemReference>,
+}
+
+impl ItemBindingConfig {
+    pub fn is_bind_on_equip(&self, item: ItemReference) -> bool {
+        self.bind_on_equip.contains(&item)
+    }
+
+    pub fn is_bind_on_pickup(&self, item: ItemReference) -> bool {
+        self.bind_on_pickup.contains(&item)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CharacterCreationItem {
+    pub item: ItemReference,
+    #[serde(default = "default_item_quantity")]
+    pub quantity: u32,
+}
+
+fn default_item_quantity() -> u32 {
+    1
+}
+
+/// A starting kit given to newly created characters, a kit with no gender or
+/// job is given to every character.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CharacterCreationKit {
+    #[serde(default)]
+    pub gender: Option<CharacterGender>,
+    #[serde(default)]
+    pub job: Option<u16>,
+    #[serde(default)]
+    pub equipped_items: Vec<ItemReference>,
+    #[serde(default)]
+    pub items: Vec<CharacterCreationItem>,
+    #[serde(default)]
+    pub skills: Vec<SkillId>,
+}
+
+fn deserialize_regex_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Regex>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+/// Overrides for the character creation data loaded from the game data.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CharacterCreationConfig {
+    /// Start zone, if no start_position is set the zone's start position is used
+    #[serde(default)]
+    pub start_zone: Option<ZoneId>,
+    #[serde(default)]
+    pub start_position: Option<Vec3>,
+    #[serde(default)]
+    pub start_level: Option<u32>,
+
+    /// Remove the default starting items before adding the kits
+    #[serde(default)]
+    pub replace_default_items: bool,
+    #[serde(default)]
+    pub kits: Vec<CharacterCreationKit>,
+
+    /// When set only these faces and hairs may be chosen
+    #[serde(default)]
+    pub allowed_faces: Option<Vec<u8>>,
+    #[serde(default)]
+    pub allowed_hairs: Option<Vec<u8>>,
+}
+
+impl CharacterCreationConfig {
+    pub fn is_face_allowed(&self, face: u8) -> bool {
+        self.allowed_faces
+            .as_ref()
+            .map_or(true, |faces| faces.contains(&face))
+    }
+
+    pub fn is_hair_allowed(&self, hair: u8) -> bool {
+        self.allowed_hairs
+            .as_ref()
+            .map_or(true, |hairs| hairs.contains(&hair))
+    }
+}
+
+fn default_reserve_npc_names() -> bool {
+    true
+}
+
+/// Rules for validating character and clan names.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NameFilterConfig {
+    /// Names matching any pattern, or containing any word, are rejected
+    #[serde(default, deserialize_with = "deserialize_regex_list")]
+    pub blacklist_patterns: Vec<Regex>,
+    #[serde(default)]
+    pub blacklist_words: Vec<String>,
+
+    /// Names which can not be used, such as GM names
+    #[serde(default)]
+    pub reserved_names: Vec<String>,
+
+    /// Prevent names which match the name of an NPC placed in a zone
+    #[serde(default = "default_reserve_npc_names")]
+    pub reserve_npc_names: bool,
+}
+
+impl Default for NameFilterConfig {
+    fn default() -> Self {
+        Self {
+            blacklist_patterns: Vec::new(),
+            blacklist_words: Vec::new(),
+            reserved_names: Vec::new(),
+            reserve_npc_names: default_reserve_npc_names(),
+        }
+    }
+}
+
+fn default_character_search_distance() -> f32 {
+    6000.0
+}
+
+fn default_min_scale() -> f32 {
+    0.5
+}
+
+fn default_max_scale() -> f32 {
+    2.0
+}
+
+fn default_scale_per_character() -> f32 {
+    0.25
+}
+
+/// Scales the monster limit and respawn interval of each spawn point by the
+/// number of characters near it, so crowded spawns get more monsters and
+/// spawns with no characters nearby do less work.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MonsterSpawnScalingConfig {
+    /// Distance around a spawn point within which characters are counted
+    #[serde(default = "default_character_search_distance")]
+    pub character_search_distance: f32,
+
+    /// Scale used when there are no characters nearby, 0 stops the spawn point
+    #[serde(default = "default_min_scale")]
+    pub min_scale: f32,
+
+    #[serde(default = "default_max_scale")]
+    pub max_scale: f32,
+
+    /// Scale added for each character nearby after the first
+    #[serde(default = "default_scale_per_character")]
+    pub scale_per_character: f32,
+}
+
+impl Default for MonsterSpawnScalingConfig {
+    fn default() -> Self {
+        Self {
+            character_search_distance: default_character_search_distance(),
+            min_scale: default_min_scale(),
+            max_scale: default_max_scale(),
+            scale_per_character: default_scale_per_character(),
+        }
+    }
+}
+
+impl MonsterSpawnScalingConfig {
+    pub fn get_scale(&self, num_characters: usize) -> f32 {
+        if num_characters == 0 {
+            self.min_scale
+        } else {
+            (1.0 + (num_characters - 1) as f32 * self.scale_per_character)
+                .clamp(self.min_scale, self.max_scale)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EliteMonsterZoneChance {
+    pub zone: ZoneId,
+    pub chance: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EliteMonsterNpcChance {
+    pub npc: NpcId,
+    pub chance: f32,
+}
+
+fn default_elite_health_multiplier() -> f32 {
+    3.0
+}
+
+fn default_elite_attack_multiplier() -> f32 {
+    1.5
+}
+
+fn default_elite_defence_multiplier() -> f32 {
+    1.5
+}
+
+fn default_elite_experience_multiplier() -> f32 {
+    3.0
+}
+
+fn default_elite_drop_count() -> usize {
+    3
+}
+
+/// The chance for monsters spawned from a spawn point to be promoted to an
+/// elite variant, and how elite monsters are scaled.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EliteMonsterConfig {
+    /// Chance from 0.0 to 1.0 used when there is no zone or npc override
+    #[serde(default)]
+    pub chance: f32,
+
+    /// Overrides the chance for monsters spawned in a zone
+    #[serde(default)]
+    pub zones: Vec<EliteMonsterZoneChance>,
+
+    /// Overrides the chance for a monster, takes priority over the zone chance
+    #[serde(default)]
+    pub npcs: Vec<EliteMonsterNpcChance>,
+
+    #[serde(default = "default_elite_health_multiplier")]
+    pub health_multiplier: f32,
+
+    #[serde(default = "default_elite_attack_multiplier")]
+    pub attack_multiplier: f32,
+
+    /// Multiplier for both defence and resistance
+    #[serde(default = "default_elite_defence_multiplier")]
+    pub defence_multiplier: f32,
+
+    #[serde(default = "default_elite_experience_multiplier")]
+    pub experience_multiplier: f32,
+
+    /// How many times the drop table is rolled when an elite monster is killed
+    #[serde(default = "default_elite_drop_count")]
+    pub drop_count: usize,
+}
+
+impl EliteMonsterConfig {
+    pub fn get_chance(&self, npc_id: NpcId, zone_id: ZoneId) -> f32 {
+        if let Some(npc_chance) = self.npcs.iter().find(|npc_chance| npc_chance.npc == npc_id) {
+            npc_chance.chance
+        } else if let Some(zone_chance) = self
+            .zones
+            .iter()
+            .find(|zone_chance| zone_chance.zone == zone_id)
+        {
+            zone_chance.chance
+        } else {
+            self.chance
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ZoneWeatherChance {
+    pub weather: ZoneWeather,
+    pub weight: u32,
+}
+
+/// The weathers which a zone can have, zones with no config are always clear.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ZoneWeatherConfig {
+    pub zone: ZoneId,
+    pub weather: Vec<ZoneWeatherChance>,
+}
+
+/// The rules of a zone, zones with no config are towns with no other rules.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ZoneRules {
+    pub zone: ZoneId,
+
+    /// Characters can not be attacked or damaged by other characters or monsters
+    #[serde(default)]
+    pub safe_zone: bool,
+
+    /// NPC stores and the bank can only be used in towns
+    #[serde(default = "default_town")]
+    pub town: bool,
+
+    /// Characters below this level can not teleport into the zone
+    #[serde(default)]
+    pub min_level: u32,
+}
+
+fn default_town() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ZoneRulesConfig {
+    #[serde(default)]
+    pub zones: Vec<ZoneRules>,
+}
+
+/// Multiplies the drop rate when all of the set conditions match, a condition
+/// which is not set matches everything.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ZoneEnvironmentDropRate {
+    #[serde(default)]
+    pub zone: Option<ZoneId>,
+    #[serde(default)]
+    pub time_of_day: Option<ZoneTimeOfDay>,
+    #[serde(default)]
+    pub weather: Option<ZoneWeather>,
+    pub rate: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NpcTimeOfDayConfig {
+    pub npc: NpcId,
+    pub times_of_day: Vec<ZoneTimeOfDay>,
+}
+
+fn default_weather_duration_secs() -> u64 {
+    30 * 60
+}
+
+/// How the time of day and weather of each zone affect the game.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ZoneEnvironmentConfig {
+    /// How long a weather lasts before the next weather is chosen
+    #[serde(default = "default_weather_duration_secs")]
+    pub weather_duration_secs: u64,
+
+    #[serde(default)]
+    pub weather: Vec<ZoneWeatherConfig>,
+
+    #[serde(default)]
+    pub drop_rates: Vec<ZoneEnvironmentDropRate>,
+
+    /// Monsters which only spawn at these times of day, such as night only monsters
+    #[serde(default)]
+    pub monster_spawn_times: Vec<NpcTimeOfDayConfig>,
+
+    /// NPC stores which are only open at these times of day
+    #[serde(default)]
+    pub npc_store_times: Vec<NpcTimeOfDayConfig>,
+}
+
+impl Default for ZoneEnvironmentConfig {
+    fn default() -> Self {
+        Self {
+            weather_duration_secs: default_weather_duration_secs(),
+            weather: Vec::new(),
+            drop_rates: Vec::new(),
+            monster_spawn_times: Vec::new(),
+            npc_store_times: Vec::new(),
+        }
+    }
+}
+
+impl ZoneEnvironmentConfig {
+    pub fn get_weather_duration(&self) -> Duration {
+        Duration::from_secs(self.weather_duration_secs)
+    }
+
+    pub fn choose_weather(&self, zone_id: ZoneId) -> ZoneWeather {
+        let Some(zone_weather) = self
+            .weather
+            .iter()
+            .find(|zone_weather| zone_weather.zone == zone_id)
+        else {
+            return ZoneWeather::Clear;
+        };
+
+        let total_weight: u32 = zone_weather
+            .weather
+            .iter()
+            .map(|chance| chance.weight)
+            .sum();
+        if total_weight == 0 {
+            return ZoneWeather::Clear;
+        }
+
+        let mut roll = rand::thread_rng().gen_range(0..total_weight);
+        for chance in zone_weather.weather.iter() {
+            if roll < chance.weight {
+                return chance.weather;
+            }
+            roll -= chance.weight;
+        }
+
+        ZoneWeather::Clear
+    }
+
+    pub fn get_drop_rate(
+        &self,
+        zone_id: ZoneId,
+        time_of_day: ZoneTimeOfDay,
+        weather: ZoneWeather,
+    ) -> f32 {
+        self.drop_rates
+            .iter()
+            .filter(|drop_rate| {
+                drop_rate.zone.map_or(true, |zone| zone == zone_id)
+                    && drop_rate
+                        .time_of_day
+                        .map_or(true, |drop_time_of_day| drop_time_of_day == time_of_day)
+                    && drop_rate
+                        .weather
+                        .map_or(true, |drop_weather| drop_weather == weather)
+            })
+            .map(|drop_rate| drop_rate.rate)
+            .product()
+    }
+
+    pub fn can_monster_spawn(&self, npc_id: NpcId, time_of_day: ZoneTimeOfDay) -> bool {
+        self.monster_spawn_times
+            .iter()
+            .find(|spawn_times| spawn_times.npc == npc_id)
+            .map_or(true, |spawn_times| {
+                spawn_times.times_of_day.contains(&time_of_day)
+            })
+    }
+
+    pub fn is_npc_store_open(&self, npc_id: NpcId, time_of_day: ZoneTimeOfDay) -> bool {
+        self.npc_store_times
+            .iter()
+            .find(|store_times| store_times.npc == npc_id)
+            .map_or(true, |store_times| {
+                store_times.times_of_day.contains(&time_of_day)
+            })
+    }
+}
+
+fn default_teleport_gate_radius() -> f32 {
+    1000.0
+}
+
+/// The area which a teleport gate can be used from.
+#[derive(Clone, Debug, Deserialize)]
+pub struct TeleportGateSource {
+    pub zone: ZoneId,
+    pub position: Vec3,
+    #[serde(default = "default_teleport_gate_radius")]
+    pub radius: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TeleportGateDestination {
+    pub zone: ZoneId,
+    pub position: Vec3,
+}
+
+/// Requirements and fees for using a warp gate, the source and destination
+/// default to the warp gate's zone object and target event position.
+#[derive(Clone, Debug, Deserialize)]
+pub struct TeleportGateConfig {
+    pub warp_gate: WarpGateId,
+    #[serde(default)]
+    pub source: Option<TeleportGateSource>,
+    #[serde(default)]
+    pub destination: Option<TeleportGateDestination>,
+    #[serde(default)]
+    pub min_level: Option<u32>,
+
+    /// The quest switch which must be set, usually by completing a quest
+    #[serde(default)]
+    pub required_quest_switch: Option<usize>,
+    #[serde(default)]
+    pub fee: Money,
+}
+
+fn default_clan_min_level() -> u32 {
+    30
+}
+
+fn default_clan_cost() -> Money {
+    Money(1000000)
+}
+
+fn default_clan_min_name_length() -> usize {
+    2
+}
+
+fn default_clan_max_name_length() -> usize {
+    20
+}
+
+fn default_clan_max_mark_id() -> u16 {
+    255
+}
+
+/// The requirements a character must meet to create a clan.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ClanCreationConfig {
+    #[serde(default = "default_clan_min_level")]
+    pub min_level: u32,
+    #[serde(default = "default_clan_cost")]
+    pub cost: Money,
+
+    /// An item which is taken from the creator's inventory, or None
+    #[serde(default)]
+    pub required_item: Option<ItemReference>,
+
+    /// The allowed length of clan names in characters
+    #[serde(default = "default_clan_min_name_length")]
+    pub min_name_length: usize,
+    #[serde(default = "default_clan_max_name_length")]
+    pub max_name_length: usize,
+
+    /// The highest premade mark background and foreground, which should not
+    /// exceed the number of clan mark images the client has
+    #[serde(default = "default_clan_max_mark_id")]
+    pub max_mark_background: u16,
+    #[serde(default = "default_clan_max_mark_id")]
+    pub max_mark_foreground: u16,
+
+    /// Allow custom marks, which clients can only display when they are
+    /// served by an external clan mark server
+    #[serde(default)]
+    pub allow_custom_marks: bool,
+}
+
+impl ClanCreationConfig {
+    pub fn is_valid_mark(&self, mark: &ClanMark) -> bool {
+        match *mark {
+            ClanMark::Premade {
+                background,
+                foreground,
+            } => {
+                background.get() <= self.max_mark_background
+                    && foreground.get() <= self.max_mark_foreground
+            }
+            ClanMark::Custom { .. } => self.allow_custom_marks,
+        }
+    }
+}
+
+impl Default for ClanCreationConfig {
+    fn default() -> Self {
+        Self {
+            min_level: default_clan_min_level(),
+            cost: default_clan_cost(),
+            required_item: None,
+            min_name_length: default_clan_min_name_length(),
+            max_name_length: default_clan_max_name_length(),
+            max_mark_background: default_clan_max_mark_id(),
+            max_mark_foreground: default_clan_max_mark_id(),
+            allow_custom_marks: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TeleportGatesConfig {
+    #[serde(default)]
+    pub gates: Vec<TeleportGateConfig>,
+}
+
+impl TeleportGatesConfig {
+    pub fn get_gate(&self, warp_gate_id: WarpGateId) -> Option<&TeleportGateConfig> {
+        self.gates
+            .iter()
+            .find(|gate| gate.warp_gate == warp_gate_id)
+    }
+}
+
+fn default_npc_store_restock_interval_secs() -> u64 {
+    60 * 60
+}
+
+/// An item which an NPC store only has a limited quantity of, the store
+/// starts fully stocked and is restocked on a fixed interval.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NpcStoreStockItemConfig {
+    pub npc: NpcId,
+    pub item: ItemReference,
+    pub max_quantity: u32,
+
+    /// How many are added each restock, or None to restock fully
+    #[serde(default)]
+    pub restock_quantity: Option<u32>,
+    #[serde(default = "default_npc_store_restock_interval_secs")]
+    pub restock_interval_secs: u64,
+
+    /// Announce to the store's zone when a rare item is restocked
+    #[serde(default)]
+    pub announce_restock: bool,
+}
+
+impl NpcStoreStockItemConfig {
+    pub fn get_restock_interval(&self) -> Duration {
+        Duration::from_secs(self.restock_interval_secs)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NpcStoreStockConfig {
+    #[serde(default)]
+    pub items: Vec<NpcStoreStockItemConfig>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NpcStoreCurrency {
+    /// Points of `union`, or of the buyer's current union when None
+    UnionPoints {
+        #[serde(default)]
+        union: Option<NonZeroUsize>,
+    },
+
+    /// A stackable item which is taken from the buyer's inventory
+    EventToken {
+        item: ItemReference,
+    },
+
+    HonorPoints,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NpcStoreCurrencyPrice {
+    pub item: ItemReference,
+    pub price: u32,
+}
+
+/// Prices the items of an NPC store tab in a currency other than zuly, items
+/// of the tab which have no price can not be bought.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NpcStoreCurrencyTab {
+    pub npc: NpcId,
+    pub tab_index: usize,
+    pub currency: NpcStoreCurrency,
+    pub prices: Vec<NpcStoreCurrencyPrice>,
+}
+
+impl NpcStoreCurrencyTab {
+    pub fn get_price(&self, item: ItemReference) -> Option<u32> {
+        self.prices
+            .iter()
+            .find(|price| price.item == item)
+            .map(|price| price.price)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NpcStoreCurrenciesConfig {
+    #[serde(default)]
+    pub tabs: Vec<NpcStoreCurrencyTab>,
+}
+
+impl NpcStoreCurrenciesConfig {
+    pub fn get_tab(&self, npc: NpcId, tab_index: usize) -> Option<&NpcStoreCurrencyTab> {
+        self.tabs
+            .iter()
+            .find(|tab| tab.npc == npc && tab.tab_index == tab_index)
+    }
+}
+
+fn default_item_drop_expire_secs() -> u64 {
+    120
+}
+
+fn default_max_item_drops_per_zone() -> Option<usize> {
+    Some(1000)
+}
+
+/// A longer expire time for rare item drops, items match when their quality is
+/// at least `min_quality`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ItemDropRarityExpireTime {
+    pub min_quality: u32,
+    pub expire_secs: u64,
+}
+
+fn default_item_drop_checkpoint_interval_secs() -> u64 {
+    60
+}
+
+/// Item drops with a quality of at least `min_quality` are periodically saved
+/// to storage and restored into their zones when the server starts. Money is
+/// never saved.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ItemDropPersistConfig {
+    pub min_quality: u32,
+
+    #[serde(default = "default_item_drop_checkpoint_interval_secs")]
+    pub checkpoint_interval_secs: u64,
+}
+
+/// How long item drops stay on the ground and how many each zone can hold.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ItemDropConfig {
+    /// How long money and items which match no rarity stay on the ground
+    #[serde(default = "default_item_drop_expire_secs")]
+    pub expire_secs: u64,
+
+    #[serde(default)]
+    pub rarity_expire_times: Vec<ItemDropRarityExpireTime>,
+
+    /// The most item drops a zone can have before the oldest are removed, or
+    /// null for no limit
+    #[serde(default = "default_max_item_drops_per_zone")]
+    pub max_drops_per_zone: Option<usize>,
+
+    /// Keeps rare item drops across server restarts, disabled when null
+    #[serde(default)]
+    pub persist: Option<ItemDropPersistConfig>,
+}
+
+impl Default for ItemDropConfig {
+    fn default() -> Self {
+        Self {
+            expire_secs: default_item_drop_expire_secs(),
+            rarity_expire_times: Vec::new(),
+            max_drops_per_zone: default_max_item_drops_per_zone(),
+            persist: None,
+        }
+    }
+}
+
+fn get_dropped_item_quality(item: &DroppedItem, item_database: &ItemDatabase) -> Option<u32> {
+    match item {
+        DroppedItem::Item(item) => item_database
+            .get_base_item(item.get_item_reference())
+            .map(|base_item| base_item.quality),
+        DroppedItem::Money(_) => None,
+    }
+}
+
+impl ItemDropConfig {
+    /// Returns the expire time of the rarity with the highest `min_quality`
+    /// which the item matches.
+    pub fn get_expire_time(&self, item: &DroppedItem, item_database: &ItemDatabase) -> Duration {
+        let expire_secs = get_dropped_item_quality(item, item_database)
+            .and_then(|quality| {
+                self.rarity_expire_times
+                    .iter()
+                    .filter(|rarity| quality >= rarity.min_quality)
+                    .max_by_key(|rarity| rarity.min_quality)
+            })
+            .map_or(self.expire_secs, |rarity| rarity.expire_secs);
+        Duration::from_secs(expire_secs)
+    }
+
+    /// Returns true if the item drop should be saved to storage.
+    pub fn is_persistent(&self, item: &DroppedItem, item_database: &ItemDatabase) -> bool {
+        let Some(persist) = self.persist.as_ref() else {
+            return false;
+        };
+
+        get_dropped_item_quality(item, item_database)
+            .map_or(false, |quality| quality >= persist.min_quality)
+    }
+}
+
+fn default_skill_chain_falloff() -> f32 {
+    0.75
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkillChainType {
+    /// Jumps from each target to the nearest target within range
+    Chain,
+
+    /// Hits every target along the line from the caster through the target
+    Pierce,
+}
+
+/// A skill which hits further targets after its primary target, each target
+/// takes `falloff` times the damage of the target before it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SkillChainConfig {
+    /// The skill, which also matches every level of the skill
+    pub skill: SkillId,
+    #[serde(rename = "type")]
+    pub chain_type: SkillChainType,
+
+    /// The most targets hit, including the primary target
+    pub max_targets: usize,
+
+    /// How far a chain can jump between targets, or the width of a pierce
+    pub range: f32,
+    #[serde(default = "default_skill_chain_falloff")]
+    pub falloff: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SkillChainsConfig {
+    #[serde(default)]
+    pub skills: Vec<SkillChainConfig>,
+}
+
+impl SkillChainsConfig {
+    pub fn get_skill_chain(&self, skill_data: &SkillData) -> Option<&SkillChainConfig> {
+        self.skills.iter().find(|skill_chain| {
+            skill_chain.skill == skill_data.id
+                || Some(skill_chain.skill) == skill_data.base_skill_id
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkillMovementEffect {
+    /// Cancels the target's command and blocks new commands
+    Stun { duration_secs: f32 },
+
+    /// Prevents the target from moving, but still allows attacks and skills
+    Root { duration_secs: f32 },
+
+    /// Pushes the target directly away from the caster
+    Knockback { distance: f32 },
+}
+
+/// A movement impairing effect applied to every target a skill hits, in
+/// addition to the skill's status effects.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SkillMovementEffectConfig {
+    /// The skill, which also matches every level of the skill
+    pub skill: SkillId,
+    #[serde(flatten)]
+    pub effect: SkillMovementEffect,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SkillMovementEffectsConfig {
+    #[serde(default)]
+    pub skills: Vec<SkillMovementEffectConfig>,
+}
+
+impl SkillMovementEffectsConfig {
+    pub fn iter_skill_effects<'a>(
+        &'a self,
+        skill_data: &'a SkillData,
+    ) -> impl Iterator<Item = SkillMovementEffect> + 'a {
+        self.skills
+            .iter()
+            .filter(|skill_effect| {
+                skill_effect.skill == skill_data.id
+                    || Some(skill_effect.skill) == skill_data.base_skill_id
+            })
+            .map(|skill_effect| skill_effect.effect)
+    }
+}
+
+fn default_chat_channel_enabled() -> bool {
+    true
+}
+
+/// Who can send to a chat channel and how often.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChatChannelConfig {
+    #[serde(default = "default_chat_channel_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub min_level: u32,
+
+    /// How long a character must wait between messages sent to the channel
+    #[serde(default)]
+    pub cooldown_secs: u64,
+}
+
+impl ChatChannelConfig {
+    fn new(min_level: u32, cooldown_secs: u64) -> Self {
+        Self {
+            enabled: true,
+            min_level,
+            cooldown_secs,
+        }
+    }
+
+    pub fn get_cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
+}
+
+fn default_local_chat_channel() -> ChatChannelConfig {
+    ChatChannelConfig::new(0, 0)
+}
+
+fn default_shout_chat_channel() -> ChatChannelConfig {
+    ChatChannelConfig::new(10, 10)
+}
+
+fn default_trade_chat_channel() -> ChatChannelConfig {
+    ChatChannelConfig::new(10, 30)
+}
+
+fn default_announce_chat_channel() -> ChatChannelConfig {
+    ChatChannelConfig::new(0, 0)
+}
+
+/// The chat channels, local chat is sent to nearby characters, shout to the
+/// whole zone, and trade and announcements to every zone.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChatChannelsConfig {
+    #[serde(default = "default_local_chat_channel")]
+    pub local: ChatChannelConfig,
+    #[serde(default = "default_shout_chat_channel")]
+    pub shout: ChatChannelConfig,
+    #[serde(default = "default_trade_chat_channel")]
+    pub trade: ChatChannelConfig,
+
+    /// Announcements are sent with the announce chat command
+    #[serde(default = "default_announce_chat_channel")]
+    pub announce: ChatChannelConfig,
+}
+
+impl Default for ChatChannelsConfig {
+    fn default() -> Self {
+        Self {
+            local: default_local_chat_channel(),
+            shout: default_shout_chat_channel(),
+            trade: default_trade_chat_channel(),
+            announce: default_announce_chat_channel(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BannedWordAction {
+    /// Banned words are replaced with asterisks
+    #[default]
+    Mask,
+
+    /// Messages containing a banned word are not sent
+    Block,
+}
+
+fn default_spam_repeat_count() -> u32 {
+    3
+}
+
+fn default_spam_repeat_window_secs() -> u64 {
+    30
+}
+
+fn default_spam_mute_secs() -> u64 {
+    60
+}
+
+/// The banned word filter and spam detection applied to all chat.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChatModerationConfig {
+    /// Words are matched after folding lookalike characters, in the same way
+    /// as the name filter
+    #[serde(default)]
+    pub banned_words: Vec<String>,
+    #[serde(default)]
+    pub banned_word_action: BannedWordAction,
+
+    /// Sending the same message this many times within the window mutes the
+    /// character, 0 disables spam detection
+    #[serde(default = "default_spam_repeat_count")]
+    pub spam_repeat_count: u32,
+    #[serde(default = "default_spam_repeat_window_secs")]
+    pub spam_repeat_window_secs: u64,
+    #[serde(default = "default_spam_mute_secs")]
+    pub spam_mute_secs: u64,
+}
+
+impl Default for ChatModerationConfig {
+    fn default() -> Self {
+        Self {
+            banned_words: Vec::new(),
+            banned_word_action: BannedWordAction::default(),
+            spam_repeat_count: default_spam_repeat_count(),
+            spam_repeat_window_secs: default_spam_repeat_window_secs(),
+            spam_mute_secs: default_spam_mute_secs(),
+        }
+    }
+}
+
+fn default_suspicion_weight() -> u32 {
+    1
+}
+
+fn default_malformed_packet_weight() -> u32 {
+    5
+}
+
+fn default_max_attack_requests() -> u32 {
+    4
+}
+
+fn default_item_cooldown_tolerance() -> f32 {
+    0.8
+}
+
+fn default_movement_tolerance_secs() -> f32 {
+    2.0
+}
+
+fn default_audit_score() -> u32 {
+    10
+}
+
+/// How suspicious client behaviour is scored, signals add their weight to the
+/// session score which decides when to write audit log entries and kick, and
+/// to the account's stored score for GMs to review.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CheatDetectionConfig {
+    #[serde(default = "default_suspicion_weight")]
+    pub attack_rate_weight: u32,
+    #[serde(default = "default_suspicion_weight")]
+    pub item_use_rate_weight: u32,
+    #[serde(default = "default_suspicion_weight")]
+    pub movement_speed_weight: u32,
+    #[serde(default = "default_malformed_packet_weight")]
+    pub malformed_packet_weight: u32,
+    #[serde(default = "default_malformed_packet_weight")]
+    pub client_integrity_weight: u32,
+
+    /// How many attack requests are allowed within the time of one attack at
+    /// the character's attack speed, clicking the target again is legitimate
+    #[serde(default = "default_max_attack_requests")]
+    pub max_attack_requests: u32,
+
+    /// The fraction of an item's cooldown which must pass before it can be
+    /// used again, allowing for latency
+    #[serde(default = "default_item_cooldown_tolerance")]
+    pub item_cooldown_tolerance: f32,
+
+    /// A reported position must be within this many seconds of movement at
+    /// the character's move speed from the server's position
+    #[serde(default = "default_movement_tolerance_secs")]
+    pub movement_tolerance_secs: f32,
+
+    /// Every signal is written to the audit log once the session score reaches
+    /// this
+    #[serde(default = "default_audit_score")]
+    pub audit_score: u32,
+
+    /// Disconnect the client when the session score reaches this, or None to
+    /// never kick
+    #[serde(default)]
+    pub kick_score: Option<u32>,
+}
+
+impl Default for CheatDetectionConfig {
+    fn default() -> Self {
+        Self {
+            attack_rate_weight: default_suspicion_weight(),
+            item_use_rate_weight: default_suspicion_weight(),
+            movement_speed_weight: default_suspicion_weight(),
+            malformed_packet_weight: default_malformed_packet_weight(),
+            client_integrity_weight: default_malformed_packet_weight(),
+            max_attack_requests: default_max_attack_requests(),
+            item_cooldown_tolerance: default_item_cooldown_tolerance(),
+            movement_tolerance_secs: default_movement_tolerance_secs(),
+            audit_score: default_audit_score(),
+            kick_score: None,
+        }
+    }
+}
+
+fn default_client_integrity_sample_size() -> usize {
+    3
+}
+
+fn default_client_integrity_response_timeout_secs() -> u64 {
+    30
+}
+
+/// Asks each game client for the hashes of a random sample of game data files
+/// when it joins, so clients with modified data files can be detected.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ClientIntegrityConfig {
+    /// Paths in the game data which clients can be asked to hash
+    pub files: Vec<String>,
+
+    /// How many of `files` each client is asked to hash
+    #[serde(default = "default_client_integrity_sample_size")]
+    pub sample_size: usize,
+
+    /// A client which has not responded within this many seconds is treated
+    /// as if every hash did not match
+    #[serde(default = "default_client_integrity_response_timeout_secs")]
+    pub response_timeout_secs: u64,
+
+    /// Disconnect clients whose hashes do not match, otherwise they are only
+    /// written to the audit log and added to the suspicion score
+    #[serde(default)]
+    pub kick_on_mismatch: bool,
+
+    /// The hashes of `files` in the server's game data, read at startup.
+    /// Files which do not exist in the game data are left out.
+    #[serde(skip)]
+    pub file_hashes: Vec<(String, [u8; 32])>,
+}
+
+/// Login rules for the country of each login client, which is found with the
+/// GeoIP database given by `network.geo_ip_database`. Country codes are the
+/// ones used in the database.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GeoIpConfig {
+    /// Countries where new accounts can be created, empty for every country
+    #[serde(default)]
+    pub registration_countries: Vec<String>,
+
+    /// Countries where accounts can log in, empty for every country. GM
+    /// accounts can always log in.
+    #[serde(default)]
+    pub login_countries: Vec<String>,
+
+    /// Clients whose country is unknown, such as from a private network,
+    /// pass the country restrictions
+    #[serde(default = "default_geo_ip_allow_unknown_country")]
+    pub allow_unknown_country: bool,
+
+    /// The world server IP sent to clients from each country, when the world
+    /// server can be reached at a different address from each region
+    #[serde(default)]
+    pub world_server_ips: HashMap<String, String>,
+}
+
+fn default_geo_ip_allow_unknown_country() -> bool {
+    true
+}
+
+impl GeoIpConfig {
+    fn is_allowed(&self, countries: &[String], country: Option<&str>) -> bool {
+        if countries.is_empty() {
+            return true;
+        }
+
+        country.map_or(self.allow_unknown_country, |country| {
+            countries
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(country))
+        })
+    }
+
+    pub fn can_register(&self, country: Option<&str>) -> bool {
+        self.is_allowed(&self.registration_countries, country)
+    }
+
+    pub fn can_login(&self, country: Option<&str>) -> bool {
+        self.is_allowed(&self.login_countries, country)
+    }
+
+    pub fn world_server_ip(&self, country: Option<&str>) -> Option<&str> {
+        let country = country?;
+        self.world_server_ips
+            .iter()
+            .find(|(ip_country, _)| ip_country.eq_ignore_ascii_case(country))
+            .map(|(_, ip)| ip.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementGoal {
+    /// Kill `count` monsters, or only monsters with `npc_id` if it is set
+    KillNpc {
+        #[serde(default)]
+        npc_id: Option<NpcId>,
+        count: u32,
+    },
+
+    /// Complete `count` quests, or only the quest with `quest_id` if it is set
+    CompleteQuest {
+        #[serde(default)]
+        quest_id: Option<usize>,
+        count: u32,
+    },
+
+    ReachLevel {
+        level: u32,
+    },
+}
+
+impl AchievementGoal {
+    /// Returns the progress needed to unlock the achievement.
+    pub fn get_required_progress(&self) -> u32 {
+        match *self {
+            AchievementGoal::KillNpc { count, .. } => count,
+            AchievementGoal::CompleteQuest { count, .. } => count,
+            AchievementGoal::ReachLevel { level } => level,
+        }
+    }
+}
+
+/// The ability values added to a character whilst it has a title equipped, or
+/// for each time it has been reborn.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AbilityValuesBonus {
+    #[serde(default)]
+    pub max_health: i32,
+    #[serde(default)]
+    pub max_mana: i32,
+    #[serde(default)]
+    pub attack_power: i32,
+    #[serde(default)]
+    pub defence: i32,
+    #[serde(default)]
+    pub hit: i32,
+    #[serde(default)]
+    pub avoid: i32,
+    #[serde(default)]
+    pub critical: i32,
+    #[serde(default)]
+    pub resistance: i32,
+}
+
+impl AbilityValuesBonus {
+    pub fn apply(&self, ability_values: &mut AbilityValues) {
+        self.apply_multiple(ability_values, 1);
+    }
+
+    pub fn apply_multiple(&self, ability_values: &mut AbilityValues, count: i32) {
+        ability_values.max_health += self.max_health * count;
+        ability_values.max_mana += self.max_mana * count;
+        ability_values.attack_power += self.attack_power * count;
+        ability_values.defence += self.defence * count;
+        ability_values.hit += self.hit * count;
+        ability_values.avoid += self.avoid * count;
+        ability_values.critical += self.critical * count;
+        ability_values.resistance += self.resistance * count;
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AchievementTitle {
+    pub name: String,
+    #[serde(default)]
+    pub bonus: AbilityValuesBonus,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Achievement {
+    /// Progress is stored by id, so it must not change once players have
+    /// started the achievement
+    pub id: String,
+    pub name: String,
+    pub goal: AchievementGoal,
+
+    /// The title which can be equipped once the achievement is unlocked
+    #[serde(default)]
+    pub title: Option<AchievementTitle>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AchievementsConfig {
+    #[serde(default)]
+    pub achievements: Vec<Achievement>,
+}
+
+impl AchievementsConfig {
+    pub fn get(&self, id: &str) -> Option<&Achievement> {
+        self.achievements
+            .iter()
+            .find(|achievement| achievement.id == id)
+    }
+
+    pub fn get_title(&self, id: &str) -> Option<&AchievementTitle> {
+        self.get(id)
+            .and_then(|achievement| achievement.title.as_ref())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LevelUpRewardItem {
+    pub item: ItemReference,
+    #[serde(default = "default_item_quantity")]
+    pub quantity: u32,
+}
+
+/// Given to a character when it first reaches a level.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LevelUpReward {
+    pub level: u32,
+    #[serde(default)]
+    pub items: Vec<LevelUpRewardItem>,
+    #[serde(default)]
+    pub skill_points: u32,
+    #[serde(default)]
+    pub money: Money,
+}
+
+/// Overrides the xp required for each level from the game data, and rewards
+/// characters when they level up.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LevelUpConfig {
+    /// The xp required to level up from each level, levels which are not
+    /// listed use the game data
+    #[serde(default)]
+    pub required_xp: HashMap<u32, u64>,
+    #[serde(default)]
+    pub rewards: Vec<LevelUpReward>,
+}
+
+impl LevelUpConfig {
+    pub fn get_required_xp(&self, level: u32) -> Option<u64> {
+        self.required_xp.get(&level).copied()
+    }
+
+    pub fn get_rewards(&self, level: u32) -> impl Iterator<Item = &LevelUpReward> {
+        self.rewards
+            .iter()
+            .filter(move |reward| reward.level == level)
+    }
+}
+
+fn default_quest_share_radius() -> f32 {
+    5000.0
+}
+
+/// Quests whose kill and collection progress is shared with nearby party
+/// members, and optionally clan members.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SharedQuestsConfig {
+    /// The ids of the shared quests
+    #[serde(default)]
+    pub quests: Vec<usize>,
+
+    /// How close a member must be to the character which made progress
+    #[serde(default = "default_quest_share_radius")]
+    pub share_radius: f32,
+
+    #[serde(default)]
+    pub share_with_clan: bool,
+}
+
+impl Default for SharedQuestsConfig {
+    fn default() -> Self {
+        Self {
+            quests: Vec::new(),
+            share_radius: default_quest_share_radius(),
+            share_with_clan: false,
+        }
+    }
+}
+
+impl SharedQuestsConfig {
+    pub fn is_shared_quest(&self, quest_id: usize) -> bool {
+        self.quests.contains(&quest_id)
+    }
+}
+
+/// Items given by a quest, only to characters which match every condition.
+#[derive(Clone, Debug, Deserialize)]
+pub struct QuestRewardOption {
+    pub items: Vec<LevelUpRewardItem>,
+
+    /// The jobs which can receive the items, or empty for every job
+    #[serde(default)]
+    pub jobs: Vec<u16>,
+    #[serde(default)]
+    pub gender: Option<CharacterGender>,
+    #[serde(default)]
+    pub min_level: Option<u32>,
+    #[serde(default)]
+    pub max_level: Option<u32>,
+}
+
+impl QuestRewardOption {
+    pub fn is_available(&self, job: u16, gender: CharacterGender, level: u32) -> bool {
+        (self.jobs.is_empty() || self.jobs.contains(&job))
+            && self
+                .gender
+                .map_or(true, |reward_gender| reward_gender == gender)
+            && self.min_level.map_or(true, |min_level| level >= min_level)
+            && self.max_level.map_or(true, |max_level| level <= max_level)
+    }
+}
+
+/// Rewards given in addition to the quest data when `trigger` applies its
+/// rewards.
+#[derive(Clone, Debug, Deserialize)]
+pub struct QuestRewards {
+    pub trigger: String,
+
+    /// The player must choose one of these, the trigger fails unless the
+    /// client sends a choice which is available to the character
+    #[serde(default)]
+    pub choices: Vec<QuestRewardOption>,
+
+    /// Every option which is available to the character is given
+    #[serde(default)]
+    pub conditional: Vec<QuestRewardOption>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct QuestRewardsConfig {
+    #[serde(default)]
+    pub quests: Vec<QuestRewards>,
+}
+
+impl QuestRewardsConfig {
+    pub fn get(&self, trigger: &str) -> Option<&QuestRewards> {
+        self.quests.iter().find(|quest| quest.trigger == trigger)
+    }
+}
+
+/// Lets characters which have reached the level cap be reborn at level 1.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RebirthConfig {
+    /// The level a character must reach to be reborn, defaults to the level cap
+    #[serde(default)]
+    pub min_level: Option<u32>,
+
+    /// How many times a character can be reborn, or None for no limit
+    #[serde(default)]
+    pub max_rebirths: Option<u32>,
+
+    /// Keep the learnt active and passive skills and the unspent skill points,
+    /// otherwise they are removed
+    #[serde(default)]
+    pub keep_skills: bool,
+
+    /// Keep the equipped and inventory items, otherwise every item is removed
+    /// and only the money is kept
+    #[serde(default)]
+    pub keep_items: bool,
+
+    /// The ability values added to a character for each time it was reborn
+    #[serde(default)]
+    pub bonus_per_rebirth: AbilityValuesBonus,
+}
+
+impl RebirthConfig {
+    pub fn get_min_level(&self, level_cap: Option<u32>) -> Option<u32> {
+        self.min_level.or(level_cap)
+    }
+}
+
+fn default_bot_profile_weight() -> u32 {
+    1
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BotScenarioProfile {
+    pub profile: BotProfile,
+    #[serde(default = "default_bot_profile_weight")]
+    pub weight: u32,
+}
+
+/// A group of bots which are spawned and removed together with the
+/// botscenario chat command.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BotScenario {
+    pub name: String,
+    pub count: usize,
+
+    /// The bots are spread evenly across these zones, spawning at the zone
+    /// start position
+    pub zones: Vec<ZoneId>,
+
+    /// The chance of each bot having a profile is its weight out of the total,
+    /// every bot is a farmer when empty
+    #[serde(default)]
+    pub profiles: Vec<BotScenarioProfile>,
+
+    /// The level range of the bots, defaults to a range based on the monsters
+    /// in each zone
+    #[serde(default)]
+    pub min_level: Option<u32>,
+    #[serde(default)]
+    pub max_level: Option<u32>,
+
+    /// Start the scenario when the server starts
+    #[serde(default)]
+    pub autostart: bool,
+}
+
+impl BotScenario {
+    pub fn choose_profile(&self, rng: &mut impl Rng) -> BotProfile {
+        let total_weight: u32 = self.profiles.iter().map(|profile| profile.weight).sum();
+        if total_weight == 0 {
+            return BotProfile::Farmer;
+        }
+
+        let mut roll = rng.gen_range(0..total_weight);
+        for profile in self.profiles.iter() {
+            if roll < profile.weight {
+                return profile.profile;
+            }
+            roll -= profile.weight;
+        }
+
+        BotProfile::Farmer
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct BotScenariosConfig {
+    #[serde(default)]
+    pub scenarios: Vec<BotScenario>,
+}
+
+impl BotScenariosConfig {
+    pub fn get(&self, name: &str) -> Option<&BotScenario> {
+        self.scenarios
+            .iter()
+            .find(|scenario| scenario.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventZoneMode {
+    /// Teams score by taking another team's flag from its base back to their
+    /// own base whilst their own flag is at home
+    CaptureTheFlag,
+
+    /// A team scores a point every second it is the only team on the hill
+    KingOfTheHill,
+}
+
+impl EventZoneMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventZoneMode::CaptureTheFlag => "capture the flag",
+            EventZoneMode::KingOfTheHill => "king of the hill",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EventZoneTeam {
+    pub name: String,
+
+    /// Where the team's members enter the zone, and where its flag is kept
+    pub base: Vec3,
+}
+
+fn default_event_zone_objective_radius() -> f32 {
+    300.0
+}
+
+fn default_event_zone_duration_mins() -> u64 {
+    15
+}
+
+fn default_event_zone_signup_mins() -> u64 {
+    5
+}
+
+fn default_event_zone_min_players() -> usize {
+    2
+}
+
+/// A team mini-game held in its own zone, players join with the event chat
+/// command whilst signup is open and are split evenly across the teams.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EventZone {
+    pub name: String,
+    pub mode: EventZoneMode,
+    pub zone: ZoneId,
+    pub teams: Vec<EventZoneTeam>,
+
+    /// The hill for king of the hill
+    #[serde(default)]
+    pub hill: Option<Vec3>,
+
+    /// How close a character must be to take or return a flag, or to be on
+    /// the hill
+    #[serde(default = "default_event_zone_objective_radius")]
+    pub objective_radius: f32,
+
+    /// Flag captures, or seconds holding the hill, which win the event early
+    pub score_to_win: u32,
+
+    #[serde(default = "default_event_zone_duration_mins")]
+    pub duration_mins: u64,
+    #[serde(default = "default_event_zone_signup_mins")]
+    pub signup_mins: u64,
+
+    /// Open signup every this many minutes, or None to only start the event
+    /// with the event chat command
+    #[serde(default)]
+    pub interval_mins: Option<u64>,
+
+    #[serde(default = "default_event_zone_min_players")]
+    pub min_players: usize,
+    #[serde(default)]
+    pub max_players: Option<usize>,
+    #[serde(default)]
+    pub min_level: Option<u32>,
+
+    /// Given to every member of the winning team
+    #[serde(default)]
+    pub rewards: Vec<LevelUpRewardItem>,
+    #[serde(default)]
+    pub honor_points: u32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct EventZonesConfig {
+    #[serde(default)]
+    pub events: Vec<EventZone>,
+}
+
+impl EventZonesConfig {
+    pub fn get(&self, name: &str) -> Option<&EventZone> {
+        self.events
+            .iter()
+            .find(|event| event.name.eq_ignore_ascii_case(name))
+    }
+}
+
+fn default_invasion_count() -> usize {
+    1
+}
+
+fn default_invasion_duration_mins() -> u64 {
+    30
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvasionMonster {
+    pub npc: NpcId,
+    #[serde(default = "default_invasion_count")]
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvasionWave {
+    /// How long after the previous wave is defeated, or after the invasion
+    /// starts, before this wave spawns
+    #[serde(default)]
+    pub delay_secs: u64,
+    pub monsters: Vec<InvasionMonster>,
+}
+
+/// Guards which defend the town for the duration of the invasion, the npc
+/// should be a monster with an aggressive AI as it fights on the players' team
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvasionGuard {
+    pub npc: NpcId,
+    pub position: Vec3,
+    #[serde(default = "default_invasion_count")]
+    pub count: usize,
+}
+
+/// Given to players whose share of the damage dealt to the invading monsters
+/// is at least `min_contribution`, from 0.0 to 1.0. Each player receives the
+/// highest reward they qualify for.
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvasionReward {
+    #[serde(default)]
+    pub min_contribution: f32,
+    #[serde(default)]
+    pub xp: u64,
+    #[serde(default)]
+    pub items: Vec<LevelUpRewardItem>,
+    #[serde(default)]
+    pub honor_points: u32,
+}
+
+/// Waves of monsters which spawn at the edge of a zone and move towards its
+/// town, the invasion is repelled once every wave has been defeated.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Invasion {
+    pub name: String,
+    pub zone: ZoneId,
+    pub town: Vec3,
+
+    /// Each monster of a wave spawns at one of these positions in turn
+    pub spawn_points: Vec<Vec3>,
+    pub waves: Vec<InvasionWave>,
+    #[serde(default)]
+    pub guards: Vec<InvasionGuard>,
+
+    /// The invasion fails if it has not been repelled after this long
+    #[serde(default = "default_invasion_duration_mins")]
+    pub duration_mins: u64,
+
+    /// Start the invasion every this many minutes, or None to only start the
+    /// invasion with the invasion chat command
+    #[serde(default)]
+    pub interval_mins: Option<u64>,
+
+    #[serde(default)]
+    pub rewards: Vec<InvasionReward>,
+}
+
+impl Invasion {
+    pub fn get_reward(&self, contribution: f32) -> Option<&InvasionReward> {
+        self.rewards
+            .iter()
+            .filter(|reward| contribution >= reward.min_contribution)
+            .max_by(|a, b| a.min_contribution.total_cmp(&b.min_contribution))
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct InvasionsConfig {
+    #[serde(default)]
+    pub invasions: Vec<Invasion>,
+}
+
+fn default_guard_aggro_range() -> f32 {
+    1500.0
+}
+
+fn default_guard_leash_range() -> f32 {
+    3000.0
+}
+
+fn default_guard_attack() -> bool {
+    true
+}
+
+/// Which targets a guard npc attacks, it attacks the nearest target allowed by
+/// these rules and returns to its post when there are none.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GuardAggroRules {
+    pub npc: NpcId,
+
+    /// How close to the guard a target must be to be attacked
+    #[serde(default = "default_guard_aggro_range")]
+    pub aggro_range: f32,
+
+    /// How far from its post the guard chases a target before returning
+    #[serde(default = "default_guard_leash_range")]
+    pub leash_range: f32,
+
+    #[serde(default = "default_guard_attack")]
+    pub attack_pk_flagged: bool,
+    #[serde(default = "default_guard_attack")]
+    pub attack_invasion_monsters: bool,
+    #[serde(default)]
+    pub attack_monsters: bool,
+
+    /// Attack anything which is damaging a nearby npc of the guard's team
+    /// before any other target
+    #[serde(default = "default_guard_attack")]
+    pub defend_npcs: bool,
+}
+
+fn default_pk_flag_duration_secs() -> u64 {
+    5 * 60
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GuardsConfig {
+    /// How long a character is pk flagged after killing an ordinary player
+    #[serde(default = "default_pk_flag_duration_secs")]
+    pub pk_flag_duration_secs: u64,
+
+    #[serde(default)]
+    pub guards: Vec<GuardAggroRules>,
+}
+
+impl Default for GuardsConfig {
+    fn default() -> Self {
+        Self {
+            pk_flag_duration_secs: default_pk_flag_duration_secs(),
+            guards: Vec::new(),
+        }
+    }
+}
+
+impl GuardsConfig {
+    pub fn get(&self, npc: NpcId) -> Option<&GuardAggroRules> {
+        self.guards.iter().find(|guard| guard.npc == npc)
+    }
+}
+
+impl InvasionsConfig {
+    pub fn get(&self, name: &str) -> Option<&Invasion> {
+        self.invasions
+            .iter()
+            .find(|invasion| invasion.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Disconnects players who are AFK whilst the server is busy.
+#[derive(Clone, Debug)]
+pub struct AfkConfig {
+    /// How long without any message from the game client before a player is AFK
+    pub timeout: Duration,
+
+    /// How long AFK players are warned before they are disconnected
+    pub warning_duration: Duration,
+
+    /// AFK players are only disconnected whilst at least this many players are
+    /// online, so they only make room when the server is near capacity
+    pub kick_min_online: usize,
+
+    /// Never disconnect players who have a personal store open
+    pub exempt_personal_store: bool,
+}
+
+/// Limits how many players can join each channel of a world server.
+#[derive(Clone, Debug)]
+pub struct ChannelCapacityConfig {
+    /// Joining a channel with this many connected clients is refused
+    pub max_players: usize,
+
+    /// Log a warning when a channel becomes this percent full
+    pub overload_warning_percent: u8,
+}
+
+/// Keeps personal stores open after their owner disconnects.
+#[derive(Clone, Debug)]
+pub struct OfflineVendorConfig {
+    /// How long a personal store stays open after its owner disconnects
+    pub max_duration: Duration,
+
+    /// How many characters of an account can be offline vendors at once
+    pub max_per_account: usize,
+}
 
 #[derive(Resource)]
 pub struct GameConfig {
     pub enable_npc_spawns: bool,
     pub enable_monster_spawns: bool,
+    pub reward_calendar: Option<RewardCalendarConfig>,
+    pub item_binding: ItemBindingConfig,
+    pub character_creation: CharacterCreationConfig,
+    pub name_filter: NameFilterConfig,
+
+    /// Scale monster spawns by the number of nearby characters, or None to use
+    /// the static spawn data
+    pub monster_spawn_scaling: Option<MonsterSpawnScalingConfig>,
+
+    /// Promote monsters from spawn points to elite variants, or None to disable
+    pub elite_monsters: Option<EliteMonsterConfig>,
+
+    /// How often party members are sent the world map position of other members,
+    /// or None to disable party map markers
+    pub party_map_marker_interval: Option<Duration>,
+
+    /// How far back in time a target's position can be used when checking if
+    /// it is within range of an attack or skill, or None to only use the
+    /// current position
+    pub latency_compensation: Option<Duration>,
+
+    /// How long item drops can only be picked up by their owner or the owner's
+    /// party, or None for every item drop to be free for all
+    pub item_drop_owner_duration: Option<Duration>,
+    pub item_drops: ItemDropConfig,
+    pub skill_chains: SkillChainsConfig,
+    pub skill_movement_effects: SkillMovementEffectsConfig,
+
+    pub zone_environment: ZoneEnvironmentConfig,
+    pub zone_rules: ZoneRulesConfig,
+    pub teleport_gates: TeleportGatesConfig,
+    pub npc_store_stock: NpcStoreStockConfig,
+    pub npc_store_currencies: NpcStoreCurrenciesConfig,
+    pub chat_channels: ChatChannelsConfig,
+    pub chat_moderation: ChatModerationConfig,
+    pub cheat_detection: CheatDetectionConfig,
+
+    /// Check the game data files of clients when they join, or None to disable
+    pub client_integrity: Option<ClientIntegrityConfig>,
+
+    /// Restrict logins and route clients by the country they connect from, or
+    /// None to disable
+    pub geo_ip: Option<GeoIpConfig>,
+
+    pub achievements: AchievementsConfig,
+
+    pub level_up: LevelUpConfig,
+
+    /// The highest level a character can reach, or None for no cap
+    pub level_cap: Option<u32>,
+
+    /// Let characters be reborn at level 1 with the rebirth chat command, or
+    /// None to disable rebirth
+    pub rebirth: Option<RebirthConfig>,
+
+    pub bot_scenarios: BotScenariosConfig,
+    pub clan_creation: ClanCreationConfig,
+    pub shared_quests: SharedQuestsConfig,
+    pub quest_rewards: QuestRewardsConfig,
+    pub event_zones: EventZonesConfig,
+    pub invasions: InvasionsConfig,
+    pub guards: GuardsConfig,
+
+    /// When an account which is already logged in logs in again, disconnect
+    /// the existing session instead of rejecting the new login
+    pub disconnect_duplicate_login: bool,
+
+    /// Forget status effects and skill cooldowns when a character logs out,
+    /// instead of restoring them with their remaining duration when it next
+    /// joins the game
+    pub clear_effects_on_logout: bool,
+
+    /// Accounts which can still log in during server maintenance
+    pub gm_accounts: Vec<String>,
+
+    /// How long a character stays in the world after its game client
+    /// disconnects, so the player can reconnect and resume control of it, or
+    /// None to save and remove the character immediately
+    pub reconnect_grace_period: Option<Duration>,
+
+    /// Disconnect AFK players when the server is busy, or None to never
+    /// disconnect AFK players
+    pub afk: Option<AfkConfig>,
+
+    /// Keep personal stores open after their owner disconnects, or None to
+    /// close them with the usual disconnect handling
+    pub offline_vendor: Option<OfflineVendorConfig>,
+
+    /// The maximum population of each channel, or None for no limit
+    pub channel_capacity: Option<ChannelCapacityConfig>,
+
+    /// Record the execution time of every system each tick and warn when a
+    /// tick takes longer than this budget, or None to disable the profiler
+    pub tick_profiler_budget: Option<Duration>,
+
+    /// How often the server-wide leaderboards are rebuilt from storage, or None
+    /// to disable leaderboards
+    pub leaderboard_refresh_interval: Option<Duration>,
+
+    /// Periodically back up all storage documents, or None to disable backups
+    pub storage_backup: Option<StorageBackupConfig>,
+
+    /// The mail server used to send email verification and password reset
+    /// tokens to players, or None to show tokens to the operator instead
+    pub smtp: Option<SmtpConfig>,
+
+    /// The initial world rates, which can be changed whilst the server is
+    /// running with the rate chat command
+    pub world_rates: WorldRates,
 }
 
 impl GameConfig {
@@ -11,6 +1976,57 @@ impl GameConfig {
         Self {
             enable_monster_spawns: true,
             enable_npc_spawns: true,
+            reward_calendar: None,
+            item_binding: ItemBindingConfig::default(),
+            character_creation: CharacterCreationConfig::default(),
+            name_filter: NameFilterConfig::default(),
+            monster_spawn_scaling: None,
+            elite_monsters: None,
+            party_map_marker_interval: Some(Duration::from_secs(5)),
+            latency_compensation: None,
+            item_drop_owner_duration: Some(Duration::from_secs(60)),
+            item_drops: ItemDropConfig::default(),
+            skill_chains: SkillChainsConfig::default(),
+            skill_movement_effects: SkillMovementEffectsConfig::default(),
+            zone_environment: ZoneEnvironmentConfig::default(),
+            zone_rules: ZoneRulesConfig::default(),
+            teleport_gates: TeleportGatesConfig::default(),
+            npc_store_stock: NpcStoreStockConfig::default(),
+            npc_store_currencies: NpcStoreCurrenciesConfig::default(),
+            chat_channels: ChatChannelsConfig::default(),
+            chat_moderation: ChatModerationConfig::default(),
+            cheat_detection: CheatDetectionConfig::default(),
+            client_integrity: None,
+            geo_ip: None,
+            achievements: AchievementsConfig::default(),
+            level_up: LevelUpConfig::default(),
+            level_cap: None,
+            rebirth: None,
+            bot_scenarios: BotScenariosConfig::default(),
+            clan_creation: ClanCreationConfig::default(),
+            shared_quests: SharedQuestsConfig::default(),
+            quest_rewards: QuestRewardsConfig::default(),
+            event_zones: EventZonesConfig::default(),
+            invasions: InvasionsConfig::default(),
+            guards: GuardsConfig::default(),
+            disconnect_duplicate_login: false,
+            clear_effects_on_logout: false,
+            gm_accounts: Vec::new(),
+            reconnect_grace_period: Some(Duration::from_secs(30)),
+            afk: None,
+            offline_vendor: None,
+            channel_capacity: None,
+            tick_profiler_budget: None,
+            leaderboard_refresh_interval: None,
+            storage_backup: None,
+            smtp: None,
+            world_rates: WorldRates::new(),
         }
     }
+
+    pub fn is_gm_account(&self, account_name: &str) -> bool {
+        self.gm_accounts
+            .iter()
+            .any(|gm_account| gm_account.eq_ignore_ascii_case(account_name))
+    }
 }
@@ -2,9 +2,9 @@ use bevy::prelude::Resource;
 use std::sync::Arc;
 
 use rose_data::{
-    AiDatabase, CharacterMotionDatabase, DataDecoder, ItemDatabase, JobClassDatabase, NpcDatabase,
-    QuestDatabase, SkillDatabase, StatusEffectDatabase, StringDatabase, WarpGateDatabase,
-    ZoneDatabase,
+    AiDatabase, CharacterMotionDatabase, DataDecoder, ItemDatabase, ItemReference,
+    JobClassDatabase, NpcDatabase, NpcId, QuestDatabase, SkillDatabase, StatusEffectDatabase,
+    StringDatabase, WarpGateDatabase, ZoneDatabase, ZoneGeometryDatabase, ZoneNavGridDatabase,
 };
 use rose_game_common::data::{AbilityValueCalculator, DropTable};
 
@@ -27,4 +27,39 @@ pub struct GameData {
     pub string_database: Arc<StringDatabase>,
     pub warp_gates: Arc<WarpGateDatabase>,
     pub zones: Arc<ZoneDatabase>,
+    pub zone_geometry: Arc<ZoneGeometryDatabase>,
+    pub zone_nav_grids: Arc<ZoneNavGridDatabase>,
+}
+
+impl GameData {
+    /// Returns the name of an item in a client's chosen language, or in the
+    /// server language when `language` is None.
+    pub fn get_item_name(&self, item: ItemReference, language: Option<usize>) -> &str {
+        let Some(item_data) = self.items.get_base_item(item) else {
+            return "?";
+        };
+
+        match language {
+            Some(language) => {
+                self.string_database
+                    .get_item_name(language, item.item_type, &item_data.string_id)
+            }
+            None => item_data.name.as_ref(),
+        }
+    }
+
+    /// Returns the name of an npc in a client's chosen language, or in the
+    /// server language when `language` is None.
+    pub fn get_npc_name(&self, npc_id: NpcId, language: Option<usize>) -> &str {
+        let Some(npc_data) = self.npcs.get_npc(npc_id) else {
+            return "?";
+        };
+
+        match (language, npc_data.string_id.as_deref()) {
+            (Some(language), Some(string_id)) => {
+                self.string_database.get_npc_name(language, string_id)
+            }
+            _ => npc_data.name.as_ref(),
+        }
+    }
 }
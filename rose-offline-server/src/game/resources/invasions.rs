@@ -0,0 +1,50 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::prelude::Entity, prelude::Resource};
+
+/// The invasion which is currently in progress.
+pub struct ActiveInvasion {
+    pub name: String,
+    pub end_time: Instant,
+
+    /// The index of the next wave to spawn, and when it will spawn once the
+    /// current wave has been defeated
+    pub next_wave: usize,
+    pub next_wave_time: Option<Instant>,
+
+    pub monsters: Vec<Entity>,
+    pub guards: Vec<Entity>,
+
+    /// Damage dealt to the invading monsters by each character
+    pub contributions: HashMap<Entity, u64>,
+}
+
+impl ActiveInvasion {
+    pub fn new(name: String, now: Instant, duration: Duration, first_wave_delay: Duration) -> Self {
+        Self {
+            name,
+            end_time: now + duration,
+            next_wave: 0,
+            next_wave_time: Some(now + first_wave_delay),
+            monsters: Vec::new(),
+            guards: Vec::new(),
+            contributions: HashMap::new(),
+        }
+    }
+
+    pub fn total_contribution(&self) -> u64 {
+        self.contributions.values().sum()
+    }
+}
+
+/// Only one invasion can be in progress at a time.
+#[derive(Default, Resource)]
+pub struct Invasions {
+    pub active: Option<ActiveInvasion>,
+
+    /// When each scheduled invasion will next start
+    pub next_scheduled: HashMap<String, Instant>,
+}
@@ -0,0 +1,71 @@
+use std::{
+    collections::HashMap,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::Resource;
+use log::error;
+
+use crate::game::storage::leaderboard::{LeaderboardCharacter, Leaderboards};
+
+/// The number of characters in each leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// The latest server-wide leaderboards, which are periodically rebuilt from
+/// storage on a background thread.
+#[derive(Resource)]
+pub struct LeaderboardCache {
+    refresh_interval: Duration,
+    next_refresh: Instant,
+    running: Option<JoinHandle<Leaderboards>>,
+    leaderboards: Option<Leaderboards>,
+}
+
+impl LeaderboardCache {
+    /// The first refresh happens immediately so the leaderboards are available
+    /// soon after the server starts.
+    pub fn new(refresh_interval: Duration, now: Instant) -> Self {
+        Self {
+            refresh_interval,
+            next_refresh: now,
+            running: None,
+            leaderboards: None,
+        }
+    }
+
+    pub fn get(&self) -> Option<&Leaderboards> {
+        self.leaderboards.as_ref()
+    }
+
+    /// Refresh the leaderboards on the next update, instead of waiting for the
+    /// refresh interval.
+    pub fn refresh_now(&mut self, now: Instant) {
+        self.next_refresh = now;
+    }
+
+    /// Collects a finished refresh, then returns true if the next refresh is due.
+    pub fn update(&mut self, now: Instant) -> bool {
+        if self
+            .running
+            .as_ref()
+            .map_or(false, |running| running.is_finished())
+        {
+            match self.running.take().unwrap().join() {
+                Ok(leaderboards) => self.leaderboards = Some(leaderboards),
+                Err(_) => error!("Leaderboard refresh thread panicked"),
+            }
+        }
+
+        self.running.is_none() && now >= self.next_refresh
+    }
+
+    /// Starts rebuilding the leaderboards from storage, with the values of the
+    /// online characters replacing their stored values.
+    pub fn start_refresh(&mut self, now: Instant, online: HashMap<String, LeaderboardCharacter>) {
+        self.next_refresh = now + self.refresh_interval;
+        self.running = Some(std::thread::spawn(move || {
+            Leaderboards::load(online, LEADERBOARD_SIZE)
+        }));
+    }
+}
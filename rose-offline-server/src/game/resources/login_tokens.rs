@@ -1,5 +1,15 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
 use bevy::{ecs::prelude::Entity, prelude::Resource};
 
+use crate::game::{messages::control::ClientType, resources::PacketCodecSeeds};
+
+// How long a token can remain unclaimed by a world or game client before it expires
+pub const LOGIN_TOKEN_UNCLAIMED_TIMEOUT: Duration = Duration::from_secs(60);
+
 pub struct LoginToken {
     pub username: String,
     pub token: u32,
@@ -9,22 +19,50 @@ pub struct LoginToken {
     pub login_client: Option<Entity>,
     pub world_client: Option<Entity>,
     pub game_client: Option<Entity>,
+    pub created_time: Instant,
+    pub world_packet_codec_seed: u32,
+    pub game_packet_codec_seed: u32,
+
+    /// The address of the login client, world and game connections using
+    /// this token's packet codec seeds must come from the same address
+    pub ip: IpAddr,
+}
+
+impl LoginToken {
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.world_client.is_none()
+            && self.game_client.is_none()
+            && now.saturating_duration_since(self.created_time) >= LOGIN_TOKEN_UNCLAIMED_TIMEOUT
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LoginTokenMetrics {
+    pub total: usize,
+    pub unclaimed: usize,
+    pub world_clients: usize,
+    pub game_clients: usize,
 }
 
 #[derive(Default, Resource)]
 pub struct LoginTokens {
     pub tokens: Vec<LoginToken>,
+    packet_codec_seeds: PacketCodecSeeds,
 }
 
 impl LoginTokens {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(packet_codec_seeds: PacketCodecSeeds) -> Self {
+        Self {
+            tokens: Vec::new(),
+            packet_codec_seeds,
+        }
     }
 
     pub fn generate(
         &mut self,
         username: String,
         login_client: Entity,
+        ip: IpAddr,
         selected_world_server: Entity,
         selected_game_server: Entity,
     ) -> u32 {
@@ -41,15 +79,78 @@ impl LoginTokens {
             login_client: Some(login_client),
             world_client: None,
             game_client: None,
+            created_time: Instant::now(),
+            world_packet_codec_seed: self
+                .packet_codec_seeds
+                .generate(ClientType::World, token, ip),
+            game_packet_codec_seed: 0,
+            ip,
         });
         token
     }
 
-    pub fn find_username_token(&self, username: &str) -> Option<&LoginToken> {
-        self.tokens.iter().find(|token| token.username == username)
+    /// Generates a new packet codec seed for the token's next game server connection
+    pub fn generate_game_packet_codec_seed(&mut self, token_id: u32) -> Option<u32> {
+        let token = self
+            .tokens
+            .iter_mut()
+            .find(|token| token.token == token_id)?;
+        self.packet_codec_seeds.remove(token.game_packet_codec_seed);
+        token.game_packet_codec_seed =
+            self.packet_codec_seeds
+                .generate(ClientType::Game, token_id, token.ip);
+        Some(token.game_packet_codec_seed)
+    }
+
+    pub fn remove(&mut self, index: usize) -> LoginToken {
+        let token = self.tokens.remove(index);
+        self.packet_codec_seeds
+            .remove(token.world_packet_codec_seed);
+        self.packet_codec_seeds.remove(token.game_packet_codec_seed);
+        token
     }
 
     pub fn get_token_mut(&mut self, token_id: u32) -> Option<&mut LoginToken> {
         self.tokens.iter_mut().find(|token| token.token == token_id)
     }
+
+    /// Removes all expired tokens, returning the ids of the removed tokens
+    pub fn remove_expired(&mut self, now: Instant) -> Vec<u32> {
+        let mut expired = Vec::new();
+        let packet_codec_seeds = &self.packet_codec_seeds;
+        self.tokens.retain(|token| {
+            if token.is_expired(now) {
+                packet_codec_seeds.remove(token.world_packet_codec_seed);
+                packet_codec_seeds.remove(token.game_packet_codec_seed);
+                expired.push(token.token);
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+
+    pub fn get_metrics(&self) -> LoginTokenMetrics {
+        let mut metrics = LoginTokenMetrics {
+            total: self.tokens.len(),
+            ..Default::default()
+        };
+
+        for token in self.tokens.iter() {
+            if token.world_client.is_none() && token.game_client.is_none() {
+                metrics.unclaimed += 1;
+            }
+
+            if token.world_client.is_some() {
+                metrics.world_clients += 1;
+            }
+
+            if token.game_client.is_some() {
+                metrics.game_clients += 1;
+            }
+        }
+
+        metrics
+    }
 }
@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::Resource;
+
+/// New characters can not enter the game once the countdown is below this
+const MAINTENANCE_ZONE_ENTRY_CUTOFF: Duration = Duration::from_secs(60);
+
+#[derive(Copy, Clone, Debug)]
+pub enum MaintenanceState {
+    /// Only GM accounts can log in, the server shuts down at `shutdown_at`
+    Countdown { shutdown_at: Instant },
+
+    /// Every character has been saved and disconnected, the game world exits
+    /// once all storage writes have completed
+    ShuttingDown { started_at: Instant },
+}
+
+/// The state of a scheduled shutdown for server maintenance.
+#[derive(Default, Resource)]
+pub struct Maintenance {
+    state: Option<MaintenanceState>,
+    reason: String,
+
+    /// The remaining seconds of the countdown when it was last announced
+    last_announced_secs: Option<u64>,
+}
+
+impl Maintenance {
+    /// Starts or restarts the countdown, a shutdown which has already started
+    /// can not be changed.
+    pub fn start(&mut self, now: Instant, countdown: Duration, reason: String) -> bool {
+        if matches!(self.state, Some(MaintenanceState::ShuttingDown { .. })) {
+            return false;
+        }
+
+        self.state = Some(MaintenanceState::Countdown {
+            shutdown_at: now + countdown,
+        });
+        self.reason = reason;
+        self.last_announced_secs = None;
+        true
+    }
+
+    /// Returns false if there is no countdown to cancel.
+    pub fn cancel(&mut self) -> bool {
+        if matches!(self.state, Some(MaintenanceState::Countdown { .. })) {
+            self.state = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn get_state(&self) -> Option<MaintenanceState> {
+        self.state
+    }
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn set_shutting_down(&mut self, now: Instant) {
+        self.state = Some(MaintenanceState::ShuttingDown { started_at: now });
+    }
+
+    /// Returns true when only GM accounts can log in.
+    pub fn is_active(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn get_remaining(&self, now: Instant) -> Option<Duration> {
+        match self.state {
+            Some(MaintenanceState::Countdown { shutdown_at }) => {
+                Some(shutdown_at.saturating_duration_since(now))
+            }
+            Some(MaintenanceState::ShuttingDown { .. }) => Some(Duration::ZERO),
+            None => None,
+        }
+    }
+
+    /// Returns true when characters can no longer enter the game.
+    pub fn is_zone_entry_blocked(&self, now: Instant) -> bool {
+        self.get_remaining(now)
+            .map_or(false, |remaining| remaining < MAINTENANCE_ZONE_ENTRY_CUTOFF)
+    }
+
+    /// Returns the remaining seconds if the countdown should be announced,
+    /// the countdown is announced when it starts and then at set intervals.
+    pub fn take_announcement(&mut self, now: Instant, announce_at_secs: &[u64]) -> Option<u64> {
+        let remaining_secs = self.get_remaining(now)?.as_secs_f64().ceil() as u64;
+        if remaining_secs == 0 || self.last_announced_secs == Some(remaining_secs) {
+            return None;
+        }
+
+        if self.last_announced_secs.is_none() || announce_at_secs.contains(&remaining_secs) {
+            self.last_announced_secs = Some(remaining_secs);
+            Some(remaining_secs)
+        } else {
+            None
+        }
+    }
+}
@@ -1,23 +1,69 @@
+mod account_sessions;
 mod bot_list;
+mod character_list_cache;
+mod chat_channels;
+mod chat_moderation;
+mod cheat_detection;
+mod clan_wars;
 mod client_entity_list;
+mod client_integrity;
 mod control_channel;
+mod email_sender;
+mod event_zones;
 mod game_config;
 mod game_data;
+mod invasions;
+mod leaderboard_cache;
 mod login_tokens;
+mod maintenance;
+mod name_filter;
+mod npc_store_stock;
+mod packet_codec_seeds;
+mod personal_store_list;
 mod server_list;
 mod server_messages;
+mod storage_service;
+mod tick_profiler;
 mod world_rates;
 mod world_time;
+mod zone_geometry;
 mod zone_list;
 
+pub use account_sessions::{AccountSession, AccountSessions};
 pub use bot_list::{BotList, BotListEntry};
+pub use character_list_cache::CharacterListCache;
+pub use chat_channels::{ChatChannel, ChatChannelError, ChatChannels};
+pub use chat_moderation::ChatModeration;
+pub use cheat_detection::CheatDetection;
+pub use clan_wars::{ClanWar, ClanWarDeclaration, ClanWars};
 pub use client_entity_list::{ClientEntityList, ClientEntitySet, ClientEntityZone};
+pub use client_integrity::ClientIntegrity;
 pub use control_channel::ControlChannel;
-pub use game_config::GameConfig;
+pub use email_sender::{EmailSender, SmtpConfig};
+pub use event_zones::{ActiveEventZone, EventZoneParticipant, EventZoneState, EventZones};
+pub use game_config::{
+    Achievement, AchievementGoal, AchievementsConfig, AfkConfig, BannedWordAction, BotScenario,
+    ChannelCapacityConfig, CharacterCreationConfig, ChatChannelConfig, ChatChannelsConfig,
+    ChatModerationConfig, CheatDetectionConfig, ClientIntegrityConfig, EventZone, EventZoneMode,
+    GameConfig, GeoIpConfig, GuardAggroRules, Invasion, ItemBindingConfig, NameFilterConfig,
+    NpcStoreCurrency, NpcStoreStockConfig, OfflineVendorConfig, QuestRewardOption, RebirthConfig,
+    RewardCalendarConfig, RewardCalendarReward, SkillChainConfig, SkillChainType,
+    SkillMovementEffect, ZoneRules,
+};
 pub use game_data::GameData;
+pub use invasions::{ActiveInvasion, Invasions};
+pub use leaderboard_cache::LeaderboardCache;
 pub use login_tokens::{LoginToken, LoginTokens};
+pub use maintenance::{Maintenance, MaintenanceState};
+pub use name_filter::NameFilter;
+pub use npc_store_stock::{NpcStoreStock, NpcStoreStockItem};
+pub use packet_codec_seeds::{PacketCodecSeedUpdate, PacketCodecSeeds, PendingPacketCodecSeed};
+pub use personal_store_list::PersonalStoreList;
 pub use server_list::{GameServer, ServerList, WorldServer};
 pub use server_messages::ServerMessages;
+pub use storage_service::{StorageBackupConfig, StorageKey, StorageService, StorageWriteStatus};
+pub use tick_profiler::TickProfiler;
 pub use world_rates::WorldRates;
 pub use world_time::WorldTime;
-pub use zone_list::ZoneList;
+pub use zone_geometry::ZoneGeometry;
+pub use zone_list::{ZoneEnvironment, ZoneList};
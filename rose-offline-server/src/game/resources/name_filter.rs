@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::Resource;
+use regex::Regex;
+
+use rose_data::{NpcDatabase, ZoneDatabase};
+
+use crate::game::{resources::NameFilterConfig, storage::character::CharacterStorage};
+
+/// Maps a character to a canonical form so names which look the same are
+/// treated as the same name, e.g. "Adm1n", "ADMIN" and "Аdmin" (Cyrillic А).
+fn fold_lookalike_char(c: char) -> Option<char> {
+    // Fullwidth forms of ASCII characters
+    let c = match c as u32 {
+        0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    };
+
+    Some(match c {
+        // Combining diacritical marks are removed
+        '\u{0300}'..='\u{036F}' => return None,
+        // Zero width characters are removed
+        '\u{200B}'..='\u{200D}' | '\u{FEFF}' => return None,
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'а' | 'α' | '4' | '@' => 'a',
+        'в' | 'β' | '8' => 'b',
+        'ç' | 'ć' | 'с' => 'c',
+        'è' | 'é' | 'ê' | 'ë' | 'е' | 'ё' | 'ε' | '3' => 'e',
+        'н' | 'η' => 'h',
+        'ì' | 'í' | 'î' | 'ï' | 'і' | 'ι' | 'i' | '1' | '!' | '|' => 'l',
+        'ј' => 'j',
+        'к' | 'κ' => 'k',
+        'м' | 'μ' => 'm',
+        'ñ' | 'ν' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'о' | 'ο' | '0' => 'o',
+        'р' | 'ρ' => 'p',
+        'ѕ' | '5' | '$' => 's',
+        'т' | 'τ' | '7' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'υ' => 'u',
+        'х' | 'χ' => 'x',
+        'ý' | 'ÿ' | 'у' => 'y',
+        c => c,
+    })
+}
+
+/// Returns the canonical form of a name used for all name comparisons.
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .flat_map(char::to_lowercase)
+        .filter_map(fold_lookalike_char)
+        .collect()
+}
+
+/// Validates character and clan names against the blacklist and reserved names.
+///
+/// The canonical names of every stored character are kept in memory, so a new
+/// character name can be checked without listing the storage directory.
+#[derive(Resource)]
+pub struct NameFilter {
+    blacklist_patterns: Vec<Regex>,
+    blacklist_words: Vec<String>,
+    reserved_names: HashSet<String>,
+    character_names: HashMap<String, usize>,
+}
+
+impl NameFilter {
+    pub fn new(config: &NameFilterConfig, npcs: &NpcDatabase, zones: &ZoneDatabase) -> Self {
+        let mut reserved_names: HashSet<String> = config
+            .reserved_names
+            .iter()
+            .map(|name| normalize_name(name))
+            .collect();
+
+        // Only the NPCs placed in zones are reserved, monster names such as
+        // "Wolf" would otherwise reject many ordinary names
+        if config.reserve_npc_names {
+            reserved_names.extend(
+                zones
+                    .iter()
+                    .flat_map(|zone| zone.npcs.iter())
+                    .filter_map(|npc_spawn| npcs.get_npc(npc_spawn.npc_id))
+                    .filter(|npc| !npc.name.is_empty())
+                    .map(|npc| normalize_name(&npc.name)),
+            );
+        }
+
+        let mut name_filter = Self {
+            blacklist_patterns: config.blacklist_patterns.clone(),
+            blacklist_words: config
+                .blacklist_words
+                .iter()
+                .map(|word| normalize_name(word))
+                .filter(|word| !word.is_empty())
+                .collect(),
+            reserved_names,
+            character_names: HashMap::new(),
+        };
+        for name in CharacterStorage::find_matching(|_| true) {
+            name_filter.add_character_name(&name);
+        }
+        name_filter
+    }
+
+    pub fn is_name_allowed(&self, name: &str) -> bool {
+        let normalized_name = normalize_name(name);
+
+        !self.reserved_names.contains(&normalized_name)
+            && !self
+                .blacklist_words
+                .iter()
+                .any(|word| normalized_name.contains(word.as_str()))
+            && !self
+                .blacklist_patterns
+                .iter()
+                .any(|pattern| pattern.is_match(name))
+    }
+
+    pub fn is_same_name(&self, a: &str, b: &str) -> bool {
+        normalize_name(a) == normalize_name(b)
+    }
+
+    /// Returns true if a stored character has the same canonical name.
+    pub fn is_character_name_taken(&self, name: &str) -> bool {
+        self.character_names.contains_key(&normalize_name(name))
+    }
+
+    pub fn add_character_name(&mut self, name: &str) {
+        *self
+            .character_names
+            .entry(normalize_name(name))
+            .or_default() += 1;
+    }
+
+    pub fn remove_character_name(&mut self, name: &str) {
+        let key = normalize_name(name);
+        if let Some(count) = self.character_names.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                self.character_names.remove(&key);
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::Resource;
+use chrono::{DateTime, Utc};
+
+use rose_data::{ItemReference, NpcId};
+
+use crate::game::{
+    resources::NpcStoreStockConfig,
+    storage::npc_store_stock::{NpcStoreStockItemStorage, NpcStoreStockStorage},
+};
+
+pub struct NpcStoreStockItem {
+    pub npc_id: NpcId,
+    pub item: ItemReference,
+    pub quantity: u32,
+    pub max_quantity: u32,
+    pub restock_quantity: u32,
+    pub restock_interval: Duration,
+    pub next_restock_time: Instant,
+    pub announce_restock: bool,
+}
+
+/// The remaining quantity of NPC store items which have limited stock, any
+/// item which is not listed has unlimited stock.
+#[derive(Resource)]
+pub struct NpcStoreStock {
+    items: Vec<NpcStoreStockItem>,
+
+    /// Set when the stock has changed since it was last saved
+    changed: bool,
+}
+
+impl NpcStoreStock {
+    /// Creates the stock from the config, restoring the remaining quantity and
+    /// restock time of items from the saved stock.
+    pub fn new(
+        config: &NpcStoreStockConfig,
+        saved: Option<&NpcStoreStockStorage>,
+        now: Instant,
+        now_utc: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            items: config
+                .items
+                .iter()
+                .map(|item_config| {
+                    let saved_item = saved.and_then(|saved| {
+                        saved.items.iter().find(|saved_item| {
+                            saved_item.npc == item_config.npc && saved_item.item == item_config.item
+                        })
+                    });
+                    let restock_interval = item_config.get_restock_interval();
+
+                    NpcStoreStockItem {
+                        npc_id: item_config.npc,
+                        item: item_config.item,
+                        quantity: saved_item.map_or(item_config.max_quantity, |saved_item| {
+                            saved_item.quantity.min(item_config.max_quantity)
+                        }),
+                        max_quantity: item_config.max_quantity,
+                        restock_quantity: item_config
+                            .restock_quantity
+                            .unwrap_or(item_config.max_quantity),
+                        restock_interval,
+                        next_restock_time: saved_item.map_or(
+                            now + restock_interval,
+                            |saved_item| {
+                                now + (saved_item.next_restock - now_utc)
+                                    .to_std()
+                                    .unwrap_or(Duration::ZERO)
+                                    .min(restock_interval)
+                            },
+                        ),
+                        announce_restock: item_config.announce_restock,
+                    }
+                })
+                .collect(),
+            changed: false,
+        }
+    }
+
+    /// Returns the stock to save if it has changed since it was last saved.
+    pub fn take_changed_storage(
+        &mut self,
+        now: Instant,
+        now_utc: DateTime<Utc>,
+    ) -> Option<NpcStoreStockStorage> {
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+
+        Some(NpcStoreStockStorage {
+            items: self
+                .items
+                .iter()
+                .map(|stock_item| NpcStoreStockItemStorage {
+                    npc: stock_item.npc_id,
+                    item: stock_item.item,
+                    quantity: stock_item.quantity,
+                    next_restock: now_utc
+                        + chrono::Duration::from_std(
+                            stock_item.next_restock_time.saturating_duration_since(now),
+                        )
+                        .unwrap_or_else(|_| chrono::Duration::zero()),
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the remaining quantity of the item, or None if it is unlimited.
+    pub fn get_quantity(&self, npc_id: NpcId, item: ItemReference) -> Option<u32> {
+        self.items
+            .iter()
+            .find(|stock_item| stock_item.npc_id == npc_id && stock_item.item == item)
+            .map(|stock_item| stock_item.quantity)
+    }
+
+    /// Removes purchased items from stock, the purchase must have already been
+    /// checked against `get_quantity`.
+    pub fn take_quantity(&mut self, npc_id: NpcId, item: ItemReference, quantity: u32) {
+        if let Some(stock_item) = self
+            .items
+            .iter_mut()
+            .find(|stock_item| stock_item.npc_id == npc_id && stock_item.item == item)
+        {
+            stock_item.quantity = stock_item.quantity.saturating_sub(quantity);
+            self.changed = true;
+        }
+    }
+
+    /// Restocks every item whose restock time has passed, returning the items
+    /// which were restocked.
+    pub fn restock(&mut self, now: Instant) -> Vec<&NpcStoreStockItem> {
+        let mut restocked_items = Vec::new();
+
+        for stock_item in self.items.iter_mut() {
+            if now < stock_item.next_restock_time {
+                continue;
+            }
+            stock_item.next_restock_time = now + stock_item.restock_interval;
+            self.changed = true;
+
+            if stock_item.quantity < stock_item.max_quantity {
+                stock_item.quantity = stock_item
+                    .quantity
+                    .saturating_add(stock_item.restock_quantity)
+                    .min(stock_item.max_quantity);
+                restocked_items.push(&*stock_item);
+            }
+        }
+
+        restocked_items
+    }
+}
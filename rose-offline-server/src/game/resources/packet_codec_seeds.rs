@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::game::messages::control::ClientType;
+
+/// A change to the pending packet codec seeds, used to keep the seeds in sync
+/// with server processes connected over the remote control protocol.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PacketCodecSeedUpdate {
+    Added {
+        seed: u32,
+        pending: PendingPacketCodecSeed,
+    },
+    Removed {
+        seed: u32,
+    },
+}
+
+/// A seed is bound to the login token it was issued for, and can only be
+/// used by connections from the address the token was issued to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingPacketCodecSeed {
+    pub client_type: ClientType,
+    pub login_token: u32,
+    pub ip: IpAddr,
+}
+
+#[derive(Default)]
+struct PacketCodecSeedsState {
+    seeds: HashMap<u32, PendingPacketCodecSeed>,
+    subscribers: Vec<UnboundedSender<PacketCodecSeedUpdate>>,
+}
+
+impl PacketCodecSeedsState {
+    fn notify(&mut self, update: PacketCodecSeedUpdate) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(update.clone()).is_ok());
+    }
+}
+
+/// The packet codec seeds which have been sent to a client but not yet used
+/// to connect, shared between the game world and the protocol servers.
+#[derive(Clone, Default)]
+pub struct PacketCodecSeeds {
+    state: Arc<Mutex<PacketCodecSeedsState>>,
+}
+
+impl PacketCodecSeeds {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Generates a new unique non-zero seed for a connection to the server of
+    /// `client_type` using `login_token`
+    pub fn generate(&self, client_type: ClientType, login_token: u32, ip: IpAddr) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        let mut seed = 0u32;
+        while seed == 0 || state.seeds.contains_key(&seed) {
+            seed = rand::random();
+        }
+        let pending = PendingPacketCodecSeed {
+            client_type,
+            login_token,
+            ip,
+        };
+        state.seeds.insert(seed, pending);
+        state.notify(PacketCodecSeedUpdate::Added { seed, pending });
+        seed
+    }
+
+    /// Returns the seeds and their login tokens which are waiting for a
+    /// connection from `ip` to the server of `client_type`
+    pub fn get_pending(&self, client_type: ClientType, ip: IpAddr) -> Vec<(u32, u32)> {
+        self.state
+            .lock()
+            .unwrap()
+            .seeds
+            .iter()
+            .filter(|(_, pending)| pending.client_type == client_type && pending.ip == ip)
+            .map(|(seed, pending)| (*seed, pending.login_token))
+            .collect()
+    }
+
+    /// Removes a seed, returning false if it had already been claimed or removed
+    pub fn remove(&self, seed: u32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.seeds.remove(&seed).is_none() {
+            return false;
+        }
+        state.notify(PacketCodecSeedUpdate::Removed { seed });
+        true
+    }
+
+    /// Applies an update received from another server process, subscribers
+    /// are only notified when the update changes the pending seeds.
+    pub fn apply(&self, update: PacketCodecSeedUpdate) {
+        match update {
+            PacketCodecSeedUpdate::Added { seed, pending } => {
+                let mut state = self.state.lock().unwrap();
+                if state.seeds.insert(seed, pending).is_none() {
+                    state.notify(update);
+                }
+            }
+            PacketCodecSeedUpdate::Removed { seed } => {
+                self.remove(seed);
+            }
+        }
+    }
+
+    /// Returns a receiver for every change to the pending seeds, starting with
+    /// the seeds which are currently pending.
+    pub fn subscribe(&self) -> UnboundedReceiver<PacketCodecSeedUpdate> {
+        let (update_tx, update_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut state = self.state.lock().unwrap();
+        for (&seed, &pending) in state.seeds.iter() {
+            update_tx
+                .send(PacketCodecSeedUpdate::Added { seed, pending })
+                .ok();
+        }
+        state.subscribers.push(update_tx);
+        update_rx
+    }
+}
@@ -0,0 +1,34 @@
+use bevy::{ecs::prelude::Entity, prelude::Resource};
+use std::collections::HashMap;
+
+use rose_data::ZoneId;
+
+/// Index of all open personal stores, used to search stores without
+/// having to query every character in the zone.
+#[derive(Resource)]
+pub struct PersonalStoreList {
+    stores: HashMap<Entity, ZoneId>,
+}
+
+impl PersonalStoreList {
+    pub fn new() -> Self {
+        Self {
+            stores: Default::default(),
+        }
+    }
+
+    pub fn add_store(&mut self, entity: Entity, zone_id: ZoneId) {
+        self.stores.insert(entity, zone_id);
+    }
+
+    pub fn remove_store(&mut self, entity: Entity) {
+        self.stores.remove(&entity);
+    }
+
+    pub fn iter_zone(&self, zone_id: ZoneId) -> impl Iterator<Item = Entity> + '_ {
+        self.stores
+            .iter()
+            .filter(move |(_, store_zone_id)| **store_zone_id == zone_id)
+            .map(|(entity, _)| *entity)
+    }
+}
@@ -6,6 +6,26 @@ pub struct GameServer {
     pub ip: String,
     pub port: u16,
     pub packet_codec_seed: u32,
+
+    /// The number of clients connected to the game server
+    pub num_clients: usize,
+
+    /// Set whilst the channel is above the overload warning, so the warning
+    /// is only logged once each time it becomes crowded
+    pub is_overloaded: bool,
+}
+
+impl GameServer {
+    /// How crowded the channel is from 0 to 100, or always 0 when channels do
+    /// not have a maximum number of players
+    pub fn percent_full(&self, max_players: Option<usize>) -> u8 {
+        match max_players {
+            Some(max_players) if max_players > 0 => {
+                (self.num_clients * 100 / max_players).min(100) as u8
+            }
+            _ => 0,
+        }
+    }
 }
 
 pub struct WorldServer {
@@ -26,4 +46,11 @@ impl ServerList {
     pub fn new() -> Self {
         Default::default()
     }
+
+    pub fn find_game_server_mut(&mut self, entity: Entity) -> Option<&mut GameServer> {
+        self.world_servers
+            .iter_mut()
+            .flat_map(|world_server| world_server.channels.iter_mut())
+            .find(|game_server| game_server.entity == entity)
+    }
 }
@@ -0,0 +1,509 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::ErrorKind,
+    path::PathBuf,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::Resource;
+use log::{error, info, warn};
+
+use rose_data::ZoneId;
+
+use crate::game::{
+    components::PartyUniqueId,
+    storage::{backup::create_backup, item_transaction::ItemTransaction},
+};
+
+/// The delay before retrying after the first transient storage failure, this
+/// doubles for every consecutive failure up to `STORAGE_RETRY_MAX_BACKOFF`
+const STORAGE_RETRY_BASE_BACKOFF: Duration = Duration::from_millis(250);
+const STORAGE_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The number of consecutive transient failures after which the circuit
+/// breaker opens and new writes are queued without being attempted
+const STORAGE_CIRCUIT_BREAKER_THRESHOLD: u32 = 5;
+
+/// Limits how long flushing the queue can stall a single frame after recovery
+const STORAGE_MAX_RETRIES_PER_UPDATE: usize = 64;
+
+/// Queued writes hold a copy of the documents they write, so new writes are
+/// rejected once this many are queued instead of growing without limit
+const STORAGE_MAX_PENDING_WRITES: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageKey {
+    Account(String),
+    Bank(String),
+    Character(String),
+    ChatMute(String),
+    Clan(String),
+    ClanBank(String),
+    ItemDrops(ZoneId),
+    NpcStoreStock,
+    Party(PartyUniqueId),
+    RewardCalendar(String),
+    Suspicion(String),
+}
+
+impl Display for StorageKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageKey::Account(name) => write!(f, "account {}", name),
+            StorageKey::Bank(account_name) => write!(f, "bank for account {}", account_name),
+            StorageKey::Character(name) => write!(f, "character {}", name),
+            StorageKey::ChatMute(name) => write!(f, "chat mute for character {}", name),
+            StorageKey::Clan(name) => write!(f, "clan {}", name),
+            StorageKey::ClanBank(clan_name) => write!(f, "bank for clan {}", clan_name),
+            StorageKey::ItemDrops(zone_id) => write!(f, "item drops for zone {}", zone_id.get()),
+            StorageKey::NpcStoreStock => write!(f, "npc store stock"),
+            StorageKey::Party(unique_id) => write!(f, "party {}", unique_id),
+            StorageKey::RewardCalendar(account_name) => {
+                write!(f, "reward calendar for account {}", account_name)
+            }
+            StorageKey::Suspicion(account_name) => {
+                write!(f, "suspicion for account {}", account_name)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StorageHealth {
+    /// The last storage write succeeded
+    Healthy,
+    /// Recent writes failed with transient errors and are being retried
+    Degraded,
+    /// The circuit breaker is open, writes are queued until a retry succeeds
+    Unavailable,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StorageWriteStatus {
+    Written,
+    Queued,
+}
+
+/// Periodically backs up every storage document to a compressed snapshot.
+#[derive(Clone, Debug)]
+pub struct StorageBackupConfig {
+    pub dir: PathBuf,
+    pub interval: Duration,
+
+    /// The number of backups to keep, older backups are deleted
+    pub retention: usize,
+}
+
+struct StorageBackupSchedule {
+    config: StorageBackupConfig,
+    next_backup: Instant,
+    running: Option<JoinHandle<Result<PathBuf, anyhow::Error>>>,
+    last_backup: Option<PathBuf>,
+}
+
+type StorageWriteFn = Box<dyn FnMut() -> Result<(), anyhow::Error> + Send + Sync>;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum StorageWriteKind {
+    /// Saves the whole of each document
+    Document,
+    /// Saves only the items and money of each document, see `ItemTransaction`
+    ItemTransaction,
+}
+
+struct PendingStorageWrite {
+    kind: StorageWriteKind,
+    keys: Vec<StorageKey>,
+    write: StorageWriteFn,
+}
+
+impl Display for PendingStorageWrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, key) in self.keys.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Performs storage writes, retrying writes which fail with a transient error
+/// with exponential backoff instead of losing the data.
+///
+/// Queued writes are always retried in the order they were made, so a write
+/// for a document which already has a queued write is queued behind it.
+#[derive(Resource)]
+pub struct StorageService {
+    pending_writes: VecDeque<PendingStorageWrite>,
+    consecutive_failures: u32,
+    next_retry: Instant,
+    last_error: Option<String>,
+    backup: Option<StorageBackupSchedule>,
+}
+
+// The error kinds for a full disk, quota or busy file are not stable in our
+// minimum supported rust version, so check for the os error codes instead
+#[cfg(unix)]
+fn is_transient_os_error(code: i32) -> bool {
+    const EBUSY: i32 = 16;
+    const ENOSPC: i32 = 28;
+    #[cfg(target_os = "linux")]
+    const EDQUOT: i32 = 122;
+    #[cfg(not(target_os = "linux"))]
+    const EDQUOT: i32 = 69;
+    #[cfg(target_os = "linux")]
+    const ESTALE: i32 = 116;
+    #[cfg(not(target_os = "linux"))]
+    const ESTALE: i32 = 70;
+
+    matches!(code, EBUSY | ENOSPC | EDQUOT | ESTALE)
+}
+
+#[cfg(windows)]
+fn is_transient_os_error(code: i32) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    const ERROR_HANDLE_DISK_FULL: i32 = 39;
+    const ERROR_DISK_FULL: i32 = 112;
+    const ERROR_DISK_QUOTA_EXCEEDED: i32 = 1295;
+
+    matches!(
+        code,
+        ERROR_SHARING_VIOLATION
+            | ERROR_LOCK_VIOLATION
+            | ERROR_HANDLE_DISK_FULL
+            | ERROR_DISK_FULL
+            | ERROR_DISK_QUOTA_EXCEEDED
+    )
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_transient_os_error(_code: i32) -> bool {
+    false
+}
+
+fn is_transient_error(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|io_error| {
+            matches!(
+                io_error.kind(),
+                ErrorKind::Interrupted | ErrorKind::TimedOut | ErrorKind::WouldBlock
+            ) || io_error.raw_os_error().map_or(false, is_transient_os_error)
+        })
+}
+
+impl StorageService {
+    pub fn new() -> Self {
+        Self {
+            pending_writes: VecDeque::new(),
+            consecutive_failures: 0,
+            next_retry: Instant::now(),
+            last_error: None,
+            backup: None,
+        }
+    }
+
+    /// Enables scheduled backups, the first backup is made after one interval.
+    pub fn set_backup_config(&mut self, config: StorageBackupConfig, now: Instant) {
+        self.backup = Some(StorageBackupSchedule {
+            next_backup: now + config.interval,
+            config,
+            running: None,
+            last_backup: None,
+        });
+    }
+
+    /// Returns the path of the last backup which completed successfully.
+    pub fn last_backup(&self) -> Option<&PathBuf> {
+        self.backup
+            .as_ref()
+            .and_then(|backup| backup.last_backup.as_ref())
+    }
+
+    /// Returns the time until the next scheduled backup, if backups are enabled.
+    pub fn time_until_backup(&self, now: Instant) -> Option<Duration> {
+        self.backup
+            .as_ref()
+            .map(|backup| backup.next_backup.saturating_duration_since(now))
+    }
+
+    pub fn health(&self) -> StorageHealth {
+        if self.consecutive_failures == 0 {
+            StorageHealth::Healthy
+        } else if self.consecutive_failures < STORAGE_CIRCUIT_BREAKER_THRESHOLD {
+            StorageHealth::Degraded
+        } else {
+            StorageHealth::Unavailable
+        }
+    }
+
+    pub fn num_pending_writes(&self) -> usize {
+        self.pending_writes.len()
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Returns the time until queued writes are next retried, if any are queued.
+    pub fn time_until_retry(&self, now: Instant) -> Option<Duration> {
+        if self.pending_writes.is_empty() {
+            None
+        } else {
+            Some(self.next_retry.saturating_duration_since(now))
+        }
+    }
+
+    /// Documents with a queued write must not be loaded, as storage does not
+    /// contain their latest data yet.
+    pub fn has_pending_write(&self, key: &StorageKey) -> bool {
+        self.pending_writes
+            .iter()
+            .any(|pending_write| pending_write.keys.contains(key))
+    }
+
+    /// Attempts the write immediately unless it must be queued, transient
+    /// errors queue the write to be retried and only other errors are returned.
+    pub fn write(
+        &mut self,
+        key: StorageKey,
+        write: impl FnMut() -> Result<(), anyhow::Error> + Send + Sync + 'static,
+    ) -> Result<StorageWriteStatus, anyhow::Error> {
+        self.write_many(StorageWriteKind::Document, vec![key], write)
+    }
+
+    /// Performs a single write which changes several documents, it is queued
+    /// behind any queued write for any of the documents.
+    fn write_many(
+        &mut self,
+        kind: StorageWriteKind,
+        keys: Vec<StorageKey>,
+        write: impl FnMut() -> Result<(), anyhow::Error> + Send + Sync + 'static,
+    ) -> Result<StorageWriteStatus, anyhow::Error> {
+        let mut pending_write = PendingStorageWrite {
+            kind,
+            keys,
+            write: Box::new(write),
+        };
+
+        if self.health() == StorageHealth::Unavailable
+            || pending_write
+                .keys
+                .iter()
+                .any(|key| self.has_pending_write(key))
+        {
+            return self.queue_write(pending_write);
+        }
+
+        let now = Instant::now();
+        match (pending_write.write)() {
+            Ok(()) => {
+                self.record_success(now);
+                Ok(StorageWriteStatus::Written)
+            }
+            Err(error) if is_transient_error(&error) => {
+                self.record_failure(now, &error);
+                warn!(
+                    "Queued write of {} after transient storage error {:?}",
+                    pending_write, error
+                );
+                self.queue_write(pending_write)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn queue_write(
+        &mut self,
+        pending_write: PendingStorageWrite,
+    ) -> Result<StorageWriteStatus, anyhow::Error> {
+        // A queued write of the same kind for exactly the same documents is
+        // superseded by the new write, which is queued behind every other write
+        // for them. An item transaction only saves part of a document, so it can
+        // never supersede or be superseded by a save of the whole document.
+        if let Some(index) = self.pending_writes.iter().position(|queued| {
+            queued.kind == pending_write.kind && queued.keys == pending_write.keys
+        }) {
+            self.pending_writes.remove(index);
+        }
+
+        if self.pending_writes.len() >= STORAGE_MAX_PENDING_WRITES {
+            return Err(anyhow::anyhow!(
+                "Storage write queue is full with {} writes, dropped write of {}",
+                self.pending_writes.len(),
+                pending_write
+            ));
+        }
+
+        self.pending_writes.push_back(pending_write);
+        Ok(StorageWriteStatus::Queued)
+    }
+
+    /// Saves every document changed by the item transaction in one write.
+    pub fn write_item_transaction(
+        &mut self,
+        transaction: ItemTransaction,
+    ) -> Result<StorageWriteStatus, anyhow::Error> {
+        if transaction.is_empty() {
+            return Ok(StorageWriteStatus::Written);
+        }
+
+        self.write_many(
+            StorageWriteKind::ItemTransaction,
+            transaction.storage_keys(),
+            move || transaction.commit(),
+        )
+    }
+
+    /// Retries queued writes once their backoff has elapsed, and starts the
+    /// next scheduled backup when it is due.
+    pub fn update(&mut self, now: Instant) {
+        self.update_backup(now);
+
+        if now < self.next_retry {
+            return;
+        }
+
+        self.retry_pending_writes(now);
+    }
+
+    /// Retries queued writes until the queue is empty or the timeout elapses,
+    /// and waits for a running backup to complete. Used when the server exits.
+    pub fn flush(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+
+        if let Some(running) = self
+            .backup
+            .as_mut()
+            .and_then(|backup| backup.running.take())
+        {
+            if running.join().is_err() {
+                error!("Storage backup thread panicked");
+            }
+        }
+
+        while !self.pending_writes.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                for pending_write in self.pending_writes.iter() {
+                    error!("Lost queued write of {} on exit", pending_write);
+                }
+                return;
+            }
+
+            if now < self.next_retry {
+                std::thread::sleep(self.next_retry.min(deadline) - now);
+                continue;
+            }
+
+            self.retry_pending_writes(now);
+        }
+    }
+
+    fn retry_pending_writes(&mut self, now: Instant) {
+        for _ in 0..STORAGE_MAX_RETRIES_PER_UPDATE {
+            let Some(pending_write) = self.pending_writes.front_mut() else {
+                break;
+            };
+
+            let result = (pending_write.write)();
+            match result {
+                Ok(()) => {
+                    let pending_write = self.pending_writes.pop_front().unwrap();
+                    if self.consecutive_failures > 0 {
+                        info!(
+                            "Storage recovered after {} consecutive failures, {} writes still queued",
+                            self.consecutive_failures,
+                            self.pending_writes.len()
+                        );
+                    }
+                    self.record_success(now);
+                    info!("Saved {} from storage queue", pending_write);
+                }
+                Err(error) if is_transient_error(&error) => {
+                    self.record_failure(now, &error);
+                    warn!(
+                        "Failed to retry queued write of {} with error {:?}, {} writes queued",
+                        self.pending_writes[0],
+                        error,
+                        self.pending_writes.len()
+                    );
+                    break;
+                }
+                Err(error) => {
+                    let pending_write = self.pending_writes.pop_front().unwrap();
+                    error!(
+                        "Dropped queued write of {} after storage error {:?}",
+                        pending_write, error
+                    );
+                }
+            }
+        }
+    }
+
+    fn update_backup(&mut self, now: Instant) {
+        let pending_writes = self.pending_writes.len();
+        let Some(backup) = self.backup.as_mut() else {
+            return;
+        };
+
+        if backup
+            .running
+            .as_ref()
+            .map_or(false, |running| running.is_finished())
+        {
+            match backup.running.take().unwrap().join() {
+                Ok(Ok(path)) => {
+                    info!("Saved storage backup {}", path.to_string_lossy());
+                    backup.last_backup = Some(path);
+                }
+                Ok(Err(error)) => error!("Failed to save storage backup with error {:?}", error),
+                Err(_) => error!("Storage backup thread panicked"),
+            }
+        }
+
+        // Storage does not contain the latest data whilst writes are queued,
+        // so the backup is delayed until the queue has been flushed
+        if backup.running.is_some() || now < backup.next_backup || pending_writes > 0 {
+            return;
+        }
+
+        let backup_dir = backup.config.dir.clone();
+        let retention = backup.config.retention;
+        backup.next_backup = now + backup.config.interval;
+        backup.running = Some(std::thread::spawn(move || {
+            create_backup(&backup_dir, retention)
+        }));
+    }
+
+    fn record_success(&mut self, now: Instant) {
+        self.consecutive_failures = 0;
+        self.next_retry = now;
+    }
+
+    fn record_failure(&mut self, now: Instant, error: &anyhow::Error) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.consecutive_failures == STORAGE_CIRCUIT_BREAKER_THRESHOLD {
+            warn!(
+                "Storage circuit breaker opened after {} consecutive failures",
+                self.consecutive_failures
+            );
+        }
+
+        let backoff = STORAGE_RETRY_BASE_BACKOFF
+            .saturating_mul(1u32 << (self.consecutive_failures - 1).min(16))
+            .min(STORAGE_RETRY_MAX_BACKOFF);
+        self.next_retry = now + backoff;
+        self.last_error = Some(format!("{:#}", error));
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::Resource,
+    utils::tracing::{
+        self,
+        field::{Field, Visit},
+        span, Subscriber,
+    },
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+/// The execution time of every system which ran during a single tick.
+pub struct TickProfile {
+    pub duration: Duration,
+    pub systems: Vec<(String, Duration)>,
+}
+
+impl TickProfile {
+    /// Returns the systems which took the longest to execute, slowest first.
+    pub fn slowest_systems(&self, count: usize) -> Vec<&(String, Duration)> {
+        let mut systems: Vec<_> = self.systems.iter().collect();
+        systems.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+        systems.truncate(count);
+        systems
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct TickProfilerSystemStats {
+    pub num_runs: u64,
+    pub total_duration: Duration,
+    pub max_duration: Duration,
+}
+
+impl TickProfilerSystemStats {
+    pub fn average_duration(&self) -> Duration {
+        if self.num_runs == 0 {
+            Duration::ZERO
+        } else {
+            self.total_duration.div_f64(self.num_runs as f64)
+        }
+    }
+}
+
+#[derive(Default)]
+struct TickProfilerShared {
+    systems: Vec<(String, Duration)>,
+    ticks: Vec<TickProfile>,
+}
+
+/// Records the execution time of systems and ticks from the spans which bevy
+/// creates when built with its trace feature.
+#[derive(Resource)]
+pub struct TickProfiler {
+    shared: Arc<Mutex<TickProfilerShared>>,
+    pub budget: Duration,
+    pub num_ticks: u64,
+    pub num_ticks_over_budget: u64,
+    pub total_duration: Duration,
+    pub max_duration: Duration,
+    pub systems: HashMap<String, TickProfilerSystemStats>,
+}
+
+impl TickProfiler {
+    /// Installs the profiler as the global tracing subscriber, which fails if
+    /// a subscriber has already been set.
+    pub fn install(budget: Duration) -> Result<Self, tracing::subscriber::SetGlobalDefaultError> {
+        let shared = Arc::new(Mutex::new(TickProfilerShared::default()));
+        tracing::subscriber::set_global_default(tracing_subscriber::registry().with(
+            TickProfilerLayer {
+                shared: shared.clone(),
+            },
+        ))?;
+
+        Ok(Self {
+            shared,
+            budget,
+            num_ticks: 0,
+            num_ticks_over_budget: 0,
+            total_duration: Duration::ZERO,
+            max_duration: Duration::ZERO,
+            systems: HashMap::new(),
+        })
+    }
+
+    pub fn average_duration(&self) -> Duration {
+        if self.num_ticks == 0 {
+            Duration::ZERO
+        } else {
+            self.total_duration.div_f64(self.num_ticks as f64)
+        }
+    }
+
+    /// Returns the systems with the longest average execution time, slowest first.
+    pub fn slowest_systems(&self, count: usize) -> Vec<(&str, TickProfilerSystemStats)> {
+        let mut systems: Vec<_> = self
+            .systems
+            .iter()
+            .map(|(name, stats)| (name.as_str(), *stats))
+            .collect();
+        systems.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.average_duration()));
+        systems.truncate(count);
+        systems
+    }
+
+    /// Accumulates the ticks which have completed since the last update,
+    /// returning the ticks which exceeded the budget.
+    pub fn update(&mut self) -> Vec<TickProfile> {
+        let ticks = std::mem::take(&mut self.shared.lock().unwrap().ticks);
+        let mut ticks_over_budget = Vec::new();
+
+        for tick in ticks {
+            self.num_ticks += 1;
+            self.total_duration += tick.duration;
+            self.max_duration = self.max_duration.max(tick.duration);
+
+            for (name, duration) in tick.systems.iter() {
+                let stats = self.systems.entry(name.clone()).or_default();
+                stats.num_runs += 1;
+                stats.total_duration += *duration;
+                stats.max_duration = stats.max_duration.max(*duration);
+            }
+
+            if tick.duration > self.budget {
+                self.num_ticks_over_budget += 1;
+                ticks_over_budget.push(tick);
+            }
+        }
+
+        ticks_over_budget
+    }
+}
+
+/// Strips the module path from a system name, e.g.
+/// `rose_offline_server::game::systems::npc_ai_system::npc_ai_system`
+fn get_short_system_name(name: &str) -> &str {
+    let path_end = name.find('<').unwrap_or(name.len());
+    name[..path_end]
+        .rfind("::")
+        .map_or(name, |index| &name[index + 2..])
+}
+
+#[derive(Default)]
+struct SystemNameVisitor {
+    name: Option<String>,
+}
+
+impl Visit for SystemNameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.name = Some(get_short_system_name(value).to_string());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+struct SystemSpan {
+    name: String,
+    busy: Duration,
+    entered: Option<Instant>,
+}
+
+struct TickSpan {
+    started: Instant,
+}
+
+struct TickProfilerLayer {
+    shared: Arc<Mutex<TickProfilerShared>>,
+}
+
+impl<S> Layer<S> for TickProfilerLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        match attrs.metadata().name() {
+            "system" => {
+                let mut visitor = SystemNameVisitor::default();
+                attrs.record(&mut visitor);
+                if let Some(name) = visitor.name {
+                    span.extensions_mut().insert(SystemSpan {
+                        name,
+                        busy: Duration::ZERO,
+                        entered: None,
+                    });
+                }
+            }
+            "update" => {
+                span.extensions_mut().insert(TickSpan {
+                    started: Instant::now(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(system_span) = span.extensions_mut().get_mut::<SystemSpan>() {
+                system_span.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(system_span) = span.extensions_mut().get_mut::<SystemSpan>() {
+                if let Some(entered) = system_span.entered.take() {
+                    system_span.busy += entered.elapsed();
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+
+        if let Some(system_span) = extensions.remove::<SystemSpan>() {
+            self.shared
+                .lock()
+                .unwrap()
+                .systems
+                .push((system_span.name, system_span.busy));
+        } else if let Some(tick_span) = extensions.remove::<TickSpan>() {
+            let mut shared = self.shared.lock().unwrap();
+            let systems = std::mem::take(&mut shared.systems);
+            shared.ticks.push(TickProfile {
+                duration: tick_span.started.elapsed(),
+                systems,
+            });
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource)]
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct WorldRates {
     pub xp_rate: i32,
     pub drop_rate: i32,
@@ -11,6 +13,8 @@ pub struct WorldRates {
     pub world_price_rate: i32,
     pub item_price_rate: i32,
     pub town_price_rate: i32,
+    pub npc_store_buy_rate: i32,
+    pub npc_store_sell_rate: i32,
 }
 
 impl WorldRates {
@@ -25,6 +29,14 @@ impl WorldRates {
             world_price_rate: 100,
             item_price_rate: 50,
             town_price_rate: 100,
+            npc_store_buy_rate: 0,
+            npc_store_sell_rate: 0,
         }
     }
 }
+
+impl Default for WorldRates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::{
+    math::{Vec3, Vec3Swizzles},
+    prelude::Resource,
+};
+use std::sync::Arc;
+
+use rose_data::{ZoneGeometryDatabase, ZoneId};
+
+// Height above the ground which line of sight is checked from and to
+const LINE_OF_SIGHT_EYE_HEIGHT: f32 = 150.0;
+
+#[derive(Resource)]
+pub struct ZoneGeometry {
+    database: Arc<ZoneGeometryDatabase>,
+}
+
+impl ZoneGeometry {
+    pub fn new(database: Arc<ZoneGeometryDatabase>) -> Self {
+        Self { database }
+    }
+
+    /// Returns the first position along the segment from start to end which
+    /// hits the zone terrain or an obstacle.
+    pub fn raycast(&self, zone_id: ZoneId, start: Vec3, end: Vec3) -> Option<Vec3> {
+        self.database
+            .get_zone_geometry(zone_id)
+            .and_then(|zone_geometry| zone_geometry.raycast(start, end))
+    }
+
+    /// Returns true if an entity standing at `from` can see an entity standing
+    /// at `to`, zones without geometry data never block line of sight.
+    pub fn has_line_of_sight(&self, zone_id: ZoneId, from: Vec3, to: Vec3) -> bool {
+        let Some(zone_geometry) = self.database.get_zone_geometry(zone_id) else {
+            return true;
+        };
+
+        // Entity positions do not always have an accurate height, so never go below the terrain
+        let get_eye_position = |position: Vec3| {
+            let ground_height = zone_geometry
+                .get_terrain_height(position.xy())
+                .map_or(position.z, |terrain_height| terrain_height.max(position.z));
+            Vec3::new(
+                position.x,
+                position.y,
+                ground_height + LINE_OF_SIGHT_EYE_HEIGHT,
+            )
+        };
+
+        zone_geometry
+            .raycast(get_eye_position(from), get_eye_position(to))
+            .is_none()
+    }
+}
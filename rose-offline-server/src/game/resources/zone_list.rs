@@ -1,7 +1,12 @@
 use bevy::{ecs::prelude::Entity, prelude::Resource};
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
 
-use rose_data::{NpcId, ZoneId};
+use rose_data::{NpcId, ZoneId, ZoneTimeOfDay, ZoneWeather};
+
+use crate::game::resources::ZoneRules;
 
 #[derive(Hash, PartialEq, Eq)]
 struct EventObjectKey {
@@ -10,9 +15,22 @@ struct EventObjectKey {
     map_chunk_y: i32,
 }
 
+#[derive(Clone, Debug)]
+pub struct ZoneEnvironment {
+    pub time_of_day: ZoneTimeOfDay,
+    pub weather: ZoneWeather,
+    pub next_weather_time: Instant,
+}
+
 struct ZoneData {
     monster_spawns_enabled: bool,
     event_objects: HashMap<EventObjectKey, Entity>,
+    environment: Option<ZoneEnvironment>,
+    rules: Option<ZoneRules>,
+
+    /// Item drops in the order they were spawned, this can contain item drops
+    /// which have since been removed
+    item_drops: VecDeque<Entity>,
 }
 
 #[derive(Resource)]
@@ -35,6 +53,9 @@ impl ZoneList {
             ZoneData {
                 monster_spawns_enabled: true,
                 event_objects: Default::default(),
+                environment: None,
+                rules: None,
+                item_drops: VecDeque::new(),
             },
         );
     }
@@ -55,6 +76,47 @@ impl ZoneList {
         }
     }
 
+    pub fn get_environment(&self, zone_id: ZoneId) -> Option<&ZoneEnvironment> {
+        self.zones
+            .get(&zone_id)
+            .and_then(|zone| zone.environment.as_ref())
+    }
+
+    pub fn set_environment(&mut self, zone_id: ZoneId, environment: ZoneEnvironment) {
+        if let Some(zone) = self.zones.get_mut(&zone_id) {
+            zone.environment = Some(environment);
+        }
+    }
+
+    pub fn set_rules(&mut self, zone_id: ZoneId, rules: ZoneRules) {
+        if let Some(zone) = self.zones.get_mut(&zone_id) {
+            zone.rules = Some(rules);
+        }
+    }
+
+    fn get_rules(&self, zone_id: ZoneId) -> Option<&ZoneRules> {
+        self.zones
+            .get(&zone_id)
+            .and_then(|zone| zone.rules.as_ref())
+    }
+
+    pub fn is_safe_zone(&self, zone_id: ZoneId) -> bool {
+        self.get_rules(zone_id)
+            .map_or(false, |rules| rules.safe_zone)
+    }
+
+    pub fn is_town(&self, zone_id: ZoneId) -> bool {
+        self.get_rules(zone_id).map_or(true, |rules| rules.town)
+    }
+
+    pub fn get_min_level(&self, zone_id: ZoneId) -> u32 {
+        self.get_rules(zone_id).map_or(0, |rules| rules.min_level)
+    }
+
+    pub fn iter_zone_ids(&self) -> impl Iterator<Item = ZoneId> + '_ {
+        self.zones.keys().copied()
+    }
+
     pub fn add_event_object(
         &mut self,
         zone_id: ZoneId,
@@ -93,6 +155,18 @@ impl ZoneList {
         })
     }
 
+    pub fn add_item_drop(&mut self, zone_id: ZoneId, entity: Entity) {
+        if let Some(zone) = self.zones.get_mut(&zone_id) {
+            zone.item_drops.push_back(entity);
+        }
+    }
+
+    pub fn get_item_drops_mut(&mut self, zone_id: ZoneId) -> Option<&mut VecDeque<Entity>> {
+        self.zones
+            .get_mut(&zone_id)
+            .map(|zone| &mut zone.item_drops)
+    }
+
     pub fn add_npc(&mut self, npc_id: NpcId, entity: Entity) {
         self.npcs.insert(npc_id, entity);
     }
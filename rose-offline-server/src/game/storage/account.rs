@@ -1,12 +1,26 @@
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
 use std::{io::Write, path::PathBuf};
 use thiserror::Error;
 
 use rose_game_common::data::Password;
 
-use crate::game::storage::ACCOUNT_STORAGE_DIR;
+use crate::game::storage::{
+    index_storage_document,
+    schema_version::{migrate_add_schema_version, migrate_insert_default, StorageSchema},
+    storage_document_path, storage_name_key, storage_names, ACCOUNT_STORAGE_DIR,
+};
+
+const EMAIL_VERIFICATION_TOKEN_HOURS: i64 = 24;
+const PASSWORD_RESET_TOKEN_HOURS: i64 = 1;
+
+/// The number of consecutive wrong secondary PINs after which the PIN is
+/// locked, and for how long
+pub const SECONDARY_PIN_MAX_FAILURES: u32 = 5;
+const SECONDARY_PIN_LOCKOUT_MINUTES: i64 = 15;
 
 #[derive(Error, Debug)]
 pub enum AccountStorageError {
@@ -15,17 +29,144 @@ pub enum AccountStorageError {
 
     #[error("Account not found")]
     NotFound,
+
+    #[error("Invalid email address")]
+    InvalidEmail,
+
+    #[error("Account does not have a verified email address")]
+    EmailNotVerified,
+
+    #[error("Invalid or expired token")]
+    InvalidToken,
+
+    #[error("Secondary PIN must be 4 to 8 digits")]
+    InvalidSecondaryPinFormat,
+
+    #[error("Invalid secondary PIN")]
+    InvalidSecondaryPin { attempts_remaining: u32 },
+
+    #[error("Secondary PIN is locked")]
+    SecondaryPinLocked { locked_until: DateTime<Utc> },
+}
+
+/// A single use token which is given to the player by email, only the hash of
+/// the token is stored.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccountToken {
+    pub token_sha256: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+fn hash_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+impl AccountToken {
+    /// Returns a new random token and its stored form
+    fn generate(now: DateTime<Utc>, valid_hours: i64) -> (String, Self) {
+        let token = hex::encode(rand::random::<[u8; 16]>());
+        let account_token = Self {
+            token_sha256: hash_token(&token),
+            expires_at: now + chrono::Duration::hours(valid_hours),
+        };
+        (token, account_token)
+    }
+
+    fn is_valid(&self, token: &str, now: DateTime<Utc>) -> bool {
+        now < self.expires_at && self.token_sha256 == hash_token(token.trim())
+    }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct AccountStorage {
     pub name: String,
     pub password_md5_sha256: String,
     pub character_names: Vec<String>,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub email_verification_token: Option<AccountToken>,
+    pub password_reset_token: Option<AccountToken>,
+    pub secondary_pin_sha256: Option<String>,
+    pub secondary_pin_failures: u32,
+    pub secondary_pin_locked_until: Option<DateTime<Utc>>,
+
+    /// The language used for strings the server sends to the account, or
+    /// None for the server language
+    pub language: Option<usize>,
+}
+
+const ACCOUNT_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[
+    migrate_add_schema_version,
+    migrate_account_v1,
+    migrate_account_v2,
+    migrate_account_v3,
+]);
+
+/// Accounts saved before email binding have no email or tokens.
+fn migrate_account_v1(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "email", Option::<String>::None)?;
+    migrate_insert_default(document, "email_verified", false)?;
+    migrate_insert_default(
+        document,
+        "email_verification_token",
+        Option::<AccountToken>::None,
+    )?;
+    migrate_insert_default(
+        document,
+        "password_reset_token",
+        Option::<AccountToken>::None,
+    )?;
+    Ok(())
+}
+
+/// Accounts saved before secondary PINs were added have no PIN.
+fn migrate_account_v2(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "secondary_pin_sha256", Option::<String>::None)?;
+    migrate_insert_default(document, "secondary_pin_failures", 0u32)?;
+    migrate_insert_default(
+        document,
+        "secondary_pin_locked_until",
+        Option::<DateTime<Utc>>::None,
+    )?;
+    Ok(())
+}
+
+/// Accounts saved before language selection use the server language.
+fn migrate_account_v3(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "language", Option::<usize>::None)?;
+    Ok(())
+}
+
+/// A minimal check which rejects addresses that could not be delivered to,
+/// including any with whitespace which could inject email headers.
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains('@')
+        && email.len() <= 254
+        && !email.chars().any(|c| c.is_whitespace() || c.is_control())
 }
 
 fn get_account_path(name: &str) -> PathBuf {
-    ACCOUNT_STORAGE_DIR.join(format!("{}.json", name))
+    storage_document_path(&ACCOUNT_STORAGE_DIR, name)
+}
+
+/// The account name is included in the hash so that the same PIN does not
+/// have the same hash for every account.
+fn hash_secondary_pin(account_name: &str, pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(account_name.as_bytes());
+    hasher.update(b":");
+    hasher.update(pin.as_bytes());
+    hex::encode(hasher.finalize())
 }
 
 fn hash_password(password: &Password) -> String {
@@ -40,29 +181,190 @@ impl AccountStorage {
             name: String::from(name),
             password_md5_sha256: hash_password(password),
             character_names: Vec::new(),
+            email: None,
+            email_verified: false,
+            email_verification_token: None,
+            password_reset_token: None,
+            secondary_pin_sha256: None,
+            secondary_pin_failures: 0,
+            secondary_pin_locked_until: None,
+            language: None,
         };
         account.save_impl(false)?;
         Ok(account)
     }
 
     pub fn try_load(name: &str, password: &Password) -> Result<Self, anyhow::Error> {
+        let account = Self::load(name)?;
+        account.check_password(password)?;
+        Ok(account)
+    }
+
+    /// Loads the account without checking its password, for operator tools.
+    pub fn load(name: &str) -> Result<Self, anyhow::Error> {
         let path = get_account_path(name);
         if path.exists() {
             let str = std::fs::read_to_string(&path)
                 .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
-            let account: Self = serde_json::from_str(&str).with_context(|| {
+            let account: Self = ACCOUNT_STORAGE_SCHEMA.deserialize(&str).with_context(|| {
                 format!(
                     "Failed to deserialise AccountStorage from file {}",
                     path.to_string_lossy()
                 )
             })?;
-            account.check_password(password)?;
             Ok(account)
         } else {
             Err(AccountStorageError::NotFound.into())
         }
     }
 
+    /// Finds the account which owns the character by loading every account,
+    /// this is slow so should only be used by operator tools.
+    pub fn find_character_account(character_name: &str) -> Result<Option<Self>, anyhow::Error> {
+        let character_key = storage_name_key(character_name);
+        for account_name in storage_names(&ACCOUNT_STORAGE_DIR) {
+            let account = Self::load(&account_name)?;
+            if account
+                .character_names
+                .iter()
+                .any(|name| storage_name_key(name) == character_key)
+            {
+                return Ok(Some(account));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Binds an unverified email address to the account, returning the token
+    /// which must be sent to the address to verify it.
+    pub fn set_email(
+        &mut self,
+        email: &str,
+        now: DateTime<Utc>,
+    ) -> Result<String, AccountStorageError> {
+        if !is_valid_email(email) {
+            return Err(AccountStorageError::InvalidEmail);
+        }
+
+        let (token, verification_token) =
+            AccountToken::generate(now, EMAIL_VERIFICATION_TOKEN_HOURS);
+        self.email = Some(email.to_string());
+        self.email_verified = false;
+        self.email_verification_token = Some(verification_token);
+        self.password_reset_token = None;
+        Ok(token)
+    }
+
+    pub fn verify_email(
+        &mut self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), AccountStorageError> {
+        if !self
+            .email_verification_token
+            .as_ref()
+            .map_or(false, |verification_token| {
+                verification_token.is_valid(token, now)
+            })
+        {
+            return Err(AccountStorageError::InvalidToken);
+        }
+
+        self.email_verified = true;
+        self.email_verification_token = None;
+        Ok(())
+    }
+
+    /// Returns a token which must be sent to the verified email address of the
+    /// account, and which can then be used once to set a new password.
+    pub fn create_password_reset_token(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<String, AccountStorageError> {
+        if self.email.is_none() || !self.email_verified {
+            return Err(AccountStorageError::EmailNotVerified);
+        }
+
+        let (token, reset_token) = AccountToken::generate(now, PASSWORD_RESET_TOKEN_HOURS);
+        self.password_reset_token = Some(reset_token);
+        Ok(token)
+    }
+
+    pub fn reset_password(
+        &mut self,
+        token: &str,
+        password: &Password,
+        now: DateTime<Utc>,
+    ) -> Result<(), AccountStorageError> {
+        if !self
+            .password_reset_token
+            .as_ref()
+            .map_or(false, |reset_token| reset_token.is_valid(token, now))
+        {
+            return Err(AccountStorageError::InvalidToken);
+        }
+
+        self.password_md5_sha256 = hash_password(password);
+        self.password_reset_token = None;
+        Ok(())
+    }
+
+    pub fn has_secondary_pin(&self) -> bool {
+        self.secondary_pin_sha256.is_some()
+    }
+
+    /// Sets the secondary PIN which must be entered before selecting a
+    /// character, or removes it when `pin` is None. This also clears any lockout.
+    pub fn set_secondary_pin(&mut self, pin: Option<&str>) -> Result<(), AccountStorageError> {
+        if let Some(pin) = pin {
+            if !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+                return Err(AccountStorageError::InvalidSecondaryPinFormat);
+            }
+        }
+
+        self.secondary_pin_sha256 = pin.map(|pin| hash_secondary_pin(&self.name, pin));
+        self.secondary_pin_failures = 0;
+        self.secondary_pin_locked_until = None;
+        Ok(())
+    }
+
+    /// Checks the secondary PIN, the PIN is locked after
+    /// `SECONDARY_PIN_MAX_FAILURES` consecutive failures.
+    pub fn check_secondary_pin(
+        &mut self,
+        pin: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), AccountStorageError> {
+        let Some(secondary_pin_sha256) = self.secondary_pin_sha256.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(locked_until) = self.secondary_pin_locked_until {
+            if now < locked_until {
+                return Err(AccountStorageError::SecondaryPinLocked { locked_until });
+            }
+            self.secondary_pin_locked_until = None;
+        }
+
+        if *secondary_pin_sha256 == hash_secondary_pin(&self.name, pin) {
+            self.secondary_pin_failures = 0;
+            return Ok(());
+        }
+
+        self.secondary_pin_failures += 1;
+        if self.secondary_pin_failures >= SECONDARY_PIN_MAX_FAILURES {
+            let locked_until = now + chrono::Duration::minutes(SECONDARY_PIN_LOCKOUT_MINUTES);
+            self.secondary_pin_failures = 0;
+            self.secondary_pin_locked_until = Some(locked_until);
+            return Err(AccountStorageError::SecondaryPinLocked { locked_until });
+        }
+
+        Err(AccountStorageError::InvalidSecondaryPin {
+            attempts_remaining: SECONDARY_PIN_MAX_FAILURES - self.secondary_pin_failures,
+        })
+    }
+
     pub fn check_password(&self, password: &Password) -> Result<(), anyhow::Error> {
         if self.password_md5_sha256 == hash_password(password) {
             Ok(())
@@ -86,7 +388,7 @@ impl AccountStorage {
             )
         })?;
 
-        let json = serde_json::to_string_pretty(&self).with_context(|| {
+        let json = ACCOUNT_STORAGE_SCHEMA.serialize(self).with_context(|| {
             format!(
                 "Failed to serialise AccountStorage whilst saving account {}",
                 &self.name
@@ -124,6 +426,7 @@ impl AccountStorage {
             })?;
         }
 
+        index_storage_document(&path);
         Ok(())
     }
 }
@@ -0,0 +1,312 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    io::{BufReader, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+use crate::game::storage::{
+    ACCOUNT_STORAGE_DIR, BANK_STORAGE_DIR, CHARACTER_STORAGE_DIR, CHAT_MUTE_STORAGE_DIR,
+    CLAN_BANK_STORAGE_DIR, CLAN_STORAGE_DIR, ITEM_DROP_STORAGE_DIR, NPC_STORE_STOCK_STORAGE_DIR,
+    PARTY_STORAGE_DIR, REWARD_CALENDAR_STORAGE_DIR,
+};
+
+const STORAGE_BACKUP_VERSION: u32 = 1;
+const STORAGE_BACKUP_PREFIX: &str = "storage-";
+const STORAGE_BACKUP_EXTENSION: &str = ".json.gz";
+
+#[derive(Error, Debug)]
+pub enum StorageBackupError {
+    #[error("Unsupported storage backup version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Unknown storage collection {0}")]
+    UnknownCollection(String),
+
+    #[error("Invalid document name {0}")]
+    InvalidDocumentName(String),
+}
+
+/// Returns the name of every storage collection with the directory its
+/// documents are stored in.
+fn storage_collections() -> [(&'static str, &'static Path); 10] {
+    [
+        ("accounts", ACCOUNT_STORAGE_DIR.as_path()),
+        ("bank", BANK_STORAGE_DIR.as_path()),
+        ("characters", CHARACTER_STORAGE_DIR.as_path()),
+        ("chat_mutes", CHAT_MUTE_STORAGE_DIR.as_path()),
+        ("clan", CLAN_STORAGE_DIR.as_path()),
+        ("clan_bank", CLAN_BANK_STORAGE_DIR.as_path()),
+        ("item_drops", ITEM_DROP_STORAGE_DIR.as_path()),
+        ("npc_store_stock", NPC_STORE_STOCK_STORAGE_DIR.as_path()),
+        ("party", PARTY_STORAGE_DIR.as_path()),
+        ("reward_calendar", REWARD_CALENDAR_STORAGE_DIR.as_path()),
+    ]
+}
+
+fn is_valid_document_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', '\0'])
+}
+
+/// A copy of every storage document, keyed by collection and then document
+/// name. Documents are kept exactly as they were saved, including their
+/// schema version, so they are migrated as usual when loaded after a restore.
+#[derive(Deserialize, Serialize)]
+pub struct StorageBackup {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    pub collections: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl StorageBackup {
+    /// Reads every document from storage. Each document is read whole, but
+    /// documents saved whilst the backup is being captured may or may not be
+    /// included.
+    pub fn capture() -> Result<Self, anyhow::Error> {
+        let mut collections = BTreeMap::new();
+
+        for (collection_name, storage_dir) in storage_collections() {
+            let mut documents = BTreeMap::new();
+            let dir = match std::fs::read_dir(storage_dir) {
+                Ok(dir) => dir,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    collections.insert(collection_name.to_string(), documents);
+                    continue;
+                }
+                Err(error) => {
+                    return Err(error).with_context(|| {
+                        format!(
+                            "Failed to read storage directory {}",
+                            storage_dir.to_string_lossy()
+                        )
+                    })
+                }
+            };
+
+            for entry in dir {
+                let path = entry
+                    .with_context(|| {
+                        format!(
+                            "Failed to read storage directory {}",
+                            storage_dir.to_string_lossy()
+                        )
+                    })?
+                    .path();
+                if path
+                    .extension()
+                    .map_or(true, |extension| extension != "json")
+                {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+
+                let str = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+                let document: Value = serde_json::from_str(&str).with_context(|| {
+                    format!("Failed to parse JSON from file {}", path.to_string_lossy())
+                })?;
+                documents.insert(name.to_string(), document);
+            }
+
+            collections.insert(collection_name.to_string(), documents);
+        }
+
+        Ok(Self {
+            version: STORAGE_BACKUP_VERSION,
+            created: Utc::now(),
+            collections,
+        })
+    }
+
+    pub fn num_documents(&self) -> usize {
+        self.collections
+            .values()
+            .map(|documents| documents.len())
+            .sum()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open backup {}", path.to_string_lossy()))?;
+        let backup: Self = serde_json::from_reader(BufReader::new(GzDecoder::new(file)))
+            .with_context(|| {
+                format!(
+                    "Failed to deserialise StorageBackup from file {}",
+                    path.to_string_lossy()
+                )
+            })?;
+
+        if backup.version > STORAGE_BACKUP_VERSION {
+            return Err(StorageBackupError::UnsupportedVersion(backup.version).into());
+        }
+
+        Ok(backup)
+    }
+
+    /// Writes the backup to a new timestamped file in `backup_dir`, returning
+    /// the path of the file.
+    pub fn save(&self, backup_dir: &Path) -> Result<PathBuf, anyhow::Error> {
+        // Backups made within the same millisecond would have the same name, so
+        // move the timestamp forward until it is unused to keep every backup
+        let mut timestamp = self.created;
+        let path = loop {
+            let path = backup_dir.join(format!(
+                "{}{}{}",
+                STORAGE_BACKUP_PREFIX,
+                timestamp.format("%Y%m%d-%H%M%S-%3f"),
+                STORAGE_BACKUP_EXTENSION
+            ));
+            if !path.exists() {
+                break path;
+            }
+            timestamp += chrono::Duration::milliseconds(1);
+        };
+
+        std::fs::create_dir_all(backup_dir).with_context(|| {
+            format!(
+                "Failed to create backup directory {}",
+                backup_dir.to_string_lossy()
+            )
+        })?;
+
+        let file = tempfile::Builder::new()
+            .tempfile_in(backup_dir)
+            .context("Failed to create temporary file whilst saving backup")?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        serde_json::to_writer(&mut encoder, self)
+            .context("Failed to serialise StorageBackup whilst saving backup")?;
+        let mut file = encoder
+            .finish()
+            .context("Failed to write data to temporary file whilst saving backup")?;
+        file.flush()
+            .context("Failed to write data to temporary file whilst saving backup")?;
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary backup file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(path)
+    }
+
+    /// Writes every document in the backup to storage, replacing any existing
+    /// document with the same name. Documents which are not in the backup are
+    /// left untouched.
+    pub fn restore(&self) -> Result<(), anyhow::Error> {
+        let collections = storage_collections();
+
+        // Validate the whole backup before writing anything
+        for (collection_name, documents) in self.collections.iter() {
+            if !collections.iter().any(|(name, _)| name == collection_name) {
+                return Err(StorageBackupError::UnknownCollection(collection_name.clone()).into());
+            }
+
+            if let Some(name) = documents.keys().find(|name| !is_valid_document_name(name)) {
+                return Err(StorageBackupError::InvalidDocumentName(name.clone()).into());
+            }
+        }
+
+        for (collection_name, storage_dir) in collections {
+            let Some(documents) = self.collections.get(collection_name) else {
+                continue;
+            };
+
+            std::fs::create_dir_all(storage_dir).with_context(|| {
+                format!(
+                    "Failed to create {} storage directory {}",
+                    collection_name,
+                    storage_dir.to_string_lossy()
+                )
+            })?;
+
+            for (name, document) in documents.iter() {
+                let path = storage_dir.join(format!("{}.json", name));
+                let json = serde_json::to_string_pretty(document).with_context(|| {
+                    format!("Failed to serialise {} document {}", collection_name, name)
+                })?;
+
+                let mut file = tempfile::Builder::new()
+                    .tempfile_in(storage_dir)
+                    .with_context(|| {
+                        format!(
+                            "Failed to create temporary file whilst restoring {} document {}",
+                            collection_name, name
+                        )
+                    })?;
+                file.write_all(json.as_bytes()).with_context(|| {
+                    format!(
+                        "Failed to write data to temporary file whilst restoring {} document {}",
+                        collection_name, name
+                    )
+                })?;
+                file.persist(&path).with_context(|| {
+                    format!(
+                        "Failed to persist temporary file to path {}",
+                        path.to_string_lossy()
+                    )
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the backups in `backup_dir`, oldest first.
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let dir = match std::fs::read_dir(backup_dir) {
+        Ok(dir) => dir,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!(
+                    "Failed to read backup directory {}",
+                    backup_dir.to_string_lossy()
+                )
+            })
+        }
+    };
+
+    // The timestamp in the file name sorts in the order the backups were made
+    let mut backups: Vec<PathBuf> = dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| {
+                    name.starts_with(STORAGE_BACKUP_PREFIX)
+                        && name.ends_with(STORAGE_BACKUP_EXTENSION)
+                })
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Captures a new backup into `backup_dir` and then deletes the oldest
+/// backups so that at most `retention` remain.
+pub fn create_backup(backup_dir: &Path, retention: usize) -> Result<PathBuf, anyhow::Error> {
+    let path = StorageBackup::capture()?.save(backup_dir)?;
+
+    let backups = list_backups(backup_dir)?;
+    let num_expired = backups.len().saturating_sub(retention.max(1));
+    for expired in backups.iter().take(num_expired) {
+        std::fs::remove_file(expired).with_context(|| {
+            format!(
+                "Failed to remove expired backup {}",
+                expired.to_string_lossy()
+            )
+        })?;
+    }
+
+    Ok(path)
+}
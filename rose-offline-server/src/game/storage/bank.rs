@@ -5,7 +5,10 @@ use thiserror::Error;
 
 use rose_data::Item;
 
-use crate::game::storage::BANK_STORAGE_DIR;
+use crate::game::storage::{
+    schema_version::{migrate_add_schema_version, StorageSchema},
+    BANK_STORAGE_DIR,
+};
 
 #[derive(Error, Debug)]
 pub enum BankStorageError {
@@ -18,6 +21,8 @@ pub struct BankStorage {
     pub slots: Vec<Option<Item>>,
 }
 
+const BANK_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[migrate_add_schema_version]);
+
 fn get_bank_path(account_name: &str) -> PathBuf {
     BANK_STORAGE_DIR.join(format!("{}.json", account_name))
 }
@@ -34,7 +39,7 @@ impl BankStorage {
         if path.exists() {
             let str = std::fs::read_to_string(&path)
                 .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
-            let bank: Self = serde_json::from_str(&str).with_context(|| {
+            let bank: Self = BANK_STORAGE_SCHEMA.deserialize(&str).with_context(|| {
                 format!(
                     "Failed to deserialise AccountStorage from file {}",
                     path.to_string_lossy()
@@ -61,7 +66,7 @@ impl BankStorage {
             )
         })?;
 
-        let json = serde_json::to_string_pretty(&self).with_context(|| {
+        let json = BANK_STORAGE_SCHEMA.serialize(self).with_context(|| {
             format!(
                 "Failed to serialise BankStorage whilst saving bank for account {}",
                 account_name
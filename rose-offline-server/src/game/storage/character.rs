@@ -1,18 +1,125 @@
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::{io::Write, path::PathBuf};
+use serde_json::{Map, Value};
+use std::{
+    io::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use rose_game_common::components::CharacterGender;
+use rose_data::{SkillId, StatusEffectDatabase, StatusEffectId};
+use rose_game_common::components::{CharacterGender, MAX_STAMINA};
 
 use crate::game::{
     components::{
-        BasicStats, CharacterDeleteTime, CharacterInfo, Equipment, ExperiencePoints, HealthPoints,
-        Hotbar, Inventory, Level, ManaPoints, Position, QuestState, SkillList, SkillPoints,
-        Stamina, StatPoints, UnionMembership,
+        Achievements, ActiveStatusEffectRegen, BasicStats, CharacterDeleteTime, CharacterInfo,
+        Cooldowns, Equipment, ExperiencePoints, HealthPoints, HonorPoints, Hotbar, Inventory,
+        Level, ManaPoints, OfflineVendorProceeds, Position, QuestState, Rebirth, SkillList,
+        SkillPoints, Stamina, StatPoints, Statistics, StatusEffects, StatusEffectsRegen,
+        UnionMembership,
+    },
+    storage::{
+        index_storage_document,
+        schema_version::{migrate_insert_default, StorageSchema},
+        storage_document_path, storage_names, unindex_storage_document, CHARACTER_STORAGE_DIR,
     },
-    storage::CHARACTER_STORAGE_DIR,
 };
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SavedStatusEffect {
+    pub id: StatusEffectId,
+    pub value: i32,
+    pub remaining: Duration,
+    pub regen: Option<ActiveStatusEffectRegen>,
+}
+
+/// Status effects and skill cooldowns which were active when the character
+/// was saved. Their remaining durations are stored, so they continue from where
+/// they were when the character next joins the game.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SavedEffects {
+    pub status_effects: Vec<SavedStatusEffect>,
+    pub skill_cooldowns: Vec<(SkillId, Duration)>,
+    pub global_cooldown: Option<Duration>,
+    pub group_cooldowns: Vec<(usize, Duration)>,
+}
+
+impl SavedEffects {
+    pub fn new(
+        status_effects: &StatusEffects,
+        status_effects_regen: &StatusEffectsRegen,
+        cooldowns: &Cooldowns,
+        now: Instant,
+    ) -> Self {
+        let remaining = |time: Instant| time.checked_duration_since(now);
+
+        Self {
+            status_effects: status_effects
+                .active
+                .iter()
+                .filter_map(|(status_effect_type, active)| {
+                    let active = active.as_ref()?;
+                    Some(SavedStatusEffect {
+                        id: active.id,
+                        value: active.value,
+                        remaining: remaining(status_effects.expire_times[status_effect_type]?)?,
+                        regen: status_effects_regen.regens[status_effect_type].clone(),
+                    })
+                })
+                .collect(),
+            skill_cooldowns: cooldowns
+                .skill
+                .iter()
+                .filter_map(|(&skill_id, &time)| Some((skill_id, remaining(time)?)))
+                .collect(),
+            global_cooldown: cooldowns.skill_global.and_then(remaining),
+            group_cooldowns: cooldowns
+                .skill_group
+                .iter()
+                .enumerate()
+                .filter_map(|(group, time)| Some((group, remaining((*time)?)?)))
+                .collect(),
+        }
+    }
+
+    pub fn restore(
+        &self,
+        status_effect_database: &StatusEffectDatabase,
+        now: Instant,
+    ) -> (StatusEffects, StatusEffectsRegen, Cooldowns) {
+        let mut status_effects = StatusEffects::new();
+        let mut status_effects_regen = StatusEffectsRegen::new();
+        for saved in self.status_effects.iter() {
+            let Some(status_effect_data) = status_effect_database.get_status_effect(saved.id)
+            else {
+                continue;
+            };
+
+            if status_effects.apply_status_effect(
+                status_effect_data,
+                now + saved.remaining,
+                saved.value,
+            ) {
+                status_effects_regen.regens[status_effect_data.status_effect_type] =
+                    saved.regen.clone();
+            }
+        }
+
+        let mut cooldowns = Cooldowns::default();
+        for &(skill_id, remaining) in self.skill_cooldowns.iter() {
+            cooldowns.skill.insert(skill_id, now + remaining);
+        }
+        cooldowns.skill_global = self.global_cooldown.map(|remaining| now + remaining);
+        for &(group, remaining) in self.group_cooldowns.iter() {
+            if let Some(cooldown) = cooldowns.skill_group.get_mut(group) {
+                *cooldown = Some(now + remaining);
+            }
+        }
+
+        (status_effects, status_effects_regen, cooldowns)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct CharacterStorage {
     pub info: CharacterInfo,
@@ -32,10 +139,67 @@ pub struct CharacterStorage {
     pub quest_state: QuestState,
     pub union_membership: UnionMembership,
     pub stamina: Stamina,
+    pub achievements: Achievements,
+    pub statistics: Statistics,
+    pub rebirth: Rebirth,
+    pub honor_points: HonorPoints,
+    pub saved_effects: SavedEffects,
+
+    /// Sales made whilst the character was an offline vendor, which have not
+    /// yet been reported to its owner
+    pub offline_vendor_proceeds: Option<OfflineVendorProceeds>,
+}
+
+const CHARACTER_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[
+    migrate_character_v0,
+    migrate_character_v1,
+    migrate_character_v2,
+    migrate_character_v3,
+    migrate_character_v4,
+    migrate_character_v5,
+    migrate_character_v6,
+]);
+
+/// Characters saved before schema versioning may be missing fields which were
+/// added to CharacterStorage later, so fill them in with their defaults.
+fn migrate_character_v0(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "hotbar", Hotbar::default())?;
+    migrate_insert_default(document, "quest_state", QuestState::default())?;
+    migrate_insert_default(document, "union_membership", UnionMembership::default())?;
+    migrate_insert_default(document, "stamina", Stamina::new(MAX_STAMINA))?;
+    Ok(())
+}
+
+fn migrate_character_v1(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "achievements", Achievements::default())
+}
+
+fn migrate_character_v2(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "statistics", Statistics::default())
+}
+
+fn migrate_character_v3(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(
+        document,
+        "offline_vendor_proceeds",
+        None::<OfflineVendorProceeds>,
+    )
+}
+
+fn migrate_character_v4(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "rebirth", Rebirth::default())
+}
+
+fn migrate_character_v5(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "honor_points", HonorPoints::default())
+}
+
+fn migrate_character_v6(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "saved_effects", SavedEffects::default())
 }
 
 fn get_character_path(name: &str) -> PathBuf {
-    CHARACTER_STORAGE_DIR.join(format!("{}.json", name))
+    storage_document_path(&CHARACTER_STORAGE_DIR, name)
 }
 
 #[allow(dead_code)]
@@ -71,12 +235,14 @@ impl CharacterStorage {
         let path = get_character_path(name);
         let str = std::fs::read_to_string(&path)
             .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
-        let character: CharacterStorage = serde_json::from_str(&str).with_context(|| {
-            format!(
-                "Failed to deserialise CharacterStorage from file {}",
-                path.to_string_lossy()
-            )
-        })?;
+        let character: Self = CHARACTER_STORAGE_SCHEMA
+            .deserialize(&str)
+            .with_context(|| {
+                format!(
+                    "Failed to deserialise CharacterStorage from file {}",
+                    path.to_string_lossy()
+                )
+            })?;
         Ok(character)
     }
 
@@ -84,7 +250,18 @@ impl CharacterStorage {
         self.save_character_impl(&self.info.name, true)
     }
 
-    fn save_character_impl(&self, character_name: &str, allow_overwrite: bool) -> Result<(), anyhow::Error> {
+    /// Returns true if the stored character with this character's name has
+    /// the same contents as this character.
+    pub fn matches_stored(&self) -> Result<bool, anyhow::Error> {
+        let stored = Self::try_load(&self.info.name)?;
+        Ok(serde_json::to_value(&stored)? == serde_json::to_value(self)?)
+    }
+
+    fn save_character_impl(
+        &self,
+        character_name: &str,
+        allow_overwrite: bool,
+    ) -> Result<(), anyhow::Error> {
         let path = get_character_path(character_name);
         let storage_dir = path.parent().unwrap();
 
@@ -95,7 +272,7 @@ impl CharacterStorage {
             )
         })?;
 
-        let json = serde_json::to_string_pretty(&self).with_context(|| {
+        let json = CHARACTER_STORAGE_SCHEMA.serialize(self).with_context(|| {
             format!(
                 "Failed to serialise CharacterStorage whilst saving character {}",
                 character_name
@@ -133,6 +310,7 @@ impl CharacterStorage {
             })?;
         }
 
+        index_storage_document(&path);
         Ok(())
     }
 
@@ -140,11 +318,23 @@ impl CharacterStorage {
         get_character_path(name).exists()
     }
 
+    /// Returns the names of every stored character for which `is_match`
+    /// returns true, sorted by name.
+    pub fn find_matching(is_match: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut names: Vec<String> = storage_names(&CHARACTER_STORAGE_DIR)
+            .into_iter()
+            .filter(|name| is_match(name))
+            .collect();
+        names.sort();
+        names
+    }
+
     pub fn delete(name: &str) -> Result<(), anyhow::Error> {
         let path = get_character_path(name);
         if path.exists() {
-            std::fs::remove_file(path)?;
+            std::fs::remove_file(&path)?;
         }
+        unindex_storage_document(&path);
         Ok(())
     }
 }
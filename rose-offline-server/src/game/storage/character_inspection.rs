@@ -0,0 +1,111 @@
+use anyhow::Context;
+use chrono::Utc;
+use serde::Serialize;
+use std::{io::Write, path::PathBuf};
+use thiserror::Error;
+
+use crate::game::storage::{
+    account::AccountStorage, bank::BankStorage, character::CharacterStorage,
+    CHARACTER_INSPECTION_DIR,
+};
+
+#[derive(Error, Debug)]
+pub enum CharacterInspectionError {
+    #[error("Character not found")]
+    NotFound,
+}
+
+/// Everything stored for a character, as inspected by a GM when handling a
+/// support ticket.
+#[derive(Serialize)]
+pub struct CharacterInspection {
+    pub account_name: Option<String>,
+
+    /// True if the data was read from the character in the game world, rather
+    /// than from storage
+    pub online: bool,
+
+    /// True if storage has a queued write for the character, so the stored
+    /// data may be older than the data in the game world
+    pub pending_write: bool,
+
+    pub character: CharacterStorage,
+    pub bank: Option<BankStorage>,
+}
+
+impl CharacterInspection {
+    /// Loads a character from storage, the name does not need to match the
+    /// case of the stored name.
+    pub fn load(name: &str) -> Result<Self, anyhow::Error> {
+        if !CharacterStorage::exists(name) {
+            return Err(CharacterInspectionError::NotFound.into());
+        }
+        let character = CharacterStorage::try_load(name)?;
+        let character_name = character.info.name.clone();
+
+        let account_name =
+            AccountStorage::find_character_account(&character_name)?.map(|account| account.name);
+        let bank = account_name
+            .as_ref()
+            .and_then(|account_name| BankStorage::try_load(account_name).ok());
+
+        Ok(Self {
+            account_name,
+            online: false,
+            pending_write: false,
+            character,
+            bank,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        serde_json::to_string_pretty(self).with_context(|| {
+            format!(
+                "Failed to serialise CharacterInspection for character {}",
+                self.character.info.name
+            )
+        })
+    }
+
+    /// Writes the inspection to a new timestamped file which can be attached
+    /// to a support ticket, returning the path of the file.
+    pub fn save(&self) -> Result<PathBuf, anyhow::Error> {
+        let storage_dir = CHARACTER_INSPECTION_DIR.as_path();
+        let path = storage_dir.join(format!(
+            "{}-{}.json",
+            self.character.info.name,
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create character inspection directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = self.to_json()?;
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving inspection of character {}",
+                    self.character.info.name
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving inspection of character {}",
+                self.character.info.name
+            )
+        })?;
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary inspection file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(path)
+    }
+}
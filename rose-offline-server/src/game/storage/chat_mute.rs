@@ -0,0 +1,130 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{io::Write, path::PathBuf};
+
+use crate::game::storage::{schema_version::StorageSchema, CHAT_MUTE_STORAGE_DIR};
+
+/// A character which can not chat until the mute expires. Mutes are stored
+/// by lowercase character name so they match regardless of how the name was
+/// typed in the mute command.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ChatMuteStorage {
+    pub character_name: String,
+    pub until: DateTime<Utc>,
+    pub reason: String,
+
+    /// The GM who muted the character, None for automatic spam mutes
+    pub muted_by: Option<String>,
+}
+
+const CHAT_MUTE_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[]);
+
+fn get_chat_mute_path(character_name: &str) -> PathBuf {
+    CHAT_MUTE_STORAGE_DIR.join(format!("{}.json", character_name.to_lowercase()))
+}
+
+impl ChatMuteStorage {
+    /// Loads every mute which has not expired, expired mutes are deleted.
+    pub fn try_load_active(now: DateTime<Utc>) -> Result<Vec<Self>, anyhow::Error> {
+        let mut mutes = Vec::new();
+
+        let dir = match CHAT_MUTE_STORAGE_DIR.read_dir() {
+            Ok(dir) => dir,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(mutes),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!(
+                        "Failed to read chat mute storage directory {}",
+                        CHAT_MUTE_STORAGE_DIR.to_string_lossy()
+                    )
+                })
+            }
+        };
+
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "json")
+            {
+                continue;
+            }
+
+            let str = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+            let mute: Self = CHAT_MUTE_STORAGE_SCHEMA
+                .deserialize(&str)
+                .with_context(|| {
+                    format!(
+                        "Failed to deserialise ChatMuteStorage from file {}",
+                        path.to_string_lossy()
+                    )
+                })?;
+
+            if mute.until > now {
+                mutes.push(mute);
+            } else {
+                std::fs::remove_file(&path).with_context(|| {
+                    format!(
+                        "Failed to remove expired chat mute {}",
+                        path.to_string_lossy()
+                    )
+                })?;
+            }
+        }
+
+        Ok(mutes)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let path = get_chat_mute_path(&self.character_name);
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create chat mute storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = CHAT_MUTE_STORAGE_SCHEMA.serialize(self).with_context(|| {
+            format!(
+                "Failed to serialise ChatMuteStorage whilst saving chat mute for character {}",
+                self.character_name
+            )
+        })?;
+
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving chat mute for character {}",
+                    self.character_name
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving chat mute for character {}",
+                self.character_name
+            )
+        })?;
+
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary chat mute file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub fn delete(character_name: &str) -> Result<(), anyhow::Error> {
+        let path = get_chat_mute_path(character_name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
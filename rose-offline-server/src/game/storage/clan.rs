@@ -2,11 +2,18 @@ use std::{io::Write, path::PathBuf};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use rose_data::{ClanMemberPosition, SkillId};
-use rose_game_common::components::{ClanLevel, ClanMark, ClanPoints, Money};
+use rose_game_common::{
+    components::{ClanLevel, ClanMark, ClanPoints, Money},
+    messages::server::ClanWarResult,
+};
 
-use crate::game::storage::CLAN_STORAGE_DIR;
+use crate::game::storage::{
+    schema_version::{migrate_add_schema_version, migrate_insert_default, StorageSchema},
+    CLAN_STORAGE_DIR,
+};
 
 #[derive(Deserialize, Serialize)]
 pub struct ClanStorageMember {
@@ -25,18 +32,41 @@ impl ClanStorageMember {
     }
 }
 
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ClanStorageWarResult {
+    pub opponent: String,
+    pub result: ClanWarResult,
+    pub score: u32,
+    pub opponent_score: u32,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct ClanStorage {
     pub name: String,
     pub description: String,
+    pub notice: String,
     pub mark: ClanMark,
     pub money: Money,
     pub points: ClanPoints,
     pub level: ClanLevel,
     pub members: Vec<ClanStorageMember>,
     pub skills: Vec<SkillId>,
+    pub war_results: Vec<ClanStorageWarResult>,
+}
+
+/// Clans saved before clan wars were added have no war results.
+fn migrate_clan_v1(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "war_results", Vec::<ClanStorageWarResult>::new())
 }
 
+/// Clans saved before clan notices were added have no notice.
+fn migrate_clan_v2(document: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    migrate_insert_default(document, "notice", String::default())
+}
+
+const CLAN_STORAGE_SCHEMA: StorageSchema =
+    StorageSchema::new(&[migrate_add_schema_version, migrate_clan_v1, migrate_clan_v2]);
+
 fn get_clan_path(name: &str) -> PathBuf {
     CLAN_STORAGE_DIR.join(format!("{}.json", name))
 }
@@ -46,12 +76,14 @@ impl ClanStorage {
         Self {
             name,
             description,
+            notice: String::default(),
             mark,
             money: Money(0),
             points: ClanPoints(0),
             level: ClanLevel::new(1).unwrap(),
             members: Vec::default(),
             skills: Vec::default(),
+            war_results: Vec::default(),
         }
     }
 
@@ -67,7 +99,7 @@ impl ClanStorage {
         let path = get_clan_path(name);
         let str = std::fs::read_to_string(&path)
             .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
-        let clan: Self = serde_json::from_str(&str).with_context(|| {
+        let clan: Self = CLAN_STORAGE_SCHEMA.deserialize(&str).with_context(|| {
             format!(
                 "Failed to deserialise ClanStorage from file {}",
                 path.to_string_lossy()
@@ -83,7 +115,7 @@ impl ClanStorage {
             let path = entry.path();
             let str = std::fs::read_to_string(&path)
                 .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
-            let clan: Self = serde_json::from_str(&str).with_context(|| {
+            let clan: Self = CLAN_STORAGE_SCHEMA.deserialize(&str).with_context(|| {
                 format!(
                     "Failed to deserialise ClanStorage from file {}",
                     path.to_string_lossy()
@@ -110,7 +142,7 @@ impl ClanStorage {
             )
         })?;
 
-        let json = serde_json::to_string_pretty(&self).with_context(|| {
+        let json = CLAN_STORAGE_SCHEMA.serialize(self).with_context(|| {
             format!(
                 "Failed to serialise ClanStorage whilst saving clan {}",
                 &self.name
@@ -0,0 +1,95 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use rose_data::Item;
+use rose_game_common::messages::server::ClanBankLogEntry;
+
+use crate::game::storage::{
+    schema_version::{migrate_add_schema_version, StorageSchema},
+    CLAN_BANK_STORAGE_DIR,
+};
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct ClanBankStorage {
+    pub slots: Vec<Option<Item>>,
+    pub log: Vec<ClanBankLogEntry>,
+}
+
+const CLAN_BANK_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[migrate_add_schema_version]);
+
+fn get_clan_bank_path(clan_name: &str) -> PathBuf {
+    CLAN_BANK_STORAGE_DIR.join(format!("{}.json", clan_name))
+}
+
+impl ClanBankStorage {
+    /// Loads the clan bank, a clan which has never used its bank has an empty bank.
+    pub fn try_load(clan_name: &str) -> Result<Self, anyhow::Error> {
+        let path = get_clan_bank_path(clan_name);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let str = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+        let clan_bank: Self = CLAN_BANK_STORAGE_SCHEMA
+            .deserialize(&str)
+            .with_context(|| {
+                format!(
+                    "Failed to deserialise ClanBankStorage from file {}",
+                    path.to_string_lossy()
+                )
+            })?;
+        Ok(clan_bank)
+    }
+
+    pub fn save(&self, clan_name: &str) -> Result<(), anyhow::Error> {
+        let path = get_clan_bank_path(clan_name);
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create clan bank storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = CLAN_BANK_STORAGE_SCHEMA.serialize(self).with_context(|| {
+            format!(
+                "Failed to serialise ClanBankStorage whilst saving bank for clan {}",
+                clan_name
+            )
+        })?;
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving bank for clan {}",
+                    clan_name
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving bank for clan {}",
+                clan_name
+            )
+        })?;
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary clan bank file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub fn delete(clan_name: &str) -> Result<(), anyhow::Error> {
+        let path = get_clan_bank_path(clan_name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
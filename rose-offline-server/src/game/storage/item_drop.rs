@@ -0,0 +1,130 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{io::Write, path::PathBuf};
+
+use rose_data::ZoneId;
+
+use crate::game::{
+    components::{DroppedItem, Position},
+    storage::{schema_version::StorageSchema, ITEM_DROP_STORAGE_DIR},
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemDropStorage {
+    pub position: Position,
+    pub item: DroppedItem,
+    pub expire_time: DateTime<Utc>,
+}
+
+/// The item drops of a zone which are restored when the server starts.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ZoneItemDropStorage {
+    pub zone_id: ZoneId,
+    pub item_drops: Vec<ItemDropStorage>,
+}
+
+const ITEM_DROP_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[]);
+
+fn get_zone_item_drop_path(zone_id: ZoneId) -> PathBuf {
+    ITEM_DROP_STORAGE_DIR.join(format!("{}.json", zone_id.get()))
+}
+
+impl ZoneItemDropStorage {
+    /// Loads the item drops of every zone, item drops which have expired are
+    /// not returned.
+    pub fn try_load_all(now: DateTime<Utc>) -> Result<Vec<Self>, anyhow::Error> {
+        let mut zones = Vec::new();
+
+        let dir = match ITEM_DROP_STORAGE_DIR.read_dir() {
+            Ok(dir) => dir,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(zones),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!(
+                        "Failed to read item drop storage directory {}",
+                        ITEM_DROP_STORAGE_DIR.to_string_lossy()
+                    )
+                })
+            }
+        };
+
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "json")
+            {
+                continue;
+            }
+
+            let str = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+            let mut zone: Self = ITEM_DROP_STORAGE_SCHEMA
+                .deserialize(&str)
+                .with_context(|| {
+                    format!(
+                        "Failed to deserialise ZoneItemDropStorage from file {}",
+                        path.to_string_lossy()
+                    )
+                })?;
+
+            zone.item_drops
+                .retain(|item_drop| item_drop.expire_time > now);
+            zones.push(zone);
+        }
+
+        Ok(zones)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let path = get_zone_item_drop_path(self.zone_id);
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create item drop storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = ITEM_DROP_STORAGE_SCHEMA.serialize(self).with_context(|| {
+            format!(
+                "Failed to serialise ZoneItemDropStorage whilst saving item drops for zone {}",
+                self.zone_id.get()
+            )
+        })?;
+
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving item drops for zone {}",
+                    self.zone_id.get()
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving item drops for zone {}",
+                self.zone_id.get()
+            )
+        })?;
+
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary item drop file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub fn delete(zone_id: ZoneId) -> Result<(), anyhow::Error> {
+        let path = get_zone_item_drop_path(zone_id);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::game::{
+    components::Inventory,
+    resources::StorageKey,
+    storage::{
+        bank::BankStorage,
+        character::CharacterStorage,
+        clan_bank::ClanBankStorage,
+        journal::{new_journal_id, read_journal_files, remove_journal_file, write_journal_file},
+        reward_calendar::RewardCalendarStorage,
+        ITEM_TRANSACTION_STORAGE_DIR,
+    },
+};
+
+/// The documents changed by moving items or money between characters and
+/// banks, which must be saved together so that a crash part way through
+/// saving can not duplicate or lose items.
+///
+/// The transaction is first written to a journal, then each document is
+/// saved, and finally the journal is removed. A journal left behind by a
+/// crash is replayed by `recover_item_transactions` before the game starts.
+#[derive(Deserialize, Serialize)]
+pub struct ItemTransaction {
+    id: String,
+    inventories: Vec<(String, Inventory)>,
+    banks: Vec<(String, BankStorage)>,
+
+    /// Saved with the items they reward, so a reward can never be claimed
+    /// twice or lost
+    #[serde(default)]
+    reward_calendars: Vec<(String, RewardCalendarStorage)>,
+
+    #[serde(default)]
+    clan_banks: Vec<(String, ClanBankStorage)>,
+}
+
+impl Default for ItemTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ItemTransaction {
+    pub fn new() -> Self {
+        Self {
+            id: new_journal_id(),
+            inventories: Vec::new(),
+            banks: Vec::new(),
+            reward_calendars: Vec::new(),
+            clan_banks: Vec::new(),
+        }
+    }
+
+    /// Stages the inventory of a character, replacing any inventory already
+    /// staged for the same character.
+    pub fn update_inventory(&mut self, character_name: &str, inventory: &Inventory) {
+        if let Some((_, staged)) = self
+            .inventories
+            .iter_mut()
+            .find(|(name, _)| name == character_name)
+        {
+            *staged = inventory.clone();
+        } else {
+            self.inventories
+                .push((character_name.to_string(), inventory.clone()));
+        }
+    }
+
+    /// Stages the bank of an account, replacing any bank already staged for
+    /// the same account.
+    pub fn update_bank(&mut self, account_name: &str, bank: BankStorage) {
+        if let Some((_, staged)) = self.banks.iter_mut().find(|(name, _)| name == account_name) {
+            *staged = bank;
+        } else {
+            self.banks.push((account_name.to_string(), bank));
+        }
+    }
+
+    /// Stages the reward calendar of an account, replacing any reward
+    /// calendar already staged for the same account.
+    pub fn update_reward_calendar(
+        &mut self,
+        account_name: &str,
+        reward_calendar: RewardCalendarStorage,
+    ) {
+        if let Some((_, staged)) = self
+            .reward_calendars
+            .iter_mut()
+            .find(|(name, _)| name == account_name)
+        {
+            *staged = reward_calendar;
+        } else {
+            self.reward_calendars
+                .push((account_name.to_string(), reward_calendar));
+        }
+    }
+
+    /// Stages the bank of a clan, replacing any bank already staged for the
+    /// same clan.
+    pub fn update_clan_bank(&mut self, clan_name: &str, clan_bank: ClanBankStorage) {
+        if let Some((_, staged)) = self
+            .clan_banks
+            .iter_mut()
+            .find(|(name, _)| name == clan_name)
+        {
+            *staged = clan_bank;
+        } else {
+            self.clan_banks.push((clan_name.to_string(), clan_bank));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inventories.is_empty()
+            && self.banks.is_empty()
+            && self.reward_calendars.is_empty()
+            && self.clan_banks.is_empty()
+    }
+
+    /// Returns the key of every document changed by the transaction.
+    pub fn storage_keys(&self) -> Vec<StorageKey> {
+        self.inventories
+            .iter()
+            .map(|(name, _)| StorageKey::Character(name.clone()))
+            .chain(
+                self.banks
+                    .iter()
+                    .map(|(name, _)| StorageKey::Bank(name.clone())),
+            )
+            .chain(
+                self.reward_calendars
+                    .iter()
+                    .map(|(name, _)| StorageKey::RewardCalendar(name.clone())),
+            )
+            .chain(
+                self.clan_banks
+                    .iter()
+                    .map(|(name, _)| StorageKey::ClanBank(name.clone())),
+            )
+            .collect()
+    }
+
+    /// Writes the transaction to its journal, which is the first step of
+    /// `commit`. Once the journal has been written the transaction will be
+    /// completed even if the server crashes before the documents are saved.
+    pub fn write_journal(&self) -> Result<(), anyhow::Error> {
+        write_journal_file(
+            &ITEM_TRANSACTION_STORAGE_DIR,
+            &self.id,
+            "item transaction",
+            self,
+        )
+    }
+
+    fn apply(&self) -> Result<(), anyhow::Error> {
+        for (character_name, inventory) in self.inventories.iter() {
+            let mut character = CharacterStorage::try_load(character_name)?;
+            character.inventory = inventory.clone();
+            character.save()?;
+        }
+
+        for (account_name, bank) in self.banks.iter() {
+            bank.save(account_name)?;
+        }
+
+        for (account_name, reward_calendar) in self.reward_calendars.iter() {
+            reward_calendar.save(account_name)?;
+        }
+
+        for (clan_name, clan_bank) in self.clan_banks.iter() {
+            clan_bank.save(clan_name)?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_journal(&self) -> Result<(), anyhow::Error> {
+        remove_journal_file(&ITEM_TRANSACTION_STORAGE_DIR, &self.id, "item transaction")
+    }
+
+    /// Saves every staged document. This is safe to retry after a failure,
+    /// each attempt rewrites the journal before saving the documents.
+    pub fn commit(&self) -> Result<(), anyhow::Error> {
+        self.write_journal()?;
+
+        if let Err(error) = self.apply() {
+            // The in-memory state will be saved again later, so a journal
+            // which is not being retried must not be replayed over it
+            self.remove_journal().ok();
+            return Err(error);
+        }
+
+        self.remove_journal()
+    }
+}
+
+/// Completes every item transaction which was interrupted by a crash, in the
+/// order they were made, returning how many were recovered. This must run
+/// before any characters or banks are loaded.
+pub fn recover_item_transactions() -> Result<usize, anyhow::Error> {
+    let transactions: Vec<(PathBuf, ItemTransaction)> =
+        read_journal_files(&ITEM_TRANSACTION_STORAGE_DIR, "ItemTransaction")?;
+
+    for (path, transaction) in transactions.iter() {
+        transaction.apply().with_context(|| {
+            format!(
+                "Failed to recover item transaction {}",
+                path.to_string_lossy()
+            )
+        })?;
+        std::fs::remove_file(path).with_context(|| {
+            format!(
+                "Failed to remove item transaction journal {}",
+                path.to_string_lossy()
+            )
+        })?;
+    }
+
+    Ok(transactions.len())
+}
@@ -0,0 +1,303 @@
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::game::storage::{
+    account::AccountStorage, character::CharacterStorage, STORAGE_JOURNAL_DIR,
+};
+
+static NEXT_JOURNAL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a unique id for a journal, the timestamp prefix makes the journals
+/// sort in the order they were made.
+pub(super) fn new_journal_id() -> String {
+    format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%d-%H%M%S-%9f"),
+        NEXT_JOURNAL_ID.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Writes a journal to the storage directory. The file is synced before it is
+/// moved into place, so a crash can never leave a partially written journal.
+pub(super) fn write_journal_file<T: Serialize>(
+    storage_dir: &Path,
+    id: &str,
+    description: &str,
+    journal: &T,
+) -> Result<(), anyhow::Error> {
+    std::fs::create_dir_all(storage_dir).with_context(|| {
+        format!(
+            "Failed to create {} storage directory {}",
+            description,
+            storage_dir.to_string_lossy()
+        )
+    })?;
+
+    let json = serde_json::to_string(journal)
+        .with_context(|| format!("Failed to serialise {} {}", description, id))?;
+
+    let mut file = tempfile::Builder::new()
+        .suffix(".tmp")
+        .tempfile_in(storage_dir)
+        .with_context(|| {
+            format!(
+                "Failed to create temporary file whilst saving {} {}",
+                description, id
+            )
+        })?;
+    file.write_all(json.as_bytes()).with_context(|| {
+        format!(
+            "Failed to write data to temporary file whilst saving {} {}",
+            description, id
+        )
+    })?;
+    file.as_file().sync_all().with_context(|| {
+        format!(
+            "Failed to sync temporary file whilst saving {} {}",
+            description, id
+        )
+    })?;
+
+    let path = storage_dir.join(format!("{}.json", id));
+    file.persist(&path).with_context(|| {
+        format!(
+            "Failed to persist temporary {} file to path {}",
+            description,
+            path.to_string_lossy()
+        )
+    })?;
+    Ok(())
+}
+
+pub(super) fn remove_journal_file(
+    storage_dir: &Path,
+    id: &str,
+    description: &str,
+) -> Result<(), anyhow::Error> {
+    let path = storage_dir.join(format!("{}.json", id));
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error).with_context(|| {
+            format!(
+                "Failed to remove {} journal {}",
+                description,
+                path.to_string_lossy()
+            )
+        }),
+    }
+}
+
+/// Reads every journal left in the storage directory, oldest first.
+pub(super) fn read_journal_files<T: for<'de> Deserialize<'de>>(
+    storage_dir: &Path,
+    description: &str,
+) -> Result<Vec<(PathBuf, T)>, anyhow::Error> {
+    let dir = match std::fs::read_dir(storage_dir) {
+        Ok(dir) => dir,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!(
+                    "Failed to read {} storage directory {}",
+                    description,
+                    storage_dir.to_string_lossy()
+                )
+            })
+        }
+    };
+
+    let mut paths: Vec<PathBuf> = dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+
+    let mut journals = Vec::with_capacity(paths.len());
+    for path in paths {
+        let str = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+        let journal = serde_json::from_str(&str).with_context(|| {
+            format!(
+                "Failed to deserialise {} from file {}",
+                description,
+                path.to_string_lossy()
+            )
+        })?;
+        journals.push((path, journal));
+    }
+
+    Ok(journals)
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum StorageJournalOperation {
+    /// Saves a new character, which fails if the character already exists
+    CreateCharacter(Box<CharacterStorage>),
+    DeleteCharacter(String),
+    SaveAccount(AccountStorage),
+}
+
+/// Changes to several storage documents which must be applied together, such
+/// as creating a character and adding it to the account.
+///
+/// Like `ItemTransaction`, the operations are written to a journal before any
+/// document is changed, and a journal left behind by a crash is replayed by
+/// `recover_storage_journals` before the game starts.
+#[derive(Deserialize, Serialize)]
+pub struct StorageJournal {
+    id: String,
+    operations: Vec<StorageJournalOperation>,
+}
+
+impl Default for StorageJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageJournal {
+    pub fn new() -> Self {
+        Self {
+            id: new_journal_id(),
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn create_character(&mut self, character: CharacterStorage) {
+        self.operations
+            .push(StorageJournalOperation::CreateCharacter(Box::new(
+                character,
+            )));
+    }
+
+    pub fn delete_character(&mut self, name: &str) {
+        self.operations
+            .push(StorageJournalOperation::DeleteCharacter(name.to_string()));
+    }
+
+    pub fn save_account(&mut self, account: AccountStorage) {
+        self.operations
+            .push(StorageJournalOperation::SaveAccount(account));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Writes the operations to the journal, which is the first step of
+    /// `commit`. Once the journal has been written the operations will be
+    /// completed even if the server crashes before they are applied.
+    pub fn write_journal(&self) -> Result<(), anyhow::Error> {
+        write_journal_file(&STORAGE_JOURNAL_DIR, &self.id, "storage journal", self)
+    }
+
+    fn apply(&self, is_recovery: bool) -> Result<(), anyhow::Error> {
+        let mut conflicting_names = Vec::new();
+
+        for operation in self.operations.iter() {
+            match operation {
+                StorageJournalOperation::CreateCharacter(character) => {
+                    if !is_recovery {
+                        character.try_create(&character.info.name)?;
+                    } else if !self.recover_create_character(character)? {
+                        conflicting_names.push(character.info.name.clone());
+                    }
+                }
+                StorageJournalOperation::DeleteCharacter(name) => {
+                    CharacterStorage::delete(name)?;
+                }
+                StorageJournalOperation::SaveAccount(account) => {
+                    if conflicting_names.is_empty() {
+                        account.save()?;
+                    } else {
+                        let mut account = account.clone();
+                        account
+                            .character_names
+                            .retain(|name| !conflicting_names.contains(name));
+                        account.save()?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a character from an interrupted journal, returning false if a
+    /// different character already exists with the same name.
+    ///
+    /// The character may have been created before the crash, but it can not
+    /// have been played as the journal was still waiting to be completed, so
+    /// it must still match the journal.
+    fn recover_create_character(
+        &self,
+        character: &CharacterStorage,
+    ) -> Result<bool, anyhow::Error> {
+        let name = &character.info.name;
+        if !CharacterStorage::exists(name) {
+            character.try_create(name)?;
+            return Ok(true);
+        }
+
+        if character.matches_stored()? {
+            return Ok(true);
+        }
+
+        log::error!(
+            "Storage journal {} creates character {} which already exists as a different character, leaving the existing character in place",
+            self.id,
+            name
+        );
+        Ok(false)
+    }
+
+    /// Applies every operation in order. If an operation fails the journal is
+    /// removed and the operations before it are not undone, so operations
+    /// which can fail, such as creating a character, must come first.
+    pub fn commit(&self) -> Result<(), anyhow::Error> {
+        self.write_journal()?;
+
+        if let Err(error) = self.apply(false) {
+            remove_journal_file(&STORAGE_JOURNAL_DIR, &self.id, "storage journal").ok();
+            return Err(error);
+        }
+
+        remove_journal_file(&STORAGE_JOURNAL_DIR, &self.id, "storage journal")
+    }
+}
+
+/// Completes every storage journal which was interrupted by a crash, in the
+/// order they were made, returning how many were recovered. This must run
+/// before any accounts or characters are loaded.
+pub fn recover_storage_journals() -> Result<usize, anyhow::Error> {
+    let journals: Vec<(PathBuf, StorageJournal)> =
+        read_journal_files(&STORAGE_JOURNAL_DIR, "StorageJournal")?;
+
+    for (path, journal) in journals.iter() {
+        journal.apply(true).with_context(|| {
+            format!(
+                "Failed to recover storage journal {}",
+                path.to_string_lossy()
+            )
+        })?;
+        std::fs::remove_file(path).with_context(|| {
+            format!(
+                "Failed to remove storage journal {}",
+                path.to_string_lossy()
+            )
+        })?;
+    }
+
+    Ok(journals.len())
+}
@@ -0,0 +1,139 @@
+use std::{collections::HashMap, fmt};
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::game::storage::character::CharacterStorage;
+
+/// The values of a character which are ranked by the leaderboards.
+#[derive(Clone, Debug)]
+pub struct LeaderboardCharacter {
+    pub name: String,
+    pub level: u32,
+    pub experience_points: u64,
+    pub money: i64,
+    pub pvp_kills: u32,
+}
+
+impl From<&CharacterStorage> for LeaderboardCharacter {
+    fn from(character: &CharacterStorage) -> Self {
+        Self {
+            name: character.info.name.clone(),
+            level: character.level.level,
+            experience_points: character.experience_points.xp,
+            money: character.inventory.money.0,
+            pvp_kills: character.statistics.pvp_kills,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Leaderboards {
+    pub updated_at: DateTime<Utc>,
+    pub num_characters: usize,
+    pub top_level: Vec<LeaderboardEntry>,
+    pub richest: Vec<LeaderboardEntry>,
+    pub most_pvp_kills: Vec<LeaderboardEntry>,
+}
+
+/// Returns the `size` characters with the highest value, characters with the
+/// same value are ordered by `tie_break` and then by name.
+fn rank_characters<T: Ord>(
+    characters: &[LeaderboardCharacter],
+    size: usize,
+    value: impl Fn(&LeaderboardCharacter) -> i64,
+    tie_break: impl Fn(&LeaderboardCharacter) -> T,
+) -> Vec<LeaderboardEntry> {
+    let mut ranked: Vec<&LeaderboardCharacter> = characters.iter().collect();
+    ranked.sort_by(|a, b| {
+        value(b)
+            .cmp(&value(a))
+            .then_with(|| tie_break(b).cmp(&tie_break(a)))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    ranked
+        .into_iter()
+        .take(size)
+        .map(|character| LeaderboardEntry {
+            name: character.name.clone(),
+            value: value(character),
+        })
+        .collect()
+}
+
+impl fmt::Display for Leaderboards {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Leaderboards of {} characters, updated {}",
+            self.num_characters,
+            self.updated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        )?;
+        for (title, entries) in [
+            ("Top level", &self.top_level),
+            ("Richest", &self.richest),
+            ("Most PvP kills", &self.most_pvp_kills),
+        ] {
+            writeln!(f, "{}:", title)?;
+            for (rank, entry) in entries.iter().enumerate() {
+                writeln!(f, "  {}. {} {}", rank + 1, entry.name, entry.value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Leaderboards {
+    pub fn build(characters: &[LeaderboardCharacter], size: usize) -> Self {
+        Self {
+            updated_at: Utc::now(),
+            num_characters: characters.len(),
+            top_level: rank_characters(
+                characters,
+                size,
+                |character| character.level as i64,
+                |character| character.experience_points,
+            ),
+            richest: rank_characters(characters, size, |character| character.money, |_| ()),
+            most_pvp_kills: rank_characters(
+                characters,
+                size,
+                |character| character.pvp_kills as i64,
+                |_| (),
+            ),
+        }
+    }
+
+    /// Builds the leaderboards from every stored character, using the values
+    /// in `online` for characters which are in the game world as they may be
+    /// newer than the stored values. This reads every character document so
+    /// should not be run on the game world thread.
+    pub fn load(mut online: HashMap<String, LeaderboardCharacter>, size: usize) -> Self {
+        let mut characters = Vec::new();
+        for name in CharacterStorage::find_matching(|_| true) {
+            if let Some(character) = online.remove(&name) {
+                characters.push(character);
+                continue;
+            }
+
+            match CharacterStorage::try_load(&name) {
+                Ok(character) => characters.push(LeaderboardCharacter::from(&character)),
+                Err(error) => warn!(
+                    "Failed to load character {} for leaderboards with error {:?}",
+                    name, error
+                ),
+            }
+        }
+
+        // Online characters which have not been saved yet
+        characters.extend(online.into_values());
+        Self::build(&characters, size)
+    }
+}
@@ -1,20 +1,249 @@
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{OnceLock, RwLock},
+};
 
+use anyhow::Context;
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
+use unicode_normalization::UnicodeNormalization;
+
+static LOCAL_STORAGE_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the storage directory, this must be called before any storage is
+/// accessed and can only be called once.
+pub fn set_local_storage_dir(path: PathBuf) -> Result<(), anyhow::Error> {
+    LOCAL_STORAGE_DIR_OVERRIDE
+        .set(path)
+        .map_err(|_| anyhow::anyhow!("The storage directory has already been set"))
+}
 
 lazy_static! {
     pub static ref LOCAL_STORAGE_DIR: PathBuf = {
-        let project = ProjectDirs::from("", "", "rose-offline").unwrap();
-        PathBuf::from(project.data_local_dir())
+        if let Some(path) = LOCAL_STORAGE_DIR_OVERRIDE.get() {
+            path.clone()
+        } else if let Some(path) = std::env::var_os("ROSE_OFFLINE_STORAGE_DIR") {
+            PathBuf::from(path)
+        } else {
+            let project = ProjectDirs::from("", "", "rose-offline").unwrap();
+            PathBuf::from(project.data_local_dir())
+        }
     };
     pub static ref ACCOUNT_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("accounts");
     pub static ref BANK_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("bank");
     pub static ref CHARACTER_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("characters");
+    pub static ref CHAT_MUTE_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("chat_mutes");
     pub static ref CLAN_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("clan");
+    pub static ref CHARACTER_INSPECTION_DIR: PathBuf = LOCAL_STORAGE_DIR.join("inspections");
+    pub static ref CLAN_BANK_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("clan_bank");
+    pub static ref ITEM_DROP_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("item_drops");
+    pub static ref ITEM_TRANSACTION_STORAGE_DIR: PathBuf =
+        LOCAL_STORAGE_DIR.join("item_transactions");
+    pub static ref NPC_STORE_STOCK_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("npc_store_stock");
+    pub static ref PARTY_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("party");
+    pub static ref REWARD_CALENDAR_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("reward_calendar");
+    pub static ref STORAGE_JOURNAL_DIR: PathBuf = LOCAL_STORAGE_DIR.join("journal");
+    pub static ref SUSPICION_STORAGE_DIR: PathBuf = LOCAL_STORAGE_DIR.join("suspicion");
+    pub static ref ZONE_SNAPSHOT_DIR: PathBuf = LOCAL_STORAGE_DIR.join("zone_snapshots");
+
+    /// The stored name of every indexed document, keyed by storage directory
+    /// and then by `storage_name_key`.
+    static ref STORAGE_NAME_INDEX: RwLock<HashMap<PathBuf, HashMap<String, String>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Returns the name of every document in the storage directory.
+fn storage_names(storage_dir: &Path) -> Vec<String> {
+    let Ok(dir) = std::fs::read_dir(storage_dir) else {
+        return Vec::new();
+    };
+
+    dir.filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path
+                .extension()
+                .map_or(false, |extension| extension == "json")
+            {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the key which account and character names are indexed by, names
+/// with the same key refer to the same document. This is the lowercase NFC
+/// form of the name, so "Bob" and "bob" can not both exist even on a case
+/// sensitive filesystem.
+pub fn storage_name_key(name: &str) -> String {
+    name.nfc().flat_map(char::to_lowercase).nfc().collect()
+}
+
+/// Returns the path of the document whose name has the same key as `name`,
+/// or the path a new document named `name` would be saved to. Documents keep
+/// the name they were created with, so when the name does not exactly match
+/// it is looked up in the index built by `index_account_character_names`.
+fn storage_document_path(storage_dir: &Path, name: &str) -> PathBuf {
+    let name: String = name.nfc().collect();
+    let path = storage_dir.join(format!("{}.json", name));
+    if path.exists() {
+        return path;
+    }
+
+    let key = storage_name_key(&name);
+    STORAGE_NAME_INDEX
+        .read()
+        .unwrap()
+        .get(storage_dir)
+        .and_then(|names| names.get(&key))
+        .map_or(path, |stored_name| {
+            storage_dir.join(format!("{}.json", stored_name))
+        })
+}
+
+/// Adds the document at `path` to the name index, this must be called after
+/// a new document is saved so it can be found by any name with the same key.
+fn index_storage_document(path: &Path) {
+    let (Some(storage_dir), Some(name)) = (path.parent(), path.file_stem()) else {
+        return;
+    };
+    let name = name.to_string_lossy().into_owned();
+
+    STORAGE_NAME_INDEX
+        .write()
+        .unwrap()
+        .entry(storage_dir.to_path_buf())
+        .or_default()
+        .insert(storage_name_key(&name), name);
+}
+
+/// Removes the document at `path` from the name index, if it is the
+/// document indexed for its key.
+fn unindex_storage_document(path: &Path) {
+    let (Some(storage_dir), Some(name)) = (path.parent(), path.file_stem()) else {
+        return;
+    };
+
+    let name = name.to_string_lossy();
+    let key = storage_name_key(&name);
+
+    // Another document with the same key may be indexed in its place
+    if let Some(names) = STORAGE_NAME_INDEX.write().unwrap().get_mut(storage_dir) {
+        if names
+            .get(&key)
+            .map_or(false, |stored_name| *stored_name == name)
+        {
+            names.remove(&key);
+        }
+    }
+}
+
+/// Replaces the name index of the storage directory with the documents which
+/// are currently in it, returning how many were indexed.
+fn index_storage_names(storage_dir: &Path) -> usize {
+    let names: HashMap<String, String> = storage_names(storage_dir)
+        .into_iter()
+        .map(|name| (storage_name_key(&name), name))
+        .collect();
+    let num_indexed = names.len();
+
+    STORAGE_NAME_INDEX
+        .write()
+        .unwrap()
+        .insert(storage_dir.to_path_buf(), names);
+    num_indexed
+}
+
+/// Renames documents in the storage directory whose names are not in NFC
+/// form, returning how many were renamed. Fails without renaming anything if
+/// several documents have the same key, as only one of them could ever be
+/// loaded, so an operator must rename or delete the others first.
+fn migrate_storage_name_keys(storage_dir: &Path) -> Result<usize, anyhow::Error> {
+    let mut names_by_key: HashMap<String, Vec<String>> = HashMap::new();
+    for name in storage_names(storage_dir) {
+        names_by_key
+            .entry(storage_name_key(&name))
+            .or_default()
+            .push(name);
+    }
+
+    let mut duplicate_names: Vec<String> = names_by_key
+        .values()
+        .filter(|names| names.len() > 1)
+        .map(|names| {
+            let mut names = names.clone();
+            names.sort();
+            names.join(", ")
+        })
+        .collect();
+    if !duplicate_names.is_empty() {
+        duplicate_names.sort();
+        anyhow::bail!(
+            "Storage documents in {} have the same name, only one of each can be kept: {}",
+            storage_dir.to_string_lossy(),
+            duplicate_names.join("; ")
+        );
+    }
+
+    let mut num_renamed = 0;
+    for names in names_by_key.values() {
+        let name = &names[0];
+        let normalized_name: String = name.nfc().collect();
+        if *name == normalized_name {
+            continue;
+        }
+
+        let path = storage_dir.join(format!("{}.json", name));
+        let normalized_path = storage_dir.join(format!("{}.json", normalized_name));
+        std::fs::rename(&path, &normalized_path).with_context(|| {
+            format!(
+                "Failed to rename storage document {} to {}",
+                path.to_string_lossy(),
+                normalized_path.to_string_lossy()
+            )
+        })?;
+        num_renamed += 1;
+    }
+
+    Ok(num_renamed)
+}
+
+/// Migrates the account and character documents which were saved before
+/// names were indexed by `storage_name_key`. This must run before any
+/// accounts or characters are loaded.
+pub fn migrate_account_character_names() -> Result<usize, anyhow::Error> {
+    Ok(migrate_storage_name_keys(&ACCOUNT_STORAGE_DIR)?
+        + migrate_storage_name_keys(&CHARACTER_STORAGE_DIR)?)
+}
+
+/// Builds the index used to find account and character documents by a name
+/// which does not exactly match the stored name, returning how many documents
+/// were indexed. This must run after `migrate_account_character_names` and
+/// before any accounts or characters are loaded.
+pub fn index_account_character_names() -> usize {
+    index_storage_names(&ACCOUNT_STORAGE_DIR) + index_storage_names(&CHARACTER_STORAGE_DIR)
 }
 
 pub mod account;
+pub mod backup;
 pub mod bank;
 pub mod character;
+pub mod character_inspection;
+pub mod chat_mute;
 pub mod clan;
+pub mod clan_bank;
+pub mod item_drop;
+pub mod item_transaction;
+pub mod journal;
+pub mod leaderboard;
+pub mod npc_store_stock;
+pub mod party;
+pub mod quest_repair;
+pub mod reward_calendar;
+pub mod schema_version;
+pub mod suspicion;
+pub mod zone_snapshot;
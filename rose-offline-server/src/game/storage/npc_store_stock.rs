@@ -0,0 +1,82 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{io::Write, path::PathBuf};
+
+use rose_data::{ItemReference, NpcId};
+
+use crate::game::storage::{schema_version::StorageSchema, NPC_STORE_STOCK_STORAGE_DIR};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NpcStoreStockItemStorage {
+    pub npc: NpcId,
+    pub item: ItemReference,
+    pub quantity: u32,
+    pub next_restock: DateTime<Utc>,
+}
+
+/// The remaining quantity of every limited stock NPC store item, so that
+/// restarting the server does not restock every store.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct NpcStoreStockStorage {
+    pub items: Vec<NpcStoreStockItemStorage>,
+}
+
+const NPC_STORE_STOCK_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[]);
+
+fn get_npc_store_stock_path() -> PathBuf {
+    NPC_STORE_STOCK_STORAGE_DIR.join("stock.json")
+}
+
+impl NpcStoreStockStorage {
+    /// Loads the saved stock, or None if it has never been saved.
+    pub fn try_load() -> Result<Option<Self>, anyhow::Error> {
+        let path = get_npc_store_stock_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let str = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+        let stock: Self = NPC_STORE_STOCK_STORAGE_SCHEMA
+            .deserialize(&str)
+            .with_context(|| {
+                format!(
+                    "Failed to deserialise NpcStoreStockStorage from file {}",
+                    path.to_string_lossy()
+                )
+            })?;
+        Ok(Some(stock))
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let path = get_npc_store_stock_path();
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create npc store stock storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = NPC_STORE_STOCK_STORAGE_SCHEMA
+            .serialize(self)
+            .context("Failed to serialise NpcStoreStockStorage")?;
+
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .context("Failed to create temporary file whilst saving npc store stock")?;
+        file.write_all(json.as_bytes())
+            .context("Failed to write data to temporary file whilst saving npc store stock")?;
+
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary npc store stock file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use rose_game_common::messages::{PartyItemSharing, PartyXpSharing};
+
+use crate::game::{
+    components::{CharacterUniqueId, PartyUniqueId},
+    storage::{
+        schema_version::{migrate_add_schema_version, StorageSchema},
+        PARTY_STORAGE_DIR,
+    },
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct PartyStorageMember {
+    pub character_id: CharacterUniqueId,
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PartyStorage {
+    pub unique_id: PartyUniqueId,
+    pub members: Vec<PartyStorageMember>,
+    pub item_sharing: PartyItemSharing,
+    pub xp_sharing: PartyXpSharing,
+}
+
+const PARTY_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[migrate_add_schema_version]);
+
+fn get_party_path(unique_id: PartyUniqueId) -> PathBuf {
+    PARTY_STORAGE_DIR.join(format!("{}.json", unique_id))
+}
+
+impl PartyStorage {
+    pub fn try_load_party_list() -> Result<Vec<Self>, anyhow::Error> {
+        let mut party_list = Vec::new();
+
+        for entry in (PARTY_STORAGE_DIR.read_dir()?).flatten() {
+            let path = entry.path();
+            let str = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+            let party: Self = PARTY_STORAGE_SCHEMA.deserialize(&str).with_context(|| {
+                format!(
+                    "Failed to deserialise PartyStorage from file {}",
+                    path.to_string_lossy()
+                )
+            })?;
+            party_list.push(party);
+        }
+
+        Ok(party_list)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        let path = get_party_path(self.unique_id);
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create party storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = PARTY_STORAGE_SCHEMA.serialize(self).with_context(|| {
+            format!(
+                "Failed to serialise PartyStorage whilst saving party {}",
+                self.unique_id
+            )
+        })?;
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving party {}",
+                    self.unique_id
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving party {}",
+                self.unique_id
+            )
+        })?;
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary party file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+
+    pub fn delete(unique_id: PartyUniqueId) -> Result<(), anyhow::Error> {
+        let path = get_party_path(unique_id);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
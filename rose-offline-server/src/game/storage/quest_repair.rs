@@ -0,0 +1,219 @@
+use anyhow::Context;
+use std::{collections::HashMap, fmt, path::Path};
+
+use rose_data::{ItemDatabase, ItemReference, QuestDatabase};
+
+use crate::game::{components::QuestState, storage::character::CharacterStorage};
+
+/// Maps the id of a quest which was removed or renumbered by a game data
+/// update to the id of the quest which replaces it.
+pub type QuestRemap = HashMap<usize, usize>;
+
+/// Loads a quest remap from a JSON object of old quest id to new quest id,
+/// for example `{ "120": 1120 }`.
+pub fn load_quest_remap(path: &Path) -> Result<QuestRemap, anyhow::Error> {
+    let str = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+    serde_json::from_str(&str).with_context(|| {
+        format!(
+            "Failed to deserialise quest remap from file {}",
+            path.to_string_lossy()
+        )
+    })
+}
+
+/// A change made to an active quest which is not valid for the loaded game data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuestRepair {
+    Remapped {
+        slot: usize,
+        from: usize,
+        to: usize,
+    },
+    RemovedQuest {
+        slot: usize,
+        quest_id: usize,
+    },
+    RemovedDuplicateQuest {
+        slot: usize,
+        quest_id: usize,
+    },
+    ClearedExpireTime {
+        slot: usize,
+        quest_id: usize,
+    },
+    RemovedItem {
+        slot: usize,
+        quest_id: usize,
+        item: ItemReference,
+    },
+}
+
+impl fmt::Display for QuestRepair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestRepair::Remapped { slot, from, to } => {
+                write!(f, "slot {}: remapped quest {} to quest {}", slot, from, to)
+            }
+            QuestRepair::RemovedQuest { slot, quest_id } => {
+                write!(f, "slot {}: removed unknown quest {}", slot, quest_id)
+            }
+            QuestRepair::RemovedDuplicateQuest { slot, quest_id } => {
+                write!(f, "slot {}: removed duplicate quest {}", slot, quest_id)
+            }
+            QuestRepair::ClearedExpireTime { slot, quest_id } => write!(
+                f,
+                "slot {}: cleared expire time of quest {} which no longer has a time limit",
+                slot, quest_id
+            ),
+            QuestRepair::RemovedItem {
+                slot,
+                quest_id,
+                item,
+            } => write!(
+                f,
+                "slot {}: removed unknown item {:?} {} from quest {}",
+                slot, item.item_type, item.item_number, quest_id
+            ),
+        }
+    }
+}
+
+/// Checks the active quests against the loaded game data, remapping quests
+/// listed in `remap` and removing quests and quest items which no longer
+/// exist. Returns every change which was made.
+///
+/// The quest variables and switches are not described by the game data, so
+/// they are kept as they are.
+pub fn repair_quest_state(
+    quest_state: &mut QuestState,
+    quests: &QuestDatabase,
+    items: &ItemDatabase,
+    remap: &QuestRemap,
+) -> Vec<QuestRepair> {
+    let mut repairs = Vec::new();
+    let mut seen_quest_ids = Vec::new();
+
+    for (slot, active_quest_slot) in quest_state.active_quests.iter_mut().enumerate() {
+        let Some(active_quest) = active_quest_slot.as_mut() else {
+            continue;
+        };
+
+        if let Some(&to) = remap.get(&active_quest.quest_id) {
+            repairs.push(QuestRepair::Remapped {
+                slot,
+                from: active_quest.quest_id,
+                to,
+            });
+            active_quest.quest_id = to;
+        }
+        let quest_id = active_quest.quest_id;
+
+        let Some(quest_data) = quests.get_quest_data(quest_id) else {
+            repairs.push(QuestRepair::RemovedQuest { slot, quest_id });
+            *active_quest_slot = None;
+            continue;
+        };
+
+        if seen_quest_ids.contains(&quest_id) {
+            repairs.push(QuestRepair::RemovedDuplicateQuest { slot, quest_id });
+            *active_quest_slot = None;
+            continue;
+        }
+        seen_quest_ids.push(quest_id);
+
+        if quest_data.time_limit.is_none() && active_quest.expire_time.is_some() {
+            repairs.push(QuestRepair::ClearedExpireTime { slot, quest_id });
+            active_quest.expire_time = None;
+        }
+
+        for item_slot in active_quest.items.iter_mut() {
+            let Some(item) = item_slot.as_ref() else {
+                continue;
+            };
+
+            let item_reference = item.get_item_reference();
+            if items.get_base_item(item_reference).is_none() {
+                repairs.push(QuestRepair::RemovedItem {
+                    slot,
+                    quest_id,
+                    item: item_reference,
+                });
+                *item_slot = None;
+            }
+        }
+    }
+
+    repairs
+}
+
+/// The result of checking the quest state of every stored character.
+#[derive(Default)]
+pub struct QuestRepairReport {
+    pub num_characters: usize,
+    pub repaired: Vec<(String, Vec<QuestRepair>)>,
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+impl fmt::Display for QuestRepairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Checked {} characters, {} needed repairs, {} failed",
+            self.num_characters,
+            self.repaired.len(),
+            self.failed.len()
+        )?;
+        for (character_name, repairs) in self.repaired.iter() {
+            writeln!(f, "{}:", character_name)?;
+            for repair in repairs.iter() {
+                writeln!(f, "  {}", repair)?;
+            }
+        }
+        for (character_name, error) in self.failed.iter() {
+            writeln!(f, "{}: failed with error {:?}", character_name, error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Repairs the quest state of every stored character, this must only be run
+/// whilst the server is stopped. When `dry_run` is set the repairs are only
+/// reported and no characters are saved.
+pub fn repair_stored_quest_states(
+    quests: &QuestDatabase,
+    items: &ItemDatabase,
+    remap: &QuestRemap,
+    dry_run: bool,
+) -> QuestRepairReport {
+    let mut report = QuestRepairReport::default();
+    let mut character_names = CharacterStorage::find_matching(|_| true);
+    character_names.sort();
+
+    for character_name in character_names {
+        report.num_characters += 1;
+
+        let mut character = match CharacterStorage::try_load(&character_name) {
+            Ok(character) => character,
+            Err(error) => {
+                report.failed.push((character_name, error));
+                continue;
+            }
+        };
+
+        let repairs = repair_quest_state(&mut character.quest_state, quests, items, remap);
+        if repairs.is_empty() {
+            continue;
+        }
+
+        if !dry_run {
+            if let Err(error) = character.save() {
+                report.failed.push((character_name, error));
+                continue;
+            }
+        }
+        report.repaired.push((character_name, repairs));
+    }
+
+    report
+}
@@ -0,0 +1,93 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{io::Write, path::PathBuf};
+use thiserror::Error;
+
+use crate::game::storage::{
+    schema_version::{migrate_add_schema_version, StorageSchema},
+    REWARD_CALENDAR_STORAGE_DIR,
+};
+
+#[derive(Error, Debug)]
+pub enum RewardCalendarStorageError {
+    #[error("Account not found")]
+    NotFound,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct RewardCalendarStorage {
+    /// Day of the last claimed reward, as days since the common era
+    pub last_claim_day: Option<i32>,
+    pub streak: u32,
+}
+
+const REWARD_CALENDAR_STORAGE_SCHEMA: StorageSchema =
+    StorageSchema::new(&[migrate_add_schema_version]);
+
+fn get_reward_calendar_path(account_name: &str) -> PathBuf {
+    REWARD_CALENDAR_STORAGE_DIR.join(format!("{}.json", account_name))
+}
+
+impl RewardCalendarStorage {
+    pub fn try_load(account_name: &str) -> Result<Self, anyhow::Error> {
+        let path = get_reward_calendar_path(account_name);
+        if path.exists() {
+            let str = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+            let reward_calendar: Self = REWARD_CALENDAR_STORAGE_SCHEMA
+                .deserialize(&str)
+                .with_context(|| {
+                    format!(
+                        "Failed to deserialise RewardCalendarStorage from file {}",
+                        path.to_string_lossy()
+                    )
+                })?;
+            Ok(reward_calendar)
+        } else {
+            Err(RewardCalendarStorageError::NotFound.into())
+        }
+    }
+
+    pub fn save(&self, account_name: &str) -> Result<(), anyhow::Error> {
+        let path = get_reward_calendar_path(account_name);
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create reward calendar storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = REWARD_CALENDAR_STORAGE_SCHEMA.serialize(self).with_context(|| {
+            format!(
+                "Failed to serialise RewardCalendarStorage whilst saving reward calendar for account {}",
+                account_name
+            )
+        })?;
+
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving reward calendar for account {}",
+                    account_name
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving reward calendar for account {}",
+                account_name
+            )
+        })?;
+
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary reward calendar file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+}
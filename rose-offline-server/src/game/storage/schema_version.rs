@@ -0,0 +1,106 @@
+use anyhow::Context;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a storage document from one schema version to the next.
+pub type StorageMigration = fn(&mut Map<String, Value>) -> Result<(), anyhow::Error>;
+
+#[derive(Error, Debug)]
+pub enum StorageSchemaError {
+    #[error("Storage document is not a JSON object")]
+    InvalidDocument,
+
+    #[error("Invalid storage schema version")]
+    InvalidVersion,
+
+    #[error(
+        "Storage schema version {version} is newer than the supported version {supported_version}"
+    )]
+    UnsupportedVersion {
+        version: u64,
+        supported_version: u64,
+    },
+}
+
+#[derive(Serialize)]
+struct VersionedDocument<'a, T: Serialize> {
+    schema_version: u64,
+    #[serde(flatten)]
+    document: &'a T,
+}
+
+/// The migrations for a storage document type, `migrations[n]` upgrades a
+/// document from version `n` to version `n + 1`. Documents saved before
+/// versioning was introduced have no schema_version and are treated as version 0.
+pub struct StorageSchema {
+    pub migrations: &'static [StorageMigration],
+}
+
+impl StorageSchema {
+    pub const fn new(migrations: &'static [StorageMigration]) -> Self {
+        Self { migrations }
+    }
+
+    pub fn current_version(&self) -> u64 {
+        self.migrations.len() as u64
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, str: &str) -> Result<T, anyhow::Error> {
+        let Value::Object(mut document) = serde_json::from_str(str)? else {
+            return Err(StorageSchemaError::InvalidDocument.into());
+        };
+
+        let version = match document.remove(SCHEMA_VERSION_KEY) {
+            Some(version) => version.as_u64().ok_or(StorageSchemaError::InvalidVersion)?,
+            None => 0,
+        };
+
+        if version > self.current_version() {
+            return Err(StorageSchemaError::UnsupportedVersion {
+                version,
+                supported_version: self.current_version(),
+            }
+            .into());
+        }
+
+        for (from_version, migration) in self.migrations.iter().enumerate().skip(version as usize) {
+            migration(&mut document).with_context(|| {
+                format!(
+                    "Failed to migrate storage from schema version {} to {}",
+                    from_version,
+                    from_version + 1
+                )
+            })?;
+        }
+
+        Ok(serde_json::from_value(Value::Object(document))?)
+    }
+
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String, anyhow::Error> {
+        Ok(serde_json::to_string_pretty(&VersionedDocument {
+            schema_version: self.current_version(),
+            document: value,
+        })?)
+    }
+}
+
+/// Migration for the initial schema version which only adds the schema_version field.
+pub fn migrate_add_schema_version(_: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    Ok(())
+}
+
+/// Inserts `value` for `key` if the document does not already contain it,
+/// used when migrating documents to add new fields with a default value.
+pub fn migrate_insert_default<T: Serialize>(
+    document: &mut Map<String, Value>,
+    key: &str,
+    value: T,
+) -> Result<(), anyhow::Error> {
+    if !document.contains_key(key) {
+        document.insert(key.to_string(), serde_json::to_value(value)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Write, path::PathBuf};
+use thiserror::Error;
+
+use crate::game::{
+    events::SuspicionSignal,
+    storage::{schema_version::StorageSchema, storage_name_key, SUSPICION_STORAGE_DIR},
+};
+
+#[derive(Error, Debug)]
+pub enum SuspicionStorageError {
+    #[error("Account not found")]
+    NotFound,
+}
+
+/// The suspicious signals recorded for an account across every session, kept
+/// for GMs to review with the suspicion chat command.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct SuspicionStorage {
+    pub score: u64,
+    pub signals: BTreeMap<SuspicionSignal, u32>,
+
+    /// How many times the account was disconnected for reaching the kick score
+    pub kicks: u32,
+    pub last_signal: Option<DateTime<Utc>>,
+}
+
+const SUSPICION_STORAGE_SCHEMA: StorageSchema = StorageSchema::new(&[]);
+
+fn get_suspicion_path(account_name: &str) -> PathBuf {
+    SUSPICION_STORAGE_DIR.join(format!("{}.json", storage_name_key(account_name)))
+}
+
+impl SuspicionStorage {
+    pub fn add_signal(&mut self, signal: SuspicionSignal, weight: u32, now: DateTime<Utc>) {
+        self.score += weight as u64;
+        *self.signals.entry(signal).or_default() += 1;
+        self.last_signal = Some(now);
+    }
+
+    pub fn try_load(account_name: &str) -> Result<Self, anyhow::Error> {
+        let path = get_suspicion_path(account_name);
+        if path.exists() {
+            let str = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+            let suspicion: Self =
+                SUSPICION_STORAGE_SCHEMA
+                    .deserialize(&str)
+                    .with_context(|| {
+                        format!(
+                            "Failed to deserialise SuspicionStorage from file {}",
+                            path.to_string_lossy()
+                        )
+                    })?;
+            Ok(suspicion)
+        } else {
+            Err(SuspicionStorageError::NotFound.into())
+        }
+    }
+
+    pub fn save(&self, account_name: &str) -> Result<(), anyhow::Error> {
+        let path = get_suspicion_path(account_name);
+        let storage_dir = path.parent().unwrap();
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create suspicion storage directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = SUSPICION_STORAGE_SCHEMA.serialize(self).with_context(|| {
+            format!(
+                "Failed to serialise SuspicionStorage whilst saving suspicion for account {}",
+                account_name
+            )
+        })?;
+
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving suspicion for account {}",
+                    account_name
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving suspicion for account {}",
+                account_name
+            )
+        })?;
+
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary suspicion file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Context;
+use bevy::{ecs::prelude::Entity, math::Vec3};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use rose_data::{NpcId, ZoneId};
+
+use crate::game::{
+    components::{ClientEntityId, ClientEntityType, Command, CommandData},
+    storage::ZONE_SNAPSHOT_DIR,
+};
+
+/// The state of a single entity when the zone snapshot was taken. Components
+/// the entity does not have are None.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ZoneSnapshotEntity {
+    pub entity: Entity,
+    pub client_entity_id: Option<ClientEntityId>,
+    pub entity_type: Option<ClientEntityType>,
+    pub name: Option<String>,
+    pub npc_id: Option<NpcId>,
+    pub position: Vec3,
+    pub command: Option<Command>,
+    pub next_command: Option<CommandData>,
+    pub health: Option<i32>,
+    pub max_health: Option<i32>,
+    pub level: Option<u32>,
+    pub team: Option<u32>,
+    pub owner: Option<Entity>,
+    pub ai_index: Option<usize>,
+}
+
+/// Every entity in a zone at one point in time, saved by a GM so desync and
+/// AI bug reports can be analysed offline with rose-zone-snapshot.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ZoneSnapshot {
+    pub zone_id: ZoneId,
+    pub created: DateTime<Utc>,
+    pub created_by: String,
+    pub entities: Vec<ZoneSnapshotEntity>,
+}
+
+impl ZoneSnapshot {
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let str = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {}", path.to_string_lossy()))?;
+        serde_json::from_str(&str).with_context(|| {
+            format!(
+                "Failed to deserialise ZoneSnapshot from file {}",
+                path.to_string_lossy()
+            )
+        })
+    }
+
+    /// Writes the snapshot to a new timestamped file, returning the path of
+    /// the file.
+    pub fn save(&self) -> Result<PathBuf, anyhow::Error> {
+        let storage_dir = ZONE_SNAPSHOT_DIR.as_path();
+        let path = storage_dir.join(format!(
+            "zone{}-{}.json",
+            self.zone_id.get(),
+            self.created.format("%Y%m%d-%H%M%S")
+        ));
+
+        std::fs::create_dir_all(storage_dir).with_context(|| {
+            format!(
+                "Failed to create zone snapshot directory {}",
+                storage_dir.to_string_lossy()
+            )
+        })?;
+
+        let json = serde_json::to_string_pretty(self).with_context(|| {
+            format!(
+                "Failed to serialise ZoneSnapshot for zone {}",
+                self.zone_id.get()
+            )
+        })?;
+        let mut file = tempfile::Builder::new()
+            .tempfile_in(storage_dir)
+            .with_context(|| {
+                format!(
+                    "Failed to create temporary file whilst saving snapshot of zone {}",
+                    self.zone_id.get()
+                )
+            })?;
+        file.write_all(json.as_bytes()).with_context(|| {
+            format!(
+                "Failed to write data to temporary file whilst saving snapshot of zone {}",
+                self.zone_id.get()
+            )
+        })?;
+        file.persist(&path).with_context(|| {
+            format!(
+                "Failed to persist temporary zone snapshot file to path {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        Ok(path)
+    }
+}
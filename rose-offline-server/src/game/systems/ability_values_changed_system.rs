@@ -3,7 +3,9 @@ use bevy::{
     prelude::Or,
 };
 
-use crate::game::components::{AbilityValues, HealthPoints, ManaPoints, MoveMode, MoveSpeed};
+use crate::game::components::{
+    AbilityValues, HealthPoints, ManaPoints, MoveMode, MoveSpeed, Weight,
+};
 
 #[derive(WorldQuery)]
 #[world_query(mutable)]
@@ -13,10 +15,14 @@ pub struct AbilityValuesChangedQuery<'w> {
     mana_points: Option<&'w mut ManaPoints>,
     move_mode: &'w MoveMode,
     move_speed: &'w mut MoveSpeed,
+    weight: Option<&'w Weight>,
 }
 
 pub fn ability_values_changed_system(
-    mut query: Query<AbilityValuesChangedQuery, Or<(Changed<AbilityValues>, Changed<MoveMode>)>>,
+    mut query: Query<
+        AbilityValuesChangedQuery,
+        Or<(Changed<AbilityValues>, Changed<MoveMode>, Changed<Weight>)>,
+    >,
 ) {
     for mut object in query.iter_mut() {
         // Update is_driving so vehicle stats are used correctly
@@ -36,8 +42,12 @@ pub fn ability_values_changed_system(
             }
         }
 
-        // Update move speed
-        let updated_move_speed = object.ability_values.get_move_speed(object.move_mode);
+        // Update move speed, which is limited when over max weight
+        let updated_move_speed = if let Some(weight) = object.weight {
+            weight.get_move_speed(&object.ability_values, object.move_mode)
+        } else {
+            object.ability_values.get_move_speed(object.move_mode)
+        };
         if (object.move_speed.speed - updated_move_speed).abs() > f32::EPSILON {
             object.move_speed.speed = updated_move_speed;
         }
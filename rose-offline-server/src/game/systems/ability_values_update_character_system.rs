@@ -6,8 +6,10 @@ use bevy::ecs::{
 
 use crate::game::{
     components::{
-        AbilityValues, BasicStats, CharacterInfo, Equipment, Level, SkillList, StatusEffects,
+        AbilityValues, Achievements, BasicStats, CharacterInfo, Equipment, Level, Rebirth,
+        SkillList, StatusEffects,
     },
+    resources::GameConfig,
     GameData,
 };
 
@@ -21,6 +23,8 @@ pub struct AbilityValuesCharacterQuery<'w> {
     level: &'w Level,
     skill_list: &'w SkillList,
     status_effects: &'w StatusEffects,
+    achievements: Option<&'w Achievements>,
+    rebirth: Option<&'w Rebirth>,
 }
 
 pub fn ability_values_update_character_system(
@@ -33,8 +37,11 @@ pub fn ability_values_update_character_system(
             Changed<BasicStats>,
             Changed<SkillList>,
             Changed<StatusEffects>,
+            Changed<Achievements>,
+            Changed<Rebirth>,
         )>,
     >,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
 ) {
     for mut character in query.iter_mut() {
@@ -46,5 +53,21 @@ pub fn ability_values_update_character_system(
             character.skill_list,
             character.status_effects,
         );
+
+        if let Some(title) = character
+            .achievements
+            .and_then(|achievements| achievements.equipped_title.as_deref())
+            .and_then(|achievement_id| game_config.achievements.get_title(achievement_id))
+        {
+            title.bonus.apply(&mut character.ability_values);
+        }
+
+        if let (Some(rebirth_config), Some(rebirth)) =
+            (game_config.rebirth.as_ref(), character.rebirth)
+        {
+            rebirth_config
+                .bonus_per_rebirth
+                .apply_multiple(&mut character.ability_values, rebirth.count as i32);
+        }
     }
 }
@@ -5,7 +5,7 @@ use bevy::ecs::{
 };
 
 use crate::game::{
-    components::{AbilityValues, Npc, StatusEffects},
+    components::{AbilityValues, EliteMonster, Npc, StatusEffects},
     GameData,
 };
 
@@ -15,6 +15,7 @@ pub struct AbilityValuesNpcQuery<'w> {
     ability_values: &'w mut AbilityValues,
     npc: &'w Npc,
     status_effects: &'w StatusEffects,
+    elite_monster: Option<&'w EliteMonster>,
 }
 
 pub fn ability_values_update_npc_system(
@@ -31,5 +32,9 @@ pub fn ability_values_update_npc_system(
                 npc.ability_values.summon_skill_level,
             )
             .unwrap();
+
+        if let Some(elite_monster) = npc.elite_monster {
+            elite_monster.apply_ability_values(&mut npc.ability_values);
+        }
     }
 }
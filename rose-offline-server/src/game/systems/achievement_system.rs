@@ -0,0 +1,181 @@
+use bevy::ecs::{
+    change_detection::DetectChangesMut,
+    prelude::{Changed, Entity, EventReader, Query, Res, With},
+    query::WorldQuery,
+};
+
+use crate::game::{
+    components::{Achievements, GameClient, Level},
+    events::AchievementEvent,
+    messages::server::ServerMessage,
+    resources::{Achievement, AchievementGoal, AchievementsConfig, GameConfig},
+};
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct AchievementCharacterQuery<'w> {
+    achievements: &'w mut Achievements,
+    game_client: Option<&'w GameClient>,
+}
+
+fn send_achievement_message(game_client: Option<&GameClient>, text: String) {
+    if let Some(game_client) = game_client {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text,
+            })
+            .ok();
+    }
+}
+
+fn send_achievement_unlocked(game_client: Option<&GameClient>, achievement: &Achievement) {
+    send_achievement_message(
+        game_client,
+        format!("Achievement unlocked: {}", achievement.name),
+    );
+
+    if let Some(title) = achievement.title.as_ref() {
+        send_achievement_message(
+            game_client,
+            format!(
+                "You can now equip the title {} with /title {}",
+                title.name, achievement.id
+            ),
+        );
+    }
+}
+
+/// Adds one to the progress of every achievement whose goal matches.
+fn add_achievement_progress(
+    character: &mut AchievementCharacterQueryItem,
+    achievements_config: &AchievementsConfig,
+    is_match: impl Fn(&AchievementGoal) -> bool,
+) {
+    for achievement in achievements_config.achievements.iter() {
+        if !is_match(&achievement.goal) {
+            continue;
+        }
+
+        // Only the equipped title changes the ability values, so progress
+        // does not trigger change detection
+        if character
+            .achievements
+            .bypass_change_detection()
+            .add_progress(&achievement.id, 1, achievement.goal.get_required_progress())
+        {
+            send_achievement_unlocked(character.game_client, achievement);
+        }
+    }
+}
+
+pub fn achievement_system(
+    mut achievement_events: EventReader<AchievementEvent>,
+    mut query: Query<AchievementCharacterQuery>,
+    query_level_changed: Query<(Entity, &Level), (Changed<Level>, With<Achievements>)>,
+    game_config: Res<GameConfig>,
+) {
+    let achievements_config = &game_config.achievements;
+
+    // Level achievements are also checked when a character joins the game, so
+    // characters which were above the level before it was added unlock it
+    for (entity, level) in query_level_changed.iter() {
+        let Ok(mut character) = query.get_mut(entity) else {
+            continue;
+        };
+
+        for achievement in achievements_config.achievements.iter() {
+            let AchievementGoal::ReachLevel {
+                level: required_level,
+            } = achievement.goal
+            else {
+                continue;
+            };
+
+            if level.level >= required_level
+                && character
+                    .achievements
+                    .bypass_change_detection()
+                    .unlock(&achievement.id)
+            {
+                send_achievement_unlocked(character.game_client, achievement);
+            }
+        }
+    }
+
+    for event in achievement_events.iter() {
+        match event {
+            &AchievementEvent::Kill { entity, npc_id } => {
+                let Ok(mut character) = query.get_mut(entity) else {
+                    continue;
+                };
+
+                add_achievement_progress(&mut character, achievements_config, |goal| match *goal {
+                    AchievementGoal::KillNpc {
+                        npc_id: goal_npc_id,
+                        ..
+                    } => goal_npc_id.map_or(true, |goal_npc_id| goal_npc_id == npc_id),
+                    _ => false,
+                });
+            }
+            &AchievementEvent::CompleteQuest { entity, quest_id } => {
+                let Ok(mut character) = query.get_mut(entity) else {
+                    continue;
+                };
+
+                add_achievement_progress(&mut character, achievements_config, |goal| match *goal {
+                    AchievementGoal::CompleteQuest {
+                        quest_id: goal_quest_id,
+                        ..
+                    } => goal_quest_id.map_or(true, |goal_quest_id| goal_quest_id == quest_id),
+                    _ => false,
+                });
+            }
+            AchievementEvent::EquipTitle {
+                entity,
+                achievement_id,
+            } => {
+                let Ok(mut character) = query.get_mut(*entity) else {
+                    continue;
+                };
+
+                let Some(achievement_id) = achievement_id else {
+                    if character.achievements.equipped_title.is_some() {
+                        character.achievements.equipped_title = None;
+                        send_achievement_message(
+                            character.game_client,
+                            String::from("Title unequipped"),
+                        );
+                    }
+                    continue;
+                };
+
+                let Some(title) = achievements_config.get_title(achievement_id) else {
+                    send_achievement_message(
+                        character.game_client,
+                        format!("Achievement {} does not have a title", achievement_id),
+                    );
+                    continue;
+                };
+
+                if !character.achievements.is_unlocked(achievement_id) {
+                    send_achievement_message(
+                        character.game_client,
+                        format!(
+                            "You must unlock achievement {} to equip the title {}",
+                            achievement_id, title.name
+                        ),
+                    );
+                    continue;
+                }
+
+                character.achievements.equipped_title = Some(achievement_id.clone());
+                send_achievement_message(
+                    character.game_client,
+                    format!("Equipped the title {}", title.name),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use bevy::{
+    ecs::prelude::{Commands, Entity, Local, Query, Res, With, Without},
+    time::Time,
+};
+use log::info;
+
+use crate::game::{
+    components::{Activity, CharacterInfo, GameClient, PersonalStore, Statistics, WorldClient},
+    messages::server::ServerMessage,
+    resources::GameConfig,
+};
+
+fn send_afk_message(game_client: &GameClient, text: String) {
+    game_client
+        .server_message_tx
+        .send(ServerMessage::Whisper {
+            from: String::from("SERVER"),
+            text,
+        })
+        .ok();
+}
+
+pub fn activity_system(
+    mut commands: Commands,
+    mut query_joined: Query<(Entity, &mut Statistics), (With<GameClient>, Without<Activity>)>,
+    query_disconnected: Query<Entity, (With<Activity>, Without<GameClient>)>,
+    mut query_active: Query<(
+        Entity,
+        &mut Activity,
+        &mut Statistics,
+        &GameClient,
+        &CharacterInfo,
+        Option<&PersonalStore>,
+    )>,
+    mut playtime: Local<Duration>,
+    game_config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    // A new session starts whenever a game client takes control of a
+    // character, which includes reconnecting to a disconnected character
+    for (entity, mut statistics) in query_joined.iter_mut() {
+        statistics.start_session();
+        commands.entity(entity).insert(Activity::new(now));
+    }
+
+    for entity in query_disconnected.iter() {
+        commands.entity(entity).remove::<Activity>();
+    }
+
+    // Playtime is counted in whole seconds for every character with a
+    // connected game client
+    *playtime += time.delta();
+    let seconds = playtime.as_secs();
+    if seconds > 0 {
+        *playtime -= Duration::from_secs(seconds);
+        for (_, _, mut statistics, _, _, _) in query_active.iter_mut() {
+            statistics.add_playtime(seconds);
+        }
+    }
+
+    let Some(afk_config) = game_config.afk.as_ref() else {
+        return;
+    };
+
+    // AFK players are only disconnected to make room when the server is busy
+    let num_online = query_active.iter().len();
+    if num_online < afk_config.kick_min_online {
+        return;
+    }
+
+    for (entity, mut activity, _, game_client, character_info, personal_store) in
+        query_active.iter_mut()
+    {
+        if afk_config.exempt_personal_store && personal_store.is_some() {
+            continue;
+        }
+
+        if now.saturating_duration_since(activity.last_activity) < afk_config.timeout {
+            continue;
+        }
+
+        match activity.afk_warned_time {
+            None => {
+                activity.afk_warned_time = Some(now);
+                send_afk_message(
+                    game_client,
+                    format!(
+                        "You are AFK and will be disconnected in {} seconds",
+                        afk_config.warning_duration.as_secs()
+                    ),
+                );
+            }
+            Some(afk_warned_time)
+                if now.saturating_duration_since(afk_warned_time)
+                    >= afk_config.warning_duration =>
+            {
+                info!(
+                    "Disconnecting character {} for being AFK with {} players online",
+                    &character_info.name, num_online
+                );
+                send_afk_message(
+                    game_client,
+                    String::from("You have been disconnected for being AFK"),
+                );
+
+                // Removing the client components closes the connection, the
+                // character is then saved by the usual disconnect handling
+                if let Some(world_client_entity) = game_client.world_client_entity {
+                    commands.entity(world_client_entity).remove::<WorldClient>();
+                }
+                commands.entity(entity).remove::<GameClient>();
+            }
+            Some(_) => {}
+        }
+    }
+}
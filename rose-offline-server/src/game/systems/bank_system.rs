@@ -1,22 +1,70 @@
-use bevy::prelude::{EventReader, Query};
+use bevy::prelude::{EventReader, Query, Res, ResMut};
+use log::error;
 
 use rose_data::ItemSlotBehaviour;
 use rose_game_common::messages::server::ServerMessage;
 
 use crate::game::{
-    components::{Bank, GameClient, Inventory},
+    components::{Account, Bank, CharacterInfo, GameClient, Inventory, PersonalStore, Position},
     events::BankEvent,
+    resources::{StorageService, ZoneList},
+    storage::{bank::BankStorage, item_transaction::ItemTransaction},
 };
 
+fn save_bank_transaction(
+    storage_service: &mut StorageService,
+    character_info: &CharacterInfo,
+    account: &Account,
+    inventory: &Inventory,
+    bank: &Bank,
+) {
+    let mut transaction = ItemTransaction::new();
+    transaction.update_inventory(&character_info.name, inventory);
+    transaction.update_bank(&account.name, BankStorage::from(bank));
+    if let Err(error) = storage_service.write_item_transaction(transaction) {
+        error!(
+            "Failed to save bank transaction for character {} with error {:?}",
+            &character_info.name, error
+        );
+    }
+}
+
 pub fn bank_system(
     mut bank_events: EventReader<BankEvent>,
-    mut query_entity: Query<(&GameClient, &mut Bank, &mut Inventory)>,
+    mut query_entity: Query<(
+        &GameClient,
+        &CharacterInfo,
+        &Account,
+        &mut Bank,
+        &mut Inventory,
+        Option<&PersonalStore>,
+    )>,
+    query_position: Query<(&GameClient, &Position)>,
+    mut storage_service: ResMut<StorageService>,
+    zone_list: Res<ZoneList>,
 ) {
     for event in bank_events.iter() {
+        let (BankEvent::Open { entity }
+        | BankEvent::DepositItem { entity, .. }
+        | BankEvent::WithdrawItem { entity, .. }) = *event;
+
+        if let Ok((game_client, position)) = query_position.get(entity) {
+            if !zone_list.is_town(position.zone_id) {
+                game_client
+                    .server_message_tx
+                    .send(ServerMessage::Whisper {
+                        from: String::from("SERVER"),
+                        text: String::from("The bank can only be used in towns"),
+                    })
+                    .ok();
+                continue;
+            }
+        }
+
         match *event {
             BankEvent::Open { entity } => {
                 let (game_client, mut bank) =
-                    if let Ok((game_client, bank, _)) = query_entity.get_mut(entity) {
+                    if let Ok((game_client, _, _, bank, _, _)) = query_entity.get_mut(entity) {
                         (game_client, bank)
                     } else {
                         continue;
@@ -50,13 +98,33 @@ pub fn bank_system(
                 ref item,
                 .. // TODO: is_premium,
             } => {
-                let (game_client, mut bank, mut inventory) =
-                    if let Ok((game_client, bank, inventory)) = query_entity.get_mut(entity) {
-                        (game_client, bank, inventory)
+                let (game_client, character_info, account, mut bank, mut inventory, personal_store) =
+                    if let Ok((game_client, character_info, account, bank, inventory, personal_store)) = query_entity.get_mut(entity) {
+                        (game_client, character_info, account, bank, inventory, personal_store)
                     } else {
                         continue;
                     };
 
+                if personal_store.map_or(false, |store| store.is_item_slot_reserved(item_slot)) {
+                    continue;
+                }
+
+                // Bound items can not be stored, as the bank is shared by every character of the account
+                if item.is_bound()
+                    || inventory
+                        .get_item(item_slot)
+                        .map_or(false, |inventory_item| inventory_item.is_bound())
+                {
+                    game_client
+                        .server_message_tx
+                        .send(ServerMessage::Whisper {
+                            from: String::from("SERVER"),
+                            text: String::from("Bound items can not be stored in the bank"),
+                        })
+                        .ok();
+                    continue;
+                }
+
                 if inventory.get_item(item_slot).map_or(false, |inventory_item| inventory_item.is_same_item(item)) {
                     if let Some(inventory_slot) = inventory.get_item_slot_mut(item_slot) {
                         if let Some(deposit_item) =
@@ -64,6 +132,15 @@ pub fn bank_system(
                         {
                             match bank.try_add_item(deposit_item) {
                                 Ok((bank_slot, bank_item)) => {
+                                    let bank_item = bank_item.clone();
+                                    save_bank_transaction(
+                                        &mut storage_service,
+                                        character_info,
+                                        account,
+                                        &inventory,
+                                        &bank,
+                                    );
+
                                     game_client
                                         .server_message_tx
                                         .send(ServerMessage::BankTransaction {
@@ -73,7 +150,7 @@ pub fn bank_system(
                                                 .cloned(),
                                             inventory_money: Some(inventory.money),
                                             bank_slot,
-                                            bank_item: Some(bank_item.clone()),
+                                            bank_item: Some(bank_item),
                                         })
                                         .ok();
                                 }
@@ -93,9 +170,9 @@ pub fn bank_system(
                 ref item,
                 .. // TODO: is_premium,
             } => {
-                let (game_client, mut bank, mut inventory) =
-                    if let Ok((game_client, bank, inventory)) = query_entity.get_mut(entity) {
-                        (game_client, bank, inventory)
+                let (game_client, character_info, account, mut bank, mut inventory) =
+                    if let Ok((game_client, character_info, account, bank, inventory, _)) = query_entity.get_mut(entity) {
+                        (game_client, character_info, account, bank, inventory)
                     } else {
                         continue;
                     };
@@ -105,11 +182,20 @@ pub fn bank_system(
                         if let Some(withdraw_item) = bank_slot.try_take_quantity(item.get_quantity()) {
                             match inventory.try_add_item(withdraw_item) {
                                 Ok((inventory_item_slot, inventory_item)) => {
+                                    let inventory_item = inventory_item.clone();
+                                    save_bank_transaction(
+                                        &mut storage_service,
+                                        character_info,
+                                        account,
+                                        &inventory,
+                                        &bank,
+                                    );
+
                                     game_client
                                         .server_message_tx
                                         .send(ServerMessage::BankTransaction {
                                             inventory_item_slot,
-                                            inventory_item: Some(inventory_item.clone()),
+                                            inventory_item: Some(inventory_item),
                                             inventory_money: Some(inventory.money),
                                             bank_slot: bank_slot_index,
                                             bank_item: bank.slots.get(bank_slot_index).unwrap().clone(),
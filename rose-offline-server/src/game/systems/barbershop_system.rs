@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use bevy::{
+    ecs::{
+        prelude::{Commands, Entity, EventReader, Query, Res, With},
+        query::WorldQuery,
+    },
+    math::Vec3Swizzles,
+    time::Time,
+};
+
+use rose_data::AbilityType;
+
+use crate::game::{
+    components::{
+        BarbershopSession, CharacterInfo, ClientEntity, GameClient, Inventory, Money, Npc, Position,
+    },
+    events::BarbershopEvent,
+    messages::server::{BarbershopError, ServerMessage},
+    GameData,
+};
+
+const BARBERSHOP_MAX_DISTANCE: f32 = 6000.0;
+const BARBERSHOP_PREVIEW_TIMEOUT: Duration = Duration::from_secs(60);
+const BARBERSHOP_FACE_PRICE: Money = Money(50000);
+const BARBERSHOP_HAIR_PRICE: Money = Money(20000);
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct BarbershopCustomerQuery<'w> {
+    entity: Entity,
+    game_client: &'w GameClient,
+    character_info: &'w mut CharacterInfo,
+    inventory: &'w mut Inventory,
+    position: &'w Position,
+    client_entity: Option<&'w ClientEntity>,
+    barbershop_session: Option<&'w BarbershopSession>,
+}
+
+fn send_barbershop_error(game_client: &GameClient, error: BarbershopError) {
+    game_client
+        .server_message_tx
+        .send(ServerMessage::BarbershopError { error })
+        .ok();
+}
+
+fn is_npc_in_range(
+    npc_query: &Query<&Position, With<Npc>>,
+    npc_entity: Entity,
+    position: &Position,
+) -> bool {
+    npc_query.get(npc_entity).map_or(false, |npc_position| {
+        npc_position.zone_id == position.zone_id
+            && position.position.xy().distance(npc_position.position.xy())
+                <= BARBERSHOP_MAX_DISTANCE
+    })
+}
+
+pub fn barbershop_system(
+    mut commands: Commands,
+    mut barbershop_events: EventReader<BarbershopEvent>,
+    mut customer_query: Query<BarbershopCustomerQuery>,
+    npc_query: Query<&Position, With<Npc>>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    // Cancel any preview sessions which have timed out or where the character has left the zone
+    for customer in customer_query.iter() {
+        let Some(session) = customer.barbershop_session else {
+            continue;
+        };
+
+        let error =
+            if customer.client_entity.is_none() || customer.position.zone_id != session.zone_id {
+                BarbershopError::ZoneChanged
+            } else if now >= session.expire_time {
+                BarbershopError::PreviewExpired
+            } else {
+                continue;
+            };
+
+        commands
+            .entity(customer.entity)
+            .remove::<BarbershopSession>();
+        send_barbershop_error(customer.game_client, error);
+    }
+
+    for event in barbershop_events.iter() {
+        match *event {
+            BarbershopEvent::Preview {
+                entity,
+                npc_entity,
+                face,
+                hair,
+            } => {
+                let Ok(customer) = customer_query.get_mut(entity) else {
+                    continue;
+                };
+
+                if !is_npc_in_range(&npc_query, npc_entity, customer.position) {
+                    send_barbershop_error(customer.game_client, BarbershopError::NpcTooFarAway);
+                    continue;
+                }
+
+                if game_data.items.get_face_item(face as usize).is_none() {
+                    send_barbershop_error(customer.game_client, BarbershopError::InvalidAppearance);
+                    continue;
+                }
+
+                // Only charge for the parts of the appearance which are changing
+                let mut price = Money(0);
+                if face != customer.character_info.face {
+                    price = price + BARBERSHOP_FACE_PRICE;
+                }
+                if hair != customer.character_info.hair {
+                    price = price + BARBERSHOP_HAIR_PRICE;
+                }
+
+                // A new preview replaces any existing preview session
+                commands.entity(entity).insert(BarbershopSession {
+                    npc_entity,
+                    zone_id: customer.position.zone_id,
+                    face,
+                    hair,
+                    price,
+                    expire_time: now + BARBERSHOP_PREVIEW_TIMEOUT,
+                });
+
+                customer
+                    .game_client
+                    .server_message_tx
+                    .send(ServerMessage::BarbershopPreview { face, hair, price })
+                    .ok();
+            }
+            BarbershopEvent::Confirm { entity } => {
+                let Ok(mut customer) = customer_query.get_mut(entity) else {
+                    continue;
+                };
+
+                let Some(session) = customer.barbershop_session else {
+                    send_barbershop_error(customer.game_client, BarbershopError::NoPreview);
+                    continue;
+                };
+                commands.entity(entity).remove::<BarbershopSession>();
+
+                if now >= session.expire_time {
+                    send_barbershop_error(customer.game_client, BarbershopError::PreviewExpired);
+                    continue;
+                }
+
+                if !is_npc_in_range(&npc_query, session.npc_entity, customer.position) {
+                    send_barbershop_error(customer.game_client, BarbershopError::NpcTooFarAway);
+                    continue;
+                }
+
+                if customer.inventory.try_take_money(session.price).is_err() {
+                    send_barbershop_error(customer.game_client, BarbershopError::NotEnoughMoney);
+                    continue;
+                }
+
+                customer.character_info.face = session.face;
+                customer.character_info.hair = session.hair;
+
+                customer
+                    .game_client
+                    .server_message_tx
+                    .send(ServerMessage::UpdateAbilityValueSet {
+                        ability_type: AbilityType::Face,
+                        value: session.face as i32,
+                    })
+                    .ok();
+                customer
+                    .game_client
+                    .server_message_tx
+                    .send(ServerMessage::UpdateAbilityValueSet {
+                        ability_type: AbilityType::Hair,
+                        value: session.hair as i32,
+                    })
+                    .ok();
+                customer
+                    .game_client
+                    .server_message_tx
+                    .send(ServerMessage::UpdateMoney {
+                        money: customer.inventory.money,
+                    })
+                    .ok();
+            }
+            BarbershopEvent::Cancel { entity } => {
+                commands.entity(entity).remove::<BarbershopSession>();
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use bevy::{
+    ecs::{
+        prelude::{Commands, Entity, EventReader, EventWriter, Local, Query, Res, ResMut},
+        query::WorldQuery,
+    },
+    math::Vec3,
+    prelude::DespawnRecursiveExt,
+};
+use log::{info, warn};
+use rand::Rng;
+
+use crate::game::{
+    bots::{bot_level_range, bot_spawn},
+    bundles::client_entity_leave_zone,
+    components::{
+        CharacterInfo, ClientEntity, ClientEntitySector, GameClient, PartyMembership, Position,
+    },
+    events::{BotScenarioEvent, PartyMemberEvent},
+    messages::server::ServerMessage,
+    resources::{BotList, BotListEntry, BotScenario, ClientEntityList, GameConfig},
+    GameData,
+};
+
+/// How far from the zone start position scenario bots are spawned
+const SCENARIO_SPAWN_RADIUS: f32 = 500.0;
+
+#[derive(WorldQuery)]
+pub struct BotRemoveQuery<'w> {
+    character_info: &'w CharacterInfo,
+    client_entity: Option<&'w ClientEntity>,
+    client_entity_sector: Option<&'w ClientEntitySector>,
+    party_membership: &'w PartyMembership,
+    position: &'w Position,
+}
+
+fn send_bot_scenario_message(
+    query_game_client: &Query<&GameClient>,
+    entity: Option<Entity>,
+    text: String,
+) {
+    info!("{}", text);
+
+    if let Some(game_client) = entity.and_then(|entity| query_game_client.get(entity).ok()) {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text,
+            })
+            .ok();
+    }
+}
+
+fn start_bot_scenario(
+    commands: &mut Commands,
+    bot_list: &mut BotList,
+    game_data: &GameData,
+    scenario: &BotScenario,
+) -> usize {
+    let mut rng = rand::thread_rng();
+    let mut num_spawned = 0;
+
+    for i in 0..scenario.count {
+        let zone_id = scenario.zones[i % scenario.zones.len()];
+        let Some(zone_data) = game_data.zones.get_zone(zone_id) else {
+            continue;
+        };
+
+        let zone_level_range = bot_level_range(game_data, zone_id);
+        let min_level = scenario.min_level.unwrap_or(*zone_level_range.start());
+        let max_level = scenario
+            .max_level
+            .unwrap_or(*zone_level_range.end())
+            .max(min_level);
+
+        let position = Position::new(
+            zone_data.start_position
+                + Vec3::new(
+                    rng.gen_range(-SCENARIO_SPAWN_RADIUS..SCENARIO_SPAWN_RADIUS),
+                    rng.gen_range(-SCENARIO_SPAWN_RADIUS..SCENARIO_SPAWN_RADIUS),
+                    0.0,
+                ),
+            zone_id,
+        );
+
+        let entity = bot_spawn(
+            commands,
+            game_data,
+            format!("{} {}", scenario.name, i + 1),
+            position,
+            rng.gen_range(min_level..=max_level),
+            scenario.choose_profile(&mut rng),
+        );
+        bot_list.push(BotListEntry::with_scenario(entity, scenario.name.clone()));
+        num_spawned += 1;
+    }
+
+    num_spawned
+}
+
+fn remove_bot(
+    commands: &mut Commands,
+    client_entity_list: &mut ClientEntityList,
+    party_member_events: &mut EventWriter<PartyMemberEvent>,
+    query_bot: &Query<BotRemoveQuery>,
+    entity: Entity,
+) {
+    if let Ok(bot) = query_bot.get(entity) {
+        if let (Some(client_entity), Some(client_entity_sector)) =
+            (bot.client_entity, bot.client_entity_sector)
+        {
+            client_entity_leave_zone(
+                commands,
+                client_entity_list,
+                entity,
+                client_entity,
+                client_entity_sector,
+                bot.position,
+            );
+        }
+
+        if let Some(party_entity) = bot.party_membership.party {
+            party_member_events.send(PartyMemberEvent::Disconnect {
+                party_entity,
+                disconnect_entity: entity,
+                character_id: bot.character_info.unique_id,
+                name: bot.character_info.name.clone(),
+            });
+        }
+    }
+
+    // The big_brain thinker is a child entity of the bot
+    commands.entity(entity).despawn_recursive();
+}
+
+pub fn bot_scenario_system(
+    mut commands: Commands,
+    mut bot_scenario_events: EventReader<BotScenarioEvent>,
+    mut bot_list: ResMut<BotList>,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    mut party_member_events: EventWriter<PartyMemberEvent>,
+    query_bot: Query<BotRemoveQuery>,
+    query_game_client: Query<&GameClient>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    mut autostarted: Local<bool>,
+) {
+    let mut start_scenarios = Vec::new();
+    if !*autostarted {
+        *autostarted = true;
+
+        for scenario in game_config.bot_scenarios.scenarios.iter() {
+            if scenario.autostart {
+                start_scenarios.push((scenario.name.clone(), None));
+            }
+        }
+    }
+
+    for event in bot_scenario_events.iter() {
+        match event {
+            BotScenarioEvent::Start { name, entity } => {
+                start_scenarios.push((name.clone(), *entity));
+            }
+            BotScenarioEvent::Stop { name, entity } => {
+                let mut num_removed = 0;
+                bot_list.retain(|bot| {
+                    if bot
+                        .scenario
+                        .as_ref()
+                        .map_or(false, |scenario| scenario.eq_ignore_ascii_case(name))
+                    {
+                        remove_bot(
+                            &mut commands,
+                            &mut client_entity_list,
+                            &mut party_member_events,
+                            &query_bot,
+                            bot.entity,
+                        );
+                        num_removed += 1;
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                send_bot_scenario_message(
+                    &query_game_client,
+                    *entity,
+                    format!(
+                        "Stopped bot scenario {}, removed {} bots",
+                        name, num_removed
+                    ),
+                );
+            }
+            BotScenarioEvent::Clear { entity } => {
+                let num_removed = bot_list.len();
+                for bot in bot_list.drain(..) {
+                    remove_bot(
+                        &mut commands,
+                        &mut client_entity_list,
+                        &mut party_member_events,
+                        &query_bot,
+                        bot.entity,
+                    );
+                }
+
+                send_bot_scenario_message(
+                    &query_game_client,
+                    *entity,
+                    format!("Removed {} bots", num_removed),
+                );
+            }
+        }
+    }
+
+    for (name, entity) in start_scenarios {
+        let Some(scenario) = game_config.bot_scenarios.get(&name) else {
+            send_bot_scenario_message(
+                &query_game_client,
+                entity,
+                format!("Unknown bot scenario {}", name),
+            );
+            continue;
+        };
+
+        if scenario.zones.is_empty() {
+            warn!("Bot scenario {} has no zones", scenario.name);
+            continue;
+        }
+
+        if bot_list.iter().any(|bot| {
+            bot.scenario.as_ref().map_or(false, |running| {
+                running.eq_ignore_ascii_case(&scenario.name)
+            })
+        }) {
+            send_bot_scenario_message(
+                &query_game_client,
+                entity,
+                format!("Bot scenario {} is already running", scenario.name),
+            );
+            continue;
+        }
+
+        let num_spawned = start_bot_scenario(&mut commands, &mut bot_list, &game_data, scenario);
+        send_bot_scenario_message(
+            &query_game_client,
+            entity,
+            format!(
+                "Started bot scenario {}, spawned {} bots",
+                scenario.name, num_spawned
+            ),
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use bevy::prelude::{EventReader, Query, Res};
+use log::error;
+use std::collections::HashSet;
+
+use rose_data::Item;
+use rose_game_common::messages::server::ServerMessage;
+
+use crate::game::{
+    components::GameClient,
+    events::CharacterInspectEvent,
+    resources::{GameData, StorageKey, StorageService},
+    storage::{character::CharacterStorage, character_inspection::CharacterInspection},
+};
+
+use super::save_system::SaveEntityQuery;
+
+const CHARACTER_SEARCH_MAX_RESULTS: usize = 20;
+
+fn send_whisper_lines(game_client: &GameClient, lines: &[String]) {
+    for line in lines {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text: line.clone(),
+            })
+            .ok();
+    }
+}
+
+fn format_item(game_data: &GameData, item: &Item) -> String {
+    let item_reference = item.get_item_reference();
+    let name = game_data
+        .items
+        .get_base_item(item_reference)
+        .map_or("?", |item_data| item_data.name.as_ref());
+    let mut text = format!(
+        "{:?} {} {} x{}",
+        item_reference.item_type,
+        item_reference.item_number,
+        name,
+        item.get_quantity()
+    );
+    if let Some(instance_id) = item.instance_id() {
+        text.push_str(&format!(" #{}", instance_id));
+    }
+    text
+}
+
+fn format_inspection(game_data: &GameData, inspection: &CharacterInspection) -> Vec<String> {
+    let character = &inspection.character;
+    let mut lines = vec![
+        format!(
+            "{} ({}) account: {}",
+            character.info.name,
+            if inspection.online {
+                "online"
+            } else {
+                "offline"
+            },
+            inspection.account_name.as_deref().unwrap_or("unknown"),
+        ),
+        format!(
+            "level: {} job: {} zone: {} position: ({}, {})",
+            character.level.level,
+            character.info.job,
+            character.position.zone_id.get(),
+            character.position.position.x,
+            character.position.position.y,
+        ),
+        format!("money: {}", character.inventory.money.0),
+    ];
+
+    lines.push(String::from("inventory:"));
+    for page in [
+        &character.inventory.equipment,
+        &character.inventory.consumables,
+        &character.inventory.materials,
+        &character.inventory.vehicles,
+    ] {
+        for (index, item) in page.slots.iter().enumerate() {
+            if let Some(item) = item {
+                lines.push(format!(
+                    "  {:?} {}: {}",
+                    page.page_type,
+                    index,
+                    format_item(game_data, item)
+                ));
+            }
+        }
+    }
+
+    match inspection.bank.as_ref() {
+        Some(bank) => {
+            lines.push(String::from("bank:"));
+            for (index, item) in bank.slots.iter().enumerate() {
+                if let Some(item) = item {
+                    lines.push(format!("  {}: {}", index, format_item(game_data, item)));
+                }
+            }
+        }
+        None => lines.push(String::from("bank: not found")),
+    }
+
+    let skills: Vec<String> = character
+        .skill_list
+        .pages
+        .iter()
+        .flat_map(|page| page.skills.iter().flatten())
+        .map(|skill_id| skill_id.get().to_string())
+        .collect();
+    lines.push(format!("skills: {}", skills.join(", ")));
+
+    let quests: Vec<String> = character
+        .quest_state
+        .active_quests
+        .iter()
+        .flatten()
+        .map(|active_quest| active_quest.quest_id.to_string())
+        .collect();
+    lines.push(format!("active quests: {}", quests.join(", ")));
+
+    if inspection.pending_write {
+        lines.push(String::from(
+            "storage has a queued write for this character, stored data may be outdated",
+        ));
+    }
+
+    lines
+}
+
+/// Returns where the item instance is in the character's inventory, equipment
+/// and bank. Banks are shared by every character of an account, so a bank is
+/// only searched the first time its account is seen.
+fn find_item_instance(
+    inspection: &CharacterInspection,
+    instance_id: u64,
+    searched_banks: &mut HashSet<String>,
+) -> Vec<String> {
+    let character = &inspection.character;
+    let mut locations = Vec::new();
+
+    for page in [
+        &character.inventory.equipment,
+        &character.inventory.consumables,
+        &character.inventory.materials,
+        &character.inventory.vehicles,
+    ] {
+        for (index, item) in page.slots.iter().enumerate() {
+            if item.as_ref().and_then(|item| item.instance_id()) == Some(instance_id) {
+                locations.push(format!("inventory {:?} {}", page.page_type, index));
+            }
+        }
+    }
+
+    for (index, item) in character.equipment.equipped_items.iter() {
+        if item.as_ref().and_then(|item| item.instance_id) == Some(instance_id) {
+            locations.push(format!("equipped {:?}", index));
+        }
+    }
+
+    for (index, item) in character.equipment.equipped_vehicle.iter() {
+        if item.as_ref().and_then(|item| item.instance_id) == Some(instance_id) {
+            locations.push(format!("vehicle {:?}", index));
+        }
+    }
+
+    if let (Some(account_name), Some(bank)) =
+        (inspection.account_name.as_ref(), inspection.bank.as_ref())
+    {
+        if searched_banks.insert(account_name.to_lowercase()) {
+            for (index, item) in bank.slots.iter().enumerate() {
+                if item.as_ref().and_then(|item| item.instance_id()) == Some(instance_id) {
+                    locations.push(format!("bank of account {} slot {}", account_name, index));
+                }
+            }
+        }
+    }
+
+    locations
+        .into_iter()
+        .map(|location| {
+            format!(
+                "  {} ({}): {}",
+                character.info.name,
+                if inspection.online {
+                    "online"
+                } else {
+                    "offline"
+                },
+                location
+            )
+        })
+        .collect()
+}
+
+/// Inspects the character in the game world if it is online, otherwise the
+/// character is loaded from storage.
+fn inspect_character(
+    query_character: &Query<SaveEntityQuery>,
+    storage_service: &StorageService,
+    name: &str,
+) -> Result<CharacterInspection, anyhow::Error> {
+    if let Some(character) = query_character
+        .iter()
+        .find(|character| character.character_name().eq_ignore_ascii_case(name))
+    {
+        let pending_write = storage_service.has_pending_write(&StorageKey::Character(
+            character.character_name().to_string(),
+        ));
+        return Ok(character.character_inspection(pending_write));
+    }
+
+    let mut inspection = CharacterInspection::load(name)?;
+    inspection.pending_write = storage_service.has_pending_write(&StorageKey::Character(
+        inspection.character.info.name.clone(),
+    ));
+    Ok(inspection)
+}
+
+pub fn character_inspect_system(
+    mut character_inspect_events: EventReader<CharacterInspectEvent>,
+    query_character: Query<SaveEntityQuery>,
+    query_game_client: Query<&GameClient>,
+    storage_service: Res<StorageService>,
+    game_data: Res<GameData>,
+) {
+    for event in character_inspect_events.iter() {
+        match event {
+            CharacterInspectEvent::Search { entity, pattern } => {
+                let Ok(game_client) = query_game_client.get(*entity) else {
+                    continue;
+                };
+
+                let pattern = pattern.to_lowercase();
+                let names =
+                    CharacterStorage::find_matching(|name| name.to_lowercase().contains(&pattern));
+
+                let mut lines = vec![format!("Found {} characters", names.len())];
+                for name in names.iter().take(CHARACTER_SEARCH_MAX_RESULTS) {
+                    let online = query_character
+                        .iter()
+                        .any(|character| character.character_name() == name);
+                    lines.push(format!(
+                        "  {}{}",
+                        name,
+                        if online { " (online)" } else { "" }
+                    ));
+                }
+                if names.len() > CHARACTER_SEARCH_MAX_RESULTS {
+                    lines.push(format!(
+                        "  ... and {} more",
+                        names.len() - CHARACTER_SEARCH_MAX_RESULTS
+                    ));
+                }
+                send_whisper_lines(game_client, &lines);
+            }
+            CharacterInspectEvent::Inspect { entity, name } => {
+                let Ok(game_client) = query_game_client.get(*entity) else {
+                    continue;
+                };
+
+                match inspect_character(&query_character, &storage_service, name) {
+                    Ok(inspection) => {
+                        send_whisper_lines(game_client, &format_inspection(&game_data, &inspection))
+                    }
+                    Err(error) => send_whisper_lines(
+                        game_client,
+                        &[format!("Failed to inspect character {}: {}", name, error)],
+                    ),
+                }
+            }
+            CharacterInspectEvent::Dump { entity, name } => {
+                let Ok(game_client) = query_game_client.get(*entity) else {
+                    continue;
+                };
+
+                match inspect_character(&query_character, &storage_service, name)
+                    .and_then(|inspection| inspection.save())
+                {
+                    Ok(path) => send_whisper_lines(
+                        game_client,
+                        &[format!(
+                            "Saved character {} to {}",
+                            name,
+                            path.to_string_lossy()
+                        )],
+                    ),
+                    Err(error) => {
+                        error!("Failed to dump character {} with error {:?}", name, error);
+                        send_whisper_lines(
+                            game_client,
+                            &[format!("Failed to dump character {}: {}", name, error)],
+                        );
+                    }
+                }
+            }
+            CharacterInspectEvent::FindItem {
+                entity,
+                instance_id,
+            } => {
+                let instance_id = *instance_id;
+                let Ok(game_client) = query_game_client.get(*entity) else {
+                    continue;
+                };
+
+                // Online characters are searched in the game world, as storage
+                // may not have their latest items yet
+                let mut searched_banks = HashSet::new();
+                let mut locations = Vec::new();
+                let mut online_names = HashSet::new();
+                for character in query_character.iter() {
+                    online_names.insert(character.character_name().to_string());
+                    locations.extend(find_item_instance(
+                        &character.character_inspection(false),
+                        instance_id,
+                        &mut searched_banks,
+                    ));
+                }
+
+                for name in CharacterStorage::find_matching(|name| !online_names.contains(name)) {
+                    match CharacterInspection::load(&name) {
+                        Ok(inspection) => locations.extend(find_item_instance(
+                            &inspection,
+                            instance_id,
+                            &mut searched_banks,
+                        )),
+                        Err(error) => error!(
+                            "Failed to load character {} whilst finding item instance {} with error {:?}",
+                            name, instance_id, error
+                        ),
+                    }
+                }
+
+                let mut lines = vec![format!(
+                    "Found item instance {} in {} places",
+                    instance_id,
+                    locations.len()
+                )];
+                lines.extend(locations);
+                send_whisper_lines(game_client, &lines);
+            }
+        }
+    }
+}
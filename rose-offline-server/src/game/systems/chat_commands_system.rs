@@ -1,68 +1,122 @@
 use std::{
     f32::consts::PI,
-    num::{ParseFloatError, ParseIntError},
+    num::{NonZeroU16, ParseFloatError, ParseIntError},
+    time::{Duration, Instant},
 };
 
 use bevy::{
     ecs::{
-        prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut},
+        prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut, With},
         query::WorldQuery,
         system::SystemParam,
     },
     math::{UVec2, Vec3, Vec3Swizzles},
     time::Time,
-    utils::HashSet,
 };
 use clap::{Arg, PossibleValue};
 use lazy_static::lazy_static;
 use rand::Rng;
 
 use rose_data::{
-    AbilityType, EquipmentIndex, EquipmentItem, Item, ItemReference, ItemType, NpcId, SkillId,
-    StackableItem, ZoneId,
+    AbilityType, EquipmentItem, Item, ItemReference, ItemType, NpcId, SkillId, StackableItem,
+    ZoneId,
 };
 use rose_game_common::{
-    components::{BasicStatType, ClanLevel, ClanPoints, DroppedItem, ExperiencePoints, SkillSlot},
-    data::Damage,
+    components::{
+        BasicStatType, ClanLevel, ClanMark, ClanPoints, DroppedItem, ExperiencePoints, SkillSlot,
+        INVENTORY_PAGE_SIZE,
+    },
+    data::{Damage, Password},
+    messages::{server::ClanBankAction, PartyItemSharing},
 };
 
 use crate::game::{
     bots::{
         bot_build_artisan, bot_build_bourgeois, bot_build_champion, bot_build_cleric,
-        bot_build_knight, bot_build_mage, bot_build_raider, bot_build_scout,
-        bot_create_random_build, bot_create_with_build, bot_snowball_fight, bot_thinker,
+        bot_build_knight, bot_build_mage, bot_build_raider, bot_build_scout, bot_create_with_build,
+        bot_level_range, bot_snowball_fight, bot_spawn, BotProfile,
     },
     bundles::{
-        ability_values_add_value, ability_values_set_value, client_entity_teleport_zone,
-        CharacterBundle, ItemDropBundle, MonsterBundle,
+        ability_values_add_value, ability_values_set_value, ItemDropBundle, ItemSpawn,
+        MonsterBundle,
     },
     components::{
-        AbilityValues, BasicStats, CharacterInfo, ClanMembership, ClientEntity, ClientEntitySector,
-        ClientEntityType, Command, Cooldowns, DamageSources, EquipmentItemDatabase, GameClient,
-        HealthPoints, Inventory, Level, ManaPoints, Money, MotionData, MoveMode, MoveSpeed,
-        NextCommand, PartyMembership, PassiveRecoveryTime, PersonalStore, Position, SkillList,
-        SkillPoints, SpawnOrigin, Stamina, StatPoints, StatusEffects, StatusEffectsRegen, Team,
-        UnionMembership, PERSONAL_STORE_ITEM_SLOTS,
+        AbilityValues, Account, Achievements, BasicStats, CharacterInfo, ClanBank, ClanMembership,
+        ClientEntity, ClientEntityType, EntityExpireTime, GameClient, HealthPoints, Inventory,
+        InventoryPageType, ItemSlot, Level, ManaPoints, Money, MoveSpeed, NextCommand, Npc, Party,
+        PartyMapMarkerHidden, PartyMembership, PersonalStore, Position, SkillList, SkillPoints,
+        SpawnOrigin, Spectator, Stamina, StatPoints, Statistics, StatusEffects, Team, TeleportGate,
+        UnionMembership, Weight, PERSONAL_STORE_ITEM_SLOTS,
+    },
+    events::{
+        AchievementEvent, BarbershopEvent, BotScenarioEvent, CharacterInspectEvent,
+        ChatCommandEvent, ChatEvent, ClanBankEvent, ClanEvent, DamageEvent, EventZoneEvent,
+        InvasionEvent, InventoryEvent, PartyEvent, PersonalStoreEvent, RebirthEvent,
+        RewardCalendarEvent, RewardItemEvent, RewardXpEvent, TeleportEvent, ZoneSnapshotEvent,
     },
-    events::{ChatCommandEvent, ClanEvent, DamageEvent, RewardItemEvent, RewardXpEvent},
     messages::server::ServerMessage,
-    resources::{BotList, BotListEntry, ClientEntityList, ServerMessages, WorldRates},
+    resources::{
+        BotList, BotListEntry, ChatChannel, ChatModeration, CheatDetection, ClientEntityList,
+        EmailSender, GameConfig, LeaderboardCache, Maintenance, ServerMessages, StorageKey,
+        StorageService, TickProfiler, WorldRates,
+    },
+    storage::{
+        account::{AccountStorage, AccountStorageError},
+        character::CharacterStorage,
+        chat_mute::ChatMuteStorage,
+    },
     GameData,
 };
 
+use super::{
+    chat_system::{delete_chat_mute, format_chat_mute, save_chat_mute},
+    cheat_detection_system::save_suspicion,
+};
+
+const TELEPORT_GATE_RADIUS: f32 = 300.0;
+
+const INVENTORY_PAGE_NAMES: [&str; 4] = ["equipment", "consumables", "materials", "vehicles"];
+
 #[derive(SystemParam)]
 pub struct ChatCommandParams<'w, 's> {
     commands: Commands<'w, 's>,
+    account_query: Query<'w, 's, &'static mut Account>,
+    achievement_events: EventWriter<'w, AchievementEvent>,
+    barbershop_events: EventWriter<'w, BarbershopEvent>,
     bot_list: ResMut<'w, BotList>,
+    bot_scenario_events: EventWriter<'w, BotScenarioEvent>,
+    character_inspect_events: EventWriter<'w, CharacterInspectEvent>,
+    chat_events: EventWriter<'w, ChatEvent>,
+    chat_moderation: ResMut<'w, ChatModeration>,
+    cheat_detection: ResMut<'w, CheatDetection>,
     client_entity_list: ResMut<'w, ClientEntityList>,
+    event_zone_events: EventWriter<'w, EventZoneEvent>,
+    game_config: Res<'w, GameConfig>,
     game_data: Res<'w, GameData>,
+    invasion_events: EventWriter<'w, InvasionEvent>,
+    inventory_events: EventWriter<'w, InventoryEvent>,
+    leaderboard_cache: Option<ResMut<'w, LeaderboardCache>>,
+    maintenance: ResMut<'w, Maintenance>,
+    npc_query: Query<'w, 's, (Entity, &'static Position), With<Npc>>,
     clan_events: EventWriter<'w, ClanEvent>,
+    clan_bank_events: EventWriter<'w, ClanBankEvent>,
+    clan_bank_query: Query<'w, 's, &'static ClanBank>,
     reward_xp_events: EventWriter<'w, RewardXpEvent>,
+    reward_calendar_events: EventWriter<'w, RewardCalendarEvent>,
     damage_events: EventWriter<'w, DamageEvent>,
+    email_sender: Option<Res<'w, EmailSender>>,
+    party_events: EventWriter<'w, PartyEvent>,
+    party_query: Query<'w, 's, &'static Party>,
+    personal_store_events: EventWriter<'w, PersonalStoreEvent>,
+    rebirth_events: EventWriter<'w, RebirthEvent>,
     reward_item_events: EventWriter<'w, RewardItemEvent>,
     server_messages: ResMut<'w, ServerMessages>,
+    storage_service: ResMut<'w, StorageService>,
+    teleport_events: EventWriter<'w, TeleportEvent>,
+    tick_profiler: Option<Res<'w, TickProfiler>>,
     time: Res<'w, Time>,
     world_rates: ResMut<'w, WorldRates>,
+    zone_snapshot_events: EventWriter<'w, ZoneSnapshotEvent>,
 }
 
 #[derive(WorldQuery)]
@@ -70,8 +124,8 @@ pub struct ChatCommandParams<'w, 's> {
 pub struct ChatCommandUserQuery<'w> {
     entity: Entity,
     ability_values: &'w AbilityValues,
+    achievements: &'w Achievements,
     client_entity: &'w ClientEntity,
-    client_entity_sector: &'w ClientEntitySector,
     game_client: &'w GameClient,
     level: &'w mut Level,
     position: &'w Position,
@@ -85,8 +139,11 @@ pub struct ChatCommandUserQuery<'w> {
     skill_points: &'w mut SkillPoints,
     stamina: &'w mut Stamina,
     stat_points: &'w mut StatPoints,
+    statistics: &'w Statistics,
     union_membership: &'w mut UnionMembership,
     clan_membership: &'w ClanMembership,
+    party_membership: &'w PartyMembership,
+    weight: Option<&'w Weight>,
 }
 
 lazy_static! {
@@ -95,6 +152,105 @@ lazy_static! {
             .subcommand(clap::Command::new("help"))
             .subcommand(clap::Command::new("where"))
             .subcommand(clap::Command::new("ability_values"))
+            .subcommand(clap::Command::new("dailyreward"))
+            .subcommand(
+                clap::Command::new("clannotice")
+                    .arg(Arg::new("text").required(false).multiple_values(true)),
+            )
+            .subcommand(
+                clap::Command::new("clandescription")
+                    .arg(Arg::new("text").required(false).multiple_values(true)),
+            )
+            .subcommand(
+                clap::Command::new("clanmark")
+                    .arg(Arg::new("background").required(true))
+                    .arg(Arg::new("foreground").required(true)),
+            )
+            .subcommand(
+                clap::Command::new("clanbank")
+                    .subcommand(clap::Command::new("list"))
+                    .subcommand(clap::Command::new("log"))
+                    .subcommand(
+                        clap::Command::new("deposit")
+                            .arg(
+                                Arg::new("page")
+                                    .possible_values(INVENTORY_PAGE_NAMES)
+                                    .required(true),
+                            )
+                            .arg(Arg::new("slot").required(true))
+                            .arg(Arg::new("quantity").required(false)),
+                    )
+                    .subcommand(
+                        clap::Command::new("withdraw")
+                            .arg(Arg::new("slot").required(true))
+                            .arg(Arg::new("quantity").required(false)),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("clanwar")
+                    .subcommand(clap::Command::new("declare").arg(Arg::new("clan").required(true)))
+                    .subcommand(clap::Command::new("accept").arg(Arg::new("clan").required(true))),
+            )
+            .subcommand(clap::Command::new("storesearch").arg(Arg::new("name").required(true)))
+            .subcommand(
+                clap::Command::new("barbershop")
+                    .subcommand(
+                        clap::Command::new("preview")
+                            .arg(Arg::new("face").required(true))
+                            .arg(Arg::new("hair").required(true)),
+                    )
+                    .subcommand(clap::Command::new("confirm"))
+                    .subcommand(clap::Command::new("cancel")),
+            )
+            .subcommand(
+                clap::Command::new("inventory")
+                    .subcommand(clap::Command::new("sort"))
+                    .subcommand(
+                        clap::Command::new("split")
+                            .arg(
+                                Arg::new("page")
+                                    .possible_values(INVENTORY_PAGE_NAMES)
+                                    .required(true),
+                            )
+                            .arg(Arg::new("slot").required(true))
+                            .arg(Arg::new("quantity").required(true)),
+                    )
+                    .subcommand(
+                        clap::Command::new("move")
+                            .arg(
+                                Arg::new("page")
+                                    .possible_values(INVENTORY_PAGE_NAMES)
+                                    .required(true),
+                            )
+                            .arg(Arg::new("from").required(true))
+                            .arg(Arg::new("to").required(true)),
+                    ),
+            )
+            .subcommand(clap::Command::new("achievements"))
+            .subcommand(clap::Command::new("title").arg(Arg::new("id").required(true)))
+            .subcommand(clap::Command::new("statistics"))
+            .subcommand(clap::Command::new("rebirth"))
+            .subcommand(
+                clap::Command::new("leaderboards").arg(
+                    Arg::new("refresh")
+                        .value_parser([PossibleValue::new("refresh")])
+                        .required(false),
+                ),
+            )
+            .subcommand(
+                clap::Command::new("partymarker").arg(
+                    Arg::new("visible")
+                        .possible_values(["on", "off"])
+                        .required(true),
+                ),
+            )
+            .subcommand(
+                clap::Command::new("partyloot").arg(
+                    Arg::new("mode")
+                        .possible_values(["equal", "order", "leader"])
+                        .required(true),
+                ),
+            )
             .subcommand(
                 clap::Command::new("damage")
                     .arg(Arg::new("amount").required(true))
@@ -108,7 +264,9 @@ lazy_static! {
                     .arg(Arg::new("quantity").required(false))
                     .arg(Arg::new("socket").required(false))
                     .arg(Arg::new("gem").required(false))
-                    .arg(Arg::new("grade").required(false)),
+                    .arg(Arg::new("grade").required(false))
+                    .arg(Arg::new("durability").required(false))
+                    .arg(Arg::new("bound").required(false)),
             )
             .subcommand(
                 clap::Command::new("item")
@@ -117,7 +275,9 @@ lazy_static! {
                     .arg(Arg::new("quantity").required(false))
                     .arg(Arg::new("socket").required(false))
                     .arg(Arg::new("gem").required(false))
-                    .arg(Arg::new("grade").required(false)),
+                    .arg(Arg::new("grade").required(false))
+                    .arg(Arg::new("durability").required(false))
+                    .arg(Arg::new("bound").required(false)),
             )
             .subcommand(
                 clap::Command::new("mm")
@@ -125,6 +285,13 @@ lazy_static! {
                     .arg(Arg::new("x"))
                     .arg(Arg::new("y")),
             )
+            .subcommand(
+                clap::Command::new("gate")
+                    .arg(Arg::new("duration").required(true))
+                    .arg(Arg::new("zone").required(true))
+                    .arg(Arg::new("x"))
+                    .arg(Arg::new("y")),
+            )
             .subcommand(
                 clap::Command::new("mon")
                     .arg(Arg::new("id").required(true))
@@ -133,7 +300,36 @@ lazy_static! {
                     .arg(Arg::new("team").required(false)),
             )
             .subcommand(clap::Command::new("level").arg(Arg::new("level").required(true)))
-            .subcommand(clap::Command::new("bot").arg(Arg::new("n").required(true)))
+            .subcommand(
+                clap::Command::new("bot")
+                    .arg(Arg::new("n").required(true))
+                    .arg(
+                        Arg::new("profile")
+                            .possible_values(BotProfile::ALL.map(|profile| profile.name()))
+                            .required(false),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("botscenario")
+                    .subcommand(clap::Command::new("start").arg(Arg::new("name").required(true)))
+                    .subcommand(clap::Command::new("stop").arg(Arg::new("name").required(true)))
+                    .subcommand(clap::Command::new("list")),
+            )
+            .subcommand(clap::Command::new("botclear"))
+            .subcommand(
+                clap::Command::new("event")
+                    .subcommand(clap::Command::new("join"))
+                    .subcommand(clap::Command::new("leave"))
+                    .subcommand(clap::Command::new("start").arg(Arg::new("name").required(true)))
+                    .subcommand(clap::Command::new("stop"))
+                    .subcommand(clap::Command::new("list")),
+            )
+            .subcommand(
+                clap::Command::new("invasion")
+                    .subcommand(clap::Command::new("start").arg(Arg::new("name").required(true)))
+                    .subcommand(clap::Command::new("stop"))
+                    .subcommand(clap::Command::new("list")),
+            )
             .subcommand(
                 clap::Command::new("build")
                     .arg(Arg::new("name").required(true))
@@ -229,11 +425,84 @@ lazy_static! {
                                 "world_price",
                                 "item_price",
                                 "town_price",
+                                "store_buy",
+                                "store_sell",
                             ])
                             .required(true),
                     )
                     .arg(Arg::new("value").required(true)),
             )
+            .subcommand(clap::Command::new("storage"))
+            .subcommand(clap::Command::new("tick"))
+            .subcommand(clap::Command::new("zonesnapshot"))
+            .subcommand(
+                clap::Command::new("account")
+                    .subcommand(
+                        clap::Command::new("email")
+                            .arg(Arg::new("account").required(true))
+                            .arg(Arg::new("email").required(true)),
+                    )
+                    .subcommand(
+                        clap::Command::new("verify")
+                            .arg(Arg::new("account").required(true))
+                            .arg(Arg::new("token").required(true)),
+                    )
+                    .subcommand(clap::Command::new("reset").arg(Arg::new("account").required(true)))
+                    .subcommand(
+                        clap::Command::new("password")
+                            .arg(Arg::new("account").required(true))
+                            .arg(Arg::new("token").required(true))
+                            .arg(Arg::new("password").required(true)),
+                    )
+                    .subcommand(
+                        clap::Command::new("pin")
+                            .arg(Arg::new("account").required(true))
+                            .arg(Arg::new("pin").required(false)),
+                    ),
+            )
+            .subcommand(clap::Command::new("pin").arg(Arg::new("pin").required(false)))
+            .subcommand(clap::Command::new("language").arg(Arg::new("language").required(false)))
+            .subcommand(
+                clap::Command::new("character")
+                    .subcommand(
+                        clap::Command::new("search").arg(Arg::new("pattern").required(true)),
+                    )
+                    .subcommand(clap::Command::new("inspect").arg(Arg::new("name").required(true)))
+                    .subcommand(clap::Command::new("dump").arg(Arg::new("name").required(true)))
+                    .subcommand(
+                        clap::Command::new("find-item").arg(Arg::new("instance_id").required(true)),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("announce")
+                    .arg(Arg::new("text").required(true).multiple_values(true)),
+            )
+            .subcommand(
+                clap::Command::new("mute")
+                    .arg(Arg::new("name").required(true))
+                    .arg(Arg::new("minutes").required(true))
+                    .arg(Arg::new("reason").required(false).multiple_values(true)),
+            )
+            .subcommand(clap::Command::new("unmute").arg(Arg::new("name").required(true)))
+            .subcommand(clap::Command::new("mutes"))
+            .subcommand(
+                clap::Command::new("suspicion")
+                    .subcommand(clap::Command::new("show").arg(Arg::new("account").required(true)))
+                    .subcommand(
+                        clap::Command::new("clear").arg(Arg::new("account").required(true)),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("spectate")
+                    .subcommand(clap::Command::new("on"))
+                    .subcommand(clap::Command::new("off"))
+                    .subcommand(clap::Command::new("follow").arg(Arg::new("name").required(true))),
+            )
+            .subcommand(
+                clap::Command::new("maintenance")
+                    .arg(Arg::new("minutes").required(true))
+                    .arg(Arg::new("reason").required(false).multiple_values(true)),
+            )
     };
 }
 
@@ -249,6 +518,41 @@ fn send_multiline_whisper(client: &GameClient, str: &str) {
     }
 }
 
+/// Parses an inventory slot given as a page name and a 1 based slot number,
+/// counting across then down the page as shown by the client.
+fn parse_inventory_slot(page: &str, slot: &str) -> Result<ItemSlot, ChatCommandError> {
+    let page_type = match page {
+        "equipment" => InventoryPageType::Equipment,
+        "consumables" => InventoryPageType::Consumables,
+        "materials" => InventoryPageType::Materials,
+        "vehicles" => InventoryPageType::Vehicles,
+        _ => return Err(ChatCommandError::InvalidArguments),
+    };
+
+    let slot = slot.parse::<usize>()?;
+    if slot == 0 || slot > INVENTORY_PAGE_SIZE {
+        return Err(ChatCommandError::WithMessage(format!(
+            "Inventory slot must be between 1 and {}",
+            INVENTORY_PAGE_SIZE
+        )));
+    }
+
+    Ok(ItemSlot::Inventory(page_type, slot - 1))
+}
+
+/// Copies `item` with `quantity` of it, or all of it when no quantity is given.
+fn item_with_quantity(item: &Item, quantity: Option<&str>) -> Result<Item, ChatCommandError> {
+    let Some(quantity) = quantity else {
+        return Ok(item.clone());
+    };
+
+    item.clone()
+        .try_take_subquantity(quantity.parse::<u32>()?)
+        .ok_or_else(|| {
+            ChatCommandError::WithMessage(String::from("Cannot take that quantity from that slot"))
+        })
+}
+
 fn send_chat_commands_help(client: &GameClient) {
     for subcommand in CHAT_COMMANDS.get_subcommands() {
         let mut help_string = String::from(subcommand.get_name());
@@ -277,6 +581,120 @@ fn send_chat_commands_help(client: &GameClient) {
     }
 }
 
+fn get_user_account_name(
+    chat_command_params: &ChatCommandParams,
+    chat_command_user: &ChatCommandUserQueryItem,
+) -> Result<String, ChatCommandError> {
+    chat_command_params
+        .account_query
+        .get(chat_command_user.entity)
+        .map(|account| account.name.clone())
+        .map_err(|_| ChatCommandError::InvalidCommand)
+}
+
+fn check_gm_account(
+    chat_command_params: &ChatCommandParams,
+    chat_command_user: &ChatCommandUserQueryItem,
+) -> Result<(), ChatCommandError> {
+    let account_name = get_user_account_name(chat_command_params, chat_command_user)?;
+    if chat_command_params.game_config.is_gm_account(&account_name) {
+        Ok(())
+    } else {
+        Err(ChatCommandError::WithMessage(String::from(
+            "Only GM accounts can use this command",
+        )))
+    }
+}
+
+/// The irose client can not answer the secondary PIN request, so a player who
+/// sets a PIN would be locked out of character select. Only GM accounts can set
+/// a PIN, anyone can remove the PIN of their own account.
+fn check_secondary_pin_allowed(
+    chat_command_params: &ChatCommandParams,
+    chat_command_user: &ChatCommandUserQueryItem,
+) -> Result<(), ChatCommandError> {
+    check_gm_account(chat_command_params, chat_command_user).map_err(|_| {
+        ChatCommandError::WithMessage(String::from(
+            "Your client does not support a secondary PIN, only GM accounts can set one",
+        ))
+    })
+}
+
+/// Applies `update` to the account and saves it, keeping the Account component
+/// of any logged in client for the account in sync so it is not overwritten
+/// by a later save of the stale component.
+fn update_account<T>(
+    chat_command_params: &mut ChatCommandParams,
+    account_name: &str,
+    update: impl FnOnce(&mut AccountStorage) -> Result<T, AccountStorageError>,
+) -> Result<T, ChatCommandError> {
+    let storage_key = StorageKey::Account(account_name.to_string());
+    let mut account = if let Some(account) = chat_command_params
+        .account_query
+        .iter()
+        .find(|account| account.name == account_name)
+    {
+        AccountStorage::from(account)
+    } else if chat_command_params
+        .storage_service
+        .has_pending_write(&storage_key)
+    {
+        return Err(ChatCommandError::WithMessage(format!(
+            "Account {} has a queued save, try again later",
+            account_name
+        )));
+    } else {
+        AccountStorage::load(account_name).map_err(|error| {
+            ChatCommandError::WithMessage(format!(
+                "Failed to load account {}: {}",
+                account_name, error
+            ))
+        })?
+    };
+
+    let result =
+        update(&mut account).map_err(|error| ChatCommandError::WithMessage(error.to_string()))?;
+
+    for mut online_account in chat_command_params.account_query.iter_mut() {
+        if online_account.name == account_name {
+            *online_account = Account::from(account.clone());
+        }
+    }
+
+    chat_command_params
+        .storage_service
+        .write(storage_key, move || account.save())
+        .map_err(|error| {
+            ChatCommandError::WithMessage(format!(
+                "Failed to save account {}: {}",
+                account_name, error
+            ))
+        })?;
+    Ok(result)
+}
+
+/// Emails the token to the player, or writes it to the server log for the
+/// operator when no mail server is configured. The token is never sent over
+/// chat, as it must only reach the owner of the email address.
+fn send_account_token(
+    chat_command_params: &ChatCommandParams,
+    client: &GameClient,
+    email: &str,
+    subject: &str,
+    body: String,
+) {
+    if let Some(email_sender) = chat_command_params.email_sender.as_ref() {
+        email_sender.send(email.to_string(), subject.to_string(), body);
+        send_multiline_whisper(client, &format!("Sent \"{}\" to {}", subject, email));
+    } else {
+        log::info!("SMTP is not configured, {}", body);
+        send_multiline_whisper(
+            client,
+            "SMTP is not configured, ask the server operator for the token",
+        );
+    }
+}
+
 pub enum ChatCommandError {
     InvalidCommand,
     InvalidArguments,
@@ -310,136 +728,17 @@ impl From<ParseFloatError> for ChatCommandError {
     }
 }
 
-fn create_bot_entity(
-    chat_command_params: &mut ChatCommandParams,
-    name: String,
-    position: Position,
-    level: u32,
-) -> Option<Entity> {
-    let (bot_build, mut bot_data) =
-        bot_create_random_build(&chat_command_params.game_data, name, level);
-
-    let status_effects = StatusEffects::new();
-    let status_effects_regen = StatusEffectsRegen::new();
-
-    let ability_values = chat_command_params
-        .game_data
-        .ability_value_calculator
-        .calculate(
-            &bot_data.info,
-            &bot_data.level,
-            &bot_data.equipment,
-            &bot_data.basic_stats,
-            &bot_data.skill_list,
-            &status_effects,
-        );
-
-    let move_mode = MoveMode::Run;
-    let move_speed = MoveSpeed::new(ability_values.get_move_speed(&move_mode));
-
-    let weapon_motion_type = chat_command_params
-        .game_data
-        .items
-        .get_equipped_weapon_item_data(&bot_data.equipment, EquipmentIndex::Weapon)
-        .map(|item_data| item_data.motion_type)
-        .unwrap_or(0) as usize;
-
-    let motion_data = MotionData::from_character(
-        chat_command_params.game_data.motions.as_ref(),
-        weapon_motion_type,
-        bot_data.info.gender,
-    );
-
-    bot_data.position = position;
-    bot_data.health_points.hp = ability_values.get_max_health();
-    bot_data.mana_points.mp = ability_values.get_max_mana();
-
-    let entity = chat_command_params
-        .commands
-        .spawn((
-            bot_build,
-            bot_thinker(),
-            CharacterBundle {
-                ability_values,
-                basic_stats: bot_data.basic_stats,
-                bank: Default::default(),
-                cooldowns: Cooldowns::default(),
-                command: Command::default(),
-                damage_sources: DamageSources::default_character(),
-                equipment: bot_data.equipment,
-                experience_points: bot_data.experience_points,
-                health_points: bot_data.health_points,
-                hotbar: bot_data.hotbar,
-                info: bot_data.info,
-                inventory: bot_data.inventory,
-                level: bot_data.level,
-                mana_points: bot_data.mana_points,
-                motion_data,
-                move_mode,
-                move_speed,
-                next_command: NextCommand::default(),
-                party_membership: PartyMembership::default(),
-                passive_recovery_time: PassiveRecoveryTime::default(),
-                position: bot_data.position,
-                quest_state: bot_data.quest_state,
-                skill_list: bot_data.skill_list,
-                skill_points: bot_data.skill_points,
-                stamina: bot_data.stamina,
-                stat_points: bot_data.stat_points,
-                status_effects,
-                status_effects_regen,
-                team: Team::default_character(),
-                union_membership: bot_data.union_membership,
-                clan_membership: ClanMembership::default(),
-            },
-        ))
-        .id();
-
-    Some(entity)
-}
-
 fn create_random_bot_entities(
     chat_command_params: &mut ChatCommandParams,
     num_bots: usize,
     spacing: f32,
     origin: Position,
+    profile: BotProfile,
 ) -> Vec<Entity> {
     let mut rng = rand::thread_rng();
     let spawn_radius = f32::max(num_bots as f32 * spacing, 100.0);
     let mut bot_entities = Vec::new();
-
-    // Try to calculate an ideal bot level range based on the current zone
-    let mut bot_level_range = 1..=170;
-    if let Some(zone_data) = chat_command_params.game_data.zones.get_zone(origin.zone_id) {
-        // Find all monsters which spawn in this zone
-        let mut monster_ids = HashSet::new();
-        for spawn in zone_data.monster_spawns.iter() {
-            for npc_id in spawn.basic_spawns.iter().map(|(npc_id, _)| *npc_id) {
-                monster_ids.insert(npc_id);
-            }
-
-            for npc_id in spawn.tactic_spawns.iter().map(|(npc_id, _)| *npc_id) {
-                monster_ids.insert(npc_id);
-            }
-        }
-
-        // Create a sorted list of the levels of the monsters in this zone
-        let mut monster_levels: Vec<_> = monster_ids
-            .iter()
-            .filter_map(|npc_id| chat_command_params.game_data.npcs.get_npc(*npc_id))
-            .map(|npc_data| npc_data.level)
-            .collect();
-        monster_levels.sort();
-
-        // Calculate a bot level range based on the median monster level +/- 20%
-        if let Some(median_monster_level) = monster_levels.get((monster_levels.len() + 1) / 2) {
-            // Ensure delta_level is mininum of 2 to ensure at least some variation in bot levels
-            let delta_level = (*median_monster_level / 5).max(2);
-
-            bot_level_range = (median_monster_level - delta_level).max(1)
-                ..=(median_monster_level + delta_level).min(170);
-        }
-    }
+    let level_range = bot_level_range(&chat_command_params.game_data, origin.zone_id);
 
     for i in 0..num_bots {
         let angle = (i as f32 * (2.0 * PI)) / num_bots as f32;
@@ -447,17 +746,18 @@ fn create_random_bot_entities(
         bot_position.position.x += spawn_radius * angle.cos();
         bot_position.position.y += spawn_radius * angle.sin();
 
-        if let Some(bot_entity) = create_bot_entity(
-            chat_command_params,
+        let bot_entity = bot_spawn(
+            &mut chat_command_params.commands,
+            &chat_command_params.game_data,
             format!("Friend {}", chat_command_params.bot_list.len()),
             bot_position,
-            rng.gen_range::<i32, _>(bot_level_range.clone()) as u32,
-        ) {
-            chat_command_params
-                .bot_list
-                .push(BotListEntry::new(bot_entity));
-            bot_entities.push(bot_entity);
-        }
+            rng.gen_range(level_range.clone()),
+            profile,
+        );
+        chat_command_params
+            .bot_list
+            .push(BotListEntry::new(bot_entity));
+        bot_entities.push(bot_entity);
     }
 
     bot_entities
@@ -524,16 +824,437 @@ fn handle_chat_command(
                     ChatCommandError::WithMessage(format!("Invalid zone id {}", zone_id.get()))
                 })?;
 
-            client_entity_teleport_zone(
-                &mut chat_command_params.commands,
-                &mut chat_command_params.client_entity_list,
-                chat_command_user.entity,
-                chat_command_user.client_entity,
-                chat_command_user.client_entity_sector,
-                chat_command_user.position,
-                Position::new(Vec3::new(x, y, 0.0), zone_id),
-                Some(chat_command_user.game_client),
+            chat_command_params.teleport_events.send(TeleportEvent {
+                entity: chat_command_user.entity,
+                position: Position::new(Vec3::new(x, y, 0.0), zone_id),
+            });
+        }
+        ("gate", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let duration = arg_matches.value_of("duration").unwrap().parse::<u64>()?;
+            let zone_id = arg_matches.value_of("zone").unwrap().parse::<ZoneId>()?;
+            let (x, y) = if let (Some(x), Some(y)) =
+                (arg_matches.value_of("x"), arg_matches.value_of("y"))
+            {
+                (x.parse::<f32>()? * 1000.0, y.parse::<f32>()? * 1000.0)
+            } else if let Some(zone_data) = chat_command_params.game_data.zones.get_zone(zone_id) {
+                (zone_data.start_position.x, zone_data.start_position.y)
+            } else {
+                (520.0, 520.0)
+            };
+
+            let _zone = chat_command_params
+                .client_entity_list
+                .get_zone(zone_id)
+                .ok_or_else(|| {
+                    ChatCommandError::WithMessage(format!("Invalid zone id {}", zone_id.get()))
+                })?;
+
+            chat_command_params.commands.spawn((
+                TeleportGate::new(
+                    Position::new(Vec3::new(x, y, 0.0), zone_id),
+                    TELEPORT_GATE_RADIUS,
+                ),
+                chat_command_user.position.clone(),
+                EntityExpireTime::new(
+                    chat_command_params.time.last_update().unwrap() + Duration::from_secs(duration),
+                ),
+            ));
+        }
+        ("barbershop", arg_matches) => {
+            let entity = chat_command_user.entity;
+            let event = match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("preview", sub_matches) => {
+                    // The barber is the closest NPC, the barbershop system
+                    // checks it is close enough
+                    let npc_entity = chat_command_params
+                        .npc_query
+                        .iter()
+                        .filter(|(_, position)| {
+                            position.zone_id == chat_command_user.position.zone_id
+                        })
+                        .min_by(|(_, a), (_, b)| {
+                            let position = chat_command_user.position.position.xy();
+                            a.position
+                                .xy()
+                                .distance_squared(position)
+                                .total_cmp(&b.position.xy().distance_squared(position))
+                        })
+                        .map(|(npc_entity, _)| npc_entity)
+                        .ok_or_else(|| {
+                            ChatCommandError::WithMessage(String::from("There is no barber nearby"))
+                        })?;
+
+                    BarbershopEvent::Preview {
+                        entity,
+                        npc_entity,
+                        face: sub_matches.value_of("face").unwrap().parse::<u8>()?,
+                        hair: sub_matches.value_of("hair").unwrap().parse::<u8>()?,
+                    }
+                }
+                ("confirm", _) => BarbershopEvent::Confirm { entity },
+                ("cancel", _) => BarbershopEvent::Cancel { entity },
+                _ => return Err(ChatCommandError::InvalidArguments),
+            };
+            chat_command_params.barbershop_events.send(event);
+        }
+        ("clannotice", arg_matches) => {
+            let text: Vec<&str> = arg_matches
+                .values_of("text")
+                .map(|values| values.collect())
+                .unwrap_or_default();
+            chat_command_params
+                .clan_events
+                .send(ClanEvent::UpdateNotice {
+                    entity: chat_command_user.entity,
+                    notice: text.join(" "),
+                });
+        }
+        ("clandescription", arg_matches) => {
+            let text: Vec<&str> = arg_matches
+                .values_of("text")
+                .map(|values| values.collect())
+                .unwrap_or_default();
+            chat_command_params
+                .clan_events
+                .send(ClanEvent::UpdateDescription {
+                    entity: chat_command_user.entity,
+                    description: text.join(" "),
+                });
+        }
+        ("clanmark", arg_matches) => {
+            let background = arg_matches.value_of("background").unwrap().parse::<u16>()?;
+            let foreground = arg_matches.value_of("foreground").unwrap().parse::<u16>()?;
+            let mark = NonZeroU16::new(background)
+                .zip(NonZeroU16::new(foreground))
+                .map(|(background, foreground)| ClanMark::Premade {
+                    background,
+                    foreground,
+                })
+                .ok_or(ChatCommandError::InvalidArguments)?;
+            chat_command_params.clan_events.send(ClanEvent::UpdateMark {
+                entity: chat_command_user.entity,
+                mark,
+            });
+        }
+        ("clanbank", arg_matches) => {
+            let clan_bank = chat_command_user
+                .clan_membership
+                .clan()
+                .and_then(|clan_entity| chat_command_params.clan_bank_query.get(clan_entity).ok())
+                .ok_or_else(|| {
+                    ChatCommandError::WithMessage(String::from("You are not in a clan"))
+                })?;
+            let language = chat_command_params
+                .account_query
+                .get(chat_command_user.entity)
+                .ok()
+                .and_then(|account| account.language);
+            let entity = chat_command_user.entity;
+
+            match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("list", _) => {
+                    let mut text = String::from("Clan bank:");
+                    for (index, item) in clan_bank.slots.iter().enumerate() {
+                        if let Some(item) = item {
+                            text += &format!(
+                                "\n{}: {} x{}",
+                                index + 1,
+                                chat_command_params
+                                    .game_data
+                                    .get_item_name(item.get_item_reference(), language),
+                                item.get_quantity()
+                            );
+                        }
+                    }
+                    send_multiline_whisper(chat_command_user.game_client, &text);
+                }
+                ("log", _) => {
+                    let mut text = String::from("Clan bank log:");
+                    for entry in clan_bank.log.iter() {
+                        text += &format!(
+                            "\n{} {} {} {} x{}",
+                            chrono::DateTime::from_timestamp(entry.timestamp, 0)
+                                .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_default(),
+                            entry.name,
+                            match entry.action {
+                                ClanBankAction::Deposit => "deposited",
+                                ClanBankAction::Withdraw => "withdrew",
+                            },
+                            chat_command_params
+                                .game_data
+                                .get_item_name(entry.item.get_item_reference(), language),
+                            entry.item.get_quantity()
+                        );
+                    }
+                    send_multiline_whisper(chat_command_user.game_client, &text);
+                }
+                ("deposit", sub_matches) => {
+                    let item_slot = parse_inventory_slot(
+                        sub_matches.value_of("page").unwrap(),
+                        sub_matches.value_of("slot").unwrap(),
+                    )?;
+                    let item =
+                        chat_command_user
+                            .inventory
+                            .get_item(item_slot)
+                            .ok_or_else(|| {
+                                ChatCommandError::WithMessage(String::from("That slot is empty"))
+                            })?;
+
+                    chat_command_params
+                        .clan_bank_events
+                        .send(ClanBankEvent::DepositItem {
+                            entity,
+                            item_slot,
+                            item: item_with_quantity(item, sub_matches.value_of("quantity"))?,
+                        });
+                }
+                ("withdraw", sub_matches) => {
+                    let bank_slot = sub_matches.value_of("slot").unwrap().parse::<usize>()?;
+                    let item = bank_slot
+                        .checked_sub(1)
+                        .and_then(|index| clan_bank.slots.get(index))
+                        .and_then(|item| item.as_ref())
+                        .ok_or_else(|| {
+                            ChatCommandError::WithMessage(String::from("That slot is empty"))
+                        })?;
+
+                    chat_command_params
+                        .clan_bank_events
+                        .send(ClanBankEvent::WithdrawItem {
+                            entity,
+                            bank_slot: bank_slot - 1,
+                            item: item_with_quantity(item, sub_matches.value_of("quantity"))?,
+                        });
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
+        ("clanwar", arg_matches) => {
+            let entity = chat_command_user.entity;
+            let event = match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("declare", sub_matches) => ClanEvent::DeclareWar {
+                    declarer: entity,
+                    clan_name: sub_matches.value_of("clan").unwrap().to_string(),
+                },
+                ("accept", sub_matches) => ClanEvent::AcceptWar {
+                    accepter: entity,
+                    clan_name: sub_matches.value_of("clan").unwrap().to_string(),
+                },
+                _ => return Err(ChatCommandError::InvalidArguments),
+            };
+            chat_command_params.clan_events.send(event);
+        }
+        ("storesearch", arg_matches) => {
+            chat_command_params
+                .personal_store_events
+                .send(PersonalStoreEvent::Search {
+                    entity: chat_command_user.entity,
+                    item_type: None,
+                    item_name: Some(arg_matches.value_of("name").unwrap().to_string()),
+                });
+        }
+        ("inventory", arg_matches) => {
+            let entity = chat_command_user.entity;
+            let event = match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("sort", _) => InventoryEvent::Sort { entity },
+                ("split", sub_matches) => InventoryEvent::SplitItem {
+                    entity,
+                    item_slot: parse_inventory_slot(
+                        sub_matches.value_of("page").unwrap(),
+                        sub_matches.value_of("slot").unwrap(),
+                    )?,
+                    quantity: sub_matches.value_of("quantity").unwrap().parse::<u32>()?,
+                },
+                ("move", sub_matches) => {
+                    let page = sub_matches.value_of("page").unwrap();
+                    InventoryEvent::MoveItem {
+                        entity,
+                        from_slot: parse_inventory_slot(
+                            page,
+                            sub_matches.value_of("from").unwrap(),
+                        )?,
+                        to_slot: parse_inventory_slot(page, sub_matches.value_of("to").unwrap())?,
+                    }
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            };
+            chat_command_params.inventory_events.send(event);
+        }
+        ("dailyreward", _) => {
+            chat_command_params
+                .reward_calendar_events
+                .send(RewardCalendarEvent::Claim {
+                    entity: chat_command_user.entity,
+                });
+        }
+        ("achievements", _) => {
+            let achievements = chat_command_user.achievements;
+            for achievement in chat_command_params
+                .game_config
+                .achievements
+                .achievements
+                .iter()
+            {
+                let status = if achievements.is_unlocked(&achievement.id) {
+                    String::from("unlocked")
+                } else {
+                    format!(
+                        "{}/{}",
+                        achievements.get_progress(&achievement.id),
+                        achievement.goal.get_required_progress()
+                    )
+                };
+                let title = achievement
+                    .title
+                    .as_ref()
+                    .map(|title| format!(" title: {}", title.name))
+                    .unwrap_or_default();
+                send_multiline_whisper(
+                    chat_command_user.game_client,
+                    &format!(
+                        "{} {}: {}{}",
+                        achievement.id, achievement.name, status, title
+                    ),
+                );
+            }
+
+            let equipped_title = achievements
+                .equipped_title
+                .as_deref()
+                .and_then(|id| chat_command_params.game_config.achievements.get_title(id))
+                .map_or("none", |title| title.name.as_str());
+            send_multiline_whisper(
+                chat_command_user.game_client,
+                &format!("Equipped title: {}", equipped_title),
+            );
+        }
+        ("title", arg_matches) => {
+            let id = arg_matches.value_of("id").unwrap();
+            chat_command_params
+                .achievement_events
+                .send(AchievementEvent::EquipTitle {
+                    entity: chat_command_user.entity,
+                    achievement_id: if id == "off" {
+                        None
+                    } else {
+                        Some(id.to_string())
+                    },
+                });
+        }
+        ("rebirth", _) => {
+            chat_command_params.rebirth_events.send(RebirthEvent {
+                entity: chat_command_user.entity,
+            });
+        }
+        ("statistics", _) => {
+            let statistics = chat_command_user.statistics;
+            let mut text = format!(
+                "monster kills: {} pvp kills: {} deaths: {}\nplaytime: {}h {}m session: {}h {}m longest session: {}h {}m\nzuly earned: {}",
+                statistics.total_monster_kills(),
+                statistics.pvp_kills,
+                statistics.deaths,
+                statistics.playtime_secs / 3600,
+                (statistics.playtime_secs / 60) % 60,
+                statistics.session_playtime_secs / 3600,
+                (statistics.session_playtime_secs / 60) % 60,
+                statistics.longest_session_playtime_secs / 3600,
+                (statistics.longest_session_playtime_secs / 60) % 60,
+                statistics.money_earned,
             );
+
+            let mut monster_kills: Vec<(u16, u32)> = statistics
+                .monster_kills
+                .iter()
+                .map(|(&npc_id, &kills)| (npc_id, kills))
+                .collect();
+            monster_kills.sort_by_key(|&(npc_id, kills)| (std::cmp::Reverse(kills), npc_id));
+            for (npc_id, kills) in monster_kills.into_iter().take(5) {
+                let name = NpcId::new(npc_id)
+                    .and_then(|npc_id| chat_command_params.game_data.npcs.get_npc(npc_id))
+                    .map_or("?", |npc_data| npc_data.name.as_ref());
+                text += &format!("\n  {} ({}): {}", name, npc_id, kills);
+            }
+            send_multiline_whisper(chat_command_user.game_client, &text);
+        }
+        ("leaderboards", arg_matches) => {
+            // Refreshing reads every stored character, viewing is public
+            if arg_matches.is_present("refresh") {
+                check_gm_account(chat_command_params, chat_command_user)?;
+            }
+            let Some(leaderboard_cache) = chat_command_params.leaderboard_cache.as_mut() else {
+                return Err(ChatCommandError::WithMessage(String::from(
+                    "Leaderboards are disabled",
+                )));
+            };
+
+            if arg_matches.is_present("refresh") {
+                leaderboard_cache.refresh_now(chat_command_params.time.last_update().unwrap());
+                send_multiline_whisper(
+                    chat_command_user.game_client,
+                    "Leaderboards will be refreshed",
+                );
+                return Ok(());
+            }
+
+            match leaderboard_cache.get() {
+                Some(leaderboards) => {
+                    send_multiline_whisper(chat_command_user.game_client, &leaderboards.to_string())
+                }
+                None => send_multiline_whisper(
+                    chat_command_user.game_client,
+                    "Leaderboards have not been built yet",
+                ),
+            }
+        }
+        ("partymarker", arg_matches) => {
+            let mut entity_commands = chat_command_params
+                .commands
+                .entity(chat_command_user.entity);
+            if arg_matches.value_of("visible") == Some("on") {
+                entity_commands.remove::<PartyMapMarkerHidden>();
+            } else {
+                entity_commands.insert(PartyMapMarkerHidden);
+            }
+        }
+        ("partyloot", arg_matches) => {
+            let party = chat_command_user
+                .party_membership
+                .party
+                .and_then(|party_entity| chat_command_params.party_query.get(party_entity).ok())
+                .ok_or_else(|| ChatCommandError::WithMessage("You are not in a party".into()))?;
+            if party.owner != chat_command_user.entity {
+                return Err(ChatCommandError::WithMessage(
+                    "Only the party owner can change how items are shared".into(),
+                ));
+            }
+
+            let item_sharing = match arg_matches.value_of("mode") {
+                Some("order") => PartyItemSharing::AcquisitionOrder,
+                Some("leader") => PartyItemSharing::PartyLeader,
+                _ => PartyItemSharing::EqualLootDistribution,
+            };
+            chat_command_params
+                .party_events
+                .send(PartyEvent::UpdateRules {
+                    owner_entity: chat_command_user.entity,
+                    item_sharing,
+                    xp_sharing: party.xp_sharing,
+                });
         }
         ("ability_values", _) => {
             send_multiline_whisper(
@@ -548,9 +1269,15 @@ fn handle_chat_command(
 
             for level in current_level..target_level {
                 required_xp += chat_command_params
-                    .game_data
-                    .ability_value_calculator
-                    .calculate_levelup_require_xp(level);
+                    .game_config
+                    .level_up
+                    .get_required_xp(level)
+                    .unwrap_or_else(|| {
+                        chat_command_params
+                            .game_data
+                            .ability_value_calculator
+                            .calculate_levelup_require_xp(level)
+                    });
             }
 
             chat_command_params
@@ -564,14 +1291,167 @@ fn handle_chat_command(
         }
         ("bot", arg_matches) => {
             let num_bots = arg_matches.value_of("n").unwrap().parse::<usize>()?;
+            let profile = arg_matches
+                .value_of("profile")
+                .and_then(BotProfile::from_name)
+                .unwrap_or(BotProfile::Farmer);
 
             create_random_bot_entities(
                 chat_command_params,
                 num_bots,
                 15.0,
                 chat_command_user.position.clone(),
+                profile,
             );
         }
+        ("botscenario", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let (scenario_command, sub_matches) = arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?;
+
+            match scenario_command {
+                "start" => {
+                    chat_command_params
+                        .bot_scenario_events
+                        .send(BotScenarioEvent::Start {
+                            name: sub_matches.value_of("name").unwrap().to_string(),
+                            entity: Some(chat_command_user.entity),
+                        });
+                }
+                "stop" => {
+                    chat_command_params
+                        .bot_scenario_events
+                        .send(BotScenarioEvent::Stop {
+                            name: sub_matches.value_of("name").unwrap().to_string(),
+                            entity: Some(chat_command_user.entity),
+                        });
+                }
+                "list" => {
+                    let mut text = String::from("Bot scenarios:");
+                    for scenario in chat_command_params
+                        .game_config
+                        .bot_scenarios
+                        .scenarios
+                        .iter()
+                    {
+                        let num_running = chat_command_params
+                            .bot_list
+                            .iter()
+                            .filter(|bot| bot.scenario.as_ref() == Some(&scenario.name))
+                            .count();
+                        text += &format!(
+                            "\n{}: {} bots, {} running",
+                            scenario.name, scenario.count, num_running
+                        );
+                    }
+                    send_multiline_whisper(chat_command_user.game_client, &text);
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
+        ("botclear", _) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            chat_command_params
+                .bot_scenario_events
+                .send(BotScenarioEvent::Clear {
+                    entity: Some(chat_command_user.entity),
+                });
+        }
+        ("event", arg_matches) => {
+            let (event_command, sub_matches) = arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?;
+            let entity = chat_command_user.entity;
+
+            match event_command {
+                "join" => {
+                    chat_command_params
+                        .event_zone_events
+                        .send(EventZoneEvent::Join { entity });
+                }
+                "leave" => {
+                    chat_command_params
+                        .event_zone_events
+                        .send(EventZoneEvent::Leave { entity });
+                }
+                "start" => {
+                    check_gm_account(chat_command_params, chat_command_user)?;
+                    chat_command_params
+                        .event_zone_events
+                        .send(EventZoneEvent::Start {
+                            name: sub_matches.value_of("name").unwrap().to_string(),
+                            entity: Some(entity),
+                        });
+                }
+                "stop" => {
+                    check_gm_account(chat_command_params, chat_command_user)?;
+                    chat_command_params
+                        .event_zone_events
+                        .send(EventZoneEvent::Stop {
+                            entity: Some(entity),
+                        });
+                }
+                "list" => {
+                    let mut text = String::from("Events:");
+                    for event_zone in chat_command_params.game_config.event_zones.events.iter() {
+                        text += &format!(
+                            "\n{}: {}, {} teams, first to {}",
+                            event_zone.name,
+                            event_zone.mode.name(),
+                            event_zone.teams.len(),
+                            event_zone.score_to_win
+                        );
+                        if let Some(interval_mins) = event_zone.interval_mins {
+                            text += &format!(", every {} minutes", interval_mins);
+                        }
+                    }
+                    send_multiline_whisper(chat_command_user.game_client, &text);
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
+        ("invasion", arg_matches) => {
+            let (invasion_command, sub_matches) = arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?;
+
+            match invasion_command {
+                "start" => {
+                    check_gm_account(chat_command_params, chat_command_user)?;
+                    chat_command_params
+                        .invasion_events
+                        .send(InvasionEvent::Start {
+                            name: sub_matches.value_of("name").unwrap().to_string(),
+                            entity: Some(chat_command_user.entity),
+                        });
+                }
+                "stop" => {
+                    check_gm_account(chat_command_params, chat_command_user)?;
+                    chat_command_params
+                        .invasion_events
+                        .send(InvasionEvent::Stop {
+                            entity: Some(chat_command_user.entity),
+                        });
+                }
+                "list" => {
+                    let mut text = String::from("Invasions:");
+                    for invasion in chat_command_params.game_config.invasions.invasions.iter() {
+                        text += &format!(
+                            "\n{}: zone {}, {} waves",
+                            invasion.name,
+                            invasion.zone.get(),
+                            invasion.waves.len()
+                        );
+                        if let Some(interval_mins) = invasion.interval_mins {
+                            text += &format!(", every {} minutes", interval_mins);
+                        }
+                    }
+                    send_multiline_whisper(chat_command_user.game_client, &text);
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
         ("build", arg_matches) => {
             let name = arg_matches.value_of("name").unwrap();
             let bot_build = match name {
@@ -704,6 +1584,7 @@ fn handle_chat_command(
                 num_bots,
                 30.0,
                 chat_command_user.position.clone(),
+                BotProfile::Farmer,
             );
 
             for entity in bot_entities.into_iter() {
@@ -751,6 +1632,7 @@ fn handle_chat_command(
                 num_bots,
                 30.0,
                 chat_command_user.position.clone(),
+                BotProfile::Farmer,
             );
             let mut index = 0usize;
 
@@ -901,6 +1783,9 @@ fn handle_chat_command(
                     passive_attack_speed: chat_command_user
                         .ability_values
                         .get_passive_attack_speed(),
+                    weight_rate: chat_command_user
+                        .weight
+                        .map_or(0, |weight| weight.get_client_weight_rate()),
                 },
             );
         }
@@ -963,6 +1848,9 @@ fn handle_chat_command(
                 apply_hit_stun: arg_matches
                     .value_of("type")
                     .map_or(false, |str| str == "hit"),
+                is_blocked: arg_matches
+                    .value_of("type")
+                    .map_or(false, |str| str == "block"),
             };
 
             if let Some(client_entity_zone) = chat_command_params
@@ -1014,64 +1902,56 @@ fn handle_chat_command(
                     team.clone(),
                     None,
                     None,
+                    None,
                 );
             }
         }
         ("item", arg_matches) | ("drop", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
             let is_drop = command_matches.subcommand().unwrap().0 == "drop";
 
-            let item_type_id = arg_matches.value_of("type").unwrap().parse::<usize>()?;
-            let item_type: ItemType = chat_command_params
-                .game_data
-                .data_decoder
-                .decode_item_type(item_type_id)
-                .ok_or_else(|| {
-                    ChatCommandError::WithMessage(format!("Invalid item type {}", item_type_id))
-                })?;
-
-            let item_number = arg_matches.value_of("id").unwrap().parse::<usize>()?;
-
-            let quantity = arg_matches
-                .value_of("quantity")
-                .and_then(|str| str.parse::<u32>().ok())
-                .unwrap_or(1);
-
-            let has_socket = arg_matches
-                .value_of("socket")
-                .and_then(|str| str.parse::<u8>().ok())
-                .unwrap_or(0)
-                != 0;
-
-            let gem = arg_matches
-                .value_of("gem")
-                .and_then(|str| str.parse::<u16>().ok())
-                .unwrap_or(0);
-
-            let grade = arg_matches
-                .value_of("grade")
-                .and_then(|str| str.parse::<u8>().ok())
-                .unwrap_or(0);
-
-            let item_reference = ItemReference::new(item_type, item_number);
-            let item_data = chat_command_params
-                .game_data
-                .items
-                .get_base_item(item_reference)
-                .ok_or_else(|| {
-                    ChatCommandError::WithMessage(format!("Invalid item {:?}", item_reference))
-                })?;
-
-            let mut item = Item::from_item_data(item_data, quantity)
-                .ok_or(ChatCommandError::InvalidArguments)?;
+            let item_spawn = ItemSpawn {
+                item_type: arg_matches.value_of("type").unwrap().parse::<usize>()?,
+                item_number: arg_matches.value_of("id").unwrap().parse::<usize>()?,
+                quantity: arg_matches
+                    .value_of("quantity")
+                    .map(|str| str.parse::<u32>())
+                    .transpose()?
+                    .unwrap_or(1),
+                has_socket: arg_matches
+                    .value_of("socket")
+                    .map(|str| str.parse::<u8>())
+                    .transpose()?
+                    .unwrap_or(0)
+                    != 0,
+                gem: arg_matches
+                    .value_of("gem")
+                    .map(|str| str.parse::<u16>())
+                    .transpose()?
+                    .unwrap_or(0),
+                grade: arg_matches
+                    .value_of("grade")
+                    .map(|str| str.parse::<u8>())
+                    .transpose()?
+                    .unwrap_or(0),
+                durability: arg_matches
+                    .value_of("durability")
+                    .map(|str| str.parse::<u8>())
+                    .transpose()?,
+                is_bound: arg_matches
+                    .value_of("bound")
+                    .map(|str| str.parse::<u8>())
+                    .transpose()?
+                    .unwrap_or(0)
+                    != 0,
+            };
 
-            match &mut item {
-                Item::Equipment(equipment_item) => {
-                    equipment_item.has_socket = has_socket;
-                    equipment_item.gem = gem;
-                    equipment_item.grade = grade;
-                }
-                Item::Stackable(_) => {}
-            }
+            let item = item_spawn
+                .create_item(
+                    &chat_command_params.game_data,
+                    &chat_command_user.character_info.name,
+                )
+                .map_err(|error| ChatCommandError::WithMessage(error.to_string()))?;
 
             if is_drop {
                 ItemDropBundle::spawn(
@@ -1080,7 +1960,6 @@ fn handle_chat_command(
                     DroppedItem::Item(item),
                     chat_command_user.position,
                     None,
-                    None,
                     &chat_command_params.time,
                 );
             } else {
@@ -1182,9 +2061,10 @@ fn handle_chat_command(
             }
         }
         ("rate", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
             let rate_type = arg_matches.value_of("type").unwrap();
             let value = arg_matches.value_of("value").unwrap().parse::<i32>()?;
-            
+
             match rate_type {
                 "xp" => chat_command_params.world_rates.xp_rate = value,
                 "drop" => chat_command_params.world_rates.drop_rate = value,
@@ -1195,6 +2075,8 @@ fn handle_chat_command(
                 "world_price" => chat_command_params.world_rates.world_price_rate = value,
                 "item_price" => chat_command_params.world_rates.item_price_rate = value,
                 "town_price" => chat_command_params.world_rates.town_price_rate = value,
+                "store_buy" => chat_command_params.world_rates.npc_store_buy_rate = value,
+                "store_sell" => chat_command_params.world_rates.npc_store_sell_rate = value,
                 _ => return Err(ChatCommandError::InvalidArguments),
             }
 
@@ -1207,6 +2089,421 @@ fn handle_chat_command(
                 })
                 .ok();
         }
+        ("storage", _) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let storage_service = &chat_command_params.storage_service;
+            let mut status = format!(
+                "storage: {:?} pending writes: {} consecutive failures: {}",
+                storage_service.health(),
+                storage_service.num_pending_writes(),
+                storage_service.consecutive_failures(),
+            );
+            if let Some(time_until_retry) = storage_service.time_until_retry(Instant::now()) {
+                status += &format!("\nnext retry in: {:.1}s", time_until_retry.as_secs_f32());
+            }
+            if let Some(last_error) = storage_service.last_error() {
+                status += &format!("\nlast error: {}", last_error);
+            }
+            if let Some(time_until_backup) = storage_service.time_until_backup(Instant::now()) {
+                status += &format!("\nnext backup in: {}s", time_until_backup.as_secs());
+            }
+            if let Some(last_backup) = storage_service.last_backup() {
+                status += &format!("\nlast backup: {}", last_backup.to_string_lossy());
+            }
+            send_multiline_whisper(chat_command_user.game_client, &status);
+        }
+        ("account", arg_matches) => {
+            let (account_command, sub_matches) = arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?;
+            let account_name = sub_matches.value_of("account").unwrap();
+            let now = chrono::Utc::now();
+
+            // Players can only change their own account
+            let user_account_name = get_user_account_name(chat_command_params, chat_command_user)?;
+            if !account_name.eq_ignore_ascii_case(&user_account_name)
+                && !chat_command_params
+                    .game_config
+                    .is_gm_account(&user_account_name)
+            {
+                return Err(ChatCommandError::WithMessage(String::from(
+                    "Only GM accounts can change another account",
+                )));
+            }
+
+            match account_command {
+                "email" => {
+                    let email = sub_matches.value_of("email").unwrap();
+                    let token = update_account(chat_command_params, account_name, |account| {
+                        account.set_email(email, now)
+                    })?;
+                    send_account_token(
+                        chat_command_params,
+                        chat_command_user.game_client,
+                        email,
+                        "Verify your email address",
+                        format!(
+                            "the email verification token for account {} is {}",
+                            account_name, token
+                        ),
+                    );
+                }
+                "verify" => {
+                    let token = sub_matches.value_of("token").unwrap();
+                    update_account(chat_command_params, account_name, |account| {
+                        account.verify_email(token, now)
+                    })?;
+                }
+                "reset" => {
+                    let (email, token) =
+                        update_account(chat_command_params, account_name, |account| {
+                            let token = account.create_password_reset_token(now)?;
+                            Ok((account.email.clone().unwrap_or_default(), token))
+                        })?;
+                    send_account_token(
+                        chat_command_params,
+                        chat_command_user.game_client,
+                        &email,
+                        "Reset your password",
+                        format!(
+                            "the password reset token for account {} is {}",
+                            account_name, token
+                        ),
+                    );
+                }
+                "password" => {
+                    let token = sub_matches.value_of("token").unwrap();
+                    let password =
+                        Password::Plaintext(sub_matches.value_of("password").unwrap().to_string());
+                    update_account(chat_command_params, account_name, |account| {
+                        account.reset_password(token, &password, now)
+                    })?;
+                }
+                "pin" => {
+                    // Without a PIN this removes the PIN and any lockout
+                    let pin = sub_matches.value_of("pin");
+                    if pin.is_some() {
+                        check_secondary_pin_allowed(chat_command_params, chat_command_user)?;
+                    }
+                    update_account(chat_command_params, account_name, |account| {
+                        account.set_secondary_pin(pin)
+                    })?;
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
+        ("language", arg_matches) => {
+            let (account_name, current_language) = chat_command_params
+                .account_query
+                .get(chat_command_user.entity)
+                .map(|account| (account.name.clone(), account.language))
+                .map_err(|_| ChatCommandError::InvalidCommand)?;
+            let string_database = &chat_command_params.game_data.string_database;
+
+            let Some(language) = arg_matches.value_of("language") else {
+                let languages: Vec<String> = string_database
+                    .languages
+                    .iter()
+                    .map(|language| language.to_string())
+                    .collect();
+                send_multiline_whisper(
+                    chat_command_user.game_client,
+                    &format!(
+                        "language: {} server language: {} available: {}",
+                        current_language.map_or_else(
+                            || String::from("default"),
+                            |language| language.to_string()
+                        ),
+                        string_database.language,
+                        languages.join(", ")
+                    ),
+                );
+                return Ok(());
+            };
+
+            let language = if language == "default" {
+                None
+            } else {
+                let language = language.parse::<usize>()?;
+                if !string_database.languages.contains(&language) {
+                    return Err(ChatCommandError::WithMessage(format!(
+                        "Language {} is not available",
+                        language
+                    )));
+                }
+                Some(language)
+            };
+            update_account(chat_command_params, &account_name, |account| {
+                account.language = language;
+                Ok(())
+            })?;
+        }
+        ("pin", arg_matches) => {
+            let account_name = get_user_account_name(chat_command_params, chat_command_user)?;
+            let pin = arg_matches.value_of("pin");
+            if pin.is_some() {
+                check_secondary_pin_allowed(chat_command_params, chat_command_user)?;
+            }
+            update_account(chat_command_params, &account_name, |account| {
+                account.set_secondary_pin(pin)
+            })?;
+        }
+        ("character", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let entity = chat_command_user.entity;
+            let event = match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("search", sub_matches) => CharacterInspectEvent::Search {
+                    entity,
+                    pattern: sub_matches.value_of("pattern").unwrap().to_string(),
+                },
+                ("inspect", sub_matches) => CharacterInspectEvent::Inspect {
+                    entity,
+                    name: sub_matches.value_of("name").unwrap().to_string(),
+                },
+                ("dump", sub_matches) => CharacterInspectEvent::Dump {
+                    entity,
+                    name: sub_matches.value_of("name").unwrap().to_string(),
+                },
+                ("find-item", sub_matches) => CharacterInspectEvent::FindItem {
+                    entity,
+                    instance_id: sub_matches
+                        .value_of("instance_id")
+                        .unwrap()
+                        .parse::<u64>()?,
+                },
+                _ => return Err(ChatCommandError::InvalidArguments),
+            };
+            chat_command_params.character_inspect_events.send(event);
+        }
+        ("announce", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let text: Vec<&str> = arg_matches.values_of("text").unwrap().collect();
+            chat_command_params.chat_events.send(ChatEvent {
+                entity: chat_command_user.entity,
+                channel: ChatChannel::Announce,
+                text: text.join(" "),
+            });
+        }
+        ("mute", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let name = arg_matches.value_of("name").unwrap();
+            let minutes = arg_matches.value_of("minutes").unwrap().parse::<i64>()?;
+            let reason = arg_matches
+                .values_of("reason")
+                .map(|reason| reason.collect::<Vec<&str>>().join(" "))
+                .unwrap_or_else(|| String::from("No reason given"));
+            let character_name = CharacterStorage::try_load(name)
+                .map(|character| character.info.name)
+                .map_err(|_| {
+                    ChatCommandError::WithMessage(format!("Character {} not found", name))
+                })?;
+
+            let mute = ChatMuteStorage {
+                character_name,
+                until: chrono::Utc::now() + chrono::Duration::minutes(minutes.max(1)),
+                reason,
+                muted_by: Some(chat_command_user.character_info.name.clone()),
+            };
+            send_multiline_whisper(chat_command_user.game_client, &format_chat_mute(&mute));
+            chat_command_params.chat_moderation.mute(mute.clone());
+            save_chat_mute(&mut chat_command_params.storage_service, mute);
+        }
+        ("unmute", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let name = arg_matches.value_of("name").unwrap();
+            if let Some(mute) = chat_command_params.chat_moderation.unmute(name) {
+                delete_chat_mute(
+                    &mut chat_command_params.storage_service,
+                    &mute.character_name,
+                );
+                send_multiline_whisper(
+                    chat_command_user.game_client,
+                    &format!("Unmuted {}", mute.character_name),
+                );
+            } else {
+                send_multiline_whisper(
+                    chat_command_user.game_client,
+                    &format!("{} is not muted", name),
+                );
+            }
+        }
+        ("mutes", _) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let mutes = chat_command_params
+                .chat_moderation
+                .get_active_mutes(chrono::Utc::now());
+            send_multiline_whisper(
+                chat_command_user.game_client,
+                &format!("{} muted characters", mutes.len()),
+            );
+            for mute in mutes {
+                send_multiline_whisper(chat_command_user.game_client, &format_chat_mute(mute));
+            }
+        }
+        ("suspicion", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("show", arg_matches) => {
+                    let account_name = arg_matches.value_of("account").unwrap();
+                    let suspicion = chat_command_params
+                        .cheat_detection
+                        .get_account(account_name);
+                    send_multiline_whisper(
+                        chat_command_user.game_client,
+                        &format!(
+                            "Account {} has a suspicion score of {} and was kicked {} times",
+                            account_name, suspicion.score, suspicion.kicks
+                        ),
+                    );
+                    if let Some(last_signal) = suspicion.last_signal {
+                        send_multiline_whisper(
+                            chat_command_user.game_client,
+                            &format!("Last signal at {}", last_signal.format("%Y-%m-%d %H:%M:%S")),
+                        );
+                    }
+                    for (signal, count) in suspicion.signals.iter() {
+                        send_multiline_whisper(
+                            chat_command_user.game_client,
+                            &format!("{:?}: {}", signal, count),
+                        );
+                    }
+                }
+                ("clear", arg_matches) => {
+                    let account_name = arg_matches.value_of("account").unwrap();
+                    let suspicion = chat_command_params
+                        .cheat_detection
+                        .clear_account(account_name);
+                    save_suspicion(
+                        &mut chat_command_params.storage_service,
+                        account_name.to_string(),
+                        suspicion,
+                    );
+                    log::warn!(
+                        target: "audit",
+                        "{} cleared the suspicion score of account {}",
+                        chat_command_user.character_info.name,
+                        account_name
+                    );
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
+        ("spectate", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let mut entity_commands = chat_command_params
+                .commands
+                .entity(chat_command_user.entity);
+            match arg_matches
+                .subcommand()
+                .ok_or(ChatCommandError::InvalidArguments)?
+            {
+                ("on", _) => {
+                    entity_commands.insert(Spectator::default());
+                    send_multiline_whisper(
+                        chat_command_user.game_client,
+                        "You are now hidden from other players and monsters",
+                    );
+                }
+                ("off", _) => {
+                    entity_commands.remove::<Spectator>();
+                    send_multiline_whisper(
+                        chat_command_user.game_client,
+                        "You are now visible to other players and monsters",
+                    );
+                }
+                ("follow", sub_matches) => {
+                    let name = sub_matches.value_of("name").unwrap();
+                    entity_commands.insert(Spectator {
+                        follow_character: Some(name.to_string()),
+                    });
+                    send_multiline_whisper(
+                        chat_command_user.game_client,
+                        &format!("You are now hidden and following {}", name),
+                    );
+                }
+                _ => return Err(ChatCommandError::InvalidArguments),
+            }
+        }
+        ("maintenance", arg_matches) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let now = chat_command_params.time.last_update().unwrap();
+            let minutes = arg_matches.value_of("minutes").unwrap();
+            if minutes == "cancel" {
+                let message = if chat_command_params.maintenance.cancel() {
+                    "Server maintenance cancelled"
+                } else {
+                    "Server maintenance is not scheduled"
+                };
+                send_multiline_whisper(chat_command_user.game_client, message);
+                return Ok(());
+            }
+
+            let minutes = minutes.parse::<u64>()?;
+            let reason = arg_matches
+                .values_of("reason")
+                .map(|reason| reason.collect::<Vec<&str>>().join(" "))
+                .unwrap_or_default();
+            if !chat_command_params.maintenance.start(
+                now,
+                Duration::from_secs(minutes * 60),
+                reason,
+            ) {
+                return Err(ChatCommandError::WithMessage(String::from(
+                    "Server is already shutting down",
+                )));
+            }
+            log::info!(
+                "Server maintenance starts in {} minutes, started by {}",
+                minutes,
+                chat_command_user.character_info.name
+            );
+            send_multiline_whisper(
+                chat_command_user.game_client,
+                &format!("Server maintenance starts in {} minutes", minutes),
+            );
+        }
+        ("tick", _) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            let Some(tick_profiler) = chat_command_params.tick_profiler.as_ref() else {
+                send_multiline_whisper(
+                    chat_command_user.game_client,
+                    "tick profiler is not enabled, start the server with --tick-profiler",
+                );
+                return Ok(());
+            };
+
+            let mut status = format!(
+                "ticks: {} over budget: {} average: {:.2}ms max: {:.2}ms budget: {:.2}ms",
+                tick_profiler.num_ticks,
+                tick_profiler.num_ticks_over_budget,
+                tick_profiler.average_duration().as_secs_f64() * 1000.0,
+                tick_profiler.max_duration.as_secs_f64() * 1000.0,
+                tick_profiler.budget.as_secs_f64() * 1000.0,
+            );
+            for (name, stats) in tick_profiler.slowest_systems(10) {
+                status += &format!(
+                    "\n{} average: {:.2}ms max: {:.2}ms",
+                    name,
+                    stats.average_duration().as_secs_f64() * 1000.0,
+                    stats.max_duration.as_secs_f64() * 1000.0,
+                );
+            }
+            send_multiline_whisper(chat_command_user.game_client, &status);
+        }
+        ("zonesnapshot", _) => {
+            check_gm_account(chat_command_params, chat_command_user)?;
+            chat_command_params
+                .zone_snapshot_events
+                .send(ZoneSnapshotEvent {
+                    entity: chat_command_user.entity,
+                });
+        }
         _ => return Err(ChatCommandError::InvalidCommand),
     }
 
@@ -0,0 +1,191 @@
+use bevy::prelude::{EventReader, Query, ResMut};
+use chrono::Utc;
+use log::{error, warn};
+use std::time::{Duration, Instant};
+
+use rose_game_common::messages::server::ServerMessage;
+
+use crate::game::{
+    components::{CharacterInfo, ClientEntity, GameClient, Level, Position},
+    events::ChatEvent,
+    resources::{
+        ChatChannel, ChatChannelError, ChatChannels, ChatModeration, ServerMessages, StorageKey,
+        StorageService, StorageWriteStatus,
+    },
+    storage::chat_mute::ChatMuteStorage,
+};
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs().max(1);
+    if secs >= 60 {
+        format!("{} minutes", secs.div_ceil(60))
+    } else {
+        format!("{} seconds", secs)
+    }
+}
+
+fn format_chat_channel_error(channel: ChatChannel, error: ChatChannelError) -> String {
+    match error {
+        ChatChannelError::Disabled => format!("{:?} chat is disabled", channel),
+        ChatChannelError::LevelTooLow { min_level } => {
+            format!("You must be level {} to use {:?} chat", min_level, channel)
+        }
+        ChatChannelError::Cooldown { remaining } => format!(
+            "You must wait {} before using {:?} chat again",
+            format_duration(remaining),
+            channel
+        ),
+    }
+}
+
+pub fn format_chat_mute(mute: &ChatMuteStorage) -> String {
+    let remaining = (mute.until - Utc::now()).to_std().unwrap_or_default();
+    format!(
+        "{} is muted for {} by {}: {}",
+        mute.character_name,
+        format_duration(remaining),
+        mute.muted_by.as_deref().unwrap_or("SERVER"),
+        mute.reason
+    )
+}
+
+pub fn save_chat_mute(storage_service: &mut StorageService, mute: ChatMuteStorage) {
+    let character_name = mute.character_name.clone();
+    match storage_service.write(
+        StorageKey::ChatMute(character_name.to_lowercase()),
+        move || mute.save(),
+    ) {
+        Ok(StorageWriteStatus::Written) => {}
+        Ok(StorageWriteStatus::Queued) => warn!(
+            "Queued save of chat mute for character {} until storage recovers",
+            character_name
+        ),
+        Err(error) => error!(
+            "Failed to save chat mute for character {} with error {:?}",
+            character_name, error
+        ),
+    }
+}
+
+pub fn delete_chat_mute(storage_service: &mut StorageService, character_name: &str) {
+    let key_name = character_name.to_lowercase();
+    if let Err(error) = storage_service.write(StorageKey::ChatMute(key_name.clone()), move || {
+        ChatMuteStorage::delete(&key_name)
+    }) {
+        error!(
+            "Failed to delete chat mute for character {} with error {:?}",
+            character_name, error
+        );
+    }
+}
+
+fn send_server_whisper(game_client: Option<&GameClient>, text: String) {
+    if let Some(game_client) = game_client {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text,
+            })
+            .ok();
+    }
+}
+
+pub fn chat_system(
+    mut chat_events: EventReader<ChatEvent>,
+    query_sender: Query<(
+        &CharacterInfo,
+        &ClientEntity,
+        &Level,
+        &Position,
+        Option<&GameClient>,
+    )>,
+    mut chat_channels: ResMut<ChatChannels>,
+    mut chat_moderation: ResMut<ChatModeration>,
+    mut server_messages: ResMut<ServerMessages>,
+    mut storage_service: ResMut<StorageService>,
+) {
+    let now = Instant::now();
+
+    for event in chat_events.iter() {
+        let Ok((character_info, client_entity, level, position, game_client)) =
+            query_sender.get(event.entity)
+        else {
+            continue;
+        };
+
+        if let Some(mute) = chat_moderation.get_mute(&character_info.name, Utc::now()) {
+            let remaining = (mute.until - Utc::now()).to_std().unwrap_or_default();
+            send_server_whisper(
+                game_client,
+                format!(
+                    "You are muted for {}: {}",
+                    format_duration(remaining),
+                    mute.reason
+                ),
+            );
+            continue;
+        }
+
+        let Some(text) = chat_moderation.filter_text(&event.text) else {
+            send_server_whisper(
+                game_client,
+                String::from("Your message was not sent because it contains a banned word"),
+            );
+            continue;
+        };
+
+        if chat_moderation.is_spam(event.entity, &text, now) {
+            let duration = chat_moderation.get_spam_mute_duration();
+            let mute = ChatMuteStorage {
+                character_name: character_info.name.clone(),
+                until: Utc::now()
+                    + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::zero()),
+                reason: String::from("Spamming"),
+                muted_by: None,
+            };
+            chat_moderation.mute(mute.clone());
+            save_chat_mute(&mut storage_service, mute);
+            send_server_whisper(
+                game_client,
+                format!(
+                    "You have been muted for {} for spamming",
+                    format_duration(duration)
+                ),
+            );
+            continue;
+        }
+
+        if let Err(error) = chat_channels.try_send(event.entity, level.level, event.channel, now) {
+            send_server_whisper(game_client, format_chat_channel_error(event.channel, error));
+            continue;
+        }
+
+        match event.channel {
+            ChatChannel::Local => server_messages.send_entity_message(
+                client_entity,
+                ServerMessage::LocalChat {
+                    entity_id: client_entity.id,
+                    text,
+                },
+            ),
+            ChatChannel::Shout => server_messages.send_zone_message(
+                position.zone_id,
+                ServerMessage::ShoutChat {
+                    name: character_info.name.clone(),
+                    text,
+                },
+            ),
+            ChatChannel::Trade => server_messages.send_global_message(ServerMessage::ShoutChat {
+                name: format!("[Trade] {}", character_info.name),
+                text,
+            }),
+            ChatChannel::Announce => {
+                server_messages.send_global_message(ServerMessage::AnnounceChat {
+                    name: Some(character_info.name.clone()),
+                    text,
+                })
+            }
+        }
+    }
+}
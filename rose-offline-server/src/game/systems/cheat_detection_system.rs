@@ -0,0 +1,113 @@
+use bevy::prelude::{Commands, EventReader, Query, ResMut, With};
+use chrono::Utc;
+use log::{error, warn};
+
+use rose_game_common::messages::server::ServerMessage;
+
+use crate::game::{
+    components::{Account, CharacterInfo, GameClient},
+    events::SuspicionEvent,
+    resources::{CheatDetection, StorageKey, StorageService, StorageWriteStatus},
+    storage::{storage_name_key, suspicion::SuspicionStorage},
+};
+
+pub fn save_suspicion(
+    storage_service: &mut StorageService,
+    account_name: String,
+    suspicion: SuspicionStorage,
+) {
+    match storage_service.write(StorageKey::Suspicion(storage_name_key(&account_name)), {
+        let account_name = account_name.clone();
+        move || suspicion.save(&account_name)
+    }) {
+        Ok(StorageWriteStatus::Written) => {}
+        Ok(StorageWriteStatus::Queued) => warn!(
+            "Queued save of suspicion for account {} until storage recovers",
+            account_name
+        ),
+        Err(error) => error!(
+            "Failed to save suspicion for account {} with error {:?}",
+            account_name, error
+        ),
+    }
+}
+
+pub fn cheat_detection_system(
+    mut commands: Commands,
+    mut cheat_detection: ResMut<CheatDetection>,
+    mut storage_service: ResMut<StorageService>,
+    mut suspicion_events: EventReader<SuspicionEvent>,
+    query_account: Query<(&Account, Option<&CharacterInfo>, Option<&GameClient>)>,
+    query_connected: Query<(), With<GameClient>>,
+) {
+    let now = Utc::now();
+    let mut changed_accounts: Vec<String> = Vec::new();
+
+    for &SuspicionEvent { entity, signal } in suspicion_events.iter() {
+        let Ok((account, character_info, game_client)) = query_account.get(entity) else {
+            continue;
+        };
+        let character_name = character_info.map_or("", |character_info| &character_info.name);
+        let ip = game_client.map_or_else(
+            || String::from("unknown"),
+            |game_client| game_client.ip.to_string(),
+        );
+
+        let session_score = cheat_detection.add_signal(entity, &account.name, signal, now);
+        if !changed_accounts.contains(&account.name) {
+            changed_accounts.push(account.name.clone());
+        }
+
+        if session_score >= cheat_detection.audit_score() {
+            warn!(
+                target: "audit",
+                "Suspicious {:?} from account {} character {} at {}, session score {}",
+                signal,
+                account.name,
+                character_name,
+                ip,
+                session_score
+            );
+        }
+
+        let (Some(kick_score), Some(game_client)) = (cheat_detection.kick_score(), game_client)
+        else {
+            continue;
+        };
+        if session_score < kick_score {
+            continue;
+        }
+
+        warn!(
+            target: "audit",
+            "Disconnected account {} character {} at {} for reaching suspicion score {}",
+            account.name,
+            character_name,
+            ip,
+            session_score
+        );
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text: String::from("You have been disconnected for suspicious activity"),
+            })
+            .ok();
+
+        // Removing the client component closes its connection
+        commands.entity(entity).remove::<GameClient>();
+        cheat_detection.add_kick(entity, &account.name);
+    }
+
+    for account_name in changed_accounts {
+        let suspicion = cheat_detection.get_account(&account_name);
+        save_suspicion(&mut storage_service, account_name, suspicion);
+    }
+
+    cheat_detection.retain(
+        |entity| query_connected.contains(entity),
+        |account_key| {
+            !storage_service.has_pending_write(&StorageKey::Suspicion(account_key.to_string()))
+        },
+    );
+}
@@ -0,0 +1,321 @@
+use bevy::{
+    ecs::query::WorldQuery,
+    prelude::{Entity, EventReader, Query, ResMut},
+};
+use log::error;
+
+use rose_data::{Item, ItemSlotBehaviour};
+use rose_game_common::messages::server::{
+    ClanBankAction, ClanBankError, ClanBankLogEntry, ServerMessage,
+};
+
+use crate::game::{
+    components::{
+        CharacterInfo, Clan, ClanBank, ClanMember, ClanMembership, GameClient, Inventory,
+        PersonalStore,
+    },
+    events::ClanBankEvent,
+    resources::StorageService,
+    storage::{clan_bank::ClanBankStorage, item_transaction::ItemTransaction},
+};
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct ClanBankUserQuery<'w> {
+    game_client: &'w GameClient,
+    character_info: &'w CharacterInfo,
+    clan_membership: &'w ClanMembership,
+    inventory: &'w mut Inventory,
+    personal_store: Option<&'w PersonalStore>,
+}
+
+fn send_clan_bank_error(game_client: &GameClient, error: ClanBankError) {
+    game_client
+        .server_message_tx
+        .send(ServerMessage::ClanBankError { error })
+        .ok();
+}
+
+/// Saves the clan bank together with the inventory of the member who moved
+/// an item, so a crash between the two saves can not duplicate or lose it.
+fn save_clan_bank_transaction(
+    storage_service: &mut StorageService,
+    character_info: &CharacterInfo,
+    inventory: &Inventory,
+    clan: &Clan,
+    clan_bank: &ClanBank,
+) {
+    let mut transaction = ItemTransaction::new();
+    transaction.update_inventory(&character_info.name, inventory);
+    transaction.update_clan_bank(&clan.name, ClanBankStorage::from(clan_bank));
+    if let Err(error) = storage_service.write_item_transaction(transaction) {
+        error!(
+            "Failed to save bank transaction for clan {} with error {:?}",
+            &clan.name, error
+        );
+    }
+}
+
+fn add_clan_bank_log_entry(
+    clan_bank: &mut ClanBank,
+    name: &str,
+    action: ClanBankAction,
+    item: Item,
+) {
+    clan_bank.add_log_entry(ClanBankLogEntry {
+        timestamp: chrono::Utc::now().timestamp(),
+        name: name.to_string(),
+        action,
+        item,
+    });
+}
+
+// Keep the bank contents up to date for any other clan members who have it open
+fn send_bank_update_to_other_members(
+    query_game_client: &Query<&GameClient>,
+    clan: &Clan,
+    except_entity: Entity,
+    bank_slot: usize,
+    bank_item: Option<Item>,
+) {
+    for clan_member in clan.members.iter() {
+        let &ClanMember::Online {
+            entity: clan_member_entity,
+            ..
+        } = clan_member
+        else {
+            continue;
+        };
+
+        if clan_member_entity == except_entity {
+            continue;
+        }
+
+        if let Ok(game_client) = query_game_client.get(clan_member_entity) {
+            game_client
+                .server_message_tx
+                .send(ServerMessage::ClanBankUpdateItems {
+                    items: vec![(bank_slot, bank_item.clone())],
+                })
+                .ok();
+        }
+    }
+}
+
+pub fn clan_bank_system(
+    mut clan_bank_events: EventReader<ClanBankEvent>,
+    mut query_user: Query<ClanBankUserQuery>,
+    mut query_clans: Query<(&Clan, &mut ClanBank)>,
+    query_game_client: Query<&GameClient>,
+    mut storage_service: ResMut<StorageService>,
+) {
+    for event in clan_bank_events.iter() {
+        let entity = match *event {
+            ClanBankEvent::Open { entity }
+            | ClanBankEvent::DepositItem { entity, .. }
+            | ClanBankEvent::WithdrawItem { entity, .. }
+            | ClanBankEvent::GetLog { entity } => entity,
+        };
+
+        let Ok(mut user) = query_user.get_mut(entity) else {
+            continue;
+        };
+
+        let Some((clan, mut clan_bank)) = user
+            .clan_membership
+            .clan()
+            .and_then(|clan_entity| query_clans.get_mut(clan_entity).ok())
+        else {
+            send_clan_bank_error(user.game_client, ClanBankError::NotInClan);
+            continue;
+        };
+
+        let Some(position) = clan
+            .find_online_member(entity)
+            .map(|clan_member| clan_member.position())
+        else {
+            send_clan_bank_error(user.game_client, ClanBankError::NotInClan);
+            continue;
+        };
+
+        match *event {
+            ClanBankEvent::Open { .. } => {
+                user.game_client
+                    .server_message_tx
+                    .send(ServerMessage::ClanBankOpen {
+                        items: clan_bank
+                            .slots
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, item)| item.is_some())
+                            .map(|(index, item)| (index, item.clone()))
+                            .collect(),
+                        can_deposit: ClanBank::can_deposit(position),
+                        can_withdraw: ClanBank::can_withdraw(position),
+                    })
+                    .ok();
+            }
+            ClanBankEvent::DepositItem {
+                item_slot,
+                ref item,
+                ..
+            } => {
+                if !ClanBank::can_deposit(position) {
+                    send_clan_bank_error(user.game_client, ClanBankError::NoPermission);
+                    continue;
+                }
+
+                if user
+                    .personal_store
+                    .map_or(false, |store| store.is_item_slot_reserved(item_slot))
+                    || !user
+                        .inventory
+                        .get_item(item_slot)
+                        .map_or(false, |inventory_item| inventory_item.is_same_item(item))
+                {
+                    send_clan_bank_error(user.game_client, ClanBankError::InvalidItem);
+                    continue;
+                }
+
+                if item.is_bound()
+                    || user
+                        .inventory
+                        .get_item(item_slot)
+                        .map_or(false, |inventory_item| inventory_item.is_bound())
+                {
+                    send_clan_bank_error(user.game_client, ClanBankError::ItemBound);
+                    continue;
+                }
+
+                let Some(inventory_slot) = user.inventory.get_item_slot_mut(item_slot) else {
+                    continue;
+                };
+                let Some(deposit_item) = inventory_slot.try_take_quantity(item.get_quantity())
+                else {
+                    send_clan_bank_error(user.game_client, ClanBankError::InvalidItem);
+                    continue;
+                };
+
+                let (bank_slot, bank_item) = match clan_bank.try_add_item(deposit_item.clone()) {
+                    Ok((bank_slot, bank_item)) => (bank_slot, bank_item.clone()),
+                    Err(deposit_item) => {
+                        inventory_slot
+                            .try_stack_with_item(deposit_item)
+                            .expect("bad things happened");
+                        send_clan_bank_error(user.game_client, ClanBankError::BankFull);
+                        continue;
+                    }
+                };
+
+                add_clan_bank_log_entry(
+                    &mut clan_bank,
+                    &user.character_info.name,
+                    ClanBankAction::Deposit,
+                    deposit_item,
+                );
+                save_clan_bank_transaction(
+                    &mut storage_service,
+                    user.character_info,
+                    &user.inventory,
+                    clan,
+                    &clan_bank,
+                );
+
+                user.game_client
+                    .server_message_tx
+                    .send(ServerMessage::ClanBankTransaction {
+                        inventory_item_slot: item_slot,
+                        inventory_item: user.inventory.get_item(item_slot).cloned(),
+                        bank_slot,
+                        bank_item: Some(bank_item.clone()),
+                    })
+                    .ok();
+                send_bank_update_to_other_members(
+                    &query_game_client,
+                    clan,
+                    entity,
+                    bank_slot,
+                    Some(bank_item),
+                );
+            }
+            ClanBankEvent::WithdrawItem {
+                bank_slot,
+                ref item,
+                ..
+            } => {
+                if !ClanBank::can_withdraw(position) {
+                    send_clan_bank_error(user.game_client, ClanBankError::NoPermission);
+                    continue;
+                }
+
+                let Some(bank_item_slot) = clan_bank
+                    .slots
+                    .get_mut(bank_slot)
+                    .filter(|slot| slot.contains_same_item(item))
+                else {
+                    send_clan_bank_error(user.game_client, ClanBankError::InvalidItem);
+                    continue;
+                };
+                let Some(withdraw_item) = bank_item_slot.try_take_quantity(item.get_quantity())
+                else {
+                    send_clan_bank_error(user.game_client, ClanBankError::InvalidItem);
+                    continue;
+                };
+
+                let (inventory_item_slot, inventory_item) =
+                    match user.inventory.try_add_item(withdraw_item.clone()) {
+                        Ok((inventory_item_slot, inventory_item)) => {
+                            (inventory_item_slot, inventory_item.clone())
+                        }
+                        Err(withdraw_item) => {
+                            bank_item_slot
+                                .try_stack_with_item(withdraw_item)
+                                .expect("bad things happened");
+                            send_clan_bank_error(user.game_client, ClanBankError::InventoryFull);
+                            continue;
+                        }
+                    };
+
+                add_clan_bank_log_entry(
+                    &mut clan_bank,
+                    &user.character_info.name,
+                    ClanBankAction::Withdraw,
+                    withdraw_item,
+                );
+                save_clan_bank_transaction(
+                    &mut storage_service,
+                    user.character_info,
+                    &user.inventory,
+                    clan,
+                    &clan_bank,
+                );
+
+                let bank_item = clan_bank.slots[bank_slot].clone();
+                user.game_client
+                    .server_message_tx
+                    .send(ServerMessage::ClanBankTransaction {
+                        inventory_item_slot,
+                        inventory_item: Some(inventory_item),
+                        bank_slot,
+                        bank_item: bank_item.clone(),
+                    })
+                    .ok();
+                send_bank_update_to_other_members(
+                    &query_game_client,
+                    clan,
+                    entity,
+                    bank_slot,
+                    bank_item,
+                );
+            }
+            ClanBankEvent::GetLog { .. } => {
+                user.game_client
+                    .server_message_tx
+                    .send(ServerMessage::ClanBankLog {
+                        entries: clan_bank.log.clone(),
+                    })
+                    .ok();
+            }
+        }
+    }
+}
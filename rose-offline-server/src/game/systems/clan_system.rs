@@ -1,26 +1,49 @@
-use std::num::{NonZeroU32, NonZeroUsize};
+use std::{
+    cmp::Ordering,
+    num::{NonZeroU32, NonZeroUsize},
+    time::Duration,
+};
 
 use bevy::{
     ecs::query::WorldQuery,
-    prelude::{Changed, Commands, Entity, EventReader, Query, ResMut},
+    prelude::{Changed, Commands, Entity, EventReader, Query, Res, ResMut, With},
+    time::Time,
 };
+use log::error;
 
 use rose_data::{ClanMemberPosition, QuestTriggerHash};
 use rose_game_common::{
     components::{ClanLevel, ClanPoints, ClanUniqueId},
-    messages::server::{ClanCreateError, ClanMemberInfo, ServerMessage},
+    messages::server::{
+        ClanCreateError, ClanMemberInfo, ClanUpdateError, ClanWarError, ClanWarResult,
+        ServerMessage,
+    },
 };
 
 use crate::game::{
     components::{
-        CharacterInfo, Clan, ClanMember, ClanMembership, ClientEntity, GameClient, Inventory,
-        Level, Money,
+        Account, CharacterInfo, Clan, ClanBank, ClanMember, ClanMembership, ClientEntity,
+        GameClient, Inventory, Level, Money,
     },
     events::ClanEvent,
-    resources::ServerMessages,
-    storage::clan::{ClanStorage, ClanStorageMember},
+    resources::{
+        ClanWar, ClanWarDeclaration, ClanWars, GameConfig, GameData, NameFilter, ServerMessages,
+        StorageKey, StorageService,
+    },
+    storage::{
+        clan::{ClanStorage, ClanStorageMember, ClanStorageWarResult},
+        clan_bank::ClanBankStorage,
+    },
 };
 
+const CLAN_DESCRIPTION_MAX_LENGTH: usize = 255;
+const CLAN_NOTICE_MAX_LENGTH: usize = 255;
+
+const CLAN_WAR_DECLARATION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const CLAN_WAR_DURATION: Duration = Duration::from_secs(30 * 60);
+const CLAN_WAR_KILL_POINTS: u64 = 10;
+const CLAN_WAR_VICTORY_POINTS: u64 = 500;
+
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 pub struct CreatorQuery<'w> {
@@ -30,6 +53,7 @@ pub struct CreatorQuery<'w> {
     inventory: &'w mut Inventory,
     game_client: Option<&'w GameClient>,
     clan_membership: &'w ClanMembership,
+    account: Option<&'w Account>,
 }
 
 #[derive(WorldQuery)]
@@ -38,10 +62,11 @@ pub struct MemberQuery<'w> {
     character_info: &'w CharacterInfo,
     clan_membership: &'w ClanMembership,
     level: &'w Level,
+    client_entity: Option<&'w ClientEntity>,
     game_client: Option<&'w GameClient>,
 }
 
-fn send_update_clan_info(clan: &Clan, query_member: &Query<MemberQuery>) {
+fn send_clan_message(clan: &Clan, query_member: &Query<MemberQuery>, message: ServerMessage) {
     for clan_member in clan.members.iter() {
         let &ClanMember::Online {
             entity: clan_member_entity,
@@ -55,20 +80,244 @@ fn send_update_clan_info(clan: &Clan, query_member: &Query<MemberQuery>) {
             if let Some(online_member_game_client) = online_member.game_client {
                 online_member_game_client
                     .server_message_tx
-                    .send(ServerMessage::ClanUpdateInfo {
-                        id: clan.unique_id,
-                        mark: clan.mark,
-                        level: clan.level,
-                        points: clan.points,
-                        money: clan.money,
-                        skills: clan.skills.clone(),
-                    })
+                    .send(message.clone())
                     .ok();
             }
         }
     }
 }
 
+fn send_update_clan_info(clan: &Clan, query_member: &Query<MemberQuery>) {
+    send_clan_message(
+        clan,
+        query_member,
+        ServerMessage::ClanUpdateInfo {
+            id: clan.unique_id,
+            mark: clan.mark,
+            level: clan.level,
+            points: clan.points,
+            money: clan.money,
+            skills: clan.skills.clone(),
+        },
+    );
+}
+
+fn send_clan_war_error(game_client: Option<&GameClient>, error: ClanWarError) {
+    if let Some(game_client) = game_client {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::ClanWarError { error })
+            .ok();
+    }
+}
+
+fn send_clan_update_error(game_client: Option<&GameClient>, error: ClanUpdateError) {
+    if let Some(game_client) = game_client {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::ClanUpdateError { error })
+            .ok();
+    }
+}
+
+fn send_clan_info(clan: &Clan, query_member: &Query<MemberQuery>, member_entity: Entity) {
+    let Some(&ClanMember::Online {
+        position,
+        contribution,
+        ..
+    }) = clan.find_online_member(member_entity)
+    else {
+        return;
+    };
+
+    if let Some(game_client) = query_member
+        .get(member_entity)
+        .ok()
+        .and_then(|member| member.game_client)
+    {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::ClanInfo {
+                id: clan.unique_id,
+                name: clan.name.clone(),
+                description: clan.description.clone(),
+                mark: clan.mark,
+                level: clan.level,
+                points: clan.points,
+                money: clan.money,
+                skills: clan.skills.clone(),
+                position,
+                contribution,
+            })
+            .ok();
+    }
+}
+
+fn save_clan_info(storage_service: &mut StorageService, clan: &Clan) {
+    let clan_name = clan.name.clone();
+    let description = clan.description.clone();
+    let notice = clan.notice.clone();
+    let mark = clan.mark;
+    if let Err(error) = storage_service.write(StorageKey::Clan(clan.name.clone()), move || {
+        let mut clan_storage = ClanStorage::try_load(&clan_name)?;
+        clan_storage.description = description.clone();
+        clan_storage.notice = notice.clone();
+        clan_storage.mark = mark;
+        clan_storage.save()
+    }) {
+        error!(
+            "Failed to save clan info for clan {} with error {:?}",
+            &clan.name, error
+        );
+    }
+}
+
+fn find_clan_by_name(
+    query_clan_entities: &Query<Entity, With<Clan>>,
+    query_clans: &Query<&mut Clan>,
+    name: &str,
+) -> Option<Entity> {
+    query_clan_entities.iter().find(|&clan_entity| {
+        query_clans
+            .get(clan_entity)
+            .map_or(false, |clan| clan.name == name)
+    })
+}
+
+fn is_clan_master_or_deputy(clan: &Clan, entity: Entity) -> bool {
+    matches!(
+        clan.find_online_member(entity)
+            .map(|clan_member| clan_member.position()),
+        Some(ClanMemberPosition::Master | ClanMemberPosition::DeputyMaster)
+    )
+}
+
+fn finish_clan_war(
+    storage_service: &mut StorageService,
+    clan: &mut Clan,
+    query_member: &Query<MemberQuery>,
+    opponent_name: &str,
+    score: u32,
+    opponent_score: u32,
+) {
+    let result = match score.cmp(&opponent_score) {
+        Ordering::Greater => ClanWarResult::Won,
+        Ordering::Less => ClanWarResult::Lost,
+        Ordering::Equal => ClanWarResult::Draw,
+    };
+
+    if result == ClanWarResult::Won {
+        clan.points = ClanPoints(clan.points.0.saturating_add(CLAN_WAR_VICTORY_POINTS));
+        send_update_clan_info(clan, query_member);
+    }
+
+    send_clan_message(
+        clan,
+        query_member,
+        ServerMessage::ClanWarEnded {
+            clan_name: opponent_name.to_string(),
+            result,
+            score,
+            opponent_score,
+        },
+    );
+
+    // Clan points are otherwise only kept in memory, so save them with the war result
+    let clan_name = clan.name.clone();
+    let points = clan.points;
+    let war_result = ClanStorageWarResult {
+        opponent: opponent_name.to_string(),
+        result,
+        score,
+        opponent_score,
+    };
+    if let Err(error) = storage_service.write(StorageKey::Clan(clan.name.clone()), move || {
+        let mut clan_storage = ClanStorage::try_load(&clan_name)?;
+        clan_storage.points = points;
+        clan_storage.war_results.push(war_result.clone());
+        clan_storage.save()
+    }) {
+        error!(
+            "Failed to save war result for clan {} with error {:?}",
+            &clan.name, error
+        );
+    }
+}
+
+fn end_clan_war(
+    storage_service: &mut StorageService,
+    war: &ClanWar,
+    query_clans: &mut Query<&mut Clan>,
+    query_member: &Query<MemberQuery>,
+    server_messages: &mut ServerMessages,
+) {
+    let Ok([mut first_clan, mut second_clan]) = query_clans.get_many_mut(war.clans) else {
+        return;
+    };
+    let [first_score, second_score] = war.scores;
+    let first_name = first_clan.name.clone();
+    let second_name = second_clan.name.clone();
+
+    finish_clan_war(
+        storage_service,
+        &mut first_clan,
+        query_member,
+        &second_name,
+        first_score,
+        second_score,
+    );
+    finish_clan_war(
+        storage_service,
+        &mut second_clan,
+        query_member,
+        &first_name,
+        second_score,
+        first_score,
+    );
+
+    let text = match first_score.cmp(&second_score) {
+        Ordering::Greater => format!(
+            "Clan {} has won the clan war against {} with a score of {} to {}",
+            first_name, second_name, first_score, second_score
+        ),
+        Ordering::Less => format!(
+            "Clan {} has won the clan war against {} with a score of {} to {}",
+            second_name, first_name, second_score, first_score
+        ),
+        Ordering::Equal => format!(
+            "The clan war between {} and {} has ended in a draw with a score of {} to {}",
+            first_name, second_name, first_score, second_score
+        ),
+    };
+    server_messages.send_global_message(ServerMessage::AnnounceChat { name: None, text });
+}
+
+/// Sends a clan creation error, followed by a notice describing the requirement
+/// which was not met as the error can not include it.
+fn send_clan_create_error(
+    game_client: Option<&GameClient>,
+    error: ClanCreateError,
+    requirement: Option<String>,
+) {
+    let Some(game_client) = game_client else {
+        return;
+    };
+
+    game_client
+        .server_message_tx
+        .send(ServerMessage::ClanCreateError { error })
+        .ok();
+    if let Some(text) = requirement {
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text,
+            })
+            .ok();
+    }
+}
+
 pub fn clan_system(
     mut commands: Commands,
     mut clan_events: EventReader<ClanEvent>,
@@ -76,8 +325,19 @@ pub fn clan_system(
     query_member: Query<MemberQuery>,
     mut query_creator: Query<CreatorQuery>,
     mut query_clans: Query<&mut Clan>,
+    query_clan_entities: Query<Entity, With<Clan>>,
+    mut clan_wars: ResMut<ClanWars>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    name_filter: Res<NameFilter>,
     mut server_messages: ResMut<ServerMessages>,
+    mut storage_service: ResMut<StorageService>,
+    time: Res<Time>,
 ) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
     for event in clan_events.iter() {
         match event {
             ClanEvent::Create {
@@ -90,54 +350,94 @@ pub fn clan_system(
                     continue;
                 };
 
+                let clan_creation = &game_config.clan_creation;
+
                 // Cannot create a clan if already in one
                 if creator.clan_membership.is_some() {
-                    if let Some(game_client) = creator.game_client {
-                        game_client
-                            .server_message_tx
-                            .send(ServerMessage::ClanCreateError {
-                                error: ClanCreateError::Failed,
-                            })
-                            .ok();
-                    }
+                    send_clan_create_error(creator.game_client, ClanCreateError::Failed, None);
                     continue;
                 }
 
-                if creator.level.level < 30 {
-                    if let Some(game_client) = creator.game_client {
-                        game_client
-                            .server_message_tx
-                            .send(ServerMessage::ClanCreateError {
-                                error: ClanCreateError::UnmetCondition,
-                            })
-                            .ok();
-                    }
+                if creator.level.level < clan_creation.min_level {
+                    send_clan_create_error(
+                        creator.game_client,
+                        ClanCreateError::UnmetCondition,
+                        Some(format!(
+                            "You must be level {} to create a clan",
+                            clan_creation.min_level
+                        )),
+                    );
                     continue;
                 }
 
-                if ClanStorage::exists(name) {
-                    if let Some(game_client) = creator.game_client {
-                        game_client
-                            .server_message_tx
-                            .send(ServerMessage::ClanCreateError {
-                                error: ClanCreateError::NameExists,
-                            })
-                            .ok();
-                    }
+                let name_length = name.chars().count();
+                if name_length < clan_creation.min_name_length
+                    || name_length > clan_creation.max_name_length
+                {
+                    send_clan_create_error(
+                        creator.game_client,
+                        ClanCreateError::Failed,
+                        Some(format!(
+                            "Clan names must be {} to {} characters",
+                            clan_creation.min_name_length, clan_creation.max_name_length
+                        )),
+                    );
                     continue;
                 }
 
-                let Ok(money) = creator.inventory.try_take_money(Money(1000000)) else {
-                    if let Some(game_client) = creator.game_client {
-                        game_client
-                            .server_message_tx
-                            .send(ServerMessage::ClanCreateError {
-                                error: ClanCreateError::UnmetCondition,
-                            })
-                            .ok();
+                if !name_filter.is_name_allowed(name) {
+                    send_clan_create_error(creator.game_client, ClanCreateError::Failed, None);
+                    continue;
+                }
+
+                if !clan_creation.is_valid_mark(mark) {
+                    send_clan_create_error(
+                        creator.game_client,
+                        ClanCreateError::Failed,
+                        Some(String::from("That clan mark is not available")),
+                    );
+                    continue;
+                }
+
+                // Every clan is loaded at startup, so only the clan entities need to be checked
+                if ClanStorage::exists(name)
+                    || query_clans
+                        .iter()
+                        .any(|clan| name_filter.is_same_name(&clan.name, name))
+                {
+                    send_clan_create_error(creator.game_client, ClanCreateError::NameExists, None);
+                    continue;
+                }
+
+                if let Some(required_item) = clan_creation.required_item {
+                    if creator.inventory.find_item(required_item).is_none() {
+                        let item_name = game_data.get_item_name(
+                            required_item,
+                            creator.account.and_then(|account| account.language),
+                        );
+                        send_clan_create_error(
+                            creator.game_client,
+                            ClanCreateError::UnmetCondition,
+                            Some(format!("You need a {} to create a clan", item_name)),
+                        );
+                        continue;
                     }
+                }
+
+                let Ok(money) = creator.inventory.try_take_money(clan_creation.cost) else {
+                    send_clan_create_error(
+                        creator.game_client,
+                        ClanCreateError::UnmetCondition,
+                        Some(format!(
+                            "You need {} zuly to create a clan",
+                            clan_creation.cost.0
+                        )),
+                    );
                     continue;
                 };
+                let taken_item = clan_creation
+                    .required_item
+                    .and_then(|required_item| creator.inventory.try_take_item(required_item, 1));
 
                 let mut clan_storage = ClanStorage::new(name.clone(), description.clone(), *mark);
                 clan_storage.members.push(ClanStorageMember::new(
@@ -145,19 +445,39 @@ pub fn clan_system(
                     ClanMemberPosition::Master,
                 ));
                 if clan_storage.try_create().is_err() {
-                    if let Some(game_client) = creator.game_client {
-                        game_client
-                            .server_message_tx
-                            .send(ServerMessage::ClanCreateError {
-                                error: ClanCreateError::Failed,
-                            })
-                            .ok();
-                    }
+                    send_clan_create_error(creator.game_client, ClanCreateError::Failed, None);
 
                     creator.inventory.try_add_money(money).ok();
+                    if let Some((_, item)) = taken_item {
+                        creator.inventory.try_add_item(item).ok();
+                    }
                     continue;
                 }
 
+                // A new clan starts with an empty bank, never one left behind
+                // by a removed clan with the same name
+                if let Err(error) = ClanBankStorage::delete(name) {
+                    error!(
+                        "Failed to delete old bank for new clan {} with error {:?}",
+                        name, error
+                    );
+                }
+
+                if let Some(game_client) = creator.game_client {
+                    game_client
+                        .server_message_tx
+                        .send(ServerMessage::UpdateInventory {
+                            items: taken_item
+                                .map(|(item_slot, _)| {
+                                    (item_slot, creator.inventory.get_item(item_slot).cloned())
+                                })
+                                .into_iter()
+                                .collect(),
+                            money: Some(creator.inventory.money),
+                        })
+                        .ok();
+                }
+
                 // Create clan entity
                 let unique_id =
                     ClanUniqueId::new(QuestTriggerHash::from(name.as_str()).hash).unwrap();
@@ -167,17 +487,21 @@ pub fn clan_system(
                     contribution: ClanPoints(0),
                 }];
                 let clan_entity = commands
-                    .spawn(Clan {
-                        unique_id,
-                        name: clan_storage.name.clone(),
-                        description: clan_storage.description,
-                        mark: clan_storage.mark,
-                        money: clan_storage.money,
-                        points: clan_storage.points,
-                        level: clan_storage.level,
-                        skills: clan_storage.skills,
-                        members,
-                    })
+                    .spawn((
+                        Clan {
+                            unique_id,
+                            name: clan_storage.name.clone(),
+                            description: clan_storage.description,
+                            notice: clan_storage.notice,
+                            mark: clan_storage.mark,
+                            money: clan_storage.money,
+                            points: clan_storage.points,
+                            level: clan_storage.level,
+                            skills: clan_storage.skills,
+                            members,
+                        },
+                        ClanBank::default(),
+                    ))
                     .id();
 
                 // Add clan membership to creator
@@ -377,9 +701,355 @@ pub fn clan_system(
                     }
                 }
             }
+            &ClanEvent::UpdateDescription {
+                entity,
+                ref description,
+            } => {
+                let Ok(member) = query_member.get(entity) else {
+                    continue;
+                };
+
+                let Some(mut clan) = member
+                    .clan_membership
+                    .and_then(|clan_entity| query_clans.get_mut(clan_entity).ok())
+                else {
+                    send_clan_update_error(member.game_client, ClanUpdateError::NoPermission);
+                    continue;
+                };
+
+                if !is_clan_master_or_deputy(&clan, entity) {
+                    send_clan_update_error(member.game_client, ClanUpdateError::NoPermission);
+                    continue;
+                }
+
+                if description.chars().count() > CLAN_DESCRIPTION_MAX_LENGTH {
+                    send_clan_update_error(member.game_client, ClanUpdateError::InvalidDescription);
+                    continue;
+                }
+
+                clan.description = description.clone();
+                save_clan_info(&mut storage_service, &clan);
+
+                // Only ClanInfo contains the description, so resend it to all online members
+                for clan_member_entity in
+                    clan.members
+                        .iter()
+                        .filter_map(|clan_member| match *clan_member {
+                            ClanMember::Online { entity, .. } => Some(entity),
+                            ClanMember::Offline { .. } => None,
+                        })
+                {
+                    send_clan_info(&clan, &query_member, clan_member_entity);
+                }
+            }
+            &ClanEvent::UpdateNotice { entity, ref notice } => {
+                let Ok(member) = query_member.get(entity) else {
+                    continue;
+                };
+
+                let Some(mut clan) = member
+                    .clan_membership
+                    .and_then(|clan_entity| query_clans.get_mut(clan_entity).ok())
+                else {
+                    send_clan_update_error(member.game_client, ClanUpdateError::NoPermission);
+                    continue;
+                };
+
+                if !is_clan_master_or_deputy(&clan, entity) {
+                    send_clan_update_error(member.game_client, ClanUpdateError::NoPermission);
+                    continue;
+                }
+
+                if notice.chars().count() > CLAN_NOTICE_MAX_LENGTH {
+                    send_clan_update_error(member.game_client, ClanUpdateError::InvalidNotice);
+                    continue;
+                }
+
+                clan.notice = notice.clone();
+                save_clan_info(&mut storage_service, &clan);
+                send_clan_message(
+                    &clan,
+                    &query_member,
+                    ServerMessage::ClanNotice {
+                        notice: notice.clone(),
+                    },
+                );
+            }
+            &ClanEvent::UpdateMark { entity, mark } => {
+                let Ok(member) = query_member.get(entity) else {
+                    continue;
+                };
+
+                let Some(mut clan) = member
+                    .clan_membership
+                    .and_then(|clan_entity| query_clans.get_mut(clan_entity).ok())
+                else {
+                    send_clan_update_error(member.game_client, ClanUpdateError::NoPermission);
+                    continue;
+                };
+
+                if !is_clan_master_or_deputy(&clan, entity) {
+                    send_clan_update_error(member.game_client, ClanUpdateError::NoPermission);
+                    continue;
+                }
+
+                if !game_config.clan_creation.is_valid_mark(&mark) {
+                    send_clan_update_error(member.game_client, ClanUpdateError::InvalidMark);
+                    continue;
+                }
+
+                clan.mark = mark;
+                save_clan_info(&mut storage_service, &clan);
+                send_update_clan_info(&clan, &query_member);
+
+                // Update the clan mark shown to nearby entities for each online member
+                for clan_member in clan.members.iter() {
+                    let &ClanMember::Online {
+                        entity: clan_member_entity,
+                        position,
+                        ..
+                    } = clan_member
+                    else {
+                        continue;
+                    };
+
+                    if let Some(client_entity) = query_member
+                        .get(clan_member_entity)
+                        .ok()
+                        .and_then(|clan_member| clan_member.client_entity)
+                    {
+                        server_messages.send_entity_message(
+                            client_entity,
+                            ServerMessage::CharacterUpdateClan {
+                                client_entity_id: client_entity.id,
+                                id: clan.unique_id,
+                                name: clan.name.clone(),
+                                mark: clan.mark,
+                                level: clan.level,
+                                position,
+                            },
+                        );
+                    }
+                }
+            }
+            &ClanEvent::DeclareWar {
+                declarer,
+                ref clan_name,
+            } => {
+                let Ok(declarer) = query_member.get(declarer) else {
+                    continue;
+                };
+
+                let Some((declaring_clan_entity, declaring_clan)) =
+                    declarer.clan_membership.and_then(|clan_entity| {
+                        query_clans
+                            .get(clan_entity)
+                            .ok()
+                            .map(|clan| (clan_entity, clan))
+                    })
+                else {
+                    send_clan_war_error(declarer.game_client, ClanWarError::NoPermission);
+                    continue;
+                };
+
+                if !is_clan_master_or_deputy(declaring_clan, declarer.entity) {
+                    send_clan_war_error(declarer.game_client, ClanWarError::NoPermission);
+                    continue;
+                }
+
+                let Some(target_clan_entity) =
+                    find_clan_by_name(&query_clan_entities, &query_clans, clan_name)
+                        .filter(|&clan_entity| clan_entity != declaring_clan_entity)
+                else {
+                    send_clan_war_error(declarer.game_client, ClanWarError::InvalidClan);
+                    continue;
+                };
+
+                if clan_wars.find_war(declaring_clan_entity).is_some()
+                    || clan_wars.find_war(target_clan_entity).is_some()
+                {
+                    send_clan_war_error(declarer.game_client, ClanWarError::AlreadyAtWar);
+                    continue;
+                }
+
+                // A repeated declaration replaces the previous one
+                clan_wars.declarations.retain(|declaration| {
+                    declaration.declaring_clan != declaring_clan_entity
+                        || declaration.target_clan != target_clan_entity
+                });
+                clan_wars.declarations.push(ClanWarDeclaration {
+                    declaring_clan: declaring_clan_entity,
+                    target_clan: target_clan_entity,
+                    expire_time: now + CLAN_WAR_DECLARATION_TIMEOUT,
+                });
+
+                if let Ok(target_clan) = query_clans.get(target_clan_entity) {
+                    send_clan_message(
+                        target_clan,
+                        &query_member,
+                        ServerMessage::ClanWarDeclared {
+                            clan_name: declaring_clan.name.clone(),
+                        },
+                    );
+                }
+            }
+            &ClanEvent::AcceptWar {
+                accepter,
+                ref clan_name,
+            } => {
+                let Ok(accepter) = query_member.get(accepter) else {
+                    continue;
+                };
+
+                let Some((accepting_clan_entity, accepting_clan)) =
+                    accepter.clan_membership.and_then(|clan_entity| {
+                        query_clans
+                            .get(clan_entity)
+                            .ok()
+                            .map(|clan| (clan_entity, clan))
+                    })
+                else {
+                    send_clan_war_error(accepter.game_client, ClanWarError::NoPermission);
+                    continue;
+                };
+
+                if !is_clan_master_or_deputy(accepting_clan, accepter.entity) {
+                    send_clan_war_error(accepter.game_client, ClanWarError::NoPermission);
+                    continue;
+                }
+
+                let Some(declaring_clan_entity) =
+                    find_clan_by_name(&query_clan_entities, &query_clans, clan_name)
+                else {
+                    send_clan_war_error(accepter.game_client, ClanWarError::InvalidClan);
+                    continue;
+                };
+
+                let Some(declaration_index) =
+                    clan_wars.declarations.iter().position(|declaration| {
+                        declaration.declaring_clan == declaring_clan_entity
+                            && declaration.target_clan == accepting_clan_entity
+                            && declaration.expire_time > now
+                    })
+                else {
+                    send_clan_war_error(accepter.game_client, ClanWarError::NotDeclared);
+                    continue;
+                };
+                clan_wars.declarations.remove(declaration_index);
+
+                if clan_wars.find_war(declaring_clan_entity).is_some()
+                    || clan_wars.find_war(accepting_clan_entity).is_some()
+                {
+                    send_clan_war_error(accepter.game_client, ClanWarError::AlreadyAtWar);
+                    continue;
+                }
+
+                let Ok(declaring_clan) = query_clans.get(declaring_clan_entity) else {
+                    continue;
+                };
+
+                clan_wars.wars.push(ClanWar {
+                    clans: [declaring_clan_entity, accepting_clan_entity],
+                    scores: [0, 0],
+                    end_time: now + CLAN_WAR_DURATION,
+                });
+
+                send_clan_message(
+                    declaring_clan,
+                    &query_member,
+                    ServerMessage::ClanWarStarted {
+                        clan_name: accepting_clan.name.clone(),
+                        duration: CLAN_WAR_DURATION,
+                    },
+                );
+                send_clan_message(
+                    accepting_clan,
+                    &query_member,
+                    ServerMessage::ClanWarStarted {
+                        clan_name: declaring_clan.name.clone(),
+                        duration: CLAN_WAR_DURATION,
+                    },
+                );
+                server_messages.send_global_message(ServerMessage::AnnounceChat {
+                    name: None,
+                    text: format!(
+                        "Clan {} has gone to war against clan {}",
+                        declaring_clan.name, accepting_clan.name
+                    ),
+                });
+            }
+            &ClanEvent::WarKill { killer, killed } => {
+                let (Ok(killer), Ok(killed)) = (query_member.get(killer), query_member.get(killed))
+                else {
+                    continue;
+                };
+
+                let (Some(killer_clan_entity), Some(killed_clan_entity)) =
+                    (killer.clan_membership.clan(), killed.clan_membership.clan())
+                else {
+                    continue;
+                };
+
+                if !clan_wars.is_at_war(Some(killer_clan_entity), Some(killed_clan_entity)) {
+                    continue;
+                }
+
+                let Some(war) = clan_wars.find_war_mut(killer_clan_entity) else {
+                    continue;
+                };
+                let killer_index = war.clan_index(killer_clan_entity).unwrap();
+                war.scores[killer_index] += 1;
+                let killer_score = war.scores[killer_index];
+                let killed_score = war.scores[1 - killer_index];
+
+                if let Ok(mut killer_clan) = query_clans.get_mut(killer_clan_entity) {
+                    killer_clan.points =
+                        ClanPoints(killer_clan.points.0.saturating_add(CLAN_WAR_KILL_POINTS));
+                    send_update_clan_info(&killer_clan, &query_member);
+                    send_clan_message(
+                        &killer_clan,
+                        &query_member,
+                        ServerMessage::ClanWarScore {
+                            score: killer_score,
+                            opponent_score: killed_score,
+                        },
+                    );
+                }
+
+                if let Ok(killed_clan) = query_clans.get(killed_clan_entity) {
+                    send_clan_message(
+                        killed_clan,
+                        &query_member,
+                        ServerMessage::ClanWarScore {
+                            score: killed_score,
+                            opponent_score: killer_score,
+                        },
+                    );
+                }
+            }
         }
     }
 
+    // Remove expired war declarations and end any wars which have run their full duration
+    clan_wars
+        .declarations
+        .retain(|declaration| declaration.expire_time > now);
+
+    let (ended_wars, active_wars): (Vec<_>, Vec<_>) = std::mem::take(&mut clan_wars.wars)
+        .into_iter()
+        .partition(|war| now >= war.end_time);
+    clan_wars.wars = active_wars;
+
+    for war in ended_wars.iter() {
+        end_clan_war(
+            &mut storage_service,
+            war,
+            &mut query_clans,
+            &query_member,
+            &mut server_messages,
+        );
+    }
+
     for connected_member in query_member_connected.iter() {
         let Some(clan) = connected_member
             .clan_membership
@@ -388,31 +1058,21 @@ pub fn clan_system(
             continue;
         };
 
-        let Some(&ClanMember::Online {
-            position: connected_member_position,
-            contribution: connected_member_contribution,
-            ..
-        }) = clan.find_online_member(connected_member.entity)
-        else {
+        if clan.find_online_member(connected_member.entity).is_none() {
             continue;
-        };
+        }
+
+        send_clan_info(clan, &query_member, connected_member.entity);
 
-        if let Some(game_client) = connected_member.game_client.as_ref() {
-            game_client
-                .server_message_tx
-                .send(ServerMessage::ClanInfo {
-                    id: clan.unique_id,
-                    name: clan.name.clone(),
-                    description: clan.description.clone(),
-                    mark: clan.mark,
-                    level: clan.level,
-                    points: clan.points,
-                    money: clan.money,
-                    skills: clan.skills.clone(),
-                    position: connected_member_position,
-                    contribution: connected_member_contribution,
-                })
-                .ok();
+        if !clan.notice.is_empty() {
+            if let Some(game_client) = connected_member.game_client.as_ref() {
+                game_client
+                    .server_message_tx
+                    .send(ServerMessage::ClanNotice {
+                        notice: clan.notice.clone(),
+                    })
+                    .ok();
+            }
         }
 
         // Send message to other clan members that we have connected
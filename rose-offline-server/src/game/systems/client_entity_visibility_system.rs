@@ -1,6 +1,6 @@
 use bevy::{
     ecs::{
-        prelude::{Query, Res, ResMut},
+        prelude::{Query, RemovedComponents, Res, ResMut, With},
         query::WorldQuery,
     },
     time::Time,
@@ -13,14 +13,20 @@ use crate::game::{
     components::{
         AbilityValues, CharacterInfo, Clan, ClanMembership, ClientEntity, ClientEntityId,
         ClientEntitySector, ClientEntityType, ClientEntityVisibility, Command,
-        CommandCastSkillTarget, CommandData, EntityExpireTime, Equipment, GameClient, HealthPoints,
-        ItemDrop, Level, MoveMode, MoveSpeed, Npc, NpcStandingDirection, Owner, PersonalStore,
-        Position, StatusEffects, Team,
+        CommandCastSkillTarget, CommandData, EliteMonster, EntityExpireTime, Equipment, GameClient,
+        HealthPoints, ItemDrop, Level, MoveMode, MoveSpeed, Npc, NpcStandingDirection, Owner,
+        PersonalStore, Position, Rebirth, Spectator, StatusEffects, Team,
     },
     messages::server::{ServerMessage, SpawnCommandState, SpawnEntityCharacter},
     resources::ClientEntityList,
 };
 
+// Limits how many item drops are spawned or despawned for a client each update,
+// so a large number of drops entering or leaving visibility at once are sent
+// over the following updates
+const MAX_ITEM_DROP_SPAWNS_PER_UPDATE: usize = 32;
+const MAX_ITEM_DROP_DESPAWNS_PER_UPDATE: usize = 32;
+
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 pub struct GameClientQuery<'w> {
@@ -47,6 +53,7 @@ pub struct CharacterQuery<'w> {
     team: &'w Team,
     personal_store: Option<&'w PersonalStore>,
     clan_membership: &'w ClanMembership,
+    rebirth: Option<&'w Rebirth>,
 }
 
 #[derive(WorldQuery)]
@@ -66,6 +73,7 @@ pub struct MonsterQuery<'w> {
     command: &'w Command,
     move_mode: &'w MoveMode,
     status_effects: &'w StatusEffects,
+    elite_monster: Option<&'w EliteMonster>,
 }
 
 #[derive(WorldQuery)]
@@ -159,173 +167,266 @@ pub fn client_entity_visibility_system(
     npcs_query: Query<NpcQuery>,
     clan_query: Query<&Clan>,
     query_target: Query<TargetQuery>,
+    spectator_query: Query<&ClientEntity, With<Spectator>>,
+    mut removed_spectators: RemovedComponents<Spectator>,
     mut client_entity_list: ResMut<ClientEntityList>,
     time: Res<Time>,
 ) {
+    // Spectators are hidden again after changing zone, as they have a new client entity
+    for client_entity in spectator_query.iter() {
+        if let Some(zone) = client_entity_list.get_zone_mut(client_entity.zone_id) {
+            zone.set_entity_hidden(client_entity.id, true);
+        }
+    }
+
+    for entity in removed_spectators.iter() {
+        if let Ok(client_entity) = entity_id_query.get(entity) {
+            if let Some(zone) = client_entity_list.get_zone_mut(client_entity.zone_id) {
+                zone.set_entity_hidden(client_entity.id, false);
+            }
+        }
+    }
+
     // First loop through all client entities and generate visibility changes that need to be sent
-    for mut game_client in game_clients_query.iter_mut() {
-        if let Some(client_entity_zone) = client_entity_list.get_zone(game_client.position.zone_id)
-        {
-            let sector_visible_entities = client_entity_zone
-                .get_sector_visible_entities(game_client.client_entity_sector.sector);
+    let zones = &*client_entity_list;
+    game_clients_query
+        .par_iter_mut()
+        .for_each_mut(|mut game_client| {
+            if let Some(client_entity_zone) = zones.get_zone(game_client.position.zone_id) {
+                // Nothing can have changed if no entity has changed sector and the client has not moved
+                let visibility_version = client_entity_zone.get_visibility_version();
+                if game_client.client_entity_visibility.last_update
+                    == Some((visibility_version, game_client.position.position))
+                {
+                    return;
+                }
 
-            let mut visibility_difference =
-                game_client.client_entity_visibility.entities ^ *sector_visible_entities;
+                let mut visible_entities = client_entity_zone.get_visible_entities(
+                    game_client.client_entity_sector.sector,
+                    game_client.position.position,
+                    &game_client.client_entity_visibility.entities,
+                );
 
-            // Ignore self
-            visibility_difference.set(game_client.client_entity.id.0, false);
+                // A hidden client must still receive messages about itself
+                visible_entities.set(game_client.client_entity.id.0, true);
 
-            let mut remove_entity_ids = Vec::new();
-            for index in visibility_difference.iter_ones() {
-                let is_visible = sector_visible_entities.get(index).map_or(false, |b| *b);
+                let mut visibility_difference =
+                    game_client.client_entity_visibility.entities ^ visible_entities;
 
-                if !is_visible {
-                    remove_entity_ids.push(ClientEntityId(index));
-                } else if let Some((spawn_entity, spawn_client_entity, _)) =
-                    client_entity_zone.get_entity(ClientEntityId(index))
-                {
-                    match spawn_client_entity.entity_type {
-                        ClientEntityType::Character => {
-                            if let Ok(character) = characters_query.get(*spawn_entity) {
-                                game_client
-                                    .game_client
-                                    .server_message_tx
-                                    .send(ServerMessage::SpawnEntityCharacter {
-                                        data: Box::new(SpawnEntityCharacter {
+                // Ignore self
+                visibility_difference.set(game_client.client_entity.id.0, false);
+
+                let mut remove_entity_ids = Vec::new();
+                let mut num_item_drop_spawns = 0;
+                let mut num_item_drop_despawns = 0;
+                let mut deferred_entity_ids = Vec::new();
+                let mut deferred_remove_entity_ids = Vec::new();
+                for index in visibility_difference.iter_ones() {
+                    let is_visible = visible_entities.get(index).map_or(false, |b| *b);
+
+                    if !is_visible {
+                        // Only item drops which stay in the zone can be deferred, the id
+                        // of an entity leaving the zone can be reused by a new entity
+                        let is_item_drop = !client_entity_zone
+                            .is_entity_leaving(ClientEntityId(index))
+                            && client_entity_zone.get_entity(ClientEntityId(index)).map_or(
+                                false,
+                                |(_, client_entity, _)| {
+                                    client_entity.entity_type == ClientEntityType::ItemDrop
+                                },
+                            );
+                        if is_item_drop {
+                            if num_item_drop_despawns >= MAX_ITEM_DROP_DESPAWNS_PER_UPDATE {
+                                deferred_remove_entity_ids.push(index);
+                                continue;
+                            }
+                            num_item_drop_despawns += 1;
+                        }
+
+                        remove_entity_ids.push(ClientEntityId(index));
+                    } else if let Some((spawn_entity, spawn_client_entity, _)) =
+                        client_entity_zone.get_entity(ClientEntityId(index))
+                    {
+                        match spawn_client_entity.entity_type {
+                            ClientEntityType::Character => {
+                                if let Ok(character) = characters_query.get(*spawn_entity) {
+                                    game_client
+                                        .game_client
+                                        .server_message_tx
+                                        .send(ServerMessage::SpawnEntityCharacter {
+                                            data: Box::new(SpawnEntityCharacter {
+                                                entity_id: spawn_client_entity.id,
+                                                character_info: character.character_info.clone(),
+                                                position: character.position.position,
+                                                health: *character.health_points,
+                                                team: character.team.clone(),
+                                                equipment: character.equipment.clone(),
+                                                level: *character.level,
+                                                move_mode: *character.move_mode,
+                                                move_speed: *character.move_speed,
+                                                passive_attack_speed: character
+                                                    .ability_values
+                                                    .passive_attack_speed,
+                                                status_effects: character
+                                                    .status_effects
+                                                    .active
+                                                    .clone(),
+                                                spawn_command_state: spawn_command_state(
+                                                    character.command,
+                                                    &query_target,
+                                                ),
+                                                personal_store_info: character.personal_store.map(
+                                                    |personal_store| {
+                                                        (
+                                                            personal_store.skin,
+                                                            personal_store.title.clone(),
+                                                        )
+                                                    },
+                                                ),
+                                                clan_membership: character
+                                                    .clan_membership
+                                                    .and_then(|clan_entity| {
+                                                        if let Ok(clan) =
+                                                            clan_query.get(clan_entity)
+                                                        {
+                                                            Some(CharacterClanMembership {
+                                                                clan_unique_id: clan.unique_id,
+                                                                mark: clan.mark,
+                                                                level: clan.level,
+                                                                name: clan.name.clone(),
+                                                                position: clan
+                                                                    .find_online_member(
+                                                                        *spawn_entity,
+                                                                    )
+                                                                    .map_or(
+                                                                        ClanMemberPosition::Junior,
+                                                                        |member| member.position(),
+                                                                    ),
+                                                            })
+                                                        } else {
+                                                            None
+                                                        }
+                                                    }),
+                                                rebirth_count: character
+                                                    .rebirth
+                                                    .map_or(0, |rebirth| rebirth.count),
+                                            }),
+                                        })
+                                        .ok();
+                                }
+                            }
+                            ClientEntityType::ItemDrop => {
+                                if num_item_drop_spawns >= MAX_ITEM_DROP_SPAWNS_PER_UPDATE {
+                                    deferred_entity_ids.push(index);
+                                    continue;
+                                }
+
+                                if let Ok(item_drop) = item_drop_query.get(*spawn_entity) {
+                                    if let Some(dropped_item) = item_drop.item_drop.item.clone() {
+                                        let owner_entity_id = item_drop
+                                            .owner
+                                            .and_then(|owner| {
+                                                entity_id_query.get(owner.entity).ok()
+                                            })
+                                            .map(|owner_client_entity| owner_client_entity.id);
+
+                                        game_client
+                                            .game_client
+                                            .server_message_tx
+                                            .send(ServerMessage::SpawnEntityItemDrop {
+                                                entity_id: spawn_client_entity.id,
+                                                dropped_item,
+                                                position: item_drop.position.position,
+                                                remaining_time: item_drop.expire_time.when
+                                                    - time.last_update().unwrap(),
+                                                owner_entity_id,
+                                            })
+                                            .ok();
+                                        num_item_drop_spawns += 1;
+                                    }
+                                }
+                            }
+                            ClientEntityType::Monster => {
+                                if let Ok(monster) = monsters_query.get(*spawn_entity) {
+                                    game_client
+                                        .game_client
+                                        .server_message_tx
+                                        .send(ServerMessage::SpawnEntityMonster {
                                             entity_id: spawn_client_entity.id,
-                                            character_info: character.character_info.clone(),
-                                            position: character.position.position,
-                                            health: *character.health_points,
-                                            team: character.team.clone(),
-                                            equipment: character.equipment.clone(),
-                                            level: *character.level,
-                                            move_mode: *character.move_mode,
-                                            move_speed: *character.move_speed,
-                                            passive_attack_speed: character
-                                                .ability_values
-                                                .passive_attack_speed,
-                                            status_effects: character.status_effects.active.clone(),
+                                            npc: monster.npc.clone(),
+                                            position: monster.position.position,
+                                            team: monster.team.clone(),
+                                            health: *monster.health,
                                             spawn_command_state: spawn_command_state(
-                                                character.command,
+                                                monster.command,
                                                 &query_target,
                                             ),
-                                            personal_store_info: character.personal_store.map(
-                                                |personal_store| {
-                                                    (
-                                                        personal_store.skin,
-                                                        personal_store.title.clone(),
-                                                    )
-                                                },
-                                            ),
-                                            clan_membership: character.clan_membership.and_then(
-                                                |clan_entity| {
-                                                    if let Ok(clan) = clan_query.get(clan_entity) {
-                                                        Some(CharacterClanMembership {
-                                                            clan_unique_id: clan.unique_id,
-                                                            mark: clan.mark,
-                                                            level: clan.level,
-                                                            name: clan.name.clone(),
-                                                            position: clan
-                                                                .find_online_member(*spawn_entity)
-                                                                .map_or(
-                                                                    ClanMemberPosition::Junior,
-                                                                    |member| member.position(),
-                                                                ),
-                                                        })
-                                                    } else {
-                                                        None
-                                                    }
-                                                },
-                                            ),
-                                        }),
-                                    })
-                                    .ok();
+                                            move_mode: *monster.move_mode,
+                                            status_effects: monster.status_effects.active.clone(),
+                                            is_elite: monster.elite_monster.is_some(),
+                                        })
+                                        .ok();
+                                }
                             }
-                        }
-                        ClientEntityType::ItemDrop => {
-                            if let Ok(item_drop) = item_drop_query.get(*spawn_entity) {
-                                if let Some(dropped_item) = item_drop.item_drop.item.clone() {
-                                    let owner_entity_id = item_drop
-                                        .owner
-                                        .and_then(|owner| entity_id_query.get(owner.entity).ok())
-                                        .map(|owner_client_entity| owner_client_entity.id);
-
+                            ClientEntityType::Npc => {
+                                if let Ok(npc) = npcs_query.get(*spawn_entity) {
                                     game_client
                                         .game_client
                                         .server_message_tx
-                                        .send(ServerMessage::SpawnEntityItemDrop {
+                                        .send(ServerMessage::SpawnEntityNpc {
                                             entity_id: spawn_client_entity.id,
-                                            dropped_item,
-                                            position: item_drop.position.position,
-                                            remaining_time: item_drop.expire_time.when
-                                                - time.last_update().unwrap(),
-                                            owner_entity_id,
+                                            npc: npc.npc.clone(),
+                                            direction: npc.direction.direction,
+                                            position: npc.position.position,
+                                            team: npc.team.clone(),
+                                            health: *npc.health,
+                                            spawn_command_state: spawn_command_state(
+                                                npc.command,
+                                                &query_target,
+                                            ),
+                                            move_mode: *npc.move_mode,
+                                            status_effects: npc.status_effects.active.clone(),
                                         })
                                         .ok();
                                 }
                             }
                         }
-                        ClientEntityType::Monster => {
-                            if let Ok(monster) = monsters_query.get(*spawn_entity) {
-                                game_client
-                                    .game_client
-                                    .server_message_tx
-                                    .send(ServerMessage::SpawnEntityMonster {
-                                        entity_id: spawn_client_entity.id,
-                                        npc: monster.npc.clone(),
-                                        position: monster.position.position,
-                                        team: monster.team.clone(),
-                                        health: *monster.health,
-                                        spawn_command_state: spawn_command_state(
-                                            monster.command,
-                                            &query_target,
-                                        ),
-                                        move_mode: *monster.move_mode,
-                                        status_effects: monster.status_effects.active.clone(),
-                                    })
-                                    .ok();
-                            }
-                        }
-                        ClientEntityType::Npc => {
-                            if let Ok(npc) = npcs_query.get(*spawn_entity) {
-                                game_client
-                                    .game_client
-                                    .server_message_tx
-                                    .send(ServerMessage::SpawnEntityNpc {
-                                        entity_id: spawn_client_entity.id,
-                                        npc: npc.npc.clone(),
-                                        direction: npc.direction.direction,
-                                        position: npc.position.position,
-                                        team: npc.team.clone(),
-                                        health: *npc.health,
-                                        spawn_command_state: spawn_command_state(
-                                            npc.command,
-                                            &query_target,
-                                        ),
-                                        move_mode: *npc.move_mode,
-                                        status_effects: npc.status_effects.active.clone(),
-                                    })
-                                    .ok();
-                            }
-                        }
                     }
                 }
-            }
 
-            if !remove_entity_ids.is_empty() {
-                game_client
-                    .game_client
-                    .server_message_tx
-                    .send(ServerMessage::RemoveEntities {
-                        entity_ids: remove_entity_ids,
-                    })
-                    .ok();
-            }
+                if !remove_entity_ids.is_empty() {
+                    game_client
+                        .game_client
+                        .server_message_tx
+                        .send(ServerMessage::RemoveEntities {
+                            entity_ids: remove_entity_ids,
+                        })
+                        .ok();
+                }
 
-            // Update visibility
-            game_client.client_entity_visibility.entities = *sector_visible_entities;
-        }
-    }
+                // Update visibility, deferred entities must be spawned or despawned on
+                // a later update
+                game_client.client_entity_visibility.entities = visible_entities;
+                for &index in deferred_entity_ids.iter() {
+                    game_client
+                        .client_entity_visibility
+                        .entities
+                        .set(index, false);
+                }
+                for &index in deferred_remove_entity_ids.iter() {
+                    game_client
+                        .client_entity_visibility
+                        .entities
+                        .set(index, true);
+                }
+                game_client.client_entity_visibility.last_update =
+                    if deferred_entity_ids.is_empty() && deferred_remove_entity_ids.is_empty() {
+                        Some((visibility_version, game_client.position.position))
+                    } else {
+                        None
+                    };
+            }
+        });
 
     client_entity_list.process_zone_leavers();
 }
@@ -0,0 +1,95 @@
+use bevy::{
+    prelude::{Added, Commands, Entity, EventReader, EventWriter, Or, Query, Res, ResMut, With},
+    time::Time,
+};
+use log::warn;
+
+use rose_game_common::messages::server::ServerMessage;
+
+use crate::game::{
+    components::{Account, CharacterInfo, GameClient},
+    events::{ClientIntegrityEvent, SuspicionEvent, SuspicionSignal},
+    resources::ClientIntegrity,
+};
+
+pub fn client_integrity_system(
+    mut commands: Commands,
+    client_integrity: Option<ResMut<ClientIntegrity>>,
+    query_joined: Query<
+        (Entity, &GameClient),
+        (
+            With<CharacterInfo>,
+            Or<(Added<GameClient>, Added<CharacterInfo>)>,
+        ),
+    >,
+    query_client: Query<(&Account, &CharacterInfo, Option<&GameClient>)>,
+    query_connected: Query<(), With<GameClient>>,
+    mut client_integrity_events: EventReader<ClientIntegrityEvent>,
+    mut suspicion_events: EventWriter<SuspicionEvent>,
+    time: Res<Time>,
+) {
+    let Some(mut client_integrity) = client_integrity else {
+        client_integrity_events.clear();
+        return;
+    };
+    let now = time.last_update().unwrap();
+
+    // Characters which just joined the game, or a client which reconnected
+    // to its character, are sent a new challenge
+    for (entity, game_client) in query_joined.iter() {
+        let paths = client_integrity.start_challenge(entity, now);
+        if !paths.is_empty() {
+            game_client
+                .server_message_tx
+                .send(ServerMessage::ClientIntegrityChallenge { paths })
+                .ok();
+        }
+    }
+
+    let mut mismatches = Vec::new();
+    for event in client_integrity_events.iter() {
+        if let Some(paths) = client_integrity.check_response(event.entity, &event.hashes) {
+            if !paths.is_empty() {
+                mismatches.push((event.entity, paths));
+            }
+        }
+    }
+    mismatches.extend(client_integrity.take_expired(now));
+
+    for (entity, paths) in mismatches {
+        let Ok((account, character_info, game_client)) = query_client.get(entity) else {
+            continue;
+        };
+
+        warn!(
+            target: "audit",
+            "Client integrity mismatch from account {} character {} at {} for files {}",
+            account.name,
+            character_info.name,
+            game_client.map_or_else(
+                || String::from("unknown"),
+                |game_client| game_client.ip.to_string()
+            ),
+            paths.join(", ")
+        );
+        suspicion_events.send(SuspicionEvent::new(
+            entity,
+            SuspicionSignal::ClientIntegrity,
+        ));
+
+        if let (true, Some(game_client)) = (client_integrity.kick_on_mismatch(), game_client) {
+            game_client
+                .server_message_tx
+                .send(ServerMessage::Whisper {
+                    from: String::from("SERVER"),
+                    text: String::from("You have been disconnected for modified game data"),
+                })
+                .ok();
+
+            // Removing the client component closes its connection
+            commands.entity(entity).remove::<GameClient>();
+        }
+    }
+
+    client_integrity.retain(|entity| query_connected.contains(entity));
+}
@@ -4,13 +4,14 @@ use bevy::{
     ecs::{
         prelude::{Commands, Entity, EventWriter, Query, Res, ResMut},
         query::WorldQuery,
+        system::SystemParam,
     },
     math::{Vec3, Vec3Swizzles},
     time::Time,
 };
 
 use rose_data::{
-    AmmoIndex, EquipmentIndex, ItemClass, SkillActionMode, SkillId, SkillType, VehiclePartIndex,
+    EquipmentIndex, SkillActionMode, SkillId, SkillType, StatusEffectType, VehiclePartIndex,
 };
 use rose_game_common::components::{CharacterGender, CharacterInfo};
 
@@ -20,22 +21,23 @@ use crate::game::{
         SkillCasterBundle, SkillTargetBundle,
     },
     components::{
-        AbilityValues, ClientEntity, ClientEntitySector, ClientEntityType, Command,
+        AbilityValues, ClanMembership, ClientEntity, ClientEntitySector, ClientEntityType, Command,
         CommandCastSkillTarget, CommandData, Equipment, GameClient, HealthPoints, ItemDrop,
-        MotionData, MoveMode, MoveSpeed, NextCommand, Npc, Owner, PartyOwner, PersonalStore,
-        Position, Team,
+        MotionData, MoveMode, MovePath, MoveSpeed, MovementImpairment, NextCommand, Npc, Owner,
+        PartyOwner, PersonalStore, Position, PositionHistory, StatusEffects, Team, Weight,
     },
     events::{
         DamageEvent, ItemLifeEvent, PickupItemEvent, SkillEvent, SkillEventTarget, UseAmmoEvent,
     },
     messages::server::ServerMessage,
-    resources::{GameData, ServerMessages},
+    resources::{ClanWars, GameConfig, GameData, ServerMessages, ZoneGeometry, ZoneList},
 };
 
 const NPC_MOVE_TO_DISTANCE: f32 = 250.0;
 const CHARACTER_MOVE_TO_DISTANCE: f32 = 1000.0;
 const DROPPED_ITEM_MOVE_TO_DISTANCE: f32 = 150.0;
 const DROPPED_ITEM_PICKUP_DISTANCE: f32 = 200.0;
+const MOVE_PATH_REPLAN_DISTANCE: f32 = 500.0;
 
 #[derive(WorldQuery)]
 #[world_query(mutable)]
@@ -53,10 +55,15 @@ pub struct QueryCommandEntity<'w> {
     team: &'w Team,
 
     character_info: Option<&'w CharacterInfo>,
+    clan_membership: Option<&'w ClanMembership>,
     equipment: Option<&'w Equipment>,
     game_client: Option<&'w GameClient>,
+    move_path: Option<&'w mut MovePath>,
+    movement_impairment: Option<&'w MovementImpairment>,
     npc: Option<&'w Npc>,
     personal_store: Option<&'w PersonalStore>,
+    status_effects: Option<&'w StatusEffects>,
+    weight: Option<&'w Weight>,
 }
 
 #[derive(WorldQuery)]
@@ -66,6 +73,8 @@ pub struct CommandAttackTargetQuery<'w> {
     health_points: &'w HealthPoints,
     position: &'w Position,
     team: &'w Team,
+    clan_membership: Option<&'w ClanMembership>,
+    position_history: Option<&'w PositionHistory>,
 }
 
 #[derive(WorldQuery)]
@@ -85,6 +94,15 @@ pub struct CommandPickupItemTargetQuery<'w> {
     party_owner: Option<&'w PartyOwner>,
 }
 
+#[derive(SystemParam)]
+pub struct CommandEvents<'w> {
+    damage_events: EventWriter<'w, DamageEvent>,
+    item_life_events: EventWriter<'w, ItemLifeEvent>,
+    pickup_item_events: EventWriter<'w, PickupItemEvent>,
+    skill_events: EventWriter<'w, SkillEvent>,
+    use_ammo_events: EventWriter<'w, UseAmmoEvent>,
+}
+
 fn command_stop(
     command: &mut Command,
     client_entity: &ClientEntity,
@@ -106,6 +124,30 @@ fn command_stop(
     *command = Command::with_stop();
 }
 
+/// Returns the multiplier applied to the speed of attack motions.
+fn get_attack_speed(ability_values: &AbilityValues) -> f32 {
+    i32::max(ability_values.get_attack_speed(), 30) as f32 / 100.0
+}
+
+/// Returns the distance to the target, when latency compensation is enabled
+/// for a player this is the closest the target has been since
+/// `position_history_since`.
+fn get_target_distance(
+    position: &Position,
+    target_position: &Position,
+    target_position_history: Option<&PositionHistory>,
+    position_history_since: Option<Instant>,
+) -> f32 {
+    match (target_position_history, position_history_since) {
+        (Some(target_position_history), Some(since)) => target_position_history
+            .get_min_distance_xy(position.position.xy(), target_position.position, since),
+        _ => position
+            .position
+            .xy()
+            .distance(target_position.position.xy()),
+    }
+}
+
 fn is_valid_move_target(target: &CommandMoveTargetQueryItem, position: &Position) -> bool {
     if target.position.zone_id != position.zone_id {
         return false;
@@ -118,8 +160,31 @@ fn is_valid_attack_target(
     target: &CommandAttackTargetQueryItem,
     position: &Position,
     team: &Team,
+    clan_membership: Option<&ClanMembership>,
+    clan_wars: &ClanWars,
+    zone_list: &ZoneList,
 ) -> bool {
-    if target.team.id == team.id || target.team.id == Team::DEFAULT_NPC_TEAM_ID {
+    if target.team.id == Team::DEFAULT_NPC_TEAM_ID {
+        return false;
+    }
+
+    if matches!(
+        target.client_entity.entity_type,
+        ClientEntityType::Character
+    ) && zone_list.is_safe_zone(target.position.zone_id)
+    {
+        return false;
+    }
+
+    // Members of clans which are at war can attack each other whilst on the same team
+    if target.team.id == team.id
+        && !clan_wars.is_at_war(
+            clan_membership.and_then(|clan_membership| clan_membership.clan()),
+            target
+                .clan_membership
+                .and_then(|clan_membership| clan_membership.clan()),
+        )
+    {
         return false;
     }
 
@@ -158,6 +223,7 @@ fn can_cast_skill(
     skill_id: SkillId,
     query_skill_caster: &Query<SkillCasterBundle>,
     query_skill_target: &Query<SkillTargetBundle>,
+    clan_wars: &ClanWars,
 ) -> bool {
     let Ok(skill_caster) = query_skill_caster.get(command_entity) else {
         return false;
@@ -177,7 +243,7 @@ fn can_cast_skill(
                 return false;
             };
 
-            if !skill_can_target_entity(&skill_caster, &skill_target, skill_data) {
+            if !skill_can_target_entity(&skill_caster, &skill_target, skill_data, clan_wars) {
                 return false;
             }
         }
@@ -210,21 +276,26 @@ pub fn command_system(
     query_move_target: Query<CommandMoveTargetQuery>,
     query_attack_target: Query<CommandAttackTargetQuery>,
     mut query_pickup_item: Query<CommandPickupItemTargetQuery>,
-    query_position: Query<(&ClientEntity, &Position)>,
+    query_position: Query<(&ClientEntity, &Position, Option<&PositionHistory>)>,
     query_skill_target: Query<SkillTargetBundle>,
     query_skill_caster: Query<SkillCasterBundle>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
+    clan_wars: Res<ClanWars>,
+    zone_geometry: Res<ZoneGeometry>,
+    zone_list: Res<ZoneList>,
     time: Res<Time>,
-    mut damage_events: EventWriter<DamageEvent>,
-    mut skill_events: EventWriter<SkillEvent>,
-    mut pickup_item_event: EventWriter<PickupItemEvent>,
-    mut item_life_event: EventWriter<ItemLifeEvent>,
-    mut use_ammo_event: EventWriter<UseAmmoEvent>,
+    mut command_events: CommandEvents,
     mut server_messages: ResMut<ServerMessages>,
 ) {
     let Some(now) = time.last_update() else {
         return;
     };
+    // Latency compensation only applies to attacks from players, whose view
+    // of their target can be behind its position on the server
+    let player_position_history_since = game_config
+        .latency_compensation
+        .and_then(|latency_compensation| now.checked_sub(latency_compensation));
 
     for mut command_entity in query_command_entity.iter_mut() {
         if command_entity.command.is_dead() {
@@ -232,6 +303,41 @@ pub fn command_system(
             command_entity.next_command.command = None;
         }
 
+        let is_stunned = command_entity
+            .movement_impairment
+            .map_or(false, |movement_impairment| {
+                movement_impairment.is_stunned(now)
+            })
+            || command_entity
+                .status_effects
+                .map_or(false, |status_effects| {
+                    status_effects.active[StatusEffectType::Fainting].is_some()
+                        || status_effects.active[StatusEffectType::Sleep].is_some()
+                });
+        let is_rooted = is_stunned
+            || command_entity
+                .movement_impairment
+                .map_or(false, |movement_impairment| {
+                    movement_impairment.is_rooted(now)
+                });
+        let is_move_command = matches!(
+            command_entity.next_command.command,
+            Some(CommandData::Move { .. })
+        );
+
+        if (is_stunned && command_entity.next_command.command.is_some())
+            || (is_rooted && is_move_command)
+        {
+            // Reject the command, the client will have already started to perform it
+            command_stop(
+                &mut command_entity.command,
+                command_entity.client_entity,
+                command_entity.position,
+                Some(&mut server_messages),
+            );
+            *command_entity.next_command = NextCommand::default();
+        }
+
         if !command_entity.next_command.has_sent_server_message
             && command_entity.next_command.command.is_some()
         {
@@ -262,6 +368,36 @@ pub fn command_system(
                         } else {
                             *target = None;
                         }
+                    } else if command_entity.game_client.is_some() {
+                        // Players cannot move to a destination which is not movable
+                        if let Some(nav_grid) = game_data
+                            .zone_nav_grids
+                            .get_zone_nav_grid(command_entity.position.zone_id)
+                        {
+                            if !nav_grid.contains(destination.xy()) {
+                                log::warn!(
+                                    "Rejected move of entity {:?} to {:?} outside of zone {}",
+                                    command_entity.entity,
+                                    destination,
+                                    command_entity.position.zone_id.get()
+                                );
+                                command_stop(
+                                    &mut command_entity.command,
+                                    command_entity.client_entity,
+                                    command_entity.position,
+                                    Some(&mut server_messages),
+                                );
+                                *command_entity.next_command = NextCommand::default();
+                                continue;
+                            }
+
+                            let clamped = nav_grid.clamp_destination(
+                                command_entity.position.position.xy(),
+                                destination.xy(),
+                            );
+                            destination.x = clamped.x;
+                            destination.y = clamped.y;
+                        }
                     }
 
                     let distance = command_entity
@@ -294,6 +430,9 @@ pub fn command_system(
                                     target,
                                     command_entity.position,
                                     command_entity.team,
+                                    command_entity.clan_membership,
+                                    &clan_wars,
+                                    &zone_list,
                                 )
                             })
                     {
@@ -332,10 +471,11 @@ pub fn command_system(
                         skill_id,
                         &query_skill_caster,
                         &query_skill_target,
+                        &clan_wars,
                     ) {
                         match skill_target {
                             Some(CommandCastSkillTarget::Entity(target_entity)) => {
-                                let (target_client_entity, target_position) =
+                                let (target_client_entity, target_position, _) =
                                     query_position.get(*target_entity).unwrap();
                                 let distance = command_entity
                                     .position
@@ -386,10 +526,51 @@ pub fn command_system(
 
         command_entity.command.duration += time.delta();
 
+        if let CommandData::Attack {
+            target: target_entity,
+        } = command_entity.command.command
+        {
+            if let Some(attack_hit_duration) = command_entity.command.attack_hit_duration {
+                if is_stunned {
+                    // The attack was interrupted before it could hit
+                    command_entity.command.attack_hit_duration = None;
+                } else if command_entity.command.duration
+                    >= attack_hit_duration.div_f32(get_attack_speed(command_entity.ability_values))
+                {
+                    command_entity.command.attack_hit_duration = None;
+
+                    if let Some(target) =
+                        query_attack_target
+                            .get(target_entity)
+                            .ok()
+                            .filter(|target| {
+                                target.health_points.hp > 0
+                                    && target.position.zone_id == command_entity.position.zone_id
+                            })
+                    {
+                        let hit_count = command_entity
+                            .motion_data
+                            .get_attack()
+                            .map_or(0, |attack_motion| attack_motion.total_attack_frames);
+
+                        // Send damage event to damage system
+                        command_events.damage_events.send(DamageEvent::Attack {
+                            attacker: command_entity.entity,
+                            defender: target_entity,
+                            damage: game_data.ability_value_calculator.calculate_damage(
+                                command_entity.ability_values,
+                                target.ability_values,
+                                hit_count as i32,
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
         let required_duration = match &mut command_entity.command.command {
             CommandData::Attack { .. } => {
-                let attack_speed =
-                    i32::max(command_entity.ability_values.get_attack_speed(), 30) as f32 / 100.0;
+                let attack_speed = get_attack_speed(command_entity.ability_values);
                 command_entity
                     .command
                     .required_duration
@@ -499,6 +680,7 @@ pub fn command_system(
                 move_mode: command_move_mode,
             } => {
                 let mut entity_commands = commands.entity(command_entity.entity);
+                let mut target_entity_id = None;
 
                 if let Some(target_entity) = *target {
                     if let Some(target) = query_move_target
@@ -506,6 +688,7 @@ pub fn command_system(
                         .ok()
                         .filter(|target| is_valid_move_target(target, command_entity.position))
                     {
+                        target_entity_id = Some(target.client_entity.id);
                         let required_distance = match target.client_entity.entity_type {
                             ClientEntityType::Character => Some(CHARACTER_MOVE_TO_DISTANCE),
                             ClientEntityType::Npc => Some(NPC_MOVE_TO_DISTANCE),
@@ -559,11 +742,89 @@ pub fn command_system(
                     .xy()
                     .distance(destination.xy());
                 if distance < 0.1 {
+                    if command_entity.move_path.is_some() {
+                        entity_commands.remove::<MovePath>();
+                    }
                     *command_entity.command = Command::with_stop();
-                } else {
-                    *command_entity.command =
-                        Command::with_move(*destination, *target, *command_move_mode);
+                    continue;
+                }
+
+                // Non-player entities path find around terrain which is not movable
+                let mut move_destination = *destination;
+                if let Some(nav_grid) = game_data
+                    .zone_nav_grids
+                    .get_zone_nav_grid(command_entity.position.zone_id)
+                    .filter(|_| command_entity.game_client.is_none())
+                {
+                    let position = command_entity.position.position.xy();
+                    let mut move_path = command_entity
+                        .move_path
+                        .as_deref()
+                        .filter(|move_path| {
+                            move_path.zone_id == command_entity.position.zone_id
+                                && move_path.destination.xy().distance(destination.xy())
+                                    < MOVE_PATH_REPLAN_DISTANCE
+                                && nav_grid
+                                    .has_line_of_sight(position, move_path.get_next_waypoint().xy())
+                        })
+                        .cloned();
+                    let mut waypoint_changed = false;
+
+                    if move_path.is_none() {
+                        // If no path can be found we fall back to moving in a straight line
+                        let new_move_path = MovePath::new(
+                            command_entity.position.zone_id,
+                            *destination,
+                            nav_grid
+                                .find_path(position, destination.xy())
+                                .unwrap_or_default(),
+                        );
+                        waypoint_changed = !new_move_path.waypoints.is_empty();
+                        move_path = Some(new_move_path);
+                    }
+
+                    let mut move_path = move_path.unwrap();
+                    while move_path
+                        .waypoints
+                        .last()
+                        .map_or(false, |waypoint| waypoint.distance(position) < 0.1)
+                    {
+                        move_path.waypoints.pop();
+                        waypoint_changed = true;
+                    }
+
+                    if let Some(waypoint) = move_path.waypoints.last() {
+                        move_destination = Vec3::new(waypoint.x, waypoint.y, destination.z);
+                    }
+
+                    if waypoint_changed {
+                        server_messages.send_entity_message(
+                            command_entity.client_entity,
+                            ServerMessage::MoveEntity {
+                                entity_id: command_entity.client_entity.id,
+                                target_entity_id: if move_path.waypoints.is_empty() {
+                                    target_entity_id
+                                } else {
+                                    None
+                                },
+                                distance: position.distance(move_destination.xy()) as u16,
+                                x: move_destination.x,
+                                y: move_destination.y,
+                                z: move_destination.z as u16,
+                                move_mode: *command_move_mode,
+                            },
+                        );
+                    }
+
+                    if let Some(current_move_path) = command_entity.move_path.as_deref_mut() {
+                        *current_move_path = move_path;
+                    } else {
+                        entity_commands.insert(move_path);
+                    }
                 }
+
+                *command_entity.command =
+                    Command::with_move(move_destination, *target, *command_move_mode);
             }
             &mut CommandData::PickupItemDrop {
                 target: target_entity,
@@ -575,7 +836,7 @@ pub fn command_system(
                         is_valid_pickup_target(&target, command_entity.position)
                     })
                 {
-                    pickup_item_event.send(PickupItemEvent {
+                    command_events.pickup_item_events.send(PickupItemEvent {
                         pickup_entity: command_entity.entity,
                         item_entity: target_entity,
                     });
@@ -597,11 +858,33 @@ pub fn command_system(
             &mut CommandData::Attack {
                 target: target_entity,
             } => {
+                if command_entity
+                    .weight
+                    .map_or(false, |weight| !weight.can_attack())
+                {
+                    // Cannot attack whilst over max weight, cancel command.
+                    command_stop(
+                        &mut command_entity.command,
+                        command_entity.client_entity,
+                        command_entity.position,
+                        Some(&mut server_messages),
+                    );
+                    *command_entity.next_command = NextCommand::default();
+                    continue;
+                }
+
                 let Some(target) = query_attack_target
                     .get(target_entity)
                     .ok()
                     .filter(|target| {
-                        is_valid_attack_target(target, command_entity.position, command_entity.team)
+                        is_valid_attack_target(
+                            target,
+                            command_entity.position,
+                            command_entity.team,
+                            command_entity.clan_membership,
+                            &clan_wars,
+                            &zone_list,
+                        )
                     })
                 else {
                     // Cannot attack target, cancel command.
@@ -616,11 +899,14 @@ pub fn command_system(
                 };
 
                 let attack_range = command_entity.ability_values.get_attack_range() as f32;
-                let distance = command_entity
-                    .position
-                    .position
-                    .xy()
-                    .distance(target.position.position.xy());
+                let distance = get_target_distance(
+                    command_entity.position,
+                    target.position,
+                    target.position_history,
+                    command_entity
+                        .game_client
+                        .and(player_position_history_since),
+                );
                 if attack_range < distance {
                     // Not in range, set current command to move
                     *command_entity.command = Command::with_move(
@@ -631,15 +917,37 @@ pub fn command_system(
                     continue;
                 }
 
+                if !zone_geometry.has_line_of_sight(
+                    command_entity.position.zone_id,
+                    command_entity.position.position,
+                    target.position.position,
+                ) {
+                    // Target is blocked by terrain or an obstacle, cancel command.
+                    command_stop(
+                        &mut command_entity.command,
+                        command_entity.client_entity,
+                        command_entity.position,
+                        Some(&mut server_messages),
+                    );
+                    *command_entity.next_command = NextCommand::default();
+                    continue;
+                }
+
                 let mut cancel_attack = false;
 
-                let (attack_duration, hit_count) =
+                let (attack_duration, attack_hit_duration, hit_count) =
                     if let Some(attack_motion) = command_entity.motion_data.get_attack() {
-                        (attack_motion.duration, attack_motion.total_attack_frames)
+                        (
+                            attack_motion.duration,
+                            attack_motion
+                                .get_first_attack_frame_time()
+                                .unwrap_or_default(),
+                            attack_motion.total_attack_frames,
+                        )
                     } else {
                         // No attack animation, cancel attack
                         cancel_attack = true;
-                        (Duration::ZERO, 0)
+                        (Duration::ZERO, Duration::ZERO, 0)
                     };
 
                 if matches!(command_entity.move_mode, MoveMode::Drive) {
@@ -675,21 +983,16 @@ pub fn command_system(
                     if !cancel_attack {
                         if let Some(equipment) = command_entity.equipment {
                             if let Some(weapon_item_data) = weapon_item_data {
-                                let ammo_index = match weapon_item_data.item_data.class {
-                                    ItemClass::Bow | ItemClass::Crossbow => Some(AmmoIndex::Arrow),
-                                    ItemClass::Gun | ItemClass::DualGuns => Some(AmmoIndex::Bullet),
-                                    ItemClass::Launcher => Some(AmmoIndex::Throw),
-                                    _ => None,
-                                };
-
-                                if let Some(ammo_index) = ammo_index {
+                                if let Some(ammo_index) =
+                                    weapon_item_data.item_data.class.weapon_ammo_index()
+                                {
                                     if equipment
                                         .get_ammo_item(ammo_index)
                                         .map_or(false, |ammo_item| {
                                             ammo_item.quantity >= hit_count as u32
                                         })
                                     {
-                                        use_ammo_event.send(UseAmmoEvent {
+                                        command_events.use_ammo_events.send(UseAmmoEvent {
                                             entity: command_entity.entity,
                                             ammo_index,
                                             quantity: hit_count,
@@ -718,32 +1021,27 @@ pub fn command_system(
 
                 if matches!(command_entity.move_mode, MoveMode::Drive) {
                     // Decrease vehicle engine item life on attack
-                    item_life_event.send(ItemLifeEvent::DecreaseVehicleEngineLife {
-                        entity: command_entity.entity,
-                        amount: None,
-                    });
+                    command_events.item_life_events.send(
+                        ItemLifeEvent::DecreaseVehicleEngineLife {
+                            entity: command_entity.entity,
+                            amount: None,
+                        },
+                    );
                 }
 
                 // Decrease weapon item life on attack
                 if command_entity.character_info.is_some() {
-                    item_life_event.send(ItemLifeEvent::DecreaseWeaponLife {
-                        entity: command_entity.entity,
-                    });
+                    command_events
+                        .item_life_events
+                        .send(ItemLifeEvent::DecreaseWeaponLife {
+                            entity: command_entity.entity,
+                        });
                 }
 
-                // In range, set current command to attack
-                *command_entity.command = Command::with_attack(target_entity, attack_duration);
-
-                // Send damage event to damage system
-                damage_events.send(DamageEvent::Attack {
-                    attacker: command_entity.entity,
-                    defender: target_entity,
-                    damage: game_data.ability_value_calculator.calculate_damage(
-                        command_entity.ability_values,
-                        target.ability_values,
-                        hit_count as i32,
-                    ),
-                });
+                // In range, set current command to attack, the damage is applied
+                // once the attack motion reaches its attack frame
+                *command_entity.command =
+                    Command::with_attack(target_entity, attack_duration, attack_hit_duration);
             }
             &mut CommandData::CastSkill {
                 skill_id,
@@ -760,6 +1058,7 @@ pub fn command_system(
                     skill_id,
                     &query_skill_caster,
                     &query_skill_target,
+                    &clan_wars,
                 ) {
                     // Cannot use skill, cancel command.
                     command_stop(
@@ -774,16 +1073,35 @@ pub fn command_system(
 
                 let skill_data = game_data.skills.get_skill(skill_id).unwrap();
 
-                let (target_position, target_entity) = match skill_target {
+                let (target_position, target_entity, target_distance) = match skill_target {
                     Some(CommandCastSkillTarget::Entity(target_entity)) => {
-                        let (_, target_position) = query_position.get(target_entity).unwrap();
-                        (Some(target_position.position), Some(target_entity))
+                        let (_, target_position, target_position_history) =
+                            query_position.get(target_entity).unwrap();
+                        (
+                            Some(target_position.position),
+                            Some(target_entity),
+                            Some(get_target_distance(
+                                command_entity.position,
+                                target_position,
+                                target_position_history,
+                                command_entity
+                                    .game_client
+                                    .and(player_position_history_since),
+                            )),
+                        )
                     }
                     Some(CommandCastSkillTarget::Position(target_position)) => (
                         Some(Vec3::new(target_position.x, target_position.y, 0.0)),
                         None,
+                        Some(
+                            command_entity
+                                .position
+                                .position
+                                .xy()
+                                .distance(target_position),
+                        ),
                     ),
-                    None => (None, None),
+                    None => (None, None, None),
                 };
 
                 let cast_range = if skill_data.cast_range > 0 {
@@ -792,14 +1110,8 @@ pub fn command_system(
                     command_entity.ability_values.get_attack_range() as f32
                 };
 
-                let in_distance = target_position.map_or(true, |target_position| {
-                    command_entity
-                        .position
-                        .position
-                        .xy()
-                        .distance_squared(target_position.xy())
-                        < cast_range * cast_range
-                });
+                let in_distance =
+                    target_distance.map_or(true, |target_distance| target_distance < cast_range);
                 if !in_distance {
                     // Not in range, set current command to move
                     // TODO: By changing command to move here we affect SkillActionMode::Restore, should save current command
@@ -828,23 +1140,45 @@ pub fn command_system(
                     .unwrap_or_else(|| Duration::from_secs(0))
                     .mul_f32(skill_data.casting_motion_speed);
 
-                let action_duration = action_motion_id
-                    .or(skill_data.action_motion_id)
-                    .and_then(|motion_id| {
-                        if let Some(npc) = command_entity.npc {
-                            game_data.npcs.get_npc_motion(npc.id, motion_id)
-                        } else {
-                            game_data.motions.find_first_character_motion(
-                                motion_id,
-                                weapon_motion_type,
-                                weapon_motion_gender,
-                            )
-                        }
-                    })
+                let action_motion =
+                    action_motion_id
+                        .or(skill_data.action_motion_id)
+                        .and_then(|motion_id| {
+                            if let Some(npc) = command_entity.npc {
+                                game_data.npcs.get_npc_motion(npc.id, motion_id)
+                            } else {
+                                game_data.motions.find_first_character_motion(
+                                    motion_id,
+                                    weapon_motion_type,
+                                    weapon_motion_gender,
+                                )
+                            }
+                        });
+                let action_duration = action_motion
                     .map(|motion_data| motion_data.duration)
                     .unwrap_or_else(|| Duration::from_secs(0))
                     .mul_f32(skill_data.action_motion_speed);
 
+                // The skill is applied when the action motion reaches its attack
+                // frame, or as soon as casting completes if it has none
+                let action_hit_duration = action_motion
+                    .and_then(|motion_data| motion_data.get_first_attack_frame_time())
+                    .unwrap_or_default()
+                    .mul_f32(skill_data.action_motion_speed);
+
+                // Skills which fire the weapon consume a single ammo, the rest save it
+                if skill_data.skill_type.uses_ammo() {
+                    if let Some(ammo_index) = weapon_item_data.and_then(|weapon_item_data| {
+                        weapon_item_data.item_data.class.weapon_ammo_index()
+                    }) {
+                        command_events.use_ammo_events.send(UseAmmoEvent {
+                            entity: command_entity.entity,
+                            ammo_index,
+                            quantity: 1,
+                        });
+                    }
+                }
+
                 // For skills which target an entity, we must send a message indicating start of skill
                 if target_entity.is_some() {
                     server_messages.send_entity_message(
@@ -855,10 +1189,10 @@ pub fn command_system(
                     );
                 }
 
-                // Send skill event for effect to be applied after casting motion
-                skill_events.send(SkillEvent::new(
+                // Send skill event for effect to be applied during the action motion
+                command_events.skill_events.send(SkillEvent::new(
                     command_entity.entity,
-                    time.last_update().unwrap() + casting_duration,
+                    time.last_update().unwrap() + casting_duration + action_hit_duration,
                     skill_id,
                     match skill_target {
                         None => SkillEventTarget::Entity(command_entity.entity),
@@ -1,36 +1,78 @@
-use bevy::ecs::prelude::{Commands, EventWriter, Res, ResMut};
+use bevy::{
+    ecs::prelude::{Commands, Entity, EventWriter, Query, Res, ResMut, With},
+    time::Time,
+};
 
 use crate::game::{
-    components::{GameClient, LoginClient, ServerInfo, WorldClient},
-    events::SaveEvent,
+    components::{
+        Account, CharacterInfo, ClientEntity, DisconnectedCharacter, GameClient, LoginClient,
+        NextCommand, OfflineVendor, OfflineVendorProceeds, PersonalStore, ServerInfo, WorldClient,
+    },
+    events::{RewardItemEvent, SaveEvent, SuspicionEvent, SuspicionSignal},
     messages::control::{ClientType, ControlMessage},
-    resources::{ControlChannel, GameServer, LoginTokens, ServerList, WorldServer},
+    resources::{
+        AccountSessions, ControlChannel, GameConfig, GameServer, LeaderboardCache, LoginTokens,
+        Maintenance, ServerList, WorldServer,
+    },
+    GameData,
 };
 
 pub fn control_server_system(
     mut commands: Commands,
     channel: Res<ControlChannel>,
+    query_in_game: Query<(Entity, &CharacterInfo), With<ClientEntity>>,
+    query_personal_store: Query<
+        (&Account, &CharacterInfo, Option<&OfflineVendorProceeds>),
+        (With<PersonalStore>, With<ClientEntity>),
+    >,
+    query_offline_vendors: Query<&Account, With<OfflineVendor>>,
+    mut account_sessions: ResMut<AccountSessions>,
     mut login_tokens: ResMut<LoginTokens>,
+    mut maintenance: ResMut<Maintenance>,
+    leaderboard_cache: Option<Res<LeaderboardCache>>,
     mut server_list: ResMut<ServerList>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
     mut save_events: EventWriter<SaveEvent>,
+    mut suspicion_events: EventWriter<SuspicionEvent>,
+    mut reward_item_events: EventWriter<RewardItemEvent>,
 ) {
     while let Ok(message) = channel.control_rx.try_recv() {
         match message {
             ControlMessage::AddClient {
                 client_type,
+                ip,
+                country,
+                packet_codec_login_token,
                 client_message_rx,
                 server_message_tx,
                 response_tx,
             } => {
                 let entity = match client_type {
                     ClientType::Login => commands
-                        .spawn(LoginClient::new(client_message_rx, server_message_tx))
+                        .spawn(LoginClient::new(
+                            client_message_rx,
+                            server_message_tx,
+                            ip,
+                            country,
+                        ))
                         .id(),
                     ClientType::World => commands
-                        .spawn(WorldClient::new(client_message_rx, server_message_tx))
+                        .spawn(WorldClient::new(
+                            client_message_rx,
+                            server_message_tx,
+                            ip,
+                            packet_codec_login_token,
+                        ))
                         .id(),
                     ClientType::Game => commands
-                        .spawn(GameClient::new(client_message_rx, server_message_tx))
+                        .spawn(GameClient::new(
+                            client_message_rx,
+                            server_message_tx,
+                            ip,
+                            packet_codec_login_token,
+                        ))
                         .id(),
                 };
                 response_tx.send(entity).unwrap();
@@ -45,43 +87,118 @@ pub fn control_server_system(
                             login_token.login_client = None;
                         }
                     }
+                    account_sessions.release_client(entity);
 
                     commands.entity(entity).despawn();
                 }
                 ClientType::World => {
-                    for (index, login_token) in login_tokens.tokens.iter_mut().enumerate() {
-                        if login_token.world_client == Some(entity) {
-                            login_token.world_client = None;
-                        }
+                    if let Some(index) = login_tokens
+                        .tokens
+                        .iter()
+                        .position(|login_token| login_token.world_client == Some(entity))
+                    {
+                        let login_token = &mut login_tokens.tokens[index];
+                        login_token.world_client = None;
 
-                        if login_token.game_client.is_none() && login_token.world_client.is_none() {
-                            login_tokens.tokens.remove(index);
-                            break;
+                        if login_token.game_client.is_none() {
+                            let login_token = login_tokens.remove(index);
+                            account_sessions.release_login_token(login_token.token);
                         }
                     }
+                    account_sessions.release_client(entity);
 
                     commands.entity(entity).despawn();
                 }
                 ClientType::Game => {
-                    for (index, login_token) in login_tokens.tokens.iter_mut().enumerate() {
-                        if login_token.game_client == Some(entity) {
-                            login_token.game_client = None;
-                        }
+                    // A game client which reconnected controls an existing character entity
+                    let entity = account_sessions
+                        .remove_reconnected_game_client(entity)
+                        .unwrap_or(entity);
+
+                    if let Some(index) = login_tokens
+                        .tokens
+                        .iter()
+                        .position(|login_token| login_token.game_client == Some(entity))
+                    {
+                        let login_token = &mut login_tokens.tokens[index];
+                        login_token.game_client = None;
 
-                        if login_token.game_client.is_none() && login_token.world_client.is_none() {
-                            login_tokens.tokens.remove(index);
-                            break;
+                        if login_token.world_client.is_none() {
+                            let login_token = login_tokens.remove(index);
+                            account_sessions.release_login_token(login_token.token);
                         }
                     }
 
-                    // Let the save system handle despawning the entity
-                    save_events.send(SaveEvent::Character {
-                        entity,
-                        remove_after_save: true,
-                    });
+                    // The session is released once the save system has despawned the entity
+                    account_sessions.disconnect_game_client(entity);
+
                     commands.entity(entity).remove::<GameClient>();
+
+                    if let (Some(offline_vendor_config), Ok((account, character_info, proceeds))) = (
+                        game_config.offline_vendor.as_ref(),
+                        query_personal_store.get(entity),
+                    ) {
+                        let num_offline_vendors = query_offline_vendors
+                            .iter()
+                            .filter(|vendor_account| vendor_account.name == account.name)
+                            .count();
+
+                        if num_offline_vendors < offline_vendor_config.max_per_account {
+                            // Leave the store open, the account is free to log in again
+                            // whilst the character stays in the world as a vendor
+                            account_sessions.release_client(entity);
+                            commands.entity(entity).insert((
+                                OfflineVendor::new(
+                                    time.last_update().unwrap()
+                                        + offline_vendor_config.max_duration,
+                                    proceeds.cloned().unwrap_or_default(),
+                                ),
+                                NextCommand::default(),
+                            ));
+                            save_events.send(SaveEvent::Character {
+                                entity,
+                                remove_after_save: false,
+                            });
+                            log::info!(
+                                "Character {} of account {} is now an offline vendor",
+                                &character_info.name,
+                                &account.name
+                            );
+                            continue;
+                        }
+                    }
+
+                    match game_config.reconnect_grace_period {
+                        Some(reconnect_grace_period) if query_in_game.contains(entity) => {
+                            // Leave the character in the world so the player can reconnect to
+                            // it, it is saved now in case the server stops before it expires
+                            commands.entity(entity).insert((
+                                DisconnectedCharacter::new(
+                                    time.last_update().unwrap() + reconnect_grace_period,
+                                ),
+                                NextCommand::with_stop(true),
+                            ));
+                            save_events.send(SaveEvent::Character {
+                                entity,
+                                remove_after_save: false,
+                            });
+                        }
+                        _ => {
+                            // Let the save system handle despawning the entity
+                            save_events.send(SaveEvent::Character {
+                                entity,
+                                remove_after_save: true,
+                            });
+                        }
+                    }
                 }
             },
+            ControlMessage::MalformedPacket { entity } => {
+                suspicion_events.send(SuspicionEvent::new(
+                    entity,
+                    SuspicionSignal::MalformedPacket,
+                ));
+            }
             ControlMessage::AddWorldServer {
                 name,
                 ip,
@@ -134,12 +251,84 @@ pub fn control_server_system(
                     ip,
                     port,
                     packet_codec_seed,
+                    num_clients: 0,
+                    is_overloaded: false,
                 });
                 response_tx.send(entity).unwrap();
             }
             ControlMessage::RemoveServer { entity } => {
                 commands.entity(entity).despawn();
             }
+            ControlMessage::UpdateChannelPopulation {
+                game_server,
+                num_clients,
+            } => {
+                let Some(game_server) = server_list.find_game_server_mut(game_server) else {
+                    continue;
+                };
+                game_server.num_clients = num_clients;
+
+                if let Some(channel_capacity) = game_config.channel_capacity.as_ref() {
+                    let is_overloaded = game_server
+                        .percent_full(Some(channel_capacity.max_players))
+                        >= channel_capacity.overload_warning_percent;
+                    if is_overloaded && !game_server.is_overloaded {
+                        log::warn!(
+                            "Channel {} is overloaded with {} of {} players",
+                            game_server.name,
+                            num_clients,
+                            channel_capacity.max_players
+                        );
+                    }
+                    game_server.is_overloaded = is_overloaded;
+                }
+            }
+            ControlMessage::StartMaintenance { countdown, reason } => {
+                if maintenance.start(time.last_update().unwrap(), countdown, reason) {
+                    log::info!(
+                        "Server maintenance starts in {} seconds",
+                        countdown.as_secs()
+                    );
+                }
+            }
+            ControlMessage::CancelMaintenance => {
+                if maintenance.cancel() {
+                    log::info!("Server maintenance cancelled");
+                }
+            }
+            ControlMessage::GetLeaderboards { response_tx } => {
+                response_tx
+                    .send(
+                        leaderboard_cache
+                            .as_ref()
+                            .and_then(|leaderboard_cache| leaderboard_cache.get().cloned()),
+                    )
+                    .ok();
+            }
+            ControlMessage::SpawnItem {
+                character_name,
+                item_spawn,
+                response_tx,
+            } => {
+                let Some((entity, _)) = query_in_game.iter().find(|(_, character_info)| {
+                    character_info.name.eq_ignore_ascii_case(&character_name)
+                }) else {
+                    response_tx
+                        .send(Err(format!("Character {} is not online", character_name)))
+                        .ok();
+                    continue;
+                };
+
+                match item_spawn.create_item(&game_data, "admin command") {
+                    Ok(item) => {
+                        reward_item_events.send(RewardItemEvent::new(entity, item, true));
+                        response_tx.send(Ok(())).ok();
+                    }
+                    Err(error) => {
+                        response_tx.send(Err(error.to_string())).ok();
+                    }
+                }
+            }
         }
     }
 }
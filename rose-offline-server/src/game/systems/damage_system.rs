@@ -1,39 +1,54 @@
 use std::time::Duration;
 
 use bevy::{
-    ecs::prelude::{Commands, EventReader, Query, Res, ResMut},
+    ecs::prelude::{Commands, EventReader, Query, Res, ResMut, Without},
     prelude::EventWriter,
     time::Time,
 };
+use rand::Rng;
 use rose_game_common::data::Damage;
 
 use crate::game::{
     components::{
-        ClientEntity, ClientEntityType, Command, DamageSource, DamageSources, Dead, HealthPoints,
-        MotionData, NpcAi,
+        AbilityValues, ClanMembership, ClientEntity, ClientEntityType, Command, DamageSource,
+        DamageSources, Dead, HealthPoints, MotionData, NpcAi, OfflineVendor, PkFlag, Position,
+        Team,
     },
-    events::{DamageEvent, ItemLifeEvent},
+    events::{ClanEvent, DamageEvent, ItemLifeEvent, StatisticsEvent},
     messages::server::ServerMessage,
-    resources::ServerMessages,
+    resources::{ClanWars, GameConfig, ServerMessages, ZoneList},
 };
 
 pub fn damage_system(
     mut commands: Commands,
     attacker_query: Query<&ClientEntity>,
-    mut defender_query: Query<(
-        &ClientEntity,
-        &mut HealthPoints,
-        Option<&mut DamageSources>,
-        Option<&mut NpcAi>,
-        Option<&MotionData>,
-    )>,
+    character_query: Query<(&Team, Option<&ClanMembership>)>,
+    mut defender_query: Query<
+        (
+            &ClientEntity,
+            &mut HealthPoints,
+            Option<&mut DamageSources>,
+            Option<&mut NpcAi>,
+            Option<&MotionData>,
+            Option<&AbilityValues>,
+            Option<&Position>,
+        ),
+        Without<OfflineVendor>,
+    >,
     mut damage_events: EventReader<DamageEvent>,
     mut item_life_events: EventWriter<ItemLifeEvent>,
+    mut clan_events: EventWriter<ClanEvent>,
+    mut statistics_events: EventWriter<StatisticsEvent>,
     mut server_messages: ResMut<ServerMessages>,
+    clan_wars: Res<ClanWars>,
+    game_config: Res<GameConfig>,
     time: Res<Time>,
+    zone_list: Res<ZoneList>,
 ) {
+    let mut rng = rand::thread_rng();
+
     for damage_event in damage_events.iter() {
-        let (attacker_entity, defender_entity, damage, from_skill) = match *damage_event {
+        let (attacker_entity, defender_entity, mut damage, from_skill) = match *damage_event {
             DamageEvent::Attack {
                 attacker: attacker_entity,
                 defender: defender_entity,
@@ -50,6 +65,7 @@ pub fn damage_system(
                 damage,
                 skill_id,
                 attacker_intelligence,
+                ..
             } => (
                 attacker_entity,
                 defender_entity,
@@ -66,6 +82,7 @@ pub fn damage_system(
                     amount: 0,
                     is_critical: false,
                     apply_hit_stun: false,
+                    is_blocked: false,
                 },
                 None,
             ),
@@ -76,8 +93,15 @@ pub fn damage_system(
             .map(|client_entity| Some(client_entity.id))
             .unwrap_or(None);
 
-        if let Ok((client_entity, mut health_points, damage_sources, npc_ai, motion_data)) =
-            defender_query.get_mut(defender_entity)
+        if let Ok((
+            client_entity,
+            mut health_points,
+            damage_sources,
+            npc_ai,
+            motion_data,
+            ability_values,
+            position,
+        )) = defender_query.get_mut(defender_entity)
         {
             if damage.apply_hit_stun {
                 // TODO: Apply hit stun by setting next command to HitStun ?
@@ -88,6 +112,40 @@ pub fn damage_system(
                 continue;
             }
 
+            if attacker_entity != defender_entity
+                && matches!(client_entity.entity_type, ClientEntityType::Character)
+                && position.map_or(false, |position| zone_list.is_safe_zone(position.zone_id))
+            {
+                continue;
+            }
+
+            // Normal attacks can be blocked by a shield to halve their damage
+            if matches!(damage_event, DamageEvent::Attack { .. })
+                && attacker_entity != defender_entity
+                && damage.amount > 0
+                && ability_values.map_or(false, |ability_values| {
+                    rng.gen_range(0..100) < ability_values.get_block_rate()
+                })
+            {
+                damage.amount /= 2;
+                damage.apply_hit_stun = false;
+                damage.is_blocked = true;
+            }
+
+            // Elemental skills are scaled by the defender's resistance to the element
+            if let DamageEvent::Skill {
+                element: Some(element),
+                ..
+            } = *damage_event
+            {
+                if let Some(ability_values) = ability_values {
+                    let resistance = ability_values
+                        .get_elemental_resistance(element)
+                        .clamp(-100, 90);
+                    damage.amount = (damage.amount as i64 * (100 - resistance) as i64 / 100) as u32;
+                }
+            }
+
             health_points.hp = i32::max(health_points.hp - damage.amount as i32, 0);
 
             if !matches!(damage_event, DamageEvent::Tagged { .. }) {
@@ -170,6 +228,55 @@ pub fn damage_system(
                             .or_else(|| Some(Duration::from_secs(1))),
                     ),
                 ));
+
+                if matches!(client_entity.entity_type, ClientEntityType::Character) {
+                    clan_events.send(ClanEvent::WarKill {
+                        killer: attacker_entity,
+                        killed: defender_entity,
+                    });
+
+                    statistics_events.send(StatisticsEvent::Death {
+                        entity: defender_entity,
+                    });
+                    if attacker_query
+                        .get(attacker_entity)
+                        .map_or(false, |attacker| {
+                            matches!(attacker.entity_type, ClientEntityType::Character)
+                        })
+                        && attacker_entity != defender_entity
+                    {
+                        statistics_events.send(StatisticsEvent::PvpKill {
+                            entity: attacker_entity,
+                        });
+
+                        // Killing an ordinary player, rather than a clan war
+                        // enemy or an event opponent, makes the killer a target
+                        // for guards
+                        if let (
+                            Ok((_, attacker_clan_membership)),
+                            Ok((defender_team, defender_clan_membership)),
+                        ) = (
+                            character_query.get(attacker_entity),
+                            character_query.get(defender_entity),
+                        ) {
+                            if defender_team.id == Team::DEFAULT_CHARACTER_TEAM_ID
+                                && !clan_wars.is_at_war(
+                                    attacker_clan_membership
+                                        .and_then(|clan_membership| clan_membership.clan()),
+                                    defender_clan_membership
+                                        .and_then(|clan_membership| clan_membership.clan()),
+                                )
+                            {
+                                commands.entity(attacker_entity).insert(PkFlag::new(
+                                    time.last_update().unwrap()
+                                        + Duration::from_secs(
+                                            game_config.guards.pk_flag_duration_secs,
+                                        ),
+                                ));
+                            }
+                        }
+                    }
+                }
             }
         }
     }
@@ -17,7 +17,7 @@ use crate::game::{
         Stamina, StatPoints, Team, UnionMembership,
     },
     events::EquipmentEvent,
-    resources::ServerMessages,
+    resources::{GameConfig, ItemBindingConfig, ServerMessages},
     GameData,
 };
 
@@ -50,6 +50,7 @@ pub struct EquipmentEventEntity<'w> {
 pub fn equipment_event_system(
     mut equipment_events: EventReader<EquipmentEvent>,
     mut query: Query<EquipmentEventEntity>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
     mut server_messages: ResMut<ServerMessages>,
 ) {
@@ -68,7 +69,14 @@ pub fn equipment_event_system(
                 }
 
                 let updated_inventory_items = if let Some(item_slot) = item_slot {
-                    equip_from_inventory(&game_data, &mut entity, equipment_index, item_slot).ok()
+                    equip_from_inventory(
+                        &game_config.item_binding,
+                        &game_data,
+                        &mut entity,
+                        equipment_index,
+                        item_slot,
+                    )
+                    .ok()
                 } else {
                     unequip_to_inventory(
                         &mut entity.equipment,
@@ -120,7 +128,16 @@ pub fn equipment_event_system(
                         let ammo_slot = entity.equipment.get_ammo_slot_mut(ammo_index);
 
                         if let Some(Item::Stackable(ammo_item)) = inventory_slot {
-                            // TODO: Verify bullet type
+                            if game_data
+                                .items
+                                .get_base_item(ammo_item.item)
+                                .and_then(|item_data| item_data.class.ammo_index())
+                                != Some(ammo_index)
+                            {
+                                // Ammo type does not match the ammo slot
+                                continue;
+                            }
+
                             match ammo_slot.can_stack_with(ammo_item) {
                                 Ok(_) => {
                                     // Can fully stack into ammo slot
@@ -223,6 +240,7 @@ pub fn equipment_event_system(
 
                 let updated_inventory_items = if let Some(item_slot) = item_slot {
                     equip_vehicle_from_inventory(
+                        &game_config.item_binding,
                         &game_data,
                         &mut entity,
                         vehicle_part_index,
@@ -277,6 +295,7 @@ enum EquipItemError {
 }
 
 fn equip_from_inventory(
+    item_binding: &ItemBindingConfig,
     game_data: &GameData,
     entity: &mut EquipmentEventEntityItem,
     equipment_index: EquipmentIndex,
@@ -354,10 +373,13 @@ fn equip_from_inventory(
     // Equip item from inventory
     let inventory_slot = entity.inventory.get_item_slot_mut(item_slot).unwrap();
     let equipment_slot = entity.equipment.get_equipment_slot_mut(equipment_index);
-    let equipment_item = match inventory_slot.take() {
+    let mut equipment_item = match inventory_slot.take() {
         Some(Item::Equipment(equipment_item)) => equipment_item,
         _ => unreachable!(),
     };
+    if item_binding.is_bind_on_equip(equipment_item.item) {
+        equipment_item.is_bound = true;
+    }
     *inventory_slot = equipment_slot.take().map(Item::Equipment);
     *equipment_slot = Some(equipment_item);
 
@@ -371,6 +393,7 @@ fn equip_from_inventory(
 }
 
 fn equip_vehicle_from_inventory(
+    item_binding: &ItemBindingConfig,
     game_data: &GameData,
     entity: &mut EquipmentEventEntityItem,
     vehicle_part_index: VehiclePartIndex,
@@ -409,10 +432,13 @@ fn equip_vehicle_from_inventory(
     // Equip item from inventory
     let inventory_slot = entity.inventory.get_item_slot_mut(item_slot).unwrap();
     let vehicle_slot = entity.equipment.get_vehicle_slot_mut(vehicle_part_index);
-    let equipment_item = match inventory_slot.take() {
+    let mut equipment_item = match inventory_slot.take() {
         Some(Item::Equipment(equipment_item)) => equipment_item,
         _ => unreachable!(),
     };
+    if item_binding.is_bind_on_equip(equipment_item.item) {
+        equipment_item.is_bound = true;
+    }
     *inventory_slot = vehicle_slot.take().map(Item::Equipment);
     *vehicle_slot = Some(equipment_item);
 
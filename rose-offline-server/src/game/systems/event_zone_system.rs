@@ -0,0 +1,649 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    ecs::query::WorldQuery,
+    math::{Vec3, Vec3Swizzles},
+    prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut},
+    time::Time,
+};
+use log::{info, warn};
+use rand::prelude::SliceRandom;
+use rose_data::Item;
+
+use crate::game::{
+    components::{CharacterInfo, Dead, GameClient, HonorPoints, Level, Position, Team},
+    events::{EventZoneEvent, RewardItemEvent, TeleportEvent},
+    messages::server::ServerMessage,
+    resources::{
+        ActiveEventZone, EventZone, EventZoneMode, EventZoneParticipant, EventZoneState,
+        EventZones, GameConfig, GameData, ServerMessages,
+    },
+};
+
+/// Participants of an event are put on teams starting from this id, so they
+/// can attack the other teams but not their own
+const EVENT_ZONE_TEAM_ID_BASE: u32 = 10;
+
+/// How often the team holding the hill scores in king of the hill
+const HILL_SCORE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(WorldQuery)]
+pub struct EventZoneCharacterQuery<'w> {
+    character_info: &'w CharacterInfo,
+    level: &'w Level,
+    position: &'w Position,
+    dead: Option<&'w Dead>,
+    game_client: Option<&'w GameClient>,
+}
+
+fn send_event_zone_message(
+    query_character: &Query<EventZoneCharacterQuery>,
+    entity: Option<Entity>,
+    text: String,
+) {
+    let Some(game_client) = entity
+        .and_then(|entity| query_character.get(entity).ok())
+        .and_then(|character| character.game_client)
+    else {
+        info!("{}", text);
+        return;
+    };
+
+    game_client
+        .server_message_tx
+        .send(ServerMessage::Whisper {
+            from: String::from("SERVER"),
+            text,
+        })
+        .ok();
+}
+
+fn send_participants_message(
+    query_character: &Query<EventZoneCharacterQuery>,
+    active: &ActiveEventZone,
+    text: String,
+) {
+    for participant in active.participants.iter() {
+        if let Some(game_client) = query_character
+            .get(participant.entity)
+            .ok()
+            .and_then(|character| character.game_client)
+        {
+            game_client
+                .server_message_tx
+                .send(ServerMessage::AnnounceChat {
+                    name: None,
+                    text: text.clone(),
+                })
+                .ok();
+        }
+    }
+}
+
+fn format_scores(event_zone: &EventZone, active: &ActiveEventZone) -> String {
+    event_zone
+        .teams
+        .iter()
+        .zip(active.scores.iter())
+        .map(|(team, score)| format!("{} {}", team.name, score))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn is_within_radius(position: &Position, event_zone: &EventZone, target: Vec3) -> bool {
+    position.zone_id == event_zone.zone
+        && position.position.xy().distance(target.xy()) <= event_zone.objective_radius
+}
+
+fn start_event_zone(
+    event_zones: &mut EventZones,
+    server_messages: &mut ServerMessages,
+    event_zone: &EventZone,
+    now: Instant,
+) -> Result<(), String> {
+    if let Some(active) = event_zones.active.as_ref() {
+        return Err(format!("The event {} is already in progress", active.name));
+    }
+
+    if event_zone.teams.len() < 2 {
+        return Err(format!(
+            "The event {} must have at least 2 teams",
+            event_zone.name
+        ));
+    }
+
+    if matches!(event_zone.mode, EventZoneMode::KingOfTheHill) && event_zone.hill.is_none() {
+        return Err(format!("The event {} has no hill", event_zone.name));
+    }
+
+    event_zones.active = Some(ActiveEventZone::new(
+        event_zone.name.clone(),
+        event_zone.teams.len(),
+        now + Duration::from_secs(event_zone.signup_mins * 60),
+    ));
+    server_messages.send_global_message(ServerMessage::AnnounceChat {
+        name: None,
+        text: format!(
+            "{} ({}) starts in {} minutes, use /event join to take part",
+            event_zone.name,
+            event_zone.mode.name(),
+            event_zone.signup_mins
+        ),
+    });
+    Ok(())
+}
+
+/// Returns a participant to their own team and to where they were before the
+/// event started.
+fn return_participant(
+    commands: &mut Commands,
+    teleport_events: &mut EventWriter<TeleportEvent>,
+    participant: &EventZoneParticipant,
+) {
+    if let Some(return_position) = participant.return_position.as_ref() {
+        commands
+            .entity(participant.entity)
+            .insert(Team::default_character());
+        teleport_events.send(TeleportEvent {
+            entity: participant.entity,
+            position: return_position.clone(),
+        });
+    }
+}
+
+fn begin_event_zone(
+    commands: &mut Commands,
+    teleport_events: &mut EventWriter<TeleportEvent>,
+    server_messages: &mut ServerMessages,
+    query_character: &Query<EventZoneCharacterQuery>,
+    event_zone: &EventZone,
+    mut active: ActiveEventZone,
+    now: Instant,
+) -> Option<ActiveEventZone> {
+    active
+        .participants
+        .retain(|participant| query_character.contains(participant.entity));
+
+    if active.participants.len() < event_zone.min_players {
+        server_messages.send_global_message(ServerMessage::AnnounceChat {
+            name: None,
+            text: format!(
+                "{} has been cancelled as not enough players joined",
+                event_zone.name
+            ),
+        });
+        return None;
+    }
+
+    active.participants.shuffle(&mut rand::thread_rng());
+    for (index, participant) in active.participants.iter_mut().enumerate() {
+        let Ok(character) = query_character.get(participant.entity) else {
+            continue;
+        };
+
+        participant.team_index = index % event_zone.teams.len();
+        participant.return_position = Some(character.position.clone());
+        commands.entity(participant.entity).insert(Team::new(
+            EVENT_ZONE_TEAM_ID_BASE + participant.team_index as u32,
+        ));
+        teleport_events.send(TeleportEvent {
+            entity: participant.entity,
+            position: Position::new(
+                event_zone.teams[participant.team_index].base,
+                event_zone.zone,
+            ),
+        });
+
+        if let Some(game_client) = character.game_client {
+            game_client
+                .server_message_tx
+                .send(ServerMessage::Whisper {
+                    from: String::from("SERVER"),
+                    text: format!(
+                        "{} has started, you are on team {}",
+                        event_zone.name, event_zone.teams[participant.team_index].name
+                    ),
+                })
+                .ok();
+        }
+    }
+
+    active.state = EventZoneState::Running {
+        end_time: now + Duration::from_secs(event_zone.duration_mins * 60),
+    };
+    Some(active)
+}
+
+fn update_capture_the_flag(
+    query_character: &Query<EventZoneCharacterQuery>,
+    event_zone: &EventZone,
+    active: &mut ActiveEventZone,
+) {
+    let mut messages = Vec::new();
+
+    for participant in active.participants.iter() {
+        let Ok(character) = query_character.get(participant.entity) else {
+            continue;
+        };
+
+        if character.dead.is_some() {
+            for (team_index, carrier) in active.flag_carriers.iter_mut().enumerate() {
+                if *carrier == Some(participant.entity) {
+                    *carrier = None;
+                    messages.push(format!(
+                        "The {} flag has been returned",
+                        event_zone.teams[team_index].name
+                    ));
+                }
+            }
+            continue;
+        }
+
+        for (team_index, team) in event_zone.teams.iter().enumerate() {
+            if team_index != participant.team_index
+                && active.flag_carriers[team_index].is_none()
+                && is_within_radius(character.position, event_zone, team.base)
+            {
+                active.flag_carriers[team_index] = Some(participant.entity);
+                messages.push(format!(
+                    "{} has taken the {} flag",
+                    character.character_info.name, team.name
+                ));
+            }
+        }
+
+        let own_team = &event_zone.teams[participant.team_index];
+        if active.flag_carriers[participant.team_index].is_some()
+            || !is_within_radius(character.position, event_zone, own_team.base)
+        {
+            continue;
+        }
+
+        for (team_index, carrier) in active.flag_carriers.iter_mut().enumerate() {
+            if *carrier == Some(participant.entity) {
+                *carrier = None;
+                active.scores[participant.team_index] += 1;
+                messages.push(format!(
+                    "{} has captured the {} flag for team {}",
+                    character.character_info.name, event_zone.teams[team_index].name, own_team.name
+                ));
+            }
+        }
+    }
+
+    if !messages.is_empty() {
+        messages.push(format!("Scores: {}", format_scores(event_zone, active)));
+        for text in messages {
+            send_participants_message(query_character, active, text);
+        }
+    }
+}
+
+fn update_king_of_the_hill(
+    query_character: &Query<EventZoneCharacterQuery>,
+    event_zone: &EventZone,
+    active: &mut ActiveEventZone,
+    now: Instant,
+) {
+    let Some(hill) = event_zone.hill else {
+        return;
+    };
+
+    if active
+        .last_hill_score_time
+        .map_or(false, |last_score_time| {
+            now - last_score_time < HILL_SCORE_INTERVAL
+        })
+    {
+        return;
+    }
+    active.last_hill_score_time = Some(now);
+
+    let mut teams_on_hill = active
+        .participants
+        .iter()
+        .filter(|participant| {
+            query_character
+                .get(participant.entity)
+                .map_or(false, |character| {
+                    character.dead.is_none()
+                        && is_within_radius(character.position, event_zone, hill)
+                })
+        })
+        .map(|participant| participant.team_index)
+        .collect::<Vec<_>>();
+    teams_on_hill.sort_unstable();
+    teams_on_hill.dedup();
+
+    let hill_holder = if teams_on_hill.len() == 1 {
+        Some(teams_on_hill[0])
+    } else {
+        None
+    };
+
+    if let Some(team_index) = hill_holder {
+        active.scores[team_index] += 1;
+
+        if active.hill_holder != Some(team_index) {
+            send_participants_message(
+                query_character,
+                active,
+                format!(
+                    "Team {} has taken the hill. Scores: {}",
+                    event_zone.teams[team_index].name,
+                    format_scores(event_zone, active)
+                ),
+            );
+        }
+    }
+    active.hill_holder = hill_holder;
+}
+
+fn finish_event_zone(
+    commands: &mut Commands,
+    teleport_events: &mut EventWriter<TeleportEvent>,
+    reward_item_events: &mut EventWriter<RewardItemEvent>,
+    server_messages: &mut ServerMessages,
+    query_honor_points: &mut Query<&mut HonorPoints>,
+    game_data: &GameData,
+    event_zone: &EventZone,
+    active: ActiveEventZone,
+) {
+    let text = if let Some(winning_team) = active.winning_team() {
+        for participant in active
+            .participants
+            .iter()
+            .filter(|participant| participant.team_index == winning_team)
+        {
+            for reward in event_zone.rewards.iter() {
+                if let Some(item) = game_data
+                    .items
+                    .get_base_item(reward.item)
+                    .and_then(|item_data| Item::from_item_data(item_data, reward.quantity))
+                {
+                    reward_item_events.send(RewardItemEvent::new(participant.entity, item, true));
+                } else {
+                    warn!(
+                        "Invalid reward item {:?} for event {}",
+                        reward.item, event_zone.name
+                    );
+                }
+            }
+
+            if let Ok(mut honor_points) = query_honor_points.get_mut(participant.entity) {
+                honor_points.add(event_zone.honor_points);
+            }
+        }
+
+        format!(
+            "Team {} has won {}! Scores: {}",
+            event_zone.teams[winning_team].name,
+            event_zone.name,
+            format_scores(event_zone, &active)
+        )
+    } else {
+        format!(
+            "{} has ended in a draw. Scores: {}",
+            event_zone.name,
+            format_scores(event_zone, &active)
+        )
+    };
+    server_messages.send_global_message(ServerMessage::AnnounceChat { name: None, text });
+
+    for participant in active.participants.iter() {
+        return_participant(commands, teleport_events, participant);
+    }
+}
+
+pub fn event_zone_system(
+    mut commands: Commands,
+    mut event_zone_events: EventReader<EventZoneEvent>,
+    mut event_zones: ResMut<EventZones>,
+    mut reward_item_events: EventWriter<RewardItemEvent>,
+    mut server_messages: ResMut<ServerMessages>,
+    mut teleport_events: EventWriter<TeleportEvent>,
+    query_character: Query<EventZoneCharacterQuery>,
+    mut query_honor_points: Query<&mut HonorPoints>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    for event_zone in game_config.event_zones.events.iter() {
+        let Some(interval_mins) = event_zone.interval_mins else {
+            continue;
+        };
+        let interval = Duration::from_secs(interval_mins * 60);
+        let next_start = *event_zones
+            .next_scheduled
+            .entry(event_zone.name.clone())
+            .or_insert(now + interval);
+
+        if now >= next_start {
+            event_zones
+                .next_scheduled
+                .insert(event_zone.name.clone(), now + interval);
+
+            if event_zones.active.is_none() {
+                if let Err(error) =
+                    start_event_zone(&mut event_zones, &mut server_messages, event_zone, now)
+                {
+                    warn!("Failed to start scheduled event: {}", error);
+                }
+            }
+        }
+    }
+
+    for event in event_zone_events.iter() {
+        match event {
+            &EventZoneEvent::Join { entity } => {
+                let Ok(character) = query_character.get(entity) else {
+                    continue;
+                };
+                let Some(active) = event_zones.active.as_mut() else {
+                    send_event_zone_message(
+                        &query_character,
+                        Some(entity),
+                        String::from("There is no event open for signup"),
+                    );
+                    continue;
+                };
+                let Some(event_zone) = game_config.event_zones.get(&active.name) else {
+                    continue;
+                };
+
+                let result = if !matches!(active.state, EventZoneState::Signup { .. }) {
+                    Err(format!("Signup for {} has closed", active.name))
+                } else if active.find_participant(entity).is_some() {
+                    Err(format!("You have already joined {}", active.name))
+                } else if event_zone.max_players.map_or(false, |max_players| {
+                    active.participants.len() >= max_players
+                }) {
+                    Err(format!("{} is full", active.name))
+                } else if event_zone
+                    .min_level
+                    .map_or(false, |min_level| character.level.level < min_level)
+                {
+                    Err(format!(
+                        "You must be at least level {} to join {}",
+                        event_zone.min_level.unwrap_or_default(),
+                        active.name
+                    ))
+                } else {
+                    active.participants.push(EventZoneParticipant {
+                        entity,
+                        team_index: 0,
+                        return_position: None,
+                        has_entered_zone: false,
+                    });
+                    Ok(format!(
+                        "You have joined {}, {} players have signed up",
+                        active.name,
+                        active.participants.len()
+                    ))
+                };
+
+                send_event_zone_message(
+                    &query_character,
+                    Some(entity),
+                    result.unwrap_or_else(|error| error),
+                );
+            }
+            &EventZoneEvent::Leave { entity } => {
+                let Some(active) = event_zones.active.as_mut() else {
+                    continue;
+                };
+                let Some(index) = active
+                    .participants
+                    .iter()
+                    .position(|participant| participant.entity == entity)
+                else {
+                    send_event_zone_message(
+                        &query_character,
+                        Some(entity),
+                        format!("You have not joined {}", active.name),
+                    );
+                    continue;
+                };
+
+                let participant = active.participants.remove(index);
+                for carrier in active.flag_carriers.iter_mut() {
+                    if *carrier == Some(entity) {
+                        *carrier = None;
+                    }
+                }
+                return_participant(&mut commands, &mut teleport_events, &participant);
+                send_event_zone_message(
+                    &query_character,
+                    Some(entity),
+                    format!("You have left {}", active.name),
+                );
+            }
+            EventZoneEvent::Start { name, entity } => {
+                let result = if let Some(event_zone) = game_config.event_zones.get(name) {
+                    start_event_zone(&mut event_zones, &mut server_messages, event_zone, now)
+                        .map(|_| format!("Opened signup for {}", event_zone.name))
+                } else {
+                    Err(format!("Unknown event {}", name))
+                };
+
+                send_event_zone_message(
+                    &query_character,
+                    *entity,
+                    result.unwrap_or_else(|error| error),
+                );
+            }
+            &EventZoneEvent::Stop { entity } => {
+                let Some(active) = event_zones.active.take() else {
+                    send_event_zone_message(
+                        &query_character,
+                        entity,
+                        String::from("There is no event in progress"),
+                    );
+                    continue;
+                };
+
+                for participant in active.participants.iter() {
+                    return_participant(&mut commands, &mut teleport_events, participant);
+                }
+                server_messages.send_global_message(ServerMessage::AnnounceChat {
+                    name: None,
+                    text: format!("{} has been cancelled", active.name),
+                });
+                send_event_zone_message(
+                    &query_character,
+                    entity,
+                    format!("Stopped {}", active.name),
+                );
+            }
+        }
+    }
+
+    let Some(mut active) = event_zones.active.take() else {
+        return;
+    };
+    let Some(event_zone) = game_config.event_zones.get(&active.name) else {
+        return;
+    };
+
+    match active.state {
+        EventZoneState::Signup { start_time } => {
+            if now >= start_time {
+                event_zones.active = begin_event_zone(
+                    &mut commands,
+                    &mut teleport_events,
+                    &mut server_messages,
+                    &query_character,
+                    event_zone,
+                    active,
+                    now,
+                );
+            } else {
+                event_zones.active = Some(active);
+            }
+        }
+        EventZoneState::Running { end_time } => {
+            // Participants who have left the event zone, or disconnected, are
+            // no longer part of the event
+            active.participants.retain_mut(|participant| {
+                let Ok(character) = query_character.get(participant.entity) else {
+                    return false;
+                };
+
+                if character.position.zone_id == event_zone.zone {
+                    participant.has_entered_zone = true;
+                } else if participant.has_entered_zone {
+                    commands
+                        .entity(participant.entity)
+                        .insert(Team::default_character());
+                    return false;
+                }
+
+                true
+            });
+            for carrier in active.flag_carriers.iter_mut() {
+                if carrier.map_or(false, |carrier| {
+                    !active
+                        .participants
+                        .iter()
+                        .any(|participant| participant.entity == carrier)
+                }) {
+                    *carrier = None;
+                }
+            }
+
+            match event_zone.mode {
+                EventZoneMode::CaptureTheFlag => {
+                    update_capture_the_flag(&query_character, event_zone, &mut active)
+                }
+                EventZoneMode::KingOfTheHill => {
+                    update_king_of_the_hill(&query_character, event_zone, &mut active, now)
+                }
+            }
+
+            if now >= end_time
+                || active.participants.is_empty()
+                || active
+                    .scores
+                    .iter()
+                    .any(|score| *score >= event_zone.score_to_win)
+            {
+                finish_event_zone(
+                    &mut commands,
+                    &mut teleport_events,
+                    &mut reward_item_events,
+                    &mut server_messages,
+                    &mut query_honor_points,
+                    &game_data,
+                    event_zone,
+                    active,
+                );
+            } else {
+                event_zones.active = Some(active);
+            }
+        }
+    }
+}
@@ -1,14 +1,17 @@
 use bevy::ecs::prelude::{Entity, EventReader, EventWriter, Query, Res, ResMut};
+use log::warn;
+
+use rose_data::Item;
 
 use crate::game::{
     components::{
         BasicStats, CharacterInfo, ClientEntity, Equipment, ExperiencePoints, GameClient,
-        HealthPoints, Level, ManaPoints, SkillList, SkillPoints, Stamina, StatPoints,
+        HealthPoints, Inventory, Level, ManaPoints, SkillList, SkillPoints, Stamina, StatPoints,
         StatusEffects, MAX_STAMINA,
     },
-    events::{QuestTriggerEvent, RewardXpEvent},
+    events::{QuestTriggerEvent, RewardItemEvent, RewardXpEvent},
     messages::server::ServerMessage,
-    resources::{ServerMessages, WorldRates},
+    resources::{GameConfig, ServerMessages, WorldRates},
     GameData,
 };
 
@@ -21,6 +24,7 @@ pub fn experience_points_system(
         &mut Stamina,
         &mut SkillPoints,
         &mut StatPoints,
+        Option<&mut Inventory>,
         Option<&GameClient>,
     )>,
     mut ability_values_query: Query<(
@@ -33,9 +37,11 @@ pub fn experience_points_system(
         &StatusEffects,
     )>,
     source_entity_query: Query<&ClientEntity>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
     world_rates: Res<WorldRates>,
     mut quest_trigger_events: EventWriter<QuestTriggerEvent>,
+    mut reward_item_events: EventWriter<RewardItemEvent>,
     mut reward_xp_events: EventReader<RewardXpEvent>,
     mut server_messages: ResMut<ServerMessages>,
 ) {
@@ -48,6 +54,7 @@ pub fn experience_points_system(
             mut stamina,
             mut skill_points,
             mut stat_points,
+            mut inventory,
             game_client,
         )) = entity_query.get_mut(reward_xp_event.entity)
         {
@@ -68,14 +75,29 @@ pub fn experience_points_system(
                 }
             }
 
-            // TODO: Apply level cap
             // TODO: Penalty xp?
 
             let level_before = level.level;
+            let mut reward_money = false;
             loop {
-                let need_xp = game_data
-                    .ability_value_calculator
-                    .calculate_levelup_require_xp(level.level);
+                let need_xp = game_config
+                    .level_up
+                    .get_required_xp(level.level)
+                    .unwrap_or_else(|| {
+                        game_data
+                            .ability_value_calculator
+                            .calculate_levelup_require_xp(level.level)
+                    });
+
+                if game_config
+                    .level_cap
+                    .map_or(false, |level_cap| level.level >= level_cap)
+                {
+                    // Stop gaining xp at the level cap
+                    experience_points.xp = experience_points.xp.min(need_xp);
+                    break;
+                }
+
                 if experience_points.xp < need_xp {
                     break;
                 }
@@ -90,6 +112,46 @@ pub fn experience_points_system(
                 stat_points.points += game_data
                     .ability_value_calculator
                     .calculate_levelup_reward_stat_points(level.level);
+
+                for reward in game_config.level_up.get_rewards(level.level) {
+                    skill_points.points += reward.skill_points;
+
+                    if reward.money.0 > 0 {
+                        if let Some(inventory) = inventory.as_mut() {
+                            if inventory.try_add_money(reward.money).is_ok() {
+                                reward_money = true;
+                            }
+                        }
+                    }
+
+                    for reward_item in reward.items.iter() {
+                        if let Some(item) = game_data
+                            .items
+                            .get_base_item(reward_item.item)
+                            .and_then(|item_data| {
+                                Item::from_item_data(item_data, reward_item.quantity)
+                            })
+                        {
+                            reward_item_events.send(RewardItemEvent::new(entity, item, true));
+                        } else {
+                            warn!(
+                                "Invalid level up reward item {:?} for level {}",
+                                reward_item.item, level.level
+                            );
+                        }
+                    }
+                }
+            }
+
+            if reward_money {
+                if let (Some(inventory), Some(game_client)) = (inventory.as_ref(), game_client) {
+                    game_client
+                        .server_message_tx
+                        .send(ServerMessage::UpdateMoney {
+                            money: inventory.money,
+                        })
+                        .ok();
+                }
             }
 
             if level.level != level_before {
@@ -98,6 +160,7 @@ pub fn experience_points_system(
                     quest_trigger_events.send(QuestTriggerEvent {
                         trigger_entity: entity,
                         trigger_hash: format!("levelup_{}", trigger_level).as_str().into(),
+                        reward_choice: None,
                     });
                 }
 
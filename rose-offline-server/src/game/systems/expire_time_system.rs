@@ -1,14 +1,15 @@
 use bevy::{
-    ecs::prelude::{Commands, Entity, Query, Res, ResMut},
+    ecs::prelude::{Commands, Entity, EventWriter, Query, Res, ResMut},
     time::Time,
 };
 
 use crate::game::{
     bundles::client_entity_leave_zone,
     components::{
-        ClientEntity, ClientEntitySector, Command, EntityExpireTime, Owner, OwnerExpireTime,
-        PartyOwner, Position,
+        ClientEntity, ClientEntitySector, Command, DisconnectedCharacter, EntityExpireTime,
+        OfflineVendor, Owner, OwnerExpireTime, PartyOwner, PersonalStore, PkFlag, Position,
     },
+    events::SaveEvent,
     resources::ClientEntityList,
 };
 
@@ -23,8 +24,12 @@ pub fn expire_time_system(
         Option<&Command>,
     )>,
     owner_expire_time_query: Query<(Entity, &OwnerExpireTime)>,
+    disconnected_character_query: Query<(Entity, &DisconnectedCharacter)>,
+    offline_vendor_query: Query<(Entity, &OfflineVendor)>,
+    pk_flag_query: Query<(Entity, &PkFlag)>,
     mut client_entity_list: ResMut<ClientEntityList>,
     time: Res<Time>,
+    mut save_events: EventWriter<SaveEvent>,
 ) {
     entity_expire_time_query.for_each(
         |(entity, entity_expire_time, position, client_entity, client_entity_sector, command)| {
@@ -60,4 +65,36 @@ pub fn expire_time_system(
                 .remove::<PartyOwner>();
         }
     });
+
+    disconnected_character_query.for_each(|(entity, disconnected_character)| {
+        if time.last_update().unwrap() >= disconnected_character.expire_time {
+            // The player did not reconnect in time, let the save system despawn the character
+            commands.entity(entity).remove::<DisconnectedCharacter>();
+            save_events.send(SaveEvent::Character {
+                entity,
+                remove_after_save: true,
+            });
+        }
+    });
+
+    offline_vendor_query.for_each(|(entity, offline_vendor)| {
+        if time.last_update().unwrap() >= offline_vendor.expire_time {
+            // Close the store and let the save system despawn the character, the
+            // proceeds are saved to be reported when the owner next joins the game
+            commands
+                .entity(entity)
+                .remove::<(OfflineVendor, PersonalStore)>()
+                .insert(offline_vendor.proceeds.clone());
+            save_events.send(SaveEvent::Character {
+                entity,
+                remove_after_save: true,
+            });
+        }
+    });
+
+    pk_flag_query.for_each(|(entity, pk_flag)| {
+        if time.last_update().unwrap() >= pk_flag.expire_time {
+            commands.entity(entity).remove::<PkFlag>();
+        }
+    });
 }
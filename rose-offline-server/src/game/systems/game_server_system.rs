@@ -8,6 +8,7 @@ use bevy::{
     time::Time,
 };
 use log::warn;
+use std::time::Instant;
 
 use rose_data::{EquipmentIndex, Item, ItemClass, ItemSlotBehaviour, ItemType};
 use rose_game_common::{
@@ -17,55 +18,230 @@ use rose_game_common::{
 
 use crate::game::{
     bundles::{
-        client_entity_join_zone, client_entity_leave_zone, client_entity_teleport_zone,
-        skill_list_try_level_up_skill, CharacterBundle, ItemDropBundle, SkillListBundle,
+        basic_stats_try_increase, client_entity_join_zone, client_entity_leave_zone,
+        item_assign_character_instance_ids, item_assign_instance_id, skill_list_try_level_up_skill,
+        CharacterBundle, ItemDropBundle, SkillListBundle,
     },
     components::{
-        AbilityValues, Account, Bank, BasicStatType, BasicStats, CharacterInfo, Clan, ClanMember,
+        AbilityValues, Account, Activity, Bank, BasicStats, CharacterInfo, Clan, ClanMember,
         ClanMembership, ClientEntity, ClientEntitySector, ClientEntityType, ClientEntityVisibility,
-        Command, CommandData, Cooldowns, DamageSources, Dead, DrivingTime, DroppedItem, Equipment,
-        EquipmentItemDatabase, ExperiencePoints, GameClient, HealthPoints, Hotbar, Inventory,
-        ItemSlot, Level, ManaPoints, Money, MotionData, MoveMode, MoveSpeed, NextCommand, Party,
-        PartyMember, PartyMembership, PassiveRecoveryTime, Position, QuestState, SkillList,
-        SkillPoints, StatPoints, StatusEffects, StatusEffectsRegen, Team, WorldClient,
+        Command, CommandData, Cooldowns, DamageSources, Dead, DisconnectedCharacter, DrivingTime,
+        DroppedItem, Equipment, EquipmentItemDatabase, ExperiencePoints, GameClient, HealthPoints,
+        Hotbar, Inventory, ItemSlot, Level, ManaPoints, Money, MotionData, MoveMode, MoveSpeed,
+        MovementImpairment, NextCommand, OfflineVendor, OfflineVendorProceeds, Party, PartyMember,
+        PartyMembership, PassiveRecoveryTime, PersonalStore, Position, QuestState, RewardCalendar,
+        SkillList, SkillPoints, Stamina, StatPoints, StatusEffects, StatusEffectsRegen, Team,
+        UnionMembership, WorldClient,
     },
     events::{
-        BankEvent, ChatCommandEvent, ClanEvent, EquipmentEvent, ItemLifeEvent, NpcStoreEvent,
-        PartyEvent, PartyMemberEvent, PersonalStoreEvent, QuestTriggerEvent, ReviveEvent,
-        RevivePosition, UseItemEvent,
+        BankEvent, BarbershopEvent, ChatCommandEvent, ChatEvent, ClanBankEvent, ClanEvent,
+        ClientIntegrityEvent, EquipmentEvent, InventoryEvent, ItemLifeEvent, NpcConversationEvent,
+        NpcStoreEvent, PartyEvent, PartyMemberEvent, PersonalStoreEvent, QuestTriggerEvent,
+        ReviveEvent, RevivePosition, RewardCalendarEvent, SuspicionEvent, SuspicionSignal,
+        UseItemEvent, WarpGateEvent,
     },
     messages::{
         client::ClientMessage,
         server::{ConnectionRequestError, ServerMessage},
     },
-    resources::{ClientEntityList, GameData, LoginTokens, ServerMessages, WorldRates, WorldTime},
-    storage::{account::AccountStorage, bank::BankStorage, character::CharacterStorage},
+    resources::{
+        AccountSessions, ChatChannel, CheatDetection, ClientEntityList, GameConfig, GameData,
+        LoginToken, LoginTokens, Maintenance, ServerMessages, StorageKey, StorageService,
+        WorldRates, WorldTime, ZoneList,
+    },
+    storage::{
+        account::AccountStorage, bank::BankStorage, character::CharacterStorage,
+        reward_calendar::RewardCalendarStorage,
+    },
 };
 
+/// Chat starting with this prefix is sent to the trade channel instead of
+/// local chat
+const TRADE_CHAT_PREFIX: char = '$';
+
+type GameConnectionResult = Result<
+    (
+        u32,
+        Box<CharacterData>,
+        Box<CharacterDataItems>,
+        Box<QuestState>,
+    ),
+    ConnectionRequestError,
+>;
+
+/// A character which is still in the world without a game client, either
+/// during its reconnect grace period or as an offline vendor.
+#[derive(WorldQuery)]
+pub struct DisconnectedCharacterQuery<'w> {
+    entity: Entity,
+    disconnected_character: Option<&'w DisconnectedCharacter>,
+    offline_vendor: Option<&'w OfflineVendor>,
+    account: &'w Account,
+    character_info: &'w CharacterInfo,
+    basic_stats: &'w BasicStats,
+    level: &'w Level,
+    equipment: &'w Equipment,
+    experience_points: &'w ExperiencePoints,
+    skill_list: &'w SkillList,
+    hotbar: &'w Hotbar,
+    health_points: &'w HealthPoints,
+    mana_points: &'w ManaPoints,
+    stat_points: &'w StatPoints,
+    skill_points: &'w SkillPoints,
+    union_membership: &'w UnionMembership,
+    stamina: &'w Stamina,
+    inventory: &'w Inventory,
+    quest_state: &'w QuestState,
+    position: &'w Position,
+    client_entity: Option<&'w ClientEntity>,
+    client_entity_sector: Option<&'w ClientEntitySector>,
+}
+
+fn handle_game_reconnect_request(
+    commands: &mut Commands,
+    account_sessions: &mut AccountSessions,
+    client_entity_list: &mut ClientEntityList,
+    login_token: &mut LoginToken,
+    world_client: &mut WorldClient,
+    entity: Entity,
+    game_client: &mut GameClient,
+    character: DisconnectedCharacterQueryItem,
+) -> GameConnectionResult {
+    if character.offline_vendor.is_some() {
+        account_sessions.claim_offline_vendor(
+            &login_token.username,
+            login_token.token,
+            entity,
+            character.entity,
+        )
+    } else {
+        account_sessions.reconnect_game_client(
+            &login_token.username,
+            login_token.token,
+            entity,
+            character.entity,
+        )
+    }
+    .map_err(|error| {
+        log::warn!(
+            "Rejected game reconnection for account {} with error {:?}",
+            &login_token.username,
+            error
+        );
+        ConnectionRequestError::Failed
+    })?;
+
+    // Rejoin the zone as if teleporting, so the new client is sent every nearby entity
+    if let (Some(client_entity), Some(client_entity_sector)) =
+        (character.client_entity, character.client_entity_sector)
+    {
+        client_entity_leave_zone(
+            commands,
+            client_entity_list,
+            character.entity,
+            client_entity,
+            client_entity_sector,
+            character.position,
+        );
+    }
+
+    // Update token
+    login_token.game_client = Some(character.entity);
+    game_client.login_token = login_token.token;
+
+    // Associate world / game clients
+    game_client.world_client_entity = login_token.world_client;
+    world_client.game_client_entity = Some(character.entity);
+
+    // The character takes over the game client, our entity is no longer needed
+    commands
+        .entity(character.entity)
+        .remove::<DisconnectedCharacter>()
+        .insert(GameClient {
+            client_message_rx: game_client.client_message_rx.clone(),
+            server_message_tx: game_client.server_message_tx.clone(),
+            ip: game_client.ip,
+            login_token: game_client.login_token,
+            world_client_entity: game_client.world_client_entity,
+        });
+    commands.entity(entity).despawn();
+
+    if let Some(offline_vendor) = character.offline_vendor {
+        // Taking control of the character closes its store, the proceeds are
+        // reported once the character has joined the zone
+        commands
+            .entity(character.entity)
+            .remove::<(OfflineVendor, PersonalStore)>()
+            .insert((
+                offline_vendor.proceeds.clone(),
+                Command::default(),
+                NextCommand::default(),
+            ));
+    }
+
+    log::info!(
+        "Character {} reconnected to account {}",
+        &character.character_info.name,
+        &login_token.username
+    );
+
+    Ok((
+        rand::random(),
+        Box::new(CharacterData {
+            character_info: character.character_info.clone(),
+            position: character.position.position,
+            zone_id: character.position.zone_id,
+            basic_stats: character.basic_stats.clone(),
+            level: *character.level,
+            equipment: character.equipment.clone(),
+            experience_points: *character.experience_points,
+            skill_list: character.skill_list.clone(),
+            hotbar: character.hotbar.clone(),
+            health_points: *character.health_points,
+            mana_points: *character.mana_points,
+            stat_points: *character.stat_points,
+            skill_points: *character.skill_points,
+            union_membership: character.union_membership.clone(),
+            stamina: *character.stamina,
+        }),
+        Box::new(CharacterDataItems {
+            inventory: character.inventory.clone(),
+            equipment: character.equipment.clone(),
+        }),
+        Box::new(character.quest_state.clone()),
+    ))
+}
+
 fn handle_game_connection_request(
     commands: &mut Commands,
+    game_config: &GameConfig,
     game_data: &GameData,
+    account_sessions: &mut AccountSessions,
+    client_entity_list: &mut ClientEntityList,
     login_tokens: &mut LoginTokens,
+    storage_service: &StorageService,
+    now: Instant,
     entity: Entity,
     game_client: &mut GameClient,
     token_id: u32,
     password: &Password,
     query_world_client: &mut Query<&mut WorldClient>,
     query_clans: &mut Query<(Entity, &mut Clan)>,
-) -> Result<
-    (
-        u32,
-        Box<CharacterData>,
-        Box<CharacterDataItems>,
-        Box<QuestState>,
-    ),
-    ConnectionRequestError,
-> {
+    query_disconnected_characters: &Query<DisconnectedCharacterQuery>,
+) -> GameConnectionResult {
     // Verify token
     let login_token = login_tokens
         .get_token_mut(token_id)
         .ok_or(ConnectionRequestError::InvalidToken)?;
-    if login_token.world_client.is_none() || login_token.game_client.is_some() {
+    if login_token.is_expired(Instant::now())
+        || login_token.world_client.is_none()
+        || login_token.game_client.is_some()
+        || game_client
+            .packet_codec_login_token
+            .map_or(false, |packet_codec_login_token| {
+                packet_codec_login_token != token_id
+            })
+    {
         return Err(ConnectionRequestError::InvalidToken);
     }
 
@@ -88,8 +264,46 @@ fn handle_game_connection_request(
         })?
         .into();
 
+    // Resume control of the character if it is still in the world after disconnecting
+    if let Some(character) = query_disconnected_characters.iter().find(|character| {
+        character.character_info.name == login_token.selected_character
+            && character.account.name == account.name
+            && character
+                .disconnected_character
+                .map_or(false, |disconnected_character| {
+                    now < disconnected_character.expire_time
+                })
+    }) {
+        return handle_game_reconnect_request(
+            commands,
+            account_sessions,
+            client_entity_list,
+            login_token,
+            &mut world_client,
+            entity,
+            game_client,
+            character,
+        );
+    }
+
+    // Storage does not have the latest data for documents with queued writes
+    if [
+        StorageKey::Bank(login_token.username.clone()),
+        StorageKey::Character(login_token.selected_character.clone()),
+        StorageKey::RewardCalendar(login_token.username.clone()),
+    ]
+    .iter()
+    .any(|key| storage_service.has_pending_write(key))
+    {
+        log::warn!(
+            "Rejected game connection for character {} whilst its saves are queued",
+            &login_token.selected_character
+        );
+        return Err(ConnectionRequestError::Failed);
+    }
+
     // Try load bank
-    let bank = match BankStorage::try_load(&login_token.username) {
+    let mut bank = match BankStorage::try_load(&login_token.username) {
         Ok(bank_storage) => Bank::from(bank_storage),
         Err(_) => match BankStorage::create(&login_token.username) {
             Ok(bank_storage) => {
@@ -107,8 +321,40 @@ fn handle_game_connection_request(
         },
     };
 
+    let bank_source = format!("existing bank item of account {}", &login_token.username);
+    for item in bank.slots.iter_mut().flatten() {
+        item_assign_instance_id(item, &bank_source);
+    }
+
+    // Try load reward calendar, it is created on first claim
+    let reward_calendar = RewardCalendarStorage::try_load(&login_token.username)
+        .map(RewardCalendar::from)
+        .unwrap_or_default();
+
+    // Take control of the character if it was left in the world as an offline
+    // vendor, which does not keep the account documents
+    if let Some(character) = query_disconnected_characters.iter().find(|character| {
+        character.character_info.name == login_token.selected_character
+            && character.account.name == account.name
+            && character.offline_vendor.is_some()
+    }) {
+        commands
+            .entity(character.entity)
+            .insert((bank, reward_calendar));
+        return handle_game_reconnect_request(
+            commands,
+            account_sessions,
+            client_entity_list,
+            login_token,
+            &mut world_client,
+            entity,
+            game_client,
+            character,
+        );
+    }
+
     // Try load character
-    let character =
+    let mut character =
         CharacterStorage::try_load(&login_token.selected_character).map_err(|error| {
             log::error!(
                 "Failed to load character {} with error {:?}",
@@ -117,6 +363,23 @@ fn handle_game_connection_request(
             );
             ConnectionRequestError::Failed
         })?;
+    item_assign_character_instance_ids(
+        &mut character.inventory,
+        &mut character.equipment,
+        &character.info.name,
+    );
+
+    // Only one game client can be in game for an account at a time
+    account_sessions
+        .claim_game_client(&login_token.username, token_id, entity)
+        .map_err(|error| {
+            log::warn!(
+                "Rejected game connection for account {} with error {:?}",
+                &login_token.username,
+                error
+            );
+            ConnectionRequestError::Failed
+        })?;
 
     // Try find clan membership
     let mut clan_membership = ClanMembership(None);
@@ -149,8 +412,17 @@ fn handle_game_connection_request(
     game_client.world_client_entity = login_token.world_client;
     world_client.game_client_entity = Some(entity);
 
-    let status_effects = StatusEffects::new();
-    let status_effects_regen = StatusEffectsRegen::new();
+    let (status_effects, status_effects_regen, cooldowns) = if game_config.clear_effects_on_logout {
+        (
+            StatusEffects::new(),
+            StatusEffectsRegen::new(),
+            Cooldowns::default(),
+        )
+    } else {
+        character
+            .saved_effects
+            .restore(&game_data.status_effects, now)
+    };
 
     let ability_values = game_data.ability_value_calculator.calculate(
         &character.info,
@@ -194,18 +466,25 @@ fn handle_game_connection_request(
     let move_mode = MoveMode::Run;
     let move_speed = MoveSpeed::new(ability_values.get_move_speed(&move_mode));
 
+    if let Some(offline_vendor_proceeds) = character.offline_vendor_proceeds.clone() {
+        commands.entity(entity).insert(offline_vendor_proceeds);
+    }
+
     commands.entity(entity).insert((
         account,
+        reward_calendar,
         CharacterBundle {
             ability_values,
+            achievements: character.achievements.clone(),
             basic_stats: character.basic_stats.clone(),
             bank,
             command: Command::default(),
-            cooldowns: Cooldowns::default(),
+            cooldowns,
             damage_sources: DamageSources::default_character(),
             equipment: character.equipment.clone(),
             experience_points: character.experience_points,
             health_points,
+            honor_points: character.honor_points,
             hotbar: character.hotbar.clone(),
             info: character.info.clone(),
             inventory: character.inventory.clone(),
@@ -214,15 +493,18 @@ fn handle_game_connection_request(
             motion_data,
             move_mode,
             move_speed,
+            movement_impairment: MovementImpairment::new(),
             next_command: NextCommand::default(),
             party_membership: PartyMembership::default(),
             passive_recovery_time: PassiveRecoveryTime::default(),
             position: position.clone(),
             quest_state: character.quest_state.clone(),
+            rebirth: character.rebirth,
             skill_list: character.skill_list.clone(),
             skill_points: character.skill_points,
             stamina: character.stamina,
             stat_points: character.stat_points,
+            statistics: character.statistics.clone(),
             status_effects,
             status_effects_regen,
             team: Team::default_character(),
@@ -232,7 +514,7 @@ fn handle_game_connection_request(
     ));
 
     Ok((
-        123,
+        rand::random(),
         Box::new(CharacterData {
             character_info: character.info,
             position: position.position,
@@ -263,8 +545,15 @@ pub fn game_server_authentication_system(
     mut query: Query<(Entity, &mut GameClient), Without<CharacterInfo>>,
     mut query_world_client: Query<&mut WorldClient>,
     mut query_clans: Query<(Entity, &mut Clan)>,
+    query_disconnected_characters: Query<DisconnectedCharacterQuery>,
+    mut account_sessions: ResMut<AccountSessions>,
+    mut client_entity_list: ResMut<ClientEntityList>,
     mut login_tokens: ResMut<LoginTokens>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
+    maintenance: Res<Maintenance>,
+    storage_service: Res<StorageService>,
+    time: Res<Time>,
 ) {
     query.for_each_mut(|(entity, mut game_client)| {
         if let Ok(message) = game_client.client_message_rx.try_recv() {
@@ -273,16 +562,32 @@ pub fn game_server_authentication_system(
                     login_token,
                     password,
                 } => {
+                    if maintenance.is_zone_entry_blocked(time.last_update().unwrap()) {
+                        game_client
+                            .server_message_tx
+                            .send(ServerMessage::ConnectionRequestError {
+                                error: ConnectionRequestError::Failed,
+                            })
+                            .ok();
+                        return;
+                    }
+
                     match handle_game_connection_request(
                         &mut commands,
+                        game_config.as_ref(),
                         game_data.as_ref(),
+                        account_sessions.as_mut(),
+                        client_entity_list.as_mut(),
                         login_tokens.as_mut(),
+                        storage_service.as_ref(),
+                        time.last_update().unwrap(),
                         entity,
                         game_client.as_mut(),
                         login_token,
                         &password,
                         &mut query_world_client,
                         &mut query_clans,
+                        &query_disconnected_characters,
                     ) {
                         Ok((
                             packet_sequence_id,
@@ -341,14 +646,19 @@ pub fn game_server_join_system(
             &HealthPoints,
             &ManaPoints,
             &Position,
+            &StatusEffects,
+            Option<&PartyMembership>,
+            Option<&OfflineVendorProceeds>,
         ),
         Without<ClientEntity>,
     >,
     mut client_entity_list: ResMut<ClientEntityList>,
     world_rates: Res<WorldRates>,
     world_time: Res<WorldTime>,
+    zone_list: Res<ZoneList>,
     mut party_query: Query<(Entity, &mut Party)>,
     mut party_member_events: EventWriter<PartyMemberEvent>,
+    mut reward_calendar_events: EventWriter<RewardCalendarEvent>,
 ) {
     query.for_each(
         |(
@@ -360,6 +670,9 @@ pub fn game_server_join_system(
             health_points,
             mana_points,
             position,
+            status_effects,
+            current_party_membership,
+            offline_vendor_proceeds,
         )| {
             if let Ok(message) = game_client.client_message_rx.try_recv() {
                 match message {
@@ -371,8 +684,10 @@ pub fn game_server_join_system(
                             ClientEntityType::Character,
                             position,
                         ) {
-                            // See if we are in a party as an offline member
-                            let mut party_membership = PartyMembership::default();
+                            // A character which reconnected is still online in its party,
+                            // otherwise see if we are in a party as an offline member
+                            let mut party_membership =
+                                current_party_membership.cloned().unwrap_or_default();
                             for (party_entity, mut party) in party_query.iter_mut() {
                                 for party_member in party.members.iter_mut() {
                                     if let PartyMember::Offline(
@@ -395,6 +710,16 @@ pub fn game_server_join_system(
                                         }
                                     }
                                 }
+
+                                // A party restored from storage has no owner until the
+                                // first member reconnects
+                                if party_membership.party == Some(party_entity)
+                                    && !party.members.iter().any(|party_member| {
+                                        party_member.get_entity() == Some(party.owner)
+                                    })
+                                {
+                                    party.owner = entity;
+                                }
                             }
 
                             commands
@@ -418,6 +743,50 @@ pub fn game_server_join_system(
                                     town_price_rate: world_rates.town_price_rate,
                                 })
                                 .ok();
+
+                            // Status effects are kept when changing zone, so the client
+                            // must be told about them again
+                            if status_effects
+                                .active
+                                .iter()
+                                .any(|(_, status_effect)| status_effect.is_some())
+                            {
+                                game_client
+                                    .server_message_tx
+                                    .send(ServerMessage::UpdateStatusEffects {
+                                        entity_id,
+                                        status_effects: status_effects.active.clone(),
+                                        updated_values: Vec::new(),
+                                    })
+                                    .ok();
+                            }
+
+                            if let Some(environment) = zone_list.get_environment(position.zone_id) {
+                                game_client
+                                    .server_message_tx
+                                    .send(ServerMessage::UpdateZoneEnvironment {
+                                        time_of_day: environment.time_of_day,
+                                        weather: environment.weather,
+                                    })
+                                    .ok();
+                            }
+
+                            reward_calendar_events.send(RewardCalendarEvent::Prompt { entity });
+
+                            if let Some(offline_vendor_proceeds) = offline_vendor_proceeds {
+                                game_client
+                                    .server_message_tx
+                                    .send(ServerMessage::Whisper {
+                                        from: String::from("SERVER"),
+                                        text: format!(
+                                            "Whilst you were offline your personal store sold {} items for {} zuly",
+                                            offline_vendor_proceeds.items_sold,
+                                            offline_vendor_proceeds.money.0
+                                        ),
+                                    })
+                                    .ok();
+                                commands.entity(entity).remove::<OfflineVendorProceeds>();
+                            }
                         }
                     }
                     _ => warn!("Received unexpected client message {:?}", message),
@@ -438,6 +807,7 @@ pub struct GameClientQuery<'w> {
     ability_values: &'w AbilityValues,
     command: &'w Command,
     dead: Option<&'w Dead>,
+    personal_store: Option<&'w PersonalStore>,
     level: &'w Level,
     move_speed: &'w MoveSpeed,
     team: &'w Team,
@@ -451,21 +821,30 @@ pub struct GameClientQuery<'w> {
     inventory: &'w mut Inventory,
     quest_state: &'w mut QuestState,
     move_mode: &'w mut MoveMode,
+    activity: Option<&'w mut Activity>,
 }
 
 #[derive(SystemParam)]
 pub struct GameEvents<'w> {
     bank_events: EventWriter<'w, BankEvent>,
+    barbershop_events: EventWriter<'w, BarbershopEvent>,
     chat_command_events: EventWriter<'w, ChatCommandEvent>,
+    chat_events: EventWriter<'w, ChatEvent>,
+    clan_bank_events: EventWriter<'w, ClanBankEvent>,
     clan_events: EventWriter<'w, ClanEvent>,
+    client_integrity_events: EventWriter<'w, ClientIntegrityEvent>,
     equipment_events: EventWriter<'w, EquipmentEvent>,
+    inventory_events: EventWriter<'w, InventoryEvent>,
     item_life_events: EventWriter<'w, ItemLifeEvent>,
+    npc_conversation_events: EventWriter<'w, NpcConversationEvent>,
     npc_store_events: EventWriter<'w, NpcStoreEvent>,
     party_events: EventWriter<'w, PartyEvent>,
     personal_store_events: EventWriter<'w, PersonalStoreEvent>,
     quest_trigger_events: EventWriter<'w, QuestTriggerEvent>,
     revive_events: EventWriter<'w, ReviveEvent>,
+    suspicion_events: EventWriter<'w, SuspicionEvent>,
     use_item_events: EventWriter<'w, UseItemEvent>,
+    warp_gate_events: EventWriter<'w, WarpGateEvent>,
 }
 
 pub fn game_server_main_system(
@@ -473,6 +852,7 @@ pub fn game_server_main_system(
     mut events: GameEvents,
     mut game_client_query: Query<GameClientQuery>,
     world_client_query: Query<&WorldClient>,
+    mut cheat_detection: ResMut<CheatDetection>,
     mut client_entity_list: ResMut<ClientEntityList>,
     mut server_messages: ResMut<ServerMessages>,
     game_data: Res<GameData>,
@@ -482,22 +862,38 @@ pub fn game_server_main_system(
         let mut entity_commands = commands.entity(game_client.entity);
 
         if let Ok(message) = game_client.game_client.client_message_rx.try_recv() {
+            if let (Some(activity), Some(now)) = (game_client.activity.as_mut(), time.last_update())
+            {
+                activity.record_activity(now);
+            }
+
             match message {
                 ClientMessage::Chat { text } => {
                     if text.chars().next().map_or(false, |c| c == '/') {
                         events
                             .chat_command_events
                             .send(ChatCommandEvent::new(game_client.entity, text));
+                    } else if let Some(text) = text.strip_prefix(TRADE_CHAT_PREFIX) {
+                        events.chat_events.send(ChatEvent {
+                            entity: game_client.entity,
+                            channel: ChatChannel::Trade,
+                            text: text.to_string(),
+                        });
                     } else {
-                        server_messages.send_entity_message(
-                            game_client.client_entity,
-                            ServerMessage::LocalChat {
-                                entity_id: game_client.client_entity.id,
-                                text,
-                            },
-                        );
+                        events.chat_events.send(ChatEvent {
+                            entity: game_client.entity,
+                            channel: ChatChannel::Local,
+                            text,
+                        });
                     }
                 }
+                ClientMessage::ShoutChat { text } => {
+                    events.chat_events.send(ChatEvent {
+                        entity: game_client.entity,
+                        channel: ChatChannel::Shout,
+                        text,
+                    });
+                }
                 ClientMessage::Move {
                     target_entity_id,
                     x,
@@ -522,6 +918,19 @@ pub fn game_server_main_system(
                     ));
                 }
                 ClientMessage::Attack { target_entity_id } => {
+                    if let Some(now) = time.last_update() {
+                        if !cheat_detection.check_attack_request(
+                            game_client.entity,
+                            now,
+                            game_client.ability_values.get_attack_speed(),
+                        ) {
+                            events.suspicion_events.send(SuspicionEvent::new(
+                                game_client.entity,
+                                SuspicionSignal::AttackRate,
+                            ));
+                        }
+                    }
+
                     if let Some((target_entity, _, _)) = client_entity_list
                         .get_zone(game_client.position.zone_id)
                         .and_then(|zone| zone.get_entity(target_entity_id))
@@ -548,6 +957,15 @@ pub fn game_server_main_system(
                     equipment_index,
                     item_slot,
                 } => {
+                    // Items for sale in the personal store can not be equipped
+                    if let (Some(item_slot), Some(personal_store)) =
+                        (item_slot, game_client.personal_store)
+                    {
+                        if personal_store.is_item_slot_reserved(item_slot) {
+                            continue;
+                        }
+                    }
+
                     events
                         .equipment_events
                         .send(EquipmentEvent::ChangeEquipment {
@@ -579,39 +997,28 @@ pub fn game_server_main_system(
                     });
                 }
                 ClientMessage::IncreaseBasicStat { basic_stat_type } => {
-                    if let Some(cost) = game_data
-                        .ability_value_calculator
-                        .calculate_basic_stat_increase_cost(
-                            &game_client.basic_stats,
-                            basic_stat_type,
-                        )
-                    {
-                        if cost < game_client.stat_points.points {
-                            let value = match basic_stat_type {
-                                BasicStatType::Strength => &mut game_client.basic_stats.strength,
-                                BasicStatType::Dexterity => &mut game_client.basic_stats.dexterity,
-                                BasicStatType::Intelligence => {
-                                    &mut game_client.basic_stats.intelligence
-                                }
-                                BasicStatType::Concentration => {
-                                    &mut game_client.basic_stats.concentration
-                                }
-                                BasicStatType::Charm => &mut game_client.basic_stats.charm,
-                                BasicStatType::Sense => &mut game_client.basic_stats.sense,
-                            };
-
-                            game_client.stat_points.points -= cost;
-                            *value += 1;
-
+                    match basic_stats_try_increase(
+                        &game_data,
+                        &mut game_client.basic_stats,
+                        &mut game_client.stat_points,
+                        basic_stat_type,
+                    ) {
+                        Ok(value) => {
                             game_client
                                 .game_client
                                 .server_message_tx
                                 .send(ServerMessage::UpdateBasicStat {
                                     basic_stat_type,
-                                    value: *value,
+                                    value,
                                 })
                                 .ok();
                         }
+                        Err(error) => {
+                            warn!(
+                                "Rejected increase of basic stat {:?} for entity {:?}: {:?}",
+                                basic_stat_type, game_client.entity, error
+                            );
+                        }
                     }
                 }
                 ClientMessage::PickupItemDrop { target_entity_id } => {
@@ -696,10 +1103,36 @@ pub fn game_server_main_system(
                         }
                     }
                 }
-                ClientMessage::QuestTrigger { trigger } => {
-                    events.quest_trigger_events.send(QuestTriggerEvent {
-                        trigger_entity: game_client.entity,
-                        trigger_hash: trigger,
+                ClientMessage::QuestTrigger {
+                    trigger,
+                    reward_choice,
+                } => {
+                    if game_data.npcs.is_conversation_quest_trigger(trigger) {
+                        events
+                            .npc_conversation_events
+                            .send(NpcConversationEvent::QuestTrigger {
+                                entity: game_client.entity,
+                                trigger_hash: trigger,
+                                reward_choice,
+                            });
+                    } else {
+                        events.quest_trigger_events.send(QuestTriggerEvent {
+                            trigger_entity: game_client.entity,
+                            trigger_hash: trigger,
+                            reward_choice,
+                        });
+                    }
+                }
+                ClientMessage::PersonalStoreOpen {
+                    title,
+                    sell_items,
+                    buy_items,
+                } => {
+                    events.personal_store_events.send(PersonalStoreEvent::Open {
+                        entity: game_client.entity,
+                        title,
+                        sell_items,
+                        buy_items,
                     });
                 }
                 ClientMessage::PersonalStoreListItems { store_entity_id } => {
@@ -715,6 +1148,18 @@ pub fn game_server_main_system(
                             });
                     }
                 }
+                ClientMessage::PersonalStoreSearch {
+                    item_type,
+                    item_name,
+                } => {
+                    events
+                        .personal_store_events
+                        .send(PersonalStoreEvent::Search {
+                            entity: game_client.entity,
+                            item_type,
+                            item_name,
+                        });
+                }
                 ClientMessage::PersonalStoreBuyItem {
                     store_entity_id,
                     store_slot_index,
@@ -738,6 +1183,13 @@ pub fn game_server_main_system(
                     item_slot,
                     target_entity_id,
                 } => {
+                    if game_client
+                        .personal_store
+                        .map_or(false, |store| store.is_item_slot_reserved(item_slot))
+                    {
+                        continue;
+                    }
+
                     let target_entity = target_entity_id
                         .and_then(|target_entity_id| {
                             client_entity_list
@@ -814,6 +1266,15 @@ pub fn game_server_main_system(
                     buy_items,
                     sell_items,
                 } => {
+                    if let Some(personal_store) = game_client.personal_store {
+                        if sell_items
+                            .iter()
+                            .any(|(item_slot, _)| personal_store.is_item_slot_reserved(*item_slot))
+                        {
+                            continue;
+                        }
+                    }
+
                     if let Some((npc_entity, _, _)) = client_entity_list
                         .get_zone(game_client.position.zone_id)
                         .and_then(|zone| zone.get_entity(npc_entity_id))
@@ -908,7 +1369,6 @@ pub fn game_server_main_system(
                             DroppedItem::Money(money),
                             game_client.position,
                             None,
-                            None,
                             &time,
                         );
 
@@ -925,6 +1385,22 @@ pub fn game_server_main_system(
                     item_slot,
                     quantity,
                 } => {
+                    if game_client
+                        .personal_store
+                        .map_or(false, |store| store.is_item_slot_reserved(item_slot))
+                    {
+                        continue;
+                    }
+
+                    // Bound items can not be dropped
+                    if game_client
+                        .inventory
+                        .get_item(item_slot)
+                        .map_or(false, |item| item.is_bound())
+                    {
+                        continue;
+                    }
+
                     if let Some(inventory_slot) = game_client.inventory.get_item_slot_mut(item_slot)
                     {
                         let quantity = u32::min(
@@ -943,7 +1419,6 @@ pub fn game_server_main_system(
                                 DroppedItem::Item(item),
                                 game_client.position,
                                 None,
-                                None,
                                 &time,
                             );
 
@@ -962,24 +1437,10 @@ pub fn game_server_main_system(
                     entity_commands.insert(NextCommand::with_emote(motion_id, is_stop));
                 }
                 ClientMessage::WarpGateRequest { warp_gate_id } => {
-                    if let Some(warp_gate) = game_data.warp_gates.get_warp_gate(warp_gate_id) {
-                        if let Some(zone) = game_data.zones.get_zone(warp_gate.target_zone) {
-                            if let Some(event_position) =
-                                zone.event_positions.get(&warp_gate.target_event_object)
-                            {
-                                client_entity_teleport_zone(
-                                    &mut commands,
-                                    &mut client_entity_list,
-                                    game_client.entity,
-                                    game_client.client_entity,
-                                    game_client.client_entity_sector,
-                                    game_client.position,
-                                    Position::new(*event_position, warp_gate.target_zone),
-                                    Some(game_client.game_client),
-                                );
-                            }
-                        }
-                    }
+                    events.warp_gate_events.send(WarpGateEvent {
+                        entity: game_client.entity,
+                        warp_gate_id,
+                    });
                 }
                 ClientMessage::PartyCreate { invited_entity_id }
                 | ClientMessage::PartyInvite { invited_entity_id } => {
@@ -1055,15 +1516,33 @@ pub fn game_server_main_system(
                     });
                 }
                 ClientMessage::MoveCollision { position } => {
-                    // TODO: Sanity check position
-                    entity_commands
-                        .insert(NextCommand::with_move(position, None, None))
-                        .insert(Position::new(position, game_client.position.zone_id));
+                    if !cheat_detection.check_move_collision(
+                        game_client.position.position,
+                        position,
+                        game_client.move_speed.speed,
+                    ) {
+                        events.suspicion_events.send(SuspicionEvent::new(
+                            game_client.entity,
+                            SuspicionSignal::MovementSpeed,
+                        ));
+                        entity_commands.insert(NextCommand::with_stop(true));
+                    } else {
+                        entity_commands
+                            .insert(NextCommand::with_move(position, None, None))
+                            .insert(Position::new(position, game_client.position.zone_id));
+                    }
                 }
                 ClientMessage::CraftInsertGem {
                     equipment_index,
                     item_slot,
                 } => {
+                    if game_client
+                        .personal_store
+                        .map_or(false, |store| store.is_item_slot_reserved(item_slot))
+                    {
+                        continue;
+                    }
+
                     if game_client
                         .inventory
                         .get_item(item_slot)
@@ -1150,6 +1629,28 @@ pub fn game_server_main_system(
                         }
                     }
                 }
+                ClientMessage::InventorySplitItem {
+                    item_slot,
+                    quantity,
+                } => {
+                    events.inventory_events.send(InventoryEvent::SplitItem {
+                        entity: game_client.entity,
+                        item_slot,
+                        quantity,
+                    });
+                }
+                ClientMessage::InventoryMoveItem { from_slot, to_slot } => {
+                    events.inventory_events.send(InventoryEvent::MoveItem {
+                        entity: game_client.entity,
+                        from_slot,
+                        to_slot,
+                    });
+                }
+                ClientMessage::InventorySort => {
+                    events.inventory_events.send(InventoryEvent::Sort {
+                        entity: game_client.entity,
+                    });
+                }
                 ClientMessage::BankOpen => {
                     events.bank_events.send(BankEvent::Open {
                         entity: game_client.entity,
@@ -1179,6 +1680,33 @@ pub fn game_server_main_system(
                         is_premium,
                     });
                 }
+                ClientMessage::BarbershopPreview {
+                    npc_entity_id,
+                    face,
+                    hair,
+                } => {
+                    if let Some((npc_entity, _, _)) = client_entity_list
+                        .get_zone(game_client.position.zone_id)
+                        .and_then(|zone| zone.get_entity(npc_entity_id))
+                    {
+                        events.barbershop_events.send(BarbershopEvent::Preview {
+                            entity: game_client.entity,
+                            npc_entity: *npc_entity,
+                            face,
+                            hair,
+                        });
+                    }
+                }
+                ClientMessage::BarbershopConfirm => {
+                    events.barbershop_events.send(BarbershopEvent::Confirm {
+                        entity: game_client.entity,
+                    });
+                }
+                ClientMessage::BarbershopCancel => {
+                    events.barbershop_events.send(BarbershopEvent::Cancel {
+                        entity: game_client.entity,
+                    });
+                }
                 ClientMessage::RepairItemUsingNpc {
                     npc_entity_id,
                     item_slot,
@@ -1233,6 +1761,66 @@ pub fn game_server_main_system(
                         mark,
                     });
                 }
+                ClientMessage::ClanUpdateDescription { description } => {
+                    events.clan_events.send(ClanEvent::UpdateDescription {
+                        entity: game_client.entity,
+                        description,
+                    });
+                }
+                ClientMessage::ClanUpdateNotice { notice } => {
+                    events.clan_events.send(ClanEvent::UpdateNotice {
+                        entity: game_client.entity,
+                        notice,
+                    });
+                }
+                ClientMessage::ClanUpdateMark { mark } => {
+                    events.clan_events.send(ClanEvent::UpdateMark {
+                        entity: game_client.entity,
+                        mark,
+                    });
+                }
+                ClientMessage::ClanDeclareWar { clan_name } => {
+                    events.clan_events.send(ClanEvent::DeclareWar {
+                        declarer: game_client.entity,
+                        clan_name,
+                    });
+                }
+                ClientMessage::ClanAcceptWar { clan_name } => {
+                    events.clan_events.send(ClanEvent::AcceptWar {
+                        accepter: game_client.entity,
+                        clan_name,
+                    });
+                }
+                ClientMessage::ClanBankOpen => {
+                    events.clan_bank_events.send(ClanBankEvent::Open {
+                        entity: game_client.entity,
+                    });
+                }
+                ClientMessage::ClanBankDepositItem { item_slot, item } => {
+                    events.clan_bank_events.send(ClanBankEvent::DepositItem {
+                        entity: game_client.entity,
+                        item_slot,
+                        item,
+                    });
+                }
+                ClientMessage::ClanBankWithdrawItem { bank_slot, item } => {
+                    events.clan_bank_events.send(ClanBankEvent::WithdrawItem {
+                        entity: game_client.entity,
+                        bank_slot,
+                        item,
+                    });
+                }
+                ClientMessage::ClanBankGetLog => {
+                    events.clan_bank_events.send(ClanBankEvent::GetLog {
+                        entity: game_client.entity,
+                    });
+                }
+                ClientMessage::ClientIntegrityResponse { hashes } => {
+                    events.client_integrity_events.send(ClientIntegrityEvent {
+                        entity: game_client.entity,
+                        hashes,
+                    });
+                }
                 _ => warn!("[GS] Received unimplemented client message {:?}", message),
             }
         }
@@ -0,0 +1,449 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut, With},
+    time::Time,
+};
+use log::{info, warn};
+use rose_data::Item;
+
+use crate::game::{
+    bundles::MonsterBundle,
+    components::{
+        CharacterInfo, Dead, EntityExpireTime, GameClient, HonorPoints, MoveMode, NextCommand,
+        Owner, SpawnOrigin, Team,
+    },
+    events::{DamageEvent, InvasionEvent, RewardItemEvent, RewardXpEvent},
+    messages::server::ServerMessage,
+    resources::{
+        ActiveInvasion, ClientEntityList, GameConfig, GameData, Invasion, Invasions, ServerMessages,
+    },
+};
+
+/// How far from their spawn point invading monsters and guards are spawned
+const INVASION_SPAWN_RANGE: i32 = 500;
+
+fn send_invasion_message(
+    query_character: &Query<Option<&GameClient>, With<CharacterInfo>>,
+    entity: Option<Entity>,
+    text: String,
+) {
+    let Some(game_client) = entity.and_then(|entity| query_character.get(entity).ok().flatten())
+    else {
+        info!("{}", text);
+        return;
+    };
+
+    game_client
+        .server_message_tx
+        .send(ServerMessage::Whisper {
+            from: String::from("SERVER"),
+            text,
+        })
+        .ok();
+}
+
+fn send_zone_announce(server_messages: &mut ServerMessages, invasion: &Invasion, text: String) {
+    server_messages.send_zone_message(
+        invasion.zone,
+        ServerMessage::AnnounceChat { name: None, text },
+    );
+}
+
+fn start_invasion(
+    commands: &mut Commands,
+    client_entity_list: &mut ClientEntityList,
+    invasions: &mut Invasions,
+    server_messages: &mut ServerMessages,
+    game_data: &GameData,
+    invasion: &Invasion,
+    now: Instant,
+) -> Result<(), String> {
+    if let Some(active) = invasions.active.as_ref() {
+        return Err(format!(
+            "The invasion {} is already in progress",
+            active.name
+        ));
+    }
+
+    let Some(first_wave) = invasion.waves.first() else {
+        return Err(format!("The invasion {} has no waves", invasion.name));
+    };
+
+    if invasion.spawn_points.is_empty() {
+        return Err(format!(
+            "The invasion {} has no spawn points",
+            invasion.name
+        ));
+    }
+
+    let mut active = ActiveInvasion::new(
+        invasion.name.clone(),
+        now,
+        Duration::from_secs(invasion.duration_mins * 60),
+        Duration::from_secs(first_wave.delay_secs),
+    );
+
+    for guard in invasion.guards.iter() {
+        for _ in 0..guard.count {
+            if let Some(entity) = MonsterBundle::spawn(
+                commands,
+                client_entity_list,
+                game_data,
+                guard.npc,
+                invasion.zone,
+                SpawnOrigin::Invasion(guard.position),
+                INVASION_SPAWN_RANGE,
+                Team::default_character(),
+                None,
+                None,
+                None,
+            ) {
+                active.guards.push(entity);
+            }
+        }
+    }
+
+    invasions.active = Some(active);
+    server_messages.send_global_message(ServerMessage::AnnounceChat {
+        name: None,
+        text: format!(
+            "{} has begun, {} waves of monsters are approaching the town",
+            invasion.name,
+            invasion.waves.len()
+        ),
+    });
+    Ok(())
+}
+
+fn spawn_invasion_wave(
+    commands: &mut Commands,
+    client_entity_list: &mut ClientEntityList,
+    game_data: &GameData,
+    invasion: &Invasion,
+    active: &mut ActiveInvasion,
+) {
+    let Some(wave) = invasion.waves.get(active.next_wave) else {
+        return;
+    };
+
+    let npcs = wave
+        .monsters
+        .iter()
+        .flat_map(|monster| std::iter::repeat_n(monster.npc, monster.count));
+    for (index, npc) in npcs.enumerate() {
+        let spawn_point = invasion.spawn_points[index % invasion.spawn_points.len()];
+        let Some(entity) = MonsterBundle::spawn(
+            commands,
+            client_entity_list,
+            game_data,
+            npc,
+            invasion.zone,
+            SpawnOrigin::Invasion(spawn_point),
+            INVASION_SPAWN_RANGE,
+            Team::default_monster(),
+            None,
+            None,
+            None,
+        ) else {
+            warn!("Invalid npc {:?} in invasion {}", npc, invasion.name);
+            continue;
+        };
+
+        // Invading monsters return to the town rather than their spawn point
+        // when they have nothing to attack
+        commands.entity(entity).insert((
+            SpawnOrigin::Invasion(invasion.town),
+            NextCommand::with_move(invasion.town, None, Some(MoveMode::Run)),
+        ));
+        active.monsters.push(entity);
+    }
+
+    active.next_wave += 1;
+    active.next_wave_time = None;
+}
+
+/// Removes the remaining invading monsters and guards, which die if they are
+/// still alive.
+fn remove_invasion_entities(commands: &mut Commands, active: &ActiveInvasion, now: Instant) {
+    for &entity in active.monsters.iter().chain(active.guards.iter()) {
+        commands.entity(entity).insert(EntityExpireTime::new(now));
+    }
+}
+
+fn reward_invasion_contributions(
+    reward_item_events: &mut EventWriter<RewardItemEvent>,
+    reward_xp_events: &mut EventWriter<RewardXpEvent>,
+    query_character: &Query<Option<&GameClient>, With<CharacterInfo>>,
+    query_honor_points: &mut Query<&mut HonorPoints>,
+    game_data: &GameData,
+    invasion: &Invasion,
+    active: &ActiveInvasion,
+) {
+    let total_contribution = active.total_contribution();
+    if total_contribution == 0 {
+        return;
+    }
+
+    for (&entity, &damage) in active.contributions.iter() {
+        if !query_character.contains(entity) {
+            continue;
+        }
+
+        let contribution = damage as f32 / total_contribution as f32;
+        let Some(reward) = invasion.get_reward(contribution) else {
+            continue;
+        };
+
+        if reward.xp > 0 {
+            reward_xp_events.send(RewardXpEvent::new(entity, reward.xp, false, None));
+        }
+
+        if reward.honor_points > 0 {
+            if let Ok(mut honor_points) = query_honor_points.get_mut(entity) {
+                honor_points.add(reward.honor_points);
+            }
+        }
+
+        for reward_item in reward.items.iter() {
+            if let Some(item) = game_data
+                .items
+                .get_base_item(reward_item.item)
+                .and_then(|item_data| Item::from_item_data(item_data, reward_item.quantity))
+            {
+                reward_item_events.send(RewardItemEvent::new(entity, item, true));
+            } else {
+                warn!(
+                    "Invalid reward item {:?} for invasion {}",
+                    reward_item.item, invasion.name
+                );
+            }
+        }
+
+        send_invasion_message(
+            query_character,
+            Some(entity),
+            format!(
+                "You dealt {:.1}% of the damage to the invading monsters",
+                contribution * 100.0
+            ),
+        );
+    }
+}
+
+pub fn invasion_system(
+    mut commands: Commands,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut invasion_events: EventReader<InvasionEvent>,
+    mut invasions: ResMut<Invasions>,
+    mut reward_item_events: EventWriter<RewardItemEvent>,
+    mut reward_xp_events: EventWriter<RewardXpEvent>,
+    mut server_messages: ResMut<ServerMessages>,
+    query_character: Query<Option<&GameClient>, With<CharacterInfo>>,
+    query_dead: Query<Option<&Dead>>,
+    query_owner: Query<&Owner>,
+    mut query_honor_points: Query<&mut HonorPoints>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    for invasion in game_config.invasions.invasions.iter() {
+        let Some(interval_mins) = invasion.interval_mins else {
+            continue;
+        };
+        let interval = Duration::from_secs(interval_mins * 60);
+        let next_start = *invasions
+            .next_scheduled
+            .entry(invasion.name.clone())
+            .or_insert(now + interval);
+
+        if now >= next_start {
+            invasions
+                .next_scheduled
+                .insert(invasion.name.clone(), now + interval);
+
+            if invasions.active.is_none() {
+                if let Err(error) = start_invasion(
+                    &mut commands,
+                    &mut client_entity_list,
+                    &mut invasions,
+                    &mut server_messages,
+                    &game_data,
+                    invasion,
+                    now,
+                ) {
+                    warn!("Failed to start scheduled invasion: {}", error);
+                }
+            }
+        }
+    }
+
+    for event in invasion_events.iter() {
+        match event {
+            InvasionEvent::Start { name, entity } => {
+                let result = if let Some(invasion) = game_config.invasions.get(name) {
+                    start_invasion(
+                        &mut commands,
+                        &mut client_entity_list,
+                        &mut invasions,
+                        &mut server_messages,
+                        &game_data,
+                        invasion,
+                        now,
+                    )
+                    .map(|_| format!("Started {}", invasion.name))
+                } else {
+                    Err(format!("Unknown invasion {}", name))
+                };
+
+                send_invasion_message(
+                    &query_character,
+                    *entity,
+                    result.unwrap_or_else(|error| error),
+                );
+            }
+            &InvasionEvent::Stop { entity } => {
+                let Some(active) = invasions.active.take() else {
+                    send_invasion_message(
+                        &query_character,
+                        entity,
+                        String::from("There is no invasion in progress"),
+                    );
+                    continue;
+                };
+
+                remove_invasion_entities(&mut commands, &active, now);
+                if let Some(invasion) = game_config.invasions.get(&active.name) {
+                    send_zone_announce(
+                        &mut server_messages,
+                        invasion,
+                        format!("{} has been called off", invasion.name),
+                    );
+                }
+                send_invasion_message(&query_character, entity, format!("Stopped {}", active.name));
+            }
+        }
+    }
+
+    let Some(mut active) = invasions.active.take() else {
+        damage_events.clear();
+        return;
+    };
+    let Some(invasion) = game_config.invasions.get(&active.name) else {
+        return;
+    };
+
+    for damage_event in damage_events.iter() {
+        let (attacker, defender, damage) = match *damage_event {
+            DamageEvent::Attack {
+                attacker,
+                defender,
+                damage,
+            }
+            | DamageEvent::Immediate {
+                attacker,
+                defender,
+                damage,
+            }
+            | DamageEvent::Skill {
+                attacker,
+                defender,
+                damage,
+                ..
+            } => (attacker, defender, damage),
+            DamageEvent::Tagged { .. } => continue,
+        };
+
+        if !active.monsters.contains(&defender) {
+            continue;
+        }
+
+        // Damage dealt by summons counts towards their owner
+        let attacker = query_owner
+            .get(attacker)
+            .map_or(attacker, |owner| owner.entity);
+        if query_character.contains(attacker) {
+            *active.contributions.entry(attacker).or_default() += damage.amount as u64;
+        }
+    }
+
+    let is_alive = |entity: &Entity| query_dead.get(*entity).map_or(false, |dead| dead.is_none());
+    active.monsters.retain(is_alive);
+    active.guards.retain(is_alive);
+
+    if active.monsters.is_empty() && active.next_wave_time.is_none() {
+        if let Some(next_wave) = invasion.waves.get(active.next_wave) {
+            active.next_wave_time = Some(now + Duration::from_secs(next_wave.delay_secs));
+            send_zone_announce(
+                &mut server_messages,
+                invasion,
+                format!(
+                    "Wave {} of {} has been defeated, {} waves remain",
+                    active.next_wave,
+                    invasion.waves.len(),
+                    invasion.waves.len() - active.next_wave
+                ),
+            );
+        } else {
+            reward_invasion_contributions(
+                &mut reward_item_events,
+                &mut reward_xp_events,
+                &query_character,
+                &mut query_honor_points,
+                &game_data,
+                invasion,
+                &active,
+            );
+            remove_invasion_entities(&mut commands, &active, now);
+            send_zone_announce(
+                &mut server_messages,
+                invasion,
+                format!("{} has been repelled, the town is safe!", invasion.name),
+            );
+            return;
+        }
+    }
+
+    if active
+        .next_wave_time
+        .map_or(false, |next_wave_time| now >= next_wave_time)
+    {
+        spawn_invasion_wave(
+            &mut commands,
+            &mut client_entity_list,
+            &game_data,
+            invasion,
+            &mut active,
+        );
+        send_zone_announce(
+            &mut server_messages,
+            invasion,
+            format!(
+                "Wave {} of {} is approaching the town with {} monsters",
+                active.next_wave,
+                invasion.waves.len(),
+                active.monsters.len()
+            ),
+        );
+    }
+
+    if now >= active.end_time {
+        remove_invasion_entities(&mut commands, &active, now);
+        send_zone_announce(
+            &mut server_messages,
+            invasion,
+            format!(
+                "{} was not repelled in time, the monsters have withdrawn",
+                invasion.name
+            ),
+        );
+        return;
+    }
+
+    invasions.active = Some(active);
+}
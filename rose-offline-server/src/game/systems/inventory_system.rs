@@ -0,0 +1,96 @@
+use bevy::prelude::{EventReader, Query};
+use log::warn;
+
+use crate::game::{
+    components::{GameClient, Inventory, ItemSlot, PersonalStore},
+    events::InventoryEvent,
+    messages::server::ServerMessage,
+};
+
+fn send_inventory_update(game_client: &GameClient, inventory: &Inventory, slots: &[ItemSlot]) {
+    game_client
+        .server_message_tx
+        .send(ServerMessage::UpdateInventory {
+            items: slots
+                .iter()
+                .map(|slot| (*slot, inventory.get_item(*slot).cloned()))
+                .collect(),
+            money: None,
+        })
+        .ok();
+}
+
+pub fn inventory_system(
+    mut inventory_events: EventReader<InventoryEvent>,
+    mut query: Query<(&GameClient, &mut Inventory, Option<&PersonalStore>)>,
+) {
+    for event in inventory_events.iter() {
+        let entity = match *event {
+            InventoryEvent::SplitItem { entity, .. }
+            | InventoryEvent::MoveItem { entity, .. }
+            | InventoryEvent::Sort { entity } => entity,
+        };
+
+        let Ok((game_client, mut inventory, personal_store)) = query.get_mut(entity) else {
+            continue;
+        };
+
+        // Items are reserved by slot whilst a personal store is open
+        if personal_store.is_some() {
+            continue;
+        }
+
+        match *event {
+            InventoryEvent::SplitItem {
+                item_slot,
+                quantity,
+                ..
+            } => match inventory.try_split_item(item_slot, quantity) {
+                Ok(split_slot) => {
+                    send_inventory_update(game_client, &inventory, &[item_slot, split_slot])
+                }
+                Err(error) => {
+                    warn!(
+                        "Failed to split item in slot {:?} quantity {} with error {:?}",
+                        item_slot, quantity, error
+                    );
+                    send_inventory_update(game_client, &inventory, &[item_slot]);
+                }
+            },
+            InventoryEvent::MoveItem {
+                from_slot, to_slot, ..
+            } => {
+                if let Err(error) = inventory.try_move_item(from_slot, to_slot) {
+                    warn!(
+                        "Failed to move item from slot {:?} to {:?} with error {:?}",
+                        from_slot, to_slot, error
+                    );
+                }
+
+                // Always resend both slots so the client is in sync even on failure
+                send_inventory_update(game_client, &inventory, &[from_slot, to_slot]);
+            }
+            InventoryEvent::Sort { .. } => {
+                let previous_inventory = inventory.clone();
+                inventory.sort();
+
+                let updated_slots: Vec<ItemSlot> = [
+                    &inventory.equipment,
+                    &inventory.consumables,
+                    &inventory.materials,
+                    &inventory.vehicles,
+                ]
+                .into_iter()
+                .flat_map(|page| {
+                    (0..page.slots.len()).map(|index| ItemSlot::Inventory(page.page_type, index))
+                })
+                .filter(|slot| inventory.get_item(*slot) != previous_inventory.get_item(*slot))
+                .collect();
+
+                if !updated_slots.is_empty() {
+                    send_inventory_update(game_client, &inventory, &updated_slots);
+                }
+            }
+        }
+    }
+}
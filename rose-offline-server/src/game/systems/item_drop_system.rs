@@ -0,0 +1,207 @@
+use bevy::{
+    ecs::prelude::{Added, Commands, Entity, Local, Query, Res, ResMut, With},
+    time::Time,
+};
+use chrono::Utc;
+use log::{debug, error};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use rose_data::ZoneId;
+
+use crate::game::{
+    bundles::client_entity_leave_zone,
+    components::{
+        ClientEntity, ClientEntitySector, DefaultExpireTime, EntityExpireTime, ItemDrop,
+        PersistentItemDrop, Position,
+    },
+    resources::{ClientEntityList, GameConfig, GameData, StorageKey, StorageService, ZoneList},
+    storage::item_drop::{ItemDropStorage, ZoneItemDropStorage},
+};
+
+pub fn item_drop_system(
+    mut commands: Commands,
+    mut new_item_drop_query: Query<
+        (
+            Entity,
+            &ItemDrop,
+            &Position,
+            &mut EntityExpireTime,
+            Option<&DefaultExpireTime>,
+        ),
+        Added<ItemDrop>,
+    >,
+    item_drop_query: Query<(&Position, &ClientEntity, &ClientEntitySector), With<ItemDrop>>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    mut zone_list: ResMut<ZoneList>,
+) {
+    let now = time.last_update().unwrap();
+    let max_drops_per_zone = game_config.item_drops.max_drops_per_zone;
+    let mut spawn_zone_ids = Vec::new();
+
+    for (entity, item_drop, position, mut expire_time, default_expire_time) in
+        new_item_drop_query.iter_mut()
+    {
+        // Only new item drops have the default expire time, item drops restored
+        // from storage keep their saved expire time
+        if let (Some(dropped_item), Some(_)) = (item_drop.item.as_ref(), default_expire_time) {
+            commands.entity(entity).remove::<DefaultExpireTime>();
+            expire_time.when = now
+                + game_config
+                    .item_drops
+                    .get_expire_time(dropped_item, &game_data.items);
+
+            if game_config
+                .item_drops
+                .is_persistent(dropped_item, &game_data.items)
+            {
+                commands.entity(entity).insert(PersistentItemDrop);
+            }
+        }
+
+        if max_drops_per_zone.is_some() {
+            zone_list.add_item_drop(position.zone_id, entity);
+            if !spawn_zone_ids.contains(&position.zone_id) {
+                spawn_zone_ids.push(position.zone_id);
+            }
+        }
+    }
+
+    let Some(max_drops_per_zone) = max_drops_per_zone else {
+        return;
+    };
+
+    for zone_id in spawn_zone_ids {
+        let Some(zone_item_drops) = zone_list.get_item_drops_mut(zone_id) else {
+            continue;
+        };
+        if zone_item_drops.len() <= max_drops_per_zone {
+            continue;
+        }
+
+        // Forget item drops which have already been picked up or expired
+        zone_item_drops.retain(|entity| item_drop_query.contains(*entity));
+
+        // Remove the oldest item drops until the zone is back under its limit
+        let num_removed = zone_item_drops.len().saturating_sub(max_drops_per_zone);
+        for entity in zone_item_drops.drain(..num_removed) {
+            if let Ok((position, client_entity, client_entity_sector)) = item_drop_query.get(entity)
+            {
+                client_entity_leave_zone(
+                    &mut commands,
+                    &mut client_entity_list,
+                    entity,
+                    client_entity,
+                    client_entity_sector,
+                    position,
+                );
+            }
+            commands.entity(entity).despawn();
+        }
+
+        if num_removed > 0 {
+            debug!(
+                "Removed {} oldest item drops from zone {} to stay under the limit of {}",
+                num_removed,
+                zone_id.get(),
+                max_drops_per_zone
+            );
+        }
+    }
+}
+
+/// Periodically saves the persistent item drops of every zone, zones which no
+/// longer have any persistent item drops have their saved item drops deleted.
+pub fn item_drop_persist_system(
+    item_drop_query: Query<(&ItemDrop, &Position, &EntityExpireTime), With<PersistentItemDrop>>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+    mut storage_service: ResMut<StorageService>,
+    mut time_since_checkpoint: Local<Duration>,
+    mut saved_zone_ids: Local<Option<HashSet<ZoneId>>>,
+) {
+    let Some(persist) = game_config.item_drops.persist.as_ref() else {
+        return;
+    };
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    *time_since_checkpoint += time.delta();
+    if *time_since_checkpoint < Duration::from_secs(persist.checkpoint_interval_secs) {
+        return;
+    }
+    *time_since_checkpoint = Duration::ZERO;
+
+    let utc_now = Utc::now();
+    let mut zones: HashMap<ZoneId, Vec<ItemDropStorage>> = HashMap::new();
+    for (item_drop, position, expire_time) in item_drop_query.iter() {
+        let Some(item) = item_drop.item.clone() else {
+            continue;
+        };
+        let Ok(remaining) =
+            chrono::Duration::from_std(expire_time.when.saturating_duration_since(now))
+        else {
+            continue;
+        };
+
+        zones
+            .entry(position.zone_id)
+            .or_default()
+            .push(ItemDropStorage {
+                position: position.clone(),
+                item,
+                expire_time: utc_now + remaining,
+            });
+    }
+
+    // Before the first checkpoint any zone may still have item drops saved
+    // from before the server was restarted
+    let previous_zone_ids: Vec<ZoneId> = match saved_zone_ids.take() {
+        Some(zone_ids) => zone_ids.into_iter().collect(),
+        None => game_data
+            .zones
+            .iter()
+            .map(|zone_data| zone_data.id)
+            .collect(),
+    };
+
+    for zone_id in previous_zone_ids {
+        if zones.contains_key(&zone_id) {
+            continue;
+        }
+
+        if let Err(error) = storage_service.write(StorageKey::ItemDrops(zone_id), move || {
+            ZoneItemDropStorage::delete(zone_id)
+        }) {
+            error!(
+                "Failed to delete item drops for zone {} with error {:?}",
+                zone_id.get(),
+                error
+            );
+        }
+    }
+
+    *saved_zone_ids = Some(zones.keys().copied().collect());
+    for (zone_id, item_drops) in zones {
+        let storage = ZoneItemDropStorage {
+            zone_id,
+            item_drops,
+        };
+        if let Err(error) =
+            storage_service.write(StorageKey::ItemDrops(zone_id), move || storage.save())
+        {
+            error!(
+                "Failed to save item drops for zone {} with error {:?}",
+                zone_id.get(),
+                error
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::{
+    ecs::prelude::{Entity, EventReader, Query, Res, ResMut},
+    math::{Vec2, Vec3Swizzles},
+};
+
+use crate::game::{
+    components::{ClientEntity, ClientEntitySector, Command, CommandData, Dead, Position},
+    events::KnockbackEvent,
+    messages::server::ServerMessage,
+    resources::{ClientEntityList, GameData, ServerMessages, ZoneGeometry},
+};
+
+// How many times the knockback distance is halved when the path is blocked
+// by an obstacle before the knockback is abandoned
+const KNOCKBACK_MAX_COLLISION_STEPS: usize = 4;
+
+pub fn knockback_system(
+    mut query: Query<(
+        Entity,
+        &ClientEntity,
+        &mut ClientEntitySector,
+        &mut Position,
+        &mut Command,
+        Option<&Dead>,
+    )>,
+    mut knockback_events: EventReader<KnockbackEvent>,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    mut server_messages: ResMut<ServerMessages>,
+    game_data: Res<GameData>,
+    zone_geometry: Res<ZoneGeometry>,
+) {
+    for knockback_event in knockback_events.iter() {
+        let Ok((entity, client_entity, mut client_entity_sector, mut position, mut command, dead)) =
+            query.get_mut(knockback_event.entity)
+        else {
+            continue;
+        };
+
+        if dead.is_some() || command.is_dead() {
+            continue;
+        }
+
+        let start = position.position.xy();
+        let direction = (start - knockback_event.origin).normalize_or_zero();
+        if direction == Vec2::ZERO {
+            continue;
+        }
+
+        let mut destination = start + direction * knockback_event.distance;
+        if let Some(nav_grid) = game_data.zone_nav_grids.get_zone_nav_grid(position.zone_id) {
+            destination = nav_grid.clamp_destination(start, destination);
+        }
+
+        // Shorten the knockback until it is no longer blocked by an obstacle
+        let Some(destination) = (0..=KNOCKBACK_MAX_COLLISION_STEPS)
+            .map(|step| start + (destination - start) / 2.0f32.powi(step as i32))
+            .find(|destination| {
+                zone_geometry.has_line_of_sight(
+                    position.zone_id,
+                    position.position,
+                    destination.extend(position.position.z),
+                )
+            })
+        else {
+            continue;
+        };
+
+        position.position.x = destination.x;
+        position.position.y = destination.y;
+
+        if matches!(command.command, CommandData::Move { .. }) {
+            *command = Command::with_stop();
+        }
+
+        if let Some(zone) = client_entity_list.get_zone_mut(position.zone_id) {
+            zone.update_position(
+                entity,
+                client_entity,
+                &mut client_entity_sector,
+                position.position,
+            );
+        }
+
+        server_messages.send_entity_message(
+            client_entity,
+            ServerMessage::AdjustPosition {
+                entity_id: client_entity.id,
+                position: position.position,
+            },
+        );
+    }
+}
@@ -2,17 +2,85 @@ use bevy::ecs::prelude::{Commands, Entity, Query, Res, ResMut, Without};
 use log::warn;
 
 use crate::game::{
-    components::{Account, LoginClient},
+    components::{Account, GameClient, LoginClient, WorldClient},
     messages::client::ClientMessage,
-    messages::server::{ChannelListError, JoinServerError, LoginError, ServerMessage},
-    resources::{LoginTokens, ServerList},
+    messages::server::{
+        ChannelListError, ChannelListItem, JoinServerError, LoginError, ServerMessage,
+    },
+    resources::{
+        AccountSession, AccountSessions, GameConfig, LoginTokens, Maintenance, ServerList,
+    },
     storage::account::{AccountStorage, AccountStorageError},
 };
 
+fn disconnect_account_session(
+    commands: &mut Commands,
+    login_tokens: &mut LoginTokens,
+    session: AccountSession,
+) {
+    // Removing the client component closes its connection, the entity is then
+    // cleaned up by the usual disconnect handling
+    if let Some(login_client) = session.login_client {
+        commands.entity(login_client).remove::<LoginClient>();
+    }
+
+    if let Some(world_client) = session.world_client {
+        commands.entity(world_client).remove::<WorldClient>();
+    }
+
+    if let Some(game_client) = session.game_client {
+        commands.entity(game_client).remove::<GameClient>();
+    }
+
+    if let Some(index) = session.login_token.and_then(|token_id| {
+        login_tokens
+            .tokens
+            .iter()
+            .position(|login_token| login_token.token == token_id)
+    }) {
+        login_tokens.remove(index);
+    }
+}
+
+fn claim_account_session(
+    commands: &mut Commands,
+    account_sessions: &mut AccountSessions,
+    login_tokens: &mut LoginTokens,
+    game_config: &GameConfig,
+    account_name: &str,
+    login_client: Entity,
+) -> Result<(), LoginError> {
+    if account_sessions
+        .try_claim(account_name, login_client)
+        .is_ok()
+    {
+        return Ok(());
+    }
+
+    if !game_config.disconnect_duplicate_login {
+        return Err(LoginError::AlreadyLoggedIn);
+    }
+
+    if let Some(session) = account_sessions.take_over(account_name) {
+        log::info!(
+            "Disconnecting existing session for account {} after duplicate login",
+            account_name
+        );
+        disconnect_account_session(commands, login_tokens, session);
+    }
+
+    account_sessions
+        .try_claim(account_name, login_client)
+        .map_err(|_| LoginError::AlreadyLoggedIn)
+}
+
 pub fn login_server_authentication_system(
     mut commands: Commands,
     query: Query<(Entity, &LoginClient), Without<Account>>,
-    login_tokens: Res<LoginTokens>,
+    mut account_sessions: ResMut<AccountSessions>,
+    mut login_tokens: ResMut<LoginTokens>,
+    game_config: Res<GameConfig>,
+    maintenance: Res<Maintenance>,
     server_list: Res<ServerList>,
 ) {
     query.for_each(|(entity, login_client)| {
@@ -22,47 +90,90 @@ pub fn login_server_authentication_system(
                     login_client
                         .server_message_tx
                         .send(ServerMessage::ConnectionRequestSuccess {
-                            packet_sequence_id: 123,
+                            packet_sequence_id: rand::random(),
                         })
                         .ok();
                 }
                 ClientMessage::LoginRequest { username, password } => {
-                    let login_result = if login_tokens.find_username_token(&username).is_some() {
-                        Err(LoginError::AlreadyLoggedIn)
-                    } else {
-                        match AccountStorage::try_load(&username, &password) {
-                            Ok(account) => Ok(account),
-                            Err(error) => match error.downcast_ref::<AccountStorageError>() {
-                                Some(AccountStorageError::NotFound) => {
-                                    match AccountStorage::create(&username, &password) {
-                                        Ok(account) => {
-                                            log::info!("Created account {}", &username);
-                                            Ok(account)
-                                        }
-                                        Err(error) => {
-                                            log::info!(
-                                                "Failed to create account {} with error {:?}",
-                                                &username,
-                                                error
-                                            );
-                                            Err(LoginError::InvalidAccount)
-                                        }
+                    let country = login_client.country.as_deref();
+                    let login_result = match AccountStorage::try_load(&username, &password) {
+                        Ok(account) => Ok(account),
+                        Err(error) => match error.downcast_ref::<AccountStorageError>() {
+                            Some(AccountStorageError::NotFound)
+                                if game_config
+                                    .geo_ip
+                                    .as_ref()
+                                    .map_or(false, |geo_ip| !geo_ip.can_register(country)) =>
+                            {
+                                log::info!(
+                                    "Refused creating account {} from {} in country {}",
+                                    &username,
+                                    login_client.ip,
+                                    country.unwrap_or("unknown")
+                                );
+                                Err(LoginError::OutsideRegion)
+                            }
+                            Some(AccountStorageError::NotFound) => {
+                                match AccountStorage::create(&username, &password) {
+                                    Ok(account) => {
+                                        log::info!(
+                                            "Created account {} from {}",
+                                            &username,
+                                            login_client.ip
+                                        );
+                                        Ok(account)
+                                    }
+                                    Err(error) => {
+                                        log::info!(
+                                            "Failed to create account {} with error {:?}",
+                                            &username,
+                                            error
+                                        );
+                                        Err(LoginError::InvalidAccount)
                                     }
                                 }
-                                Some(AccountStorageError::InvalidPassword) => {
-                                    Err(LoginError::InvalidPassword)
-                                }
-                                _ => {
-                                    log::error!(
-                                        "Failed to load account {} with error {:?}",
-                                        &username,
-                                        error
-                                    );
-                                    Err(LoginError::Failed)
-                                }
-                            },
+                            }
+                            Some(AccountStorageError::InvalidPassword) => {
+                                Err(LoginError::InvalidPassword)
+                            }
+                            _ => {
+                                log::error!(
+                                    "Failed to load account {} with error {:?}",
+                                    &username,
+                                    error
+                                );
+                                Err(LoginError::Failed)
+                            }
+                        },
+                    }
+                    .and_then(|account| {
+                        if maintenance.is_active() && !game_config.is_gm_account(&account.name) {
+                            Err(LoginError::Maintenance)
+                        } else {
+                            Ok(account)
                         }
-                    };
+                    })
+                    .and_then(|account| match game_config.geo_ip.as_ref() {
+                        Some(geo_ip)
+                            if !geo_ip.can_login(country)
+                                && !game_config.is_gm_account(&account.name) =>
+                        {
+                            Err(LoginError::OutsideRegion)
+                        }
+                        _ => Ok(account),
+                    })
+                    .and_then(|account| {
+                        // Only claim the session once the password has been verified
+                        claim_account_session(
+                            &mut commands,
+                            &mut account_sessions,
+                            &mut login_tokens,
+                            &game_config,
+                            &account.name,
+                            entity,
+                        )
+                        .map(|_| account)
+                    });
 
                     let response = match login_result {
                         Ok(account) => {
@@ -90,9 +201,16 @@ pub fn login_server_authentication_system(
 
 pub fn login_server_system(
     mut query: Query<(Entity, &Account, &mut LoginClient)>,
+    mut account_sessions: ResMut<AccountSessions>,
     mut login_tokens: ResMut<LoginTokens>,
     server_list: Res<ServerList>,
+    game_config: Res<GameConfig>,
 ) {
+    let max_players = game_config
+        .channel_capacity
+        .as_ref()
+        .map(|channel_capacity| channel_capacity.max_players);
+
     query.for_each_mut(|(entity, account, mut login_client)| {
         if let Ok(message) = login_client.client_message_rx.try_recv() {
             match message {
@@ -104,7 +222,11 @@ pub fn login_server_system(
                         |world_server| {
                             let mut channels = Vec::new();
                             for (id, channel) in world_server.channels.iter().enumerate() {
-                                channels.push((id as u8, channel.name.clone()));
+                                channels.push(ChannelListItem {
+                                    id: id as u8,
+                                    name: channel.name.clone(),
+                                    percent_full: channel.percent_full(max_players),
+                                });
                             }
                             ServerMessage::ChannelList {
                                 server_id,
@@ -128,16 +250,53 @@ pub fn login_server_system(
                                     error: JoinServerError::InvalidChannelId,
                                 },
                                 |game_server| {
+                                    // GM accounts can always join a full channel
+                                    if max_players.map_or(false, |max_players| {
+                                        game_server.num_clients >= max_players
+                                    }) && !game_config.is_gm_account(&account.name)
+                                    {
+                                        return ServerMessage::JoinServerError {
+                                            error: JoinServerError::ChannelFull,
+                                        };
+                                    }
+
                                     login_client.login_token = login_tokens.generate(
                                         account.name.clone(),
                                         entity,
+                                        login_client.ip,
                                         world_server.entity,
                                         game_server.entity,
                                     );
+
+                                    // A session only has one valid login token
+                                    if let Some(index) = account_sessions
+                                        .set_login_token(&account.name, login_client.login_token)
+                                        .and_then(|previous_token| {
+                                            login_tokens.tokens.iter().position(|login_token| {
+                                                login_token.token == previous_token
+                                            })
+                                        })
+                                    {
+                                        login_tokens.remove(index);
+                                    }
+                                    let packet_codec_seed = login_tokens
+                                        .get_token_mut(login_client.login_token)
+                                        .map_or(world_server.packet_codec_seed, |token| {
+                                            token.world_packet_codec_seed
+                                        });
+                                    // Clients are sent the world server address for
+                                    // their region when there is one
+                                    let ip = game_config
+                                        .geo_ip
+                                        .as_ref()
+                                        .and_then(|geo_ip| {
+                                            geo_ip.world_server_ip(login_client.country.as_deref())
+                                        })
+                                        .unwrap_or(world_server.ip.as_str());
                                     ServerMessage::JoinServerSuccess {
                                         login_token: login_client.login_token,
-                                        packet_codec_seed: world_server.packet_codec_seed,
-                                        ip: world_server.ip.clone(),
+                                        packet_codec_seed,
+                                        ip: ip.to_string(),
                                         port: world_server.port,
                                     }
                                 },
@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    ecs::prelude::{Local, Res, ResMut},
+    time::Time,
+};
+
+use crate::game::resources::{AccountSessions, LoginTokens};
+
+const LOGIN_TOKEN_EXPIRE_INTERVAL: Duration = Duration::from_secs(10);
+
+pub fn login_token_expire_system(
+    mut account_sessions: ResMut<AccountSessions>,
+    mut login_tokens: ResMut<LoginTokens>,
+    time: Res<Time>,
+    mut time_since_update: Local<Duration>,
+) {
+    *time_since_update += time.delta();
+    if *time_since_update < LOGIN_TOKEN_EXPIRE_INTERVAL {
+        return;
+    }
+    *time_since_update = Duration::ZERO;
+
+    let expired = login_tokens.remove_expired(Instant::now());
+    for &token_id in expired.iter() {
+        account_sessions.release_login_token(token_id);
+    }
+
+    let metrics = login_tokens.get_metrics();
+    if !expired.is_empty() {
+        log::info!(
+            "Removed {} expired login tokens, outstanding tokens: {:?}",
+            expired.len(),
+            metrics
+        );
+    } else {
+        log::debug!("Outstanding login tokens: {:?}", metrics);
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::{
+    app::AppExit,
+    ecs::prelude::{Commands, Entity, EventWriter, Query, Res, ResMut, With},
+    prelude::Or,
+    time::Time,
+};
+use log::{error, info};
+
+use crate::game::{
+    components::{CharacterInfo, ClientEntity, GameClient, LoginClient, WorldClient},
+    events::SaveEvent,
+    messages::server::ServerMessage,
+    resources::{Maintenance, MaintenanceState, ServerMessages, StorageService},
+};
+
+/// The remaining seconds of the countdown at which it is announced to every
+/// online player
+const MAINTENANCE_ANNOUNCE_AT_SECS: [u64; 16] = [
+    3600, 1800, 900, 600, 300, 240, 180, 120, 60, 30, 10, 5, 4, 3, 2, 1,
+];
+
+/// Disconnected clients are saved by the control server, so wait for their
+/// saves to be sent before checking the storage writes have completed
+const MAINTENANCE_MIN_SHUTDOWN_TIME: Duration = Duration::from_secs(2);
+
+/// Exit even if the storage writes have not completed by this time, the
+/// queued writes are logged so they can be recovered from a backup
+const MAINTENANCE_MAX_SHUTDOWN_TIME: Duration = Duration::from_secs(30);
+
+fn format_countdown(remaining_secs: u64) -> String {
+    if remaining_secs >= 60 && remaining_secs.is_multiple_of(60) {
+        let minutes = remaining_secs / 60;
+        format!("{} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+    } else {
+        format!(
+            "{} second{}",
+            remaining_secs,
+            if remaining_secs == 1 { "" } else { "s" }
+        )
+    }
+}
+
+pub fn maintenance_system(
+    mut commands: Commands,
+    query_characters: Query<Entity, (With<CharacterInfo>, With<ClientEntity>)>,
+    query_clients: Query<Entity, Or<(With<LoginClient>, With<WorldClient>, With<GameClient>)>>,
+    mut maintenance: ResMut<Maintenance>,
+    mut save_events: EventWriter<SaveEvent>,
+    mut server_messages: ResMut<ServerMessages>,
+    storage_service: Res<StorageService>,
+    time: Res<Time>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let now = time.last_update().unwrap();
+
+    match maintenance.get_state() {
+        None => {}
+        Some(MaintenanceState::Countdown { shutdown_at }) => {
+            if let Some(remaining_secs) =
+                maintenance.take_announcement(now, &MAINTENANCE_ANNOUNCE_AT_SECS)
+            {
+                let mut text = format!(
+                    "The server will restart for maintenance in {}",
+                    format_countdown(remaining_secs)
+                );
+                if !maintenance.get_reason().is_empty() {
+                    text = format!("{}: {}", text, maintenance.get_reason());
+                }
+                server_messages
+                    .send_global_message(ServerMessage::AnnounceChat { name: None, text });
+            }
+
+            if now < shutdown_at {
+                return;
+            }
+
+            info!("Server maintenance started, saving and disconnecting all clients");
+            for entity in query_characters.iter() {
+                save_events.send(SaveEvent::Character {
+                    entity,
+                    remove_after_save: false,
+                });
+            }
+
+            // Dropping the server message senders disconnects the clients
+            for entity in query_clients.iter() {
+                commands
+                    .entity(entity)
+                    .remove::<(LoginClient, WorldClient, GameClient)>();
+            }
+
+            maintenance.set_shutting_down(now);
+        }
+        Some(MaintenanceState::ShuttingDown { started_at }) => {
+            let shutdown_time = now.saturating_duration_since(started_at);
+            if shutdown_time < MAINTENANCE_MIN_SHUTDOWN_TIME {
+                return;
+            }
+
+            let num_pending_writes = storage_service.num_pending_writes();
+            if num_pending_writes == 0 {
+                info!("All characters saved, exiting for server maintenance");
+                app_exit_events.send(AppExit);
+            } else if shutdown_time >= MAINTENANCE_MAX_SHUTDOWN_TIME {
+                error!(
+                    "Exiting for server maintenance with {} storage writes still pending, last error: {}",
+                    num_pending_writes,
+                    storage_service.last_error().unwrap_or("none")
+                );
+                app_exit_events.send(AppExit);
+            }
+        }
+    }
+}
@@ -1,36 +1,64 @@
 mod ability_values_changed_system;
 mod ability_values_update_character_system;
 mod ability_values_update_npc_system;
+mod achievement_system;
+mod activity_system;
 mod bank_system;
+mod barbershop_system;
+mod bot_scenario_system;
+mod character_inspect_system;
 mod chat_commands_system;
+mod chat_system;
+mod cheat_detection_system;
+mod clan_bank_system;
 mod clan_system;
 mod client_entity_visibility_system;
+mod client_integrity_system;
 mod command_system;
 mod control_server_system;
 mod damage_system;
 mod driving_time_system;
 mod equipment_event_system;
+mod event_zone_system;
 mod experience_points_system;
 mod expire_time_system;
 mod game_server_system;
+mod invasion_system;
+mod inventory_system;
+mod item_drop_system;
 mod item_life_system;
+mod knockback_system;
 mod login_server_system;
+mod login_token_expire_system;
+mod maintenance_system;
 mod monster_spawn_system;
 mod npc_ai_system;
+mod npc_conversation_system;
 mod npc_store_system;
 mod party_system;
 mod passive_recovery_system;
 mod personal_store_system;
 mod pickup_item_system;
+mod position_history_system;
 mod quest_system;
+mod rebirth_system;
 mod revive_event_system;
+mod reward_calendar_system;
 mod reward_item_system;
 mod save_system;
 mod server_messages_system;
 mod skill_effect_system;
+mod spectator_system;
 mod startup_clans_system;
+mod startup_item_drops_system;
+mod startup_parties_system;
 mod startup_zones_system;
+mod statistics_system;
 mod status_effect_system;
+mod storage_service_system;
+mod teleport_event_system;
+mod teleport_system;
+mod tick_profiler_system;
 mod update_motion_data_system;
 mod update_position_system;
 mod use_ammo_system;
@@ -38,45 +66,76 @@ mod use_item_system;
 mod weight_system;
 mod world_server_system;
 mod world_time_system;
+mod zone_environment_system;
+mod zone_snapshot_system;
+mod zone_transition_system;
 
 pub use ability_values_changed_system::ability_values_changed_system;
 pub use ability_values_update_character_system::ability_values_update_character_system;
 pub use ability_values_update_npc_system::ability_values_update_npc_system;
+pub use achievement_system::achievement_system;
+pub use activity_system::activity_system;
 pub use bank_system::bank_system;
+pub use barbershop_system::barbershop_system;
+pub use bot_scenario_system::bot_scenario_system;
+pub use character_inspect_system::character_inspect_system;
 pub use chat_commands_system::chat_commands_system;
+pub use chat_system::chat_system;
+pub use cheat_detection_system::cheat_detection_system;
+pub use clan_bank_system::clan_bank_system;
 pub use clan_system::clan_system;
 pub use client_entity_visibility_system::client_entity_visibility_system;
+pub use client_integrity_system::client_integrity_system;
 pub use command_system::command_system;
 pub use control_server_system::control_server_system;
 pub use damage_system::damage_system;
 pub use driving_time_system::driving_time_system;
 pub use equipment_event_system::equipment_event_system;
+pub use event_zone_system::event_zone_system;
 pub use experience_points_system::experience_points_system;
 pub use expire_time_system::expire_time_system;
 pub use game_server_system::{
     game_server_authentication_system, game_server_join_system, game_server_main_system,
 };
+pub use invasion_system::invasion_system;
+pub use inventory_system::inventory_system;
+pub use item_drop_system::{item_drop_persist_system, item_drop_system};
 pub use item_life_system::item_life_system;
+pub use knockback_system::knockback_system;
 pub use login_server_system::{login_server_authentication_system, login_server_system};
+pub use login_token_expire_system::login_token_expire_system;
+pub use maintenance_system::maintenance_system;
 pub use monster_spawn_system::monster_spawn_system;
 pub use npc_ai_system::npc_ai_system;
-pub use npc_store_system::npc_store_system;
+pub use npc_conversation_system::npc_conversation_system;
+pub use npc_store_system::{npc_store_restock_system, npc_store_system};
 pub use party_system::{
-    party_member_event_system, party_member_update_info_system, party_system,
-    party_update_average_level_system,
+    create_party_member_map_marker, party_member_event_system, party_member_map_markers_system,
+    party_member_update_info_system, party_system, party_update_average_level_system,
 };
 pub use passive_recovery_system::passive_recovery_system;
-pub use personal_store_system::personal_store_system;
+pub use personal_store_system::{personal_store_list_system, personal_store_system};
 pub use pickup_item_system::pickup_item_system;
+pub use position_history_system::position_history_system;
 pub use quest_system::quest_system;
+pub use rebirth_system::rebirth_system;
 pub use revive_event_system::revive_event_system;
+pub use reward_calendar_system::reward_calendar_system;
 pub use reward_item_system::reward_item_system;
 pub use save_system::save_system;
 pub use server_messages_system::server_messages_system;
 pub use skill_effect_system::skill_effect_system;
+pub use spectator_system::spectator_system;
 pub use startup_clans_system::startup_clans_system;
+pub use startup_item_drops_system::startup_item_drops_system;
+pub use startup_parties_system::startup_parties_system;
 pub use startup_zones_system::startup_zones_system;
+pub use statistics_system::{leaderboard_system, statistics_system};
 pub use status_effect_system::status_effect_system;
+pub use storage_service_system::storage_service_system;
+pub use teleport_event_system::teleport_event_system;
+pub use teleport_system::teleport_system;
+pub use tick_profiler_system::tick_profiler_system;
 pub use update_motion_data_system::{
     update_character_motion_data_system, update_npc_motion_data_system,
 };
@@ -86,3 +145,6 @@ pub use use_item_system::use_item_system;
 pub use weight_system::weight_system;
 pub use world_server_system::{world_server_authentication_system, world_server_system};
 pub use world_time_system::world_time_system;
+pub use zone_environment_system::zone_environment_system;
+pub use zone_snapshot_system::zone_snapshot_system;
+pub use zone_transition_system::zone_transition_system;
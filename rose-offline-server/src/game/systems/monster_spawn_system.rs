@@ -1,5 +1,6 @@
 use bevy::{
     ecs::prelude::{Commands, Entity, Query, Res, ResMut},
+    math::Vec3Swizzles,
     time::Time,
 };
 
@@ -7,8 +8,8 @@ use rose_data::NpcId;
 
 use crate::game::{
     bundles::MonsterBundle,
-    components::{MonsterSpawnPoint, Position, SpawnOrigin, Team},
-    resources::{ClientEntityList, GameData, ZoneList},
+    components::{ClientEntityType, EliteMonster, MonsterSpawnPoint, Position, SpawnOrigin, Team},
+    resources::{ClientEntityList, GameConfig, GameData, ZoneList},
 };
 
 pub fn monster_spawn_system(
@@ -16,6 +17,7 @@ pub fn monster_spawn_system(
     mut query: Query<(Entity, &mut MonsterSpawnPoint, &Position)>,
     time: Res<Time>,
     mut client_entity_list: ResMut<ClientEntityList>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
     zone_list: Res<ZoneList>,
 ) {
@@ -27,22 +29,63 @@ pub fn monster_spawn_system(
 
             let spawn_point = &mut *spawn_point;
             spawn_point.time_since_last_check += time.delta();
-            if spawn_point.time_since_last_check < spawn_point.interval {
+
+            // The shortest the interval can be scaled to, so we only count the
+            // nearby characters when the spawn point could be ready
+            let min_interval = game_config.monster_spawn_scaling.as_ref().map_or(
+                spawn_point.interval,
+                |monster_spawn_scaling| {
+                    spawn_point
+                        .interval
+                        .div_f32(monster_spawn_scaling.max_scale.max(1.0))
+                },
+            );
+            if spawn_point.time_since_last_check < min_interval {
+                return;
+            }
+
+            let spawn_scale =
+                game_config
+                    .monster_spawn_scaling
+                    .as_ref()
+                    .map_or(1.0, |monster_spawn_scaling| {
+                        let num_characters = client_entity_list
+                            .get_zone(spawn_point_position.zone_id)
+                            .map_or(0, |client_entity_zone| {
+                                client_entity_zone
+                                    .iter_entity_type_within_distance(
+                                        spawn_point_position.position.xy(),
+                                        monster_spawn_scaling.character_search_distance,
+                                        &[ClientEntityType::Character],
+                                    )
+                                    .count()
+                            });
+                        monster_spawn_scaling.get_scale(num_characters)
+                    });
+            if spawn_scale <= 0.0 {
+                // Spawn point is disabled until characters are nearby
+                spawn_point.time_since_last_check = Default::default();
                 return;
             }
-            spawn_point.time_since_last_check -= spawn_point.interval;
 
+            let interval = spawn_point.interval.div_f32(spawn_scale);
+            if spawn_point.time_since_last_check < interval {
+                return;
+            }
+            spawn_point.time_since_last_check -= interval;
+
+            let limit_count =
+                ((spawn_point.limit_count as f32 * spawn_scale).round() as u32).max(1);
             let live_count = spawn_point.num_alive_monsters;
-            if live_count >= spawn_point.limit_count {
+            if live_count >= limit_count {
                 spawn_point.current_tactics_value =
                     spawn_point.current_tactics_value.saturating_sub(1);
                 return;
             }
 
-            let regen_value = ((spawn_point.limit_count * 2 - live_count)
-                * spawn_point.current_tactics_value
-                * 50)
-                / (spawn_point.limit_count * spawn_point.tactic_points);
+            let regen_value =
+                ((limit_count * 2 - live_count) * spawn_point.current_tactics_value * 50)
+                    / (limit_count * spawn_point.tactic_points);
 
             let mut spawn_queue: Vec<(NpcId, usize)> = Vec::new();
             match regen_value {
@@ -166,8 +209,35 @@ pub fn monster_spawn_system(
             let spawn_point_position = spawn_point_position.position;
             let spawn_range = (spawn_point.range * 100) as i32;
 
+            let time_of_day = zone_list
+                .get_environment(spawn_point_zone)
+                .map(|environment| environment.time_of_day);
+
             for (npc_id, count) in spawn_queue {
+                if time_of_day.map_or(false, |time_of_day| {
+                    !game_config
+                        .zone_environment
+                        .can_monster_spawn(npc_id, time_of_day)
+                }) {
+                    continue;
+                }
+
                 for _ in 0..count {
+                    let elite_monster =
+                        game_config
+                            .elite_monsters
+                            .as_ref()
+                            .and_then(|elite_monsters| {
+                                let chance = elite_monsters.get_chance(npc_id, spawn_point_zone);
+                                (rand::random::<f32>() < chance).then(|| EliteMonster {
+                                    health_multiplier: elite_monsters.health_multiplier,
+                                    attack_multiplier: elite_monsters.attack_multiplier,
+                                    defence_multiplier: elite_monsters.defence_multiplier,
+                                    experience_multiplier: elite_monsters.experience_multiplier,
+                                    drop_count: elite_monsters.drop_count,
+                                })
+                            });
+
                     if MonsterBundle::spawn(
                         &mut commands,
                         &mut client_entity_list,
@@ -179,6 +249,7 @@ pub fn monster_spawn_system(
                         Team::default_monster(),
                         None,
                         None,
+                        elite_monster,
                     )
                     .is_some()
                     {
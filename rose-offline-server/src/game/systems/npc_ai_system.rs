@@ -2,7 +2,7 @@ use arrayvec::ArrayVec;
 use bevy::math::{Vec3, Vec3Swizzles};
 use bevy::{
     ecs::{
-        prelude::{Commands, Entity, EventWriter, Query, Res, ResMut},
+        prelude::{Commands, Entity, EventWriter, Query, Res, ResMut, With, Without},
         query::WorldQuery,
         system::SystemParam,
     },
@@ -14,7 +14,7 @@ use std::{
     marker::PhantomData,
     num::NonZeroU8,
     ops::{Range, RangeInclusive},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use rose_data::{ClanMemberPosition, Item, MotionId, NpcId, SkillId, ZoneId};
@@ -29,21 +29,35 @@ use rose_file_readers::{
 use rose_game_common::{data::Damage, messages::PartyXpSharing};
 
 use crate::game::{
-    bundles::{client_entity_leave_zone, ItemDropBundle, MonsterBundle},
+    bundles::{client_entity_leave_zone, ItemDropBundle, ItemDropOwner, MonsterBundle},
     components::{
         AbilityValues, Clan, ClanMembership, ClientEntity, ClientEntitySector, ClientEntityType,
-        Command, CommandData, DamageSources, DroppedItem, GameClient, HealthPoints, Level,
-        MonsterSpawnPoint, MoveMode, NextCommand, Npc, NpcAi, ObjectVariables, Owner, Party,
-        PartyMember, PartyMembership, Position, SpawnOrigin, StatusEffects, Team,
+        Command, CommandData, DamageSources, DroppedItem, EliteMonster, GameClient, GuardPost,
+        HealthPoints, Level, MonsterSpawnPoint, MoveMode, NextCommand, Npc, NpcAi, ObjectVariables,
+        OfflineVendor, Owner, Party, PartyMember, PartyMembership, PkFlag, Position, SpawnOrigin,
+        Spectator, StatusEffects, Team,
+    },
+    events::{
+        AchievementEvent, DamageEvent, QuestTriggerEvent, RewardItemEvent, RewardXpEvent,
+        StatisticsEvent,
     },
-    events::{DamageEvent, QuestTriggerEvent, RewardItemEvent, RewardXpEvent},
     messages::server::ServerMessage,
-    resources::{ClientEntityList, ServerMessages, WorldRates, WorldTime, ZoneList},
+    resources::{
+        ClientEntityList, GameConfig, GuardAggroRules, ServerMessages, WorldRates, WorldTime,
+        ZoneList,
+    },
     GameData,
 };
 
 const DAMAGE_REWARD_EXPIRE_TIME: Duration = Duration::from_secs(5 * 60);
 
+/// How long after a npc was last damaged its attacker is still a target for
+/// the guards defending it
+const GUARD_DEFEND_DURATION: Duration = Duration::from_secs(10);
+
+/// How close to its post a returning guard must be before it stops
+const GUARD_POST_RADIUS: f32 = 100.0;
+
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 pub struct NpcQuery<'w> {
@@ -62,6 +76,7 @@ pub struct NpcQuery<'w> {
     owner: Option<&'w Owner>,
     spawn_origin: Option<&'w SpawnOrigin>,
     damage_sources: Option<&'w DamageSources>,
+    elite_monster: Option<&'w EliteMonster>,
 }
 
 #[derive(WorldQuery)]
@@ -100,12 +115,34 @@ pub struct TargetQuery<'w> {
     clan_membership: Option<&'w ClanMembership>,
 }
 
+#[derive(WorldQuery)]
+pub struct GuardQuery<'w> {
+    entity: Entity,
+    npc: &'w Npc,
+    command: &'w Command,
+    position: &'w Position,
+    team: &'w Team,
+    spawn_origin: Option<&'w SpawnOrigin>,
+    guard_post: Option<&'w GuardPost>,
+}
+
+#[derive(WorldQuery)]
+pub struct GuardTargetQuery<'w> {
+    client_entity: &'w ClientEntity,
+    position: &'w Position,
+    team: &'w Team,
+    health_points: &'w HealthPoints,
+    spawn_origin: Option<&'w SpawnOrigin>,
+    pk_flag: Option<&'w PkFlag>,
+}
+
 #[derive(SystemParam)]
 pub struct AiSystemParameters<'w, 's> {
     commands: Commands<'w, 's>,
     client_entity_list: ResMut<'w, ClientEntityList>,
     server_messages: ResMut<'w, ServerMessages>,
-    target_query: Query<'w, 's, TargetQuery<'static>>,
+    // Spectating GMs can not be found or targeted by monsters
+    target_query: Query<'w, 's, TargetQuery<'static>, (Without<Spectator>, Without<OfflineVendor>)>,
     object_variable_query: Query<'w, 's, &'static mut ObjectVariables>,
     owner_query: Query<'w, 's, (&'static Position, &'static Command)>,
     clan_query: Query<'w, 's, &'static Clan>,
@@ -117,6 +154,7 @@ pub struct AiSystemParameters<'w, 's> {
 
 #[derive(SystemParam)]
 pub struct AiSystemResources<'w, 's> {
+    game_config: Res<'w, GameConfig>,
     game_data: Res<'w, GameData>,
     time: Res<'w, Time>,
     world_time: Res<'w, WorldTime>,
@@ -905,6 +943,7 @@ fn ai_action_move_random_distance(
                     SpawnOrigin::MonsterSpawnPoint(_, spawn_position) => spawn_position,
                     SpawnOrigin::Summoned(_, spawn_position) => spawn_position,
                     SpawnOrigin::Quest(_, spawn_position) => spawn_position,
+                    SpawnOrigin::Invasion(spawn_position) => spawn_position,
                 })
         }
         AipMoveOrigin::FindChar => ai_parameters.find_char.map(|(_, position)| position),
@@ -1055,6 +1094,7 @@ fn ai_action_quest_trigger(
                 .send(QuestTriggerEvent {
                     trigger_entity: entity,
                     trigger_hash,
+                    reward_choice: None,
                 });
         }
     } else {
@@ -1063,6 +1103,7 @@ fn ai_action_quest_trigger(
             .send(QuestTriggerEvent {
                 trigger_entity: ai_parameters.source.entity,
                 trigger_hash,
+                reward_choice: None,
             });
     }
 }
@@ -1080,6 +1121,7 @@ fn ai_action_kill_self(
                 amount: ai_parameters.source.health_points.hp as u32 + 1,
                 is_critical: false,
                 apply_hit_stun: false,
+                is_blocked: false,
             },
         });
 }
@@ -1194,6 +1236,7 @@ fn ai_action_spawn_npc(
             ai_parameters.source.team.clone(),
             None,
             None,
+            None,
         ) {
             if is_owner {
                 ai_system_parameters
@@ -1431,7 +1474,6 @@ fn ai_action_drop_random_item(
             DroppedItem::Item(item),
             ai_parameters.source.position,
             None,
-            None,
             &ai_system_resources.time,
         );
     }
@@ -1636,16 +1678,208 @@ fn npc_ai_run_trigger(
     }
 }
 
+fn guard_is_valid_target(
+    rules: &GuardAggroRules,
+    guard: &GuardQueryItem,
+    target: &GuardTargetQueryItem,
+    zone_list: &ZoneList,
+) -> bool {
+    if target.health_points.hp <= 0
+        || target.position.zone_id != guard.position.zone_id
+        || target.team.id == guard.team.id
+        || target.team.id == Team::DEFAULT_NPC_TEAM_ID
+    {
+        return false;
+    }
+
+    match target.client_entity.entity_type {
+        ClientEntityType::Character => {
+            rules.attack_pk_flagged
+                && target.pk_flag.is_some()
+                && !zone_list.is_safe_zone(target.position.zone_id)
+        }
+        ClientEntityType::Monster => {
+            // Summons and invasion guards are on the characters' team
+            target.team.id != Team::DEFAULT_CHARACTER_TEAM_ID
+                && (rules.attack_monsters
+                    || (rules.attack_invasion_monsters
+                        && matches!(target.spawn_origin, Some(SpawnOrigin::Invasion(_)))))
+        }
+        _ => false,
+    }
+}
+
+fn guard_move_to_post(
+    commands: &mut Commands,
+    guard: &GuardQueryItem,
+    post: Vec3,
+    move_mode: MoveMode,
+) {
+    if let CommandData::Move {
+        destination,
+        target: None,
+        ..
+    } = guard.command.command
+    {
+        if destination == post {
+            return;
+        }
+    }
+
+    commands
+        .entity(guard.entity)
+        .insert(NextCommand::with_move(post, None, Some(move_mode)));
+}
+
+fn npc_ai_guard(
+    ai_system_parameters: &mut AiSystemParameters,
+    rules: &GuardAggroRules,
+    guard: &GuardQueryItem,
+    guard_target_query: &Query<GuardTargetQuery, Without<Spectator>>,
+    defended_npc_query: &Query<(&Team, &DamageSources), With<Npc>>,
+    now: Instant,
+) {
+    if matches!(guard.command.command, CommandData::Die { .. }) {
+        return;
+    }
+
+    let Some(guard_post) = guard.guard_post else {
+        let post = match guard.spawn_origin {
+            Some(&SpawnOrigin::MonsterSpawnPoint(_, spawn_position))
+            | Some(&SpawnOrigin::Summoned(_, spawn_position))
+            | Some(&SpawnOrigin::Quest(_, spawn_position))
+            | Some(&SpawnOrigin::Invasion(spawn_position)) => spawn_position,
+            None => guard.position.position,
+        };
+        ai_system_parameters
+            .commands
+            .entity(guard.entity)
+            .insert(GuardPost::new(post));
+        return;
+    };
+
+    let guard_position = guard.position.position.xy();
+    if guard_position.distance(guard_post.position.xy()) > rules.leash_range {
+        guard_move_to_post(
+            &mut ai_system_parameters.commands,
+            guard,
+            guard_post.position,
+            MoveMode::Run,
+        );
+        return;
+    }
+
+    // Keep attacking the current target whilst it is still a valid target
+    if let CommandData::Attack { target } = guard.command.command {
+        if guard_target_query.get(target).map_or(false, |target| {
+            guard_is_valid_target(rules, guard, &target, &ai_system_parameters.zone_list)
+        }) {
+            return;
+        }
+    }
+
+    let Some(zone_entities) = ai_system_parameters
+        .client_entity_list
+        .get_zone(guard.position.zone_id)
+    else {
+        return;
+    };
+
+    let mut nearest_target: Option<(Entity, f32)> = None;
+    let mut nearest_npc_attacker: Option<(Entity, f32)> = None;
+    for (entity, position) in
+        zone_entities.iter_entities_within_distance(guard_position, rules.aggro_range)
+    {
+        if entity == guard.entity {
+            continue;
+        }
+
+        if let Ok(target) = guard_target_query.get(entity) {
+            let distance = guard_position.distance(position.xy());
+            if guard_is_valid_target(rules, guard, &target, &ai_system_parameters.zone_list)
+                && nearest_target.map_or(true, |(_, nearest)| distance < nearest)
+            {
+                nearest_target = Some((entity, distance));
+            }
+        }
+
+        if !rules.defend_npcs {
+            continue;
+        }
+
+        let Ok((npc_team, damage_sources)) = defended_npc_query.get(entity) else {
+            continue;
+        };
+        if npc_team.id != guard.team.id {
+            continue;
+        }
+
+        for damage_source in damage_sources.damage_sources.iter() {
+            if now - damage_source.last_damage_time > GUARD_DEFEND_DURATION {
+                continue;
+            }
+
+            let Ok(attacker) = guard_target_query.get(damage_source.entity) else {
+                continue;
+            };
+            let distance = guard_position.distance(attacker.position.position.xy());
+            if attacker.health_points.hp > 0
+                && attacker.team.id != guard.team.id
+                && attacker.position.zone_id == guard.position.zone_id
+                && distance <= rules.aggro_range
+                && nearest_npc_attacker.map_or(true, |(_, nearest)| distance < nearest)
+            {
+                nearest_npc_attacker = Some((damage_source.entity, distance));
+            }
+        }
+    }
+
+    if let Some((target, _)) = nearest_npc_attacker.or(nearest_target) {
+        if guard.command.target_entity() != Some(target) {
+            ai_system_parameters
+                .commands
+                .entity(guard.entity)
+                .insert(NextCommand::with_attack(target));
+        }
+        return;
+    }
+
+    // Nothing left to attack, return to post
+    let is_at_post = guard_position.distance(guard_post.position.xy()) <= GUARD_POST_RADIUS;
+    match guard.command.command {
+        CommandData::Attack { .. } if is_at_post => {
+            ai_system_parameters
+                .commands
+                .entity(guard.entity)
+                .insert(NextCommand::with_stop(true));
+        }
+        CommandData::Attack { .. } | CommandData::Stop { .. } if !is_at_post => {
+            guard_move_to_post(
+                &mut ai_system_parameters.commands,
+                guard,
+                guard_post.position,
+                MoveMode::Walk,
+            );
+        }
+        _ => {}
+    }
+}
+
 pub fn npc_ai_system(
     mut ai_system_parameters: AiSystemParameters,
     ai_system_resources: AiSystemResources,
     mut npc_query: Query<NpcQuery>,
+    guard_query: Query<GuardQuery>,
+    guard_target_query: Query<GuardTargetQuery, Without<Spectator>>,
+    defended_npc_query: Query<(&Team, &DamageSources), With<Npc>>,
     mut spawn_point_query: Query<&mut MonsterSpawnPoint>,
     attacker_query: Query<AttackerQuery>,
     killer_query: Query<KillerQuery>,
     query_party: Query<&Party>,
     world_rates: Res<WorldRates>,
     mut reward_xp_events: EventWriter<RewardXpEvent>,
+    mut achievement_events: EventWriter<AchievementEvent>,
+    mut statistics_events: EventWriter<StatisticsEvent>,
 ) {
     for mut source in npc_query.iter_mut() {
         if !source.ai.has_run_created_trigger {
@@ -1789,7 +2023,7 @@ pub fn npc_ai_system(
                                     })
                                     .unwrap_or((attacker.entity, attacker.level));
 
-                                let reward_xp = ai_system_resources
+                                let mut reward_xp = ai_system_resources
                                     .game_data
                                     .ability_value_calculator
                                     .calculate_give_xp(
@@ -1800,6 +2034,11 @@ pub fn npc_ai_system(
                                         npc_data.reward_xp as i32,
                                         world_rates.xp_rate,
                                     );
+                                if let Some(elite_monster) = source.elite_monster {
+                                    reward_xp = (reward_xp as f32
+                                        * elite_monster.experience_multiplier)
+                                        as i32;
+                                }
 
                                 if reward_xp <= 0 {
                                     continue;
@@ -1929,16 +2168,21 @@ pub fn npc_ai_system(
                                         })
                                         .unwrap_or(killer);
 
+                                    achievement_events.send(AchievementEvent::Kill {
+                                        entity: killer.entity,
+                                        npc_id: source.npc.id,
+                                    });
+                                    statistics_events.send(StatisticsEvent::MonsterKill {
+                                        entity: killer.entity,
+                                        npc_id: source.npc.id,
+                                    });
+
                                     // Inform client to execute npc dead event
                                     if !npc_data.death_quest_trigger_name.is_empty() {
                                         if let Some(killer_game_client) = killer.game_client {
-                                            // TODO: Send NPC death trigger to whole party
-                                            /*
-                                            if npc_data.npc_quest_type != 0 {
-                                            }
-                                            */
-
-                                            // Send to only client
+                                            // Send to only client, quest_system shares
+                                            // the progress of shared quests with the
+                                            // killer's nearby party members
                                             killer_game_client
                                                 .server_message_tx
                                                 .send(ServerMessage::RunNpcDeathTrigger {
@@ -1948,12 +2192,30 @@ pub fn npc_ai_system(
                                         }
                                     }
 
-                                    // Drop item owned by killer
+                                    // Drop item owned by killer, elite monsters roll the drop table multiple times
                                     let level_difference =
                                         killer.level.level as i32 - source.level.level as i32;
-                                    if let Some(drop_item) =
+                                    let drop_count = source
+                                        .elite_monster
+                                        .map_or(1, |elite_monster| elite_monster.drop_count);
+                                    let drop_rate = ai_system_parameters
+                                        .zone_list
+                                        .get_environment(source.position.zone_id)
+                                        .map_or(world_rates.drop_rate, |environment| {
+                                            (world_rates.drop_rate as f32
+                                                * ai_system_resources
+                                                    .game_config
+                                                    .zone_environment
+                                                    .get_drop_rate(
+                                                        source.position.zone_id,
+                                                        environment.time_of_day,
+                                                        environment.weather,
+                                                    ))
+                                                as i32
+                                        });
+                                    for drop_item in (0..drop_count).filter_map(|_| {
                                         ai_system_resources.game_data.drop_table.get_drop(
-                                            world_rates.drop_rate,
+                                            drop_rate,
                                             world_rates.drop_money_rate,
                                             source.npc.id,
                                             source.position.zone_id,
@@ -1961,16 +2223,22 @@ pub fn npc_ai_system(
                                             killer.ability_values.get_drop_rate(),
                                             killer.ability_values.get_charm(),
                                         )
-                                    {
+                                    }) {
                                         ItemDropBundle::spawn(
                                             &mut ai_system_parameters.commands,
                                             &mut ai_system_parameters.client_entity_list,
                                             drop_item,
                                             source.position,
-                                            Some(killer_entity),
-                                            killer.party_membership.and_then(|party_membership| {
-                                                party_membership.party
-                                            }),
+                                            ai_system_resources
+                                                .game_config
+                                                .item_drop_owner_duration
+                                                .map(|duration| ItemDropOwner {
+                                                    entity: killer_entity,
+                                                    party_entity: killer.party_membership.and_then(
+                                                        |party_membership| party_membership.party,
+                                                    ),
+                                                    duration,
+                                                }),
                                             &ai_system_resources.time,
                                         );
                                     }
@@ -2005,4 +2273,20 @@ pub fn npc_ai_system(
             _ => {}
         }
     }
+
+    // Guards run after the AI programs so their targets take priority
+    if let Some(now) = ai_system_resources.time.last_update() {
+        for guard in guard_query.iter() {
+            if let Some(rules) = ai_system_resources.game_config.guards.get(guard.npc.id) {
+                npc_ai_guard(
+                    &mut ai_system_parameters,
+                    rules,
+                    &guard,
+                    &guard_target_query,
+                    &defended_npc_query,
+                    now,
+                );
+            }
+        }
+    }
 }
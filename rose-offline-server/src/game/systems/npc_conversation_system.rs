@@ -0,0 +1,119 @@
+use bevy::{
+    ecs::prelude::{EventReader, EventWriter, Query, Res},
+    math::Vec3Swizzles,
+};
+use log::warn;
+
+use rose_data::QuestTriggerHash;
+
+use crate::game::{
+    components::{EventObject, GameClient, Npc, Position},
+    events::{NpcConversationEvent, QuestTriggerEvent},
+    messages::server::ServerMessage,
+    GameData,
+};
+
+pub const NPC_CONVERSATION_MAX_DISTANCE: f32 = 6000.0;
+
+fn can_reach_conversation_quest_trigger(
+    game_data: &GameData,
+    npc_query: &Query<(&Npc, &Position)>,
+    event_object_query: &Query<(&EventObject, &Position)>,
+    position: &Position,
+    trigger_hash: QuestTriggerHash,
+) -> bool {
+    let is_in_range = |other_position: &Position| {
+        other_position.zone_id == position.zone_id
+            && other_position
+                .position
+                .xy()
+                .distance(position.position.xy())
+                <= NPC_CONVERSATION_MAX_DISTANCE
+    };
+    let has_quest_trigger = |conversation_index: usize, owner: std::fmt::Arguments| {
+        let Some(conversation) = game_data.npcs.find_conversation(conversation_index) else {
+            return false;
+        };
+
+        // A script which could not be read can not be checked, so none of its
+        // quest triggers are allowed
+        match conversation.quest_triggers.as_ref() {
+            Some(quest_triggers) => quest_triggers.contains(&trigger_hash),
+            None => {
+                warn!(
+                    "Rejected quest trigger near {} whose conversation script {} could not be read",
+                    owner, conversation.filename
+                );
+                false
+            }
+        }
+    };
+
+    npc_query.iter().any(|(npc, npc_position)| {
+        is_in_range(npc_position)
+            && has_quest_trigger(
+                npc.quest_index as usize,
+                format_args!("npc {}", npc.id.get()),
+            )
+    }) || event_object_query
+        .iter()
+        .any(|(event_object, event_object_position)| {
+            is_in_range(event_object_position)
+                && has_quest_trigger(
+                    event_object.event_id as usize,
+                    format_args!("event object {}", event_object.event_id),
+                )
+        })
+}
+
+pub fn npc_conversation_system(
+    query: Query<(&Position, Option<&GameClient>)>,
+    npc_query: Query<(&Npc, &Position)>,
+    event_object_query: Query<(&EventObject, &Position)>,
+    game_data: Res<GameData>,
+    mut npc_conversation_events: EventReader<NpcConversationEvent>,
+    mut quest_trigger_events: EventWriter<QuestTriggerEvent>,
+) {
+    for event in npc_conversation_events.iter() {
+        match *event {
+            NpcConversationEvent::QuestTrigger {
+                entity,
+                trigger_hash,
+                reward_choice,
+            } => {
+                let Ok((position, game_client)) = query.get(entity) else {
+                    continue;
+                };
+
+                if can_reach_conversation_quest_trigger(
+                    &game_data,
+                    &npc_query,
+                    &event_object_query,
+                    position,
+                    trigger_hash,
+                ) {
+                    quest_trigger_events.send(QuestTriggerEvent {
+                        trigger_entity: entity,
+                        trigger_hash,
+                        reward_choice,
+                    });
+                } else {
+                    warn!(
+                        "Rejected conversation quest trigger {:?} from entity {:?} with no nearby conversation",
+                        trigger_hash, entity
+                    );
+
+                    if let Some(game_client) = game_client {
+                        game_client
+                            .server_message_tx
+                            .send(ServerMessage::QuestTriggerResult {
+                                success: false,
+                                trigger_hash,
+                            })
+                            .ok();
+                    }
+                }
+            }
+        }
+    }
+}
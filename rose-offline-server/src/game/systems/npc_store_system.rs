@@ -1,37 +1,125 @@
-use bevy::ecs::prelude::{Entity, EventReader, Mut, Query, Res};
-use bevy::math::Vec3Swizzles;
-use log::warn;
+use bevy::ecs::prelude::{Entity, EventReader, EventWriter, Mut, Query, Res, ResMut};
+use bevy::{math::Vec3Swizzles, time::Time};
+use chrono::Utc;
 use std::collections::HashSet;
 
-use rose_data::Item;
+use rose_data::{AbilityType, Item, ItemReference, ZoneId};
 
 use crate::game::{
+    bundles::item_assign_instance_id,
     components::{
-        AbilityValues, GameClient, Inventory, ItemSlot, Money, Npc, Position, UnionMembership,
+        AbilityValues, Account, CharacterInfo, GameClient, HonorPoints, Inventory, ItemSlot, Money,
+        Npc, Position, UnionMembership,
     },
-    events::NpcStoreEvent,
+    events::{NpcStoreEvent, StatisticsEvent},
     messages::{
         client::NpcStoreBuyItem,
         server::{NpcStoreTransactionError, ServerMessage},
     },
-    resources::WorldRates,
+    resources::{
+        GameConfig, NpcStoreCurrency, NpcStoreStock, StorageKey, StorageService, WorldRates,
+        ZoneList,
+    },
     GameData,
 };
 
 pub const NPC_STORE_TRANSACTION_MAX_DISTANCE: f32 = 6000.0;
+const NPC_STORE_MAX_BUY_QUANTITY: u32 = 999;
+
+// Charm and fame give a small bonus to the buy and sell rate, every
+// NPC_STORE_CHARM_PER_RATE charm or NPC_STORE_FAME_PER_RATE fame adds 1%
+const NPC_STORE_CHARM_PER_RATE: i32 = 50;
+const NPC_STORE_FAME_PER_RATE: i32 = 20;
+const NPC_STORE_MAX_CHARM_FAME_RATE: i32 = 10;
+
+// Limits for the final rate after all modifiers are applied
+const NPC_STORE_MIN_RATE: i32 = -100;
+const NPC_STORE_MAX_RATE: i32 = 50;
 
+const UNION_POINT_ABILITY_TYPES: [AbilityType; 10] = [
+    AbilityType::UnionPoint1,
+    AbilityType::UnionPoint2,
+    AbilityType::UnionPoint3,
+    AbilityType::UnionPoint4,
+    AbilityType::UnionPoint5,
+    AbilityType::UnionPoint6,
+    AbilityType::UnionPoint7,
+    AbilityType::UnionPoint8,
+    AbilityType::UnionPoint9,
+    AbilityType::UnionPoint10,
+];
+
+fn get_npc_store_charm_fame_rate(
+    ability_values: &AbilityValues,
+    character_info: Option<&CharacterInfo>,
+) -> i32 {
+    let charm_rate = ability_values.get_charm() / NPC_STORE_CHARM_PER_RATE;
+    let fame_rate = character_info
+        .map(|character_info| character_info.fame as i32 / NPC_STORE_FAME_PER_RATE)
+        .unwrap_or(0);
+    (charm_rate + fame_rate).clamp(0, NPC_STORE_MAX_CHARM_FAME_RATE)
+}
+
+/// Takes the cost of items bought from a tab priced in another currency,
+/// failing if the buyer can not afford it.
+fn npc_store_take_currency(
+    currency: NpcStoreCurrency,
+    cost: i64,
+    inventory: &mut Inventory,
+    union_membership: &mut UnionMembership,
+    honor_points: &mut HonorPoints,
+    updated_inventory_slots: &mut HashSet<ItemSlot>,
+) -> Result<(), NpcStoreTransactionError> {
+    let cost = u32::try_from(cost).map_err(|_| NpcStoreTransactionError::NotEnoughMoney)?;
+
+    match currency {
+        NpcStoreCurrency::UnionPoints { union } => {
+            let union = union
+                .or(union_membership.current_union)
+                .ok_or(NpcStoreTransactionError::NotSameUnion)?;
+            let points = union_membership
+                .points
+                .get_mut(union.get() - 1)
+                .ok_or(NpcStoreTransactionError::NotSameUnion)?;
+            *points = points
+                .checked_sub(cost)
+                .ok_or(NpcStoreTransactionError::NotEnoughUnionPoints)?;
+        }
+        NpcStoreCurrency::EventToken { item } => {
+            let (slot, _) = inventory
+                .try_take_item(item, cost)
+                .ok_or(NpcStoreTransactionError::NotEnoughMoney)?;
+            updated_inventory_slots.insert(slot);
+        }
+        NpcStoreCurrency::HonorPoints => {
+            honor_points.points = honor_points
+                .points
+                .checked_sub(cost)
+                .ok_or(NpcStoreTransactionError::NotEnoughMoney)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the updated inventory slots and the money earned from selling items.
 fn npc_store_do_transaction(
     npc_query: &Query<(&Npc, &Position)>,
+    game_config: &GameConfig,
     game_data: &GameData,
+    npc_store_stock: &mut NpcStoreStock,
     world_rates: &WorldRates,
+    zone_list: &ZoneList,
     store_entity: Entity,
     buy_items: &[NpcStoreBuyItem],
     sell_items: &[(ItemSlot, usize)],
     ability_values: &AbilityValues,
+    character_info: Option<&CharacterInfo>,
     inventory: &mut Mut<Inventory>,
     position: &Position,
-    _union_membership: &UnionMembership,
-) -> Result<HashSet<ItemSlot>, NpcStoreTransactionError> {
+    union_membership: &mut Mut<UnionMembership>,
+    honor_points: &mut Mut<HonorPoints>,
+) -> Result<(HashSet<ItemSlot>, Money), NpcStoreTransactionError> {
     let (npc, npc_position) = npc_query
         .get(store_entity)
         .map_err(|_| NpcStoreTransactionError::NpcNotFound)?;
@@ -41,9 +129,9 @@ fn npc_store_do_transaction(
         .get_npc(npc.id)
         .ok_or(NpcStoreTransactionError::NpcNotFound)?;
 
-    if npc_data.store_union_number.is_some() {
-        warn!("Unimplemented union NPC store");
-        // TODO: if npc_data.store_union_number != union_membership.current_union { ... etc
+    if npc_data.store_union_number.is_some()
+        && npc_data.store_union_number != union_membership.current_union
+    {
         return Err(NpcStoreTransactionError::NotSameUnion);
     }
 
@@ -54,10 +142,39 @@ fn npc_store_do_transaction(
         return Err(NpcStoreTransactionError::NpcTooFarAway);
     }
 
+    if !zone_list.is_town(npc_position.zone_id) {
+        return Err(NpcStoreTransactionError::StoreClosed);
+    }
+
+    if zone_list
+        .get_environment(npc_position.zone_id)
+        .map_or(false, |environment| {
+            !game_config
+                .zone_environment
+                .is_npc_store_open(npc.id, environment.time_of_day)
+        })
+    {
+        return Err(NpcStoreTransactionError::StoreClosed);
+    }
+
+    // Prices are always calculated on the server, the client only tells us
+    // which items it wants so there is nothing for it to tamper with.
+    let charm_fame_rate = get_npc_store_charm_fame_rate(ability_values, character_info);
+    let buy_rate = (ability_values.get_npc_store_buy_rate()
+        + charm_fame_rate
+        + world_rates.npc_store_buy_rate)
+        .clamp(NPC_STORE_MIN_RATE, NPC_STORE_MAX_RATE);
+    let sell_rate = (ability_values.get_npc_store_sell_rate()
+        + charm_fame_rate
+        + world_rates.npc_store_sell_rate)
+        .clamp(NPC_STORE_MIN_RATE, NPC_STORE_MAX_RATE);
+
     let mut total_buy_cost = 0i64;
     let mut total_sell_value = 0i64;
     let mut transaction_inventory = inventory.clone();
     let mut updated_inventory_slots = HashSet::new();
+    let mut stock_purchases: Vec<(ItemReference, u32)> = Vec::new();
+    let mut currency_costs: Vec<(NpcStoreCurrency, i64)> = Vec::new();
 
     // First process sell items
     for &(sell_item_slot, sell_item_quantity) in sell_items {
@@ -68,6 +185,9 @@ fn npc_store_do_transaction(
                 .map(|item| item.get_quantity() as usize)
                 .unwrap_or(0),
         );
+        if sell_item_quantity == 0 {
+            return Err(NpcStoreTransactionError::NpcNotFound);
+        }
 
         let sell_item = transaction_inventory
             .try_take_quantity(sell_item_slot, sell_item_quantity as u32)
@@ -78,16 +198,20 @@ fn npc_store_do_transaction(
             .calculate_npc_store_item_sell_price(
                 &game_data.items,
                 &sell_item,
-                ability_values.get_npc_store_sell_rate(),
+                sell_rate,
                 world_rates.world_price_rate,
                 world_rates.item_price_rate,
                 world_rates.town_price_rate,
             )
-            .ok_or(NpcStoreTransactionError::NpcNotFound)? as i64;
+            .ok_or(NpcStoreTransactionError::NpcNotFound)?
+            .max(0) as i64;
 
         log::trace!(target: "npc_store", "Sell item {:?}, price: {}", sell_item.get_item_reference(), item_price);
         updated_inventory_slots.insert(sell_item_slot);
-        total_sell_value += item_price * sell_item.get_quantity() as i64;
+        total_sell_value = item_price
+            .checked_mul(sell_item.get_quantity() as i64)
+            .and_then(|value| total_sell_value.checked_add(value))
+            .ok_or(NpcStoreTransactionError::PriceDifference)?;
     }
 
     // Process buy items
@@ -113,25 +237,59 @@ fn npc_store_do_transaction(
             .get_base_item(store_item_reference)
             .ok_or(NpcStoreTransactionError::NpcNotFound)?;
 
-        let item_price = game_data
-            .ability_value_calculator
-            .calculate_npc_store_item_buy_price(
-                &game_data.items,
-                store_item_reference,
-                ability_values.get_npc_store_buy_rate(),
-                world_rates.item_price_rate,
-                world_rates.town_price_rate,
-            )
-            .ok_or(NpcStoreTransactionError::NpcNotFound)? as i64;
+        let currency_tab = game_config
+            .npc_store_currencies
+            .get_tab(npc.id, buy_item.tab_index);
+        let item_price = if let Some(currency_tab) = currency_tab {
+            currency_tab
+                .get_price(store_item_reference)
+                .ok_or(NpcStoreTransactionError::NpcNotFound)? as i64
+        } else {
+            game_data
+                .ability_value_calculator
+                .calculate_npc_store_item_buy_price(
+                    &game_data.items,
+                    store_item_reference,
+                    buy_rate,
+                    world_rates.item_price_rate,
+                    world_rates.town_price_rate,
+                )
+                .ok_or(NpcStoreTransactionError::NpcNotFound)? as i64
+        };
+        if item_price <= 0 {
+            return Err(NpcStoreTransactionError::PriceDifference);
+        }
 
         let buy_quantity = if store_item_reference.item_type.is_stackable_item() {
-            buy_item.quantity
+            buy_item.quantity as u32
         } else {
             1
-        } as i64;
+        };
+        if buy_quantity == 0 || buy_quantity > NPC_STORE_MAX_BUY_QUANTITY {
+            return Err(NpcStoreTransactionError::NpcNotFound);
+        }
 
-        let item = Item::from_item_data(store_item_data, buy_quantity as u32)
+        // Limited stock must cover everything bought in this transaction
+        if let Some(stock_quantity) = npc_store_stock.get_quantity(npc.id, store_item_reference) {
+            let purchased_quantity: u32 = stock_purchases
+                .iter()
+                .filter(|(item, _)| *item == store_item_reference)
+                .map(|(_, quantity)| quantity)
+                .sum();
+            if purchased_quantity + buy_quantity > stock_quantity {
+                return Err(NpcStoreTransactionError::OutOfStock);
+            }
+            stock_purchases.push((store_item_reference, buy_quantity));
+        }
+
+        let mut item = Item::from_item_data(store_item_data, buy_quantity)
             .ok_or(NpcStoreTransactionError::NpcNotFound)?;
+        if let Some(character_info) = character_info {
+            item_assign_instance_id(
+                &mut item,
+                &format!("npc store purchase by character {}", character_info.name),
+            );
+        }
 
         let (inventory_slot, _) = transaction_inventory
             .try_add_item(item)
@@ -139,7 +297,26 @@ fn npc_store_do_transaction(
 
         log::trace!(target: "npc_store", "Buy item {:?}, price: {}", store_item_reference, item_price);
         updated_inventory_slots.insert(inventory_slot);
-        total_buy_cost += item_price * buy_quantity;
+        let cost = item_price
+            .checked_mul(buy_quantity as i64)
+            .ok_or(NpcStoreTransactionError::NotEnoughMoney)?;
+
+        if let Some(currency_tab) = currency_tab {
+            if let Some((_, total_cost)) = currency_costs
+                .iter_mut()
+                .find(|(currency, _)| *currency == currency_tab.currency)
+            {
+                *total_cost = total_cost
+                    .checked_add(cost)
+                    .ok_or(NpcStoreTransactionError::NotEnoughMoney)?;
+            } else {
+                currency_costs.push((currency_tab.currency, cost));
+            }
+        } else {
+            total_buy_cost = total_buy_cost
+                .checked_add(cost)
+                .ok_or(NpcStoreTransactionError::NotEnoughMoney)?;
+        }
     }
 
     transaction_inventory
@@ -150,40 +327,85 @@ fn npc_store_do_transaction(
         .try_take_money(Money(total_buy_cost))
         .map_err(|_| NpcStoreTransactionError::NotEnoughMoney)?;
 
+    let mut transaction_union_membership = (**union_membership).clone();
+    let mut transaction_honor_points = **honor_points;
+    for (currency, cost) in currency_costs {
+        npc_store_take_currency(
+            currency,
+            cost,
+            &mut transaction_inventory,
+            &mut transaction_union_membership,
+            &mut transaction_honor_points,
+            &mut updated_inventory_slots,
+        )?;
+    }
+
+    for (item, quantity) in stock_purchases {
+        npc_store_stock.take_quantity(npc.id, item, quantity);
+    }
+
     **inventory = transaction_inventory;
-    Ok(updated_inventory_slots)
+    **union_membership = transaction_union_membership;
+    **honor_points = transaction_honor_points;
+    Ok((updated_inventory_slots, Money(total_sell_value)))
 }
 
 pub fn npc_store_system(
     npc_query: Query<(&Npc, &Position)>,
     mut transaction_entity_query: Query<(
         &AbilityValues,
+        Option<&CharacterInfo>,
         &mut Inventory,
         &Position,
-        &UnionMembership,
+        &mut UnionMembership,
+        &mut HonorPoints,
         Option<&GameClient>,
     )>,
     mut npc_store_events: EventReader<NpcStoreEvent>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
+    mut npc_store_stock: ResMut<NpcStoreStock>,
     world_rates: Res<WorldRates>,
+    zone_list: Res<ZoneList>,
+    mut statistics_events: EventWriter<StatisticsEvent>,
 ) {
     for event in npc_store_events.iter() {
-        if let Ok((ability_values, mut inventory, position, union_membership, game_client)) =
-            transaction_entity_query.get_mut(event.transaction_entity)
+        if let Ok((
+            ability_values,
+            character_info,
+            mut inventory,
+            position,
+            mut union_membership,
+            mut honor_points,
+            game_client,
+        )) = transaction_entity_query.get_mut(event.transaction_entity)
         {
+            let previous_union_points = union_membership.points;
             match npc_store_do_transaction(
                 &npc_query,
+                &game_config,
                 &game_data,
+                &mut npc_store_stock,
                 &world_rates,
+                &zone_list,
                 event.store_entity,
                 &event.buy_items,
                 &event.sell_items,
                 ability_values,
+                character_info,
                 &mut inventory,
                 position,
-                union_membership,
+                &mut union_membership,
+                &mut honor_points,
             ) {
-                Ok(updated_items) => {
+                Ok((updated_items, sell_value)) => {
+                    if sell_value.0 > 0 {
+                        statistics_events.send(StatisticsEvent::EarnMoney {
+                            entity: event.transaction_entity,
+                            amount: sell_value.0,
+                        });
+                    }
+
                     if let Some(game_client) = game_client {
                         game_client
                             .server_message_tx
@@ -195,6 +417,22 @@ pub fn npc_store_system(
                                 money: Some(inventory.money),
                             })
                             .ok();
+
+                        for (index, (previous_points, points)) in previous_union_points
+                            .iter()
+                            .zip(union_membership.points.iter())
+                            .enumerate()
+                        {
+                            if previous_points != points {
+                                game_client
+                                    .server_message_tx
+                                    .send(ServerMessage::UpdateAbilityValueSet {
+                                        ability_type: UNION_POINT_ABILITY_TYPES[index],
+                                        value: *points as i32,
+                                    })
+                                    .ok();
+                            }
+                        }
                     }
                 }
                 Err(error) => {
@@ -209,3 +447,65 @@ pub fn npc_store_system(
         }
     }
 }
+
+pub fn npc_store_restock_system(
+    npc_query: Query<(&Npc, &Position)>,
+    client_query: Query<(&GameClient, &Position, Option<&Account>)>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+    mut npc_store_stock: ResMut<NpcStoreStock>,
+    mut storage_service: ResMut<StorageService>,
+) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    // Purchases from the previous frame are saved along with any restocks
+    if let Some(storage) = npc_store_stock.take_changed_storage(now, Utc::now()) {
+        if let Err(error) = storage_service.write(StorageKey::NpcStoreStock, move || storage.save())
+        {
+            log::error!("Failed to save npc store stock with error {:?}", error);
+        }
+    }
+
+    for stock_item in npc_store_stock.restock(now) {
+        if !stock_item.announce_restock {
+            continue;
+        }
+
+        if game_data.npcs.get_npc(stock_item.npc_id).is_none()
+            || game_data.items.get_base_item(stock_item.item).is_none()
+        {
+            continue;
+        }
+
+        let zones: HashSet<ZoneId> = npc_query
+            .iter()
+            .filter(|(npc, _)| npc.id == stock_item.npc_id)
+            .map(|(_, position)| position.zone_id)
+            .collect();
+
+        // Sent to each client so the names are in the client's language
+        for (game_client, position, account) in client_query.iter() {
+            if !zones.contains(&position.zone_id) {
+                continue;
+            }
+
+            let language = account.and_then(|account| account.language);
+            game_client
+                .server_message_tx
+                .send(ServerMessage::AnnounceChat {
+                    name: Some(
+                        game_data
+                            .get_npc_name(stock_item.npc_id, language)
+                            .to_string(),
+                    ),
+                    text: format!(
+                        "{} is back in stock!",
+                        game_data.get_item_name(stock_item.item, language)
+                    ),
+                })
+                .ok();
+        }
+    }
+}
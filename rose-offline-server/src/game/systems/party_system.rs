@@ -1,7 +1,14 @@
-use bevy::ecs::{
-    prelude::{Changed, Commands, Entity, EventReader, Or, Query},
-    query::WorldQuery,
+use std::time::Duration;
+
+use bevy::{
+    ecs::{
+        prelude::{Changed, Commands, Entity, EventReader, Local, Or, Query, Res, ResMut},
+        query::WorldQuery,
+    },
+    math::Vec3Swizzles,
+    time::Time,
 };
+use log::error;
 use rose_game_common::{
     components::Level,
     messages::{PartyItemSharing, PartyRejectInviteReason, PartyXpSharing},
@@ -10,14 +17,21 @@ use rose_game_common::{
 use crate::game::{
     components::{
         AbilityValues, CharacterInfo, CharacterUniqueId, ClientEntity, GameClient, HealthPoints,
-        Party, PartyMember, PartyMembership, Stamina, StatusEffects,
+        Party, PartyMapMarkerHidden, PartyMember, PartyMembership, PartyUniqueId, Position,
+        Stamina, StatusEffects,
     },
     events::{PartyEvent, PartyMemberEvent},
     messages::server::{
-        PartyMemberInfo, PartyMemberInfoOffline, PartyMemberInfoOnline, ServerMessage,
+        PartyMemberInfo, PartyMemberInfoOffline, PartyMemberInfoOnline, PartyMemberMapMarker,
+        ServerMessage,
     },
+    resources::{GameConfig, StorageKey, StorageService},
+    storage::party::{PartyStorage, PartyStorageMember},
 };
 
+// Party map marker positions are rounded to this granularity, in cm
+const PARTY_MAP_MARKER_GRANULARITY: f32 = 1000.0;
+
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 pub struct PartyMembershipQuery<'w> {
@@ -69,6 +83,45 @@ fn send_message_to_members(
     }
 }
 
+fn save_party(
+    storage_service: &mut StorageService,
+    party_member_info_query: &Query<PartyMemberInfoQuery>,
+    party: &Party,
+) {
+    let members = party
+        .members
+        .iter()
+        .filter_map(|party_member| match party_member {
+            &PartyMember::Online(party_member_entity) => party_member_info_query
+                .get(party_member_entity)
+                .ok()
+                .map(|party_member| PartyStorageMember {
+                    character_id: party_member.character_info.unique_id,
+                    name: party_member.character_info.name.clone(),
+                }),
+            PartyMember::Offline(character_id, name) => Some(PartyStorageMember {
+                character_id: *character_id,
+                name: name.clone(),
+            }),
+        })
+        .collect();
+
+    let storage = PartyStorage {
+        unique_id: party.unique_id,
+        members,
+        item_sharing: party.item_sharing,
+        xp_sharing: party.xp_sharing,
+    };
+    if let Err(error) =
+        storage_service.write(StorageKey::Party(party.unique_id), move || storage.save())
+    {
+        error!(
+            "Failed to save party {} with error {:?}",
+            party.unique_id, error
+        );
+    }
+}
+
 fn delete_party(
     commands: &mut Commands,
     party_membership_query: &mut Query<PartyMembershipQuery>,
@@ -91,6 +144,13 @@ fn delete_party(
     }
     party.members.clear();
     commands.entity(party_entity).despawn();
+
+    if let Err(error) = PartyStorage::delete(party.unique_id) {
+        error!(
+            "Failed to delete party {} with error {:?}",
+            party.unique_id, error
+        );
+    }
 }
 
 enum PartyInviteError {
@@ -191,8 +251,32 @@ fn get_party_membership_info(
     info
 }
 
+/// Party unique ids are allocated sequentially after the highest id of the
+/// parties loaded from storage, so two parties never share a storage file.
+fn allocate_party_unique_id(
+    next_party_unique_id: &mut Option<PartyUniqueId>,
+    party_query: &Query<&mut Party>,
+) -> PartyUniqueId {
+    let mut unique_id = next_party_unique_id.unwrap_or_else(|| {
+        party_query
+            .iter()
+            .map(|party| party.unique_id)
+            .max()
+            .map_or(0, |unique_id| unique_id.wrapping_add(1))
+    });
+
+    while party_query.iter().any(|party| party.unique_id == unique_id) {
+        unique_id = unique_id.wrapping_add(1);
+    }
+
+    *next_party_unique_id = Some(unique_id.wrapping_add(1));
+    unique_id
+}
+
 fn handle_party_accept_invite(
+    storage_service: &mut StorageService,
     commands: &mut Commands,
+    next_party_unique_id: &mut Option<PartyUniqueId>,
     party_query: &mut Query<&mut Party>,
     party_membership_query: &mut Query<PartyMembershipQuery>,
     party_member_info_query: &Query<PartyMemberInfoQuery>,
@@ -227,6 +311,7 @@ fn handle_party_accept_invite(
         None => {
             // Create a new party
             let party = Party::new(
+                allocate_party_unique_id(next_party_unique_id, party_query),
                 owner_entity,
                 &[
                     PartyMember::Online(owner_entity),
@@ -236,6 +321,7 @@ fn handle_party_accept_invite(
             let item_sharing = party.item_sharing;
             let xp_sharing = party.xp_sharing;
             let party_members = party.members.clone();
+            save_party(storage_service, party_member_info_query, &party);
             let party_entity = commands.spawn(party).id();
 
             *owner.party_membership = PartyMembership::new(party_entity);
@@ -261,6 +347,7 @@ fn handle_party_accept_invite(
 
             party.members.push(PartyMember::Online(invited_entity));
             *invited.party_membership = PartyMembership::new(party_entity);
+            save_party(storage_service, party_member_info_query, &party);
 
             (party.item_sharing, party.xp_sharing, party.members.clone())
         }
@@ -359,6 +446,7 @@ enum PartyLeaveError {
 }
 
 fn handle_party_leave(
+    storage_service: &mut StorageService,
     commands: &mut Commands,
     party_query: &mut Query<&mut Party>,
     party_membership_query: &mut Query<PartyMembershipQuery>,
@@ -415,6 +503,8 @@ fn handle_party_leave(
     if party.members.len() <= 1 {
         delete_party(commands, party_membership_query, party_entity, &mut party);
     } else {
+        save_party(storage_service, party_member_info_query, &party);
+
         // Get leaver character id and owner character id for leave message
         let [leaver, owner] = party_member_info_query
             .get_many([leaver_entity, party.owner])
@@ -443,6 +533,7 @@ enum PartyKickError {
 }
 
 fn handle_party_kick(
+    storage_service: &mut StorageService,
     commands: &mut Commands,
     party_query: &mut Query<&mut Party>,
     party_membership_query: &mut Query<PartyMembershipQuery>,
@@ -533,6 +624,8 @@ fn handle_party_kick(
     // If party is down to 1 member, delete the party
     if party.members.len() <= 1 {
         delete_party(commands, party_membership_query, party_entity, &mut party);
+    } else {
+        save_party(storage_service, party_member_info_query, &party);
     }
 
     Ok(())
@@ -599,6 +692,7 @@ enum PartyUpdateRulesError {
 }
 
 fn handle_party_update_rules(
+    storage_service: &mut StorageService,
     party_query: &mut Query<&mut Party>,
     party_membership_query: &Query<PartyMembershipQuery>,
     party_member_info_query: &Query<PartyMemberInfoQuery>,
@@ -625,6 +719,7 @@ fn handle_party_update_rules(
 
     party.item_sharing = item_sharing;
     party.xp_sharing = xp_sharing;
+    save_party(storage_service, party_member_info_query, &party);
 
     send_message_to_members(
         party_member_info_query,
@@ -837,6 +932,8 @@ pub fn party_system(
     mut party_membership_query: Query<PartyMembershipQuery>,
     party_member_info_query: Query<PartyMemberInfoQuery>,
     mut party_events: EventReader<PartyEvent>,
+    mut storage_service: ResMut<StorageService>,
+    mut next_party_unique_id: Local<Option<PartyUniqueId>>,
 ) {
     for event in party_events.iter() {
         match *event {
@@ -851,7 +948,9 @@ pub fn party_system(
                 invited_entity,
             } => {
                 handle_party_accept_invite(
+                    &mut storage_service,
                     &mut commands,
+                    &mut next_party_unique_id,
                     &mut party_query,
                     &mut party_membership_query,
                     &party_member_info_query,
@@ -875,6 +974,7 @@ pub fn party_system(
             }
             PartyEvent::Leave { leaver_entity } => {
                 handle_party_leave(
+                    &mut storage_service,
                     &mut commands,
                     &mut party_query,
                     &mut party_membership_query,
@@ -888,6 +988,7 @@ pub fn party_system(
                 kick_character_id,
             } => {
                 handle_party_kick(
+                    &mut storage_service,
                     &mut commands,
                     &mut party_query,
                     &mut party_membership_query,
@@ -916,6 +1017,7 @@ pub fn party_system(
                 xp_sharing,
             } => {
                 handle_party_update_rules(
+                    &mut storage_service,
                     &mut party_query,
                     &party_membership_query,
                     &party_member_info_query,
@@ -959,6 +1061,83 @@ pub fn party_member_update_info_system(
     }
 }
 
+pub fn create_party_member_map_marker(
+    character_info: &CharacterInfo,
+    position: &Position,
+) -> PartyMemberMapMarker {
+    PartyMemberMapMarker {
+        character_id: character_info.unique_id,
+        name: character_info.name.clone(),
+        zone_id: position.zone_id,
+        position: (position.position.xy() / PARTY_MAP_MARKER_GRANULARITY).round()
+            * PARTY_MAP_MARKER_GRANULARITY,
+    }
+}
+
+pub fn party_member_map_markers_system(
+    party_query: Query<&Party>,
+    party_member_query: Query<(
+        &CharacterInfo,
+        &Position,
+        Option<&GameClient>,
+        Option<&PartyMapMarkerHidden>,
+    )>,
+    game_config: Res<GameConfig>,
+    time: Res<Time>,
+    mut time_since_update: Local<Duration>,
+) {
+    let Some(update_interval) = game_config.party_map_marker_interval else {
+        return;
+    };
+
+    *time_since_update += time.delta();
+    if *time_since_update < update_interval {
+        return;
+    }
+    *time_since_update = Duration::ZERO;
+
+    for party in party_query.iter() {
+        let markers: Vec<PartyMemberMapMarker> = party
+            .members
+            .iter()
+            .filter_map(|party_member| party_member.get_entity())
+            .filter_map(|entity| party_member_query.get(entity).ok())
+            .filter(|(_, _, _, hidden)| hidden.is_none())
+            .map(|(character_info, position, _, _)| {
+                create_party_member_map_marker(character_info, position)
+            })
+            .collect();
+
+        for party_member_entity in party
+            .members
+            .iter()
+            .filter_map(|party_member| party_member.get_entity())
+        {
+            let Ok((character_info, _, Some(game_client), _)) =
+                party_member_query.get(party_member_entity)
+            else {
+                continue;
+            };
+
+            let member_markers: Vec<PartyMemberMapMarker> = markers
+                .iter()
+                .filter(|marker| marker.character_id != character_info.unique_id)
+                .cloned()
+                .collect();
+            if member_markers.is_empty() {
+                continue;
+            }
+
+            game_client
+                .server_message_tx
+                .send(ServerMessage::PartyMemberMapMarkers {
+                    markers: member_markers,
+                })
+                .ok();
+        }
+    }
+}
+
 pub fn party_update_average_level_system(
     mut query_party: Query<&mut Party>,
     query_level: Query<&Level>,
@@ -1,29 +1,146 @@
 use bevy::{
     ecs::{
-        prelude::{EventReader, Query},
+        prelude::{
+            Added, Commands, Entity, EventReader, EventWriter, Query, RemovedComponents, Res,
+            ResMut,
+        },
         query::WorldQuery,
     },
     prelude::Mut,
+    time::Time,
 };
+use log::{error, info, warn};
 
-use rose_data::{Item, ItemSlotBehaviour};
+use rose_data::{Item, ItemSlotBehaviour, ItemType};
 use rose_game_common::{
     components::{ItemSlot, Money},
-    messages::server::PersonalStoreTransactionStatus,
+    messages::server::{PersonalStoreSearchResult, PersonalStoreTransactionStatus},
 };
 
 use crate::game::{
-    components::{ClientEntity, GameClient, Inventory, PersonalStore},
-    events::PersonalStoreEvent,
+    components::{
+        CharacterInfo, ClientEntity, Command, GameClient, Inventory, NextCommand, OfflineVendor,
+        PersonalStore, Position, PERSONAL_STORE_ITEM_SLOTS, PERSONAL_STORE_MAX_PRICE,
+        PERSONAL_STORE_MAX_TITLE_LENGTH,
+    },
+    events::{PersonalStoreEvent, StatisticsEvent},
     messages::server::ServerMessage,
+    resources::{GameData, PersonalStoreList, StorageService},
+    storage::item_transaction::ItemTransaction,
 };
 
+const PERSONAL_STORE_SEARCH_MAX_RESULTS: usize = 50;
+
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 pub struct PersonalStoreEntityQuery<'w> {
     client_entity: &'w ClientEntity,
+    command: &'w Command,
     inventory: &'w mut Inventory,
+    position: &'w Position,
+    character_info: Option<&'w CharacterInfo>,
     game_client: Option<&'w GameClient>,
+    offline_vendor: Option<&'w mut OfflineVendor>,
+}
+
+#[derive(Debug)]
+enum OpenError {
+    AlreadyOpen,
+    Dead,
+    InvalidTitle,
+    InvalidItemCount,
+    InvalidPrice,
+    InvalidItem,
+    InvalidItemSlot,
+    ItemMismatch,
+    ItemAlreadyListed,
+    ItemBound,
+}
+
+fn is_valid_price(price: Money, quantity: u32) -> bool {
+    // The total price for the full quantity must be representable, or a buyer
+    // purchasing the whole stack would overflow the transaction.
+    price > Money(0)
+        && price <= PERSONAL_STORE_MAX_PRICE
+        && quantity > 0
+        && price.0.checked_mul(quantity as i64).is_some()
+}
+
+fn personal_store_open(
+    game_data: &GameData,
+    seller: &PersonalStoreEntityQueryReadOnlyItem,
+    title: &str,
+    sell_items: &[(ItemSlot, Item, Money)],
+    buy_items: &[(Item, Money)],
+) -> Result<PersonalStore, OpenError> {
+    if seller.command.is_dead() {
+        return Err(OpenError::Dead);
+    }
+
+    if title.is_empty() || title.chars().count() > PERSONAL_STORE_MAX_TITLE_LENGTH {
+        return Err(OpenError::InvalidTitle);
+    }
+
+    if (sell_items.is_empty() && buy_items.is_empty())
+        || sell_items.len() > PERSONAL_STORE_ITEM_SLOTS
+        || buy_items.len() > PERSONAL_STORE_ITEM_SLOTS
+    {
+        return Err(OpenError::InvalidItemCount);
+    }
+
+    let mut store = PersonalStore::new(title.to_string(), 0);
+
+    for (item_slot, item, price) in sell_items.iter() {
+        let inventory_item = seller
+            .inventory
+            .get_item(*item_slot)
+            .ok_or(OpenError::InvalidItemSlot)?;
+        if !inventory_item.is_same_item(item) {
+            return Err(OpenError::ItemMismatch);
+        }
+
+        if matches!(inventory_item.get_item_type(), ItemType::Quest) {
+            return Err(OpenError::InvalidItem);
+        }
+
+        if inventory_item.is_bound() {
+            return Err(OpenError::ItemBound);
+        }
+
+        if !is_valid_price(*price, inventory_item.get_quantity()) {
+            return Err(OpenError::InvalidPrice);
+        }
+
+        // Each inventory slot may only be listed once, the slot is then
+        // reserved by the store for as long as it remains open.
+        if store.is_item_slot_reserved(*item_slot) {
+            return Err(OpenError::ItemAlreadyListed);
+        }
+
+        store
+            .add_sell_item(*item_slot, *price)
+            .map_err(|_| OpenError::InvalidItemCount)?;
+    }
+
+    for (item, price) in buy_items.iter() {
+        if game_data
+            .items
+            .get_base_item(item.get_item_reference())
+            .is_none()
+        {
+            return Err(OpenError::InvalidItem);
+        }
+
+        if !is_valid_price(*price, item.get_quantity()) {
+            return Err(OpenError::InvalidPrice);
+        }
+
+        store
+            .add_buy_item(item.clone(), *price)
+            .map_err(|_| OpenError::InvalidItemCount)?;
+    }
+
+    Ok(store)
 }
 
 fn personal_store_list_items(
@@ -59,6 +176,88 @@ fn personal_store_list_items(
     }
 }
 
+fn personal_store_search(
+    game_data: &GameData,
+    personal_store_list: &PersonalStoreList,
+    entity_query: &Query<PersonalStoreEntityQuery>,
+    store_query: &Query<&mut PersonalStore>,
+    searcher_entity: Entity,
+    item_type: Option<ItemType>,
+    item_name: Option<&str>,
+) -> Vec<PersonalStoreSearchResult> {
+    let Ok(searcher) = entity_query.get(searcher_entity) else {
+        return Vec::new();
+    };
+    let item_name = item_name.map(|item_name| item_name.to_lowercase());
+    let mut results = Vec::new();
+
+    for store_entity in personal_store_list.iter_zone(searcher.position.zone_id) {
+        if store_entity == searcher_entity {
+            continue;
+        }
+
+        let (Ok(seller), Ok(store)) = (
+            entity_query.get(store_entity),
+            store_query.get(store_entity),
+        ) else {
+            continue;
+        };
+
+        if seller.position.zone_id != searcher.position.zone_id {
+            continue;
+        }
+
+        let mut sell_items = Vec::new();
+        for (store_slot, slot) in store.sell_items.iter().enumerate() {
+            let Some((item_slot, price)) = slot else {
+                continue;
+            };
+            let Some(item) = seller.inventory.get_item(*item_slot) else {
+                continue;
+            };
+
+            if item_type.map_or(false, |item_type| item.get_item_type() != item_type) {
+                continue;
+            }
+
+            if let Some(item_name) = item_name.as_ref() {
+                if !game_data
+                    .items
+                    .get_base_item(item.get_item_reference())
+                    .map_or(false, |item_data| {
+                        item_data.name.to_lowercase().contains(item_name.as_str())
+                    })
+                {
+                    continue;
+                }
+            }
+
+            sell_items.push((store_slot as u8, item.clone(), *price));
+        }
+
+        if sell_items.is_empty() {
+            continue;
+        }
+
+        results.push(PersonalStoreSearchResult {
+            store_entity_id: seller.client_entity.id,
+            seller_name: seller
+                .character_info
+                .map(|character_info| character_info.name.clone())
+                .unwrap_or_default(),
+            title: store.title.clone(),
+            position: seller.position.position,
+            sell_items,
+        });
+
+        if results.len() >= PERSONAL_STORE_SEARCH_MAX_RESULTS {
+            break;
+        }
+    }
+
+    results
+}
+
 enum BuyError {
     InvalidStoreSlotIndex,
     ItemSoldOut,
@@ -89,7 +288,11 @@ fn personal_store_buy_item(
         return Err(BuyError::ItemSoldOut);
     }
 
-    let item_price = Money(item_price.0 * buy_item.get_quantity() as i64);
+    let item_price = item_price
+        .0
+        .checked_mul(buy_item.get_quantity() as i64)
+        .map(Money)
+        .ok_or(BuyError::NotEnoughMoney)?;
     if buyer.inventory.money < item_price {
         return Err(BuyError::NotEnoughMoney);
     }
@@ -101,6 +304,7 @@ fn personal_store_buy_item(
 
     let transaction_item = transaction_item.unwrap();
     let transaction_money = buyer.inventory.try_take_money(item_price).unwrap();
+    let instance_id = transaction_item.instance_id();
 
     match buyer.inventory.try_add_item(transaction_item) {
         Ok((buyer_item_slot, _)) => {
@@ -111,6 +315,17 @@ fn personal_store_buy_item(
 
             seller.inventory.try_add_money(transaction_money).ok();
 
+            if let Some(instance_id) = instance_id {
+                info!(
+                    target: "economy",
+                    "Item instance {} sold by character {} to character {} for {}",
+                    instance_id,
+                    seller.character_info.map_or("", |character_info| &character_info.name),
+                    buyer.character_info.map_or("", |character_info| &character_info.name),
+                    transaction_money.0
+                );
+            }
+
             Ok((buyer_item_slot, store_item_slot))
         }
         Err(rejected_item) => {
@@ -129,13 +344,82 @@ fn personal_store_buy_item(
     }
 }
 
+/// Saves the seller and buyer inventories together, so a crash can not leave
+/// the item saved in both inventories.
+fn save_personal_store_transaction(
+    storage_service: &mut StorageService,
+    seller: &PersonalStoreEntityQueryItem,
+    buyer: &PersonalStoreEntityQueryItem,
+) {
+    let mut transaction = ItemTransaction::new();
+    if let Some(character_info) = seller.character_info {
+        transaction.update_inventory(&character_info.name, &seller.inventory);
+    }
+    if let Some(character_info) = buyer.character_info {
+        transaction.update_inventory(&character_info.name, &buyer.inventory);
+    }
+
+    if let Err(error) = storage_service.write_item_transaction(transaction) {
+        error!(
+            "Failed to save personal store transaction with error {:?}",
+            error
+        );
+    }
+}
+
 pub fn personal_store_system(
+    mut commands: Commands,
     mut entity_query: Query<PersonalStoreEntityQuery>,
     mut store_query: Query<&mut PersonalStore>,
     mut personal_store_events: EventReader<PersonalStoreEvent>,
+    game_data: Res<GameData>,
+    personal_store_list: Res<PersonalStoreList>,
+    mut storage_service: ResMut<StorageService>,
+    mut statistics_events: EventWriter<StatisticsEvent>,
+    time: Res<Time>,
 ) {
     for event in personal_store_events.iter() {
         match *event {
+            PersonalStoreEvent::Open {
+                entity,
+                ref title,
+                ref sell_items,
+                ref buy_items,
+            } => {
+                let Ok(seller) = entity_query.get(entity) else {
+                    continue;
+                };
+
+                let result = if store_query.contains(entity) {
+                    Err(OpenError::AlreadyOpen)
+                } else {
+                    personal_store_open(&game_data, &seller, title, sell_items, buy_items)
+                };
+
+                match result {
+                    Ok(store) => {
+                        commands
+                            .entity(entity)
+                            .insert(store)
+                            .insert(NextCommand::with_personal_store());
+                    }
+                    Err(error) => {
+                        warn!(
+                            "Rejected personal store open request for entity {:?}: {:?}",
+                            entity, error
+                        );
+
+                        if let Some(game_client) = seller.game_client {
+                            game_client
+                                .server_message_tx
+                                .send(ServerMessage::ClosePersonalStore {
+                                    entity_id: seller.client_entity.id,
+                                })
+                                .ok();
+                        }
+                    }
+                }
+            }
             PersonalStoreEvent::ListItems {
                 store_entity,
                 list_entity,
@@ -146,6 +430,32 @@ pub fn personal_store_system(
                     }
                 }
             }
+            PersonalStoreEvent::Search {
+                entity,
+                item_type,
+                ref item_name,
+            } => {
+                let results = personal_store_search(
+                    &game_data,
+                    &personal_store_list,
+                    &entity_query,
+                    &store_query,
+                    entity,
+                    item_type,
+                    item_name.as_deref(),
+                );
+
+                if let Some(game_client) = entity_query
+                    .get(entity)
+                    .ok()
+                    .and_then(|searcher| searcher.game_client)
+                {
+                    game_client
+                        .server_message_tx
+                        .send(ServerMessage::PersonalStoreSearchResults { results })
+                        .ok();
+                }
+            }
             PersonalStoreEvent::BuyItem {
                 store_entity,
                 buyer_entity,
@@ -156,6 +466,7 @@ pub fn personal_store_system(
                     entity_query.get_many_mut([store_entity, buyer_entity])
                 {
                     if let Ok(mut store) = store_query.get_mut(store_entity) {
+                        let seller_money = seller.inventory.money;
                         match personal_store_buy_item(
                             &mut store,
                             &mut seller,
@@ -164,6 +475,28 @@ pub fn personal_store_system(
                             buy_item,
                         ) {
                             Ok((buyer_item_slot, seller_item_slot)) => {
+                                save_personal_store_transaction(
+                                    &mut storage_service,
+                                    &seller,
+                                    &buyer,
+                                );
+                                statistics_events.send(StatisticsEvent::EarnMoney {
+                                    entity: store_entity,
+                                    amount: (seller.inventory.money - seller_money).0,
+                                });
+
+                                if let Some(offline_vendor) = seller.offline_vendor.as_mut() {
+                                    offline_vendor.proceeds.add_sale(
+                                        buy_item.get_quantity(),
+                                        seller.inventory.money - seller_money,
+                                    );
+
+                                    // Close the store once everything has sold
+                                    if store.sell_items.iter().all(Option::is_none) {
+                                        offline_vendor.expire_time = time.last_update().unwrap();
+                                    }
+                                }
+
                                 if let Some(seller_game_client) = seller.game_client {
                                     seller_game_client
                                         .server_message_tx
@@ -257,3 +590,17 @@ pub fn personal_store_system(
         }
     }
 }
+
+pub fn personal_store_list_system(
+    opened_query: Query<(Entity, &Position), Added<PersonalStore>>,
+    mut closed_stores: RemovedComponents<PersonalStore>,
+    mut personal_store_list: ResMut<PersonalStoreList>,
+) {
+    for entity in closed_stores.iter() {
+        personal_store_list.remove_store(entity);
+    }
+
+    for (entity, position) in opened_query.iter() {
+        personal_store_list.add_store(entity, position.zone_id);
+    }
+}
@@ -2,7 +2,8 @@ use bevy::{
     ecs::query::WorldQuery,
     prelude::{Commands, EventReader, EventWriter, Query, Res, ResMut},
 };
-use rose_data::{ItemClass, ItemType};
+use log::error;
+use rose_data::{Item, ItemClass, ItemType};
 use rose_game_common::{
     components::{DroppedItem, Inventory, ItemDrop, Money},
     messages::{
@@ -14,11 +15,12 @@ use rose_game_common::{
 use crate::game::{
     bundles::client_entity_leave_zone,
     components::{
-        ClientEntity, ClientEntitySector, GameClient, Owner, Party, PartyMember, PartyMembership,
-        PartyOwner, Position,
+        CharacterInfo, ClientEntity, ClientEntitySector, GameClient, Owner, Party, PartyMember,
+        PartyMembership, PartyOwner, Position,
     },
-    events::{PickupItemEvent, UseItemEvent},
-    resources::ClientEntityList,
+    events::{PickupItemEvent, StatisticsEvent, UseItemEvent},
+    resources::{ClientEntityList, GameConfig, StorageService},
+    storage::item_transaction::ItemTransaction,
     GameData,
 };
 
@@ -39,13 +41,16 @@ pub fn pickup_item_system(
     mut pickup_item_events: EventReader<PickupItemEvent>,
     mut query_pickup_item: Query<PickupItemQuery>,
     mut query_party: Query<&mut Party>,
-    mut query_inventory: Query<(&mut Inventory, Option<&GameClient>)>,
+    mut query_inventory: Query<(&mut Inventory, Option<&GameClient>, Option<&CharacterInfo>)>,
     query_game_client: Query<&GameClient>,
     query_client_entity: Query<&ClientEntity>,
     query_party_membership: Query<&PartyMembership>,
     mut client_entity_list: ResMut<ClientEntityList>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
+    mut storage_service: ResMut<StorageService>,
     mut use_item_events: EventWriter<UseItemEvent>,
+    mut statistics_events: EventWriter<StatisticsEvent>,
 ) {
     for pickup_item_event in pickup_item_events.iter() {
         let mut pickup_item =
@@ -61,6 +66,9 @@ pub fn pickup_item_system(
             continue;
         };
 
+        // Every inventory changed by the pickup is saved together
+        let mut transaction = ItemTransaction::new();
+
         let pickup_party = query_party_membership
             .get(pickup_item_event.pickup_entity)
             .ok()
@@ -97,13 +105,27 @@ pub fn pickup_item_system(
 
                                 for party_member in party.members.iter() {
                                     if let PartyMember::Online(party_member_entity) = party_member {
-                                        if let Ok((mut inventory, game_client)) =
+                                        if let Ok((mut inventory, game_client, character_info)) =
                                             query_inventory.get_mut(*party_member_entity)
                                         {
                                             if inventory
                                                 .try_add_money(Money(money_per_member))
                                                 .is_ok()
                                             {
+                                                statistics_events.send(
+                                                    StatisticsEvent::EarnMoney {
+                                                        entity: *party_member_entity,
+                                                        amount: money_per_member,
+                                                    },
+                                                );
+
+                                                if let Some(character_info) = character_info {
+                                                    transaction.update_inventory(
+                                                        &character_info.name,
+                                                        &inventory,
+                                                    );
+                                                }
+
                                                 if let Some(game_client) = &game_client {
                                                     game_client
                                                         .server_message_tx
@@ -123,6 +145,18 @@ pub fn pickup_item_system(
                             }
                         }
                     }
+                    PartyItemSharing::PartyLeader => {
+                        // Give everything to the party owner, unless they are offline
+                        if party
+                            .members
+                            .iter()
+                            .any(|party_member| party_member.get_entity() == Some(party.owner))
+                        {
+                            party.owner
+                        } else {
+                            pickup_item_event.pickup_entity
+                        }
+                    }
                     PartyItemSharing::AcquisitionOrder => match dropped_item {
                         DroppedItem::Item(item) => {
                             // Take turns in getting item - per item type
@@ -176,17 +210,41 @@ pub fn pickup_item_system(
                             })
                     {
                         use_item_events.send(UseItemEvent::from_item(pickup_entity, item));
-                    } else if let Ok((mut inventory, game_client)) =
+                    } else if let Ok((mut inventory, game_client, character_info)) =
                         query_inventory.get_mut(pickup_entity)
                     {
-                        let result = match inventory.try_add_item(item.clone()) {
+                        let mut pickup_item_data = item.clone();
+                        if let Item::Equipment(equipment_item) = &mut pickup_item_data {
+                            if game_config
+                                .item_binding
+                                .is_bind_on_pickup(equipment_item.item)
+                            {
+                                equipment_item.is_bound = true;
+                            }
+                        }
+
+                        let result = match inventory.try_add_item(pickup_item_data) {
                             Ok((slot, item)) => Ok((slot, item.clone())),
-                            Err(item) => {
-                                pickup_item.item_drop.item = Some(DroppedItem::Item(item));
+                            Err(_) => {
+                                // Return the item to the drop in its original unbound state
+                                pickup_item.item_drop.item = Some(DroppedItem::Item(item.clone()));
                                 Err(PickupItemDropError::InventoryFull)
                             }
                         };
 
+                        if let (Ok(_), Some(character_info)) = (&result, character_info) {
+                            transaction.update_inventory(&character_info.name, &inventory);
+
+                            if let Some(instance_id) = item.instance_id() {
+                                log::info!(
+                                    target: "economy",
+                                    "Item instance {} picked up by character {}",
+                                    instance_id,
+                                    character_info.name
+                                );
+                            }
+                        }
+
                         if let Some(game_client) = &game_client {
                             match result {
                                 Ok((item_slot, item)) => game_client
@@ -240,9 +298,19 @@ pub fn pickup_item_system(
                     }
                 }
                 Some(DroppedItem::Money(money)) => {
-                    if let Ok((mut inventory, game_client)) = query_inventory.get_mut(pickup_entity)
+                    if let Ok((mut inventory, game_client, character_info)) =
+                        query_inventory.get_mut(pickup_entity)
                     {
                         if inventory.try_add_money(money).is_ok() {
+                            statistics_events.send(StatisticsEvent::EarnMoney {
+                                entity: pickup_entity,
+                                amount: money.0,
+                            });
+
+                            if let Some(character_info) = character_info {
+                                transaction.update_inventory(&character_info.name, &inventory);
+                            }
+
                             if let Some(game_client) = &game_client {
                                 game_client
                                     .server_message_tx
@@ -258,6 +326,10 @@ pub fn pickup_item_system(
                 _ => unreachable!(),
             }
 
+            if let Err(error) = storage_service.write_item_transaction(transaction) {
+                error!("Failed to save item pickup with error {:?}", error);
+            }
+
             if pickup_item.item_drop.item.is_none() {
                 // Delete picked up item
                 client_entity_leave_zone(
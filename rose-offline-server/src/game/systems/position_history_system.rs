@@ -0,0 +1,48 @@
+use bevy::{
+    ecs::prelude::{Changed, Commands, Entity, Query, Res, With},
+    time::Time,
+};
+
+use crate::game::{
+    components::{ClientEntity, Position, PositionHistory},
+    resources::GameConfig,
+};
+
+/// Records the positions of entities which have moved, entities which have not
+/// moved are at their current position for the whole history.
+pub fn position_history_system(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &Position, Option<&mut PositionHistory>),
+        (With<ClientEntity>, Changed<Position>),
+    >,
+    game_config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let Some(latency_compensation) = game_config.latency_compensation else {
+        return;
+    };
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    for (entity, position, position_history) in query.iter_mut() {
+        if let Some(mut position_history) = position_history {
+            position_history.push(
+                now,
+                position.position,
+                position.zone_id,
+                latency_compensation,
+            );
+        } else {
+            let mut position_history = PositionHistory::new();
+            position_history.push(
+                now,
+                position.position,
+                position.zone_id,
+                latency_compensation,
+            );
+            commands.entity(entity).insert(position_history);
+        }
+    }
+}
@@ -17,7 +17,10 @@ use chrono::{Datelike, Timelike};
 use log::warn;
 use rand::Rng;
 
-use rose_data::{EquipmentItem, Item, NpcId, QuestTrigger, SkillId, WorldTicks, ZoneId};
+use rose_data::{
+    EquipmentItem, Item, ItemReference, NpcId, QuestTrigger, QuestTriggerHash, SkillId, WorldTicks,
+    ZoneId,
+};
 use rose_file_readers::{
     QsdAbilityType, QsdClanPoints, QsdCondition, QsdConditionOperator, QsdDistance,
     QsdEquipmentIndex, QsdEventId, QsdItem, QsdNpcId, QsdNpcMessageType, QsdObjectType, QsdQuestId,
@@ -29,18 +32,24 @@ use rose_game_common::components::ClanPoints;
 use crate::game::{
     bundles::{
         ability_values_add_value, ability_values_get_value, ability_values_set_value,
-        client_entity_teleport_zone, skill_list_try_learn_skill, MonsterBundle, SkillListBundle,
+        basic_stats_reset, skill_list_try_learn_skill, MonsterBundle, SkillListBundle,
     },
     components::{
-        AbilityValues, ActiveQuest, BasicStats, CharacterInfo, Clan, ClanMembership, ClientEntity,
-        ClientEntitySector, Equipment, ExperiencePoints, GameClient, HealthPoints, Inventory,
-        Level, ManaPoints, Money, MoveSpeed, Npc, ObjectVariables, Party, PartyMembership,
+        AbilityValues, ActiveQuest, BasicStats, CharacterInfo, Clan, ClanMember, ClanMembership,
+        ClientEntity, Equipment, ExperiencePoints, GameClient, HealthPoints, Inventory, Level,
+        ManaPoints, Money, MoveSpeed, Npc, ObjectVariables, Party, PartyMember, PartyMembership,
         Position, QuestState, SkillList, SkillPoints, SpawnOrigin, Stamina, StatPoints, Team,
         UnionMembership,
     },
-    events::{ClanEvent, QuestTriggerEvent, RewardItemEvent, RewardXpEvent},
+    events::{
+        AchievementEvent, ClanEvent, QuestTriggerEvent, RewardItemEvent, RewardXpEvent,
+        StatisticsEvent, TeleportEvent,
+    },
     messages::server::ServerMessage,
-    resources::{ClientEntityList, ServerMessages, WorldRates, WorldTime, ZoneList},
+    resources::{
+        ClientEntityList, GameConfig, QuestRewardOption, ServerMessages, WorldRates, WorldTime,
+        ZoneList,
+    },
     GameData,
 };
 
@@ -52,7 +61,10 @@ pub struct QuestSystemParameters<'w, 's> {
     zone_list: ResMut<'w, ZoneList>,
     reward_item_events: EventWriter<'w, RewardItemEvent>,
     reward_xp_events: EventWriter<'w, RewardXpEvent>,
+    achievement_events: EventWriter<'w, AchievementEvent>,
+    statistics_events: EventWriter<'w, StatisticsEvent>,
     clan_events: EventWriter<'w, ClanEvent>,
+    teleport_events: EventWriter<'w, TeleportEvent>,
     object_variables_query: Query<'w, 's, (&'static mut ObjectVariables, &'static Position)>,
     party_query: Query<'w, 's, &'static Party>,
     clan_query: Query<'w, 's, &'static Clan>,
@@ -64,6 +76,7 @@ pub struct QuestSystemResources<'w, 's> {
     time: Res<'w, Time>,
     world_rates: Res<'w, WorldRates>,
     world_time: Res<'w, WorldTime>,
+    game_config: Res<'w, GameConfig>,
 
     #[system_param(ignore)]
     _secret: PhantomData<&'s ()>,
@@ -77,7 +90,6 @@ pub struct QuestSourceEntityQuery<'w> {
     basic_stats: Option<&'w mut BasicStats>,
     character_info: Option<&'w mut CharacterInfo>,
     client_entity: &'w ClientEntity,
-    client_entity_sector: &'w ClientEntitySector,
     equipment: Option<&'w Equipment>,
     experience_points: Option<&'w mut ExperiencePoints>,
     game_client: Option<&'w GameClient>,
@@ -1213,6 +1225,7 @@ fn get_quest_calculated_money_dup_count_var(
 }
 
 fn quest_reward_calculated_money(
+    quest_system_parameters: &mut QuestSystemParameters,
     quest_system_resources: &QuestSystemResources,
     quest_parameters: &mut QuestParameters,
     reward_equation_id: usize,
@@ -1249,6 +1262,13 @@ fn quest_reward_calculated_money(
 
     if let Some(inventory) = quest_parameters.source.inventory.as_mut() {
         if inventory.try_add_money(money).is_ok() {
+            quest_system_parameters
+                .statistics_events
+                .send(StatisticsEvent::EarnMoney {
+                    entity: quest_parameters.source.entity,
+                    amount: money.0,
+                });
+
             reset_quest_calculated_money_dup_count_var(
                 quest_parameters.selected_quest_index,
                 quest_parameters.source.quest_state.as_mut(),
@@ -1269,13 +1289,22 @@ fn quest_reward_calculated_money(
 }
 
 fn quest_reward_remove_selected_quest(
+    quest_system_parameters: &mut QuestSystemParameters,
     _quest_system_resources: &QuestSystemResources,
     quest_parameters: &mut QuestParameters,
 ) -> bool {
     if let Some(quest_state) = quest_parameters.source.quest_state.as_mut() {
         if let Some(quest_index) = quest_parameters.selected_quest_index {
             if let Some(quest_slot) = quest_state.get_quest_slot_mut(quest_index) {
-                *quest_slot = None;
+                // Quests are removed by their reward once they are completed
+                if let Some(active_quest) = quest_slot.take() {
+                    quest_system_parameters.achievement_events.send(
+                        AchievementEvent::CompleteQuest {
+                            entity: quest_parameters.source.entity,
+                            quest_id: active_quest.quest_id,
+                        },
+                    );
+                }
                 return true;
             }
         }
@@ -1497,33 +1526,23 @@ fn quest_reward_reset_basic_stats(
     quest_system_resources: &QuestSystemResources,
     quest_parameters: &mut QuestParameters,
 ) -> bool {
-    if let Some(character_info) = quest_parameters.source.character_info.as_ref() {
-        if let Ok(reset_basic_stats) = quest_system_resources
-            .game_data
-            .character_creator
-            .get_basic_stats(character_info.gender)
-        {
-            let mut total_stat_points = 0;
-            for level in 2..=quest_parameters.source.level.level {
-                total_stat_points += quest_system_resources
-                    .game_data
-                    .ability_value_calculator
-                    .calculate_levelup_reward_stat_points(level);
-            }
-
-            if let Some(basic_stats) = quest_parameters.source.basic_stats.as_mut() {
-                **basic_stats = reset_basic_stats;
-            }
-
-            if let Some(stat_points) = quest_parameters.source.stat_points.as_mut() {
-                stat_points.points = total_stat_points;
-            }
-
-            return true;
-        }
-    }
+    let source = &mut quest_parameters.source;
+    let (Some(character_info), Some(basic_stats), Some(stat_points)) = (
+        source.character_info.as_ref(),
+        source.basic_stats.as_mut(),
+        source.stat_points.as_mut(),
+    ) else {
+        return false;
+    };
 
-    false
+    basic_stats_reset(
+        &quest_system_resources.game_data,
+        character_info.gender,
+        source.level.level,
+        basic_stats,
+        stat_points,
+        source.game_client,
+    )
 }
 
 fn quest_reward_reset_skills(
@@ -1562,16 +1581,10 @@ fn quest_reward_teleport(
     new_zone_id: ZoneId,
     new_position: Vec3,
 ) -> bool {
-    client_entity_teleport_zone(
-        &mut quest_system_parameters.commands,
-        &mut quest_system_parameters.client_entity_list,
-        quest_parameters.source.entity,
-        quest_parameters.source.client_entity,
-        quest_parameters.source.client_entity_sector,
-        quest_parameters.source.position,
-        Position::new(new_position, new_zone_id),
-        quest_parameters.source.game_client,
-    );
+    quest_system_parameters.teleport_events.send(TeleportEvent {
+        entity: quest_parameters.source.entity,
+        position: Position::new(new_position, new_zone_id),
+    });
     true
 }
 
@@ -1831,6 +1844,7 @@ fn quest_reward_spawn_monster(
                     Team::new(team_number as u32),
                     None,
                     None,
+                    None,
                 );
             }
         }
@@ -2146,9 +2160,11 @@ fn quest_trigger_apply_rewards(
 ) -> bool {
     for reward in quest_trigger.rewards.iter() {
         let result = match *reward {
-            QsdReward::RemoveSelectedQuest => {
-                quest_reward_remove_selected_quest(quest_system_resources, quest_parameters)
-            }
+            QsdReward::RemoveSelectedQuest => quest_reward_remove_selected_quest(
+                quest_system_parameters,
+                quest_system_resources,
+                quest_parameters,
+            ),
             QsdReward::AddQuest { id } => {
                 quest_reward_add_quest(quest_system_resources, quest_parameters, id)
             }
@@ -2221,6 +2237,7 @@ fn quest_trigger_apply_rewards(
                 gem,
             ),
             QsdReward::CalculatedMoney { equation, value } => quest_reward_calculated_money(
+                quest_system_parameters,
                 quest_system_resources,
                 quest_parameters,
                 equation,
@@ -2361,6 +2378,223 @@ fn quest_trigger_apply_rewards(
     true
 }
 
+/// Runs a quest trigger and the triggers it chains to, returning whether any
+/// trigger succeeded and the id of the quest which was selected last.
+/// Returns the items from the quest rewards config which a trigger gives the
+/// character, or None if the trigger offers a choice of rewards and
+/// `reward_choice` is not one which is available to the character.
+fn quest_configured_reward_items(
+    quest_system_resources: &QuestSystemResources,
+    quest_parameters: &QuestParameters,
+    quest_trigger: &QuestTrigger,
+    reward_choice: Option<usize>,
+) -> Option<Vec<(ItemReference, u32)>> {
+    let Some(quest_rewards) = quest_system_resources
+        .game_config
+        .quest_rewards
+        .get(&quest_trigger.name)
+    else {
+        return Some(Vec::new());
+    };
+    let Some(character_info) = quest_parameters.source.character_info.as_ref() else {
+        return Some(Vec::new());
+    };
+    let level = quest_parameters.source.level.level;
+    let is_available = |option: &&QuestRewardOption| {
+        option.is_available(character_info.job, character_info.gender, level)
+    };
+
+    let mut options: Vec<&QuestRewardOption> = quest_rewards
+        .conditional
+        .iter()
+        .filter(is_available)
+        .collect();
+    if !quest_rewards.choices.is_empty() {
+        options.push(
+            reward_choice
+                .and_then(|index| quest_rewards.choices.get(index))
+                .filter(is_available)?,
+        );
+    }
+
+    Some(
+        options
+            .iter()
+            .flat_map(|option| option.items.iter())
+            .map(|reward| (reward.item, reward.quantity))
+            .collect(),
+    )
+}
+
+fn quest_trigger_run(
+    quest_system_parameters: &mut QuestSystemParameters,
+    quest_system_resources: &QuestSystemResources,
+    quest_source_entity: &mut QuestSourceEntityQueryItem,
+    trigger_hash: QuestTriggerHash,
+    reward_choice: Option<usize>,
+) -> (bool, Option<usize>) {
+    let mut trigger = quest_system_resources
+        .game_data
+        .quests
+        .get_trigger_by_hash(trigger_hash);
+    let mut success = false;
+    let mut quest_parameters = QuestParameters {
+        source: quest_source_entity,
+        selected_event_object: None,
+        selected_npc: None,
+        selected_quest_index: None,
+        next_trigger_name: None,
+    };
+
+    while trigger.is_some() {
+        let quest_trigger = trigger.unwrap();
+
+        let configured_reward_items = if quest_trigger_check_conditions(
+            quest_system_parameters,
+            quest_system_resources,
+            &mut quest_parameters,
+            quest_trigger,
+        ) {
+            let reward_items = quest_configured_reward_items(
+                quest_system_resources,
+                &quest_parameters,
+                quest_trigger,
+                reward_choice,
+            );
+            if reward_items.is_none() {
+                warn!(
+                    "Rejected quest trigger {} from entity {:?} with invalid reward choice {:?}",
+                    quest_trigger.name, quest_parameters.source.entity, reward_choice
+                );
+                success = false;
+                break;
+            }
+            reward_items
+        } else {
+            None
+        };
+
+        if configured_reward_items.is_some()
+            && quest_trigger_apply_rewards(
+                quest_system_parameters,
+                quest_system_resources,
+                &mut quest_parameters,
+                quest_trigger,
+            )
+        {
+            success = true;
+
+            for (item_reference, quantity) in configured_reward_items.unwrap_or_default() {
+                if let Some(item) = quest_system_resources
+                    .game_data
+                    .items
+                    .get_base_item(item_reference)
+                    .and_then(|item_data| Item::from_item_data(item_data, quantity))
+                {
+                    quest_system_parameters
+                        .reward_item_events
+                        .send(RewardItemEvent::new(
+                            quest_parameters.source.entity,
+                            item,
+                            true,
+                        ));
+                } else {
+                    warn!(
+                        "Invalid reward item {:?} for quest trigger {}",
+                        item_reference, quest_trigger.name
+                    );
+                }
+            }
+
+            if quest_parameters.next_trigger_name.is_some() {
+                trigger = quest_parameters.next_trigger_name.take().and_then(|name| {
+                    quest_system_resources
+                        .game_data
+                        .quests
+                        .get_trigger_by_name(&name)
+                });
+            } else {
+                trigger = None;
+            }
+        } else {
+            trigger = trigger
+                .unwrap()
+                .next_trigger_name
+                .as_ref()
+                .and_then(|name| {
+                    quest_system_resources
+                        .game_data
+                        .quests
+                        .get_trigger_by_name(name)
+                });
+        }
+    }
+
+    let selected_quest_id = quest_parameters
+        .selected_quest_index
+        .zip(quest_parameters.source.quest_state.as_ref())
+        .and_then(|(quest_index, quest_state)| quest_state.get_quest(quest_index))
+        .map(|active_quest| active_quest.quest_id);
+    (success, selected_quest_id)
+}
+
+/// Returns the party members, and clan members if enabled, which are within
+/// the share radius of the character which made progress in a shared quest.
+fn shared_quest_members(
+    quest_system_parameters: &QuestSystemParameters,
+    quest_system_resources: &QuestSystemResources,
+    query: &Query<QuestSourceEntityQuery>,
+    entity: Entity,
+) -> Vec<Entity> {
+    let Ok(source) = query.get(entity) else {
+        return Vec::new();
+    };
+    let shared_quests = &quest_system_resources.game_config.shared_quests;
+
+    let mut members: Vec<Entity> = source
+        .party_membership
+        .and_then(|party_membership| party_membership.party)
+        .and_then(|party_entity| quest_system_parameters.party_query.get(party_entity).ok())
+        .map(|party| {
+            party
+                .members
+                .iter()
+                .filter_map(PartyMember::get_entity)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if shared_quests.share_with_clan {
+        if let Some(clan) = source
+            .clan_membership
+            .and_then(|clan_membership| clan_membership.clan())
+            .and_then(|clan_entity| quest_system_parameters.clan_query.get(clan_entity).ok())
+        {
+            for clan_member in clan.members.iter() {
+                if let &ClanMember::Online { entity, .. } = clan_member {
+                    if !members.contains(&entity) {
+                        members.push(entity);
+                    }
+                }
+            }
+        }
+    }
+
+    members.retain(|&member_entity| {
+        member_entity != entity
+            && query.get(member_entity).map_or(false, |member| {
+                member.position.zone_id == source.position.zone_id
+                    && member
+                        .position
+                        .position
+                        .xy()
+                        .distance(source.position.position.xy())
+                        <= shared_quests.share_radius
+            })
+    });
+    members
+}
+
 pub fn quest_system(
     mut quest_system_parameters: QuestSystemParameters,
     quest_system_resources: QuestSystemResources,
@@ -2370,71 +2604,78 @@ pub fn quest_system(
     for &QuestTriggerEvent {
         trigger_entity,
         trigger_hash,
+        reward_choice,
     } in quest_trigger_events.iter()
     {
-        let mut trigger = quest_system_resources
-            .game_data
-            .quests
-            .get_trigger_by_hash(trigger_hash);
-        let mut success = false;
-
-        if let Ok(mut quest_source_entity) = query.get_mut(trigger_entity) {
-            let mut quest_parameters = QuestParameters {
-                source: &mut quest_source_entity,
-                selected_event_object: None,
-                selected_npc: None,
-                selected_quest_index: None,
-                next_trigger_name: None,
+        let Ok(mut quest_source_entity) = query.get_mut(trigger_entity) else {
+            continue;
+        };
+
+        let (success, selected_quest_id) = quest_trigger_run(
+            &mut quest_system_parameters,
+            &quest_system_resources,
+            &mut quest_source_entity,
+            trigger_hash,
+            reward_choice,
+        );
+
+        if let Some(game_client) = quest_source_entity.game_client {
+            game_client
+                .server_message_tx
+                .send(ServerMessage::QuestTriggerResult {
+                    success,
+                    trigger_hash,
+                })
+                .ok();
+        }
+
+        // Progress from killing a monster in a shared quest is also run for
+        // nearby members, each member's own quest conditions decide whether
+        // they progress so they can not exceed the quest's required count
+        if !success
+            || !quest_system_resources
+                .game_data
+                .npcs
+                .is_death_quest_trigger(trigger_hash)
+            || !selected_quest_id.map_or(false, |quest_id| {
+                quest_system_resources
+                    .game_config
+                    .shared_quests
+                    .is_shared_quest(quest_id)
+            })
+        {
+            continue;
+        }
+
+        for member_entity in shared_quest_members(
+            &quest_system_parameters,
+            &quest_system_resources,
+            &query,
+            trigger_entity,
+        ) {
+            let Ok(mut member) = query.get_mut(member_entity) else {
+                continue;
             };
 
-            while trigger.is_some() {
-                let quest_trigger = trigger.unwrap();
-
-                if quest_trigger_check_conditions(
-                    &mut quest_system_parameters,
-                    &quest_system_resources,
-                    &mut quest_parameters,
-                    quest_trigger,
-                ) && quest_trigger_apply_rewards(
-                    &mut quest_system_parameters,
-                    &quest_system_resources,
-                    &mut quest_parameters,
-                    quest_trigger,
-                ) {
-                    success = true;
-
-                    if quest_parameters.next_trigger_name.is_some() {
-                        trigger = quest_parameters.next_trigger_name.take().and_then(|name| {
-                            quest_system_resources
-                                .game_data
-                                .quests
-                                .get_trigger_by_name(&name)
-                        });
-                    } else {
-                        trigger = None;
-                    }
-                } else {
-                    trigger = trigger
-                        .unwrap()
-                        .next_trigger_name
-                        .as_ref()
-                        .and_then(|name| {
-                            quest_system_resources
-                                .game_data
-                                .quests
-                                .get_trigger_by_name(name)
-                        });
-                }
-            }
+            let (success, _) = quest_trigger_run(
+                &mut quest_system_parameters,
+                &quest_system_resources,
+                &mut member,
+                trigger_hash,
+                None,
+            );
 
-            if let Some(game_client) = quest_source_entity.game_client {
-                game_client
-                    .server_message_tx
-                    .send(ServerMessage::QuestTriggerResult {
-                        success,
-                        trigger_hash,
-                    })
-                    .ok();
+            // The client applies the trigger itself when it succeeds
+            if success {
+                if let Some(game_client) = member.game_client {
+                    game_client
+                        .server_message_tx
+                        .send(ServerMessage::QuestTriggerResult {
+                            success,
+                            trigger_hash,
+                        })
+                        .ok();
+                }
             }
         }
     }
@@ -0,0 +1,284 @@
+use bevy::ecs::{
+    prelude::{EventReader, Query, Res, ResMut},
+    query::WorldQuery,
+};
+use log::info;
+
+use rose_game_common::components::SkillSlot;
+
+use crate::game::{
+    bundles::basic_stats_send_update,
+    components::{
+        BasicStats, CharacterInfo, ClientEntity, Equipment, ExperiencePoints, GameClient,
+        HealthPoints, Inventory, ItemSlot, Level, ManaPoints, Rebirth, SkillList, SkillPoints,
+        StatPoints, StatusEffects,
+    },
+    events::RebirthEvent,
+    messages::server::ServerMessage,
+    resources::{GameConfig, RebirthConfig, ServerMessages},
+    GameData,
+};
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct RebirthQuery<'w> {
+    client_entity: &'w ClientEntity,
+    game_client: &'w GameClient,
+    character_info: &'w CharacterInfo,
+    basic_stats: &'w mut BasicStats,
+    equipment: &'w mut Equipment,
+    experience_points: &'w mut ExperiencePoints,
+    health_points: &'w mut HealthPoints,
+    inventory: &'w mut Inventory,
+    level: &'w mut Level,
+    mana_points: &'w mut ManaPoints,
+    rebirth: &'w mut Rebirth,
+    skill_list: &'w mut SkillList,
+    skill_points: &'w mut SkillPoints,
+    stat_points: &'w mut StatPoints,
+    status_effects: &'w StatusEffects,
+}
+
+enum RebirthError {
+    Dead,
+    Disabled,
+    LevelTooLow(u32),
+    MaxRebirths(u32),
+}
+
+fn send_rebirth_message(game_client: &GameClient, text: String) {
+    game_client
+        .server_message_tx
+        .send(ServerMessage::Whisper {
+            from: String::from("SERVER"),
+            text,
+        })
+        .ok();
+}
+
+fn check_can_rebirth(
+    game_config: &GameConfig,
+    character: &RebirthQueryItem,
+) -> Result<(), RebirthError> {
+    let rebirth_config = game_config.rebirth.as_ref().ok_or(RebirthError::Disabled)?;
+    let min_level = rebirth_config
+        .get_min_level(game_config.level_cap)
+        .ok_or(RebirthError::Disabled)?;
+
+    if character.health_points.hp == 0 {
+        return Err(RebirthError::Dead);
+    }
+
+    if character.level.level < min_level {
+        return Err(RebirthError::LevelTooLow(min_level));
+    }
+
+    if let Some(max_rebirths) = rebirth_config.max_rebirths {
+        if character.rebirth.count >= max_rebirths {
+            return Err(RebirthError::MaxRebirths(max_rebirths));
+        }
+    }
+
+    Ok(())
+}
+
+fn reset_skills(character: &mut RebirthQueryItem) {
+    // Like the reset skills quest reward, keep the skills of the first page
+    // which every character starts with
+    for page in character.skill_list.pages[1..].iter_mut() {
+        for (index, skill) in page.skills.iter_mut().enumerate() {
+            if skill.take().is_some() {
+                character
+                    .game_client
+                    .server_message_tx
+                    .send(ServerMessage::LearnSkillSuccess {
+                        skill_slot: SkillSlot(page.page_type, index),
+                        skill_id: None,
+                        updated_skill_points: SkillPoints::new(0),
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    character.skill_points.points = 0;
+}
+
+fn remove_items(character: &mut RebirthQueryItem, server_messages: &mut ServerMessages) {
+    let mut updated_items = Vec::new();
+
+    for (equipment_index, item) in character.equipment.equipped_items.iter_mut() {
+        if item.take().is_some() {
+            updated_items.push((ItemSlot::Equipment(equipment_index), None));
+            server_messages.send_entity_message(
+                character.client_entity,
+                ServerMessage::UpdateEquipment {
+                    entity_id: character.client_entity.id,
+                    equipment_index,
+                    item: None,
+                },
+            );
+        }
+    }
+
+    for (vehicle_part_index, item) in character.equipment.equipped_vehicle.iter_mut() {
+        if item.take().is_some() {
+            updated_items.push((ItemSlot::Vehicle(vehicle_part_index), None));
+            server_messages.send_entity_message(
+                character.client_entity,
+                ServerMessage::UpdateVehiclePart {
+                    entity_id: character.client_entity.id,
+                    vehicle_part_index,
+                    item: None,
+                },
+            );
+        }
+    }
+
+    for (ammo_index, item) in character.equipment.equipped_ammo.iter_mut() {
+        if item.take().is_some() {
+            updated_items.push((ItemSlot::Ammo(ammo_index), None));
+            server_messages.send_entity_message(
+                character.client_entity,
+                ServerMessage::UpdateAmmo {
+                    entity_id: character.client_entity.id,
+                    ammo_index,
+                    item: None,
+                },
+            );
+        }
+    }
+
+    let inventory = &mut *character.inventory;
+    for page in [
+        &mut inventory.equipment,
+        &mut inventory.consumables,
+        &mut inventory.materials,
+        &mut inventory.vehicles,
+    ] {
+        for (index, item) in page.slots.iter_mut().enumerate() {
+            if item.take().is_some() {
+                updated_items.push((ItemSlot::Inventory(page.page_type, index), None));
+            }
+        }
+    }
+
+    if !updated_items.is_empty() {
+        character
+            .game_client
+            .server_message_tx
+            .send(ServerMessage::UpdateInventory {
+                items: updated_items,
+                money: None,
+            })
+            .ok();
+    }
+}
+
+fn rebirth(
+    game_data: &GameData,
+    rebirth_config: &RebirthConfig,
+    server_messages: &mut ServerMessages,
+    character: &mut RebirthQueryItem,
+) {
+    character.rebirth.count += 1;
+    character.level.level = 1;
+    character.experience_points.xp = 0;
+    character.stat_points.points = 0;
+
+    if let Ok(initial_basic_stats) = game_data
+        .character_creator
+        .get_basic_stats(character.character_info.gender)
+    {
+        *character.basic_stats = initial_basic_stats;
+    }
+
+    if !rebirth_config.keep_skills {
+        reset_skills(character);
+    }
+
+    if !rebirth_config.keep_items {
+        remove_items(character, server_messages);
+    }
+
+    let ability_values = game_data.ability_value_calculator.calculate(
+        character.character_info,
+        &character.level,
+        &character.equipment,
+        &character.basic_stats,
+        &character.skill_list,
+        character.status_effects,
+    );
+    character.health_points.hp = ability_values.get_max_health();
+    character.mana_points.mp = ability_values.get_max_mana();
+
+    basic_stats_send_update(character.game_client, &character.basic_stats);
+
+    server_messages.send_entity_message(
+        character.client_entity,
+        ServerMessage::UpdateLevel {
+            entity_id: character.client_entity.id,
+            level: *character.level,
+            experience_points: *character.experience_points,
+            stat_points: *character.stat_points,
+            skill_points: *character.skill_points,
+        },
+    );
+
+    server_messages.send_entity_message(
+        character.client_entity,
+        ServerMessage::UpdateRebirthCount {
+            entity_id: character.client_entity.id,
+            rebirth_count: character.rebirth.count,
+        },
+    );
+}
+
+pub fn rebirth_system(
+    mut rebirth_events: EventReader<RebirthEvent>,
+    mut query: Query<RebirthQuery>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    mut server_messages: ResMut<ServerMessages>,
+) {
+    for event in rebirth_events.iter() {
+        let Ok(mut character) = query.get_mut(event.entity) else {
+            continue;
+        };
+
+        if let Err(error) = check_can_rebirth(&game_config, &character) {
+            let text = match error {
+                RebirthError::Dead => String::from("You can not be reborn whilst dead"),
+                RebirthError::Disabled => String::from("Rebirth is not enabled on this server"),
+                RebirthError::LevelTooLow(min_level) => {
+                    format!("You must reach level {} to be reborn", min_level)
+                }
+                RebirthError::MaxRebirths(max_rebirths) => {
+                    format!("You have already been reborn {} times", max_rebirths)
+                }
+            };
+            send_rebirth_message(character.game_client, text);
+            continue;
+        }
+
+        let rebirth_config = game_config.rebirth.as_ref().unwrap();
+        rebirth(
+            &game_data,
+            rebirth_config,
+            &mut server_messages,
+            &mut character,
+        );
+
+        info!(
+            "Character {} was reborn, rebirth count {}",
+            character.character_info.name, character.rebirth.count
+        );
+        send_rebirth_message(
+            character.game_client,
+            format!(
+                "You have been reborn, rebirth count {}",
+                character.rebirth.count
+            ),
+        );
+    }
+}
@@ -1,19 +1,17 @@
 use bevy::{
     ecs::query::WorldQuery,
-    prelude::{Commands, Entity, EventReader, Query, Res, ResMut, Vec3, With},
+    prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, Vec3, With},
 };
 use rand::Rng;
 
 use rose_game_common::components::{AbilityValues, CharacterInfo, HealthPoints, ManaPoints};
 
 use crate::game::{
-    bundles::client_entity_teleport_zone,
     components::{
-        ClientEntity, ClientEntitySector, Command, DamageSources, Dead, GameClient, MoveMode,
-        NextCommand, PassiveRecoveryTime, Position, StatusEffects,
+        Command, DamageSources, Dead, MoveMode, NextCommand, PassiveRecoveryTime, Position,
+        StatusEffects,
     },
-    events::{ReviveEvent, RevivePosition},
-    resources::ClientEntityList,
+    events::{ReviveEvent, RevivePosition, TeleportEvent},
     GameData,
 };
 
@@ -24,12 +22,8 @@ pub struct ReviveEntityQuery<'w> {
     entity: Entity,
 
     ability_values: &'w AbilityValues,
-    client_entity: &'w ClientEntity,
-    client_entity_sector: &'w ClientEntitySector,
     character_info: &'w CharacterInfo,
     position: &'w Position,
-
-    game_client: Option<&'w GameClient>,
 }
 
 pub fn revive_event_system(
@@ -37,7 +31,7 @@ pub fn revive_event_system(
     mut events: EventReader<ReviveEvent>,
     query: Query<ReviveEntityQuery, With<Dead>>,
     game_data: Res<GameData>,
-    mut client_entity_list: ResMut<ClientEntityList>,
+    mut teleport_events: EventWriter<TeleportEvent>,
 ) {
     let mut rng = rand::thread_rng();
 
@@ -89,15 +83,9 @@ pub fn revive_event_system(
         ));
 
         // Teleport to respawn position
-        client_entity_teleport_zone(
-            &mut commands,
-            &mut client_entity_list,
-            entity.entity,
-            entity.client_entity,
-            entity.client_entity_sector,
-            entity.position,
-            new_position.clone(),
-            entity.game_client,
-        );
+        teleport_events.send(TeleportEvent {
+            entity: entity.entity,
+            position: new_position,
+        });
     }
 }
@@ -0,0 +1,242 @@
+use bevy::ecs::{
+    prelude::{EventReader, Query, Res, ResMut},
+    query::WorldQuery,
+};
+use chrono::Datelike;
+use log::{error, warn};
+
+use rose_data::Item;
+
+use crate::game::{
+    components::{
+        Account, Bank, CharacterInfo, GameClient, Inventory, ItemSlot, Money, RewardCalendar,
+    },
+    events::RewardCalendarEvent,
+    messages::server::ServerMessage,
+    resources::{GameConfig, RewardCalendarConfig, RewardCalendarReward, StorageService},
+    storage::{
+        bank::BankStorage, item_transaction::ItemTransaction,
+        reward_calendar::RewardCalendarStorage,
+    },
+    GameData,
+};
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct RewardCalendarQuery<'w> {
+    account: &'w Account,
+    character_info: &'w CharacterInfo,
+    game_client: &'w GameClient,
+    reward_calendar: &'w mut RewardCalendar,
+    inventory: &'w mut Inventory,
+    bank: &'w mut Bank,
+}
+
+enum ClaimError {
+    AlreadyClaimed,
+    NoSpace,
+}
+
+fn send_reward_calendar_message(game_client: &GameClient, text: String) {
+    game_client
+        .server_message_tx
+        .send(ServerMessage::Whisper {
+            from: String::from("SERVER"),
+            text,
+        })
+        .ok();
+}
+
+fn get_rewards_for_streak(
+    config: &RewardCalendarConfig,
+    streak: u32,
+) -> impl Iterator<Item = &RewardCalendarReward> {
+    let day_reward = if config.days.is_empty() {
+        None
+    } else {
+        config
+            .days
+            .get((streak.saturating_sub(1) as usize) % config.days.len())
+    };
+
+    let streak_bonus =
+        if config.streak_bonus_interval > 0 && streak % config.streak_bonus_interval == 0 {
+            Some(&config.streak_bonus)
+        } else {
+            None
+        };
+
+    day_reward.into_iter().chain(streak_bonus)
+}
+
+fn reward_calendar_claim(
+    game_data: &GameData,
+    config: &RewardCalendarConfig,
+    storage_service: &mut StorageService,
+    claimer: &mut RewardCalendarQueryItem,
+    today: i32,
+) -> Result<u32, ClaimError> {
+    if !claimer.reward_calendar.can_claim(today) {
+        return Err(ClaimError::AlreadyClaimed);
+    }
+
+    let streak = claimer.reward_calendar.get_claim_streak(today);
+    let mut money = Money(0);
+    let mut items = Vec::new();
+    for reward in get_rewards_for_streak(config, streak) {
+        money = money + reward.money;
+
+        for reward_item in reward.items.iter() {
+            if let Some(item) = game_data
+                .items
+                .get_base_item(reward_item.item)
+                .and_then(|item_data| Item::from_item_data(item_data, reward_item.quantity))
+            {
+                items.push(item);
+            } else {
+                warn!(
+                    "Invalid reward calendar item {:?} for streak {}",
+                    reward_item.item, streak
+                );
+            }
+        }
+    }
+
+    // Deliver to a copy first so we never give out a partial reward, items
+    // which do not fit in the inventory are delivered to the bank instead.
+    let mut inventory = claimer.inventory.clone();
+    let mut bank = Bank {
+        slots: claimer.bank.slots.clone(),
+        sent_to_client: false,
+    };
+    let mut updated_inventory_slots: Vec<ItemSlot> = Vec::new();
+    let mut updated_bank = false;
+
+    inventory
+        .try_add_money(money)
+        .map_err(|_| ClaimError::NoSpace)?;
+
+    for item in items {
+        match inventory.try_add_item(item) {
+            Ok((item_slot, _)) => updated_inventory_slots.push(item_slot),
+            Err(item) => {
+                bank.try_add_item(item).map_err(|_| ClaimError::NoSpace)?;
+                updated_bank = true;
+            }
+        }
+    }
+
+    *claimer.inventory = inventory;
+    if updated_bank {
+        claimer.bank.slots = bank.slots;
+
+        // Force the bank to be resent next time it is opened
+        claimer.bank.sent_to_client = false;
+    }
+
+    claimer.reward_calendar.last_claim_day = Some(today);
+    claimer.reward_calendar.streak = streak;
+
+    // Save the claim together with the rewards, so a crash can never lose
+    // the rewards or allow them to be claimed twice
+    let mut transaction = ItemTransaction::new();
+    transaction.update_inventory(&claimer.character_info.name, &claimer.inventory);
+    if updated_bank {
+        transaction.update_bank(&claimer.account.name, BankStorage::from(&*claimer.bank));
+    }
+    transaction.update_reward_calendar(
+        &claimer.account.name,
+        RewardCalendarStorage::from(&*claimer.reward_calendar),
+    );
+    if let Err(error) = storage_service.write_item_transaction(transaction) {
+        error!(
+            "Failed to save reward calendar for account {} with error {:?}",
+            &claimer.account.name, error
+        );
+    }
+
+    claimer
+        .game_client
+        .server_message_tx
+        .send(ServerMessage::UpdateInventory {
+            items: updated_inventory_slots
+                .iter()
+                .map(|item_slot| (*item_slot, claimer.inventory.get_item(*item_slot).cloned()))
+                .collect(),
+            money: Some(claimer.inventory.money),
+        })
+        .ok();
+
+    if updated_bank {
+        send_reward_calendar_message(
+            claimer.game_client,
+            String::from("Your inventory is full, some rewards were delivered to your bank."),
+        );
+    }
+
+    Ok(streak)
+}
+
+pub fn reward_calendar_system(
+    mut query: Query<RewardCalendarQuery>,
+    mut reward_calendar_events: EventReader<RewardCalendarEvent>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    mut storage_service: ResMut<StorageService>,
+) {
+    let today = chrono::Local::now().date_naive().num_days_from_ce();
+
+    for event in reward_calendar_events.iter() {
+        let Some(config) = game_config.reward_calendar.as_ref() else {
+            continue;
+        };
+
+        match *event {
+            RewardCalendarEvent::Prompt { entity } => {
+                let Ok(mut user) = query.get_mut(entity) else {
+                    continue;
+                };
+
+                // Only prompt once per session rather than on every zone change
+                if !user.reward_calendar.prompted && user.reward_calendar.can_claim(today) {
+                    user.reward_calendar.prompted = true;
+                    send_reward_calendar_message(
+                        user.game_client,
+                        format!(
+                            "Your daily login reward for day {} is ready, type /dailyreward to claim it.",
+                            user.reward_calendar.get_claim_streak(today)
+                        ),
+                    );
+                }
+            }
+            RewardCalendarEvent::Claim { entity } => {
+                let Ok(mut claimer) = query.get_mut(entity) else {
+                    continue;
+                };
+
+                match reward_calendar_claim(
+                    &game_data,
+                    config,
+                    &mut storage_service,
+                    &mut claimer,
+                    today,
+                ) {
+                    Ok(streak) => send_reward_calendar_message(
+                        claimer.game_client,
+                        format!("You have claimed your daily login reward for day {}.", streak),
+                    ),
+                    Err(ClaimError::AlreadyClaimed) => send_reward_calendar_message(
+                        claimer.game_client,
+                        String::from("You have already claimed your daily login reward today."),
+                    ),
+                    Err(ClaimError::NoSpace) => send_reward_calendar_message(
+                        claimer.game_client,
+                        String::from(
+                            "You do not have enough space in your inventory or bank to claim your daily login reward.",
+                        ),
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use crate::game::{
-    bundles::ItemDropBundle,
-    components::{DroppedItem, GameClient, Inventory, Position},
+    bundles::{item_assign_instance_id, ItemDropBundle, ItemDropOwner},
+    components::{CharacterInfo, DroppedItem, GameClient, Inventory, Position},
     events::RewardItemEvent,
     messages::server::ServerMessage,
-    resources::ClientEntityList,
+    resources::{ClientEntityList, GameConfig},
 };
 use bevy::{
     ecs::{
@@ -15,14 +15,31 @@ use bevy::{
 
 pub fn reward_item_system(
     mut commands: Commands,
-    mut query: Query<(&Position, &mut Inventory, Option<&GameClient>)>,
+    mut query: Query<(
+        &Position,
+        &mut Inventory,
+        Option<&GameClient>,
+        Option<&CharacterInfo>,
+    )>,
     mut reward_item_events: EventReader<RewardItemEvent>,
     mut client_entity_list: ResMut<ClientEntityList>,
+    game_config: Res<GameConfig>,
     time: Res<Time>,
 ) {
     for event in reward_item_events.iter() {
-        if let Ok((position, mut inventory, game_client)) = query.get_mut(event.entity) {
-            match inventory.try_add_item(event.item.clone()) {
+        if let Ok((position, mut inventory, game_client, character_info)) =
+            query.get_mut(event.entity)
+        {
+            let mut item = event.item.clone();
+            item_assign_instance_id(
+                &mut item,
+                &format!(
+                    "reward for character {}",
+                    character_info.map_or("", |character_info| &character_info.name)
+                ),
+            );
+
+            match inventory.try_add_item(item) {
                 Ok((slot, item)) => {
                     if let Some(game_client) = game_client {
                         game_client
@@ -40,8 +57,13 @@ pub fn reward_item_system(
                             &mut client_entity_list,
                             DroppedItem::Item(item),
                             position,
-                            Some(event.entity),
-                            None,
+                            game_config
+                                .item_drop_owner_duration
+                                .map(|duration| ItemDropOwner {
+                                    entity: event.entity,
+                                    party_entity: None,
+                                    duration,
+                                }),
                             &time,
                         );
                     }
@@ -3,19 +3,30 @@ use bevy::ecs::{
     prelude::{Commands, EventReader, Query, ResMut},
     query::WorldQuery,
 };
-use log::{error, info};
+use log::{error, info, warn};
+use std::time::Instant;
 
 use crate::game::{
     bundles::client_entity_leave_zone,
     components::{
-        Account, Bank, BasicStats, CharacterInfo, ClanMembership, ClientEntity, ClientEntitySector,
-        Equipment, ExperiencePoints, HealthPoints, Hotbar, Inventory, Level, ManaPoints,
-        PartyMembership, Position, QuestState, SkillList, SkillPoints, Stamina, StatPoints,
-        UnionMembership,
+        Account, Achievements, Bank, BasicStats, CharacterInfo, ClanMembership, ClientEntity,
+        ClientEntitySector, Cooldowns, Equipment, ExperiencePoints, HealthPoints, HonorPoints,
+        Hotbar, Inventory, Level, ManaPoints, OfflineVendor, OfflineVendorProceeds,
+        PartyMembership, Position, QuestState, Rebirth, RewardCalendar, SkillList, SkillPoints,
+        Stamina, StatPoints, Statistics, StatusEffects, StatusEffectsRegen, UnionMembership,
     },
     events::{ClanEvent, PartyMemberEvent, SaveEvent},
-    resources::ClientEntityList,
-    storage::{bank::BankStorage, character::CharacterStorage},
+    messages::server::CharacterListItem,
+    resources::{
+        AccountSessions, CharacterListCache, ClientEntityList, StorageKey, StorageService,
+        StorageWriteStatus,
+    },
+    storage::{
+        bank::BankStorage,
+        character::{CharacterStorage, SavedEffects},
+        character_inspection::CharacterInspection,
+        reward_calendar::RewardCalendarStorage,
+    },
 };
 
 #[derive(WorldQuery)]
@@ -25,7 +36,8 @@ pub struct SaveEntityQuery<'w> {
     account: &'w Account,
     character_info: &'w CharacterInfo,
     basic_stats: &'w BasicStats,
-    bank: &'w Bank,
+    bank: Option<&'w Bank>,
+    reward_calendar: Option<&'w RewardCalendar>,
     inventory: &'w Inventory,
     equipment: &'w Equipment,
     level: &'w Level,
@@ -40,14 +52,82 @@ pub struct SaveEntityQuery<'w> {
     quest_state: &'w QuestState,
     union_membership: &'w UnionMembership,
     stamina: &'w Stamina,
+    achievements: &'w Achievements,
+    statistics: &'w Statistics,
+    rebirth: &'w Rebirth,
+    honor_points: &'w HonorPoints,
+    cooldowns: &'w Cooldowns,
+    status_effects: &'w StatusEffects,
+    status_effects_regen: &'w StatusEffectsRegen,
     party_membership: &'w PartyMembership,
     clan_membership: &'w ClanMembership,
+    offline_vendor: Option<&'w OfflineVendor>,
+    offline_vendor_proceeds: Option<&'w OfflineVendorProceeds>,
+}
+
+impl SaveEntityQueryItem<'_, '_> {
+    pub fn character_name(&self) -> &str {
+        &self.character_info.name
+    }
+
+    pub fn character_storage(&self) -> CharacterStorage {
+        CharacterStorage {
+            info: self.character_info.clone(),
+            basic_stats: self.basic_stats.clone(),
+            inventory: self.inventory.clone(),
+            equipment: self.equipment.clone(),
+            level: *self.level,
+            experience_points: *self.experience_points,
+            position: self.position.clone(),
+            skill_list: self.skill_list.clone(),
+            hotbar: self.hotbar.clone(),
+            delete_time: None,
+            health_points: *self.health_points,
+            mana_points: *self.mana_points,
+            stat_points: *self.stat_points,
+            skill_points: *self.skill_points,
+            quest_state: self.quest_state.clone(),
+            union_membership: self.union_membership.clone(),
+            stamina: *self.stamina,
+            achievements: self.achievements.clone(),
+            statistics: self.statistics.clone(),
+            rebirth: *self.rebirth,
+            honor_points: *self.honor_points,
+            saved_effects: SavedEffects::new(
+                self.status_effects,
+                self.status_effects_regen,
+                self.cooldowns,
+                Instant::now(),
+            ),
+            offline_vendor_proceeds: self
+                .offline_vendor
+                .map(|offline_vendor| &offline_vendor.proceeds)
+                .or(self.offline_vendor_proceeds)
+                .filter(|proceeds| proceeds.items_sold > 0)
+                .cloned(),
+        }
+    }
+
+    /// Inspects the character as it is in the game world, which may be newer
+    /// than the data in storage.
+    pub fn character_inspection(&self, pending_write: bool) -> CharacterInspection {
+        CharacterInspection {
+            account_name: Some(self.account.name.clone()),
+            online: true,
+            pending_write,
+            character: self.character_storage(),
+            bank: self.bank.map(BankStorage::from),
+        }
+    }
 }
 
 pub fn save_system(
     mut commands: Commands,
     query: Query<SaveEntityQuery>,
     mut client_entity_list: ResMut<ClientEntityList>,
+    mut account_sessions: ResMut<AccountSessions>,
+    mut character_list_cache: ResMut<CharacterListCache>,
+    mut storage_service: ResMut<StorageService>,
     mut save_events: EventReader<SaveEvent>,
     mut clan_events: EventWriter<ClanEvent>,
     mut party_member_events: EventWriter<PartyMemberEvent>,
@@ -59,40 +139,66 @@ pub fn save_system(
                 remove_after_save,
             } => {
                 if let Ok(character) = query.get(entity) {
-                    let storage = CharacterStorage {
-                        info: character.character_info.clone(),
-                        basic_stats: character.basic_stats.clone(),
-                        inventory: character.inventory.clone(),
-                        equipment: character.equipment.clone(),
-                        level: *character.level,
-                        experience_points: *character.experience_points,
-                        position: character.position.clone(),
-                        skill_list: character.skill_list.clone(),
-                        hotbar: character.hotbar.clone(),
-                        delete_time: None,
-                        health_points: *character.health_points,
-                        mana_points: *character.mana_points,
-                        stat_points: *character.stat_points,
-                        skill_points: *character.skill_points,
-                        quest_state: character.quest_state.clone(),
-                        union_membership: character.union_membership.clone(),
-                        stamina: *character.stamina,
-                    };
-                    match storage.save() {
-                        Ok(_) => info!("Saved character {}", &character.character_info.name),
+                    let storage = character.character_storage();
+                    let character_list_item = CharacterListItem::from(&storage);
+                    match storage_service.write(
+                        StorageKey::Character(character.character_info.name.clone()),
+                        move || storage.save(),
+                    ) {
+                        Ok(StorageWriteStatus::Written) => {
+                            info!("Saved character {}", &character.character_info.name)
+                        }
+                        Ok(StorageWriteStatus::Queued) => warn!(
+                            "Queued save of character {} until storage recovers",
+                            &character.character_info.name
+                        ),
                         Err(error) => error!(
                             "Failed to save character {} with error {:?}",
                             &character.character_info.name, error
                         ),
                     }
+                    character_list_cache.update(&character.account.name, character_list_item);
 
-                    let bank_storage = BankStorage::from(character.bank);
-                    match bank_storage.save(&character.account.name) {
-                        Ok(_) => info!("Saved bank for account {}", &character.account.name),
-                        Err(error) => error!(
-                            "Failed to save bank for account {} with error {:?}",
-                            &character.account.name, error
-                        ),
+                    if let Some(bank) = character.bank {
+                        let bank_storage = BankStorage::from(bank);
+                        let account_name = character.account.name.clone();
+                        match storage_service
+                            .write(StorageKey::Bank(account_name.clone()), move || {
+                                bank_storage.save(&account_name)
+                            }) {
+                            Ok(StorageWriteStatus::Written) => {
+                                info!("Saved bank for account {}", &character.account.name)
+                            }
+                            Ok(StorageWriteStatus::Queued) => warn!(
+                                "Queued save of bank for account {} until storage recovers",
+                                &character.account.name
+                            ),
+                            Err(error) => error!(
+                                "Failed to save bank for account {} with error {:?}",
+                                &character.account.name, error
+                            ),
+                        }
+                    }
+
+                    if let Some(reward_calendar) = character.reward_calendar {
+                        let reward_calendar_storage = RewardCalendarStorage::from(reward_calendar);
+                        let account_name = character.account.name.clone();
+                        if let Err(error) = storage_service.write(
+                            StorageKey::RewardCalendar(account_name.clone()),
+                            move || reward_calendar_storage.save(&account_name),
+                        ) {
+                            error!(
+                                "Failed to save reward calendar for account {} with error {:?}",
+                                &character.account.name, error
+                            );
+                        }
+                    }
+
+                    if character.offline_vendor.is_some() {
+                        // The account can join the game with another character whilst this
+                        // one is an offline vendor, so its account documents are only saved
+                        // once when it becomes a vendor
+                        commands.entity(entity).remove::<(Bank, RewardCalendar)>();
                     }
 
                     if remove_after_save {
@@ -132,6 +238,7 @@ pub fn save_system(
 
                 if remove_after_save {
                     commands.entity(entity).despawn();
+                    account_sessions.release_client(entity);
                 }
             }
         }
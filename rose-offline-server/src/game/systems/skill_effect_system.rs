@@ -1,4 +1,7 @@
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 
 use bevy::{
     ecs::{
@@ -6,7 +9,7 @@ use bevy::{
         query::WorldQuery,
         system::SystemParam,
     },
-    math::Vec3Swizzles,
+    math::{Vec2, Vec3, Vec3Swizzles},
     time::Time,
 };
 use log::warn;
@@ -21,16 +24,29 @@ use rose_game_common::{components::Money, data::Damage};
 use crate::game::{
     bundles::{ability_values_get_value, MonsterBundle, GLOBAL_SKILL_COOLDOWN},
     components::{
-        AbilityValues, ClanMembership, ClientEntity, ClientEntityType, Cooldowns, Dead,
-        ExperiencePoints, GameClient, HealthPoints, Inventory, Level, ManaPoints, MoveMode,
-        MoveSpeed, PartyMembership, Position, SpawnOrigin, Stamina, StatusEffects, Team,
+        AbilityValues, ClanMembership, ClientEntity, ClientEntityType, Command, CommandData,
+        Cooldowns, Dead, ExperiencePoints, GameClient, HealthPoints, Inventory, Level, ManaPoints,
+        MoveMode, MoveSpeed, MovementImpairment, MovementImpairmentType, NextCommand,
+        PartyMembership, Position, PositionHistory, SpawnOrigin, Stamina, StatusEffects, Team,
     },
-    events::{DamageEvent, ItemLifeEvent, SkillEvent, SkillEventTarget},
+    events::{DamageEvent, ItemLifeEvent, KnockbackEvent, SkillEvent, SkillEventTarget},
     messages::server::{CancelCastingSkillReason, ServerMessage},
-    resources::{ClientEntityList, ServerMessages},
+    resources::{
+        ClanWars, ClientEntityList, ClientEntityZone, GameConfig, ServerMessages, SkillChainConfig,
+        SkillChainType, SkillMovementEffect, ZoneGeometry,
+    },
     GameData,
 };
 
+// Fastest we expect a target to move, used to extend the search area for area
+// of effect skill targets which may have been in range within the latency
+// compensation window
+const LATENCY_COMPENSATION_MAX_MOVE_SPEED: f32 = 2000.0;
+
+// Allowance for client rounding when validating the target position of area of
+// effect skills against the skill's cast range
+const SKILL_CAST_RANGE_TOLERANCE: f32 = 100.0;
+
 #[allow(dead_code)]
 enum SkillCastError {
     InvalidSkill,
@@ -43,6 +59,7 @@ pub struct SkillSystemParameters<'w, 's> {
     server_messages: ResMut<'w, ServerMessages>,
     damage_events: EventWriter<'w, DamageEvent>,
     item_life_events: EventWriter<'w, ItemLifeEvent>,
+    knockback_events: EventWriter<'w, KnockbackEvent>,
 
     #[system_param(ignore)]
     _secret: PhantomData<&'s ()>,
@@ -50,8 +67,11 @@ pub struct SkillSystemParameters<'w, 's> {
 
 #[derive(SystemParam)]
 pub struct SkillSystemResources<'w, 's> {
+    clan_wars: Res<'w, ClanWars>,
+    game_config: Res<'w, GameConfig>,
     game_data: Res<'w, GameData>,
     time: Res<'w, Time>,
+    zone_geometry: Res<'w, ZoneGeometry>,
 
     #[system_param(ignore)]
     _secret: PhantomData<&'s ()>,
@@ -93,11 +113,16 @@ pub struct SkillTargetQuery<'w> {
     clan_membership: Option<&'w ClanMembership>,
     dead: Option<&'w Dead>,
     party_membership: Option<&'w PartyMembership>,
+    position_history: Option<&'w PositionHistory>,
 
     health_points: &'w mut HealthPoints,
     mana_points: Option<&'w mut ManaPoints>,
     stamina: Option<&'w mut Stamina>,
     status_effects: &'w mut StatusEffects,
+
+    command: Option<&'w mut Command>,
+    movement_impairment: Option<&'w mut MovementImpairment>,
+    next_command: Option<&'w mut NextCommand>,
 }
 
 // TODO: Deduplicate code with skill_use.rs check_skill_target_filter
@@ -105,9 +130,18 @@ fn check_skill_target_filter(
     skill_caster: &SkillCasterQueryItem,
     skill_target: &SkillTargetQueryItem,
     skill_data: &SkillData,
+    clan_wars: &ClanWars,
 ) -> bool {
     let target_is_alive = skill_target.health_points.hp > 0;
     let target_is_caster = skill_caster.entity == skill_target.entity;
+    let is_clan_war_target = clan_wars.is_at_war(
+        skill_caster
+            .clan_membership
+            .and_then(|clan_membership| clan_membership.clan()),
+        skill_target
+            .clan_membership
+            .and_then(|clan_membership| clan_membership.clan()),
+    );
 
     match skill_data.target_filter {
         SkillTargetFilter::OnlySelf => target_is_alive && target_is_caster,
@@ -144,11 +178,11 @@ fn check_skill_target_filter(
         SkillTargetFilter::Enemy => {
             target_is_alive
                 && skill_target.team.id != Team::DEFAULT_NPC_TEAM_ID
-                && skill_caster.team.id != skill_target.team.id
+                && (skill_caster.team.id != skill_target.team.id || is_clan_war_target)
         }
         SkillTargetFilter::EnemyCharacter => {
             target_is_alive
-                && skill_caster.team.id != skill_target.team.id
+                && (skill_caster.team.id != skill_target.team.id || is_clan_war_target)
                 && matches!(
                     skill_target.client_entity.entity_type,
                     ClientEntityType::Character
@@ -188,6 +222,149 @@ fn check_skill_target_filter(
     }
 }
 
+/// Skills which damage more than their primary target never hit the caster or
+/// its team, unless the target's clan is at war with the caster's clan.
+fn is_friendly_fire_target(
+    skill_caster: &SkillCasterQueryItem,
+    skill_target: &SkillTargetQueryItem,
+    clan_wars: &ClanWars,
+) -> bool {
+    if skill_caster.entity == skill_target.entity {
+        return true;
+    }
+
+    skill_caster.team.id == skill_target.team.id
+        && !clan_wars.is_at_war(
+            skill_caster
+                .clan_membership
+                .and_then(|clan_membership| clan_membership.clan()),
+            skill_target
+                .clan_membership
+                .and_then(|clan_membership| clan_membership.clan()),
+        )
+}
+
+/// Applies diminishing returns for an impairment, returning the multiplier for
+/// its duration or distance, or None when the target is immune.
+fn apply_movement_impairment(
+    skill_target: &mut SkillTargetQueryItem,
+    impairment_type: MovementImpairmentType,
+    now: Instant,
+) -> Option<f32> {
+    let multiplier = skill_target
+        .movement_impairment
+        .as_mut()
+        .map_or(1.0, |movement_impairment| {
+            movement_impairment.apply_diminishing_returns(impairment_type, now)
+        });
+    (multiplier > 0.0).then_some(multiplier)
+}
+
+/// Stops the target's current command, or only its movement when
+/// `only_movement` is set.
+fn stop_skill_target(
+    skill_system_parameters: &mut SkillSystemParameters,
+    skill_target: &mut SkillTargetQueryItem,
+    only_movement: bool,
+) {
+    let Some(command) = skill_target.command.as_mut() else {
+        return;
+    };
+    if command.is_dead() || (only_movement && !matches!(command.command, CommandData::Move { .. }))
+    {
+        return;
+    }
+
+    **command = Command::with_stop();
+    if !only_movement {
+        if let Some(next_command) = skill_target.next_command.as_mut() {
+            **next_command = NextCommand::default();
+        }
+    }
+
+    skill_system_parameters.server_messages.send_entity_message(
+        skill_target.client_entity,
+        ServerMessage::StopMoveEntity {
+            entity_id: skill_target.client_entity.id,
+            x: skill_target.position.position.x,
+            y: skill_target.position.position.y,
+            z: skill_target.position.position.z as u16,
+        },
+    );
+}
+
+fn apply_skill_movement_effects(
+    skill_system_parameters: &mut SkillSystemParameters,
+    skill_system_resources: &SkillSystemResources,
+    skill_caster: &SkillCasterQueryItem,
+    skill_target: &mut SkillTargetQueryItem,
+    skill_data: &SkillData,
+) {
+    let now = skill_system_resources.time.last_update().unwrap();
+
+    for skill_movement_effect in skill_system_resources
+        .game_config
+        .skill_movement_effects
+        .iter_skill_effects(skill_data)
+    {
+        match skill_movement_effect {
+            SkillMovementEffect::Stun { duration_secs } => {
+                let Some(multiplier) =
+                    apply_movement_impairment(skill_target, MovementImpairmentType::Stun, now)
+                else {
+                    continue;
+                };
+                if let Some(movement_impairment) = skill_target.movement_impairment.as_mut() {
+                    movement_impairment
+                        .stun(now + Duration::from_secs_f32(duration_secs * multiplier));
+                }
+                stop_skill_target(skill_system_parameters, skill_target, false);
+            }
+            SkillMovementEffect::Root { duration_secs } => {
+                let Some(multiplier) =
+                    apply_movement_impairment(skill_target, MovementImpairmentType::Root, now)
+                else {
+                    continue;
+                };
+                if let Some(movement_impairment) = skill_target.movement_impairment.as_mut() {
+                    movement_impairment
+                        .root(now + Duration::from_secs_f32(duration_secs * multiplier));
+                }
+                stop_skill_target(skill_system_parameters, skill_target, true);
+            }
+            SkillMovementEffect::Knockback { distance } => {
+                let Some(multiplier) =
+                    apply_movement_impairment(skill_target, MovementImpairmentType::Knockback, now)
+                else {
+                    continue;
+                };
+                skill_system_parameters
+                    .knockback_events
+                    .send(KnockbackEvent {
+                        entity: skill_target.entity,
+                        origin: skill_caster.position.position.xy(),
+                        distance: distance * multiplier,
+                    });
+            }
+        }
+    }
+}
+
+/// Returns true for skill types which damage their targets, these apply their
+/// movement effects with the damage rather than with their status effects.
+fn is_damage_skill(skill_data: &SkillData) -> bool {
+    matches!(
+        skill_data.skill_type,
+        SkillType::Immediate
+            | SkillType::EnforceWeapon
+            | SkillType::EnforceBullet
+            | SkillType::FireBullet
+            | SkillType::AreaTarget
+            | SkillType::SelfDamage
+            | SkillType::SelfAndTarget
+    )
+}
+
 fn apply_skill_status_effects_to_entity(
     skill_system_parameters: &mut SkillSystemParameters,
     skill_system_resources: &SkillSystemResources,
@@ -195,7 +372,12 @@ fn apply_skill_status_effects_to_entity(
     skill_target: &mut SkillTargetQueryItem,
     skill_data: &SkillData,
 ) -> Result<(), SkillCastError> {
-    if !check_skill_target_filter(skill_caster, skill_target, skill_data) {
+    if !check_skill_target_filter(
+        skill_caster,
+        skill_target,
+        skill_data,
+        &skill_system_resources.clan_wars,
+    ) {
         return Err(SkillCastError::InvalidTarget);
     }
 
@@ -208,6 +390,17 @@ fn apply_skill_status_effects_to_entity(
             });
     }
 
+    if !is_damage_skill(skill_data) {
+        apply_skill_movement_effects(
+            skill_system_parameters,
+            skill_system_resources,
+            skill_caster,
+            skill_target,
+            skill_data,
+        );
+    }
+
+    let now = skill_system_resources.time.last_update().unwrap();
     let mut effect_success = [false, false];
     for (effect_index, status_effect_data) in skill_data
         .status_effects
@@ -289,16 +482,17 @@ fn apply_skill_status_effects_to_entity(
             .status_effects
             .can_apply(status_effect_data, adjust_value)
         {
-            skill_target.status_effects.apply_status_effect(
-                status_effect_data,
-                skill_system_resources.time.last_update().unwrap()
-                    + skill_data.status_effect_duration,
-                adjust_value,
-            );
+            let mut duration = skill_data.status_effect_duration;
 
             match status_effect_data.status_effect_type {
                 StatusEffectType::Fainting | StatusEffectType::Sleep => {
-                    // TODO: Set current + next command to stop
+                    let Some(duration_multiplier) =
+                        apply_movement_impairment(skill_target, MovementImpairmentType::Stun, now)
+                    else {
+                        continue;
+                    };
+                    duration = duration.mul_f32(duration_multiplier);
+                    stop_skill_target(skill_system_parameters, skill_target, false);
                 }
                 StatusEffectType::Taunt => {
                     // TODO: Set current + next command to attack spell cast entity
@@ -306,6 +500,14 @@ fn apply_skill_status_effects_to_entity(
                 _ => {}
             }
 
+            if skill_target.status_effects.apply_status_effect(
+                status_effect_data,
+                now + duration,
+                adjust_value,
+            ) {
+                skill_target.status_effects.casters[status_effect_data.status_effect_type] =
+                    Some(skill_caster.entity);
+            }
             effect_success[effect_index] = true;
         }
     }
@@ -370,6 +572,125 @@ fn apply_skill_status_effects_to_entity(
     Ok(())
 }
 
+fn has_skill_line_of_sight(
+    skill_system_resources: &SkillSystemResources,
+    from: &Position,
+    to: &Position,
+) -> bool {
+    skill_system_resources
+        .zone_geometry
+        .has_line_of_sight(from.zone_id, from.position, to.position)
+}
+
+/// Returns the entities within `scope` of `skill_position`, when latency
+/// compensation is enabled for a player caster this includes entities which
+/// were recently in range. Entities which are blocked from `skill_position`
+/// by terrain or obstacles are excluded.
+fn get_area_of_effect_targets(
+    skill_system_resources: &SkillSystemResources,
+    client_entity_zone: &ClientEntityZone,
+    skill_target_query: &Query<SkillTargetQuery>,
+    skill_caster: &SkillCasterQueryItem,
+    skill_position: Vec2,
+    scope: f32,
+) -> Vec<Entity> {
+    let zone_id = skill_caster.position.zone_id;
+    let is_visible = |entity: Entity| {
+        skill_target_query
+            .get(entity)
+            .map_or(false, |skill_target| {
+                skill_system_resources.zone_geometry.has_line_of_sight(
+                    zone_id,
+                    skill_position.extend(skill_target.position.position.z),
+                    skill_target.position.position,
+                )
+            })
+    };
+
+    let now = skill_system_resources.time.last_update().unwrap();
+    let Some((latency_compensation, since)) = skill_system_resources
+        .game_config
+        .latency_compensation
+        .filter(|_| skill_caster.game_client.is_some())
+        .and_then(|latency_compensation| {
+            Some((latency_compensation, now.checked_sub(latency_compensation)?))
+        })
+    else {
+        return client_entity_zone
+            .iter_entities_within_distance(skill_position, scope)
+            .map(|(entity, _)| entity)
+            .filter(|entity| is_visible(*entity))
+            .collect();
+    };
+
+    let search_distance =
+        scope + latency_compensation.as_secs_f32() * LATENCY_COMPENSATION_MAX_MOVE_SPEED;
+    client_entity_zone
+        .iter_entities_within_distance(skill_position, search_distance)
+        .filter(|(entity, _)| {
+            skill_target_query
+                .get(*entity)
+                .map_or(false, |skill_target| {
+                    let distance = skill_target.position_history.map_or_else(
+                        || skill_position.distance(skill_target.position.position.xy()),
+                        |position_history| {
+                            position_history.get_min_distance_xy(
+                                skill_position,
+                                skill_target.position.position,
+                                since,
+                            )
+                        },
+                    );
+                    distance <= scope
+                })
+        })
+        .map(|(entity, _)| entity)
+        .filter(|entity| is_visible(*entity))
+        .collect()
+}
+
+/// Returns the centre of an area of effect skill, a target position chosen by
+/// the client must be within the skill's cast range and line of sight.
+fn get_area_of_effect_position(
+    skill_system_resources: &SkillSystemResources,
+    skill_caster: &SkillCasterQueryItem,
+    skill_target: &SkillEventTarget,
+    skill_data: &SkillData,
+    skill_target_query: &Query<SkillTargetQuery>,
+) -> Result<Vec2, SkillCastError> {
+    let skill_position = match *skill_target {
+        SkillEventTarget::Entity(target_entity) => skill_target_query
+            .get(target_entity)
+            .map(|skill_target| skill_target.position.position.xy())
+            .map_err(|_| SkillCastError::InvalidTarget)?,
+        SkillEventTarget::Position(position) => {
+            let cast_range = if skill_data.cast_range > 0 {
+                skill_data.cast_range as f32
+            } else {
+                skill_caster.ability_values.get_attack_range() as f32
+            };
+
+            if skill_caster.position.position.xy().distance(position)
+                > cast_range + SKILL_CAST_RANGE_TOLERANCE
+            {
+                return Err(SkillCastError::InvalidTarget);
+            }
+
+            position
+        }
+    };
+
+    if !skill_system_resources.zone_geometry.has_line_of_sight(
+        skill_caster.position.zone_id,
+        skill_caster.position.position,
+        skill_position.extend(skill_caster.position.position.z),
+    ) {
+        return Err(SkillCastError::InvalidTarget);
+    }
+
+    Ok(skill_position)
+}
+
 fn apply_skill_status_effects(
     skill_system_parameters: &mut SkillSystemParameters,
     skill_system_resources: &SkillSystemResources,
@@ -385,21 +706,22 @@ fn apply_skill_status_effects(
             .get_zone(skill_caster.position.zone_id)
             .ok_or(SkillCastError::InvalidTarget)?;
 
-        let skill_position = match *skill_target {
-            SkillEventTarget::Entity(target_entity) => {
-                if let Ok(skill_target) = skill_target_query.get_mut(target_entity) {
-                    Some(skill_target.position.position.xy())
-                } else {
-                    None
-                }
-            }
-            SkillEventTarget::Position(position) => Some(position),
-        }
-        .ok_or(SkillCastError::InvalidTarget)?;
-
-        for (target_entity, _) in client_entity_zone
-            .iter_entities_within_distance(skill_position, skill_data.scope as f32)
-        {
+        let skill_position = get_area_of_effect_position(
+            skill_system_resources,
+            skill_caster,
+            skill_target,
+            skill_data,
+            skill_target_query,
+        )?;
+
+        for target_entity in get_area_of_effect_targets(
+            skill_system_resources,
+            client_entity_zone,
+            skill_target_query,
+            skill_caster,
+            skill_position,
+            skill_data.scope as f32,
+        ) {
             if let Ok(mut skill_target) = skill_target_query.get_mut(target_entity) {
                 apply_skill_status_effects_to_entity(
                     skill_system_parameters,
@@ -414,7 +736,18 @@ fn apply_skill_status_effects(
 
         Ok(())
     } else if let SkillEventTarget::Entity(target_entity) = *skill_target {
-        if let Ok(mut skill_target) = skill_target_query.get_mut(target_entity) {
+        if let Some(mut skill_target) =
+            skill_target_query
+                .get_mut(target_entity)
+                .ok()
+                .filter(|skill_target| {
+                    has_skill_line_of_sight(
+                        skill_system_resources,
+                        skill_caster.position,
+                        skill_target.position,
+                    )
+                })
+        {
             apply_skill_status_effects_to_entity(
                 skill_system_parameters,
                 skill_system_resources,
@@ -438,13 +771,19 @@ fn apply_skill_damage_to_entity(
     skill_caster: &SkillCasterQueryItem,
     skill_target: &mut SkillTargetQueryItem,
     skill_data: &SkillData,
+    damage_multiplier: f32,
 ) -> Result<Damage, SkillCastError> {
-    if !check_skill_target_filter(skill_caster, skill_target, skill_data) {
+    if !check_skill_target_filter(
+        skill_caster,
+        skill_target,
+        skill_data,
+        &skill_system_resources.clan_wars,
+    ) {
         return Err(SkillCastError::InvalidTarget);
     }
 
     // TODO: Get hit count from skill action motion
-    let damage = skill_system_resources
+    let mut damage = skill_system_resources
         .game_data
         .ability_value_calculator
         .calculate_skill_damage(
@@ -453,6 +792,9 @@ fn apply_skill_damage_to_entity(
             skill_data,
             1,
         );
+    if damage_multiplier < 1.0 {
+        damage.amount = (damage.amount as f32 * damage_multiplier) as u32;
+    }
 
     skill_system_parameters
         .damage_events
@@ -461,9 +803,18 @@ fn apply_skill_damage_to_entity(
             defender: skill_target.entity,
             damage,
             skill_id: skill_data.id,
+            element: skill_data.element,
             attacker_intelligence: skill_caster.ability_values.get_intelligence(),
         });
 
+    apply_skill_movement_effects(
+        skill_system_parameters,
+        skill_system_resources,
+        skill_caster,
+        skill_target,
+        skill_data,
+    );
+
     Ok(damage)
 }
 
@@ -482,28 +833,38 @@ fn apply_skill_damage(
             .get_zone(skill_caster.position.zone_id)
             .ok_or(SkillCastError::InvalidTarget)?;
 
-        let skill_position = match *skill_target {
-            SkillEventTarget::Entity(target_entity) => {
-                if let Ok(skill_target) = skill_target_query.get_mut(target_entity) {
-                    Some(skill_target.position.position.xy())
-                } else {
-                    None
+        let skill_position = get_area_of_effect_position(
+            skill_system_resources,
+            skill_caster,
+            skill_target,
+            skill_data,
+            skill_target_query,
+        )?;
+
+        for target_entity in get_area_of_effect_targets(
+            skill_system_resources,
+            client_entity_zone,
+            skill_target_query,
+            skill_caster,
+            skill_position,
+            skill_data.scope as f32,
+        ) {
+            if let Ok(mut skill_target) = skill_target_query.get_mut(target_entity) {
+                if is_friendly_fire_target(
+                    skill_caster,
+                    &skill_target,
+                    &skill_system_resources.clan_wars,
+                ) {
+                    continue;
                 }
-            }
-            SkillEventTarget::Position(position) => Some(position),
-        }
-        .ok_or(SkillCastError::InvalidTarget)?;
 
-        for (target_entity, _) in client_entity_zone
-            .iter_entities_within_distance(skill_position, skill_data.scope as f32)
-        {
-            if let Ok(mut skill_target) = skill_target_query.get_mut(target_entity) {
                 apply_skill_damage_to_entity(
                     skill_system_parameters,
                     skill_system_resources,
                     skill_caster,
                     &mut skill_target,
                     skill_data,
+                    1.0,
                 )
                 .ok();
             }
@@ -512,15 +873,47 @@ fn apply_skill_damage(
         Ok(())
     } else if let SkillEventTarget::Entity(target_entity) = *skill_target {
         // Apply directly to entity
-        if let Ok(mut skill_target) = skill_target_query.get_mut(target_entity) {
-            apply_skill_damage_to_entity(
+        if let Some(mut skill_target) =
+            skill_target_query
+                .get_mut(target_entity)
+                .ok()
+                .filter(|skill_target| {
+                    has_skill_line_of_sight(
+                        skill_system_resources,
+                        skill_caster.position,
+                        skill_target.position,
+                    )
+                })
+        {
+            let primary_target_hit = apply_skill_damage_to_entity(
                 skill_system_parameters,
                 skill_system_resources,
                 skill_caster,
                 &mut skill_target,
                 skill_data,
+                1.0,
             )
-            .ok();
+            .is_ok();
+
+            if let (true, Some(skill_chain), Some(client_entity_zone)) = (
+                primary_target_hit,
+                skill_system_resources
+                    .game_config
+                    .skill_chains
+                    .get_skill_chain(skill_data),
+                client_entity_list.get_zone(skill_caster.position.zone_id),
+            ) {
+                apply_skill_chain_damage(
+                    skill_system_parameters,
+                    skill_system_resources,
+                    client_entity_zone,
+                    skill_caster,
+                    target_entity,
+                    skill_data,
+                    skill_chain,
+                    skill_target_query,
+                );
+            }
             Ok(())
         } else {
             Err(SkillCastError::InvalidTarget)
@@ -540,6 +933,128 @@ fn apply_skill_damage(
     result
 }
 
+/// Damages the further targets of a chain or pierce skill after its primary
+/// target was hit, each target hit takes less damage than the one before it.
+fn apply_skill_chain_damage(
+    skill_system_parameters: &mut SkillSystemParameters,
+    skill_system_resources: &SkillSystemResources,
+    client_entity_zone: &ClientEntityZone,
+    skill_caster: &SkillCasterQueryItem,
+    primary_target: Entity,
+    skill_data: &SkillData,
+    skill_chain: &SkillChainConfig,
+    skill_target_query: &mut Query<SkillTargetQuery>,
+) {
+    let Ok(primary_target_position) = skill_target_query
+        .get(primary_target)
+        .map(|skill_target| skill_target.position.position)
+    else {
+        return;
+    };
+    let zone_id = skill_caster.position.zone_id;
+    let mut hit_entities = vec![primary_target];
+
+    let mut try_hit_target = |hit_entities: &[Entity], target_entity: Entity| -> bool {
+        let Ok(mut skill_target) = skill_target_query.get_mut(target_entity) else {
+            return false;
+        };
+        if is_friendly_fire_target(
+            skill_caster,
+            &skill_target,
+            &skill_system_resources.clan_wars,
+        ) {
+            return false;
+        }
+
+        apply_skill_damage_to_entity(
+            skill_system_parameters,
+            skill_system_resources,
+            skill_caster,
+            &mut skill_target,
+            skill_data,
+            skill_chain.falloff.powi(hit_entities.len() as i32),
+        )
+        .is_ok()
+    };
+
+    match skill_chain.chain_type {
+        SkillChainType::Chain => {
+            let mut chain_position = primary_target_position;
+            while hit_entities.len() < skill_chain.max_targets {
+                let mut candidates: Vec<(Entity, Vec3)> = client_entity_zone
+                    .iter_entities_within_distance(chain_position.xy(), skill_chain.range)
+                    .filter(|(entity, position)| {
+                        !hit_entities.contains(entity)
+                            && skill_system_resources.zone_geometry.has_line_of_sight(
+                                zone_id,
+                                chain_position,
+                                *position,
+                            )
+                    })
+                    .collect();
+                candidates.sort_by(|(_, a), (_, b)| {
+                    a.xy()
+                        .distance_squared(chain_position.xy())
+                        .total_cmp(&b.xy().distance_squared(chain_position.xy()))
+                });
+
+                let Some((target_entity, target_position)) = candidates
+                    .into_iter()
+                    .find(|(entity, _)| try_hit_target(&hit_entities, *entity))
+                else {
+                    break;
+                };
+                hit_entities.push(target_entity);
+                chain_position = target_position;
+            }
+        }
+        SkillChainType::Pierce => {
+            let origin = skill_caster.position.position.xy();
+            let direction = (primary_target_position.xy() - origin).normalize_or_zero();
+            if direction == Vec2::ZERO {
+                return;
+            }
+
+            // Continue through the primary target up to the skill's cast range
+            let length = origin
+                .distance(primary_target_position.xy())
+                .max(skill_data.cast_range as f32);
+            let centre = origin + direction * (length / 2.0);
+
+            let mut candidates: Vec<(Entity, f32)> = client_entity_zone
+                .iter_entities_within_distance(centre, length / 2.0 + skill_chain.range)
+                .filter(|(entity, _)| !hit_entities.contains(entity))
+                .filter_map(|(entity, position)| {
+                    let offset = position.xy() - origin;
+                    let distance_along = offset.dot(direction);
+                    if !(0.0..=length).contains(&distance_along)
+                        || offset.distance(direction * distance_along) > skill_chain.range
+                        || !skill_system_resources.zone_geometry.has_line_of_sight(
+                            zone_id,
+                            skill_caster.position.position,
+                            position,
+                        )
+                    {
+                        return None;
+                    }
+                    Some((entity, distance_along))
+                })
+                .collect();
+            candidates.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            for (target_entity, _) in candidates {
+                if hit_entities.len() >= skill_chain.max_targets {
+                    break;
+                }
+
+                if try_hit_target(&hit_entities, target_entity) {
+                    hit_entities.push(target_entity);
+                }
+            }
+        }
+    }
+}
+
 fn subtract_skill_use_cost(
     skill_system_resources: &SkillSystemResources,
     skill_caster_query: &mut Query<SkillCasterQuery>,
@@ -751,7 +1266,16 @@ pub fn skill_effect_system(
                 SkillType::SelfAndTarget => {
                     // Only applies status effect if damage > 0
                     if let SkillEventTarget::Entity(target_entity) = skill_target {
-                        if let Ok(mut skill_target_data) = skill_target_query.get_mut(target_entity)
+                        if let Some(mut skill_target_data) = skill_target_query
+                            .get_mut(target_entity)
+                            .ok()
+                            .filter(|skill_target_data| {
+                                has_skill_line_of_sight(
+                                    &skill_system_resources,
+                                    skill_caster.position,
+                                    skill_target_data.position,
+                                )
+                            })
                         {
                             match apply_skill_damage_to_entity(
                                 &mut skill_system_parameters,
@@ -759,6 +1283,7 @@ pub fn skill_effect_system(
                                 &skill_caster,
                                 &mut skill_target_data,
                                 skill_data,
+                                1.0,
                             ) {
                                 Ok(damage) if damage.amount > 0 => apply_skill_status_effects(
                                     &mut skill_system_parameters,
@@ -795,6 +1320,7 @@ pub fn skill_effect_system(
                             skill_caster.team.clone(),
                             Some((skill_caster.entity, skill_caster.level)),
                             Some(skill_data.level as i32),
+                            None,
                         ) {
                             // Apply status effect to decrease summon's life over time
                             if let Some(status_effect_data) = skill_system_resources
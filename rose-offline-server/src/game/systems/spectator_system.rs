@@ -0,0 +1,66 @@
+use bevy::{
+    ecs::prelude::{Commands, Entity, EventWriter, Query},
+    math::Vec3Swizzles,
+};
+
+use crate::game::{
+    components::{CharacterInfo, Command, CommandData, NextCommand, Position, Spectator},
+    events::TeleportEvent,
+};
+
+// Spectators start following again once they are further than this from their target
+const SPECTATOR_FOLLOW_DISTANCE: f32 = 500.0;
+
+// Spectators further than this from their target are teleported instead of moving
+const SPECTATOR_TELEPORT_DISTANCE: f32 = 5000.0;
+
+pub fn spectator_system(
+    mut commands: Commands,
+    query_spectator: Query<(Entity, &Spectator, &Position, &Command)>,
+    query_target: Query<(Entity, &CharacterInfo, &Position)>,
+    mut teleport_events: EventWriter<TeleportEvent>,
+) {
+    for (entity, spectator, position, command) in query_spectator.iter() {
+        let Some(follow_character) = spectator.follow_character.as_ref() else {
+            continue;
+        };
+
+        let Some((target_entity, _, target_position)) =
+            query_target
+                .iter()
+                .find(|(target_entity, character_info, _)| {
+                    *target_entity != entity
+                        && character_info.name.eq_ignore_ascii_case(follow_character)
+                })
+        else {
+            continue;
+        };
+
+        if target_position.zone_id != position.zone_id {
+            teleport_events.send(TeleportEvent {
+                entity,
+                position: target_position.clone(),
+            });
+            continue;
+        }
+
+        let distance = position
+            .position
+            .xy()
+            .distance(target_position.position.xy());
+        if distance > SPECTATOR_TELEPORT_DISTANCE {
+            teleport_events.send(TeleportEvent {
+                entity,
+                position: target_position.clone(),
+            });
+        } else if distance > SPECTATOR_FOLLOW_DISTANCE
+            && !matches!(command.command, CommandData::Move { target: Some(target), .. } if target == target_entity)
+        {
+            commands.entity(entity).insert(NextCommand::with_move(
+                target_position.position,
+                Some(target_entity),
+                None,
+            ));
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use bevy::prelude::Commands;
+use log::error;
 
 use rose_data::QuestTriggerHash;
 use rose_game_common::components::ClanUniqueId;
 
 use crate::game::{
-    components::{Clan, ClanMember, Level},
-    storage::{character::CharacterStorage, clan::ClanStorage},
+    components::{Clan, ClanBank, ClanMember, Level},
+    storage::{character::CharacterStorage, clan::ClanStorage, clan_bank::ClanBankStorage},
 };
 
 pub fn startup_clans_system(mut commands: Commands) {
@@ -25,17 +26,34 @@ pub fn startup_clans_system(mut commands: Commands) {
             }
         }
 
-        commands.spawn(Clan {
-            unique_id: ClanUniqueId::new(QuestTriggerHash::from(clan_storage.name.as_str()).hash)
+        let clan_bank = match ClanBankStorage::try_load(&clan_storage.name) {
+            Ok(clan_bank_storage) => ClanBank::from(clan_bank_storage),
+            Err(error) => {
+                error!(
+                    "Failed to load bank for clan {} with error {:?}",
+                    &clan_storage.name, error
+                );
+                ClanBank::default()
+            }
+        };
+
+        commands.spawn((
+            Clan {
+                unique_id: ClanUniqueId::new(
+                    QuestTriggerHash::from(clan_storage.name.as_str()).hash,
+                )
                 .unwrap(),
-            name: clan_storage.name,
-            description: clan_storage.description,
-            mark: clan_storage.mark,
-            money: clan_storage.money,
-            points: clan_storage.points,
-            level: clan_storage.level,
-            skills: clan_storage.skills,
-            members,
-        });
+                name: clan_storage.name,
+                description: clan_storage.description,
+                notice: clan_storage.notice,
+                mark: clan_storage.mark,
+                money: clan_storage.money,
+                points: clan_storage.points,
+                level: clan_storage.level,
+                skills: clan_storage.skills,
+                members,
+            },
+            clan_bank,
+        ));
     }
 }
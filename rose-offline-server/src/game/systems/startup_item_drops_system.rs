@@ -0,0 +1,71 @@
+use std::time::Instant;
+
+use bevy::ecs::prelude::{Commands, Res, ResMut};
+use chrono::Utc;
+use log::{error, info, warn};
+
+use crate::game::{
+    bundles::{client_entity_join_zone, ItemDropBundle},
+    components::{ClientEntityType, EntityExpireTime, ItemDrop, PersistentItemDrop},
+    resources::{ClientEntityList, GameConfig},
+    storage::item_drop::ZoneItemDropStorage,
+};
+
+pub fn startup_item_drops_system(
+    mut commands: Commands,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    game_config: Res<GameConfig>,
+) {
+    if game_config.item_drops.persist.is_none() {
+        return;
+    }
+
+    let now = Instant::now();
+    let utc_now = Utc::now();
+    let zones = ZoneItemDropStorage::try_load_all(utc_now).unwrap_or_else(|error| {
+        error!("Failed to load item drops: {:?}", error);
+        Vec::new()
+    });
+
+    let mut num_restored = 0;
+    for zone in zones {
+        for item_drop in zone.item_drops {
+            let expire_time = now
+                + (item_drop.expire_time - utc_now)
+                    .to_std()
+                    .unwrap_or_default();
+            let entity = commands
+                .spawn((
+                    ItemDropBundle {
+                        drop: ItemDrop::with_dropped_item(item_drop.item),
+                        position: item_drop.position.clone(),
+                        entity_expire_time: EntityExpireTime::new(expire_time),
+                    },
+                    PersistentItemDrop,
+                ))
+                .id();
+
+            if let Err(error) = client_entity_join_zone(
+                &mut commands,
+                &mut client_entity_list,
+                entity,
+                ClientEntityType::ItemDrop,
+                &item_drop.position,
+            ) {
+                warn!(
+                    "Failed to restore item drop into zone {} with error {:?}",
+                    zone.zone_id.get(),
+                    error
+                );
+                commands.entity(entity).despawn();
+                continue;
+            }
+
+            num_restored += 1;
+        }
+    }
+
+    if num_restored > 0 {
+        info!("Restored {} item drops from storage", num_restored);
+    }
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::Commands;
+
+use crate::game::{components::Party, storage::party::PartyStorage};
+
+pub fn startup_parties_system(mut commands: Commands) {
+    let parties = PartyStorage::try_load_party_list().unwrap_or_default();
+    for party_storage in parties {
+        commands.spawn(Party::from(party_storage));
+    }
+}
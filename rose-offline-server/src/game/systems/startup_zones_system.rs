@@ -152,4 +152,8 @@ pub fn startup_zones_system(
             }
         }
     }
+
+    for zone_rules in game_config.zone_rules.zones.iter() {
+        zone_list.set_rules(zone_rules.zone, zone_rules.clone());
+    }
 }
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::prelude::{EventReader, Query, Res, ResMut, With},
+    time::Time,
+};
+
+use crate::game::{
+    components::{Account, CharacterInfo, ExperiencePoints, Inventory, Level, Statistics},
+    events::StatisticsEvent,
+    resources::LeaderboardCache,
+    storage::leaderboard::LeaderboardCharacter,
+};
+
+pub fn statistics_system(
+    mut statistics_events: EventReader<StatisticsEvent>,
+    mut query_statistics: Query<&mut Statistics>,
+) {
+    for event in statistics_events.iter() {
+        match *event {
+            StatisticsEvent::MonsterKill { entity, npc_id } => {
+                if let Ok(mut statistics) = query_statistics.get_mut(entity) {
+                    statistics.add_monster_kill(npc_id);
+                }
+            }
+            StatisticsEvent::PvpKill { entity } => {
+                if let Ok(mut statistics) = query_statistics.get_mut(entity) {
+                    statistics.pvp_kills = statistics.pvp_kills.saturating_add(1);
+                }
+            }
+            StatisticsEvent::Death { entity } => {
+                if let Ok(mut statistics) = query_statistics.get_mut(entity) {
+                    statistics.deaths = statistics.deaths.saturating_add(1);
+                }
+            }
+            StatisticsEvent::EarnMoney { entity, amount } => {
+                if amount <= 0 {
+                    continue;
+                }
+
+                if let Ok(mut statistics) = query_statistics.get_mut(entity) {
+                    statistics.money_earned = statistics.money_earned.saturating_add(amount as u64);
+                }
+            }
+        }
+    }
+}
+
+pub fn leaderboard_system(
+    query_characters: Query<
+        (
+            &CharacterInfo,
+            &Level,
+            &ExperiencePoints,
+            &Inventory,
+            &Statistics,
+        ),
+        With<Account>,
+    >,
+    leaderboard_cache: Option<ResMut<LeaderboardCache>>,
+    time: Res<Time>,
+) {
+    let Some(mut leaderboard_cache) = leaderboard_cache else {
+        return;
+    };
+    let Some(now) = time.last_update() else {
+        return;
+    };
+
+    if !leaderboard_cache.update(now) {
+        return;
+    }
+
+    let online: HashMap<String, LeaderboardCharacter> = query_characters
+        .iter()
+        .map(
+            |(character_info, level, experience_points, inventory, statistics)| {
+                (
+                    character_info.name.clone(),
+                    LeaderboardCharacter {
+                        name: character_info.name.clone(),
+                        level: level.level,
+                        experience_points: experience_points.xp,
+                        money: inventory.money.0,
+                        pvp_kills: statistics.pvp_kills,
+                    },
+                )
+            },
+        )
+        .collect();
+    leaderboard_cache.start_refresh(now, online);
+}
@@ -1,8 +1,9 @@
 use bevy::{
     ecs::{
+        change_detection::DetectChangesMut,
         entity::Entity,
         event::EventWriter,
-        prelude::{Query, Res, ResMut},
+        prelude::{Query, Res, ResMut, Without},
     },
     time::Time,
 };
@@ -14,7 +15,7 @@ use rose_game_common::data::Damage;
 
 use crate::game::{
     components::{
-        AbilityValues, ActiveStatusEffectRegen, ClientEntity, HealthPoints, ManaPoints,
+        AbilityValues, ActiveStatusEffectRegen, ClientEntity, Dead, HealthPoints, ManaPoints,
         StatusEffects, StatusEffectsRegen,
     },
     events::DamageEvent,
@@ -46,11 +47,26 @@ pub fn status_effect_system(
         &mut StatusEffects,
         &mut StatusEffectsRegen,
     )>,
+    mut transit_query: Query<&mut StatusEffects, Without<ClientEntity>>,
+    caster_query: Query<&ClientEntity, Without<Dead>>,
     mut damage_events: EventWriter<DamageEvent>,
     mut server_messages: ResMut<ServerMessages>,
     game_data: Res<GameData>,
     time: Res<Time>,
 ) {
+    // Entities which are changing zone are not updated below, so pause their status
+    // effects to keep the remaining durations for when they join the next zone
+    for mut status_effects in transit_query.iter_mut() {
+        for expire_time in status_effects
+            .bypass_change_detection()
+            .expire_times
+            .values_mut()
+            .flatten()
+        {
+            *expire_time += time.delta();
+        }
+    }
+
     for (
         entity,
         client_entity,
@@ -90,6 +106,16 @@ pub fn status_effect_system(
                             if health_points.hp == max_hp {
                                 expired_status_effects[status_effect_type] = true;
                             }
+                        } else if apply_per_second_effect && health_points.hp > 0 {
+                            // Heal over time from a skill, any overheal is lost
+                            if let Some(data) =
+                                game_data.status_effects.get_status_effect(status_effect.id)
+                            {
+                                health_points.hp = i32::min(
+                                    health_points.hp + data.apply_per_second_value,
+                                    ability_values.get_max_health(),
+                                );
+                            }
                         }
                     }
                     StatusEffectType::IncreaseMp => {
@@ -109,6 +135,16 @@ pub fn status_effect_system(
                                     expired_status_effects[status_effect_type] = true;
                                 }
                             }
+                        } else if apply_per_second_effect && health_points.hp > 0 {
+                            if let (Some(mana_points), Some(data)) = (
+                                mana_points.as_mut(),
+                                game_data.status_effects.get_status_effect(status_effect.id),
+                            ) {
+                                mana_points.mp = i32::min(
+                                    mana_points.mp + data.apply_per_second_value,
+                                    ability_values.get_max_mana(),
+                                );
+                            }
                         }
                     }
                     StatusEffectType::Poisoned => {
@@ -116,8 +152,26 @@ pub fn status_effect_system(
                             if let Some(data) =
                                 game_data.status_effects.get_status_effect(status_effect.id)
                             {
-                                health_points.hp =
-                                    i32::max(health_points.hp - data.apply_per_second_value, 1);
+                                // Attribute the damage to whoever applied the poison, so they
+                                // receive the aggro, experience and kill credit, unless they
+                                // have since died, logged out or left the zone
+                                let attacker = status_effects.casters[status_effect_type]
+                                    .filter(|caster| {
+                                        caster_query.get(*caster).map_or(false, |caster| {
+                                            caster.zone_id == client_entity.zone_id
+                                        })
+                                    })
+                                    .unwrap_or(entity);
+                                damage_events.send(DamageEvent::Immediate {
+                                    attacker,
+                                    defender: entity,
+                                    damage: Damage {
+                                        amount: data.apply_per_second_value as u32,
+                                        is_critical: false,
+                                        apply_hit_stun: false,
+                                        is_blocked: false,
+                                    },
+                                });
                             }
                         }
                     }
@@ -137,6 +191,7 @@ pub fn status_effect_system(
                                             amount: data.apply_per_second_value as u32,
                                             is_critical: false,
                                             apply_hit_stun: false,
+                                            is_blocked: false,
                                         },
                                     });
                                 }
@@ -182,6 +237,7 @@ pub fn status_effect_system(
             {
                 status_effects.active[expired_status_effect_type] = None;
                 status_effects.expire_times[expired_status_effect_type] = None;
+                status_effects.casters[expired_status_effect_type] = None;
                 status_effects_regen.regens[expired_status_effect_type] = None;
 
                 match expired_status_effect_type {
@@ -0,0 +1,22 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    app::AppExit,
+    ecs::prelude::{EventReader, ResMut},
+};
+
+use crate::game::resources::StorageService;
+
+/// How long to keep retrying queued storage writes when the server exits
+const STORAGE_FLUSH_ON_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn storage_service_system(
+    mut storage_service: ResMut<StorageService>,
+    mut app_exit_events: EventReader<AppExit>,
+) {
+    storage_service.update(Instant::now());
+
+    if app_exit_events.iter().next().is_some() {
+        storage_service.flush(STORAGE_FLUSH_ON_EXIT_TIMEOUT);
+    }
+}
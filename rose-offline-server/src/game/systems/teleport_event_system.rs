@@ -0,0 +1,195 @@
+use bevy::{
+    ecs::prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut, Without},
+    utils::HashSet,
+};
+
+use crate::game::{
+    bundles::{client_entity_leave_zone, client_entity_teleport_zone},
+    components::{
+        AbilityValues, CharacterInfo, ClientEntity, ClientEntitySector, Dead, GameClient,
+        HealthPoints, Level, Npc, Owner, Party, PartyMapMarkerHidden, PartyMembership, Position,
+        SpawnOrigin, StatusEffects, StatusEffectsRegen, ZoneTransition, ZoneTransitionSummon,
+    },
+    events::{SaveEvent, TeleportEvent},
+    messages::server::ServerMessage,
+    resources::{ClientEntityList, GameConfig, ZoneList},
+    systems::create_party_member_map_marker,
+};
+
+/// Performs all zone changes in one place in the schedule, so other systems
+/// never see an entity which has left its zone but not yet joined the next.
+pub fn teleport_event_system(
+    mut commands: Commands,
+    query: Query<(
+        &ClientEntity,
+        &ClientEntitySector,
+        &Position,
+        Option<&GameClient>,
+        Option<&CharacterInfo>,
+        Option<&PartyMembership>,
+        Option<&PartyMapMarkerHidden>,
+        Option<&Level>,
+    )>,
+    summon_query: Query<
+        (
+            Entity,
+            &Owner,
+            &SpawnOrigin,
+            &Npc,
+            &AbilityValues,
+            &HealthPoints,
+            &StatusEffects,
+            &StatusEffectsRegen,
+            &ClientEntity,
+            &ClientEntitySector,
+            &Position,
+        ),
+        Without<Dead>,
+    >,
+    party_query: Query<&Party>,
+    party_member_query: Query<&GameClient>,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    game_config: Res<GameConfig>,
+    zone_list: Res<ZoneList>,
+    mut teleport_events: EventReader<TeleportEvent>,
+    mut save_events: EventWriter<SaveEvent>,
+) {
+    let mut teleported_entities: HashSet<Entity> = HashSet::new();
+
+    for TeleportEvent { entity, position } in teleport_events.iter() {
+        // Only the first teleport for an entity each frame is used, as it has
+        // already left its zone when handling any later teleport
+        if !teleported_entities.insert(*entity) {
+            continue;
+        }
+
+        let Ok((
+            client_entity,
+            client_entity_sector,
+            previous_position,
+            game_client,
+            character_info,
+            party_membership,
+            party_map_marker_hidden,
+            level,
+        )) = query.get(*entity)
+        else {
+            continue;
+        };
+
+        let min_level = zone_list.get_min_level(position.zone_id);
+        if position.zone_id != previous_position.zone_id
+            && level.map_or(false, |level| level.level < min_level)
+        {
+            if let Some(game_client) = game_client {
+                game_client
+                    .server_message_tx
+                    .send(ServerMessage::Whisper {
+                        from: String::from("SERVER"),
+                        text: format!("You must be level {} to enter this zone", min_level),
+                    })
+                    .ok();
+            }
+            continue;
+        }
+
+        client_entity_teleport_zone(
+            &mut commands,
+            &mut client_entity_list,
+            *entity,
+            client_entity,
+            client_entity_sector,
+            previous_position,
+            position.clone(),
+            game_client,
+        );
+
+        // Summons leave with their owner and are spawned again next to it
+        // once it has joined the new zone
+        if character_info.is_some() && position.zone_id != previous_position.zone_id {
+            let mut summons = Vec::new();
+            for (
+                summon_entity,
+                owner,
+                spawn_origin,
+                npc,
+                ability_values,
+                health_points,
+                status_effects,
+                status_effects_regen,
+                summon_client_entity,
+                summon_client_entity_sector,
+                summon_position,
+            ) in summon_query.iter()
+            {
+                if owner.entity != *entity || !matches!(spawn_origin, SpawnOrigin::Summoned(..)) {
+                    continue;
+                }
+
+                summons.push(ZoneTransitionSummon {
+                    npc_id: npc.id,
+                    summon_skill_level: ability_values.summon_skill_level,
+                    health_points: *health_points,
+                    status_effects: status_effects.clone(),
+                    status_effects_regen: status_effects_regen.clone(),
+                });
+                client_entity_leave_zone(
+                    &mut commands,
+                    &mut client_entity_list,
+                    summon_entity,
+                    summon_client_entity,
+                    summon_client_entity_sector,
+                    summon_position,
+                );
+                commands.entity(summon_entity).despawn();
+            }
+
+            if !summons.is_empty() {
+                commands.entity(*entity).insert(ZoneTransition {
+                    zone_id: position.zone_id,
+                    summons,
+                });
+            }
+        }
+
+        if game_client.is_some() {
+            // Make sure the new position is saved, in case the client
+            // disconnects before it finishes joining the new zone
+            save_events.send(SaveEvent::Character {
+                entity: *entity,
+                remove_after_save: false,
+            });
+        }
+
+        // Update the map marker for party members without waiting for the
+        // next periodic update
+        if game_config.party_map_marker_interval.is_none() {
+            continue;
+        }
+
+        if let (Some(character_info), Some(party), None) = (
+            character_info,
+            party_membership
+                .and_then(|party_membership| party_membership.party)
+                .and_then(|party_entity| party_query.get(party_entity).ok()),
+            party_map_marker_hidden,
+        ) {
+            let marker = create_party_member_map_marker(character_info, position);
+
+            for party_member_game_client in party
+                .members
+                .iter()
+                .filter_map(|party_member| party_member.get_entity())
+                .filter(|party_member_entity| party_member_entity != entity)
+                .filter_map(|party_member_entity| party_member_query.get(party_member_entity).ok())
+            {
+                party_member_game_client
+                    .server_message_tx
+                    .send(ServerMessage::PartyMemberMapMarkers {
+                        markers: vec![marker.clone()],
+                    })
+                    .ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        prelude::{Entity, EventReader, EventWriter, Query, Res, With},
+        query::WorldQuery,
+    },
+    math::Vec3Swizzles,
+};
+
+use rose_data::WarpGateId;
+
+use crate::game::{
+    components::{
+        CharacterInfo, ClientEntity, GameClient, Inventory, Level, Position, QuestState,
+        TeleportGate,
+    },
+    events::{TeleportEvent, WarpGateEvent},
+    messages::server::{ServerMessage, WarpGateError},
+    resources::{GameConfig, GameData},
+};
+
+// Warp gates are large objects which the client uses once the character
+// touches the object's mesh, so allow some distance from its origin
+pub const WARP_GATE_MAX_DISTANCE: f32 = 2000.0;
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct TeleportCharacterQuery<'w> {
+    entity: Entity,
+    position: &'w Position,
+    level: &'w Level,
+    quest_state: &'w QuestState,
+    inventory: &'w mut Inventory,
+    game_client: Option<&'w GameClient>,
+}
+
+fn use_warp_gate(
+    game_config: &GameConfig,
+    game_data: &GameData,
+    character: &mut TeleportCharacterQueryItem,
+    warp_gate_id: WarpGateId,
+) -> Result<Position, WarpGateError> {
+    let gate_config = game_config.teleport_gates.get_gate(warp_gate_id);
+
+    let is_in_range = if let Some(source) = gate_config.and_then(|gate| gate.source.as_ref()) {
+        source.zone == character.position.zone_id
+            && source
+                .position
+                .xy()
+                .distance(character.position.position.xy())
+                <= source.radius
+    } else {
+        game_data
+            .zones
+            .get_zone(character.position.zone_id)
+            .map_or(false, |zone_data| {
+                zone_data.warp_gates.iter().any(|warp_gate| {
+                    warp_gate.warp_gate_id == warp_gate_id
+                        && warp_gate
+                            .position
+                            .xy()
+                            .distance(character.position.position.xy())
+                            <= WARP_GATE_MAX_DISTANCE
+                })
+            })
+    };
+    if !is_in_range {
+        return Err(WarpGateError::TooFarAway);
+    }
+
+    let destination =
+        if let Some(destination) = gate_config.and_then(|gate| gate.destination.as_ref()) {
+            Position::new(destination.position, destination.zone)
+        } else {
+            let warp_gate = game_data
+                .warp_gates
+                .get_warp_gate(warp_gate_id)
+                .ok_or(WarpGateError::NotFound)?;
+            let event_position = game_data
+                .zones
+                .get_zone(warp_gate.target_zone)
+                .and_then(|zone_data| {
+                    zone_data
+                        .event_positions
+                        .get(&warp_gate.target_event_object)
+                })
+                .ok_or(WarpGateError::NotFound)?;
+            Position::new(*event_position, warp_gate.target_zone)
+        };
+
+    if let Some(gate_config) = gate_config {
+        if gate_config
+            .min_level
+            .map_or(false, |min_level| character.level.level < min_level)
+        {
+            return Err(WarpGateError::LevelTooLow);
+        }
+
+        if let Some(quest_switch) = gate_config.required_quest_switch {
+            if !character
+                .quest_state
+                .quest_switches
+                .get(quest_switch)
+                .map_or(false, |switch| *switch)
+            {
+                return Err(WarpGateError::QuestNotCompleted);
+            }
+        }
+
+        if gate_config.fee.0 > 0 {
+            character
+                .inventory
+                .try_take_money(gate_config.fee)
+                .map_err(|_| WarpGateError::NotEnoughMoney)?;
+
+            if let Some(game_client) = character.game_client {
+                game_client
+                    .server_message_tx
+                    .send(ServerMessage::UpdateMoney {
+                        money: character.inventory.money,
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    Ok(destination)
+}
+
+pub fn teleport_system(
+    mut character_query: Query<TeleportCharacterQuery, (With<CharacterInfo>, With<ClientEntity>)>,
+    mut teleport_gate_query: Query<(&mut TeleportGate, &Position)>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    mut warp_gate_events: EventReader<WarpGateEvent>,
+    mut teleport_events: EventWriter<TeleportEvent>,
+) {
+    for &WarpGateEvent {
+        entity,
+        warp_gate_id,
+    } in warp_gate_events.iter()
+    {
+        let Ok(mut character) = character_query.get_mut(entity) else {
+            continue;
+        };
+
+        match use_warp_gate(&game_config, &game_data, &mut character, warp_gate_id) {
+            Ok(destination) => {
+                teleport_events.send(TeleportEvent {
+                    entity: character.entity,
+                    position: destination,
+                });
+            }
+            Err(error) => {
+                if let Some(game_client) = character.game_client {
+                    game_client
+                        .server_message_tx
+                        .send(ServerMessage::WarpGateError { error })
+                        .ok();
+                }
+            }
+        }
+    }
+
+    if teleport_gate_query.is_empty() {
+        return;
+    }
+
+    for (mut teleport_gate, gate_position) in teleport_gate_query.iter_mut() {
+        let occupants: HashSet<Entity> = character_query
+            .iter()
+            .filter(|character| {
+                gate_position.zone_id == character.position.zone_id
+                    && gate_position
+                        .position
+                        .xy()
+                        .distance(character.position.position.xy())
+                        <= teleport_gate.radius
+            })
+            .map(|character| character.entity)
+            .collect();
+
+        // Characters already standing on a new gate must walk out and back in
+        if !teleport_gate.is_added() {
+            for &entity in occupants.difference(&teleport_gate.occupants) {
+                teleport_events.send(TeleportEvent {
+                    entity,
+                    position: teleport_gate.destination.clone(),
+                });
+            }
+        }
+
+        teleport_gate.occupants = occupants;
+    }
+}
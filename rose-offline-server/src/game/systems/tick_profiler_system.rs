@@ -0,0 +1,28 @@
+use bevy::ecs::prelude::ResMut;
+use log::warn;
+
+use crate::game::resources::TickProfiler;
+
+const NUM_SLOWEST_SYSTEMS: usize = 5;
+
+pub fn tick_profiler_system(tick_profiler: Option<ResMut<TickProfiler>>) {
+    let Some(mut tick_profiler) = tick_profiler else {
+        return;
+    };
+
+    for tick in tick_profiler.update() {
+        let slowest_systems = tick
+            .slowest_systems(NUM_SLOWEST_SYSTEMS)
+            .iter()
+            .map(|(name, duration)| format!("{} {:.2}ms", name, duration.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        warn!(
+            "Tick took {:.2}ms which exceeds the budget of {:.2}ms, slowest systems: {}",
+            tick.duration.as_secs_f64() * 1000.0,
+            tick_profiler.budget.as_secs_f64() * 1000.0,
+            slowest_systems
+        );
+    }
+}
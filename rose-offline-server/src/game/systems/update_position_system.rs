@@ -5,7 +5,10 @@ use bevy::{
 };
 
 use crate::game::{
-    components::{ClientEntity, ClientEntitySector, Command, CommandData, MoveSpeed, Position},
+    components::{
+        ClientEntity, ClientEntitySector, Command, CommandData, MoveSpeed, MovementImpairment,
+        Position,
+    },
     resources::ClientEntityList,
 };
 
@@ -17,16 +20,31 @@ pub fn update_position_system(
         &MoveSpeed,
         &mut Position,
         &Command,
+        Option<&MovementImpairment>,
     )>,
     mut client_entity_list: ResMut<ClientEntityList>,
     time: Res<Time>,
 ) {
     query.for_each_mut(
-        |(entity, client_entity, client_entity_sector, move_speed, mut position, command)| {
+        |(
+            entity,
+            client_entity,
+            client_entity_sector,
+            move_speed,
+            mut position,
+            command,
+            movement_impairment,
+        )| {
             let CommandData::Move { destination, .. } = command.command else {
                 return;
             };
 
+            if movement_impairment.map_or(false, |movement_impairment| {
+                movement_impairment.is_rooted(time.last_update().unwrap())
+            }) {
+                return;
+            }
+
             let direction = destination.xy() - position.position.xy();
             let distance_squared = direction.length_squared();
 
@@ -1,6 +1,6 @@
 use bevy::prelude::{EventReader, Query, ResMut};
 
-use rose_data::{Item, StackableSlotBehaviour};
+use rose_data::{AmmoIndex, Item, StackableSlotBehaviour};
 use rose_game_common::{
     components::{Equipment, ItemSlot},
     messages::server::ServerMessage,
@@ -21,6 +21,7 @@ pub fn use_ammo_system(
             continue;
         };
 
+        let had_ammo = equipment.get_ammo_item(event.ammo_index).is_some();
         equipment
             .get_ammo_slot_mut(event.ammo_index)
             .try_take_quantity(event.quantity as u32);
@@ -42,6 +43,31 @@ pub fn use_ammo_system(
                     }
                 }
                 None => {
+                    if had_ammo {
+                        // Ran out of ammo, the empty stack has been unequipped
+                        game_client
+                            .server_message_tx
+                            .send(ServerMessage::UpdateInventory {
+                                items: vec![(ItemSlot::Ammo(event.ammo_index), None)],
+                                money: None,
+                            })
+                            .ok();
+                        game_client
+                            .server_message_tx
+                            .send(ServerMessage::Whisper {
+                                from: String::from("SERVER"),
+                                text: format!(
+                                    "You have run out of {}.",
+                                    match event.ammo_index {
+                                        AmmoIndex::Arrow => "arrows",
+                                        AmmoIndex::Bullet => "bullets",
+                                        AmmoIndex::Throw => "shells",
+                                    }
+                                ),
+                            })
+                            .ok();
+                    }
+
                     server_messages.send_entity_message(
                         client_entity,
                         ServerMessage::UpdateAmmo {
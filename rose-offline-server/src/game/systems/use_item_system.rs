@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use bevy::{
     ecs::{
-        prelude::{Commands, Entity, EventReader, Query, Res, ResMut},
+        prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut},
         query::WorldQuery,
         system::SystemParam,
     },
@@ -16,27 +16,28 @@ use rose_game_common::components::{Equipment, HealthPoints, ManaPoints};
 
 use crate::game::{
     bundles::{
-        ability_values_add_value, ability_values_get_value, client_entity_teleport_zone,
-        skill_list_try_learn_skill, SkillListBundle,
+        ability_values_add_value, ability_values_get_value, skill_list_try_learn_skill,
+        SkillListBundle,
     },
     components::{
-        AbilityValues, BasicStats, CharacterInfo, ClientEntity, ClientEntitySector,
-        ExperiencePoints, GameClient, Inventory, ItemSlot, Level, MoveSpeed, NextCommand, Position,
-        SkillList, SkillPoints, Stamina, StatPoints, StatusEffects, StatusEffectsRegen, Team,
-        UnionMembership,
+        AbilityValues, BasicStats, CharacterInfo, ClientEntity, Cooldowns, ExperiencePoints,
+        GameClient, Inventory, ItemSlot, Level, MoveSpeed, NextCommand, Position, SkillList,
+        SkillPoints, Stamina, StatPoints, StatusEffects, StatusEffectsRegen, Team, UnionMembership,
     },
-    events::UseItemEvent,
+    events::{SuspicionEvent, SuspicionSignal, TeleportEvent, UseItemEvent},
     messages::server::ServerMessage,
-    resources::{ClientEntityList, ServerMessages},
+    resources::{CheatDetection, ServerMessages},
     GameData,
 };
 
 #[derive(SystemParam)]
 pub struct UseItemSystemParameters<'w, 's> {
     commands: Commands<'w, 's>,
+    cheat_detection: Res<'w, CheatDetection>,
     game_data: Res<'w, GameData>,
-    client_entity_list: ResMut<'w, ClientEntityList>,
     server_messages: ResMut<'w, ServerMessages>,
+    suspicion_events: EventWriter<'w, SuspicionEvent>,
+    teleport_events: EventWriter<'w, TeleportEvent>,
     time: Res<'w, Time>,
 }
 
@@ -48,7 +49,6 @@ pub struct UseItemUserQuery<'w> {
     basic_stats: &'w mut BasicStats,
     character_info: &'w CharacterInfo,
     client_entity: &'w ClientEntity,
-    client_entity_sector: &'w ClientEntitySector,
     experience_points: &'w mut ExperiencePoints,
     equipment: &'w mut Equipment,
     game_client: Option<&'w GameClient>,
@@ -66,11 +66,13 @@ pub struct UseItemUserQuery<'w> {
     status_effects_regen: &'w mut StatusEffectsRegen,
     team: &'w Team,
     union_membership: &'w mut UnionMembership,
+    cooldowns: Option<&'w mut Cooldowns>,
 }
 
 enum UseItemError {
     InvalidItem,
     AbilityRequirement,
+    Cooldown,
 }
 
 fn apply_item_effect(
@@ -154,7 +156,22 @@ fn use_inventory_item(
         .get_consumable_item(item.get_item_number())
         .ok_or(UseItemError::InvalidItem)?;
 
-    // TODO: Check use item cooldown
+    let now = use_item_system_parameters.time.last_update();
+    if let (Some(cooldowns), Some(now)) = (use_item_user.cooldowns.as_ref(), now) {
+        if cooldowns
+            .item
+            .get(&item_data.cooldown_type_id)
+            .map_or(false, |cooldown_finished| now < *cooldown_finished)
+        {
+            use_item_system_parameters
+                .suspicion_events
+                .send(SuspicionEvent::new(
+                    use_item_user.entity,
+                    SuspicionSignal::ItemUseRate,
+                ));
+            return Err(UseItemError::Cooldown);
+        }
+    }
 
     if let Some((require_ability_type, require_ability_value)) = item_data.ability_requirement {
         let ability_value = ability_values_get_value(
@@ -190,6 +207,17 @@ fn use_inventory_item(
         .try_take_quantity(item_slot, 1)
         .ok_or(UseItemError::InvalidItem)?;
 
+    if let (Some(cooldowns), Some(now)) = (use_item_user.cooldowns.as_mut(), now) {
+        if !item_data.cooldown_duration.is_zero() {
+            cooldowns.item.insert(
+                item_data.cooldown_type_id,
+                now + use_item_system_parameters
+                    .cheat_detection
+                    .get_item_cooldown(item_data.cooldown_duration),
+            );
+        }
+    }
+
     let (consume_item, message_to_nearby) = match item_data.item_data.class {
         ItemClass::MagicItem => {
             if let Some((skill_id, skill_data)) = item_data.use_skill_id.and_then(|skill_id| {
@@ -236,19 +264,15 @@ fn use_inventory_item(
                                 .ok();
                         }
 
-                        client_entity_teleport_zone(
-                            &mut use_item_system_parameters.commands,
-                            &mut use_item_system_parameters.client_entity_list,
-                            use_item_user.entity,
-                            use_item_user.client_entity,
-                            use_item_user.client_entity_sector,
-                            use_item_user.position,
-                            Position::new(
-                                Vec3::new(skill_data.warp_zone_x, skill_data.warp_zone_y, 0.0),
-                                zone_id,
-                            ),
-                            use_item_user.game_client,
-                        );
+                        use_item_system_parameters
+                            .teleport_events
+                            .send(TeleportEvent {
+                                entity: use_item_user.entity,
+                                position: Position::new(
+                                    Vec3::new(skill_data.warp_zone_x, skill_data.warp_zone_y, 0.0),
+                                    zone_id,
+                                ),
+                            });
                     }
                     (true, false)
                 } else {
@@ -1,55 +1,111 @@
-use bevy::ecs::prelude::{Changed, Commands, Entity, Or, Query, Res};
+use bevy::ecs::prelude::{Changed, Commands, Entity, Or, Query, Res, ResMut};
 
 use crate::game::{
-    components::{Equipment, Inventory, Weight},
+    components::{AbilityValues, ClientEntity, Equipment, Inventory, MoveMode, Weight},
+    messages::server::ServerMessage,
+    resources::ServerMessages,
     GameData,
 };
 
 pub fn weight_system(
     mut commands: Commands,
     calculate_weight_query: Query<
-        (Entity, &Inventory, &Equipment),
-        Or<(Changed<Inventory>, Changed<Equipment>)>,
+        (
+            Entity,
+            &AbilityValues,
+            &Inventory,
+            &Equipment,
+            &MoveMode,
+            Option<&ClientEntity>,
+            Option<&Weight>,
+        ),
+        Or<(
+            Changed<Inventory>,
+            Changed<Equipment>,
+            Changed<AbilityValues>,
+        )>,
     >,
     game_data: Res<GameData>,
+    mut server_messages: ResMut<ServerMessages>,
 ) {
-    calculate_weight_query.for_each(|(entity, inventory, equipment)| {
-        let mut weight = 0;
-
-        for item in inventory.iter().filter_map(|slot| slot.as_ref()) {
-            weight += game_data
-                .items
-                .get_base_item(item.get_item_reference())
-                .map(|item_data| item_data.weight)
-                .unwrap_or(0)
-                * item.get_quantity();
-        }
-
-        for item in equipment.iter_equipped_items() {
-            weight += game_data
-                .items
-                .get_base_item(item.item)
-                .map(|item_data| item_data.weight)
-                .unwrap_or(0);
-        }
-
-        for item in equipment.iter_equipped_vehicles() {
-            weight += game_data
-                .items
-                .get_base_item(item.item)
-                .map(|item_data| item_data.weight)
-                .unwrap_or(0);
-        }
-
-        for item in equipment.iter_equipped_ammo() {
-            weight += game_data
-                .items
-                .get_base_item(item.item)
-                .map(|item_data| item_data.weight)
-                .unwrap_or(0)
-                * item.quantity;
-        }
-
-        commands.entity(entity).insert(Weight::new(weight));
-    });
+    calculate_weight_query.for_each(
+        |(
+            entity,
+            ability_values,
+            inventory,
+            equipment,
+            move_mode,
+            client_entity,
+            previous_weight,
+        )| {
+            let mut weight = 0;
+
+            for item in inventory.iter().filter_map(|slot| slot.as_ref()) {
+                weight += game_data
+                    .items
+                    .get_base_item(item.get_item_reference())
+                    .map(|item_data| item_data.weight)
+                    .unwrap_or(0)
+                    * item.get_quantity();
+            }
+
+            for item in equipment.iter_equipped_items() {
+                weight += game_data
+                    .items
+                    .get_base_item(item.item)
+                    .map(|item_data| item_data.weight)
+                    .unwrap_or(0);
+            }
+
+            for item in equipment.iter_equipped_vehicles() {
+                weight += game_data
+                    .items
+                    .get_base_item(item.item)
+                    .map(|item_data| item_data.weight)
+                    .unwrap_or(0);
+            }
+
+            for item in equipment.iter_equipped_ammo() {
+                weight += game_data
+                    .items
+                    .get_base_item(item.item)
+                    .map(|item_data| item_data.weight)
+                    .unwrap_or(0)
+                    * item.quantity;
+            }
+
+            let weight = Weight::new(weight, ability_values.max_weight());
+            if previous_weight.map_or(false, |previous_weight| {
+                previous_weight.weight == weight.weight
+                    && previous_weight.weight_rate == weight.weight_rate
+            }) {
+                // Avoid triggering Changed<Weight> when nothing has changed
+                return;
+            }
+
+            // Notify nearby clients when the weight penalty changes
+            let penalty_changed = previous_weight.map_or(
+                weight.is_walk_only() || !weight.can_attack(),
+                |previous_weight| {
+                    previous_weight.is_walk_only() != weight.is_walk_only()
+                        || previous_weight.can_attack() != weight.can_attack()
+                },
+            );
+            if penalty_changed {
+                if let Some(client_entity) = client_entity {
+                    server_messages.send_entity_message(
+                        client_entity,
+                        ServerMessage::UpdateSpeed {
+                            entity_id: client_entity.id,
+                            run_speed: weight.get_move_speed(ability_values, move_mode) as i32,
+                            passive_attack_speed: ability_values.get_passive_attack_speed(),
+                            weight_rate: weight.get_client_weight_rate(),
+                        },
+                    );
+                }
+            }
+
+            commands.entity(entity).insert(weight);
+        },
+    );
 }
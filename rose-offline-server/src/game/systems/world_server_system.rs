@@ -1,28 +1,44 @@
 use bevy::{
-    ecs::prelude::{Commands, Entity, Query, Res, ResMut, Without},
+    ecs::prelude::{Commands, Entity, Query, Res, ResMut, With, Without},
     prelude::EventWriter,
 };
+use chrono::Utc;
 use log::warn;
+use std::time::Instant;
 
+use rose_data::{EquipmentItem, Item};
 use rose_game_common::data::Password;
 
 use crate::game::{
-    components::{Account, CharacterDeleteTime, CharacterList, ServerInfo, WorldClient},
-    events::ClanEvent,
+    components::{
+        Account, CharacterDeleteTime, CharacterInfo, CharacterList, DisconnectedCharacter,
+        Equipment, Inventory, Position, ServerInfo, WorldClient,
+    },
+    events::{ClanEvent, SaveEvent},
     messages::{
         client::ClientMessage,
-        server::{CharacterListItem, ConnectionRequestError, CreateCharacterError, ServerMessage},
+        server::{
+            CharacterListItem, ConnectionRequestError, CreateCharacterError, SecondaryPinError,
+            ServerMessage,
+        },
+    },
+    resources::{
+        AccountSessions, CharacterCreationConfig, CharacterListCache, GameConfig, GameData,
+        LoginTokens, NameFilter, StorageKey, StorageService,
     },
-    resources::{GameData, LoginTokens},
     storage::{
         account::{AccountStorage, AccountStorageError},
         character::CharacterStorage,
+        journal::StorageJournal,
     },
 };
 
 fn handle_world_connection_request(
     commands: &mut Commands,
+    account_sessions: &mut AccountSessions,
     login_tokens: &mut LoginTokens,
+    character_list_cache: &mut CharacterListCache,
+    name_filter: &mut NameFilter,
     entity: Entity,
     world_client: &mut WorldClient,
     token_id: u32,
@@ -31,7 +47,15 @@ fn handle_world_connection_request(
     let login_token = login_tokens
         .get_token_mut(token_id)
         .ok_or(ConnectionRequestError::InvalidToken)?;
-    if login_token.world_client.is_some() || login_token.game_client.is_some() {
+    if login_token.is_expired(Instant::now())
+        || login_token.world_client.is_some()
+        || login_token.game_client.is_some()
+        || world_client
+            .packet_codec_login_token
+            .map_or(false, |packet_codec_login_token| {
+                packet_codec_login_token != token_id
+            })
+    {
         return Err(ConnectionRequestError::InvalidToken);
     }
 
@@ -52,61 +76,241 @@ fn handle_world_connection_request(
             }
         })?;
 
-    // Load character list, deleting any characters ready for deletion
-    let mut character_list = CharacterList::default();
-    account
-        .character_names
-        .retain(|name| match CharacterStorage::try_load(name) {
-            Ok(character) => {
-                if character
-                    .delete_time
-                    .as_ref()
-                    .map(|x| x.get_time_until_delete())
-                    .filter(|x| x.as_nanos() == 0)
-                    .is_some()
-                {
-                    match CharacterStorage::delete(&character.info.name) {
-                        Ok(_) => log::error!(
-                            "Deleted character {} as delete timer has expired.",
-                            &character.info.name
-                        ),
-                        Err(error) => log::error!(
-                            "Failed to delete character {} with error {:?}",
-                            &character.info.name,
-                            error
-                        ),
+    account_sessions
+        .claim_world_client(&login_token.username, token_id, entity)
+        .map_err(|_| ConnectionRequestError::InvalidToken)?;
+
+    // Load character list from the cache if possible, otherwise from storage
+    let mut characters =
+        match character_list_cache.get(&account.name, &account.character_names, Instant::now()) {
+            Some(characters) => characters.to_vec(),
+            None => account
+                .character_names
+                .iter()
+                .filter_map(|name| match CharacterStorage::try_load(name) {
+                    Ok(character) => Some(CharacterListItem::from(&character)),
+                    Err(error) => {
+                        log::error!("Failed to load character {} with error {:?}", name, error);
+                        None
                     }
-                    false
-                } else {
-                    character_list.push(character);
-                    true
+                })
+                .collect(),
+        };
+
+    // Delete any characters ready for deletion
+    let mut journal = StorageJournal::new();
+    let mut deleted_names = Vec::new();
+    characters.retain(|character| {
+        if character
+            .delete_time
+            .as_ref()
+            .map(|x| x.get_time_until_delete())
+            .filter(|x| x.as_nanos() == 0)
+            .is_some()
+        {
+            journal.delete_character(&character.info.name);
+            deleted_names.push(character.info.name.clone());
+            false
+        } else {
+            true
+        }
+    });
+    account.character_names = characters
+        .iter()
+        .map(|character| character.info.name.clone())
+        .collect();
+    character_list_cache.insert(account.name.clone(), characters.clone(), Instant::now());
+    if journal.is_empty() {
+        account.save().ok();
+    } else {
+        journal.save_account(account.clone());
+        match journal.commit() {
+            Ok(_) => {
+                for name in deleted_names.iter() {
+                    name_filter.remove_character_name(name);
                 }
+                log::info!(
+                    "Deleted characters of account {} as delete timer has expired.",
+                    &account.name
+                );
             }
-            Err(error) => {
-                log::error!("Failed to load character {} with error {:?}", name, error);
-                false
-            }
-        });
-    account.save().ok();
+            Err(error) => log::error!(
+                "Failed to delete characters of account {} with error {:?}",
+                &account.name,
+                error
+            ),
+        }
+    }
 
     // Update entity
     commands
         .entity(entity)
         .insert(Account::from(account))
-        .insert(character_list);
+        .insert(CharacterList { characters });
 
     // Update token
     login_token.world_client = Some(entity);
     world_client.login_token = login_token.token;
     world_client.selected_game_server = Some(login_token.selected_game_server);
 
-    Ok(123)
+    Ok(rand::random())
+}
+
+fn apply_character_creation_config(
+    config: &CharacterCreationConfig,
+    game_data: &GameData,
+    character: &mut CharacterStorage,
+) {
+    if let Some(start_zone) = config.start_zone {
+        if let Some(zone_data) = game_data.zones.get_zone(start_zone) {
+            let start_position = config.start_position.unwrap_or(zone_data.start_position);
+            let revive_position = zone_data
+                .get_closest_revive_position(start_position)
+                .unwrap_or(zone_data.start_position);
+
+            character.position = Position::new(start_position, start_zone);
+            character.info.revive_zone_id = start_zone;
+            character.info.revive_position = revive_position;
+        } else {
+            log::warn!("Invalid character creation start zone {}", start_zone.get());
+        }
+    } else if let Some(start_position) = config.start_position {
+        character.position.position = start_position;
+    }
+
+    // Grant the stat and skill points for each level gained, as if levelled up normally
+    if let Some(start_level) = config.start_level {
+        while character.level.level < start_level {
+            character.level.level += 1;
+
+            character.skill_points.points += game_data
+                .ability_value_calculator
+                .calculate_levelup_reward_skill_points(character.level.level);
+
+            character.stat_points.points += game_data
+                .ability_value_calculator
+                .calculate_levelup_reward_stat_points(character.level.level);
+        }
+    }
+
+    if config.replace_default_items {
+        character.equipment = Equipment::default();
+        character.inventory = Inventory::default();
+    }
+
+    for kit in config.kits.iter().filter(|kit| {
+        kit.gender
+            .map_or(true, |gender| gender == character.info.gender)
+            && kit.job.map_or(true, |job| job == character.info.job)
+    }) {
+        for &item_reference in kit.equipped_items.iter() {
+            if let Some(item) = game_data
+                .items
+                .get_base_item(item_reference)
+                .and_then(EquipmentItem::from_item_data)
+            {
+                character.equipment.equip_item(item).ok();
+            }
+        }
+
+        for kit_item in kit.items.iter() {
+            if let Some(item) = game_data
+                .items
+                .get_base_item(kit_item.item)
+                .and_then(|item_data| Item::from_item_data(item_data, kit_item.quantity))
+            {
+                character.inventory.try_add_item(item).ok();
+            }
+        }
+
+        for &skill_id in kit.skills.iter() {
+            if let Some(skill_data) = game_data.skills.get_skill(skill_id) {
+                if character.skill_list.find_skill_exact(skill_data).is_none() {
+                    character.skill_list.add_skill(skill_data);
+                }
+            }
+        }
+    }
+}
+
+/// Ends the reconnect grace period of the account's characters which are still
+/// in the world after disconnecting, except for `keep_character_name`, so they
+/// are saved and despawned and the account can join with another character.
+fn end_reconnect_grace_periods(
+    commands: &mut Commands,
+    save_events: &mut EventWriter<SaveEvent>,
+    disconnected_character_query: &Query<
+        (Entity, &Account, &CharacterInfo),
+        With<DisconnectedCharacter>,
+    >,
+    account_name: &str,
+    keep_character_name: Option<&str>,
+) {
+    for (entity, account, character_info) in disconnected_character_query.iter() {
+        if account.name != account_name
+            || keep_character_name.map_or(false, |name| name == character_info.name)
+        {
+            continue;
+        }
+
+        commands.entity(entity).remove::<DisconnectedCharacter>();
+        save_events.send(SaveEvent::Character {
+            entity,
+            remove_after_save: true,
+        });
+    }
+}
+
+fn handle_select_character(
+    world_client: &WorldClient,
+    character_list: &mut CharacterList,
+    login_tokens: &mut LoginTokens,
+    server_info_query: &Query<&ServerInfo>,
+    slot: u8,
+    name: &str,
+) -> ServerMessage {
+    character_list
+        .get_mut(slot as usize)
+        .filter(|character| character.info.name == name)
+        .map_or(ServerMessage::SelectCharacterError, |selected_character| {
+            // Set the selected_character for the login token
+            if let Some(token) = login_tokens
+                .tokens
+                .iter_mut()
+                .find(|t| t.token == world_client.login_token)
+            {
+                token.selected_character = selected_character.info.name.clone()
+            }
+
+            // Find the selected game server details
+            if let Some(selected_game_server) = world_client.selected_game_server {
+                if let Ok(server_info) = server_info_query.get(selected_game_server) {
+                    // Each game server connection uses a new packet codec seed
+                    let packet_codec_seed = login_tokens
+                        .generate_game_packet_codec_seed(world_client.login_token)
+                        .unwrap_or(server_info.packet_codec_seed);
+                    ServerMessage::SelectCharacterSuccess {
+                        login_token: world_client.login_token,
+                        packet_codec_seed,
+                        ip: server_info.ip.clone(),
+                        port: server_info.port,
+                    }
+                } else {
+                    ServerMessage::SelectCharacterError
+                }
+            } else {
+                ServerMessage::SelectCharacterError
+            }
+        })
 }
 
 pub fn world_server_authentication_system(
     mut commands: Commands,
     mut query: Query<(Entity, &mut WorldClient), Without<Account>>,
+    mut account_sessions: ResMut<AccountSessions>,
     mut login_tokens: ResMut<LoginTokens>,
+    mut character_list_cache: ResMut<CharacterListCache>,
+    mut name_filter: ResMut<NameFilter>,
 ) {
     query.for_each_mut(|(entity, mut world_client)| {
         if let Ok(message) = world_client.client_message_rx.try_recv() {
@@ -117,7 +321,10 @@ pub fn world_server_authentication_system(
                 } => {
                     let response = match handle_world_connection_request(
                         &mut commands,
+                        account_sessions.as_mut(),
                         login_tokens.as_mut(),
+                        character_list_cache.as_mut(),
+                        name_filter.as_mut(),
                         entity,
                         world_client.as_mut(),
                         login_token,
@@ -137,28 +344,33 @@ pub fn world_server_authentication_system(
 }
 
 pub fn world_server_system(
-    mut world_client_query: Query<(&mut WorldClient, &mut Account, &mut CharacterList)>,
+    mut commands: Commands,
+    mut world_client_query: Query<
+        (&mut WorldClient, &mut Account, &mut CharacterList),
+        Without<DisconnectedCharacter>,
+    >,
+    disconnected_character_query: Query<
+        (Entity, &Account, &CharacterInfo),
+        With<DisconnectedCharacter>,
+    >,
     server_info_query: Query<&ServerInfo>,
     mut login_tokens: ResMut<LoginTokens>,
+    mut character_list_cache: ResMut<CharacterListCache>,
+    game_config: Res<GameConfig>,
     game_data: Res<GameData>,
+    mut name_filter: ResMut<NameFilter>,
     mut clan_events: EventWriter<ClanEvent>,
+    mut save_events: EventWriter<SaveEvent>,
+    mut storage_service: ResMut<StorageService>,
 ) {
-    world_client_query.for_each_mut(|(world_client, mut account, mut character_list)| {
+    world_client_query.for_each_mut(|(mut world_client, mut account, mut character_list)| {
         if let Ok(message) = world_client.client_message_rx.try_recv() {
             match message {
                 ClientMessage::GetCharacterList => {
                     world_client
                         .server_message_tx
                         .send(ServerMessage::CharacterList {
-                            character_list: character_list
-                                .iter()
-                                .map(|character| CharacterListItem {
-                                    info: character.info.clone(),
-                                    level: character.level,
-                                    delete_time: character.delete_time,
-                                    equipment: character.equipment.clone(),
-                                })
-                                .collect(),
+                            character_list: character_list.characters.clone(),
                         })
                         .ok();
                 }
@@ -178,7 +390,16 @@ pub fn world_server_system(
                         ServerMessage::CreateCharacterError {
                             error: CreateCharacterError::InvalidValue,
                         }
-                    } else if CharacterStorage::exists(&name) {
+                    } else if !name_filter.is_name_allowed(&name)
+                        || !game_config.character_creation.is_face_allowed(face as u8)
+                        || !game_config.character_creation.is_hair_allowed(hair as u8)
+                    {
+                        ServerMessage::CreateCharacterError {
+                            error: CreateCharacterError::InvalidValue,
+                        }
+                    } else if CharacterStorage::exists(&name)
+                        || name_filter.is_character_name_taken(&name)
+                    {
                         ServerMessage::CreateCharacterError {
                             error: CreateCharacterError::AlreadyExists,
                         }
@@ -190,8 +411,25 @@ pub fn world_server_system(
                             face as u8,
                             hair as u8,
                         ) {
-                            Ok(character) => {
-                                if let Err(error) = character.try_create(&name) {
+                            Ok(mut character) => {
+                                apply_character_creation_config(
+                                    &game_config.character_creation,
+                                    &game_data,
+                                    &mut character,
+                                );
+
+                                // The character and the account which lists it are
+                                // saved together, so a crash can not leave either
+                                // without the other
+                                let list_item = CharacterListItem::from(&character);
+                                let mut account_storage = AccountStorage::from(&*account);
+                                account_storage.character_names.push(name.clone());
+
+                                let mut journal = StorageJournal::new();
+                                journal.create_character(character);
+                                journal.save_account(account_storage);
+
+                                if let Err(error) = journal.commit() {
                                     log::error!(
                                         "Failed to create character {} with error {:?}",
                                         &name,
@@ -202,9 +440,10 @@ pub fn world_server_system(
                                     }
                                 } else {
                                     let character_slot = account.character_names.len();
-                                    account.character_names.push(character.info.name.clone());
-                                    AccountStorage::from(&*account).save().ok();
-                                    character_list.push(character);
+                                    account.character_names.push(name.clone());
+                                    character_list.push(list_item);
+                                    character_list_cache.invalidate(&account.name);
+                                    name_filter.add_character_name(&name);
                                     ServerMessage::CreateCharacterSuccess { character_slot }
                                 }
                             }
@@ -228,28 +467,65 @@ pub fn world_server_system(
                     name,
                     is_delete,
                 } => {
+                    // A character which is still in the world would overwrite the
+                    // delete time when it is saved, so it must leave the world first
+                    if disconnected_character_query.iter().any(
+                        |(_, character_account, character_info)| {
+                            character_account.name == account.name && character_info.name == name
+                        },
+                    ) {
+                        end_reconnect_grace_periods(
+                            &mut commands,
+                            &mut save_events,
+                            &disconnected_character_query,
+                            &account.name,
+                            None,
+                        );
+                        world_client
+                            .server_message_tx
+                            .send(ServerMessage::DeleteCharacterError { name })
+                            .ok();
+                        return;
+                    }
+
                     let response = character_list
                         .get_mut(slot as usize)
                         .filter(|character| character.info.name == name)
                         .map_or_else(
                             || ServerMessage::DeleteCharacterError { name: name.clone() },
                             |character| {
+                                let mut storage = match CharacterStorage::try_load(&name) {
+                                    Ok(storage) => storage,
+                                    Err(error) => {
+                                        log::error!(
+                                            "Failed to load character {} with error {:?}",
+                                            &name,
+                                            error
+                                        );
+                                        return ServerMessage::DeleteCharacterError {
+                                            name: name.clone(),
+                                        };
+                                    }
+                                };
+
                                 if is_delete {
-                                    if character.delete_time.is_none() {
-                                        character.delete_time = Some(CharacterDeleteTime::new());
+                                    if storage.delete_time.is_none() {
+                                        storage.delete_time = Some(CharacterDeleteTime::new());
                                     }
                                 } else {
-                                    character.delete_time = None;
+                                    storage.delete_time = None;
                                 }
 
-                                match character.save() {
-                                    Ok(_) => log::info!("Saved character {}", character.info.name),
+                                match storage.save() {
+                                    Ok(_) => log::info!("Saved character {}", storage.info.name),
                                     Err(error) => log::error!(
                                         "Failed to save character {} with error {:?}",
-                                        character.info.name,
+                                        storage.info.name,
                                         error
                                     ),
                                 }
+                                character.delete_time = storage.delete_time;
+                                character_list_cache.invalidate(&account.name);
 
                                 if let Some(delete_time) = character.delete_time {
                                     ServerMessage::DeleteCharacterStart {
@@ -264,36 +540,95 @@ pub fn world_server_system(
                     world_client.server_message_tx.send(response).ok();
                 }
                 ClientMessage::SelectCharacter { slot, name } => {
-                    let response = character_list
-                        .get_mut(slot as usize)
-                        .filter(|character| character.info.name == name)
-                        .map_or(ServerMessage::SelectCharacterError, |selected_character| {
-                            // Set the selected_character for the login token
-                            if let Some(token) = login_tokens
-                                .tokens
-                                .iter_mut()
-                                .find(|t| t.token == world_client.login_token)
-                            {
-                                token.selected_character = selected_character.info.name.clone()
-                            }
+                    let response = if account.secondary_pin_sha256.is_some()
+                        && !world_client.secondary_pin_verified
+                    {
+                        world_client.pending_select_character = Some((slot, name));
+                        ServerMessage::SecondaryPinRequest { error: None }
+                    } else {
+                        handle_select_character(
+                            &world_client,
+                            &mut character_list,
+                            &mut login_tokens,
+                            &server_info_query,
+                            slot,
+                            &name,
+                        )
+                    };
+                    if matches!(response, ServerMessage::SelectCharacterSuccess { .. }) {
+                        end_reconnect_grace_periods(
+                            &mut commands,
+                            &mut save_events,
+                            &disconnected_character_query,
+                            &account.name,
+                            Some(&name),
+                        );
+                    }
+                    world_client.server_message_tx.send(response).ok();
+                }
+                ClientMessage::SecondaryPin { pin } => {
+                    let Some((slot, name)) = world_client.pending_select_character.take() else {
+                        warn!("[WS] Received secondary PIN without a pending character select");
+                        return;
+                    };
 
-                            // Find the selected game server details
-                            if let Some(selected_game_server) = world_client.selected_game_server {
-                                if let Ok(server_info) = server_info_query.get(selected_game_server)
-                                {
-                                    ServerMessage::SelectCharacterSuccess {
-                                        login_token: world_client.login_token,
-                                        packet_codec_seed: server_info.packet_codec_seed,
-                                        ip: server_info.ip.clone(),
-                                        port: server_info.port,
-                                    }
-                                } else {
-                                    ServerMessage::SelectCharacterError
-                                }
-                            } else {
-                                ServerMessage::SelectCharacterError
+                    // Failures are saved so that the lockout can not be
+                    // avoided by reconnecting
+                    let now = Utc::now();
+                    let mut account_storage = AccountStorage::from(&*account);
+                    let result = account_storage.check_secondary_pin(&pin, now);
+                    *account = Account::from(account_storage.clone());
+                    if let Err(error) = storage_service
+                        .write(StorageKey::Account(account.name.clone()), move || {
+                            account_storage.save()
+                        })
+                    {
+                        log::error!(
+                            "Failed to save account {} with error {:?}",
+                            &account.name,
+                            error
+                        );
+                    }
+
+                    let response = match result {
+                        Ok(()) => {
+                            world_client.secondary_pin_verified = true;
+                            let response = handle_select_character(
+                                &world_client,
+                                &mut character_list,
+                                &mut login_tokens,
+                                &server_info_query,
+                                slot,
+                                &name,
+                            );
+                            if matches!(response, ServerMessage::SelectCharacterSuccess { .. }) {
+                                end_reconnect_grace_periods(
+                                    &mut commands,
+                                    &mut save_events,
+                                    &disconnected_character_query,
+                                    &account.name,
+                                    Some(&name),
+                                );
                             }
-                        });
+                            response
+                        }
+                        Err(AccountStorageError::InvalidSecondaryPin { attempts_remaining }) => {
+                            world_client.pending_select_character = Some((slot, name));
+                            ServerMessage::SecondaryPinRequest {
+                                error: Some(SecondaryPinError::InvalidPin { attempts_remaining }),
+                            }
+                        }
+                        Err(AccountStorageError::SecondaryPinLocked { locked_until }) => {
+                            world_client.pending_select_character = Some((slot, name));
+                            ServerMessage::SecondaryPinRequest {
+                                error: Some(SecondaryPinError::Locked {
+                                    seconds_remaining: (locked_until - now).num_seconds().max(0)
+                                        as u32,
+                                }),
+                            }
+                        }
+                        Err(_) => ServerMessage::SelectCharacterError,
+                    };
                     world_client.server_message_tx.send(response).ok();
                 }
                 ClientMessage::ClanGetMemberList => {
@@ -0,0 +1,65 @@
+use bevy::{
+    ecs::prelude::{Res, ResMut},
+    time::Time,
+};
+
+use crate::game::{
+    messages::server::ServerMessage,
+    resources::{GameConfig, GameData, ServerMessages, WorldTime, ZoneEnvironment, ZoneList},
+};
+
+pub fn zone_environment_system(
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    time: Res<Time>,
+    world_time: Res<WorldTime>,
+    mut server_messages: ResMut<ServerMessages>,
+    mut zone_list: ResMut<ZoneList>,
+) {
+    let Some(now) = time.last_update() else {
+        return;
+    };
+    let world_time = world_time.ticks.get_world_time();
+    let zone_ids: Vec<_> = zone_list.iter_zone_ids().collect();
+
+    for zone_id in zone_ids {
+        let Some(zone_data) = game_data.zones.get_zone(zone_id) else {
+            continue;
+        };
+        let time_of_day = zone_data.get_time_of_day(world_time);
+        let previous_environment = zone_list.get_environment(zone_id).cloned();
+
+        let (weather, next_weather_time) = match previous_environment.as_ref() {
+            Some(environment) if now < environment.next_weather_time => {
+                (environment.weather, environment.next_weather_time)
+            }
+            _ => (
+                game_config.zone_environment.choose_weather(zone_id),
+                now + game_config.zone_environment.get_weather_duration(),
+            ),
+        };
+
+        let changed = previous_environment.as_ref().map_or(true, |environment| {
+            environment.time_of_day != time_of_day || environment.weather != weather
+        });
+
+        zone_list.set_environment(
+            zone_id,
+            ZoneEnvironment {
+                time_of_day,
+                weather,
+                next_weather_time,
+            },
+        );
+
+        if changed {
+            server_messages.send_zone_message(
+                zone_id,
+                ServerMessage::UpdateZoneEnvironment {
+                    time_of_day,
+                    weather,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::ecs::{
+    prelude::{Entity, EventReader, Query, Res},
+    query::WorldQuery,
+};
+use chrono::Utc;
+use log::{error, info};
+
+use crate::game::{
+    components::{
+        AbilityValues, CharacterInfo, ClientEntity, Command, GameClient, HealthPoints, Level,
+        NextCommand, Npc, NpcAi, Owner, Position, Team,
+    },
+    events::ZoneSnapshotEvent,
+    messages::server::ServerMessage,
+    resources::GameData,
+    storage::zone_snapshot::{ZoneSnapshot, ZoneSnapshotEntity},
+};
+
+#[derive(WorldQuery)]
+pub struct ZoneSnapshotQuery<'w> {
+    entity: Entity,
+    position: &'w Position,
+    client_entity: Option<&'w ClientEntity>,
+    character_info: Option<&'w CharacterInfo>,
+    npc: Option<&'w Npc>,
+    command: Option<&'w Command>,
+    next_command: Option<&'w NextCommand>,
+    health_points: Option<&'w HealthPoints>,
+    ability_values: Option<&'w AbilityValues>,
+    level: Option<&'w Level>,
+    team: Option<&'w Team>,
+    owner: Option<&'w Owner>,
+    npc_ai: Option<&'w NpcAi>,
+}
+
+fn snapshot_entity(game_data: &GameData, item: &ZoneSnapshotQueryItem) -> ZoneSnapshotEntity {
+    let name = item
+        .character_info
+        .map(|character_info| character_info.name.clone())
+        .or_else(|| {
+            item.npc
+                .and_then(|npc| game_data.npcs.get_npc(npc.id))
+                .map(|npc_data| npc_data.name.to_string())
+        });
+
+    ZoneSnapshotEntity {
+        entity: item.entity,
+        client_entity_id: item.client_entity.map(|client_entity| client_entity.id),
+        entity_type: item
+            .client_entity
+            .map(|client_entity| client_entity.entity_type),
+        name,
+        npc_id: item.npc.map(|npc| npc.id),
+        position: item.position.position,
+        command: item.command.cloned(),
+        next_command: item
+            .next_command
+            .and_then(|next_command| next_command.command.clone()),
+        health: item.health_points.map(|health_points| health_points.hp),
+        max_health: item
+            .ability_values
+            .map(|ability_values| ability_values.get_max_health()),
+        level: item.level.map(|level| level.level),
+        team: item.team.map(|team| team.id),
+        owner: item.owner.map(|owner| owner.entity),
+        ai_index: item.npc_ai.map(|npc_ai| npc_ai.ai_index),
+    }
+}
+
+pub fn zone_snapshot_system(
+    mut zone_snapshot_events: EventReader<ZoneSnapshotEvent>,
+    query_requester: Query<(&CharacterInfo, &GameClient, &Position)>,
+    query_entities: Query<ZoneSnapshotQuery>,
+    game_data: Res<GameData>,
+) {
+    for event in zone_snapshot_events.iter() {
+        let Ok((character_info, game_client, requester_position)) =
+            query_requester.get(event.entity)
+        else {
+            continue;
+        };
+        let zone_id = requester_position.zone_id;
+
+        let snapshot = ZoneSnapshot {
+            zone_id,
+            created: Utc::now(),
+            created_by: character_info.name.clone(),
+            entities: query_entities
+                .iter()
+                .filter(|item| item.position.zone_id == zone_id)
+                .map(|item| snapshot_entity(&game_data, &item))
+                .collect(),
+        };
+
+        let text = match snapshot.save() {
+            Ok(path) => {
+                info!(
+                    "{} saved a snapshot of {} entities in zone {} to {}",
+                    character_info.name,
+                    snapshot.entities.len(),
+                    zone_id.get(),
+                    path.to_string_lossy()
+                );
+                format!(
+                    "Saved {} entities in zone {} to {}",
+                    snapshot.entities.len(),
+                    zone_id.get(),
+                    path.to_string_lossy()
+                )
+            }
+            Err(error) => {
+                error!(
+                    "Failed to save snapshot of zone {} with error {:?}",
+                    zone_id.get(),
+                    error
+                );
+                format!("Failed to save zone snapshot: {}", error)
+            }
+        };
+
+        game_client
+            .server_message_tx
+            .send(ServerMessage::Whisper {
+                from: String::from("SERVER"),
+                text,
+            })
+            .ok();
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::ecs::prelude::{Commands, Entity, Query, Res, ResMut, With};
+
+use crate::game::{
+    bundles::MonsterBundle,
+    components::{ClientEntity, Level, Position, SpawnOrigin, Team, ZoneTransition},
+    resources::{ClientEntityList, GameData},
+};
+
+/// How far from their owner summons are spawned in the new zone
+const ZONE_TRANSITION_SUMMON_SPAWN_RANGE: i32 = 150;
+
+/// Re-spawns the summons of characters which have finished joining the zone
+/// they teleported to.
+pub fn zone_transition_system(
+    mut commands: Commands,
+    mut client_entity_list: ResMut<ClientEntityList>,
+    mut query: Query<(Entity, &mut ZoneTransition, &Position, &Level, &Team), With<ClientEntity>>,
+    game_data: Res<GameData>,
+) {
+    for (entity, mut zone_transition, position, level, team) in query.iter_mut() {
+        if position.zone_id != zone_transition.zone_id {
+            continue;
+        }
+
+        for summon in zone_transition.summons.drain(..) {
+            let Some(summon_entity) = MonsterBundle::spawn(
+                &mut commands,
+                &mut client_entity_list,
+                &game_data,
+                summon.npc_id,
+                position.zone_id,
+                SpawnOrigin::Summoned(entity, position.position),
+                ZONE_TRANSITION_SUMMON_SPAWN_RANGE,
+                team.clone(),
+                Some((entity, level)),
+                summon.summon_skill_level,
+                None,
+            ) else {
+                continue;
+            };
+
+            commands.entity(summon_entity).insert((
+                summon.health_points,
+                summon.status_effects,
+                summon.status_effects_regen,
+            ));
+        }
+
+        commands.entity(entity).remove::<ZoneTransition>();
+    }
+}
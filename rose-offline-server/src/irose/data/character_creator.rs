@@ -12,11 +12,11 @@ use rose_file_readers::{stb_column, StbFile, VirtualFilesystem};
 
 use crate::game::{
     components::{
-        BasicStats, CharacterInfo, Equipment, ExperiencePoints, HealthPoints, Hotbar, Inventory,
-        Level, ManaPoints, Position, QuestState, SkillList, SkillPoints, Stamina, StatPoints,
-        UnionMembership,
+        Achievements, BasicStats, CharacterInfo, Equipment, ExperiencePoints, HealthPoints,
+        HonorPoints, Hotbar, Inventory, Level, ManaPoints, Position, QuestState, Rebirth,
+        SkillList, SkillPoints, Stamina, StatPoints, Statistics, UnionMembership,
     },
-    storage::character::{CharacterCreator, CharacterCreatorError, CharacterStorage},
+    storage::character::{CharacterCreator, CharacterCreatorError, CharacterStorage, SavedEffects},
 };
 
 struct CharacterGenderData {
@@ -158,6 +158,12 @@ impl CharacterCreator for CharacterCreatorData {
             quest_state: QuestState::default(),
             union_membership: UnionMembership::default(),
             stamina: Stamina::default(),
+            achievements: Achievements::default(),
+            statistics: Statistics::default(),
+            rebirth: Rebirth::default(),
+            honor_points: HonorPoints::default(),
+            saved_effects: SavedEffects::default(),
+            offline_vendor_proceeds: None,
         };
 
         for &skill_id in &self.skills {
@@ -0,0 +1,137 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use rose_data::{ZoneGeometryDatabase, ZoneNavGridDatabase};
+use rose_file_readers::{VfsFile, VirtualFilesystem};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Increase whenever the layout of the cached data changes so old caches are
+/// rebuilt instead of failing to read.
+const GAME_DATA_CACHE_VERSION: u32 = 2;
+
+#[derive(Debug, Error)]
+pub enum GameDataCacheError {
+    #[error("Failed to access {path}: {error}")]
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    #[error("Failed to decode {path}: {error}")]
+    Bincode {
+        path: PathBuf,
+        error: bincode::Error,
+    },
+    #[error("Cache version {0} does not match {GAME_DATA_CACHE_VERSION}")]
+    Version(u32),
+    #[error("Source file {0} has changed")]
+    SourceChanged(String),
+}
+
+/// The game data which is slow to derive from the source files, with the
+/// hash of every file it was derived from.
+#[derive(Deserialize)]
+struct GameDataCache {
+    version: u32,
+    source_files: Vec<(String, [u8; 32])>,
+    zone_geometry: ZoneGeometryDatabase,
+    zone_nav_grids: ZoneNavGridDatabase,
+}
+
+/// Serialises with the same layout as `GameDataCache` without taking
+/// ownership of the databases.
+#[derive(Serialize)]
+struct GameDataCacheRef<'a> {
+    version: u32,
+    source_files: &'a [(String, [u8; 32])],
+    zone_geometry: &'a ZoneGeometryDatabase,
+    zone_nav_grids: &'a ZoneNavGridDatabase,
+}
+
+fn hash_file(vfs: &VirtualFilesystem, path: &str) -> Option<[u8; 32]> {
+    let file = vfs.open_file(path).ok()?;
+    let data: &[u8] = match &file {
+        VfsFile::Buffer(buffer) => buffer,
+        VfsFile::View(view) => view,
+    };
+    Some(Sha256::digest(data).into())
+}
+
+/// Reads the cached zone geometry and nav grids, which are only used if none
+/// of the files they were derived from have changed.
+pub fn load_game_data_cache(
+    path: &Path,
+    vfs: &VirtualFilesystem,
+) -> Result<(ZoneGeometryDatabase, ZoneNavGridDatabase), GameDataCacheError> {
+    let file = File::open(path).map_err(|error| GameDataCacheError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    let cache: GameDataCache =
+        bincode::deserialize_from(BufReader::new(file)).map_err(|error| {
+            GameDataCacheError::Bincode {
+                path: path.to_path_buf(),
+                error,
+            }
+        })?;
+
+    if cache.version != GAME_DATA_CACHE_VERSION {
+        return Err(GameDataCacheError::Version(cache.version));
+    }
+
+    for (source_path, hash) in cache.source_files.iter() {
+        if hash_file(vfs, source_path).as_ref() != Some(hash) {
+            return Err(GameDataCacheError::SourceChanged(source_path.clone()));
+        }
+    }
+
+    Ok((cache.zone_geometry, cache.zone_nav_grids))
+}
+
+/// Writes the zone geometry and nav grids to the cache, with the hashes of
+/// the source files which were read while loading them.
+pub fn save_game_data_cache(
+    path: &Path,
+    vfs: &VirtualFilesystem,
+    source_paths: &[PathBuf],
+    zone_geometry: &ZoneGeometryDatabase,
+    zone_nav_grids: &ZoneNavGridDatabase,
+) -> Result<(), GameDataCacheError> {
+    let source_files: Vec<(String, [u8; 32])> = source_paths
+        .iter()
+        .filter_map(|source_path| {
+            let source_path = source_path.to_string_lossy().into_owned();
+            let hash = hash_file(vfs, &source_path)?;
+            Some((source_path, hash))
+        })
+        .collect();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| GameDataCacheError::Io {
+            path: parent.to_path_buf(),
+            error,
+        })?;
+    }
+
+    let file = File::create(path).map_err(|error| GameDataCacheError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    bincode::serialize_into(
+        BufWriter::new(file),
+        &GameDataCacheRef {
+            version: GAME_DATA_CACHE_VERSION,
+            source_files: &source_files,
+            zone_geometry,
+            zone_nav_grids,
+        },
+    )
+    .map_err(|error| GameDataCacheError::Bincode {
+        path: path.to_path_buf(),
+        error,
+    })
+}
@@ -1,10 +1,13 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc, time::Instant};
 
-use rose_data::{CharacterMotionDatabaseOptions, NpcDatabaseOptions};
+use rose_data::{
+    CharacterMotionDatabaseOptions, NpcDatabaseOptions, ZoneGeometryDatabase, ZoneNavGridDatabase,
+};
 use rose_data_irose::{
     get_ai_database, get_character_motion_database, get_data_decoder, get_item_database,
     get_job_class_database, get_npc_database, get_quest_database, get_skill_database,
     get_status_effect_database, get_string_database, get_warp_gate_database, get_zone_database,
+    get_zone_geometry_database, get_zone_nav_grid_database,
 };
 use rose_file_readers::VirtualFilesystem;
 use rose_game_irose::data::{get_ability_value_calculator, get_drop_table};
@@ -12,77 +15,191 @@ use rose_game_irose::data::{get_ability_value_calculator, get_drop_table};
 use crate::game::GameData;
 
 mod character_creator;
+mod game_data_cache;
 use character_creator::get_character_creator;
+use game_data_cache::{load_game_data_cache, save_game_data_cache};
 
-pub fn get_game_data(vfs: &VirtualFilesystem) -> GameData {
-    let string_database = get_string_database(vfs, 1).expect("Failed to load string database");
-    let item_database = Arc::new(
-        get_item_database(vfs, string_database.clone()).expect("Failed to load item database"),
-    );
-    let npc_database = Arc::new(
-        get_npc_database(
-            vfs,
-            string_database.clone(),
-            &NpcDatabaseOptions {
-                load_frame_data: true,
-            },
-        )
-        .expect("Failed to load npc database"),
-    );
-    let job_class_database = Arc::new(
-        get_job_class_database(vfs, string_database.clone())
-            .expect("Failed to load job class database"),
-    );
-    let skill_database = Arc::new(
-        get_skill_database(vfs, string_database.clone()).expect("Failed to load skill database"),
-    );
-    let zone_database = Arc::new(
-        get_zone_database(vfs, string_database.clone()).expect("Failed to load zone database"),
-    );
-    let drop_table = get_drop_table(vfs, item_database.clone(), npc_database.clone())
-        .expect("Failed to load drop table");
+/// Loads a database and logs how long it took.
+fn load_database<T>(name: &str, load: impl FnOnce() -> Result<T, anyhow::Error>) -> T {
+    let started_load = Instant::now();
+    let database = load().unwrap_or_else(|error| panic!("Failed to load {}: {:?}", name, error));
+    log::info!("Loaded {} in {:?}", name, started_load.elapsed());
+    database
+}
+
+pub struct GameDataOptions<'a> {
+    /// Do not load the zone geometry and nav grids, which disables line of
+    /// sight and path checks but makes restarts during development faster
+    pub skip_optional_data: bool,
+
+    /// Read the zone geometry and nav grids from the cache here if none of
+    /// their source files have changed, otherwise write them to it after
+    /// loading
+    pub cache_path: Option<&'a Path>,
+
+    /// The STL language column used for names, with the fallback language
+    /// used for any missing names
+    pub language: usize,
+    pub fallback_language: usize,
+
+    /// More STL language columns which clients can choose
+    pub client_languages: &'a [usize],
+}
 
-    GameData {
-        character_creator: get_character_creator(
+/// Loads the game data, the databases which only depend on the string
+/// database are loaded in parallel.
+pub fn get_game_data(vfs: &VirtualFilesystem, options: &GameDataOptions) -> GameData {
+    let skip_optional_data = options.skip_optional_data;
+    let cache_path = options.cache_path.filter(|_| !skip_optional_data);
+    let mut cached_zones = None;
+    if let Some(cache_path) = cache_path {
+        match load_game_data_cache(cache_path, vfs) {
+            Ok(zones) => {
+                log::info!("Loaded zone data from cache {}", cache_path.display());
+                cached_zones = Some(zones);
+            }
+            Err(error) => {
+                log::info!("Rebuilding game data cache: {}", error);
+
+                // The files read by every database are recorded, as they are
+                // loaded in parallel with the zone data
+                vfs.start_recording();
+            }
+        }
+    }
+    let has_cached_zones = cached_zones.is_some();
+    let (cached_zone_geometry, cached_zone_nav_grids) = cached_zones.unzip();
+
+    let string_database = load_database("string database", || {
+        get_string_database(
             vfs,
-            item_database.clone(),
-            skill_database.clone(),
-            &zone_database,
+            options.language,
+            options.fallback_language,
+            options.client_languages,
         )
-        .expect("Failed to get character creator"),
-        ability_value_calculator: get_ability_value_calculator(
-            item_database.clone(),
-            skill_database.clone(),
-            npc_database.clone(),
-        ),
-        data_decoder: get_data_decoder(),
-        drop_table,
-        ai: Arc::new(get_ai_database(vfs).expect("Failed to load AI database")),
-        items: item_database,
-        job_class: job_class_database,
-        motions: Arc::new(
-            get_character_motion_database(
+    });
+
+    std::thread::scope(|scope| {
+        let items = scope
+            .spawn(|| load_database("item database", || get_item_database(vfs, &string_database)));
+        let npcs = scope.spawn(|| {
+            load_database("npc database", || {
+                get_npc_database(
+                    vfs,
+                    &string_database,
+                    &NpcDatabaseOptions {
+                        load_frame_data: true,
+                    },
+                )
+            })
+        });
+        let job_class = scope.spawn(|| {
+            load_database("job class database", || {
+                get_job_class_database(vfs, &string_database)
+            })
+        });
+        let skills = scope.spawn(|| {
+            load_database("skill database", || {
+                get_skill_database(vfs, &string_database)
+            })
+        });
+        let zones = scope
+            .spawn(|| load_database("zone database", || get_zone_database(vfs, &string_database)));
+        let ai = scope.spawn(|| load_database("AI database", || get_ai_database(vfs)));
+        let motions = scope.spawn(|| {
+            load_database("motion database", || {
+                get_character_motion_database(
+                    vfs,
+                    &CharacterMotionDatabaseOptions {
+                        load_frame_data: true,
+                    },
+                )
+            })
+        });
+        let quests = scope.spawn(|| {
+            load_database("quest database", || {
+                get_quest_database(vfs, &string_database)
+            })
+        });
+        let status_effects = scope.spawn(|| {
+            load_database("status effect database", || {
+                get_status_effect_database(vfs, &string_database)
+            })
+        });
+        let warp_gates =
+            scope.spawn(|| load_database("warp gate database", || get_warp_gate_database(vfs)));
+        let zone_geometry = scope.spawn(|| {
+            if skip_optional_data {
+                log::info!("Skipped loading zone geometry database");
+                ZoneGeometryDatabase::new(Vec::new())
+            } else if let Some(zone_geometry) = cached_zone_geometry {
+                zone_geometry
+            } else {
+                load_database("zone geometry database", || get_zone_geometry_database(vfs))
+            }
+        });
+        let zone_nav_grids = scope.spawn(|| {
+            if skip_optional_data {
+                log::info!("Skipped loading zone nav grid database");
+                ZoneNavGridDatabase::new(Vec::new())
+            } else if let Some(zone_nav_grids) = cached_zone_nav_grids {
+                zone_nav_grids
+            } else {
+                load_database("zone nav grid database", || get_zone_nav_grid_database(vfs))
+            }
+        });
+
+        let item_database = Arc::new(items.join().unwrap());
+        let npc_database = Arc::new(npcs.join().unwrap());
+        let skill_database = Arc::new(skills.join().unwrap());
+        let zone_database = Arc::new(zones.join().unwrap());
+        let drop_table = get_drop_table(vfs, item_database.clone(), npc_database.clone())
+            .expect("Failed to load drop table");
+        let zone_geometry = Arc::new(zone_geometry.join().unwrap());
+        let zone_nav_grids = Arc::new(zone_nav_grids.join().unwrap());
+
+        if let Some(cache_path) = cache_path.filter(|_| !has_cached_zones) {
+            let source_paths = vfs.finish_recording();
+            match save_game_data_cache(
+                cache_path,
+                vfs,
+                &source_paths,
+                &zone_geometry,
+                &zone_nav_grids,
+            ) {
+                Ok(()) => log::info!("Saved game data cache {}", cache_path.display()),
+                Err(error) => log::warn!("Failed to save game data cache: {}", error),
+            }
+        }
+
+        GameData {
+            character_creator: get_character_creator(
                 vfs,
-                &CharacterMotionDatabaseOptions {
-                    load_frame_data: true,
-                },
+                item_database.clone(),
+                skill_database.clone(),
+                &zone_database,
             )
-            .expect("Failed to load motion database"),
-        ),
-        npcs: npc_database,
-        quests: Arc::new(
-            get_quest_database(vfs, string_database.clone())
-                .expect("Failed to load quest database"),
-        ),
-        skills: skill_database,
-        status_effects: Arc::new(
-            get_status_effect_database(vfs, string_database.clone())
-                .expect("Failed to load status effect database"),
-        ),
-        string_database,
-        warp_gates: Arc::new(
-            get_warp_gate_database(vfs).expect("Failed to load warp gate database"),
-        ),
-        zones: zone_database,
-    }
+            .expect("Failed to get character creator"),
+            ability_value_calculator: get_ability_value_calculator(
+                item_database.clone(),
+                skill_database.clone(),
+                npc_database.clone(),
+            ),
+            data_decoder: get_data_decoder(),
+            drop_table,
+            ai: Arc::new(ai.join().unwrap()),
+            items: item_database,
+            job_class: Arc::new(job_class.join().unwrap()),
+            motions: Arc::new(motions.join().unwrap()),
+            npcs: npc_database,
+            quests: Arc::new(quests.join().unwrap()),
+            skills: skill_database,
+            status_effects: Arc::new(status_effects.join().unwrap()),
+            string_database: string_database.clone(),
+            warp_gates: Arc::new(warp_gates.join().unwrap()),
+            zones: zone_database,
+            zone_geometry,
+            zone_nav_grids,
+        }
+    })
 }
@@ -1,5 +1,5 @@
 mod data;
-mod protocol;
+pub(crate) mod protocol;
 
-pub use data::get_game_data;
-pub use protocol::{game_protocol, login_protocol, world_protocol};
+pub use data::{get_game_data, GameDataOptions};
+pub use protocol::protocols;
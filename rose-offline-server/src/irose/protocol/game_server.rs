@@ -1,13 +1,20 @@
 use async_trait::async_trait;
 use log::warn;
 use num_traits::FromPrimitive;
-use std::convert::TryFrom;
+use std::{collections::HashMap, convert::TryFrom};
 
-use rose_data::QuestTriggerHash;
+use rose_data::{QuestTriggerHash, ZoneId, ZoneWeather};
 use rose_game_common::{
-    components::MoveMode,
+    components::{CharacterUniqueId, MoveMode},
     data::Password,
-    messages::{client::ClientMessage, server::ServerMessage},
+    messages::{
+        client::ClientMessage,
+        server::{
+            BarbershopError, ClanBankError, ClanUpdateError, ClanWarError, ClanWarResult,
+            NpcStoreTransactionError, ServerMessage, WarpGateError,
+        },
+        ClientEntityId,
+    },
 };
 use rose_network_common::Packet;
 use rose_network_irose::{game_client_packets::*, game_server_packets::*};
@@ -17,11 +24,39 @@ use crate::{
     protocol::{Client, ProtocolServer, ProtocolServerError},
 };
 
-pub struct GameServer;
+pub struct GameServer {
+    // The irose client has no weather, so changes to it are sent as whispers
+    weather: ZoneWeather,
+
+    // The irose client has no world map markers for party members in other
+    // zones, so when a member changes zone it is sent as a whisper
+    party_member_zones: HashMap<CharacterUniqueId, ZoneId>,
+
+    // The irose client has no block flag, so when our character blocks or is
+    // blocked it is sent as a whisper
+    entity_id: Option<ClientEntityId>,
+}
+
+/// Sends a message which has no irose packet as a whisper from the server, so
+/// it is still shown by the client.
+async fn write_server_whisper(client: &mut Client<'_>, text: &str) -> Result<(), anyhow::Error> {
+    client
+        .connection
+        .write_packet(Packet::from(&PacketServerWhisper {
+            from: "SERVER",
+            text,
+        }))
+        .await?;
+    Ok(())
+}
 
 impl GameServer {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            weather: ZoneWeather::Clear,
+            party_member_zones: HashMap::new(),
+            entity_id: None,
+        }
     }
 
     async fn handle_packet(
@@ -51,6 +86,12 @@ impl GameServer {
                     text: String::from(packet.text),
                 })?;
             }
+            Some(ClientPackets::ShoutChat) => {
+                let packet = PacketClientShoutChat::try_from(packet)?;
+                client.client_message_tx.send(ClientMessage::ShoutChat {
+                    text: String::from(packet.text),
+                })?;
+            }
             Some(ClientPackets::Move) => {
                 let packet = PacketClientMove::try_from(packet)?;
                 client.client_message_tx.send(ClientMessage::Move {
@@ -148,8 +189,10 @@ impl GameServer {
                 let packet = PacketClientQuestRequest::try_from(packet)?;
                 match packet.request_type {
                     PacketClientQuestRequestType::DoTrigger => {
+                        // The iROSE client has no way to choose a quest reward
                         client.client_message_tx.send(ClientMessage::QuestTrigger {
                             trigger: QuestTriggerHash::new(packet.quest_id),
+                            reward_choice: None,
                         })?;
                     }
                     PacketClientQuestRequestType::DeleteQuest => {
@@ -178,6 +221,16 @@ impl GameServer {
                         buy_item: packet.buy_item,
                     })?;
             }
+            Some(ClientPackets::PersonalStoreOpen) => {
+                let packet = PacketClientPersonalStoreOpen::try_from(packet)?;
+                client
+                    .client_message_tx
+                    .send(ClientMessage::PersonalStoreOpen {
+                        title: String::from(packet.title),
+                        sell_items: packet.sell_items,
+                        buy_items: packet.buy_items,
+                    })?;
+            }
             Some(ClientPackets::DropItemFromInventory) => {
                 let packet = PacketClientDropItemFromInventory::try_from(packet)?;
                 match packet {
@@ -430,6 +483,14 @@ impl GameServer {
                     mark,
                 })?,
             },
+            Some(ClientPackets::ClientIntegrityResponse) => {
+                let packet = PacketClientClientIntegrityResponse::try_from(packet)?;
+                client
+                    .client_message_tx
+                    .send(ClientMessage::ClientIntegrityResponse {
+                        hashes: packet.hashes,
+                    })?;
+            }
             _ => warn!(
                 "[GS] Unhandled packet [{:#03X}] {:02x?}",
                 packet.command,
@@ -516,6 +577,7 @@ impl GameServer {
                 item_price_rate,
                 town_price_rate,
             } => {
+                self.entity_id = Some(entity_id);
                 client
                     .connection
                     .write_packet(Packet::from(&PacketServerJoinZone {
@@ -581,35 +643,45 @@ impl GameServer {
                 is_killed,
                 is_immediate,
                 from_skill,
-            } => match from_skill {
-                None => {
-                    client
-                        .connection
-                        .write_packet(Packet::from(&PacketServerDamageEntity {
-                            attacker_entity_id,
-                            defender_entity_id,
-                            damage,
-                            is_killed,
-                            is_immediate,
-                        }))
-                        .await?;
+            } => {
+                match from_skill {
+                    None => {
+                        client
+                            .connection
+                            .write_packet(Packet::from(&PacketServerDamageEntity {
+                                attacker_entity_id,
+                                defender_entity_id,
+                                damage,
+                                is_killed,
+                                is_immediate,
+                            }))
+                            .await?;
+                    }
+                    Some((skill_id, caster_intelligence)) => {
+                        client
+                            .connection
+                            .write_packet(Packet::from(&PacketServerApplySkillDamage {
+                                entity_id: defender_entity_id,
+                                caster_entity_id: attacker_entity_id,
+                                caster_intelligence,
+                                skill_id,
+                                effect_success: [false, false],
+                                damage,
+                                is_killed,
+                                is_immediate,
+                            }))
+                            .await?;
+                    }
                 }
-                Some((skill_id, caster_intelligence)) => {
-                    client
-                        .connection
-                        .write_packet(Packet::from(&PacketServerApplySkillDamage {
-                            entity_id: defender_entity_id,
-                            caster_entity_id: attacker_entity_id,
-                            caster_intelligence,
-                            skill_id,
-                            effect_success: [false, false],
-                            damage,
-                            is_killed,
-                            is_immediate,
-                        }))
-                        .await?;
+
+                if damage.is_blocked {
+                    if Some(defender_entity_id) == self.entity_id {
+                        write_server_whisper(client, "You blocked the attack").await?;
+                    } else if Some(attacker_entity_id) == self.entity_id {
+                        write_server_whisper(client, "Your attack was blocked").await?;
+                    }
                 }
-            },
+            }
             ServerMessage::StopMoveEntity { entity_id, x, y, z } => {
                 client
                     .connection
@@ -745,6 +817,8 @@ impl GameServer {
                 spawn_command_state: command,
                 move_mode,
                 status_effects,
+                // The irose spawn packet has no elite flag
+                is_elite: _,
             } => {
                 client
                     .connection
@@ -823,6 +897,7 @@ impl GameServer {
                 entity_id,
                 run_speed,
                 passive_attack_speed,
+                weight_rate,
             } => {
                 client
                     .connection
@@ -830,6 +905,7 @@ impl GameServer {
                         entity_id,
                         run_speed,
                         passive_attack_speed,
+                        weight_rate,
                     }))
                     .await?;
             }
@@ -1266,6 +1342,17 @@ impl GameServer {
                     }))
                     .await?;
             }
+            // The irose client has no errors for these, so they are sent as whispers
+            ServerMessage::NpcStoreTransactionError {
+                error: NpcStoreTransactionError::StoreClosed,
+            } => {
+                write_server_whisper(client, "This store is closed").await?;
+            }
+            ServerMessage::NpcStoreTransactionError {
+                error: NpcStoreTransactionError::OutOfStock,
+            } => {
+                write_server_whisper(client, "This item is out of stock").await?;
+            }
             ServerMessage::NpcStoreTransactionError { error } => {
                 client
                     .connection
@@ -1637,6 +1724,220 @@ impl GameServer {
                     }))
                     .await?;
             }
+            ServerMessage::BarbershopPreview { face, hair, price } => {
+                write_server_whisper(
+                    client,
+                    &format!(
+                        "Face {} and hair {} cost {} zuly, use /barbershop confirm to buy or /barbershop cancel",
+                        face, hair, price.0
+                    ),
+                )
+                .await?;
+            }
+            ServerMessage::BarbershopError { error } => {
+                let text = match error {
+                    BarbershopError::NpcTooFarAway => "You are too far away from the barber",
+                    BarbershopError::InvalidAppearance => "That face or hair does not exist",
+                    BarbershopError::NotEnoughMoney => "You do not have enough zuly",
+                    BarbershopError::NoPreview => {
+                        "Preview a new look with /barbershop preview <face> <hair> first"
+                    }
+                    BarbershopError::PreviewExpired => "Your barbershop preview has expired",
+                    BarbershopError::ZoneChanged => "Your barbershop preview was cancelled",
+                };
+                write_server_whisper(client, text).await?;
+            }
+            ServerMessage::PersonalStoreSearchResults { results } => {
+                if results.is_empty() {
+                    write_server_whisper(client, "No personal stores are selling that item")
+                        .await?;
+                }
+
+                for result in results {
+                    for (slot_index, item, price) in result.sell_items {
+                        write_server_whisper(
+                            client,
+                            &format!(
+                                "{} \"{}\" at ({:.0}, {:.0}) slot {}: {} for {} zuly",
+                                result.seller_name,
+                                result.title,
+                                result.position.x,
+                                result.position.y,
+                                slot_index + 1,
+                                item.get_quantity(),
+                                price.0
+                            ),
+                        )
+                        .await?;
+                    }
+                }
+            }
+            ServerMessage::ClanWarDeclared { clan_name } => {
+                write_server_whisper(
+                    client,
+                    &format!(
+                        "Clan {} has declared war on your clan, use /clanwar accept \"{}\" to fight",
+                        clan_name, clan_name
+                    ),
+                )
+                .await?;
+            }
+            ServerMessage::ClanWarStarted {
+                clan_name,
+                duration,
+            } => {
+                write_server_whisper(
+                    client,
+                    &format!(
+                        "Your clan is at war with {} for {} minutes",
+                        clan_name,
+                        duration.as_secs() / 60
+                    ),
+                )
+                .await?;
+            }
+            ServerMessage::ClanWarScore {
+                score,
+                opponent_score,
+            } => {
+                write_server_whisper(
+                    client,
+                    &format!("Clan war score: {} to {}", score, opponent_score),
+                )
+                .await?;
+            }
+            ServerMessage::ClanWarEnded {
+                clan_name,
+                result,
+                score,
+                opponent_score,
+            } => {
+                let result = match result {
+                    ClanWarResult::Won => "won",
+                    ClanWarResult::Lost => "lost",
+                    ClanWarResult::Draw => "drew",
+                };
+                write_server_whisper(
+                    client,
+                    &format!(
+                        "Your clan {} the war with {} by {} to {}",
+                        result, clan_name, score, opponent_score
+                    ),
+                )
+                .await?;
+            }
+            ServerMessage::ClanWarError { error } => {
+                let text = match error {
+                    ClanWarError::NoPermission => {
+                        "Only the clan master or deputy can declare or accept a war"
+                    }
+                    ClanWarError::InvalidClan => "That clan does not exist",
+                    ClanWarError::AlreadyAtWar => "One of the clans is already at war",
+                    ClanWarError::NotDeclared => "That clan has not declared war on your clan",
+                };
+                write_server_whisper(client, text).await?;
+            }
+            ServerMessage::ClanNotice { notice } => {
+                write_server_whisper(client, &format!("Clan notice: {}", notice)).await?;
+            }
+            ServerMessage::ClanUpdateError { error } => {
+                let text = match error {
+                    ClanUpdateError::NoPermission => {
+                        "Only the clan master or deputy can change the clan"
+                    }
+                    ClanUpdateError::InvalidDescription => "That clan description is too long",
+                    ClanUpdateError::InvalidNotice => "That clan notice is too long",
+                    ClanUpdateError::InvalidMark => "That clan mark is not available",
+                };
+                write_server_whisper(client, text).await?;
+            }
+            ServerMessage::ClanBankTransaction {
+                inventory_item_slot,
+                inventory_item,
+                ..
+            } => {
+                client
+                    .connection
+                    .write_packet(Packet::from(&PacketServerUpdateInventory {
+                        items: vec![(inventory_item_slot, inventory_item)],
+                        with_money: None,
+                    }))
+                    .await?;
+            }
+            ServerMessage::ClanBankError { error } => {
+                let text = match error {
+                    ClanBankError::NotInClan => "You are not in a clan",
+                    ClanBankError::NoPermission => "Your clan position does not allow that",
+                    ClanBankError::InvalidItem => "That item is no longer there",
+                    ClanBankError::BankFull => "The clan bank is full",
+                    ClanBankError::InventoryFull => "Your inventory is full",
+                    ClanBankError::ItemBound => "Bound items cannot be stored in the clan bank",
+                };
+                write_server_whisper(client, text).await?;
+            }
+            ServerMessage::ClientIntegrityChallenge { paths } => {
+                client
+                    .connection
+                    .write_packet(Packet::from(&PacketServerClientIntegrityChallenge {
+                        paths: paths.iter().map(String::as_str).collect(),
+                    }))
+                    .await?;
+            }
+            ServerMessage::UpdateZoneEnvironment { weather, .. } => {
+                // The irose client calculates the time of day from the world time
+                // sent when joining the zone
+                if std::mem::replace(&mut self.weather, weather) != weather {
+                    let text = match weather {
+                        ZoneWeather::Clear => "The weather has cleared",
+                        ZoneWeather::Rain => "It has started to rain",
+                        ZoneWeather::Snow => "It has started to snow",
+                        ZoneWeather::Fog => "A fog has rolled in",
+                    };
+                    write_server_whisper(client, text).await?;
+                }
+            }
+            ServerMessage::WarpGateError { error } => {
+                let text = match error {
+                    WarpGateError::NotFound => "This warp gate does not lead anywhere",
+                    WarpGateError::TooFarAway => "You are too far away from the warp gate",
+                    WarpGateError::LevelTooLow => "Your level is too low to use this warp gate",
+                    WarpGateError::QuestNotCompleted => {
+                        "You must complete a quest to use this warp gate"
+                    }
+                    WarpGateError::NotEnoughMoney => {
+                        "You do not have enough money to use this warp gate"
+                    }
+                };
+                write_server_whisper(client, text).await?;
+            }
+            ServerMessage::PartyMemberMapMarkers { markers } => {
+                for marker in markers {
+                    if self
+                        .party_member_zones
+                        .insert(marker.character_id, marker.zone_id)
+                        == Some(marker.zone_id)
+                    {
+                        continue;
+                    }
+
+                    write_server_whisper(
+                        client,
+                        &format!(
+                            "{} is in zone {} near ({}, {})",
+                            marker.name,
+                            marker.zone_id.get(),
+                            marker.position.x,
+                            marker.position.y
+                        ),
+                    )
+                    .await?;
+                }
+            }
+            // These messages are not supported by the irose protocol
+            ServerMessage::ClanBankOpen { .. }
+            | ServerMessage::ClanBankUpdateItems { .. }
+            | ServerMessage::ClanBankLog { .. }
+            | ServerMessage::UpdateRebirthCount { .. } => {}
             // These messages are for other servers
             ServerMessage::ReturnToCharacterSelect
             | ServerMessage::LoginSuccess { .. }
@@ -1654,6 +1955,7 @@ impl GameServer {
             | ServerMessage::DeleteCharacterError { .. }
             | ServerMessage::SelectCharacterSuccess { .. }
             | ServerMessage::SelectCharacterError
+            | ServerMessage::SecondaryPinRequest { .. }
             | ServerMessage::UpdateSkillList { .. } => {
                 panic!("Received unexpected server message for game server")
             }
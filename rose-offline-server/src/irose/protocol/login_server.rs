@@ -113,6 +113,12 @@ impl LoginServer {
                     LoginError::InvalidPassword => Packet::from(
                         &PacketServerLoginReply::with_error_result(LoginResult::InvalidPassword),
                     ),
+                    LoginError::Maintenance => Packet::from(
+                        &PacketServerLoginReply::with_error_result(LoginResult::NoRightToConnect),
+                    ),
+                    LoginError::OutsideRegion => Packet::from(
+                        &PacketServerLoginReply::with_error_result(LoginResult::OutsideRegion),
+                    ),
                 };
                 client.connection.write_packet(packet).await?;
             }
@@ -121,13 +127,13 @@ impl LoginServer {
                 channels,
             } => {
                 let mut channel_list: Vec<PacketServerChannelListItem> = Vec::new();
-                for (id, name) in &channels {
+                for channel in &channels {
                     channel_list.push(PacketServerChannelListItem {
-                        id: *id,
+                        id: channel.id,
                         low_age: 0u8,
                         high_age: 100u8,
-                        percent_full: 50u16,
-                        name,
+                        percent_full: channel.percent_full as u16,
+                        name: &channel.name,
                     });
                 }
 
@@ -174,6 +180,9 @@ impl LoginServer {
                     JoinServerError::InvalidChannelId => Packet::from(
                         &PacketServerSelectServer::with_result(SelectServerResult::Failed),
                     ),
+                    JoinServerError::ChannelFull => Packet::from(
+                        &PacketServerSelectServer::with_result(SelectServerResult::Full),
+                    ),
                 };
                 client.connection.write_packet(packet).await?;
             }
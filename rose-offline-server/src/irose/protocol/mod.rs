@@ -2,38 +2,69 @@ use std::sync::Arc;
 
 use rose_network_irose::{ServerPacketCodec, IROSE_112_TABLE};
 
-use crate::{game::messages::control::ClientType, protocol::Protocol};
+use crate::{
+    game::messages::control::ClientType,
+    protocol::{Protocol, ProtocolOptions, ProtocolServer, ProtocolSet},
+};
 
 mod game_server;
 mod login_server;
 mod world_server;
 
-use game_server::GameServer;
-use login_server::LoginServer;
-use world_server::WorldServer;
+pub(crate) use game_server::GameServer;
+pub(crate) use login_server::LoginServer;
+pub(crate) use world_server::WorldServer;
 
-pub fn login_protocol() -> Arc<Protocol> {
-    Arc::new(Protocol {
-        client_type: ClientType::Login,
-        packet_codec: Box::new(ServerPacketCodec::default(&IROSE_112_TABLE)),
-        create_server: || Box::new(LoginServer::new()),
-    })
-}
+/// Creates the protocols for a client version which uses the irose packet
+/// codec, so a protocol dialect only has to implement the packet encoding and
+/// decoding for each server.
+pub fn create_protocols(
+    crc_table: &'static [u8; 256],
+    create_login_server: fn() -> Box<dyn ProtocolServer + Send + Sync>,
+    create_world_server: fn() -> Box<dyn ProtocolServer + Send + Sync>,
+    create_game_server: fn() -> Box<dyn ProtocolServer + Send + Sync>,
+    options: ProtocolOptions,
+) -> ProtocolSet {
+    // These can be any non-zero value, they are only used by clients which do
+    // not use the per connection seed sent with their login token
+    let world_packet_codec_seed = rand::random::<u32>().max(1);
+    let game_packet_codec_seed = rand::random::<u32>().max(1);
 
-pub fn world_protocol() -> Arc<Protocol> {
-    let packet_codec_seed = 0x12345678; // This can be any non-zero value
-    Arc::new(Protocol {
-        client_type: ClientType::World,
-        packet_codec: Box::new(ServerPacketCodec::init(&IROSE_112_TABLE, packet_codec_seed)),
-        create_server: || Box::new(WorldServer::new()),
-    })
+    ProtocolSet {
+        login: Arc::new(Protocol {
+            client_type: ClientType::Login,
+            packet_codec: Box::new(ServerPacketCodec::default(crc_table)),
+            create_server: create_login_server,
+            create_packet_codec: None,
+            options: options.clone(),
+        }),
+        world: Arc::new(Protocol {
+            client_type: ClientType::World,
+            packet_codec: Box::new(ServerPacketCodec::init(crc_table, world_packet_codec_seed)),
+            create_server: create_world_server,
+            create_packet_codec: Some(Box::new(move |seed| {
+                Box::new(ServerPacketCodec::init(crc_table, seed))
+            })),
+            options: options.clone(),
+        }),
+        game: Arc::new(Protocol {
+            client_type: ClientType::Game,
+            packet_codec: Box::new(ServerPacketCodec::init(crc_table, game_packet_codec_seed)),
+            create_server: create_game_server,
+            create_packet_codec: Some(Box::new(move |seed| {
+                Box::new(ServerPacketCodec::init(crc_table, seed))
+            })),
+            options,
+        }),
+    }
 }
 
-pub fn game_protocol() -> Arc<Protocol> {
-    let packet_codec_seed = 0x87654321; // This can be any non-zero value
-    Arc::new(Protocol {
-        client_type: ClientType::Game,
-        packet_codec: Box::new(ServerPacketCodec::init(&IROSE_112_TABLE, packet_codec_seed)),
-        create_server: || Box::new(GameServer::new()),
-    })
+pub fn protocols(options: ProtocolOptions) -> ProtocolSet {
+    create_protocols(
+        &IROSE_112_TABLE,
+        || Box::new(LoginServer::new()),
+        || Box::new(WorldServer::new()),
+        || Box::new(GameServer::new()),
+        options,
+    )
 }
@@ -25,7 +25,7 @@ impl WorldServer {
         Self {}
     }
 
-    async fn handle_packet(
+    pub(crate) async fn handle_packet(
         &mut self,
         client: &mut Client<'_>,
         packet: &Packet,
@@ -56,7 +56,7 @@ impl WorldServer {
                         face: request.face as i32,
                         start_point: request.start_point as i32,
                         hair_color: 1,
-                        weapon_type: 0,
+                        weapon_type: request.weapon_type as i32,
                         name: String::from(request.name),
                     })?;
             }
@@ -79,6 +79,12 @@ impl WorldServer {
                         name: String::from(request.name),
                     })?;
             }
+            Some(ClientPackets::SecondaryPin) => {
+                let request = PacketClientSecondaryPin::try_from(packet)?;
+                client.client_message_tx.send(ClientMessage::SecondaryPin {
+                    pin: String::from(request.pin),
+                })?;
+            }
             Some(ClientPackets::ClanCommand) => match PacketClientClanCommand::try_from(packet)? {
                 PacketClientClanCommand::GetMemberList => client
                     .client_message_tx
@@ -97,7 +103,7 @@ impl WorldServer {
         Ok(())
     }
 
-    async fn handle_server_message(
+    pub(crate) async fn handle_server_message(
         &mut self,
         client: &mut Client<'_>,
         message: ServerMessage,
@@ -148,6 +154,12 @@ impl WorldServer {
             ServerMessage::SelectCharacterError => {
                 return Err(PacketError::InvalidPacket.into());
             }
+            ServerMessage::SecondaryPinRequest { error } => {
+                client
+                    .connection
+                    .write_packet(Packet::from(&PacketServerSecondaryPinRequest { error }))
+                    .await?;
+            }
             ServerMessage::CharacterList { character_list } => {
                 client
                     .connection
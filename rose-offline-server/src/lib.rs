@@ -6,6 +6,17 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::type_complexity)]
 
-mod game;
+pub mod game;
+pub mod irose;
+mod narose667;
+pub mod protocol;
 
-pub use game::components;
+pub use game::{
+    components, storage, AfkConfig, GameConfig, GameData, GameWorld, OfflineVendorConfig,
+    PacketCodecSeeds, SmtpConfig, StorageBackupConfig, WorldRates,
+};
+pub use protocol::{
+    remote_control::{RemoteControlClient, RemoteControlServer},
+    server::{ExternalAddress, GameServer, LoginServer, WorldServer},
+    ProtocolOptions, ProtocolType,
+};
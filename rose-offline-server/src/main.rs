@@ -6,13 +6,13 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::type_complexity)]
 
-mod game;
-mod irose;
-mod protocol;
+mod server_config;
 
 use std::{
-    path::{Path, PathBuf},
-    time::Instant,
+    collections::BTreeSet,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use clap::{Arg, Command};
@@ -22,23 +22,36 @@ use tokio::net::TcpListener;
 use tokio::runtime::Builder;
 
 use rose_file_readers::{
-    HostFilesystemDevice, VfsIndex, VirtualFilesystem, VirtualFilesystemDevice,
+    HostFilesystemDevice, TitanVfsIndex, VfsFile, VfsIndex, VfsPath, VirtualFilesystem,
+    VirtualFilesystemDevice,
 };
+use sha2::{Digest, Sha256};
 
-use crate::{
-    game::GameConfig,
-    protocol::server::{GameServer, LoginServer, WorldServer},
+use rose_offline_server::{
+    game::{
+        self, game_data_inspect,
+        messages::control::ControlMessage,
+        storage::{backup, character_inspection::CharacterInspection, quest_repair},
+        GameData, ItemSpawn, PacketCodecSeeds,
+    },
+    irose,
+    protocol::{
+        geo_ip::GeoIpDatabase,
+        proxy_protocol::TrustedProxy,
+        remote_control::{self, RemoteControlClient, RemoteControlServer},
+        server::{ExternalAddress, GameServer, LoginServer, WorldServer},
+        ProtocolOptions, ProtocolType,
+    },
 };
 
-pub enum ProtocolType {
-    Irose,
-}
+use crate::server_config::ServerConfig;
 
-impl Default for ProtocolType {
-    fn default() -> Self {
-        Self::Irose
-    }
-}
+// Game data is read from the mounted device with the highest priority which
+// contains the file
+const VFS_PRIORITY_OVERLAY: i32 = 30;
+const VFS_PRIORITY_DATA_PATH: i32 = 20;
+const VFS_PRIORITY_DATA_IDX: i32 = 10;
+const VFS_PRIORITY_DATA_IDX_ROOT: i32 = 0;
 
 async fn async_main() {
     TermLogger::init(
@@ -56,6 +69,17 @@ async fn async_main() {
     .expect("Failed to initialise logging");
 
     let mut command = Command::new("rose-offline")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Optional path to a TOML server config file, any other arguments override values from the file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("print-default-config")
+                .long("print-default-config")
+                .help("Print the default server config in TOML format and exit"),
+        )
         .arg(
             Arg::new("data-idx")
                 .long("data-idx")
@@ -68,66 +92,1018 @@ async fn async_main() {
                 .help("Optional path to extracted data, any files here override ones in data.idx")
                 .takes_value(true),
         )
+        .arg(
+            Arg::new("data-overlay")
+                .long("data-overlay")
+                .help("Optional path to extracted data which overrides data-path and data.idx, can be used multiple times with earlier overlays taking priority")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("skip-optional-data")
+                .long("skip-optional-data")
+                .help("Do not load the zone geometry and nav grids, which disables line of sight and path checks, for faster restarts during development"),
+        )
+        .arg(
+            Arg::new("game-data-cache")
+                .long("game-data-cache")
+                .help("Path to cache the zone geometry and nav grids, which is rebuilt when the game files they are loaded from change")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("language")
+                .long("language")
+                .help("STL language column used for game data names [default: 1]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("fallback-language")
+                .long("fallback-language")
+                .help("STL language column used for names which are missing in the selected language [default: 1]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("client-language")
+                .long("client-language")
+                .help("Another STL language column which players can choose with /language, can be used multiple times")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
         .arg(
             Arg::new("ip")
                 .long("ip")
-                .help("Listen IP used for login, world, game servers")
+                .help("Listen IP used for login, world, game servers [default: 127.0.0.1]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("additional-ip")
+                .long("additional-ip")
+                .help("Another IP to listen on, such as an IPv6 address, can be used multiple times")
                 .takes_value(true)
-                .default_value("127.0.0.1"),
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("world-external-ip")
+                .long("world-external-ip")
+                .help("The world server IP sent to clients, for servers behind NAT [default: the listen IP]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("world-external-port")
+                .long("world-external-port")
+                .help("The world server port sent to clients, for servers behind NAT [default: the world port]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("game-external-ip")
+                .long("game-external-ip")
+                .help("The game server IP sent to clients, for servers behind NAT [default: the listen IP]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("game-external-port")
+                .long("game-external-port")
+                .help("The game server port sent to clients, for servers behind NAT [default: the game port]")
+                .takes_value(true),
         )
         .arg(
             Arg::new("login-port")
                 .long("login-port")
-                .help("Port for login server")
-                .takes_value(true)
-                .default_value("29000"),
+                .help("Port for login server [default: 29000]")
+                .takes_value(true),
         )
         .arg(
             Arg::new("world-port")
                 .long("world-port")
-                .help("Port for world server")
-                .takes_value(true)
-                .default_value("29100"),
+                .help("Port for world server [default: 29100]")
+                .takes_value(true),
         )
         .arg(
             Arg::new("game-port")
                 .long("game-port")
-                .help("Port for login server")
-                .takes_value(true)
-                .default_value("29200"),
+                .help("Port for game server [default: 29200]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("login-websocket-port")
+                .long("login-websocket-port")
+                .help("Optional port for login server WebSocket connections")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("world-websocket-port")
+                .long("world-websocket-port")
+                .help("Optional port for world server WebSocket connections")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("game-websocket-port")
+                .long("game-websocket-port")
+                .help("Optional port for game server WebSocket connections")
+                .takes_value(true),
         )
         .arg(
             clap::Arg::new("protocol")
                 .long("protocol")
                 .takes_value(true)
-                .value_parser(["irose"])
-                .default_value("irose")
-                .help("Select which protocol to use."),
+                .value_parser(["irose", "narose667"])
+                .help("Select which protocol to use. [default: irose]"),
+        )
+        .arg(
+            Arg::new("packet-dump")
+                .long("packet-dump")
+                .help("Optional directory to write a log of the decrypted packets for each connection")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("strict-packet-codec")
+                .long("strict-packet-codec")
+                .help("Reject world and game connections which do not use their per connection packet codec seed"),
+        )
+        .arg(
+            Arg::new("proxy-protocol")
+                .long("proxy-protocol")
+                .help("Read the client address from the PROXY protocol v2 header a TCP load balancer sends at the start of each connection"),
+        )
+        .arg(
+            Arg::new("proxy-protocol-trusted")
+                .long("proxy-protocol-trusted")
+                .help("Address range in CIDR notation of a load balancer which is trusted to send the PROXY protocol header, can be used multiple times")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("geo-ip-database")
+                .long("geo-ip-database")
+                .help("Optional path to a CSV file of first_ip,last_ip,country_code ranges used to look up the country of login clients")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("no-login-server")
+                .long("no-login-server")
+                .help("Do not run a login server in this process"),
+        )
+        .arg(
+            Arg::new("no-world-server")
+                .long("no-world-server")
+                .help("Do not run a world server in this process"),
+        )
+        .arg(
+            Arg::new("no-game-server")
+                .long("no-game-server")
+                .help("Do not run a game server in this process"),
+        )
+        .arg(
+            Arg::new("control-listen")
+                .long("control-listen")
+                .help("Optional address to accept connections from server processes started with --control-connect, a port on its own listens on 127.0.0.1. Must never be reachable from the public internet")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("control-connect")
+                .long("control-connect")
+                .help("Optional address of the game world process started with --control-listen, the servers in this process forward their clients to it instead of running a game world")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("control-secret")
+                .long("control-secret")
+                .help("Shared secret required by --control-listen and --control-connect, every process must use the same secret")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("storage-dir")
+                .long("storage-dir")
+                .help("Optional directory where accounts, characters and clans are stored")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("storage-backup-dir")
+                .long("storage-backup-dir")
+                .help("Optional directory where storage backups are saved [default: backups in the storage directory]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("storage-backup-interval")
+                .long("storage-backup-interval")
+                .help("How often in minutes to back up all storage to a compressed JSON snapshot, 0 to disable [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("storage-backup-retention")
+                .long("storage-backup-retention")
+                .help("How many storage backups to keep, older backups are deleted [default: 24]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("reward-calendar")
+                .long("reward-calendar")
+                .help("Optional path to a JSON file configuring the daily login reward calendar")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("item-binding")
+                .long("item-binding")
+                .help(
+                    "Optional path to a JSON file configuring which items bind on equip or pickup",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("character-creation")
+                .long("character-creation")
+                .help("Optional path to a JSON file configuring character creation rules and starting kits")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("name-filter")
+                .long("name-filter")
+                .help("Optional path to a JSON file configuring the character and clan name filter")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("monster-spawn-scaling")
+                .long("monster-spawn-scaling")
+                .help("Optional path to a JSON file enabling monster spawn scaling by nearby character count")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("elite-monsters")
+                .long("elite-monsters")
+                .help("Optional path to a JSON file configuring elite monster variants")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("latency-compensation")
+                .long("latency-compensation")
+                .help("How many milliseconds of target position history to use for player attack and skill range checks, 0 to disable [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("item-drop-owner-duration")
+                .long("item-drop-owner-duration")
+                .help("How many seconds item drops can only be picked up by the killer or their party, 0 to make every drop free for all [default: 60]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("zone-environment")
+                .long("zone-environment")
+                .help("Optional path to a JSON file configuring zone weather and how the time of day and weather affect drops, spawns, and stores")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("zone-rules")
+                .long("zone-rules")
+                .help("Optional path to a JSON file configuring safe zones, towns where NPC stores and the bank can be used, and zone level requirements")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("teleport-gates")
+                .long("teleport-gates")
+                .help("Optional path to a JSON file configuring warp gate requirements, fees, and destinations")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("item-drops")
+                .long("item-drops")
+                .help("Optional path to a JSON file configuring how long item drops stay on the ground by rarity and how many each zone can hold")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("skill-chains")
+                .long("skill-chains")
+                .help("Optional path to a JSON file configuring skills which chain between or pierce through multiple targets")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("skill-movement-effects")
+                .long("skill-movement-effects")
+                .help("Optional path to a JSON file configuring skills which stun, root or knock back their targets")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("npc-store-stock")
+                .long("npc-store-stock")
+                .help("Optional path to a JSON file configuring NPC store items with limited stock and how often they are restocked")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("npc-store-currencies")
+                .long("npc-store-currencies")
+                .help("Optional path to a JSON file configuring NPC store tabs which are priced in union points, event tokens or honor points instead of zuly")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("chat-channels")
+                .long("chat-channels")
+                .help("Optional path to a JSON file configuring the level requirement and cooldown of the local, shout, trade and announce chat channels")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("chat-moderation")
+                .long("chat-moderation")
+                .help("Optional path to a JSON file configuring the banned chat words and spam detection")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("cheat-detection")
+                .long("cheat-detection")
+                .help("Optional path to a JSON file configuring the cheat detection weights, audit log and auto-kick thresholds")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("client-integrity")
+                .long("client-integrity")
+                .help("Optional path to a JSON file listing the game data files which clients are asked to hash when they join")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("geo-ip")
+                .long("geo-ip")
+                .help("Optional path to a JSON file restricting account creation and logins to countries, and choosing the world server IP sent to clients from each country")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("achievements")
+                .long("achievements")
+                .help("Optional path to a JSON file defining the achievements and the titles they unlock")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("level-up")
+                .long("level-up")
+                .help("Optional path to a JSON file overriding the xp required for each level and defining the rewards for reaching levels")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("bot-scenarios")
+                .long("bot-scenarios")
+                .help("Optional path to a JSON file defining groups of bots which can be started with the botscenario chat command")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("clan-creation")
+                .long("clan-creation")
+                .help("Optional path to a JSON file overriding the level, cost, item and name length required to create a clan")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("shared-quests")
+                .long("shared-quests")
+                .help("Optional path to a JSON file listing quests whose kill and collection progress is shared with nearby party or clan members")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("quest-rewards")
+                .long("quest-rewards")
+                .help("Optional path to a JSON file defining quest rewards chosen by the player or limited by job, gender and level")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("event-zones")
+                .long("event-zones")
+                .help("Optional path to a JSON file defining capture the flag and king of the hill events")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("invasions")
+                .long("invasions")
+                .help("Optional path to a JSON file defining the waves of monster invasions of towns")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("guards")
+                .long("guards")
+                .help("Optional path to a JSON file defining which targets guard NPCs attack")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("level-cap")
+                .long("level-cap")
+                .help("The highest level characters can reach, 0 for no cap [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("rebirth")
+                .long("rebirth")
+                .help("Optional path to a JSON file enabling rebirth, configuring which skills and items are kept and the bonus for each rebirth")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("disconnect-duplicate-login")
+                .long("disconnect-duplicate-login")
+                .help("Disconnect the existing session when an account logs in again, instead of rejecting the new login"),
+        )
+        .arg(
+            Arg::new("clear-effects-on-logout")
+                .long("clear-effects-on-logout")
+                .help("Forget status effects and skill cooldowns when a character logs out, instead of restoring them when it next joins the game"),
+        )
+        .arg(
+            Arg::new("reconnect-grace-period")
+                .long("reconnect-grace-period")
+                .help("How many seconds a character stays in the world after disconnecting, during which the player can reconnect to it, 0 to disable [default: 30]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("afk-timeout")
+                .long("afk-timeout")
+                .help("How many minutes without any client messages before a player is AFK and may be disconnected, 0 to disable [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("afk-kick-min-online")
+                .long("afk-kick-min-online")
+                .help("Only disconnect AFK players whilst at least this many players are online [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("offline-vendor-duration")
+                .long("offline-vendor-duration")
+                .help("How many minutes a personal store stays open after its owner disconnects, 0 to disable [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("channel-max-players")
+                .long("channel-max-players")
+                .help("How many players can connect to each channel before joining it is refused, 0 for no limit [default: 0]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("leaderboard-refresh-interval")
+                .long("leaderboard-refresh-interval")
+                .help("How many minutes between rebuilding the leaderboards from every stored character, 0 to disable [default: 10]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("tick-profiler")
+                .long("tick-profiler")
+                .help("Record the execution time of every system and log the slowest systems when a tick exceeds its 16.6ms budget"),
+        )
+        .subcommand(
+            Command::new("restore-backup")
+                .about("Restore all accounts, characters and clans from a storage backup, replacing any existing documents with the same name")
+                .arg(
+                    Arg::new("backup")
+                        .help("Path to the backup, or latest for the newest backup in the backup directory")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("inspect-character")
+                .about("Print everything stored for a character as JSON, including its inventory, bank, skills and quest state")
+                .arg(
+                    Arg::new("name")
+                        .help("Name of the character, which is not case sensitive")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("repair-quests")
+                .about("Check the active quests of every stored character against the game data, removing quests and quest items which no longer exist. The server must be stopped")
+                .arg(
+                    Arg::new("remap")
+                        .long("remap")
+                        .help("Optional path to a JSON object of old quest id to new quest id, for quests which were renumbered")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Print the repairs without saving any characters"),
+                ),
+        )
+        .subcommand(
+            Command::new("gamedata-inspect")
+                .about("Look up entries in the loaded game data, useful for checking the result of data overrides")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("item")
+                        .about("Print items found by <type> <number>, using the item type ids of the /item chat command, or by name")
+                        .arg(Arg::new("query").required(true).multiple_values(true)),
+                )
+                .subcommand(
+                    Command::new("monster")
+                        .about("Print the stats and drop table of monsters found by id or name")
+                        .arg(Arg::new("query").required(true).multiple_values(true)),
+                )
+                .subcommand(
+                    Command::new("store")
+                        .about("List the store contents of npcs found by id or name")
+                        .arg(Arg::new("query").required(true).multiple_values(true)),
+                )
+                .subcommand(
+                    Command::new("skill")
+                        .about("Print the effects of skills found by id or name")
+                        .arg(Arg::new("query").required(true).multiple_values(true)),
+                )
+                .subcommand(
+                    Command::new("file")
+                        .about("List the mounted game data devices which contain a file, the first is the one which is used")
+                        .arg(Arg::new("query").required(true).multiple_values(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("extract")
+                .about("Extract files from the game data to a directory, to inspect them or to edit them for use with --data-path or --data-overlay")
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .help("Directory to extract files to, defaults to 'extracted'")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("titan-vfs")
+                        .long("titan-vfs")
+                        .help("Optional path to a TitanROSE data.idx to extract from instead of the game data, with its data.trf in the same directory. TitanROSE only stores a hash of each path, so each filter must be the path of a file")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .help("Paths of the files or directories to extract, or patterns where * matches any characters within a directory and ? matches a single character. Extracts every file by default")
+                        .multiple_values(true),
+                ),
+        )
+        .subcommand(
+            Command::new("maintenance")
+                .about("Start a countdown after which the game world saves every character and exits, only GM accounts can log in during the countdown. Requires the game world to accept remote control connections")
+                .arg(
+                    Arg::new("minutes")
+                        .help("Minutes until the server shuts down")
+                        .required_unless_present("cancel")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("reason")
+                        .long("reason")
+                        .help("Reason announced to online players with the countdown")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("cancel")
+                        .long("cancel")
+                        .help("Cancel the countdown")
+                        .conflicts_with("minutes"),
+                ),
+        )
+        .subcommand(
+            Command::new("leaderboards")
+                .about("Print the leaderboards of the game world. Requires the game world to accept remote control connections"),
+        )
+        .subcommand(
+            Command::new("spawn-item")
+                .about("Give an item to an online character, the item is validated against the game data and written to the economy log. Requires the game world to accept remote control connections")
+                .arg(Arg::new("character").help("Name of the online character").required(true))
+                .arg(
+                    Arg::new("type")
+                        .help("Item type id, as used by the /item chat command")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("id")
+                        .help("Item number")
+                        .required(true)
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("quantity")
+                        .long("quantity")
+                        .help("Quantity of a stackable item [default: 1]")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u32)),
+                )
+                .arg(
+                    Arg::new("grade")
+                        .long("grade")
+                        .help("Grade of an equipment item [default: 0]")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8)),
+                )
+                .arg(
+                    Arg::new("socket")
+                        .long("socket")
+                        .help("Give the equipment item a socket"),
+                )
+                .arg(
+                    Arg::new("gem")
+                        .long("gem")
+                        .help("Gem number, above 300 a socketed gem otherwise an appraisal option")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u16)),
+                )
+                .arg(
+                    Arg::new("durability")
+                        .long("durability")
+                        .help("Durability of an equipment item [default: durability of the item data]")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8)),
+                )
+                .arg(
+                    Arg::new("bound")
+                        .long("bound")
+                        .help("Bind the equipment item to the character"),
+                ),
         );
     let data_path_error = command.error(
         clap::ErrorKind::ArgumentNotFound,
         "Must specify at least one of --data-idx or --data-path",
     );
     let matches = command.get_matches();
-    let listen_ip = matches.value_of("ip").unwrap();
-    let login_port = matches.value_of("login-port").unwrap();
-    let world_port = matches.value_of("world-port").unwrap();
-    let game_port = matches.value_of("game-port").unwrap();
-    let protocol_type = match matches.value_of("protocol") {
-        Some("irose") => ProtocolType::Irose,
-        _ => ProtocolType::default(),
+
+    if matches.is_present("print-default-config") {
+        print!("{}", ServerConfig::default().to_toml());
+        return;
+    }
+
+    let mut server_config = matches
+        .value_of("config")
+        .map(|path| {
+            ServerConfig::load(Path::new(path))
+                .unwrap_or_else(|error| panic!("Failed to load server config: {}", error))
+        })
+        .unwrap_or_default();
+    server_config
+        .apply_args(&matches)
+        .and_then(|_| server_config.validate())
+        .unwrap_or_else(|error| panic!("Invalid server config: {}", error));
+
+    if let Some(storage_dir) = server_config.storage.dir.as_ref() {
+        std::env::set_var("ROSE_OFFLINE_STORAGE_DIR", storage_dir);
+    }
+
+    if let Some(restore_matches) = matches.subcommand_matches("restore-backup") {
+        restore_backup(&server_config, restore_matches.value_of("backup").unwrap());
+        return;
+    }
+
+    if let Some(inspect_matches) = matches.subcommand_matches("inspect-character") {
+        let name = inspect_matches.value_of("name").unwrap();
+        let json = CharacterInspection::load(name)
+            .and_then(|inspection| inspection.to_json())
+            .unwrap_or_else(|error| panic!("Failed to inspect character {}: {:?}", name, error));
+        println!("{}", json);
+        return;
+    }
+
+    if let Some(repair_matches) = matches.subcommand_matches("repair-quests") {
+        repair_quests(&server_config, data_path_error, repair_matches);
+        return;
+    }
+
+    if let Some(inspect_matches) = matches.subcommand_matches("gamedata-inspect") {
+        inspect_game_data(&server_config, data_path_error, inspect_matches);
+        return;
+    }
+
+    if let Some(extract_matches) = matches.subcommand_matches("extract") {
+        extract_game_data(&server_config, data_path_error, extract_matches);
+        return;
+    }
+
+    if let Some(maintenance_matches) = matches.subcommand_matches("maintenance") {
+        send_maintenance_request(&server_config, maintenance_matches).await;
+        return;
+    }
+
+    if matches.subcommand_matches("leaderboards").is_some() {
+        print_leaderboards(&server_config).await;
+        return;
+    }
+
+    if let Some(spawn_item_matches) = matches.subcommand_matches("spawn-item") {
+        send_spawn_item_request(&server_config, spawn_item_matches).await;
+        return;
+    }
+
+    let network_config = &server_config.network;
+    let listen_ips: Vec<&str> = std::iter::once(network_config.ip.as_str())
+        .chain(network_config.additional_ips.iter().map(String::as_str))
+        .collect();
+    let protocol_type = ProtocolType::from_name(&network_config.protocol).unwrap_or_default();
+    let packet_codec_seeds = PacketCodecSeeds::new();
+    let geo_ip = network_config.geo_ip_database.as_deref().map(|path| {
+        let geo_ip = GeoIpDatabase::load(path).unwrap_or_else(|error| panic!("{}", error));
+        log::info!(
+            "Loaded {} GeoIP ranges from {}",
+            geo_ip.len(),
+            path.display()
+        );
+        Arc::new(geo_ip)
+    });
+    let trusted_proxies: Vec<TrustedProxy> = network_config
+        .proxy_protocol_trusted
+        .iter()
+        .map(|trusted_proxy| {
+            trusted_proxy
+                .parse()
+                .unwrap_or_else(|error| panic!("{}", error))
+        })
+        .collect();
+    if network_config.proxy_protocol && trusted_proxies.is_empty() {
+        panic!("The PROXY protocol requires the address of at least one trusted load balancer");
+    }
+    let protocols = protocol_type.create_protocols(ProtocolOptions {
+        packet_dump_dir: network_config.packet_dump.clone(),
+        packet_codec_seeds: packet_codec_seeds.clone(),
+        strict_packet_codec: network_config.strict_packet_codec,
+        geo_ip,
+        proxy_protocol: network_config.proxy_protocol,
+        trusted_proxies,
+    });
+
+    let deployment_config = &server_config.deployment;
+    let (control_message_tx, mut world_server_entity) =
+        if let Some(address) = deployment_config.control_connect.as_ref() {
+            log::info!("Connecting to game world at {}", address);
+            let remote_control = RemoteControlClient::connect(
+                address,
+                deployment_config
+                    .control_secret
+                    .as_deref()
+                    .unwrap_or_default(),
+                packet_codec_seeds.clone(),
+            )
+            .await
+            .unwrap_or_else(|error| {
+                panic!("Failed to connect to game world at {}: {}", address, error)
+            });
+            (
+                remote_control.control_message_tx,
+                remote_control.world_server,
+            )
+        } else {
+            (
+                start_game_world(&server_config, data_path_error, packet_codec_seeds.clone()),
+                None,
+            )
+        };
+
+    if deployment_config.login_server {
+        let mut listeners = bind_listeners(&listen_ips, network_config.login_port).await;
+        let mut login_server = LoginServer::new(
+            listeners.remove(0),
+            protocols.login,
+            control_message_tx.clone(),
+        )
+        .await
+        .unwrap();
+
+        for listener in listeners {
+            login_server.add_listener(listener);
+        }
+        if let Some(port) = network_config.login_websocket_port {
+            for listener in bind_listeners(&listen_ips, port).await {
+                login_server.add_websocket_listener(listener);
+            }
+        }
+
+        tokio::spawn(async move {
+            login_server.run().await;
+        });
+    }
+
+    if deployment_config.world_server {
+        let mut listeners = bind_listeners(&listen_ips, network_config.world_port).await;
+        let mut world_server = WorldServer::new(
+            String::from("_WorldServer"),
+            listeners.remove(0),
+            &ExternalAddress {
+                ip: network_config.world_external_ip.clone(),
+                port: network_config.world_external_port,
+            },
+            protocols.world,
+            control_message_tx.clone(),
+        )
+        .await
+        .unwrap();
+        world_server_entity = Some(world_server.get_entity());
+
+        for listener in listeners {
+            world_server.add_listener(listener);
+        }
+        if let Some(port) = network_config.world_websocket_port {
+            for listener in bind_listeners(&listen_ips, port).await {
+                world_server.add_websocket_listener(listener);
+            }
+        }
+
+        tokio::spawn(async move {
+            world_server.run().await;
+        });
+    }
+
+    if deployment_config.game_server {
+        let mut listeners = bind_listeners(&listen_ips, network_config.game_port).await;
+        let mut game_server = GameServer::new(
+            String::from("GameServer"),
+            world_server_entity.expect("Game server requires a world server to join"),
+            listeners.remove(0),
+            &ExternalAddress {
+                ip: network_config.game_external_ip.clone(),
+                port: network_config.game_external_port,
+            },
+            protocols.game,
+            control_message_tx.clone(),
+        )
+        .await
+        .unwrap();
+
+        for listener in listeners {
+            game_server.add_listener(listener);
+        }
+        if let Some(port) = network_config.game_websocket_port {
+            for listener in bind_listeners(&listen_ips, port).await {
+                game_server.add_websocket_listener(listener);
+            }
+        }
+
+        tokio::spawn(async move {
+            game_server.run().await;
+        });
+    }
+
+    if let Some(address) = deployment_config.control_listen_address() {
+        log::info!("Accepting remote control connections on {}", address);
+        let listener = TcpListener::bind(&address).await.unwrap_or_else(|error| {
+            panic!(
+                "Failed to listen for remote control connections on {}: {}",
+                address, error
+            )
+        });
+        if listener
+            .local_addr()
+            .map_or(false, |local_addr| !local_addr.ip().is_loopback())
+        {
+            log::warn!(
+                "Remote control connections are not encrypted, {} must never be reachable from the public internet",
+                address
+            );
+        }
+
+        let mut remote_control_server = RemoteControlServer::new(
+            listener,
+            deployment_config.control_secret.clone().unwrap_or_default(),
+            control_message_tx,
+            packet_codec_seeds,
+            world_server_entity,
+        );
+        tokio::spawn(async move {
+            remote_control_server.run().await;
+        });
+    }
+
+    std::future::pending::<()>().await;
+}
+
+/// Binds a listener to the port on each of the listen IPs, which can be IPv4
+/// or IPv6 addresses.
+async fn bind_listeners(ips: &[&str], port: u16) -> Vec<TcpListener> {
+    let mut listeners = Vec::with_capacity(ips.len());
+    for ip in ips {
+        listeners.push(
+            TcpListener::bind((*ip, port))
+                .await
+                .unwrap_or_else(|error| {
+                    panic!("Failed to listen on {} port {}: {}", ip, port, error)
+                }),
+        );
+    }
+    listeners
+}
+
+fn restore_backup(server_config: &ServerConfig, backup: &str) {
+    let path = if backup == "latest" {
+        let backup_dir = server_config.storage_backup_dir();
+        backup::list_backups(&backup_dir)
+            .unwrap_or_else(|error| panic!("Failed to list backups: {:?}", error))
+            .pop()
+            .unwrap_or_else(|| panic!("No backups found in {}", backup_dir.display()))
+    } else {
+        PathBuf::from(backup)
+    };
+
+    let storage_backup = backup::StorageBackup::load(&path)
+        .unwrap_or_else(|error| panic!("Failed to load backup: {:?}", error));
+    storage_backup
+        .restore()
+        .unwrap_or_else(|error| panic!("Failed to restore backup: {:?}", error));
+    log::info!(
+        "Restored {} documents from backup {} created at {}",
+        storage_backup.num_documents(),
+        path.display(),
+        storage_backup.created
+    );
+}
+
+/// Returns the address of the game world's remote control server and the
+/// shared secret to connect with.
+fn remote_control_address<'a>(server_config: &'a ServerConfig, command: &str) -> (String, &'a str) {
+    let deployment_config = &server_config.deployment;
+    let address = deployment_config
+        .control_connect
+        .clone()
+        .or_else(|| deployment_config.control_listen_address())
+        .unwrap_or_else(|| {
+            panic!(
+                "{} requires deployment.control_listen or deployment.control_connect",
+                command
+            )
+        });
+    let secret = deployment_config
+        .control_secret
+        .as_deref()
+        .unwrap_or_else(|| panic!("{} requires deployment.control_secret", command));
+    (address, secret)
+}
+
+async fn send_maintenance_request(server_config: &ServerConfig, matches: &clap::ArgMatches) {
+    let (address, secret) = remote_control_address(server_config, "Maintenance");
+
+    let countdown = if matches.is_present("cancel") {
+        None
+    } else {
+        Some(Duration::from_secs(
+            matches.get_one::<u64>("minutes").unwrap() * 60,
+        ))
     };
+    let reason = matches.value_of("reason").unwrap_or_default().to_string();
+
+    remote_control::send_maintenance_request(&address, secret, countdown, reason)
+        .await
+        .unwrap_or_else(|error| {
+            panic!(
+                "Failed to send maintenance request to game world at {}: {}",
+                address, error
+            )
+        });
 
-    let (login_protocol, world_protocol, game_protocol) = match protocol_type {
-        ProtocolType::Irose => (
-            irose::login_protocol(),
-            irose::world_protocol(),
-            irose::game_protocol(),
+    match countdown {
+        Some(countdown) => log::info!(
+            "Server maintenance starts in {} minutes",
+            countdown.as_secs() / 60
         ),
+        None => log::info!("Server maintenance cancelled"),
+    }
+}
+
+async fn print_leaderboards(server_config: &ServerConfig) {
+    let (address, secret) = remote_control_address(server_config, "Leaderboards");
+    let leaderboards = remote_control::request_leaderboards(&address, secret)
+        .await
+        .unwrap_or_else(|error| {
+            panic!(
+                "Failed to request leaderboards from game world at {}: {}",
+                address, error
+            )
+        });
+
+    match leaderboards {
+        Some(leaderboards) => print!("{}", leaderboards),
+        None => println!("Leaderboards are disabled or have not been built yet"),
+    }
+}
+
+async fn send_spawn_item_request(server_config: &ServerConfig, matches: &clap::ArgMatches) {
+    let (address, secret) = remote_control_address(server_config, "Spawn item");
+    let character_name = matches.value_of("character").unwrap().to_string();
+    let item_spawn = ItemSpawn {
+        item_type: *matches.get_one::<usize>("type").unwrap(),
+        item_number: *matches.get_one::<usize>("id").unwrap(),
+        quantity: matches.get_one::<u32>("quantity").copied().unwrap_or(1),
+        grade: matches.get_one::<u8>("grade").copied().unwrap_or(0),
+        has_socket: matches.is_present("socket"),
+        gem: matches.get_one::<u16>("gem").copied().unwrap_or(0),
+        durability: matches.get_one::<u8>("durability").copied(),
+        is_bound: matches.is_present("bound"),
     };
 
-    let mut data_idx_path = matches.value_of("data-idx").map(Path::new);
-    let data_extracted_path = matches.value_of("data-path").map(Path::new);
+    let result =
+        remote_control::request_spawn_item(&address, secret, character_name.clone(), item_spawn)
+            .await
+            .unwrap_or_else(|error| {
+                panic!(
+                    "Failed to send spawn item request to game world at {}: {}",
+                    address, error
+                )
+            });
+
+    match result {
+        Ok(()) => log::info!("Spawned item for character {}", character_name),
+        Err(error) => log::error!(
+            "Failed to spawn item for character {}: {}",
+            character_name,
+            error
+        ),
+    }
+}
+
+fn load_virtual_filesystem(
+    server_config: &ServerConfig,
+    data_path_error: clap::Error,
+) -> VirtualFilesystem {
+    let mut data_idx_path = server_config.data.idx.as_deref();
+    let data_extracted_path = server_config.data.path.as_deref();
     if data_idx_path.is_none() && data_extracted_path.is_none() {
         if Path::new("data.idx").exists() {
             data_idx_path = Some(Path::new("data.idx"));
@@ -136,15 +1112,37 @@ async fn async_main() {
         }
     }
 
-    let mut vfs_devices: Vec<Box<dyn VirtualFilesystemDevice + Send + Sync>> = Vec::new();
+    let mut vfs = VirtualFilesystem::new(Vec::new());
+    let mut mount =
+        |name: &str, priority: i32, device: Box<dyn VirtualFilesystemDevice + Send + Sync>| {
+            vfs.mount(name, priority, device)
+                .unwrap_or_else(|error| panic!("Failed to mount game data: {}", error));
+        };
+
+    // Earlier overlays take priority over later ones, as mounts with the same
+    // priority are searched in the order they were mounted
+    for overlay_path in server_config.data.overlays.iter() {
+        log::info!(
+            "Loading game data overlay from path {}",
+            overlay_path.to_string_lossy()
+        );
+        mount(
+            &format!("overlay {}", overlay_path.to_string_lossy()),
+            VFS_PRIORITY_OVERLAY,
+            Box::new(HostFilesystemDevice::new(overlay_path.clone())),
+        );
+    }
+
     if let Some(data_extracted_path) = data_extracted_path {
         log::info!(
             "Loading game data from path {}",
             data_extracted_path.to_string_lossy()
         );
-        vfs_devices.push(Box::new(HostFilesystemDevice::new(
-            data_extracted_path.to_path_buf(),
-        )));
+        mount(
+            &format!("data path {}", data_extracted_path.to_string_lossy()),
+            VFS_PRIORITY_DATA_PATH,
+            Box::new(HostFilesystemDevice::new(data_extracted_path.to_path_buf())),
+        );
     }
 
     if let Some(data_idx_path) = data_idx_path {
@@ -152,9 +1150,15 @@ async fn async_main() {
             "Loading game data from vfs {}",
             data_idx_path.to_string_lossy()
         );
-        vfs_devices.push(Box::new(VfsIndex::load(data_idx_path).unwrap_or_else(
-            |_| panic!("Failed to load {}", data_idx_path.to_string_lossy()),
-        )));
+        mount(
+            &format!("vfs {}", data_idx_path.to_string_lossy()),
+            VFS_PRIORITY_DATA_IDX,
+            Box::new(
+                VfsIndex::load(data_idx_path).unwrap_or_else(|_| {
+                    panic!("Failed to load {}", data_idx_path.to_string_lossy())
+                }),
+            ),
+        );
 
         let index_root_path = data_idx_path
             .parent()
@@ -164,67 +1168,299 @@ async fn async_main() {
             "Loading game data from vfs root path {}",
             index_root_path.to_string_lossy()
         );
-        vfs_devices.push(Box::new(HostFilesystemDevice::new(index_root_path)));
+        mount(
+            &format!("vfs root path {}", index_root_path.to_string_lossy()),
+            VFS_PRIORITY_DATA_IDX_ROOT,
+            Box::new(HostFilesystemDevice::new(index_root_path)),
+        );
     }
 
-    let virtual_filesystem = VirtualFilesystem::new(vfs_devices);
+    vfs
+}
 
+/// Loads the game data in the configured languages, `load_optional_data` is
+/// false for tools which do not need the zone geometry and nav grids.
+fn load_game_data(
+    server_config: &ServerConfig,
+    virtual_filesystem: &VirtualFilesystem,
+    load_optional_data: bool,
+) -> GameData {
+    let data_config = &server_config.data;
     let started_load = Instant::now();
-    let game_data = irose::get_game_data(&virtual_filesystem);
+    let game_data = irose::get_game_data(
+        virtual_filesystem,
+        &irose::GameDataOptions {
+            skip_optional_data: data_config.skip_optional || !load_optional_data,
+            cache_path: data_config.cache.as_deref(),
+            language: data_config.language,
+            fallback_language: data_config.fallback_language,
+            client_languages: &data_config.client_languages,
+        },
+    );
     debug!("Time take to read game data {:?}", started_load.elapsed());
+    game_data
+}
+
+/// Hashes the game data files which clients can be asked to verify, files
+/// which do not exist are left out.
+fn hash_client_files(
+    virtual_filesystem: &VirtualFilesystem,
+    paths: &[String],
+) -> Vec<(String, [u8; 32])> {
+    paths
+        .iter()
+        .filter_map(|path| match virtual_filesystem.open_file(path) {
+            Ok(file) => {
+                let data: &[u8] = match &file {
+                    VfsFile::Buffer(buffer) => buffer,
+                    VfsFile::View(view) => view,
+                };
+                Some((path.clone(), Sha256::digest(data).into()))
+            }
+            Err(error) => {
+                log::warn!(
+                    "Client integrity file {} not found in game data: {}",
+                    path,
+                    error
+                );
+                None
+            }
+        })
+        .collect()
+}
 
-    let game_config = GameConfig {
-        enable_npc_spawns: true,
-        enable_monster_spawns: true,
+fn repair_quests(
+    server_config: &ServerConfig,
+    data_path_error: clap::Error,
+    matches: &clap::ArgMatches,
+) {
+    let remap = matches
+        .value_of("remap")
+        .map(|path| {
+            quest_repair::load_quest_remap(Path::new(path))
+                .unwrap_or_else(|error| panic!("Failed to load quest remap: {:?}", error))
+        })
+        .unwrap_or_default();
+    let dry_run = matches.is_present("dry-run");
+
+    // Quest repair only needs the quest and item data
+    let game_data = load_game_data(
+        server_config,
+        &load_virtual_filesystem(server_config, data_path_error),
+        false,
+    );
+    let report = quest_repair::repair_stored_quest_states(
+        &game_data.quests,
+        &game_data.items,
+        &remap,
+        dry_run,
+    );
+    print!("{}", report);
+    if dry_run {
+        println!("Dry run, no characters were saved");
+    }
+}
+
+fn inspect_game_data(
+    server_config: &ServerConfig,
+    data_path_error: clap::Error,
+    matches: &clap::ArgMatches,
+) {
+    let (kind, kind_matches) = matches.subcommand().unwrap();
+    let query = kind_matches
+        .values_of("query")
+        .unwrap()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let vfs = load_virtual_filesystem(server_config, data_path_error);
+    if kind == "file" {
+        println!("Mounted devices:");
+        for (name, priority) in vfs.mounts() {
+            println!("  {} (priority {})", name, priority);
+        }
+
+        let mounts = vfs.find_mounts(&query);
+        if mounts.is_empty() {
+            eprintln!("No mounted device contains {}", query);
+            return;
+        }
+        println!("{} found in:", query);
+        for name in mounts {
+            println!("  {}", name);
+        }
+        return;
+    }
+
+    // The zone geometry and nav grids are not needed for lookups
+    let game_data = load_game_data(server_config, &vfs, false);
+    let result = match kind {
+        "item" => game_data_inspect::inspect_item(&game_data, &query),
+        "monster" => game_data_inspect::inspect_monster(&game_data, &query),
+        "store" => game_data_inspect::inspect_store(&game_data, &query),
+        "skill" => game_data_inspect::inspect_skill(&game_data, &query),
+        _ => unreachable!(),
     };
 
-    let (game_control_tx, game_control_rx) = crossbeam_channel::unbounded();
-    std::thread::spawn(move || {
-        game::GameWorld::new(game_control_rx).run(game_config, game_data);
-    });
+    match result {
+        Ok(lines) => {
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        Err(error) => eprintln!("{}", error),
+    }
+}
 
-    let mut login_server = LoginServer::new(
-        TcpListener::bind(format!("{}:{}", listen_ip, login_port))
-            .await
-            .unwrap(),
-        login_protocol,
-        game_control_tx.clone(),
-    )
-    .await
-    .unwrap();
+/// Returns true if the path is relative and only made of normal components,
+/// so it can not refer to anything outside of the directory it is joined to.
+fn is_extract_path_safe(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
 
-    let mut world_server = WorldServer::new(
-        String::from("_WorldServer"),
-        TcpListener::bind(format!("{}:{}", listen_ip, world_port))
-            .await
-            .unwrap(),
-        world_protocol,
-        game_control_tx.clone(),
-    )
-    .await
-    .unwrap();
+fn extract_game_data(
+    server_config: &ServerConfig,
+    data_path_error: clap::Error,
+    matches: &clap::ArgMatches,
+) {
+    let output_path = PathBuf::from(matches.value_of("output").unwrap_or("extracted"));
+    let vfs = if let Some(titan_index_path) = matches.value_of("titan-vfs") {
+        let titan_index_path = Path::new(titan_index_path);
+        let titan_vfs: Box<dyn VirtualFilesystemDevice + Send + Sync> = Box::new(
+            TitanVfsIndex::load(
+                titan_index_path,
+                &titan_index_path.with_file_name("data.trf"),
+            )
+            .unwrap_or_else(|error| {
+                panic!(
+                    "Failed to load {}: {:?}",
+                    titan_index_path.to_string_lossy(),
+                    error
+                )
+            }),
+        );
+        VirtualFilesystem::new(vec![titan_vfs])
+    } else {
+        load_virtual_filesystem(server_config, data_path_error)
+    };
 
-    let mut game_server = GameServer::new(
-        String::from("GameServer"),
-        world_server.get_entity(),
-        TcpListener::bind(format!("{}:{}", listen_ip, game_port))
-            .await
-            .unwrap(),
-        game_protocol,
-        game_control_tx.clone(),
-    )
-    .await
-    .unwrap();
+    let filters: Vec<&str> = matches
+        .values_of("filter")
+        .map_or_else(|| vec![""], |filters| filters.collect());
+    let mut paths = BTreeSet::new();
+    for filter in filters {
+        let is_pattern = filter.contains(|c: char| c == '*' || c == '?');
+        let found = if is_pattern {
+            vfs.glob(filter)
+        } else {
+            vfs.list_files(filter)
+        };
 
-    tokio::spawn(async move {
-        game_server.run().await;
-    });
+        match found {
+            Some(found) => {
+                if found.is_empty() {
+                    eprintln!("No files found matching {}", filter);
+                }
+                paths.extend(found.iter().map(|path| path.path().to_path_buf()));
+            }
+            // Devices which cannot list their files can still open a path
+            None if !is_pattern && !filter.is_empty() => {
+                if vfs.exists(filter) {
+                    paths.insert(VfsPath::from(filter).path().to_path_buf());
+                } else {
+                    eprintln!("File {} not found", filter);
+                }
+            }
+            None => eprintln!(
+                "The files of the game data cannot be listed, {} must be the path of a file",
+                if filter.is_empty() {
+                    "each filter"
+                } else {
+                    filter
+                }
+            ),
+        }
+    }
 
-    tokio::spawn(async move {
-        world_server.run().await;
-    });
+    // Files are read from the mount which takes priority, so overlays and
+    // the data path are extracted instead of the files they override
+    let mut num_extracted = 0;
+    for path in paths.iter() {
+        // The paths come from the data index, which must not be able to write
+        // outside of the output directory
+        if !is_extract_path_safe(path) {
+            eprintln!("Skipped unsafe path {}", path.to_string_lossy());
+            continue;
+        }
 
-    login_server.run().await;
+        let file = match vfs.open_file(path.as_path()) {
+            Ok(file) => file,
+            Err(error) => {
+                eprintln!("Failed to read {}: {}", path.to_string_lossy(), error);
+                continue;
+            }
+        };
+        let data: &[u8] = match &file {
+            VfsFile::Buffer(buffer) => buffer,
+            VfsFile::View(view) => view,
+        };
+
+        let output_file_path = output_path.join(path);
+        if let Some(parent) = output_file_path.parent() {
+            if let Err(error) = std::fs::create_dir_all(parent) {
+                eprintln!(
+                    "Failed to create directory {}: {}",
+                    parent.to_string_lossy(),
+                    error
+                );
+                continue;
+            }
+        }
+        if let Err(error) = std::fs::write(&output_file_path, data) {
+            eprintln!(
+                "Failed to write {}: {}",
+                output_file_path.to_string_lossy(),
+                error
+            );
+            continue;
+        }
+        num_extracted += 1;
+    }
+
+    println!(
+        "Extracted {} files to {}",
+        num_extracted,
+        output_path.to_string_lossy()
+    );
+}
+
+/// Loads the game data and runs the game world on its own thread, returning
+/// the channel used by the servers to send it control messages.
+fn start_game_world(
+    server_config: &ServerConfig,
+    data_path_error: clap::Error,
+    packet_codec_seeds: PacketCodecSeeds,
+) -> crossbeam_channel::Sender<ControlMessage> {
+    let virtual_filesystem = load_virtual_filesystem(server_config, data_path_error);
+    let game_data = load_game_data(server_config, &virtual_filesystem, true);
+    let mut game_config = server_config.create_game_config();
+    if let Some(client_integrity) = game_config.client_integrity.as_mut() {
+        client_integrity.file_hashes =
+            hash_client_files(&virtual_filesystem, &client_integrity.files);
+    }
+
+    let (game_control_tx, game_control_rx) = crossbeam_channel::unbounded();
+    std::thread::spawn(move || {
+        game::GameWorld::new(game_control_rx, packet_codec_seeds).run(game_config, game_data);
+
+        // The game world only stops once it has shut down for maintenance
+        log::info!("Game world stopped, exiting");
+        std::process::exit(0);
+    });
+    game_control_tx
 }
 
 fn main() {
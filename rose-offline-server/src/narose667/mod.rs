@@ -0,0 +1,3 @@
+mod protocol;
+
+pub use protocol::protocols;
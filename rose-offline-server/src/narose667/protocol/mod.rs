@@ -0,0 +1,22 @@
+use rose_network_irose::IROSE_112_TABLE;
+
+use crate::{
+    irose::protocol::{create_protocols, GameServer, LoginServer},
+    protocol::{ProtocolOptions, ProtocolSet},
+};
+
+mod world_server;
+
+use world_server::WorldServer;
+
+/// The naRose 667 client uses the irose packet codec and packets, except for
+/// the packets implemented by its own servers here.
+pub fn protocols(options: ProtocolOptions) -> ProtocolSet {
+    create_protocols(
+        &IROSE_112_TABLE,
+        || Box::new(LoginServer::new()),
+        || Box::new(WorldServer::new()),
+        || Box::new(GameServer::new()),
+        options,
+    )
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use std::convert::TryFrom;
+
+use rose_game_common::messages::{client::ClientMessage, server::ServerMessage};
+use rose_network_common::Packet;
+use rose_network_irose::world_client_packets::{ClientPackets, PacketClientCreateCharacter};
+
+use crate::{
+    implement_protocol_server, irose,
+    protocol::{Client, ProtocolServer, ProtocolServerError},
+};
+
+pub struct WorldServer {
+    irose: irose::protocol::WorldServer,
+}
+
+impl WorldServer {
+    pub fn new() -> Self {
+        Self {
+            irose: irose::protocol::WorldServer::new(),
+        }
+    }
+
+    async fn handle_packet(
+        &mut self,
+        client: &mut Client<'_>,
+        packet: &Packet,
+    ) -> Result<(), anyhow::Error> {
+        if packet.command == ClientPackets::CreateCharacter as u16 {
+            // naRose has no birth stones, the client sends the chosen hair
+            // colour in their place
+            let request = PacketClientCreateCharacter::try_from(packet)?;
+            client
+                .client_message_tx
+                .send(ClientMessage::CreateCharacter {
+                    gender: request.gender,
+                    birth_stone: 0,
+                    hair: request.hair as i32,
+                    face: request.face as i32,
+                    start_point: request.start_point as i32,
+                    hair_color: request.birth_stone as i32,
+                    weapon_type: request.weapon_type as i32,
+                    name: String::from(request.name),
+                })?;
+            return Ok(());
+        }
+
+        self.irose.handle_packet(client, packet).await
+    }
+
+    async fn handle_server_message(
+        &mut self,
+        client: &mut Client<'_>,
+        message: ServerMessage,
+    ) -> Result<(), anyhow::Error> {
+        self.irose.handle_server_message(client, message).await
+    }
+}
+
+implement_protocol_server! { WorldServer }
@@ -0,0 +1,98 @@
+use std::{
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GeoIpError {
+    #[error("Failed to read {path}: {error}")]
+    Read {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    #[error("Invalid GeoIP range on line {0}")]
+    InvalidLine(usize),
+}
+
+struct GeoIpRange {
+    first: u128,
+    last: u128,
+    country: String,
+}
+
+/// Maps client IP addresses to the country they connect from, read from a CSV
+/// file where each line is `first_ip,last_ip,country_code`. Both IPv4 and IPv6
+/// ranges can be used.
+pub struct GeoIpDatabase {
+    ranges: Vec<GeoIpRange>,
+}
+
+fn ip_to_u128(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+        IpAddr::V6(ip) => u128::from(ip),
+    }
+}
+
+impl GeoIpDatabase {
+    pub fn load(path: &Path) -> Result<Self, GeoIpError> {
+        let csv = std::fs::read_to_string(path).map_err(|error| GeoIpError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Self::parse(&csv)
+    }
+
+    pub fn parse(csv: &str) -> Result<Self, GeoIpError> {
+        let mut ranges = Vec::new();
+
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split(',').map(|field| field.trim().trim_matches('"'));
+            let (Some(first), Some(last), Some(country)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(GeoIpError::InvalidLine(index + 1));
+            };
+            let (Ok(first), Ok(last)) = (first.parse::<IpAddr>(), last.parse::<IpAddr>()) else {
+                return Err(GeoIpError::InvalidLine(index + 1));
+            };
+            let (first, last) = (ip_to_u128(first), ip_to_u128(last));
+            if first > last || country.is_empty() {
+                return Err(GeoIpError::InvalidLine(index + 1));
+            }
+
+            ranges.push(GeoIpRange {
+                first,
+                last,
+                country: country.to_ascii_uppercase(),
+            });
+        }
+
+        ranges.sort_by_key(|range| range.first);
+        Ok(Self { ranges })
+    }
+
+    /// Returns the country code of the address, or None if it is not in any
+    /// range such as a private network address.
+    pub fn lookup(&self, ip: IpAddr) -> Option<&str> {
+        let ip = ip_to_u128(ip);
+        let index = self.ranges.partition_point(|range| range.first <= ip);
+        let range = self.ranges.get(index.checked_sub(1)?)?;
+        (ip <= range.last).then_some(range.country.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
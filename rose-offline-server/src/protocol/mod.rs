@@ -1,10 +1,14 @@
 use async_trait::async_trait;
+use std::{path::PathBuf, sync::Arc};
 use thiserror::Error;
 
 use rose_game_common::messages::{client::ClientMessage, server::ServerMessage};
 use rose_network_common::{Connection, PacketCodec};
 
-use crate::game::messages::control::ClientType;
+use crate::{
+    game::{messages::control::ClientType, PacketCodecSeeds},
+    protocol::{geo_ip::GeoIpDatabase, proxy_protocol::TrustedProxy},
+};
 
 pub struct Client<'a> {
     pub entity: bevy::ecs::prelude::Entity,
@@ -24,12 +28,83 @@ pub trait ProtocolServer {
     async fn run_client(&mut self, client: &mut Client) -> Result<(), anyhow::Error>;
 }
 
+pub type CreatePacketCodec = Box<dyn Fn(u32) -> Box<dyn PacketCodec + Send + Sync> + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct ProtocolOptions {
+    /// When set the decrypted packets for each connection are written to this directory
+    pub packet_dump_dir: Option<PathBuf>,
+
+    /// The per connection packet codec seeds sent to clients via the login token
+    pub packet_codec_seeds: PacketCodecSeeds,
+
+    /// When set connections which do not use one of their issued packet codec
+    /// seeds are rejected instead of falling back to the server packet codec
+    pub strict_packet_codec: bool,
+
+    /// Used to look up the country of login clients when set
+    pub geo_ip: Option<Arc<GeoIpDatabase>>,
+
+    /// Connections start with a PROXY protocol v2 header giving the client
+    /// address, for servers behind a TCP load balancer
+    pub proxy_protocol: bool,
+
+    /// The load balancer addresses which the PROXY protocol header is read
+    /// from, connections from any other address are treated as direct clients
+    pub trusted_proxies: Vec<TrustedProxy>,
+}
+
 pub struct Protocol {
     pub client_type: ClientType,
     pub packet_codec: Box<dyn PacketCodec + Send + Sync>,
     pub create_server: fn() -> Box<dyn ProtocolServer + Send + Sync>,
+
+    /// Creates the packet codec for a per connection seed, None when the
+    /// protocol only uses the server packet codec
+    pub create_packet_codec: Option<CreatePacketCodec>,
+
+    pub options: ProtocolOptions,
+}
+
+/// The login, world and game server protocols used by a client version.
+pub struct ProtocolSet {
+    pub login: Arc<Protocol>,
+    pub world: Arc<Protocol>,
+    pub game: Arc<Protocol>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ProtocolType {
+    Irose,
+    Narose667,
 }
 
+impl Default for ProtocolType {
+    fn default() -> Self {
+        Self::Irose
+    }
+}
+
+impl ProtocolType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "irose" => Some(Self::Irose),
+            "narose" | "narose667" => Some(Self::Narose667),
+            _ => None,
+        }
+    }
+
+    pub fn create_protocols(self, options: ProtocolOptions) -> ProtocolSet {
+        match self {
+            Self::Irose => crate::irose::protocols(options),
+            Self::Narose667 => crate::narose667::protocols(options),
+        }
+    }
+}
+
+pub mod geo_ip;
+pub mod proxy_protocol;
+pub mod remote_control;
 pub mod server;
 
 #[macro_export]
@@ -58,6 +133,9 @@ macro_rules! implement_protocol_server {
                         },
                         server_message = client.server_message_rx.recv() => {
                             if let Some(message) = server_message {
+                                if let Some(packet_dump) = client.connection.packet_dump_mut() {
+                                    packet_dump.write_note(&format!("{:?}", message));
+                                }
                                 self.handle_server_message(client, message).await?;
                             } else {
                                 return Err(ProtocolServerError::ServerInitiatedDisconnect.into());
@@ -0,0 +1,161 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+    time::Duration,
+};
+
+use thiserror::Error;
+use tokio::{io::AsyncReadExt, net::TcpStream};
+
+/// A load balancer sends the header immediately, so a connection which does
+/// not is not from one
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+const PROXY_V2_SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+
+const PROXY_V2_COMMAND_LOCAL: u8 = 0x0;
+const PROXY_V2_COMMAND_PROXY: u8 = 0x1;
+
+const PROXY_V2_FAMILY_INET: u8 = 0x1;
+const PROXY_V2_FAMILY_INET6: u8 = 0x2;
+
+#[derive(Debug, Error)]
+pub enum ProxyProtocolError {
+    #[error("connection did not start with a PROXY protocol v2 header")]
+    InvalidSignature,
+    #[error("unsupported PROXY protocol version {0}")]
+    UnsupportedVersion(u8),
+    #[error("unsupported PROXY protocol command {0}")]
+    UnsupportedCommand(u8),
+    #[error("PROXY protocol address block is too short")]
+    InvalidAddress,
+    #[error("timed out waiting for the PROXY protocol header")]
+    Timeout,
+    #[error("invalid trusted proxy address range {0}")]
+    InvalidTrustedProxy(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+fn ip_to_u128(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+        IpAddr::V6(ip) => u128::from(ip),
+    }
+}
+
+/// A range of load balancer addresses which are trusted to send a PROXY
+/// protocol header, written in CIDR notation such as `10.0.0.0/8`. A single
+/// address without a prefix length is also accepted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TrustedProxy {
+    network: u128,
+    mask: u128,
+}
+
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        ip_to_u128(ip) & self.mask == self.network
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = ProxyProtocolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ProxyProtocolError::InvalidTrustedProxy(s.to_string());
+        let (ip, prefix_len) = match s.trim().split_once('/') {
+            Some((ip, prefix_len)) => (ip, Some(prefix_len)),
+            None => (s.trim(), None),
+        };
+        let ip = ip.parse::<IpAddr>().map_err(|_| invalid())?;
+
+        // IPv4 ranges are stored as IPv4 mapped IPv6 ranges
+        let max_prefix_len = if ip.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.parse::<u32>().map_err(|_| invalid())?,
+            None => max_prefix_len,
+        };
+        if prefix_len > max_prefix_len {
+            return Err(invalid());
+        }
+
+        let mask = u128::MAX
+            .checked_shl(max_prefix_len - prefix_len)
+            .unwrap_or(0);
+        Ok(Self {
+            network: ip_to_u128(ip) & mask,
+            mask,
+        })
+    }
+}
+
+/// Reads the HAProxy PROXY protocol v2 header which a load balancer sends at
+/// the start of each connection, returning the address of the client which
+/// connected to the load balancer. The header must only be read from the
+/// addresses of trusted load balancers, otherwise any client could send one
+/// with whichever address it likes.
+///
+/// Returns None for health checks sent by the load balancer itself, and for
+/// address families other than IPv4 and IPv6, when the address of the
+/// connection should be used instead.
+pub async fn read_proxy_protocol_header(
+    stream: &mut TcpStream,
+) -> Result<Option<SocketAddr>, ProxyProtocolError> {
+    tokio::time::timeout(PROXY_HEADER_TIMEOUT, read_header(stream))
+        .await
+        .map_err(|_| ProxyProtocolError::Timeout)?
+}
+
+async fn read_header(stream: &mut TcpStream) -> Result<Option<SocketAddr>, ProxyProtocolError> {
+    let mut header = [0u8; 16];
+    stream.read_exact(&mut header).await?;
+
+    if header[..12] != PROXY_V2_SIGNATURE {
+        return Err(ProxyProtocolError::InvalidSignature);
+    }
+
+    let version = header[12] >> 4;
+    if version != 2 {
+        return Err(ProxyProtocolError::UnsupportedVersion(version));
+    }
+
+    let command = header[12] & 0x0F;
+    let family = header[13] >> 4;
+    let length = u16::from_be_bytes([header[14], header[15]]) as usize;
+
+    // The whole address block is read, including any TLVs we do not use
+    let mut addresses = vec![0u8; length];
+    stream.read_exact(&mut addresses).await?;
+
+    match command {
+        PROXY_V2_COMMAND_LOCAL => return Ok(None),
+        PROXY_V2_COMMAND_PROXY => {}
+        _ => return Err(ProxyProtocolError::UnsupportedCommand(command)),
+    }
+
+    match family {
+        PROXY_V2_FAMILY_INET => {
+            if addresses.len() < 12 {
+                return Err(ProxyProtocolError::InvalidAddress);
+            }
+
+            let ip = Ipv4Addr::new(addresses[0], addresses[1], addresses[2], addresses[3]);
+            let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        PROXY_V2_FAMILY_INET6 => {
+            if addresses.len() < 36 {
+                return Err(ProxyProtocolError::InvalidAddress);
+            }
+
+            let mut ip = [0u8; 16];
+            ip.copy_from_slice(&addresses[..16]);
+            let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port)))
+        }
+        _ => Ok(None),
+    }
+}
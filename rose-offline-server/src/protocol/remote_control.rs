@@ -0,0 +1,1089 @@
+//! The remote control protocol allows the login, world and game servers to
+//! run in a different process to the game world. The server process sends
+//! its control messages to the game world process over TCP, which recreates
+//! them on its own control channel, and the messages for each client are
+//! forwarded in both directions.
+//!
+//! A server process must prove it knows the shared secret before the game
+//! world accepts its connection, but the connection is not encrypted so it
+//! must only be used on loopback or a private network, and never be reachable
+//! from the public internet.
+
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+
+use bevy::ecs::prelude::Entity;
+use log::{info, warn};
+use rand::Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    sync::{mpsc, oneshot},
+};
+
+use crate::game::{
+    bundles::ItemSpawn,
+    messages::{
+        client::ClientMessage,
+        control::{ClientType, ControlMessage},
+        server::ServerMessage,
+    },
+    storage::leaderboard::Leaderboards,
+    PacketCodecSeedUpdate, PacketCodecSeeds,
+};
+
+const REMOTE_CONTROL_VERSION: u32 = 9;
+
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// The handshake is read before the connection is authenticated, so it is
+/// limited to a much smaller frame size
+const MAX_HELLO_FRAME_SIZE: usize = 1024;
+
+/// How long a server process has to answer the game world's hello
+const REMOTE_CONTROL_HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+pub enum RemoteControlError {
+    #[error("frame of {0} bytes exceeds the maximum frame size")]
+    FrameTooLarge(usize),
+    #[error("the remote control secret was not accepted")]
+    AuthenticationFailed,
+    #[error("unexpected remote control handshake")]
+    InvalidHandshake,
+    #[error("game world disconnected before responding")]
+    NoResponse,
+    #[error("remote control version {0} does not match version {1}")]
+    VersionMismatch(u32, u32),
+    #[error("timed out waiting for the remote control handshake")]
+    Timeout,
+}
+
+/// Sent from a server process to the game world process.
+#[derive(Serialize, Deserialize)]
+enum RemoteControlRequest {
+    /// The answer to the game world's hello, which must be sent first
+    Hello {
+        proof: String,
+    },
+    AddClient {
+        client_id: u32,
+        client_type: ClientType,
+        ip: IpAddr,
+        country: Option<String>,
+        packet_codec_login_token: Option<u32>,
+    },
+    ClientMessage {
+        client_id: u32,
+        message: ClientMessage,
+    },
+    RemoveClient {
+        client_id: u32,
+    },
+    MalformedPacket {
+        client_id: u32,
+    },
+    AddWorldServer {
+        request_id: u32,
+        name: String,
+        ip: String,
+        port: u16,
+        packet_codec_seed: u32,
+    },
+    AddGameServer {
+        request_id: u32,
+        world_server: u64,
+        name: String,
+        ip: String,
+        port: u16,
+        packet_codec_seed: u32,
+    },
+    RemoveServer {
+        entity: u64,
+    },
+    UpdateChannelPopulation {
+        game_server: u64,
+        num_clients: u32,
+    },
+    PacketCodecSeed(PacketCodecSeedUpdate),
+    StartMaintenance {
+        countdown_secs: u64,
+        reason: String,
+    },
+    CancelMaintenance,
+    GetLeaderboards {
+        request_id: u32,
+    },
+    SpawnItem {
+        request_id: u32,
+        character_name: String,
+        item_spawn: ItemSpawn,
+    },
+}
+
+/// Sent from the game world process to a server process.
+#[derive(Serialize, Deserialize)]
+enum RemoteControlResponse {
+    /// Sent when a server process connects, it must answer with the proof
+    /// for this nonce before anything else is accepted
+    Hello {
+        version: u32,
+        nonce: String,
+    },
+    Authenticated {
+        world_server: Option<u64>,
+    },
+    ClientAdded {
+        client_id: u32,
+        entity: u64,
+    },
+    ServerMessage {
+        client_id: u32,
+        message: ServerMessage,
+    },
+    ClientRemoved {
+        client_id: u32,
+    },
+    ServerAdded {
+        request_id: u32,
+        entity: u64,
+    },
+    PacketCodecSeed(PacketCodecSeedUpdate),
+    Leaderboards {
+        request_id: u32,
+        leaderboards: Option<Leaderboards>,
+    },
+    ItemSpawned {
+        request_id: u32,
+        result: Result<(), String>,
+    },
+}
+
+async fn read_frame<T: DeserializeOwned>(
+    reader: &mut (impl AsyncRead + Unpin),
+) -> Result<T, anyhow::Error> {
+    read_frame_with_limit(reader, MAX_FRAME_SIZE).await
+}
+
+async fn read_frame_with_limit<T: DeserializeOwned>(
+    reader: &mut (impl AsyncRead + Unpin),
+    max_frame_size: usize,
+) -> Result<T, anyhow::Error> {
+    let length = reader.read_u32_le().await? as usize;
+    if length > max_frame_size {
+        return Err(RemoteControlError::FrameTooLarge(length).into());
+    }
+
+    let mut buffer = vec![0; length];
+    reader.read_exact(&mut buffer).await?;
+    Ok(serde_json::from_slice(&buffer)?)
+}
+
+async fn write_frame<T: Serialize>(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &T,
+) -> Result<(), anyhow::Error> {
+    let buffer = serde_json::to_vec(message)?;
+    writer.write_u32_le(buffer.len() as u32).await?;
+    writer.write_all(&buffer).await?;
+    Ok(())
+}
+
+/// Returns the HMAC-SHA256 of the nonce keyed by the shared secret, which
+/// proves a server process knows the secret without sending it.
+fn remote_control_proof(secret: &str, nonce: &str) -> String {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut key = [0u8; BLOCK_SIZE];
+    if secret.len() > BLOCK_SIZE {
+        let digest = Sha256::digest(secret.as_bytes());
+        key[..digest.len()].copy_from_slice(&digest);
+    } else {
+        key[..secret.len()].copy_from_slice(secret.as_bytes());
+    }
+
+    let inner = Sha256::new()
+        .chain_update(key.map(|byte| byte ^ 0x36))
+        .chain_update(nonce.as_bytes())
+        .finalize();
+    let outer = Sha256::new()
+        .chain_update(key.map(|byte| byte ^ 0x5c))
+        .chain_update(inner)
+        .finalize();
+    hex::encode(outer)
+}
+
+/// Compares the proof in constant time, so its timing does not reveal how
+/// much of the proof was correct.
+fn is_valid_proof(secret: &str, nonce: &str, proof: &str) -> bool {
+    let expected = remote_control_proof(secret, nonce);
+    expected.len() == proof.len()
+        && expected
+            .bytes()
+            .zip(proof.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+struct RemoteClient {
+    client_type: ClientType,
+    entity: Entity,
+    client_message_tx: crossbeam_channel::Sender<ClientMessage>,
+}
+
+/// The clients and servers added by a single server process, which are
+/// removed from the game world when the server process disconnects.
+struct RemoteConnection {
+    control_message_tx: crossbeam_channel::Sender<ControlMessage>,
+    response_tx: mpsc::UnboundedSender<RemoteControlResponse>,
+    packet_codec_seeds: PacketCodecSeeds,
+    clients: HashMap<u32, RemoteClient>,
+    servers: Vec<Entity>,
+}
+
+impl RemoteConnection {
+    async fn add_server(
+        &mut self,
+        request_id: u32,
+        create_message: impl FnOnce(oneshot::Sender<Entity>) -> ControlMessage,
+    ) -> Result<(), anyhow::Error> {
+        let (entity_tx, entity_rx) = oneshot::channel();
+        self.control_message_tx.send(create_message(entity_tx))?;
+        let entity = entity_rx.await?;
+
+        self.servers.push(entity);
+        self.response_tx
+            .send(RemoteControlResponse::ServerAdded {
+                request_id,
+                entity: entity.to_bits(),
+            })
+            .ok();
+        Ok(())
+    }
+
+    async fn handle_request(&mut self, request: RemoteControlRequest) -> Result<(), anyhow::Error> {
+        match request {
+            RemoteControlRequest::Hello { .. } => {
+                return Err(RemoteControlError::InvalidHandshake.into());
+            }
+            RemoteControlRequest::AddClient {
+                client_id,
+                client_type,
+                ip,
+                country,
+                packet_codec_login_token,
+            } => {
+                let (client_message_tx, client_message_rx) = crossbeam_channel::unbounded();
+                let (server_message_tx, mut server_message_rx) = mpsc::unbounded_channel();
+                let (entity_tx, entity_rx) = oneshot::channel();
+                self.control_message_tx.send(ControlMessage::AddClient {
+                    client_type,
+                    ip,
+                    country,
+                    packet_codec_login_token,
+                    client_message_rx,
+                    server_message_tx,
+                    response_tx: entity_tx,
+                })?;
+                let entity = entity_rx.await?;
+
+                self.clients.insert(
+                    client_id,
+                    RemoteClient {
+                        client_type,
+                        entity,
+                        client_message_tx,
+                    },
+                );
+                self.response_tx
+                    .send(RemoteControlResponse::ClientAdded {
+                        client_id,
+                        entity: entity.to_bits(),
+                    })
+                    .ok();
+
+                let response_tx = self.response_tx.clone();
+                tokio::spawn(async move {
+                    while let Some(message) = server_message_rx.recv().await {
+                        if response_tx
+                            .send(RemoteControlResponse::ServerMessage { client_id, message })
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+
+                    // The game world has disconnected the client
+                    response_tx
+                        .send(RemoteControlResponse::ClientRemoved { client_id })
+                        .ok();
+                });
+            }
+            RemoteControlRequest::ClientMessage { client_id, message } => {
+                if let Some(client) = self.clients.get(&client_id) {
+                    client.client_message_tx.send(message).ok();
+                }
+            }
+            RemoteControlRequest::RemoveClient { client_id } => {
+                if let Some(client) = self.clients.remove(&client_id) {
+                    self.control_message_tx.send(ControlMessage::RemoveClient {
+                        client_type: client.client_type,
+                        entity: client.entity,
+                    })?;
+                }
+            }
+            RemoteControlRequest::MalformedPacket { client_id } => {
+                if let Some(client) = self.clients.get(&client_id) {
+                    self.control_message_tx
+                        .send(ControlMessage::MalformedPacket {
+                            entity: client.entity,
+                        })?;
+                }
+            }
+            RemoteControlRequest::AddWorldServer {
+                request_id,
+                name,
+                ip,
+                port,
+                packet_codec_seed,
+            } => {
+                self.add_server(request_id, |response_tx| ControlMessage::AddWorldServer {
+                    name,
+                    ip,
+                    port,
+                    packet_codec_seed,
+                    response_tx,
+                })
+                .await?;
+            }
+            RemoteControlRequest::AddGameServer {
+                request_id,
+                world_server,
+                name,
+                ip,
+                port,
+                packet_codec_seed,
+            } => {
+                self.add_server(request_id, |response_tx| ControlMessage::AddGameServer {
+                    world_server: Entity::from_bits(world_server),
+                    name,
+                    ip,
+                    port,
+                    packet_codec_seed,
+                    response_tx,
+                })
+                .await?;
+            }
+            RemoteControlRequest::RemoveServer { entity } => {
+                let entity = Entity::from_bits(entity);
+                self.servers.retain(|server| *server != entity);
+                self.control_message_tx
+                    .send(ControlMessage::RemoveServer { entity })?;
+            }
+            RemoteControlRequest::UpdateChannelPopulation {
+                game_server,
+                num_clients,
+            } => {
+                self.control_message_tx
+                    .send(ControlMessage::UpdateChannelPopulation {
+                        game_server: Entity::from_bits(game_server),
+                        num_clients: num_clients as usize,
+                    })?;
+            }
+            RemoteControlRequest::PacketCodecSeed(update) => {
+                self.packet_codec_seeds.apply(update);
+            }
+            RemoteControlRequest::StartMaintenance {
+                countdown_secs,
+                reason,
+            } => {
+                self.control_message_tx
+                    .send(ControlMessage::StartMaintenance {
+                        countdown: Duration::from_secs(countdown_secs),
+                        reason,
+                    })?;
+            }
+            RemoteControlRequest::CancelMaintenance => {
+                self.control_message_tx
+                    .send(ControlMessage::CancelMaintenance)?;
+            }
+            RemoteControlRequest::GetLeaderboards { request_id } => {
+                let (leaderboards_tx, leaderboards_rx) = oneshot::channel();
+                self.control_message_tx
+                    .send(ControlMessage::GetLeaderboards {
+                        response_tx: leaderboards_tx,
+                    })?;
+
+                // The game world only responds once per tick, so do not block
+                // the client messages of this connection whilst waiting
+                let response_tx = self.response_tx.clone();
+                tokio::spawn(async move {
+                    if let Ok(leaderboards) = leaderboards_rx.await {
+                        response_tx
+                            .send(RemoteControlResponse::Leaderboards {
+                                request_id,
+                                leaderboards,
+                            })
+                            .ok();
+                    }
+                });
+            }
+            RemoteControlRequest::SpawnItem {
+                request_id,
+                character_name,
+                item_spawn,
+            } => {
+                let (result_tx, result_rx) = oneshot::channel();
+                self.control_message_tx.send(ControlMessage::SpawnItem {
+                    character_name,
+                    item_spawn,
+                    response_tx: result_tx,
+                })?;
+
+                let response_tx = self.response_tx.clone();
+                tokio::spawn(async move {
+                    if let Ok(result) = result_rx.await {
+                        response_tx
+                            .send(RemoteControlResponse::ItemSpawned { request_id, result })
+                            .ok();
+                    }
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove_all(&mut self) {
+        for (_, client) in self.clients.drain() {
+            self.control_message_tx
+                .send(ControlMessage::RemoveClient {
+                    client_type: client.client_type,
+                    entity: client.entity,
+                })
+                .ok();
+        }
+
+        for entity in self.servers.drain(..) {
+            self.control_message_tx
+                .send(ControlMessage::RemoveServer { entity })
+                .ok();
+        }
+    }
+}
+
+/// Sends the hello to a newly connected server process and checks its proof
+/// of the shared secret.
+async fn authenticate_remote_connection(
+    reader: &mut OwnedReadHalf,
+    writer: &mut OwnedWriteHalf,
+    secret: &str,
+    world_server: Option<Entity>,
+) -> Result<(), anyhow::Error> {
+    let nonce = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+    write_frame(
+        writer,
+        &RemoteControlResponse::Hello {
+            version: REMOTE_CONTROL_VERSION,
+            nonce: nonce.clone(),
+        },
+    )
+    .await?;
+
+    let request = tokio::time::timeout(
+        REMOTE_CONTROL_HELLO_TIMEOUT,
+        read_frame_with_limit(reader, MAX_HELLO_FRAME_SIZE),
+    )
+    .await
+    .map_err(|_| RemoteControlError::Timeout)??;
+    let RemoteControlRequest::Hello { proof } = request else {
+        return Err(RemoteControlError::InvalidHandshake.into());
+    };
+    if !is_valid_proof(secret, &nonce, &proof) {
+        return Err(RemoteControlError::AuthenticationFailed.into());
+    }
+
+    write_frame(
+        writer,
+        &RemoteControlResponse::Authenticated {
+            world_server: world_server.map(Entity::to_bits),
+        },
+    )
+    .await
+}
+
+async fn run_remote_connection(
+    mut reader: OwnedReadHalf,
+    mut writer: OwnedWriteHalf,
+    control_message_tx: crossbeam_channel::Sender<ControlMessage>,
+    packet_codec_seeds: PacketCodecSeeds,
+) -> Result<(), anyhow::Error> {
+    let (response_tx, mut response_rx) = mpsc::unbounded_channel();
+    let mut seed_updates = packet_codec_seeds.subscribe();
+
+    let writer_task = tokio::spawn(async move {
+        loop {
+            let response = tokio::select! {
+                Some(response) = response_rx.recv() => response,
+                Some(update) = seed_updates.recv() => RemoteControlResponse::PacketCodecSeed(update),
+                else => return,
+            };
+
+            if write_frame(&mut writer, &response).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    let mut connection = RemoteConnection {
+        control_message_tx,
+        response_tx,
+        packet_codec_seeds,
+        clients: HashMap::new(),
+        servers: Vec::new(),
+    };
+    let result = async {
+        loop {
+            let request = read_frame(&mut reader).await?;
+            connection.handle_request(request).await?;
+        }
+    }
+    .await;
+
+    connection.remove_all();
+    writer_task.abort();
+    result
+}
+
+/// Accepts connections from server processes which were started without a
+/// game world, and forwards their control messages to this process's game world.
+pub struct RemoteControlServer {
+    listener: TcpListener,
+    secret: String,
+    control_message_tx: crossbeam_channel::Sender<ControlMessage>,
+    packet_codec_seeds: PacketCodecSeeds,
+    world_server: Option<Entity>,
+}
+
+impl RemoteControlServer {
+    /// Server processes must prove they know the `secret` before they are
+    /// accepted. The `world_server` of this process is sent to each server
+    /// process, so that server processes without a world server can add game
+    /// servers to it.
+    pub fn new(
+        listener: TcpListener,
+        secret: String,
+        control_message_tx: crossbeam_channel::Sender<ControlMessage>,
+        packet_codec_seeds: PacketCodecSeeds,
+        world_server: Option<Entity>,
+    ) -> Self {
+        Self {
+            listener,
+            secret,
+            control_message_tx,
+            packet_codec_seeds,
+            world_server,
+        }
+    }
+
+    pub async fn run(&mut self) {
+        loop {
+            let Ok((socket, addr)) = self.listener.accept().await else {
+                continue;
+            };
+
+            let secret = self.secret.clone();
+            let control_message_tx = self.control_message_tx.clone();
+            let packet_codec_seeds = self.packet_codec_seeds.clone();
+            let world_server = self.world_server;
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = socket.into_split();
+                if let Err(error) =
+                    authenticate_remote_connection(&mut reader, &mut writer, &secret, world_server)
+                        .await
+                {
+                    warn!(
+                        "Rejected remote control connection from {:?}: {}",
+                        addr, error
+                    );
+                    return;
+                }
+                info!("Remote control connection from: {:?}", addr);
+
+                if let Err(error) =
+                    run_remote_connection(reader, writer, control_message_tx, packet_codec_seeds)
+                        .await
+                {
+                    info!("Remote control connection error: {:?}", error);
+                }
+            });
+        }
+    }
+}
+
+/// Answers the game world's hello with the proof of the shared secret,
+/// returning the world server of the game world process.
+async fn authenticate(
+    reader: &mut OwnedReadHalf,
+    writer: &mut OwnedWriteHalf,
+    secret: &str,
+) -> Result<Option<u64>, anyhow::Error> {
+    let RemoteControlResponse::Hello { version, nonce } =
+        read_frame_with_limit(reader, MAX_HELLO_FRAME_SIZE).await?
+    else {
+        return Err(RemoteControlError::InvalidHandshake.into());
+    };
+    if version != REMOTE_CONTROL_VERSION {
+        return Err(RemoteControlError::VersionMismatch(version, REMOTE_CONTROL_VERSION).into());
+    }
+
+    write_frame(
+        writer,
+        &RemoteControlRequest::Hello {
+            proof: remote_control_proof(secret, &nonce),
+        },
+    )
+    .await?;
+
+    // The game world closes the connection if the proof is wrong
+    match read_frame_with_limit(reader, MAX_HELLO_FRAME_SIZE).await {
+        Ok(RemoteControlResponse::Authenticated { world_server }) => Ok(world_server),
+        Ok(_) => Err(RemoteControlError::InvalidHandshake.into()),
+        Err(error) => Err(error.context(RemoteControlError::AuthenticationFailed)),
+    }
+}
+
+/// Starts or cancels the maintenance countdown of the game world listening
+/// for remote control connections at `address`. A `countdown` of `None`
+/// cancels the countdown.
+pub async fn send_maintenance_request(
+    address: &str,
+    secret: &str,
+    countdown: Option<Duration>,
+    reason: String,
+) -> Result<(), anyhow::Error> {
+    let socket = TcpStream::connect(address).await?;
+    let (mut reader, mut writer) = socket.into_split();
+    authenticate(&mut reader, &mut writer, secret).await?;
+
+    let request = match countdown {
+        Some(countdown) => RemoteControlRequest::StartMaintenance {
+            countdown_secs: countdown.as_secs(),
+            reason,
+        },
+        None => RemoteControlRequest::CancelMaintenance,
+    };
+    write_frame(&mut writer, &request).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Requests the leaderboards of the game world listening for remote control
+/// connections at `address`, returns None if leaderboards are disabled or
+/// have not been built yet.
+pub async fn request_leaderboards(
+    address: &str,
+    secret: &str,
+) -> Result<Option<Leaderboards>, anyhow::Error> {
+    let socket = TcpStream::connect(address).await?;
+    let (mut reader, mut writer) = socket.into_split();
+    authenticate(&mut reader, &mut writer, secret).await?;
+
+    write_frame(
+        &mut writer,
+        &RemoteControlRequest::GetLeaderboards { request_id: 0 },
+    )
+    .await?;
+    loop {
+        match read_frame(&mut reader).await {
+            Ok(RemoteControlResponse::Leaderboards { leaderboards, .. }) => {
+                return Ok(leaderboards)
+            }
+            Ok(_) => {}
+            Err(error) => {
+                return Err(error.context(RemoteControlError::NoResponse));
+            }
+        }
+    }
+}
+
+/// Gives an item to an online character of the game world listening for
+/// remote control connections at `address`.
+pub async fn request_spawn_item(
+    address: &str,
+    secret: &str,
+    character_name: String,
+    item_spawn: ItemSpawn,
+) -> Result<Result<(), String>, anyhow::Error> {
+    let socket = TcpStream::connect(address).await?;
+    let (mut reader, mut writer) = socket.into_split();
+    authenticate(&mut reader, &mut writer, secret).await?;
+
+    write_frame(
+        &mut writer,
+        &RemoteControlRequest::SpawnItem {
+            request_id: 0,
+            character_name,
+            item_spawn,
+        },
+    )
+    .await?;
+    loop {
+        match read_frame(&mut reader).await {
+            Ok(RemoteControlResponse::ItemSpawned { result, .. }) => return Ok(result),
+            Ok(_) => {}
+            Err(error) => {
+                return Err(error.context(RemoteControlError::NoResponse));
+            }
+        }
+    }
+}
+
+struct RemoteClientState {
+    entity: Option<Entity>,
+    server_message_tx: Option<mpsc::UnboundedSender<ServerMessage>>,
+    response_tx: Option<oneshot::Sender<Entity>>,
+}
+
+/// A message from the servers in this process to send to the game world.
+enum RemoteControlOutgoing {
+    Control {
+        id: u32,
+        message: ControlMessage,
+    },
+    Client {
+        client_id: u32,
+        message: ClientMessage,
+    },
+}
+
+/// Waits on the control and client message channels of the servers in this
+/// process, which can not be awaited, and forwards their messages to the
+/// remote control task. Returns once the servers or the task have stopped.
+fn forward_control_messages(
+    control_message_rx: crossbeam_channel::Receiver<ControlMessage>,
+    outgoing_tx: mpsc::UnboundedSender<RemoteControlOutgoing>,
+) {
+    let mut client_message_rxs: Vec<(u32, crossbeam_channel::Receiver<ClientMessage>)> = Vec::new();
+    let mut next_id = 0u32;
+
+    loop {
+        let mut select = crossbeam_channel::Select::new();
+        select.recv(&control_message_rx);
+        for (_, client_message_rx) in client_message_rxs.iter() {
+            select.recv(client_message_rx);
+        }
+        select.ready();
+
+        loop {
+            match control_message_rx.try_recv() {
+                Ok(message) => {
+                    next_id = next_id.wrapping_add(1);
+                    if let ControlMessage::AddClient {
+                        client_message_rx, ..
+                    } = &message
+                    {
+                        client_message_rxs.push((next_id, client_message_rx.clone()));
+                    }
+
+                    if outgoing_tx
+                        .send(RemoteControlOutgoing::Control {
+                            id: next_id,
+                            message,
+                        })
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => break,
+                Err(crossbeam_channel::TryRecvError::Disconnected) => return,
+            }
+        }
+
+        // A client's channel is disconnected once the client has gone
+        client_message_rxs.retain(|(client_id, client_message_rx)| loop {
+            match client_message_rx.try_recv() {
+                Ok(message) => {
+                    outgoing_tx
+                        .send(RemoteControlOutgoing::Client {
+                            client_id: *client_id,
+                            message,
+                        })
+                        .ok();
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => return true,
+                Err(crossbeam_channel::TryRecvError::Disconnected) => return false,
+            }
+        });
+
+        if outgoing_tx.is_closed() {
+            return;
+        }
+    }
+}
+
+/// Forwards the control messages of the servers in this process to a game
+/// world in another process.
+pub struct RemoteControlClient {
+    pub control_message_tx: crossbeam_channel::Sender<ControlMessage>,
+
+    /// The world server of the game world process, if it has one
+    pub world_server: Option<Entity>,
+}
+
+impl RemoteControlClient {
+    /// Connects to the game world listening for remote control connections
+    /// at `address`, proving this process knows the shared `secret`.
+    pub async fn connect(
+        address: &str,
+        secret: &str,
+        packet_codec_seeds: PacketCodecSeeds,
+    ) -> Result<Self, anyhow::Error> {
+        let socket = TcpStream::connect(address).await?;
+        let (mut reader, mut writer) = socket.into_split();
+        let world_server = authenticate(&mut reader, &mut writer, secret).await?;
+
+        let (control_message_tx, control_message_rx) = crossbeam_channel::unbounded();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || forward_control_messages(control_message_rx, outgoing_tx));
+        tokio::spawn(async move {
+            if let Err(error) =
+                run_remote_control_client(reader, writer, outgoing_rx, packet_codec_seeds).await
+            {
+                warn!("Lost connection to game world: {:?}", error);
+            }
+        });
+
+        Ok(Self {
+            control_message_tx,
+            world_server: world_server.map(Entity::from_bits),
+        })
+    }
+}
+
+async fn run_remote_control_client(
+    mut reader: OwnedReadHalf,
+    mut writer: OwnedWriteHalf,
+    mut outgoing_rx: mpsc::UnboundedReceiver<RemoteControlOutgoing>,
+    packet_codec_seeds: PacketCodecSeeds,
+) -> Result<(), anyhow::Error> {
+    let (response_tx, mut response_rx) = mpsc::unbounded_channel();
+    let reader_task = tokio::spawn(async move {
+        loop {
+            match read_frame::<RemoteControlResponse>(&mut reader).await {
+                Ok(response) => {
+                    if response_tx.send(response).is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    });
+
+    let mut seed_updates = packet_codec_seeds.subscribe();
+    let mut clients: HashMap<u32, RemoteClientState> = HashMap::new();
+    let mut pending_servers: HashMap<u32, oneshot::Sender<Entity>> = HashMap::new();
+    let mut pending_leaderboards: HashMap<u32, oneshot::Sender<Option<Leaderboards>>> =
+        HashMap::new();
+    let mut pending_item_spawns: HashMap<u32, oneshot::Sender<Result<(), String>>> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            response = response_rx.recv() => {
+                let Some(response) = response else {
+                    return reader_task.await?;
+                };
+
+                match response {
+                    RemoteControlResponse::Hello { .. }
+                    | RemoteControlResponse::Authenticated { .. } => {}
+                    RemoteControlResponse::ClientAdded { client_id, entity } => {
+                        if let Some(client) = clients.get_mut(&client_id) {
+                            let entity = Entity::from_bits(entity);
+                            client.entity = Some(entity);
+                            if let Some(response_tx) = client.response_tx.take() {
+                                response_tx.send(entity).ok();
+                            }
+                        }
+                    }
+                    RemoteControlResponse::ServerMessage { client_id, message } => {
+                        if let Some(server_message_tx) = clients
+                            .get(&client_id)
+                            .and_then(|client| client.server_message_tx.as_ref())
+                        {
+                            server_message_tx.send(message).ok();
+                        }
+                    }
+                    RemoteControlResponse::ClientRemoved { client_id } => {
+                        // Dropping the sender disconnects the client
+                        if let Some(client) = clients.get_mut(&client_id) {
+                            client.server_message_tx = None;
+                        }
+                    }
+                    RemoteControlResponse::ServerAdded { request_id, entity } => {
+                        if let Some(response_tx) = pending_servers.remove(&request_id) {
+                            response_tx.send(Entity::from_bits(entity)).ok();
+                        }
+                    }
+                    RemoteControlResponse::PacketCodecSeed(update) => {
+                        packet_codec_seeds.apply(update);
+                    }
+                    RemoteControlResponse::Leaderboards { request_id, leaderboards } => {
+                        if let Some(response_tx) = pending_leaderboards.remove(&request_id) {
+                            response_tx.send(leaderboards).ok();
+                        }
+                    }
+                    RemoteControlResponse::ItemSpawned { request_id, result } => {
+                        if let Some(response_tx) = pending_item_spawns.remove(&request_id) {
+                            response_tx.send(result).ok();
+                        }
+                    }
+                }
+            }
+            Some(update) = seed_updates.recv() => {
+                write_frame(&mut writer, &RemoteControlRequest::PacketCodecSeed(update)).await?;
+            }
+            outgoing = outgoing_rx.recv() => {
+                // The servers in this process have stopped
+                let Some(outgoing) = outgoing else {
+                    return Ok(());
+                };
+
+                let request = match outgoing {
+                    RemoteControlOutgoing::Client { client_id, message } => {
+                        RemoteControlRequest::ClientMessage { client_id, message }
+                    }
+                    RemoteControlOutgoing::Control { id, message } => match message {
+                        ControlMessage::AddClient {
+                            client_type,
+                            ip,
+                            country,
+                            packet_codec_login_token,
+                            server_message_tx,
+                            response_tx,
+                            ..
+                        } => {
+                            clients.insert(
+                                id,
+                                RemoteClientState {
+                                    entity: None,
+                                    server_message_tx: Some(server_message_tx),
+                                    response_tx: Some(response_tx),
+                                },
+                            );
+                            RemoteControlRequest::AddClient {
+                                client_id: id,
+                                client_type,
+                                ip,
+                                country,
+                                packet_codec_login_token,
+                            }
+                        }
+                        ControlMessage::RemoveClient { entity, .. } => {
+                            let Some(client_id) = clients
+                                .iter()
+                                .find(|(_, client)| client.entity == Some(entity))
+                                .map(|(client_id, _)| *client_id)
+                            else {
+                                continue;
+                            };
+                            clients.remove(&client_id);
+                            RemoteControlRequest::RemoveClient { client_id }
+                        }
+                        ControlMessage::MalformedPacket { entity } => {
+                            let Some(client_id) = clients
+                                .iter()
+                                .find(|(_, client)| client.entity == Some(entity))
+                                .map(|(client_id, _)| *client_id)
+                            else {
+                                continue;
+                            };
+                            RemoteControlRequest::MalformedPacket { client_id }
+                        }
+                        ControlMessage::AddWorldServer {
+                            name,
+                            ip,
+                            port,
+                            packet_codec_seed,
+                            response_tx,
+                        } => {
+                            pending_servers.insert(id, response_tx);
+                            RemoteControlRequest::AddWorldServer {
+                                request_id: id,
+                                name,
+                                ip,
+                                port,
+                                packet_codec_seed,
+                            }
+                        }
+                        ControlMessage::AddGameServer {
+                            world_server,
+                            name,
+                            ip,
+                            port,
+                            packet_codec_seed,
+                            response_tx,
+                        } => {
+                            pending_servers.insert(id, response_tx);
+                            RemoteControlRequest::AddGameServer {
+                                request_id: id,
+                                world_server: world_server.to_bits(),
+                                name,
+                                ip,
+                                port,
+                                packet_codec_seed,
+                            }
+                        }
+                        ControlMessage::RemoveServer { entity } => {
+                            RemoteControlRequest::RemoveServer {
+                                entity: entity.to_bits(),
+                            }
+                        }
+                        ControlMessage::UpdateChannelPopulation {
+                            game_server,
+                            num_clients,
+                        } => RemoteControlRequest::UpdateChannelPopulation {
+                            game_server: game_server.to_bits(),
+                            num_clients: num_clients as u32,
+                        },
+                        ControlMessage::StartMaintenance { countdown, reason } => {
+                            RemoteControlRequest::StartMaintenance {
+                                countdown_secs: countdown.as_secs(),
+                                reason,
+                            }
+                        }
+                        ControlMessage::CancelMaintenance => {
+                            RemoteControlRequest::CancelMaintenance
+                        }
+                        ControlMessage::GetLeaderboards { response_tx } => {
+                            pending_leaderboards.insert(id, response_tx);
+                            RemoteControlRequest::GetLeaderboards {
+                                request_id: id,
+                            }
+                        }
+                        ControlMessage::SpawnItem {
+                            character_name,
+                            item_spawn,
+                            response_tx,
+                        } => {
+                            pending_item_spawns.insert(id, response_tx);
+                            RemoteControlRequest::SpawnItem {
+                                request_id: id,
+                                character_name,
+                                item_spawn,
+                            }
+                        }
+                    },
+                };
+                write_frame(&mut writer, &request).await?;
+            }
+        }
+    }
+}
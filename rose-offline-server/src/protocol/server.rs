@@ -1,22 +1,189 @@
 use bevy::ecs::prelude::Entity;
 use lazy_static::__Deref;
-use log::info;
-use std::sync::Arc;
+use log::{info, warn};
+use std::{
+    future::poll_fn,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
 use tokio::{
     net::{TcpListener, TcpStream},
     sync::oneshot,
 };
 
+use rose_network_common::{ConnectionError, PacketDump, PacketError, WebSocketStream};
+
 use crate::{
-    game::messages::{control::ControlMessage, server::ServerMessage},
-    protocol::{Client, Connection, Protocol},
+    game::messages::{
+        control::{ClientType, ControlMessage},
+        server::ServerMessage,
+    },
+    protocol::{
+        proxy_protocol::read_proxy_protocol_header, Client, Connection, CreatePacketCodec, Protocol,
+    },
 };
 
+#[derive(Copy, Clone, Debug)]
+enum ConnectionTransport {
+    Tcp,
+    WebSocket,
+}
+
+/// Waits for a connection on any of the listeners of a server.
+async fn accept_connection(
+    listeners: &[(TcpListener, ConnectionTransport)],
+) -> (TcpStream, ConnectionTransport) {
+    poll_fn(|cx| {
+        for (listener, transport) in listeners {
+            if let Poll::Ready(result) = listener.poll_accept(cx) {
+                return Poll::Ready((result.unwrap().0, *transport));
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// The address sent to clients to connect to a world or game server, for
+/// servers behind NAT where it differs from the address of their listener.
+#[derive(Clone, Debug, Default)]
+pub struct ExternalAddress {
+    pub ip: Option<String>,
+    pub port: Option<u16>,
+}
+
+impl ExternalAddress {
+    fn resolve(&self, listener: &TcpListener) -> (String, u16) {
+        let local_addr = listener.local_addr().unwrap();
+        (
+            self.ip
+                .clone()
+                .unwrap_or_else(|| local_addr.ip().to_string()),
+            self.port.unwrap_or_else(|| local_addr.port()),
+        )
+    }
+}
+
+/// Selects the packet codec for a connection from the seeds issued to login
+/// tokens for the client's address, returning the login token of the seed.
+async fn select_packet_codec(
+    connection: &mut Connection<'_>,
+    protocol: &Protocol,
+    create_packet_codec: &CreatePacketCodec,
+    ip: IpAddr,
+) -> Result<Option<u32>, anyhow::Error> {
+    // The seeds can be generated by a game world in another process, so only
+    // read them once the client has sent its first packet
+    connection.wait_for_first_packet().await?;
+
+    // Usually there is a single pending seed per address, there are only
+    // more when several clients connect from behind the same NAT
+    let seeds = protocol
+        .options
+        .packet_codec_seeds
+        .get_pending(protocol.client_type, ip);
+    let packet_codecs = seeds
+        .iter()
+        .map(|(seed, _)| create_packet_codec(*seed))
+        .collect();
+
+    match connection.select_packet_codec(packet_codecs).await {
+        Ok(Some(index)) => {
+            let (seed, login_token) = seeds[index];
+
+            // Each seed can only be used by a single connection
+            if !protocol.options.packet_codec_seeds.remove(seed) {
+                warn!(
+                    "Rejected {:?} connection using an already claimed packet codec seed",
+                    protocol.client_type
+                );
+                return Err(ConnectionError::DecryptHeaderFailed.into());
+            }
+            Ok(Some(login_token))
+        }
+        Ok(None) => {
+            if protocol.options.strict_packet_codec {
+                warn!(
+                    "Rejected {:?} connection which did not use a per connection packet codec seed",
+                    protocol.client_type
+                );
+                Err(ConnectionError::DecryptHeaderFailed.into())
+            } else {
+                Ok(None)
+            }
+        }
+        Err(error) => {
+            if let Some(ConnectionError::DecryptHeaderFailed) =
+                error.downcast_ref::<ConnectionError>()
+            {
+                warn!(
+                    "Rejected {:?} connection whose first packet failed packet codec validation",
+                    protocol.client_type
+                );
+            }
+            Err(error)
+        }
+    }
+}
+
 async fn run_connection(
-    stream: TcpStream,
+    mut stream: TcpStream,
+    transport: ConnectionTransport,
     protocol: &Protocol,
     control_message_tx: crossbeam_channel::Sender<ControlMessage>,
 ) -> Result<(), anyhow::Error> {
+    // Behind a load balancer the connection is from the load balancer, the
+    // client address is sent by it before any client data
+    let mut address = stream.peer_addr()?;
+    if protocol.options.proxy_protocol
+        && protocol
+            .options
+            .trusted_proxies
+            .iter()
+            .any(|trusted_proxy| trusted_proxy.contains(address.ip()))
+    {
+        if let Some(client_address) = read_proxy_protocol_header(&mut stream).await? {
+            address = client_address;
+        }
+    }
+    info!(
+        "{:?} server connection from: {:?}",
+        protocol.client_type, address
+    );
+
+    // The country is looked up here so the game world does not wait on it
+    let country = match (protocol.client_type, protocol.options.geo_ip.as_ref()) {
+        (ClientType::Login, Some(geo_ip)) => {
+            let ip = address.ip();
+            let country = geo_ip.lookup(ip).map(String::from);
+            info!(
+                target: "analytics",
+                "Login connection from {} in country {}",
+                ip,
+                country.as_deref().unwrap_or("unknown")
+            );
+            country
+        }
+        _ => None,
+    };
+
+    let mut connection = match transport {
+        ConnectionTransport::Tcp => Connection::new(stream, protocol.packet_codec.deref()),
+        ConnectionTransport::WebSocket => Connection::new_websocket(
+            WebSocketStream::accept(stream).await?,
+            protocol.packet_codec.deref(),
+        ),
+    };
+
+    let packet_codec_login_token = if let Some(create_packet_codec) =
+        protocol.create_packet_codec.as_ref()
+    {
+        select_packet_codec(&mut connection, protocol, create_packet_codec, address.ip()).await?
+    } else {
+        None
+    };
+
     let (client_message_tx, client_message_rx) = crossbeam_channel::unbounded();
     let (server_message_tx, server_message_rx) =
         tokio::sync::mpsc::unbounded_channel::<ServerMessage>();
@@ -24,6 +191,9 @@ async fn run_connection(
 
     control_message_tx.send(ControlMessage::AddClient {
         client_type: protocol.client_type,
+        ip: address.ip(),
+        country,
+        packet_codec_login_token,
         server_message_tx,
         client_message_rx,
         response_tx,
@@ -32,11 +202,60 @@ async fn run_connection(
     let entity = response_rx.await?;
     let mut client = Client {
         entity,
-        connection: Connection::new(stream, protocol.packet_codec.deref()),
+        connection,
         client_message_tx,
         server_message_rx,
     };
+
+    if let Some(packet_dump_dir) = protocol.options.packet_dump_dir.as_ref() {
+        let client_type = match protocol.client_type {
+            ClientType::Login => "login",
+            ClientType::World => "world",
+            ClientType::Game => "game",
+        };
+        let path = packet_dump_dir.join(format!(
+            "{}_{}_{}.log",
+            chrono::Local::now().format("%Y%m%d_%H%M%S"),
+            client_type,
+            entity.to_bits()
+        ));
+
+        match PacketDump::create(&path) {
+            Ok(mut packet_dump) => {
+                packet_dump.write_header("client_type", client_type);
+                client.connection.set_packet_dump(packet_dump);
+            }
+            Err(error) => warn!(
+                "Failed to create packet dump {} with error {:?}",
+                path.to_string_lossy(),
+                error
+            ),
+        }
+    }
     let result = (protocol.create_server)().run_client(&mut client).await;
+    if let Err(error) = result.as_ref() {
+        if let Some(ConnectionError::DecryptHeaderFailed | ConnectionError::DecryptBodyFailed) =
+            error.downcast_ref::<ConnectionError>()
+        {
+            warn!(
+                "Rejected packet which failed packet codec validation from {:?} client {:?}",
+                protocol.client_type, entity
+            );
+        }
+
+        let is_malformed = error.downcast_ref::<PacketError>().is_some()
+            || matches!(
+                error.downcast_ref::<ConnectionError>(),
+                Some(ConnectionError::DecryptHeaderFailed | ConnectionError::DecryptBodyFailed)
+            );
+        if is_malformed && matches!(protocol.client_type, ClientType::Game) {
+            control_message_tx
+                .send(ControlMessage::MalformedPacket {
+                    entity: client.entity,
+                })
+                .ok();
+        }
+    }
 
     control_message_tx
         .send(ControlMessage::RemoveClient {
@@ -49,7 +268,7 @@ async fn run_connection(
 }
 
 pub struct LoginServer {
-    listener: TcpListener,
+    listeners: Vec<(TcpListener, ConnectionTransport)>,
     protocol: Arc<Protocol>,
     control_message_tx: crossbeam_channel::Sender<ControlMessage>,
 }
@@ -61,25 +280,33 @@ impl LoginServer {
         control_message_tx: crossbeam_channel::Sender<ControlMessage>,
     ) -> Result<LoginServer, anyhow::Error> {
         Ok(LoginServer {
-            listener,
+            listeners: vec![(listener, ConnectionTransport::Tcp)],
             protocol,
             control_message_tx,
         })
     }
 
+    /// Also accept connections on another address
+    pub fn add_listener(&mut self, listener: TcpListener) {
+        self.listeners.push((listener, ConnectionTransport::Tcp));
+    }
+
+    /// Also accept connections which use WebSocket as the transport
+    pub fn add_websocket_listener(&mut self, listener: TcpListener) {
+        self.listeners
+            .push((listener, ConnectionTransport::WebSocket));
+    }
+
     pub async fn run(&mut self) {
         loop {
             tokio::select! {
                 _ = async {
                     loop {
-                        let (socket, _) = self.listener.accept().await.unwrap();
+                        let (socket, transport) = accept_connection(&self.listeners).await;
                         let protocol = self.protocol.clone();
                         let control_message_tx = self.control_message_tx.clone();
                         tokio::spawn(async move {
-                            if let Ok(addr) = socket.peer_addr() {
-                                info!("Login Server new connection from: {:?}", addr);
-                            }
-                            if let Err(err) = run_connection(socket, protocol.deref(), control_message_tx).await {
+                            if let Err(err) = run_connection(socket, transport, protocol.deref(), control_message_tx).await {
                                 info!("Login Server connection error: {:?}", err);
                             }
                         });
@@ -93,7 +320,7 @@ impl LoginServer {
 pub struct WorldServer {
     entity: Entity,
 
-    listener: TcpListener,
+    listeners: Vec<(TcpListener, ConnectionTransport)>,
     protocol: Arc<Protocol>,
     control_message_tx: crossbeam_channel::Sender<ControlMessage>,
 }
@@ -102,15 +329,16 @@ impl WorldServer {
     pub async fn new(
         name: String,
         listener: TcpListener,
+        external_address: &ExternalAddress,
         protocol: Arc<Protocol>,
         control_message_tx: crossbeam_channel::Sender<ControlMessage>,
     ) -> Result<WorldServer, anyhow::Error> {
         let (response_tx, response_rx) = oneshot::channel();
-        let local_addr = listener.local_addr().unwrap();
+        let (ip, port) = external_address.resolve(&listener);
         control_message_tx.send(ControlMessage::AddWorldServer {
             name,
-            ip: local_addr.ip().to_string(),
-            port: local_addr.port(),
+            ip,
+            port,
             packet_codec_seed: protocol.packet_codec.get_seed(),
             response_tx,
         })?;
@@ -118,7 +346,7 @@ impl WorldServer {
 
         Ok(WorldServer {
             entity,
-            listener,
+            listeners: vec![(listener, ConnectionTransport::Tcp)],
             protocol,
             control_message_tx,
         })
@@ -128,19 +356,27 @@ impl WorldServer {
         self.entity
     }
 
+    /// Also accept connections on another address
+    pub fn add_listener(&mut self, listener: TcpListener) {
+        self.listeners.push((listener, ConnectionTransport::Tcp));
+    }
+
+    /// Also accept connections which use WebSocket as the transport
+    pub fn add_websocket_listener(&mut self, listener: TcpListener) {
+        self.listeners
+            .push((listener, ConnectionTransport::WebSocket));
+    }
+
     pub async fn run(&mut self) {
         loop {
             tokio::select! {
                 _ = async {
                     loop {
-                        let (socket, _) = self.listener.accept().await.unwrap();
+                        let (socket, transport) = accept_connection(&self.listeners).await;
                         let protocol = self.protocol.clone();
                         let control_message_tx = self.control_message_tx.clone();
                         tokio::spawn(async move {
-                            if let Ok(addr) = socket.peer_addr() {
-                                info!("World Server new connection from: {:?}", addr);
-                            }
-                            if let Err(err) = run_connection(socket, protocol.deref(), control_message_tx).await {
+                            if let Err(err) = run_connection(socket, transport, protocol.deref(), control_message_tx).await {
                                 info!("World Server connection error: {:?}", err);
                             }
                         });
@@ -161,10 +397,29 @@ impl WorldServer {
     }
 }
 
+/// Reports the number of clients connected to a game server to the world
+/// server, the count is sent whilst locked so the updates can not be reordered.
+fn update_channel_population(
+    game_server: Entity,
+    num_clients: &Mutex<usize>,
+    change: isize,
+    control_message_tx: &crossbeam_channel::Sender<ControlMessage>,
+) {
+    let mut num_clients = num_clients.lock().unwrap();
+    *num_clients = num_clients.saturating_add_signed(change);
+    control_message_tx
+        .send(ControlMessage::UpdateChannelPopulation {
+            game_server,
+            num_clients: *num_clients,
+        })
+        .ok();
+}
+
 pub struct GameServer {
     entity: Entity,
+    num_clients: Arc<Mutex<usize>>,
 
-    listener: TcpListener,
+    listeners: Vec<(TcpListener, ConnectionTransport)>,
     protocol: Arc<Protocol>,
     control_message_tx: crossbeam_channel::Sender<ControlMessage>,
 }
@@ -174,16 +429,17 @@ impl GameServer {
         name: String,
         world_server: Entity,
         listener: TcpListener,
+        external_address: &ExternalAddress,
         protocol: Arc<Protocol>,
         control_message_tx: crossbeam_channel::Sender<ControlMessage>,
     ) -> Result<GameServer, anyhow::Error> {
         let (response_tx, response_rx) = oneshot::channel();
-        let local_addr = listener.local_addr().unwrap();
+        let (ip, port) = external_address.resolve(&listener);
         control_message_tx.send(ControlMessage::AddGameServer {
             name,
             world_server,
-            ip: local_addr.ip().to_string(),
-            port: local_addr.port(),
+            ip,
+            port,
             packet_codec_seed: protocol.packet_codec.get_seed(),
             response_tx,
         })?;
@@ -191,27 +447,40 @@ impl GameServer {
 
         Ok(GameServer {
             entity,
-            listener,
+            num_clients: Arc::new(Mutex::new(0)),
+            listeners: vec![(listener, ConnectionTransport::Tcp)],
             protocol,
             control_message_tx,
         })
     }
 
+    /// Also accept connections on another address
+    pub fn add_listener(&mut self, listener: TcpListener) {
+        self.listeners.push((listener, ConnectionTransport::Tcp));
+    }
+
+    /// Also accept connections which use WebSocket as the transport
+    pub fn add_websocket_listener(&mut self, listener: TcpListener) {
+        self.listeners
+            .push((listener, ConnectionTransport::WebSocket));
+    }
+
     pub async fn run(&mut self) {
         loop {
             tokio::select! {
                 _ = async {
                     loop {
-                        let (socket, _) = self.listener.accept().await.unwrap();
+                        let (socket, transport) = accept_connection(&self.listeners).await;
                         let protocol = self.protocol.clone();
                         let control_message_tx = self.control_message_tx.clone();
+                        let entity = self.entity;
+                        let num_clients = self.num_clients.clone();
                         tokio::spawn(async move {
-                            if let Ok(addr) = socket.peer_addr() {
-                                info!("Game Server connection from: {:?}", addr);
-                            }
-                            if let Err(err) = run_connection(socket, protocol.deref(), control_message_tx).await {
+                            update_channel_population(entity, &num_clients, 1, &control_message_tx);
+                            if let Err(err) = run_connection(socket, transport, protocol.deref(), control_message_tx.clone()).await {
                                 info!("Game Server connection error: {:?}", err);
                             }
+                            update_channel_population(entity, &num_clients, -1, &control_message_tx);
                         });
                     }
                 } => {},
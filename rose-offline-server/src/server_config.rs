@@ -0,0 +1,902 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::ArgMatches;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use rose_offline_server::{
+    game::{
+        storage::LOCAL_STORAGE_DIR, AfkConfig, ChannelCapacityConfig, GameConfig,
+        OfflineVendorConfig, SmtpConfig, StorageBackupConfig, WorldRates,
+    },
+    protocol::ProtocolType,
+};
+
+#[derive(Debug, Error)]
+pub enum ServerConfigError {
+    #[error("Failed to read {path}: {error}")]
+    Read {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    #[error("Failed to parse {path}: {error}")]
+    Parse {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    #[error("Invalid value for {0}: {1}")]
+    InvalidValue(&'static str, String),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DataConfig {
+    /// Path to data.idx, defaults to data.idx in the working directory
+    pub idx: Option<PathBuf>,
+
+    /// Path to extracted data, any files here override ones in data.idx
+    pub path: Option<PathBuf>,
+
+    /// Paths to extracted data which override both `path` and data.idx, such
+    /// as the files for a seasonal event. Earlier overlays take priority
+    pub overlays: Vec<PathBuf>,
+
+    /// Do not load the zone geometry and nav grids, for faster restarts
+    /// during development
+    pub skip_optional: bool,
+
+    /// Path to a cache of the zone geometry and nav grids, which is rebuilt
+    /// when any of the game files they are loaded from change
+    pub cache: Option<PathBuf>,
+
+    /// The STL language column used for game data names
+    pub language: usize,
+
+    /// The STL language column used for any string which is missing or
+    /// empty in the selected language
+    pub fallback_language: usize,
+
+    /// More STL language columns which players can choose for the names in
+    /// strings sent by the server
+    pub client_languages: Vec<usize>,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        Self {
+            idx: None,
+            path: None,
+            overlays: Vec::new(),
+            skip_optional: false,
+            cache: None,
+            language: 1,
+            fallback_language: 1,
+            client_languages: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub ip: String,
+
+    /// More IPs to listen on as well as `ip`, such as an IPv6 address
+    pub additional_ips: Vec<String>,
+
+    /// The world and game server addresses sent to clients, when clients
+    /// connect through NAT to a different address than the listen address
+    pub world_external_ip: Option<String>,
+    pub world_external_port: Option<u16>,
+    pub game_external_ip: Option<String>,
+    pub game_external_port: Option<u16>,
+
+    pub login_port: u16,
+    pub world_port: u16,
+    pub game_port: u16,
+    pub login_websocket_port: Option<u16>,
+    pub world_websocket_port: Option<u16>,
+    pub game_websocket_port: Option<u16>,
+    pub protocol: String,
+
+    /// Directory to write a log of the decrypted packets for each connection
+    pub packet_dump: Option<PathBuf>,
+
+    /// Reject world and game connections which do not use their per connection
+    /// packet codec seed
+    pub strict_packet_codec: bool,
+
+    /// CSV file of IP ranges and their country, used to look up the country
+    /// of login clients
+    pub geo_ip_database: Option<PathBuf>,
+
+    /// Connections start with a PROXY protocol v2 header from a TCP load
+    /// balancer, which gives the real address of the client
+    pub proxy_protocol: bool,
+
+    /// Address ranges in CIDR notation of the load balancers which are
+    /// trusted to send the PROXY protocol header, such as "10.0.0.0/8"
+    pub proxy_protocol_trusted: Vec<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            ip: String::from("127.0.0.1"),
+            additional_ips: Vec::new(),
+            world_external_ip: None,
+            world_external_port: None,
+            game_external_ip: None,
+            game_external_port: None,
+            login_port: 29000,
+            world_port: 29100,
+            game_port: 29200,
+            login_websocket_port: None,
+            world_websocket_port: None,
+            game_websocket_port: None,
+            protocol: String::from("irose"),
+            packet_dump: None,
+            strict_packet_codec: false,
+            geo_ip_database: None,
+            proxy_protocol: false,
+            proxy_protocol_trusted: Vec::new(),
+        }
+    }
+}
+
+/// Which servers run in this process, and how this process connects to the
+/// game world when the servers are split across multiple processes.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeploymentConfig {
+    pub login_server: bool,
+    pub world_server: bool,
+    pub game_server: bool,
+
+    /// Address to accept connections from server processes which were
+    /// started with control_connect, a port on its own listens on loopback.
+    /// The connection is not encrypted, so this must never be reachable from
+    /// the public internet
+    pub control_listen: Option<String>,
+
+    /// Address of the game world process, the servers in this process
+    /// forward their clients to it instead of running a game world
+    pub control_connect: Option<String>,
+
+    /// Shared secret which server processes must prove they know before the
+    /// game world accepts their connection, required by control_listen and
+    /// control_connect
+    pub control_secret: Option<String>,
+}
+
+impl DeploymentConfig {
+    /// Returns the address to accept remote control connections on, which
+    /// defaults to the loopback interface when only a port is given.
+    pub fn control_listen_address(&self) -> Option<String> {
+        self.control_listen.as_ref().map(|address| {
+            if address.parse::<u16>().is_ok() {
+                format!("127.0.0.1:{}", address)
+            } else {
+                address.clone()
+            }
+        })
+    }
+}
+
+impl Default for DeploymentConfig {
+    fn default() -> Self {
+        Self {
+            login_server: true,
+            world_server: true,
+            game_server: true,
+            control_listen: None,
+            control_connect: None,
+            control_secret: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Directory where accounts, characters, clans etc are stored, defaults to
+    /// ROSE_OFFLINE_STORAGE_DIR or the user's local data directory
+    pub dir: Option<PathBuf>,
+
+    /// Directory where backups are saved, defaults to backups in the storage directory
+    pub backup_dir: Option<PathBuf>,
+
+    /// 0 disables scheduled backups
+    pub backup_interval_mins: u64,
+    pub backup_retention: usize,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            backup_dir: None,
+            backup_interval_mins: 0,
+            backup_retention: 24,
+        }
+    }
+}
+
+/// Paths to the JSON files which configure game rules, and the game flags
+/// which can also be set from the command line.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameFlagsConfig {
+    pub reward_calendar: Option<PathBuf>,
+    pub item_binding: Option<PathBuf>,
+    pub character_creation: Option<PathBuf>,
+    pub name_filter: Option<PathBuf>,
+    pub monster_spawn_scaling: Option<PathBuf>,
+    pub elite_monsters: Option<PathBuf>,
+    pub zone_environment: Option<PathBuf>,
+    pub zone_rules: Option<PathBuf>,
+    pub teleport_gates: Option<PathBuf>,
+    pub item_drops: Option<PathBuf>,
+    pub skill_chains: Option<PathBuf>,
+    pub skill_movement_effects: Option<PathBuf>,
+    pub npc_store_stock: Option<PathBuf>,
+    pub npc_store_currencies: Option<PathBuf>,
+    pub chat_channels: Option<PathBuf>,
+    pub chat_moderation: Option<PathBuf>,
+    pub cheat_detection: Option<PathBuf>,
+    pub client_integrity: Option<PathBuf>,
+    pub geo_ip: Option<PathBuf>,
+    pub achievements: Option<PathBuf>,
+    pub rebirth: Option<PathBuf>,
+    pub level_up: Option<PathBuf>,
+    pub bot_scenarios: Option<PathBuf>,
+    pub clan_creation: Option<PathBuf>,
+    pub shared_quests: Option<PathBuf>,
+    pub quest_rewards: Option<PathBuf>,
+    pub event_zones: Option<PathBuf>,
+    pub invasions: Option<PathBuf>,
+    pub guards: Option<PathBuf>,
+
+    /// 0 does not cap the level of characters
+    pub level_cap: u32,
+
+    /// 0 disables latency compensation
+    pub latency_compensation_ms: u64,
+
+    /// 0 makes every item drop free for all
+    pub item_drop_owner_duration_secs: u64,
+
+    pub disconnect_duplicate_login: bool,
+
+    /// Forget status effects and skill cooldowns when a character logs out
+    pub clear_effects_on_logout: bool,
+
+    /// Accounts which can still log in during server maintenance
+    pub gm_accounts: Vec<String>,
+
+    /// 0 saves and removes characters immediately when they disconnect
+    pub reconnect_grace_period_secs: u64,
+
+    /// 0 never disconnects AFK players
+    pub afk_timeout_mins: u64,
+    pub afk_warning_secs: u64,
+
+    /// AFK players are only disconnected whilst at least this many players
+    /// are online
+    pub afk_kick_min_online: usize,
+    pub afk_exempt_personal_store: bool,
+
+    /// 0 closes personal stores when their owner disconnects
+    pub offline_vendor_duration_mins: u64,
+    pub offline_vendor_max_per_account: usize,
+
+    /// 0 does not limit the number of players in each channel
+    pub channel_max_players: usize,
+    pub channel_overload_warning_percent: u8,
+
+    /// 0 disables leaderboards
+    pub leaderboard_refresh_interval_mins: u64,
+
+    pub tick_profiler: bool,
+}
+
+impl Default for GameFlagsConfig {
+    fn default() -> Self {
+        Self {
+            reward_calendar: None,
+            item_binding: None,
+            character_creation: None,
+            name_filter: None,
+            monster_spawn_scaling: None,
+            elite_monsters: None,
+            zone_environment: None,
+            zone_rules: None,
+            teleport_gates: None,
+            item_drops: None,
+            skill_chains: None,
+            skill_movement_effects: None,
+            npc_store_stock: None,
+            npc_store_currencies: None,
+            chat_channels: None,
+            chat_moderation: None,
+            cheat_detection: None,
+            client_integrity: None,
+            geo_ip: None,
+            achievements: None,
+            rebirth: None,
+            level_up: None,
+            bot_scenarios: None,
+            clan_creation: None,
+            shared_quests: None,
+            quest_rewards: None,
+            event_zones: None,
+            invasions: None,
+            guards: None,
+            level_cap: 0,
+            latency_compensation_ms: 0,
+            item_drop_owner_duration_secs: 60,
+            disconnect_duplicate_login: false,
+            clear_effects_on_logout: false,
+            gm_accounts: Vec::new(),
+            reconnect_grace_period_secs: 30,
+            afk_timeout_mins: 0,
+            afk_warning_secs: 60,
+            afk_kick_min_online: 0,
+            afk_exempt_personal_store: true,
+            offline_vendor_duration_mins: 0,
+            offline_vendor_max_per_account: 1,
+            channel_max_players: 0,
+            channel_overload_warning_percent: 90,
+            leaderboard_refresh_interval_mins: 10,
+            tick_profiler: false,
+        }
+    }
+}
+
+/// The server configuration, read from the TOML file given by --config with
+/// any command line arguments overriding the values from the file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub data: DataConfig,
+    pub network: NetworkConfig,
+    pub deployment: DeploymentConfig,
+    pub storage: StorageConfig,
+
+    /// Mail server used to send email verification and password reset tokens
+    pub smtp: Option<SmtpConfig>,
+    pub world_rates: WorldRates,
+    pub game: GameFlagsConfig,
+}
+
+fn parse_arg<T: std::str::FromStr>(
+    matches: &ArgMatches,
+    name: &'static str,
+) -> Result<Option<T>, ServerConfigError> {
+    matches
+        .value_of(name)
+        .map(|value| {
+            value
+                .parse::<T>()
+                .map_err(|_| ServerConfigError::InvalidValue(name, value.to_string()))
+        })
+        .transpose()
+}
+
+fn read_json_config<T: DeserializeOwned>(path: &Path, name: &str) -> T {
+    let str = std::fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Failed to read {} {}", name, path.display()));
+    serde_json::from_str(&str)
+        .unwrap_or_else(|error| panic!("Failed to parse {} {}: {}", name, path.display(), error))
+}
+
+fn seconds_or_none(seconds: u64) -> Option<Duration> {
+    Some(seconds)
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs)
+}
+
+impl ServerConfig {
+    pub fn load(path: &Path) -> Result<Self, ServerConfigError> {
+        let str = std::fs::read_to_string(path).map_err(|error| ServerConfigError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        toml::from_str(&str).map_err(|error| ServerConfigError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Failed to serialise server config")
+    }
+
+    /// Overrides the values from the config file with any which were given on
+    /// the command line.
+    pub fn apply_args(&mut self, matches: &ArgMatches) -> Result<(), ServerConfigError> {
+        if let Some(path) = matches.value_of("data-idx") {
+            self.data.idx = Some(PathBuf::from(path));
+        }
+        if let Some(path) = matches.value_of("data-path") {
+            self.data.path = Some(PathBuf::from(path));
+        }
+        if let Some(paths) = matches.values_of("data-overlay") {
+            self.data.overlays = paths.map(PathBuf::from).collect();
+        }
+        if matches.is_present("skip-optional-data") {
+            self.data.skip_optional = true;
+        }
+        if let Some(path) = matches.value_of("game-data-cache") {
+            self.data.cache = Some(PathBuf::from(path));
+        }
+        if let Some(language) = parse_arg(matches, "language")? {
+            self.data.language = language;
+        }
+        if let Some(language) = parse_arg(matches, "fallback-language")? {
+            self.data.fallback_language = language;
+        }
+        if let Some(languages) = matches.values_of("client-language") {
+            self.data.client_languages = languages
+                .map(|language| {
+                    language.parse::<usize>().map_err(|_| {
+                        ServerConfigError::InvalidValue("client-language", language.to_string())
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+
+        if let Some(ip) = matches.value_of("ip") {
+            self.network.ip = ip.to_string();
+        }
+        if let Some(ips) = matches.values_of("additional-ip") {
+            self.network.additional_ips = ips.map(String::from).collect();
+        }
+        if let Some(ip) = matches.value_of("world-external-ip") {
+            self.network.world_external_ip = Some(ip.to_string());
+        }
+        if let Some(port) = parse_arg(matches, "world-external-port")? {
+            self.network.world_external_port = Some(port);
+        }
+        if let Some(ip) = matches.value_of("game-external-ip") {
+            self.network.game_external_ip = Some(ip.to_string());
+        }
+        if let Some(port) = parse_arg(matches, "game-external-port")? {
+            self.network.game_external_port = Some(port);
+        }
+        if let Some(port) = parse_arg(matches, "login-port")? {
+            self.network.login_port = port;
+        }
+        if let Some(port) = parse_arg(matches, "world-port")? {
+            self.network.world_port = port;
+        }
+        if let Some(port) = parse_arg(matches, "game-port")? {
+            self.network.game_port = port;
+        }
+        if let Some(port) = parse_arg(matches, "login-websocket-port")? {
+            self.network.login_websocket_port = Some(port);
+        }
+        if let Some(port) = parse_arg(matches, "world-websocket-port")? {
+            self.network.world_websocket_port = Some(port);
+        }
+        if let Some(port) = parse_arg(matches, "game-websocket-port")? {
+            self.network.game_websocket_port = Some(port);
+        }
+        if let Some(protocol) = matches.value_of("protocol") {
+            self.network.protocol = protocol.to_string();
+        }
+        if let Some(path) = matches.value_of("packet-dump") {
+            self.network.packet_dump = Some(PathBuf::from(path));
+        }
+        if matches.is_present("strict-packet-codec") {
+            self.network.strict_packet_codec = true;
+        }
+        if matches.is_present("proxy-protocol") {
+            self.network.proxy_protocol = true;
+        }
+        if let Some(ranges) = matches.values_of("proxy-protocol-trusted") {
+            self.network.proxy_protocol_trusted = ranges.map(String::from).collect();
+        }
+        if let Some(path) = matches.value_of("geo-ip-database") {
+            self.network.geo_ip_database = Some(PathBuf::from(path));
+        }
+
+        if matches.is_present("no-login-server") {
+            self.deployment.login_server = false;
+        }
+        if matches.is_present("no-world-server") {
+            self.deployment.world_server = false;
+        }
+        if matches.is_present("no-game-server") {
+            self.deployment.game_server = false;
+        }
+        if let Some(address) = matches.value_of("control-listen") {
+            self.deployment.control_listen = Some(address.to_string());
+        }
+        if let Some(address) = matches.value_of("control-connect") {
+            self.deployment.control_connect = Some(address.to_string());
+        }
+        if let Some(secret) = matches.value_of("control-secret") {
+            self.deployment.control_secret = Some(secret.to_string());
+        }
+
+        if let Some(path) = matches.value_of("storage-dir") {
+            self.storage.dir = Some(PathBuf::from(path));
+        }
+        if let Some(path) = matches.value_of("storage-backup-dir") {
+            self.storage.backup_dir = Some(PathBuf::from(path));
+        }
+        if let Some(minutes) = parse_arg(matches, "storage-backup-interval")? {
+            self.storage.backup_interval_mins = minutes;
+        }
+        if let Some(retention) = parse_arg(matches, "storage-backup-retention")? {
+            self.storage.backup_retention = retention;
+        }
+
+        let game_config_paths = [
+            ("reward-calendar", &mut self.game.reward_calendar),
+            ("item-binding", &mut self.game.item_binding),
+            ("character-creation", &mut self.game.character_creation),
+            ("name-filter", &mut self.game.name_filter),
+            (
+                "monster-spawn-scaling",
+                &mut self.game.monster_spawn_scaling,
+            ),
+            ("elite-monsters", &mut self.game.elite_monsters),
+            ("zone-environment", &mut self.game.zone_environment),
+            ("zone-rules", &mut self.game.zone_rules),
+            ("teleport-gates", &mut self.game.teleport_gates),
+            ("item-drops", &mut self.game.item_drops),
+            ("skill-chains", &mut self.game.skill_chains),
+            (
+                "skill-movement-effects",
+                &mut self.game.skill_movement_effects,
+            ),
+            ("npc-store-stock", &mut self.game.npc_store_stock),
+            ("npc-store-currencies", &mut self.game.npc_store_currencies),
+            ("chat-channels", &mut self.game.chat_channels),
+            ("chat-moderation", &mut self.game.chat_moderation),
+            ("cheat-detection", &mut self.game.cheat_detection),
+            ("client-integrity", &mut self.game.client_integrity),
+            ("geo-ip", &mut self.game.geo_ip),
+            ("achievements", &mut self.game.achievements),
+            ("rebirth", &mut self.game.rebirth),
+            ("level-up", &mut self.game.level_up),
+            ("bot-scenarios", &mut self.game.bot_scenarios),
+            ("clan-creation", &mut self.game.clan_creation),
+            ("shared-quests", &mut self.game.shared_quests),
+            ("quest-rewards", &mut self.game.quest_rewards),
+            ("event-zones", &mut self.game.event_zones),
+            ("invasions", &mut self.game.invasions),
+            ("guards", &mut self.game.guards),
+        ];
+        for (name, config_path) in game_config_paths {
+            if let Some(path) = matches.value_of(name) {
+                *config_path = Some(PathBuf::from(path));
+            }
+        }
+
+        if let Some(level) = parse_arg(matches, "level-cap")? {
+            self.game.level_cap = level;
+        }
+        if let Some(milliseconds) = parse_arg(matches, "latency-compensation")? {
+            self.game.latency_compensation_ms = milliseconds;
+        }
+        if let Some(seconds) = parse_arg(matches, "item-drop-owner-duration")? {
+            self.game.item_drop_owner_duration_secs = seconds;
+        }
+        if matches.is_present("disconnect-duplicate-login") {
+            self.game.disconnect_duplicate_login = true;
+        }
+        if matches.is_present("clear-effects-on-logout") {
+            self.game.clear_effects_on_logout = true;
+        }
+        if let Some(seconds) = parse_arg(matches, "reconnect-grace-period")? {
+            self.game.reconnect_grace_period_secs = seconds;
+        }
+        if let Some(minutes) = parse_arg(matches, "afk-timeout")? {
+            self.game.afk_timeout_mins = minutes;
+        }
+        if let Some(num_online) = parse_arg(matches, "afk-kick-min-online")? {
+            self.game.afk_kick_min_online = num_online;
+        }
+        if let Some(minutes) = parse_arg(matches, "offline-vendor-duration")? {
+            self.game.offline_vendor_duration_mins = minutes;
+        }
+        if let Some(num_players) = parse_arg(matches, "channel-max-players")? {
+            self.game.channel_max_players = num_players;
+        }
+        if let Some(minutes) = parse_arg(matches, "leaderboard-refresh-interval")? {
+            self.game.leaderboard_refresh_interval_mins = minutes;
+        }
+        if matches.is_present("tick-profiler") {
+            self.game.tick_profiler = true;
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ServerConfigError> {
+        if ProtocolType::from_name(&self.network.protocol).is_none() {
+            return Err(ServerConfigError::InvalidValue(
+                "network.protocol",
+                self.network.protocol.clone(),
+            ));
+        }
+
+        let deployment = &self.deployment;
+        if deployment.control_listen.is_some() && deployment.control_connect.is_some() {
+            return Err(ServerConfigError::InvalidValue(
+                "deployment.control_listen",
+                String::from("can not be used with control_connect"),
+            ));
+        }
+
+        if (deployment.control_listen.is_some() || deployment.control_connect.is_some())
+            && deployment
+                .control_secret
+                .as_ref()
+                .map_or(true, |secret| secret.is_empty())
+        {
+            return Err(ServerConfigError::InvalidValue(
+                "deployment.control_secret",
+                String::from("is required by control_listen and control_connect"),
+            ));
+        }
+
+        if !deployment.login_server
+            && !deployment.world_server
+            && !deployment.game_server
+            && deployment.control_listen.is_none()
+        {
+            return Err(ServerConfigError::InvalidValue(
+                "deployment",
+                String::from("no servers are enabled"),
+            ));
+        }
+
+        // Without a world server in this process, a game server can only join
+        // the world server of the game world process it connects to
+        if deployment.game_server
+            && !deployment.world_server
+            && deployment.control_connect.is_none()
+        {
+            return Err(ServerConfigError::InvalidValue(
+                "deployment.game_server",
+                String::from("requires world_server or control_connect"),
+            ));
+        }
+
+        if self.storage.backup_retention == 0 {
+            return Err(ServerConfigError::InvalidValue(
+                "storage.backup_retention",
+                String::from("must keep at least one backup"),
+            ));
+        }
+
+        let ports = [
+            ("network.login_port", Some(self.network.login_port)),
+            ("network.world_port", Some(self.network.world_port)),
+            ("network.game_port", Some(self.network.game_port)),
+            (
+                "network.login_websocket_port",
+                self.network.login_websocket_port,
+            ),
+            (
+                "network.world_websocket_port",
+                self.network.world_websocket_port,
+            ),
+            (
+                "network.game_websocket_port",
+                self.network.game_websocket_port,
+            ),
+        ];
+        for (index, &(name, port)) in ports.iter().enumerate() {
+            let Some(port) = port else {
+                continue;
+            };
+
+            if port == 0 || ports[..index].iter().any(|&(_, other)| other == Some(port)) {
+                return Err(ServerConfigError::InvalidValue(name, port.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The directory backups are saved to, this must only be called after the
+    /// storage directory has been set.
+    pub fn storage_backup_dir(&self) -> PathBuf {
+        self.storage
+            .backup_dir
+            .clone()
+            .unwrap_or_else(|| LOCAL_STORAGE_DIR.join("backups"))
+    }
+
+    pub fn create_game_config(&self) -> GameConfig {
+        let game = &self.game;
+
+        GameConfig {
+            enable_npc_spawns: true,
+            enable_monster_spawns: true,
+            reward_calendar: game
+                .reward_calendar
+                .as_deref()
+                .map(|path| read_json_config(path, "reward calendar")),
+            item_binding: game
+                .item_binding
+                .as_deref()
+                .map(|path| read_json_config(path, "item binding"))
+                .unwrap_or_default(),
+            character_creation: game
+                .character_creation
+                .as_deref()
+                .map(|path| read_json_config(path, "character creation"))
+                .unwrap_or_default(),
+            name_filter: game
+                .name_filter
+                .as_deref()
+                .map(|path| read_json_config(path, "name filter"))
+                .unwrap_or_default(),
+            monster_spawn_scaling: game
+                .monster_spawn_scaling
+                .as_deref()
+                .map(|path| read_json_config(path, "monster spawn scaling")),
+            elite_monsters: game
+                .elite_monsters
+                .as_deref()
+                .map(|path| read_json_config(path, "elite monsters")),
+            party_map_marker_interval: Some(Duration::from_secs(5)),
+            latency_compensation: Some(game.latency_compensation_ms)
+                .filter(|milliseconds| *milliseconds > 0)
+                .map(Duration::from_millis),
+            item_drop_owner_duration: seconds_or_none(game.item_drop_owner_duration_secs),
+            item_drops: game
+                .item_drops
+                .as_deref()
+                .map(|path| read_json_config(path, "item drops"))
+                .unwrap_or_default(),
+            skill_chains: game
+                .skill_chains
+                .as_deref()
+                .map(|path| read_json_config(path, "skill chains"))
+                .unwrap_or_default(),
+            skill_movement_effects: game
+                .skill_movement_effects
+                .as_deref()
+                .map(|path| read_json_config(path, "skill movement effects"))
+                .unwrap_or_default(),
+            zone_environment: game
+                .zone_environment
+                .as_deref()
+                .map(|path| read_json_config(path, "zone environment"))
+                .unwrap_or_default(),
+            zone_rules: game
+                .zone_rules
+                .as_deref()
+                .map(|path| read_json_config(path, "zone rules"))
+                .unwrap_or_default(),
+            teleport_gates: game
+                .teleport_gates
+                .as_deref()
+                .map(|path| read_json_config(path, "teleport gates"))
+                .unwrap_or_default(),
+            npc_store_stock: game
+                .npc_store_stock
+                .as_deref()
+                .map(|path| read_json_config(path, "npc store stock"))
+                .unwrap_or_default(),
+            npc_store_currencies: game
+                .npc_store_currencies
+                .as_deref()
+                .map(|path| read_json_config(path, "npc store currencies"))
+                .unwrap_or_default(),
+            chat_channels: game
+                .chat_channels
+                .as_deref()
+                .map(|path| read_json_config(path, "chat channels"))
+                .unwrap_or_default(),
+            chat_moderation: game
+                .chat_moderation
+                .as_deref()
+                .map(|path| read_json_config(path, "chat moderation"))
+                .unwrap_or_default(),
+            cheat_detection: game
+                .cheat_detection
+                .as_deref()
+                .map(|path| read_json_config(path, "cheat detection"))
+                .unwrap_or_default(),
+            client_integrity: game
+                .client_integrity
+                .as_deref()
+                .map(|path| read_json_config(path, "client integrity")),
+            geo_ip: game
+                .geo_ip
+                .as_deref()
+                .map(|path| read_json_config(path, "GeoIP")),
+            achievements: game
+                .achievements
+                .as_deref()
+                .map(|path| read_json_config(path, "achievements"))
+                .unwrap_or_default(),
+            level_up: game
+                .level_up
+                .as_deref()
+                .map(|path| read_json_config(path, "level up"))
+                .unwrap_or_default(),
+            level_cap: (game.level_cap > 0).then_some(game.level_cap),
+            rebirth: game
+                .rebirth
+                .as_deref()
+                .map(|path| read_json_config(path, "rebirth")),
+            bot_scenarios: game
+                .bot_scenarios
+                .as_deref()
+                .map(|path| read_json_config(path, "bot scenarios"))
+                .unwrap_or_default(),
+            clan_creation: game
+                .clan_creation
+                .as_deref()
+                .map(|path| read_json_config(path, "clan creation"))
+                .unwrap_or_default(),
+            shared_quests: game
+                .shared_quests
+                .as_deref()
+                .map(|path| read_json_config(path, "shared quests"))
+                .unwrap_or_default(),
+            quest_rewards: game
+                .quest_rewards
+                .as_deref()
+                .map(|path| read_json_config(path, "quest rewards"))
+                .unwrap_or_default(),
+            event_zones: game
+                .event_zones
+                .as_deref()
+                .map(|path| read_json_config(path, "event zones"))
+                .unwrap_or_default(),
+            invasions: game
+                .invasions
+                .as_deref()
+                .map(|path| read_json_config(path, "invasions"))
+                .unwrap_or_default(),
+            guards: game
+                .guards
+                .as_deref()
+                .map(|path| read_json_config(path, "guards"))
+                .unwrap_or_default(),
+            disconnect_duplicate_login: game.disconnect_duplicate_login,
+            clear_effects_on_logout: game.clear_effects_on_logout,
+            gm_accounts: game.gm_accounts.clone(),
+            reconnect_grace_period: seconds_or_none(game.reconnect_grace_period_secs),
+            afk: (game.afk_timeout_mins > 0).then(|| AfkConfig {
+                timeout: Duration::from_secs(game.afk_timeout_mins * 60),
+                warning_duration: Duration::from_secs(game.afk_warning_secs),
+                kick_min_online: game.afk_kick_min_online,
+                exempt_personal_store: game.afk_exempt_personal_store,
+            }),
+            offline_vendor: (game.offline_vendor_duration_mins > 0).then(|| OfflineVendorConfig {
+                max_duration: Duration::from_secs(game.offline_vendor_duration_mins * 60),
+                max_per_account: game.offline_vendor_max_per_account,
+            }),
+            channel_capacity: (game.channel_max_players > 0).then(|| ChannelCapacityConfig {
+                max_players: game.channel_max_players,
+                overload_warning_percent: game.channel_overload_warning_percent,
+            }),
+            tick_profiler_budget: game
+                .tick_profiler
+                .then(|| Duration::from_secs_f64(1.0 / 60.0)),
+            leaderboard_refresh_interval: (game.leaderboard_refresh_interval_mins > 0)
+                .then(|| Duration::from_secs(game.leaderboard_refresh_interval_mins * 60)),
+            storage_backup: (self.storage.backup_interval_mins > 0).then(|| StorageBackupConfig {
+                dir: self.storage_backup_dir(),
+                interval: Duration::from_secs(self.storage.backup_interval_mins * 60),
+                retention: self.storage.backup_retention,
+            }),
+            smtp: self.smtp.clone(),
+            world_rates: self.world_rates.clone(),
+        }
+    }
+}
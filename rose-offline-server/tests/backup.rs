@@ -0,0 +1,58 @@
+mod support;
+
+use rose_data::{ItemReference, ItemType, StackableItem};
+use rose_game_common::data::Password;
+use rose_offline_server::storage::{
+    account::AccountStorage,
+    backup::{create_backup, list_backups, StorageBackup},
+    bank::BankStorage,
+};
+
+#[test]
+fn storage_backup_restore_round_trip() {
+    let storage_dir = support::storage_dir();
+    let backup_dir = storage_dir.join("backups");
+    let password = Password::Plaintext(String::from("backup password"));
+
+    let mut account = AccountStorage::create("BackupAccount", &password).unwrap();
+    account
+        .character_names
+        .push(String::from("BackupCharacter"));
+    account.save().unwrap();
+
+    let bank = BankStorage {
+        slots: vec![
+            Some(
+                StackableItem::new(ItemReference::new(ItemType::Consumable, 12), 34)
+                    .unwrap()
+                    .into(),
+            ),
+            None,
+        ],
+    };
+    bank.save("BackupAccount").unwrap();
+
+    for _ in 0..3 {
+        create_backup(&backup_dir, 2).expect("Failed to create backup");
+    }
+    let backups = list_backups(&backup_dir).unwrap();
+    assert_eq!(backups.len(), 2);
+
+    // Documents changed after the backup are replaced by the restore
+    account.character_names.clear();
+    account.save().unwrap();
+    std::fs::remove_file(storage_dir.join("bank").join("BackupAccount.json")).unwrap();
+
+    let backup = StorageBackup::load(backups.last().unwrap()).expect("Failed to load backup");
+    assert_eq!(backup.num_documents(), 2);
+    backup.restore().expect("Failed to restore backup");
+
+    let restored_account = AccountStorage::try_load("BackupAccount", &password).unwrap();
+    assert_eq!(restored_account.character_names, vec!["BackupCharacter"]);
+
+    let restored_bank = BankStorage::try_load("BackupAccount").unwrap();
+    assert_eq!(
+        serde_json::to_value(&restored_bank).unwrap(),
+        serde_json::to_value(&bank).unwrap()
+    );
+}
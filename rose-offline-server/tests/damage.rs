@@ -0,0 +1,53 @@
+mod support;
+
+use rose_network_irose::game_server_packets::ServerPackets as GameServerPackets;
+
+use support::{HeadlessClient, TestServer};
+
+async fn join_zone(server: &TestServer, username: &str, character_name: &str) -> HeadlessClient {
+    let mut client = HeadlessClient::new(username);
+    client
+        .login(server.login_address)
+        .await
+        .expect("Failed to login");
+    client
+        .connect_world()
+        .await
+        .expect("Failed to connect to world server");
+    client
+        .create_character(character_name)
+        .await
+        .expect("Failed to create character");
+    client
+        .select_character(0, character_name)
+        .await
+        .expect("Failed to select character");
+    client.join_zone().await.expect("Failed to join zone");
+    client
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocked_damage_is_whispered() {
+    let server = TestServer::start().await;
+    let mut attacker = join_zone(&server, "blockattacker", "BlockAttacker").await;
+    let mut defender = join_zone(&server, "blockdefender", "BlockDefender").await;
+
+    // The attacker must see the defender to receive its damage
+    attacker
+        .wait_for_packet(GameServerPackets::SpawnEntityCharacter as u16)
+        .await
+        .expect("Failed to see defender");
+
+    attacker
+        .chat_command("/damage 1 1000 block")
+        .await
+        .expect("Failed to send damage command");
+    defender
+        .wait_for_server_whisper("You blocked the attack")
+        .await
+        .expect("Failed to receive block whisper");
+    attacker
+        .wait_for_server_whisper("Your attack was blocked")
+        .await
+        .expect("Failed to receive blocked whisper");
+}
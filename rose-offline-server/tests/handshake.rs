@@ -0,0 +1,165 @@
+mod support;
+
+use tokio::net::TcpListener;
+
+use rose_game_common::messages::server::SecondaryPinError;
+use rose_offline_server::{
+    storage::account::AccountStorage, PacketCodecSeeds, RemoteControlClient, RemoteControlServer,
+};
+
+use support::{HeadlessClient, TestServer, STUB_START_POSITION, STUB_ZONE_ID};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn login_world_game_handshake() {
+    let server = TestServer::start().await;
+    let mut client = HeadlessClient::new("testaccount");
+
+    client
+        .login(server.login_address)
+        .await
+        .expect("Failed to login");
+    client
+        .connect_world()
+        .await
+        .expect("Failed to connect to world server");
+    client
+        .create_character("TestCharacter")
+        .await
+        .expect("Failed to create character");
+
+    let select_character = client
+        .select_character(0, "TestCharacter")
+        .await
+        .expect("Failed to select character");
+    assert_eq!(select_character.character_info.name, "TestCharacter");
+    assert_eq!(select_character.zone_id.get(), STUB_ZONE_ID);
+
+    let entity_id = client.join_zone().await.expect("Failed to join zone");
+    assert_eq!(client.client_entity_id(), Some(entity_id));
+
+    let destination = STUB_START_POSITION + 500.0;
+    let move_entity = client
+        .move_to(destination.x, destination.y)
+        .await
+        .expect("Failed to move");
+    assert_eq!(move_entity.x, destination.x);
+    assert_eq!(move_entity.y, destination.y);
+
+    client
+        .chat("Hello world")
+        .await
+        .expect("Failed to receive chat message");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn login_world_game_handshake_with_remote_game_server() {
+    let server = TestServer::start_with_remote_game_server().await;
+    let mut client = HeadlessClient::new("remoteaccount");
+
+    client
+        .login(server.login_address)
+        .await
+        .expect("Failed to login");
+    client
+        .connect_world()
+        .await
+        .expect("Failed to connect to world server");
+    client
+        .create_character("RemoteCharacter")
+        .await
+        .expect("Failed to create character");
+
+    let select_character = client
+        .select_character(0, "RemoteCharacter")
+        .await
+        .expect("Failed to select character");
+    assert_eq!(select_character.zone_id.get(), STUB_ZONE_ID);
+
+    let entity_id = client.join_zone().await.expect("Failed to join zone");
+    assert_eq!(client.client_entity_id(), Some(entity_id));
+
+    client
+        .chat("Hello from another process")
+        .await
+        .expect("Failed to receive chat message");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn remote_control_requires_shared_secret() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (control_message_tx, _control_message_rx) = crossbeam_channel::unbounded();
+    let mut remote_control_server = RemoteControlServer::new(
+        listener,
+        String::from("remote-control-secret"),
+        control_message_tx,
+        PacketCodecSeeds::new(),
+        None,
+    );
+    tokio::spawn(async move {
+        remote_control_server.run().await;
+    });
+
+    assert!(
+        RemoteControlClient::connect(&address, "wrong-secret", PacketCodecSeeds::new())
+            .await
+            .is_err()
+    );
+    assert!(RemoteControlClient::connect(
+        &address,
+        "remote-control-secret",
+        PacketCodecSeeds::new()
+    )
+    .await
+    .is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn secondary_pin_required_before_character_select() {
+    let server = TestServer::start().await;
+    let mut client = HeadlessClient::new("pinaccount");
+
+    client
+        .login(server.login_address)
+        .await
+        .expect("Failed to login");
+
+    // The world server loads the account when the client connects to it
+    let mut account = AccountStorage::load("pinaccount").expect("Failed to load account");
+    account.set_secondary_pin(Some("1234")).unwrap();
+    account.save().unwrap();
+
+    client
+        .connect_world()
+        .await
+        .expect("Failed to connect to world server");
+    client
+        .create_character("PinCharacter")
+        .await
+        .expect("Failed to create character");
+
+    client
+        .request_select_character(0, "PinCharacter")
+        .await
+        .unwrap();
+    assert!(client
+        .wait_for_secondary_pin_request()
+        .await
+        .expect("Failed to receive secondary PIN request")
+        .is_none());
+
+    client.send_secondary_pin("4321").await.unwrap();
+    assert!(matches!(
+        client.wait_for_secondary_pin_request().await.unwrap(),
+        Some(SecondaryPinError::InvalidPin {
+            attempts_remaining: 4
+        })
+    ));
+
+    client.send_secondary_pin("1234").await.unwrap();
+    let select_character = client
+        .connect_game()
+        .await
+        .expect("Failed to select character");
+    assert_eq!(select_character.character_info.name, "PinCharacter");
+}
@@ -0,0 +1,138 @@
+use bevy::math::{Vec2, Vec3};
+
+use rose_data::{ZoneGeometryData, ZoneObstacle};
+
+const CELL_SIZE: f32 = 250.0;
+const GRID_SIZE: usize = 41;
+
+/// Creates flat terrain at height 0 covering 0..10000 with the given obstacles,
+/// `hill` raises the vertices within the x range to height 1000.
+fn zone_geometry(obstacles: Vec<ZoneObstacle>, hill: Option<(f32, f32)>) -> ZoneGeometryData {
+    let mut heights = Vec::with_capacity(GRID_SIZE * GRID_SIZE);
+    for _ in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            let world_x = x as f32 * CELL_SIZE;
+            let is_hill = hill.map_or(false, |(min, max)| (min..=max).contains(&world_x));
+            heights.push(Some(if is_hill { 1000.0 } else { 0.0 }));
+        }
+    }
+
+    ZoneGeometryData::new(
+        Vec2::ZERO,
+        CELL_SIZE,
+        GRID_SIZE,
+        GRID_SIZE,
+        heights,
+        obstacles,
+    )
+}
+
+fn wall(min_x: f32, max_x: f32) -> ZoneObstacle {
+    ZoneObstacle {
+        min: Vec3::new(min_x, 0.0, 0.0),
+        max: Vec3::new(max_x, 10000.0, 500.0),
+    }
+}
+
+#[test]
+fn raycast_without_obstacles() {
+    let zone_geometry = zone_geometry(Vec::new(), None);
+
+    assert_eq!(
+        zone_geometry.raycast(
+            Vec3::new(1000.0, 1000.0, 150.0),
+            Vec3::new(9000.0, 9000.0, 150.0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn raycast_hits_obstacle() {
+    let zone_geometry = zone_geometry(vec![wall(5000.0, 5100.0)], None);
+
+    let hit = zone_geometry
+        .raycast(
+            Vec3::new(1000.0, 5000.0, 150.0),
+            Vec3::new(9000.0, 5000.0, 150.0),
+        )
+        .expect("Expected raycast to hit the wall");
+    assert!((hit.x - 5000.0).abs() < 1.0);
+
+    // The ray passes over the top of the wall
+    assert_eq!(
+        zone_geometry.raycast(
+            Vec3::new(1000.0, 5000.0, 600.0),
+            Vec3::new(9000.0, 5000.0, 600.0)
+        ),
+        None
+    );
+
+    // The ray does not reach the wall
+    assert_eq!(
+        zone_geometry.raycast(
+            Vec3::new(1000.0, 5000.0, 150.0),
+            Vec3::new(4000.0, 5000.0, 150.0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn raycast_ignores_obstacle_containing_either_end() {
+    let zone_geometry = zone_geometry(vec![wall(5000.0, 5100.0)], None);
+
+    assert_eq!(
+        zone_geometry.raycast(
+            Vec3::new(5050.0, 5000.0, 150.0),
+            Vec3::new(9000.0, 5000.0, 150.0)
+        ),
+        None
+    );
+    assert_eq!(
+        zone_geometry.raycast(
+            Vec3::new(1000.0, 5000.0, 150.0),
+            Vec3::new(5050.0, 5000.0, 150.0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn raycast_finds_obstacle_spanning_many_grid_cells() {
+    let obstacle = ZoneObstacle {
+        min: Vec3::new(0.0, 0.0, 0.0),
+        max: Vec3::new(10000.0, 4000.0, 500.0),
+    };
+    let zone_geometry = zone_geometry(vec![obstacle], None);
+
+    assert!(zone_geometry
+        .raycast(
+            Vec3::new(9500.0, 6000.0, 150.0),
+            Vec3::new(9500.0, 1000.0, 150.0)
+        )
+        .is_some());
+}
+
+#[test]
+fn raycast_hits_terrain() {
+    let zone_geometry = zone_geometry(Vec::new(), Some((4500.0, 5500.0)));
+
+    assert_eq!(
+        zone_geometry.get_terrain_height(Vec2::new(5000.0, 5000.0)),
+        Some(1000.0)
+    );
+    assert!(zone_geometry
+        .raycast(
+            Vec3::new(1000.0, 5000.0, 150.0),
+            Vec3::new(9000.0, 5000.0, 150.0)
+        )
+        .is_some());
+    assert_eq!(
+        zone_geometry.raycast(
+            Vec3::new(1000.0, 5000.0, 1500.0),
+            Vec3::new(9000.0, 5000.0, 1500.0)
+        ),
+        None
+    );
+}
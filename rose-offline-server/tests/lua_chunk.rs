@@ -0,0 +1,85 @@
+use rose_file_readers::LuaFunction;
+
+fn lua51_abx(opcode: u32, a: u32, bx: u32) -> u32 {
+    opcode | (a << 6) | (bx << 14)
+}
+
+fn lua51_abc(opcode: u32, a: u32, b: u32, c: u32) -> u32 {
+    opcode | (a << 6) | (c << 14) | (b << 23)
+}
+
+fn push_string(chunk: &mut Vec<u8>, value: &str) {
+    chunk.extend_from_slice(&(value.len() as u32 + 1).to_le_bytes());
+    chunk.extend_from_slice(value.as_bytes());
+    chunk.push(0);
+}
+
+/// Builds a Lua 5.1 function which calls each of `calls` with a constant string argument
+fn lua51_function(chunk: &mut Vec<u8>, calls: &[(&str, &str)], nested: &[&[(&str, &str)]]) {
+    push_string(chunk, "=test");
+    chunk.extend_from_slice(&0u32.to_le_bytes()); // Line defined
+    chunk.extend_from_slice(&0u32.to_le_bytes()); // Last line defined
+    chunk.extend_from_slice(&[0, 0, 2, 2]);
+
+    let mut code = Vec::new();
+    for (index, _) in calls.iter().enumerate() {
+        let constant = index as u32 * 2;
+        code.push(lua51_abx(5, 0, constant)); // GETGLOBAL
+        code.push(lua51_abx(1, 1, constant + 1)); // LOADK
+        code.push(lua51_abc(28, 0, 2, 1)); // CALL
+    }
+    code.push(lua51_abc(30, 0, 1, 0)); // RETURN
+    chunk.extend_from_slice(&(code.len() as u32).to_le_bytes());
+    for instruction in code {
+        chunk.extend_from_slice(&instruction.to_le_bytes());
+    }
+
+    chunk.extend_from_slice(&(calls.len() as u32 * 2).to_le_bytes());
+    for (function, argument) in calls {
+        chunk.push(4);
+        push_string(chunk, function);
+        chunk.push(4);
+        push_string(chunk, argument);
+    }
+
+    chunk.extend_from_slice(&(nested.len() as u32).to_le_bytes());
+    for calls in nested {
+        lua51_function(chunk, calls, &[]);
+    }
+
+    // Line info, local variables, upvalue names
+    chunk.extend_from_slice(&0u32.to_le_bytes());
+    chunk.extend_from_slice(&0u32.to_le_bytes());
+    chunk.extend_from_slice(&0u32.to_le_bytes());
+}
+
+fn lua51_chunk(calls: &[(&str, &str)], nested: &[&[(&str, &str)]]) -> Vec<u8> {
+    let mut chunk = b"\x1bLua\x51\x00\x01\x04\x04\x04\x08\x00".to_vec();
+    lua51_function(&mut chunk, calls, nested);
+    chunk
+}
+
+#[test]
+fn finds_global_call_string_arguments() {
+    let chunk = lua51_chunk(
+        &[
+            ("QF_doQuestTrigger", "TR_001"),
+            ("QF_getEpisodeVAR", "TR_002"),
+        ],
+        &[&[("QF_doQuestTrigger", "TR_003")]],
+    );
+    let function = LuaFunction::read(&chunk).unwrap();
+
+    assert_eq!(
+        function.find_global_call_string_arguments("QF_doQuestTrigger"),
+        vec!["TR_001".to_string(), "TR_003".to_string()]
+    );
+}
+
+#[test]
+fn rejects_truncated_chunk() {
+    let chunk = lua51_chunk(&[("QF_doQuestTrigger", "TR_001")], &[]);
+
+    assert!(LuaFunction::read(&chunk[..chunk.len() - 8]).is_err());
+    assert!(LuaFunction::read(b"\x1bLua\x52").is_err());
+}
@@ -0,0 +1,108 @@
+mod support;
+
+use std::path::{Path, PathBuf};
+
+use rose_network_common::{Packet, PacketDirection, PacketDumpEntry};
+use rose_network_irose::{
+    login_client_packets::{self, PacketClientLoginRequest},
+    world_client_packets::{self, PacketClientCreateCharacter},
+    world_server_packets::{self, CreateCharacterResult, PacketServerCreateCharacterReply},
+};
+
+use support::{HeadlessClient, TestServer};
+
+/// Returns the packets of the dump written for a connection of `client_type`.
+fn read_packet_dump(packet_dump_dir: &Path, client_type: &str) -> Vec<PacketDumpEntry> {
+    let path = std::fs::read_dir(packet_dump_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .contains(&format!("_{}_", client_type))
+        })
+        .unwrap_or_else(|| panic!("No {} packet dump was written", client_type));
+    let dump = std::fs::read_to_string(path).unwrap();
+
+    assert!(dump
+        .lines()
+        .any(|line| PacketDumpEntry::parse_header(line, "client_type") == Some(client_type)));
+    dump.lines()
+        .filter_map(|line| PacketDumpEntry::parse(line).unwrap())
+        .collect()
+}
+
+fn find_packet(entries: &[PacketDumpEntry], direction: PacketDirection, command: u16) -> Packet {
+    let entry = entries
+        .iter()
+        .find(|entry| entry.direction == direction && entry.command == command)
+        .unwrap_or_else(|| panic!("No {} [{:03X}] packet", direction.as_str(), command));
+    Packet::with_data(entry.command, entry.data.as_slice().into())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packet_dump_records_decodable_packets() {
+    let packet_dump_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join(format!("packet-dump-{}", std::process::id()));
+    std::fs::remove_dir_all(&packet_dump_dir).ok();
+    std::fs::create_dir_all(&packet_dump_dir).unwrap();
+
+    let server = TestServer::start_with_packet_dump(packet_dump_dir.clone()).await;
+    let mut client = HeadlessClient::new("dumpaccount");
+    client
+        .login(server.login_address)
+        .await
+        .expect("Failed to login");
+    client
+        .connect_world()
+        .await
+        .expect("Failed to connect to world server");
+    client
+        .create_character("DumpCharacter")
+        .await
+        .expect("Failed to create character");
+
+    // The packets the server received decode to what the client sent
+    let login_entries = read_packet_dump(&packet_dump_dir, "login");
+    let packet = find_packet(
+        &login_entries,
+        PacketDirection::Recv,
+        login_client_packets::ClientPackets::LoginRequest as u16,
+    );
+    let login_request = PacketClientLoginRequest::try_from(&packet).unwrap();
+    assert_eq!(login_request.username, "dumpaccount");
+
+    let world_entries = read_packet_dump(&packet_dump_dir, "world");
+    let packet = find_packet(
+        &world_entries,
+        PacketDirection::Recv,
+        world_client_packets::ClientPackets::CreateCharacter as u16,
+    );
+    let create_character = PacketClientCreateCharacter::try_from(&packet).unwrap();
+    assert_eq!(create_character.name, "DumpCharacter");
+
+    // The packets the server sent decode to the reply the client received
+    let packet = find_packet(
+        &world_entries,
+        PacketDirection::Send,
+        world_server_packets::ServerPackets::CreateCharacterReply as u16,
+    );
+    let reply = PacketServerCreateCharacterReply::try_from(&packet).unwrap();
+    assert_eq!(reply.result, CreateCharacterResult::Ok);
+
+    // Packets are recorded in the order they were sent and received
+    let create_index = world_entries
+        .iter()
+        .position(|entry| {
+            entry.command == world_client_packets::ClientPackets::CreateCharacter as u16
+        })
+        .unwrap();
+    let reply_index = world_entries
+        .iter()
+        .position(|entry| {
+            entry.command == world_server_packets::ServerPackets::CreateCharacterReply as u16
+        })
+        .unwrap();
+    assert!(create_index < reply_index);
+}
@@ -0,0 +1,101 @@
+use bevy::math::Vec2;
+
+use rose_data::ZoneNavGrid;
+
+const CELL_SIZE: f32 = 100.0;
+
+/// Creates a nav grid from rows of '.' for walkable and '#' for blocked cells,
+/// the first row is at the lowest y.
+fn nav_grid(rows: &[&str]) -> ZoneNavGrid {
+    let width = rows[0].len();
+    let height = rows.len();
+    let walkable = rows
+        .iter()
+        .flat_map(|row| row.chars().map(|cell| cell == '.'))
+        .collect();
+    ZoneNavGrid {
+        origin: Vec2::ZERO,
+        cell_size: CELL_SIZE,
+        width,
+        height,
+        walkable,
+    }
+}
+
+fn cell(x: i32, y: i32) -> Vec2 {
+    Vec2::new((x as f32 + 0.5) * CELL_SIZE, (y as f32 + 0.5) * CELL_SIZE)
+}
+
+#[test]
+fn find_path_in_straight_line() {
+    let nav_grid = nav_grid(&["....."; 5]);
+
+    assert_eq!(
+        nav_grid.find_path(cell(0, 0), cell(4, 4)),
+        Some(vec![cell(4, 4)])
+    );
+}
+
+#[test]
+fn find_path_around_wall() {
+    let nav_grid = nav_grid(&[
+        ".....", //
+        ".###.", //
+        ".....",
+    ]);
+
+    let start = cell(2, 0);
+    let end = cell(2, 2);
+    let path = nav_grid.find_path(start, end).expect("Failed to find path");
+    assert!(path.len() > 1);
+    assert_eq!(path.last(), Some(&end));
+
+    // Every leg of the path must be movable
+    let mut position = start;
+    for waypoint in path.iter() {
+        assert!(nav_grid.has_line_of_sight(position, *waypoint));
+        position = *waypoint;
+    }
+}
+
+#[test]
+fn find_path_rejects_blocked_and_out_of_range_positions() {
+    let nav_grid = nav_grid(&[
+        "..#", //
+        "...",
+    ]);
+
+    assert_eq!(nav_grid.find_path(cell(0, 0), cell(2, 0)), None);
+    assert_eq!(nav_grid.find_path(cell(0, 0), cell(5, 0)), None);
+    assert_eq!(nav_grid.find_path(cell(-1, 0), cell(1, 1)), None);
+    assert!(!nav_grid.contains(cell(3, 0)));
+    assert!(!nav_grid.contains(cell(0, -1)));
+    assert!(nav_grid.contains(cell(2, 1)));
+}
+
+#[test]
+fn find_path_unreachable() {
+    let nav_grid = nav_grid(&[
+        ".#.", //
+        ".#.", //
+        ".#.",
+    ]);
+
+    assert_eq!(nav_grid.find_path(cell(0, 1), cell(2, 1)), None);
+}
+
+#[test]
+fn clamp_destination_stops_before_blocked_cells() {
+    let nav_grid = nav_grid(&["..#.."]);
+
+    let clamped = nav_grid.clamp_destination(cell(0, 0), cell(4, 0));
+    assert!(nav_grid.is_walkable(clamped));
+    assert!(clamped.x < 2.0 * CELL_SIZE);
+    assert!(clamped.x >= cell(1, 0).x);
+
+    // A walkable destination is not changed
+    assert_eq!(
+        nav_grid.clamp_destination(cell(0, 0), cell(1, 0)),
+        cell(1, 0)
+    );
+}
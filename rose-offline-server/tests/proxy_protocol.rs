@@ -0,0 +1,35 @@
+use std::net::IpAddr;
+
+use rose_offline_server::protocol::proxy_protocol::TrustedProxy;
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+}
+
+#[test]
+fn trusted_proxy_ranges() {
+    let range: TrustedProxy = "10.1.0.0/16".parse().unwrap();
+    assert!(range.contains(ip("10.1.0.1")));
+    assert!(range.contains(ip("10.1.255.255")));
+    assert!(range.contains(ip("::ffff:10.1.2.3")));
+    assert!(!range.contains(ip("10.2.0.1")));
+    assert!(!range.contains(ip("::a01:1")));
+
+    let single: TrustedProxy = "192.168.0.10".parse().unwrap();
+    assert!(single.contains(ip("192.168.0.10")));
+    assert!(!single.contains(ip("192.168.0.11")));
+
+    let range: TrustedProxy = "fd00::/8".parse().unwrap();
+    assert!(range.contains(ip("fd12::1")));
+    assert!(!range.contains(ip("fe80::1")));
+    assert!(!range.contains(ip("10.0.0.1")));
+
+    let any_ipv4: TrustedProxy = "0.0.0.0/0".parse().unwrap();
+    assert!(any_ipv4.contains(ip("203.0.113.1")));
+    assert!(!any_ipv4.contains(ip("2001:db8::1")));
+
+    assert!("10.0.0.0/33".parse::<TrustedProxy>().is_err());
+    assert!("fd00::/129".parse::<TrustedProxy>().is_err());
+    assert!("10.0.0.0/".parse::<TrustedProxy>().is_err());
+    assert!("load-balancer".parse::<TrustedProxy>().is_err());
+}
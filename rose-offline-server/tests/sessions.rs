@@ -0,0 +1,160 @@
+mod support;
+
+use std::time::Duration;
+
+use rose_network_irose::login_server_packets::LoginResult;
+use rose_offline_server::GameConfig;
+use support::{test_game_config, HeadlessClient, LoginFailed, TestServer};
+
+fn login_result(result: Result<(), anyhow::Error>) -> LoginResult {
+    match result {
+        Ok(()) => LoginResult::Ok,
+        Err(error) => {
+            error
+                .downcast_ref::<LoginFailed>()
+                .unwrap_or_else(|| panic!("Login failed with error {:?}", error))
+                .0
+        }
+    }
+}
+
+/// Logs in again after a client disconnected, the session is only released
+/// once the server has handled the disconnect.
+async fn login_after_disconnect(server: &TestServer, username: &str) -> HeadlessClient {
+    let mut client = HeadlessClient::new(username);
+    let mut attempts = 0;
+    loop {
+        match login_result(client.login(server.login_address).await) {
+            LoginResult::Ok => return client,
+            LoginResult::AlreadyLoggedIn if attempts < 50 => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            result => panic!("Failed to login after disconnecting with {:?}", result),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn simultaneous_logins_claim_one_session() {
+    let server = TestServer::start().await;
+    let mut first = HeadlessClient::new("sessionrace");
+    let mut second = HeadlessClient::new("sessionrace");
+
+    let (first_result, second_result) = tokio::join!(
+        first.login(server.login_address),
+        second.login(server.login_address)
+    );
+
+    let results = (login_result(first_result), login_result(second_result));
+    assert!(
+        matches!(
+            results,
+            (LoginResult::Ok, LoginResult::AlreadyLoggedIn)
+                | (LoginResult::AlreadyLoggedIn, LoginResult::Ok)
+        ),
+        "Expected exactly one login to succeed, got {:?}",
+        results
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn duplicate_login_is_rejected_whilst_in_game() {
+    let server = TestServer::start().await;
+    let mut first = HeadlessClient::new("sessionreject");
+    first.login(server.login_address).await.unwrap();
+    first.connect_world().await.unwrap();
+    first.create_character("SessionReject").await.unwrap();
+    first.select_character(0, "SessionReject").await.unwrap();
+    first.join_zone().await.unwrap();
+
+    let mut second = HeadlessClient::new("sessionreject");
+    assert_eq!(
+        login_result(second.login(server.login_address).await),
+        LoginResult::AlreadyLoggedIn
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn duplicate_login_disconnects_existing_session() {
+    let server = TestServer::start_with_config(GameConfig {
+        disconnect_duplicate_login: true,
+        ..test_game_config()
+    })
+    .await;
+    let mut first = HeadlessClient::new("sessiontakeover");
+    first.login(server.login_address).await.unwrap();
+    first.connect_world().await.unwrap();
+    first.create_character("SessionTakeover").await.unwrap();
+    first.select_character(0, "SessionTakeover").await.unwrap();
+    first.join_zone().await.unwrap();
+
+    let mut second = HeadlessClient::new("sessiontakeover");
+    second
+        .login(server.login_address)
+        .await
+        .expect("Duplicate login should take over the existing session");
+    first
+        .wait_for_disconnect()
+        .await
+        .expect("Existing session should be disconnected");
+
+    second.connect_world().await.unwrap();
+    let select_character = second
+        .select_character(0, "SessionTakeover")
+        .await
+        .expect("Failed to select character after taking over session");
+    assert_eq!(select_character.character_info.name, "SessionTakeover");
+    second.join_zone().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disconnected_character_can_reconnect_within_grace_period() {
+    let server = TestServer::start_with_config(GameConfig {
+        reconnect_grace_period: Some(Duration::from_secs(60)),
+        ..test_game_config()
+    })
+    .await;
+    let mut first = HeadlessClient::new("sessionreconnect");
+    first.login(server.login_address).await.unwrap();
+    first.connect_world().await.unwrap();
+    first.create_character("SessionReconnect").await.unwrap();
+    first.select_character(0, "SessionReconnect").await.unwrap();
+    first.join_zone().await.unwrap();
+    drop(first);
+
+    let mut second = login_after_disconnect(&server, "sessionreconnect").await;
+    second.connect_world().await.unwrap();
+    let select_character = second
+        .select_character(0, "SessionReconnect")
+        .await
+        .expect("Failed to reconnect to character whilst it is still in the world");
+    assert_eq!(select_character.character_info.name, "SessionReconnect");
+    second.join_zone().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn another_character_can_be_selected_within_grace_period() {
+    let server = TestServer::start_with_config(GameConfig {
+        reconnect_grace_period: Some(Duration::from_secs(60)),
+        ..test_game_config()
+    })
+    .await;
+    let mut first = HeadlessClient::new("sessionswitch");
+    first.login(server.login_address).await.unwrap();
+    first.connect_world().await.unwrap();
+    first.create_character("SessionSwitchA").await.unwrap();
+    first.create_character("SessionSwitchB").await.unwrap();
+    first.select_character(0, "SessionSwitchA").await.unwrap();
+    first.join_zone().await.unwrap();
+    drop(first);
+
+    let mut second = login_after_disconnect(&server, "sessionswitch").await;
+    second.connect_world().await.unwrap();
+    let select_character = second
+        .select_character(1, "SessionSwitchB")
+        .await
+        .expect("Failed to select another character whilst one is still in the world");
+    assert_eq!(select_character.character_info.name, "SessionSwitchB");
+    second.join_zone().await.unwrap();
+}
@@ -0,0 +1,988 @@
+mod support;
+
+use std::{
+    collections::HashMap,
+    num::{NonZeroU16, NonZeroUsize},
+    sync::Arc,
+};
+
+use bevy::math::Vec3;
+use chrono::{Duration, Utc};
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use serde_json::Value;
+
+use rose_data::{
+    ClanMemberPosition, EquipmentItem, Item, ItemReference, ItemType, QuestData, QuestDatabase,
+    SkillId, StackableItem, WorldTicks, ZoneId,
+};
+use rose_game_common::{
+    components::{
+        ActiveQuest, BasicStats, CharacterDeleteTime, CharacterGender, CharacterInfo, ClanLevel,
+        ClanMark, ClanPoints, DroppedItem, Equipment, ExperiencePoints, HealthPoints, Hotbar,
+        Inventory, Level, ManaPoints, Money, QuestState, SkillList, SkillPoints, Stamina,
+        StatPoints, UnionMembership,
+    },
+    data::Password,
+    messages::server::{ClanBankAction, ClanBankLogEntry, ClanWarResult},
+};
+use rose_offline_server::{
+    components::{Achievements, HonorPoints, OfflineVendorProceeds, Position, Rebirth, Statistics},
+    storage::{
+        account::AccountStorage,
+        bank::BankStorage,
+        character::{CharacterStorage, SavedEffects},
+        character_inspection::CharacterInspection,
+        chat_mute::ChatMuteStorage,
+        clan::{ClanStorage, ClanStorageMember, ClanStorageWarResult},
+        clan_bank::ClanBankStorage,
+        index_account_character_names,
+        item_drop::{ItemDropStorage, ZoneItemDropStorage},
+        item_transaction::{recover_item_transactions, ItemTransaction},
+        journal::{recover_storage_journals, StorageJournal},
+        leaderboard::{LeaderboardCharacter, LeaderboardEntry, Leaderboards},
+        migrate_account_character_names,
+        quest_repair::{repair_quest_state, QuestRepair},
+        reward_calendar::RewardCalendarStorage,
+        storage_name_key,
+        suspicion::SuspicionStorage,
+    },
+};
+
+/// The number of random documents generated for each round trip test
+const NUM_SAMPLES: usize = 32;
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("Failed to serialize storage")
+}
+
+fn read_json(path: impl AsRef<std::path::Path>) -> serde_json::Map<String, Value> {
+    let str = std::fs::read_to_string(path).expect("Failed to read storage");
+    match serde_json::from_str(&str).expect("Failed to parse storage") {
+        Value::Object(document) => document,
+        _ => panic!("Storage document is not a JSON object"),
+    }
+}
+
+fn random_name(rng: &mut StdRng) -> String {
+    let length = rng.gen_range(4..=16);
+    rng.sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+fn random_item(rng: &mut StdRng) -> Item {
+    if rng.gen_bool(0.5) {
+        let mut item = EquipmentItem::new(
+            ItemReference::new(ItemType::Weapon, rng.gen_range(1..1000)),
+            rng.gen_range(0..=120),
+        )
+        .unwrap();
+        item.instance_id = rng.gen_bool(0.5).then(|| rng.gen());
+        item.into()
+    } else {
+        StackableItem::new(
+            ItemReference::new(ItemType::Consumable, rng.gen_range(1..1000)),
+            rng.gen_range(1..=999),
+        )
+        .unwrap()
+        .into()
+    }
+}
+
+fn random_character(rng: &mut StdRng) -> CharacterStorage {
+    let zone_id = ZoneId::new(rng.gen_range(1..100)).unwrap();
+    let position = Vec3::new(
+        rng.gen_range(0.0..600000.0),
+        rng.gen_range(0.0..600000.0),
+        rng.gen_range(-1000.0..1000.0),
+    );
+
+    let mut inventory = Inventory {
+        money: Money(rng.gen_range(0..i64::MAX)),
+        ..Default::default()
+    };
+    for _ in 0..rng.gen_range(0..20) {
+        inventory.try_add_item(random_item(rng)).ok();
+    }
+
+    let mut equipment = Equipment::default();
+    if rng.gen_bool(0.5) {
+        equipment
+            .equip_item(
+                EquipmentItem::new(
+                    ItemReference::new(ItemType::Weapon, rng.gen_range(1..1000)),
+                    rng.gen_range(0..=120),
+                )
+                .unwrap(),
+            )
+            .ok();
+    }
+
+    let mut quest_state = QuestState::default();
+    for variable in quest_state.episode_variables.iter_mut() {
+        *variable = rng.gen();
+    }
+    for _ in 0..rng.gen_range(0..64) {
+        quest_state.quest_switches.set(rng.gen_range(0..1024), true);
+    }
+    for _ in 0..rng.gen_range(0..4) {
+        quest_state.try_add_quest(ActiveQuest::new(rng.gen_range(1..1000), None));
+    }
+
+    let mut union_membership = UnionMembership {
+        current_union: NonZeroUsize::new(rng.gen_range(0..8)),
+        ..Default::default()
+    };
+    for points in union_membership.points.iter_mut() {
+        *points = rng.gen();
+    }
+
+    let mut achievements = Achievements::default();
+    for id in 0..rng.gen_range(0..8) {
+        if rng.gen_bool(0.5) {
+            achievements.unlock(&format!("achievement_{}", id));
+        } else {
+            achievements.add_progress(&format!("achievement_{}", id), rng.gen_range(1..100), 100);
+        }
+    }
+    if let Some(id) = achievements.unlocked.iter().next() {
+        achievements.equipped_title = Some(id.clone());
+    }
+
+    let mut statistics = Statistics {
+        pvp_kills: rng.gen_range(0..1000),
+        deaths: rng.gen_range(0..1000),
+        playtime_secs: rng.gen_range(0..1_000_000),
+        session_playtime_secs: rng.gen_range(0..10_000),
+        longest_session_playtime_secs: rng.gen_range(10_000..100_000),
+        money_earned: rng.gen_range(0..1_000_000_000),
+        ..Default::default()
+    };
+    for _ in 0..rng.gen_range(0..8) {
+        statistics
+            .monster_kills
+            .insert(rng.gen_range(1..1000), rng.gen_range(1..10000));
+    }
+
+    CharacterStorage {
+        info: CharacterInfo {
+            name: random_name(rng),
+            unique_id: rng.gen(),
+            gender: if rng.gen_bool(0.5) {
+                CharacterGender::Male
+            } else {
+                CharacterGender::Female
+            },
+            race: rng.gen_range(0..2),
+            birth_stone: rng.gen_range(0..12),
+            job: rng.gen_range(0..400),
+            face: rng.gen(),
+            hair: rng.gen(),
+            revive_zone_id: zone_id,
+            revive_position: position,
+            fame: rng.gen(),
+            fame_b: rng.gen(),
+            fame_g: rng.gen(),
+            rank: rng.gen(),
+        },
+        basic_stats: BasicStats {
+            strength: rng.gen_range(1..300),
+            dexterity: rng.gen_range(1..300),
+            intelligence: rng.gen_range(1..300),
+            concentration: rng.gen_range(1..300),
+            charm: rng.gen_range(1..300),
+            sense: rng.gen_range(1..300),
+        },
+        inventory,
+        equipment,
+        level: Level::new(rng.gen_range(1..250)),
+        experience_points: ExperiencePoints::new(rng.gen()),
+        position: Position::new(position, zone_id),
+        skill_list: SkillList::default(),
+        hotbar: Hotbar::default(),
+        delete_time: rng.gen_bool(0.25).then(CharacterDeleteTime::new),
+        health_points: HealthPoints::new(rng.gen_range(0..10000)),
+        mana_points: ManaPoints::new(rng.gen_range(0..10000)),
+        skill_points: SkillPoints::new(rng.gen_range(0..100)),
+        stat_points: StatPoints::new(rng.gen_range(0..1000)),
+        quest_state,
+        union_membership,
+        stamina: Stamina::new(rng.gen_range(0..5000)),
+        achievements,
+        statistics,
+        rebirth: Rebirth {
+            count: rng.gen_range(0..10),
+        },
+        honor_points: HonorPoints {
+            points: rng.gen_range(0..100000),
+        },
+        saved_effects: SavedEffects {
+            status_effects: Vec::new(),
+            skill_cooldowns: (0..rng.gen_range(0..5))
+                .map(|_| {
+                    (
+                        SkillId::new(rng.gen_range(1..1000)).unwrap(),
+                        std::time::Duration::from_millis(rng.gen_range(1..100000)),
+                    )
+                })
+                .collect(),
+            global_cooldown: rng
+                .gen_bool(0.5)
+                .then(|| std::time::Duration::from_millis(rng.gen_range(1..1000))),
+            group_cooldowns: Vec::new(),
+        },
+        offline_vendor_proceeds: rng.gen_bool(0.25).then(|| OfflineVendorProceeds {
+            items_sold: rng.gen_range(1..1000),
+            money: Money(rng.gen_range(1..1_000_000_000)),
+        }),
+    }
+}
+
+#[test]
+fn character_storage_round_trip() {
+    let storage_dir = support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x524f5345);
+
+    for _ in 0..NUM_SAMPLES {
+        let character = random_character(&mut rng);
+        character.save().expect("Failed to save character");
+
+        let document = read_json(
+            storage_dir
+                .join("characters")
+                .join(format!("{}.json", character.info.name)),
+        );
+        assert!(document.contains_key("schema_version"));
+
+        let loaded =
+            CharacterStorage::try_load(&character.info.name).expect("Failed to load character");
+        assert_eq!(to_json(&loaded), to_json(&character));
+    }
+}
+
+#[test]
+fn character_storage_migrates_unversioned_documents() {
+    let storage_dir = support::storage_dir().join("characters");
+    let mut rng = StdRng::seed_from_u64(0x76657230);
+    std::fs::create_dir_all(&storage_dir).unwrap();
+
+    for _ in 0..NUM_SAMPLES {
+        let character = random_character(&mut rng);
+
+        // Documents saved before versioning had no schema_version and were
+        // missing the fields which were added to CharacterStorage later
+        let Value::Object(mut document) = to_json(&character) else {
+            unreachable!();
+        };
+        for key in [
+            "hotbar",
+            "quest_state",
+            "union_membership",
+            "stamina",
+            "achievements",
+            "statistics",
+            "rebirth",
+            "honor_points",
+            "saved_effects",
+            "offline_vendor_proceeds",
+        ] {
+            document.remove(key);
+        }
+        std::fs::write(
+            storage_dir.join(format!("{}.json", character.info.name)),
+            serde_json::to_string(&document).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = CharacterStorage::try_load(&character.info.name)
+            .expect("Failed to load unversioned character");
+        let mut expected = document;
+        for (key, value) in [
+            ("hotbar", to_json(&Hotbar::default())),
+            ("quest_state", to_json(&QuestState::default())),
+            ("union_membership", to_json(&UnionMembership::default())),
+            ("stamina", to_json(&Stamina::new(loaded.stamina.stamina))),
+            ("achievements", to_json(&Achievements::default())),
+            ("statistics", to_json(&Statistics::default())),
+            ("rebirth", to_json(&Rebirth::default())),
+            ("honor_points", to_json(&HonorPoints::default())),
+            ("saved_effects", to_json(&SavedEffects::default())),
+            ("offline_vendor_proceeds", Value::Null),
+        ] {
+            expected.insert(key.to_string(), value);
+        }
+        assert_eq!(to_json(&loaded), Value::Object(expected));
+    }
+}
+
+#[test]
+fn storage_rejects_newer_schema_versions() {
+    let storage_dir = support::storage_dir().join("characters");
+    let mut rng = StdRng::seed_from_u64(0x6675747572);
+    std::fs::create_dir_all(&storage_dir).unwrap();
+
+    let character = random_character(&mut rng);
+    let Value::Object(mut document) = to_json(&character) else {
+        unreachable!();
+    };
+    document.insert("schema_version".to_string(), Value::from(u32::MAX));
+    std::fs::write(
+        storage_dir.join(format!("{}.json", character.info.name)),
+        serde_json::to_string(&document).unwrap(),
+    )
+    .unwrap();
+
+    assert!(CharacterStorage::try_load(&character.info.name).is_err());
+}
+
+#[test]
+fn account_storage_round_trip() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x6163636f756e74);
+
+    for _ in 0..NUM_SAMPLES {
+        let name = random_name(&mut rng);
+        let password = Password::Plaintext(random_name(&mut rng));
+        let mut account = AccountStorage::create(&name, &password).unwrap();
+        account.character_names = (0..rng.gen_range(0..=5))
+            .map(|_| random_name(&mut rng))
+            .collect();
+        account.save().unwrap();
+
+        let loaded = AccountStorage::try_load(&name, &password).unwrap();
+        assert_eq!(to_json(&loaded), to_json(&account));
+    }
+}
+
+#[test]
+fn account_email_binding_and_password_reset() {
+    support::storage_dir();
+    let password = Password::Plaintext(String::from("old password"));
+    let new_password = Password::Plaintext(String::from("new password"));
+    let now = Utc::now();
+
+    let mut account = AccountStorage::create("EmailAccount", &password).unwrap();
+    assert!(account.set_email("not an email", now).is_err());
+    assert!(account.create_password_reset_token(now).is_err());
+
+    let verification_token = account.set_email("player@example.com", now).unwrap();
+    assert!(account.verify_email("wrong token", now).is_err());
+    account.verify_email(&verification_token, now).unwrap();
+    assert!(account.email_verified);
+
+    let reset_token = account.create_password_reset_token(now).unwrap();
+    assert!(account
+        .reset_password(&reset_token, &new_password, now + Duration::hours(2))
+        .is_err());
+    account
+        .reset_password(&reset_token, &new_password, now)
+        .unwrap();
+    assert!(account
+        .reset_password(&reset_token, &password, now)
+        .is_err());
+    account.save().unwrap();
+
+    assert!(AccountStorage::try_load("EmailAccount", &password).is_err());
+    let loaded = AccountStorage::try_load("EmailAccount", &new_password).unwrap();
+    assert_eq!(loaded.email.as_deref(), Some("player@example.com"));
+    assert!(loaded.email_verified);
+}
+
+#[test]
+fn account_and_character_names_are_normalized() {
+    let storage_dir = support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x6e616d65);
+    let password = Password::Plaintext(String::from("password"));
+
+    assert_eq!(storage_name_key("Bob"), storage_name_key("bOB"));
+    assert_eq!(
+        storage_name_key("Caf\u{e9}"),
+        storage_name_key("CAFE\u{301}")
+    );
+    assert_ne!(storage_name_key("Bob"), storage_name_key("Bobby"));
+
+    AccountStorage::create("MixedCaseAccount", &password).unwrap();
+    assert!(AccountStorage::create("mixedcaseaccount", &password).is_err());
+    let loaded = AccountStorage::try_load("MIXEDCASEACCOUNT", &password).unwrap();
+    assert_eq!(loaded.name, "MixedCaseAccount");
+
+    let mut character = random_character(&mut rng);
+    character.info.name = String::from("MixedCaseCharacter");
+    character.try_create(&character.info.name).unwrap();
+    assert!(CharacterStorage::exists("mixedcasecharacter"));
+    let mut duplicate = random_character(&mut rng);
+    duplicate.info.name = String::from("MIXEDCASECHARACTER");
+    assert!(duplicate.try_create(&duplicate.info.name).is_err());
+    let loaded = CharacterStorage::try_load("mixedCaseCharacter").unwrap();
+    assert_eq!(loaded.info.name, "MixedCaseCharacter");
+
+    // Documents saved before names were normalized are renamed to NFC
+    let mut character = random_character(&mut rng);
+    character.info.name = String::from("Decomposede\u{301}");
+    character.save().unwrap();
+    let characters_dir = storage_dir.join("characters");
+    std::fs::rename(
+        characters_dir.join("Decomposed\u{e9}.json"),
+        characters_dir.join("Decomposede\u{301}.json"),
+    )
+    .unwrap();
+
+    assert!(migrate_account_character_names().unwrap() >= 1);
+    assert!(characters_dir.join("Decomposed\u{e9}.json").exists());
+    assert!(CharacterStorage::exists("DECOMPOSEDE\u{301}"));
+
+    // Documents with the same key stop the migration until one is removed
+    let duplicate_path = characters_dir.join("DECOMPOSED\u{c9}.json");
+    std::fs::copy(
+        characters_dir.join("Decomposed\u{e9}.json"),
+        &duplicate_path,
+    )
+    .unwrap();
+    assert!(migrate_account_character_names().is_err());
+    std::fs::remove_file(&duplicate_path).unwrap();
+    assert!(migrate_account_character_names().is_ok());
+
+    // Documents which were not saved by this process are found by the index
+    std::fs::copy(
+        characters_dir.join("Decomposed\u{e9}.json"),
+        characters_dir.join("CopiedCharacter.json"),
+    )
+    .unwrap();
+    assert!(!CharacterStorage::exists("copiedcharacter"));
+    assert!(index_account_character_names() >= 2);
+    assert!(CharacterStorage::exists("copiedcharacter"));
+}
+
+#[test]
+fn bank_storage_round_trip() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x62616e6b);
+
+    for _ in 0..NUM_SAMPLES {
+        let account_name = random_name(&mut rng);
+        let bank = BankStorage {
+            slots: (0..160)
+                .map(|_| rng.gen_bool(0.25).then(|| random_item(&mut rng)))
+                .collect(),
+        };
+        bank.save(&account_name).unwrap();
+
+        let loaded = BankStorage::try_load(&account_name).unwrap();
+        assert_eq!(to_json(&loaded), to_json(&bank));
+    }
+}
+
+#[test]
+fn item_transaction_commit_and_recovery() {
+    let storage_dir = support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x74786e);
+
+    let mut seller = random_character(&mut rng);
+    seller.save().unwrap();
+    let mut buyer = random_character(&mut rng);
+    buyer.save().unwrap();
+
+    // Only the inventory is changed by a transaction
+    seller.inventory.money = Money(1000);
+    seller.level.level += 1;
+    buyer.inventory.money = Money(0);
+    let bank = BankStorage {
+        slots: vec![Some(random_item(&mut rng)), None],
+    };
+
+    let mut transaction = ItemTransaction::new();
+    transaction.update_inventory(&seller.info.name, &seller.inventory);
+    transaction.update_inventory(&buyer.info.name, &buyer.inventory);
+    transaction.update_bank(
+        &seller.info.name,
+        BankStorage {
+            slots: bank.slots.clone(),
+        },
+    );
+    transaction
+        .commit()
+        .expect("Failed to commit item transaction");
+
+    let loaded_seller = CharacterStorage::try_load(&seller.info.name).unwrap();
+    assert_eq!(
+        to_json(&loaded_seller.inventory),
+        to_json(&seller.inventory)
+    );
+    assert_eq!(loaded_seller.level.level + 1, seller.level.level);
+    let loaded_bank = BankStorage::try_load(&seller.info.name).unwrap();
+    assert_eq!(to_json(&loaded_bank), to_json(&bank));
+
+    // A journal left behind by a crash is replayed on recovery
+    buyer.inventory.money = Money(1000);
+    let mut transaction = ItemTransaction::new();
+    transaction.update_inventory(&buyer.info.name, &buyer.inventory);
+    transaction.write_journal().unwrap();
+
+    assert_eq!(recover_item_transactions().unwrap(), 1);
+    let loaded_buyer = CharacterStorage::try_load(&buyer.info.name).unwrap();
+    assert_eq!(loaded_buyer.inventory.money, Money(1000));
+    assert_eq!(
+        std::fs::read_dir(storage_dir.join("item_transactions"))
+            .unwrap()
+            .count(),
+        0
+    );
+    assert_eq!(recover_item_transactions().unwrap(), 0);
+}
+
+#[test]
+fn storage_journal_commit_and_recovery() {
+    let storage_dir = support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x6a726e6c);
+    let password = Password::Plaintext(String::from("password"));
+
+    let account_name = random_name(&mut rng);
+    let mut account = AccountStorage::create(&account_name, &password).unwrap();
+    let character = random_character(&mut rng);
+    let character_name = character.info.name.clone();
+    account.character_names.push(character_name.clone());
+
+    let mut journal = StorageJournal::new();
+    journal.create_character(character);
+    journal.save_account(account.clone());
+    journal.commit().expect("Failed to commit storage journal");
+
+    assert!(CharacterStorage::exists(&character_name));
+    let loaded = AccountStorage::try_load(&account_name, &password).unwrap();
+    assert_eq!(loaded.character_names, account.character_names);
+
+    // Creating a character which already exists fails and leaves no journal
+    let mut duplicate = random_character(&mut rng);
+    duplicate.info.name = character_name.clone();
+    let mut journal = StorageJournal::new();
+    journal.create_character(duplicate);
+    journal.save_account(account.clone());
+    assert!(journal.commit().is_err());
+
+    // A journal left behind by a crash is replayed on recovery
+    account.character_names.clear();
+    let mut journal = StorageJournal::new();
+    journal.delete_character(&character_name);
+    journal.save_account(account.clone());
+    journal.write_journal().unwrap();
+
+    assert_eq!(recover_storage_journals().unwrap(), 1);
+    assert!(!CharacterStorage::exists(&character_name));
+    let loaded = AccountStorage::try_load(&account_name, &password).unwrap();
+    assert!(loaded.character_names.is_empty());
+    assert_eq!(
+        std::fs::read_dir(storage_dir.join("journal"))
+            .unwrap()
+            .count(),
+        0
+    );
+    assert_eq!(recover_storage_journals().unwrap(), 0);
+
+    // Recovery does not overwrite a different character with the same name
+    let mut existing = random_character(&mut rng);
+    existing.info.name = character_name.clone();
+    existing.try_create(&character_name).unwrap();
+    let mut conflicting = random_character(&mut rng);
+    conflicting.info.name = character_name.clone();
+    account.character_names.push(character_name.clone());
+    let mut journal = StorageJournal::new();
+    journal.create_character(conflicting);
+    journal.save_account(account.clone());
+    journal.write_journal().unwrap();
+
+    assert_eq!(recover_storage_journals().unwrap(), 1);
+    let loaded = CharacterStorage::try_load(&character_name).unwrap();
+    assert_eq!(to_json(&loaded), to_json(&existing));
+    let loaded = AccountStorage::try_load(&account_name, &password).unwrap();
+    assert!(loaded.character_names.is_empty());
+
+    // A character created before the crash is kept and added to the account
+    let mut journal = StorageJournal::new();
+    journal.create_character(CharacterStorage::try_load(&character_name).unwrap());
+    journal.save_account(account.clone());
+    journal.write_journal().unwrap();
+
+    assert_eq!(recover_storage_journals().unwrap(), 1);
+    let loaded = AccountStorage::try_load(&account_name, &password).unwrap();
+    assert_eq!(loaded.character_names, account.character_names);
+}
+
+#[test]
+fn character_inspection_loads_offline_character() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x696e7370);
+    let password = Password::Plaintext(String::from("inspect password"));
+
+    let mut character = random_character(&mut rng);
+    character.info.name = String::from("InspectedCharacter");
+    character.save().unwrap();
+
+    let mut account = AccountStorage::create("InspectedAccount", &password).unwrap();
+    account.character_names.push(character.info.name.clone());
+    account.save().unwrap();
+
+    let bank = BankStorage {
+        slots: vec![Some(random_item(&mut rng)), None],
+    };
+    bank.save("InspectedAccount").unwrap();
+
+    assert_eq!(
+        CharacterStorage::find_matching(|name| name.to_lowercase().contains("inspectedchar")),
+        vec!["InspectedCharacter"]
+    );
+
+    let inspection = CharacterInspection::load("inspectedcharacter").unwrap();
+    assert!(!inspection.online);
+    assert_eq!(inspection.account_name.as_deref(), Some("InspectedAccount"));
+    assert_eq!(to_json(&inspection.character), to_json(&character));
+    assert_eq!(to_json(inspection.bank.as_ref().unwrap()), to_json(&bank));
+
+    let path = inspection.save().expect("Failed to save inspection");
+    let document = read_json(path);
+    assert_eq!(document["account_name"], "InspectedAccount");
+    assert_eq!(document["character"]["info"]["name"], "InspectedCharacter");
+
+    assert!(CharacterInspection::load("MissingCharacter").is_err());
+}
+
+#[test]
+fn clan_storage_round_trip() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x636c616e);
+    let positions = [
+        ClanMemberPosition::Penalty,
+        ClanMemberPosition::Junior,
+        ClanMemberPosition::Senior,
+        ClanMemberPosition::Veteran,
+        ClanMemberPosition::Commander,
+        ClanMemberPosition::DeputyMaster,
+        ClanMemberPosition::Master,
+    ];
+
+    for _ in 0..NUM_SAMPLES {
+        let mark = if rng.gen_bool(0.5) {
+            ClanMark::Premade {
+                background: NonZeroU16::new(rng.gen_range(1..100)).unwrap(),
+                foreground: NonZeroU16::new(rng.gen_range(1..100)).unwrap(),
+            }
+        } else {
+            ClanMark::Custom { crc16: rng.gen() }
+        };
+        let mut clan = ClanStorage::new(random_name(&mut rng), random_name(&mut rng), mark);
+        clan.notice = random_name(&mut rng);
+        clan.money = Money(rng.gen_range(0..i64::MAX));
+        clan.points = ClanPoints(rng.gen());
+        clan.level = ClanLevel::new(rng.gen_range(1..8)).unwrap();
+        clan.members = (0..rng.gen_range(1..50))
+            .map(|_| {
+                let mut member = ClanStorageMember::new(
+                    random_name(&mut rng),
+                    positions[rng.gen_range(0..positions.len())],
+                );
+                member.contribution = ClanPoints(rng.gen());
+                member
+            })
+            .collect();
+        clan.war_results = (0..rng.gen_range(0..5))
+            .map(|_| ClanStorageWarResult {
+                opponent: random_name(&mut rng),
+                result: [ClanWarResult::Won, ClanWarResult::Lost, ClanWarResult::Draw]
+                    [rng.gen_range(0..3)],
+                score: rng.gen(),
+                opponent_score: rng.gen(),
+            })
+            .collect();
+        clan.save().unwrap();
+
+        let loaded = ClanStorage::try_load(&clan.name).unwrap();
+        assert_eq!(to_json(&loaded), to_json(&clan));
+    }
+}
+
+#[test]
+fn clan_bank_storage_round_trip() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x636c616e62616e6b);
+
+    for _ in 0..NUM_SAMPLES {
+        let clan_name = random_name(&mut rng);
+        let clan_bank = ClanBankStorage {
+            slots: (0..60)
+                .map(|_| rng.gen_bool(0.25).then(|| random_item(&mut rng)))
+                .collect(),
+            log: (0..rng.gen_range(0..20))
+                .map(|_| ClanBankLogEntry {
+                    timestamp: rng.gen_range(0..i64::from(u32::MAX)),
+                    name: random_name(&mut rng),
+                    action: if rng.gen_bool(0.5) {
+                        ClanBankAction::Deposit
+                    } else {
+                        ClanBankAction::Withdraw
+                    },
+                    item: random_item(&mut rng),
+                })
+                .collect(),
+        };
+        clan_bank.save(&clan_name).unwrap();
+
+        let loaded = ClanBankStorage::try_load(&clan_name).unwrap();
+        assert_eq!(to_json(&loaded), to_json(&clan_bank));
+    }
+}
+
+#[test]
+fn reward_calendar_storage_round_trip() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x726577617264);
+
+    for _ in 0..NUM_SAMPLES {
+        let account_name = random_name(&mut rng);
+        let reward_calendar = RewardCalendarStorage {
+            last_claim_day: rng.gen_bool(0.75).then(|| rng.gen_range(700000..800000)),
+            streak: rng.gen_range(0..365),
+        };
+        reward_calendar.save(&account_name).unwrap();
+
+        let loaded = RewardCalendarStorage::try_load(&account_name).unwrap();
+        assert_eq!(to_json(&loaded), to_json(&reward_calendar));
+    }
+}
+
+#[test]
+fn suspicion_storage_round_trip() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x73757370);
+
+    for _ in 0..NUM_SAMPLES {
+        let account_name = random_name(&mut rng);
+        let suspicion: SuspicionStorage = serde_json::from_value(serde_json::json!({
+            "score": rng.gen_range(0..1000u64),
+            "signals": {
+                "attack_rate": rng.gen_range(0..100u32),
+                "malformed_packet": rng.gen_range(0..100u32),
+            },
+            "kicks": rng.gen_range(0..10u32),
+            "last_signal": rng.gen_bool(0.75).then(Utc::now),
+        }))
+        .unwrap();
+        suspicion.save(&account_name).unwrap();
+
+        let loaded = SuspicionStorage::try_load(&account_name.to_uppercase()).unwrap();
+        assert_eq!(to_json(&loaded), to_json(&suspicion));
+    }
+}
+
+#[test]
+fn chat_mute_storage_removes_expired_mutes() {
+    let storage_dir = support::storage_dir();
+    let now = Utc::now();
+
+    let active = ChatMuteStorage {
+        character_name: String::from("Spammer"),
+        until: now + Duration::hours(1),
+        reason: String::from("Spamming"),
+        muted_by: Some(String::from("GM")),
+    };
+    active.save().unwrap();
+    let expired = ChatMuteStorage {
+        character_name: String::from("Reformed"),
+        until: now - Duration::hours(1),
+        reason: String::from("Spamming"),
+        muted_by: None,
+    };
+    expired.save().unwrap();
+
+    let loaded = ChatMuteStorage::try_load_active(now).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].character_name, "Spammer");
+    assert_eq!(loaded[0].until, active.until);
+    assert!(!storage_dir
+        .join("chat_mutes")
+        .join("reformed.json")
+        .exists());
+
+    ChatMuteStorage::delete("SPAMMER").unwrap();
+    assert!(ChatMuteStorage::try_load_active(now).unwrap().is_empty());
+}
+
+#[test]
+fn item_drop_storage_skips_expired_item_drops() {
+    support::storage_dir();
+    let mut rng = StdRng::seed_from_u64(0x64726f70);
+    let now = Utc::now();
+    let zone_id = ZoneId::new(2).unwrap();
+
+    let active = ItemDropStorage {
+        position: Position::new(Vec3::new(5200.0, 5200.0, 0.0), zone_id),
+        item: DroppedItem::Item(random_item(&mut rng)),
+        expire_time: now + Duration::minutes(5),
+    };
+    let expired = ItemDropStorage {
+        position: Position::new(Vec3::new(5300.0, 5300.0, 0.0), zone_id),
+        item: DroppedItem::Item(random_item(&mut rng)),
+        expire_time: now - Duration::minutes(5),
+    };
+    ZoneItemDropStorage {
+        zone_id,
+        item_drops: vec![active.clone(), expired],
+    }
+    .save()
+    .unwrap();
+
+    let loaded = ZoneItemDropStorage::try_load_all(now).unwrap();
+    let zone = loaded
+        .iter()
+        .find(|zone| zone.zone_id == zone_id)
+        .expect("Missing saved zone");
+    assert_eq!(zone.item_drops.len(), 1);
+    assert_eq!(zone.item_drops[0].item, active.item);
+    assert_eq!(zone.item_drops[0].expire_time, active.expire_time);
+
+    ZoneItemDropStorage::delete(zone_id).unwrap();
+    assert!(ZoneItemDropStorage::try_load_all(now)
+        .unwrap()
+        .iter()
+        .all(|zone| zone.zone_id != zone_id));
+}
+
+fn quest_data(id: usize, time_limit: Option<WorldTicks>) -> Option<QuestData> {
+    Some(QuestData {
+        id,
+        name: Arc::from(""),
+        description: Arc::from(""),
+        start_message: Arc::from(""),
+        end_message: Arc::from(""),
+        time_limit,
+    })
+}
+
+#[test]
+fn quest_repair_removes_unknown_quests_and_items() {
+    let game_data = support::stub_game_data();
+    let quests = QuestDatabase {
+        quests: vec![
+            None,
+            quest_data(1, None),
+            quest_data(2, Some(WorldTicks(1000))),
+        ],
+        strings: HashMap::new(),
+        triggers: HashMap::new(),
+        triggers_by_hash: HashMap::new(),
+    };
+    let remap = HashMap::from([(10, 2)]);
+
+    let mut quest_state = QuestState::default();
+    let mut quest = ActiveQuest::new(1, Some(WorldTicks(500)));
+    quest
+        .try_add_item(
+            StackableItem::new(ItemReference::new(ItemType::Quest, 7), 1)
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    quest_state.try_add_quest(quest);
+    quest_state.try_add_quest(ActiveQuest::new(99, None));
+    quest_state.try_add_quest(ActiveQuest::new(10, Some(WorldTicks(500))));
+    quest_state.try_add_quest(ActiveQuest::new(2, None));
+
+    let repairs = repair_quest_state(&mut quest_state, &quests, &game_data.items, &remap);
+    assert_eq!(
+        repairs,
+        vec![
+            QuestRepair::ClearedExpireTime {
+                slot: 0,
+                quest_id: 1
+            },
+            QuestRepair::RemovedItem {
+                slot: 0,
+                quest_id: 1,
+                item: ItemReference::new(ItemType::Quest, 7),
+            },
+            QuestRepair::RemovedQuest {
+                slot: 1,
+                quest_id: 99
+            },
+            QuestRepair::Remapped {
+                slot: 2,
+                from: 10,
+                to: 2
+            },
+            QuestRepair::RemovedDuplicateQuest {
+                slot: 3,
+                quest_id: 2
+            },
+        ]
+    );
+
+    let quest = quest_state.get_quest(0).unwrap();
+    assert!(quest.expire_time.is_none());
+    assert!(quest.items.iter().all(Option::is_none));
+    assert!(quest_state.get_quest(1).is_none());
+    assert_eq!(quest_state.get_quest(2).unwrap().quest_id, 2);
+    assert!(quest_state.get_quest(3).is_none());
+
+    // A repaired quest state needs no further repairs
+    assert!(repair_quest_state(&mut quest_state, &quests, &game_data.items, &remap).is_empty());
+}
+
+fn leaderboard_character(
+    name: &str,
+    level: u32,
+    money: i64,
+    pvp_kills: u32,
+) -> LeaderboardCharacter {
+    LeaderboardCharacter {
+        name: name.to_string(),
+        level,
+        experience_points: 0,
+        money,
+        pvp_kills,
+    }
+}
+
+#[test]
+fn leaderboards_rank_characters() {
+    let mut characters = vec![
+        leaderboard_character("Alpha", 50, 1000, 3),
+        leaderboard_character("Bravo", 80, 10, 0),
+        leaderboard_character("Charlie", 50, 500000, 12),
+        leaderboard_character("Delta", 10, 20, 12),
+    ];
+    characters[0].experience_points = 5000;
+
+    let leaderboards = Leaderboards::build(&characters, 3);
+    let entries = |entries: &[LeaderboardEntry]| -> Vec<(String, i64)> {
+        entries
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.value))
+            .collect()
+    };
+
+    assert_eq!(leaderboards.num_characters, 4);
+    assert_eq!(
+        entries(&leaderboards.top_level),
+        vec![
+            ("Bravo".to_string(), 80),
+            ("Alpha".to_string(), 50),
+            ("Charlie".to_string(), 50),
+        ]
+    );
+    assert_eq!(
+        entries(&leaderboards.richest),
+        vec![
+            ("Charlie".to_string(), 500000),
+            ("Alpha".to_string(), 1000),
+            ("Delta".to_string(), 20),
+        ]
+    );
+    assert_eq!(
+        entries(&leaderboards.most_pvp_kills),
+        vec![
+            ("Charlie".to_string(), 12),
+            ("Delta".to_string(), 12),
+            ("Alpha".to_string(), 3),
+        ]
+    );
+}
@@ -0,0 +1,401 @@
+use std::{net::SocketAddr, time::Duration};
+
+use anyhow::{anyhow, bail, Context};
+use thiserror::Error;
+use tokio::net::TcpStream;
+
+use rose_game_common::{
+    components::CharacterGender,
+    messages::{server::SecondaryPinError, ClientEntityId},
+};
+use rose_network_common::{Connection, Packet, PacketCodec};
+use rose_network_irose::{
+    game_client_packets::{
+        PacketClientChat, PacketClientConnectRequest as PacketClientGameConnectRequest,
+        PacketClientJoinZone, PacketClientMove,
+    },
+    game_server_packets::{
+        ConnectResult as GameConnectResult, PacketConnectionReply as PacketGameConnectionReply,
+        PacketServerJoinZone, PacketServerLocalChat, PacketServerMoveEntity,
+        PacketServerSelectCharacter, PacketServerWhisper, ServerPackets as GameServerPackets,
+    },
+    login_client_packets::{
+        PacketClientChannelList, PacketClientConnect, PacketClientLoginRequest,
+        PacketClientSelectServer,
+    },
+    login_server_packets::{
+        LoginResult, PacketServerChannelList, PacketServerLoginReply, PacketServerSelectServer,
+        SelectServerResult, ServerPackets as LoginServerPackets,
+    },
+    world_client_packets::{
+        PacketClientConnectRequest as PacketClientWorldConnectRequest, PacketClientCreateCharacter,
+        PacketClientSecondaryPin, PacketClientSelectCharacter,
+    },
+    world_server_packets::{
+        ConnectResult as WorldConnectResult, CreateCharacterResult,
+        PacketConnectionReply as PacketWorldConnectionReply, PacketServerCreateCharacterReply,
+        PacketServerMoveServer, PacketServerSecondaryPinRequest,
+        ServerPackets as WorldServerPackets,
+    },
+    ClientPacketCodec, IROSE_112_TABLE,
+};
+
+/// How long to wait for an expected packet before failing the test
+const PACKET_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The md5 hash of "password"
+const PASSWORD_MD5: &str = "5f4dcc3b5aa765d61d8327deb882cf99";
+
+fn leak_packet_codec(seed: Option<u32>) -> &'static (dyn PacketCodec + Send + Sync) {
+    // Connections borrow their packet codec, which for a test client can simply
+    // live until the process exits
+    Box::leak(Box::new(match seed {
+        Some(seed) => ClientPacketCodec::init(&IROSE_112_TABLE, seed),
+        None => ClientPacketCodec::default(&IROSE_112_TABLE),
+    }))
+}
+
+#[derive(Debug, Error)]
+#[error("Login failed with result {0:?}")]
+pub struct LoginFailed(pub LoginResult);
+
+/// A client which speaks the irose protocol, used to drive the server through
+/// the same login -> world -> game handshake as a real client.
+pub struct HeadlessClient {
+    username: String,
+    connection: Option<Connection<'static>>,
+    world_connection: Option<Connection<'static>>,
+    login_token: u32,
+    packet_codec_seed: u32,
+    server_address: Option<SocketAddr>,
+    client_entity_id: Option<ClientEntityId>,
+}
+
+impl HeadlessClient {
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            connection: None,
+            world_connection: None,
+            login_token: 0,
+            packet_codec_seed: 0,
+            server_address: None,
+            client_entity_id: None,
+        }
+    }
+
+    pub fn client_entity_id(&self) -> Option<ClientEntityId> {
+        self.client_entity_id
+    }
+
+    async fn connect(
+        &mut self,
+        address: SocketAddr,
+        packet_codec_seed: Option<u32>,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(mut connection) = self.connection.take() {
+            connection.shutdown().await;
+        }
+
+        let stream = TcpStream::connect(address)
+            .await
+            .with_context(|| format!("Failed to connect to {}", address))?;
+        self.connection = Some(Connection::new(
+            stream,
+            leak_packet_codec(packet_codec_seed),
+        ));
+        Ok(())
+    }
+
+    fn connection(&mut self) -> Result<&mut Connection<'static>, anyhow::Error> {
+        self.connection
+            .as_mut()
+            .ok_or_else(|| anyhow!("Client is not connected"))
+    }
+
+    pub async fn send_packet(&mut self, packet: Packet) -> Result<(), anyhow::Error> {
+        self.connection()?.write_packet(packet).await
+    }
+
+    /// Reads packets until one with the given command is received, any other
+    /// packets received whilst waiting are ignored.
+    pub async fn wait_for_packet(&mut self, command: u16) -> Result<Packet, anyhow::Error> {
+        let connection = self.connection()?;
+        tokio::time::timeout(PACKET_TIMEOUT, async {
+            loop {
+                let packet = connection.read_packet().await?;
+                if packet.command == command {
+                    return Ok(packet);
+                }
+            }
+        })
+        .await
+        .map_err(|_| anyhow!("Timed out waiting for packet {:03X}", command))?
+    }
+
+    /// Reads packets until the server closes the connection.
+    pub async fn wait_for_disconnect(&mut self) -> Result<(), anyhow::Error> {
+        let connection = self.connection()?;
+        tokio::time::timeout(PACKET_TIMEOUT, async {
+            while connection.read_packet().await.is_ok() {}
+        })
+        .await
+        .map_err(|_| anyhow!("Timed out waiting for disconnect"))
+    }
+
+    /// Logs in to the login server and selects the first channel of the first
+    /// world server, ready to connect to it with `connect_world`.
+    pub async fn login(&mut self, login_address: SocketAddr) -> Result<(), anyhow::Error> {
+        self.connect(login_address, None).await?;
+
+        self.send_packet(Packet::from(&PacketClientConnect)).await?;
+        self.wait_for_packet(LoginServerPackets::NetworkStatus as u16)
+            .await?;
+
+        let username = self.username.clone();
+        self.send_packet(Packet::from(&PacketClientLoginRequest {
+            username: &username,
+            password_md5: PASSWORD_MD5,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(LoginServerPackets::LoginReply as u16)
+            .await?;
+        let login_reply = PacketServerLoginReply::try_from(&packet)?;
+        if login_reply.result != LoginResult::Ok {
+            bail!(LoginFailed(login_reply.result));
+        }
+        let server_id = login_reply
+            .servers
+            .first()
+            .map(|(id, _)| *id as usize)
+            .ok_or_else(|| anyhow!("Login reply contained no servers"))?;
+
+        self.send_packet(Packet::from(&PacketClientChannelList { server_id }))
+            .await?;
+        let packet = self
+            .wait_for_packet(LoginServerPackets::ChannelList as u16)
+            .await?;
+        let channel_id = PacketServerChannelList::try_from(&packet)?
+            .channels
+            .first()
+            .map(|channel| channel.id as usize)
+            .ok_or_else(|| anyhow!("Channel list contained no channels"))?;
+
+        self.send_packet(Packet::from(&PacketClientSelectServer {
+            server_id,
+            channel_id,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(LoginServerPackets::SelectServer as u16)
+            .await?;
+        let select_server = PacketServerSelectServer::try_from(&packet)?;
+        if !matches!(select_server.result, SelectServerResult::Ok) {
+            bail!("Failed to select server");
+        }
+
+        self.login_token = select_server.login_token;
+        self.packet_codec_seed = select_server.packet_codec_seed;
+        self.server_address = Some(format!("{}:{}", select_server.ip, select_server.port).parse()?);
+        Ok(())
+    }
+
+    /// Connects to the world server selected by `login`.
+    pub async fn connect_world(&mut self) -> Result<(), anyhow::Error> {
+        let world_address = self
+            .server_address
+            .ok_or_else(|| anyhow!("Must login before connecting to world server"))?;
+        self.connect(world_address, Some(self.packet_codec_seed))
+            .await?;
+
+        let login_token = self.login_token;
+        self.send_packet(Packet::from(&PacketClientWorldConnectRequest {
+            login_token,
+            password_md5: PASSWORD_MD5,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(WorldServerPackets::ConnectReply as u16)
+            .await?;
+        if !matches!(
+            PacketWorldConnectionReply::try_from(&packet)?.result,
+            WorldConnectResult::Ok
+        ) {
+            bail!("Failed to connect to world server");
+        }
+        Ok(())
+    }
+
+    pub async fn create_character(&mut self, name: &str) -> Result<(), anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientCreateCharacter {
+            gender: CharacterGender::Male,
+            birth_stone: 0,
+            hair: 0,
+            face: 0,
+            weapon_type: 0,
+            start_point: 0,
+            name,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(WorldServerPackets::CreateCharacterReply as u16)
+            .await?;
+        let reply = PacketServerCreateCharacterReply::try_from(&packet)?;
+        if reply.result != CreateCharacterResult::Ok {
+            bail!("Failed to create character with result {:?}", reply.result);
+        }
+        Ok(())
+    }
+
+    /// Selects a character on the world server and connects to the game
+    /// server, returning the selected character's data.
+    pub async fn select_character(
+        &mut self,
+        slot: u8,
+        name: &str,
+    ) -> Result<PacketServerSelectCharacter, anyhow::Error> {
+        self.request_select_character(slot, name).await?;
+        self.connect_game().await
+    }
+
+    /// Sends the character select without waiting for the world server reply.
+    pub async fn request_select_character(
+        &mut self,
+        slot: u8,
+        name: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientSelectCharacter { slot, name }))
+            .await
+    }
+
+    /// Sends the secondary PIN requested by the world server after selecting
+    /// a character.
+    pub async fn send_secondary_pin(&mut self, pin: &str) -> Result<(), anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientSecondaryPin { pin }))
+            .await
+    }
+
+    /// Waits for the world server to request the secondary PIN, returning the
+    /// error for the previous attempt if there was one.
+    pub async fn wait_for_secondary_pin_request(
+        &mut self,
+    ) -> Result<Option<SecondaryPinError>, anyhow::Error> {
+        let packet = self
+            .wait_for_packet(WorldServerPackets::SecondaryPinRequest as u16)
+            .await?;
+        Ok(PacketServerSecondaryPinRequest::try_from(&packet)?.error)
+    }
+
+    /// Connects to the game server once the world server has accepted the
+    /// character select, returning the selected character's data.
+    pub async fn connect_game(&mut self) -> Result<PacketServerSelectCharacter, anyhow::Error> {
+        let packet = self
+            .wait_for_packet(WorldServerPackets::MoveServer as u16)
+            .await?;
+        let move_server = PacketServerMoveServer::try_from(&packet)?;
+        let login_token = move_server.login_token;
+        let packet_codec_seed = move_server.packet_codec_seed;
+        let game_address: SocketAddr =
+            format!("{}:{}", move_server.ip, move_server.port).parse()?;
+
+        // The world server connection must stay open whilst in game, otherwise
+        // the server will expire our login token
+        self.world_connection = self.connection.take();
+        self.connect(game_address, Some(packet_codec_seed)).await?;
+        self.send_packet(Packet::from(&PacketClientGameConnectRequest {
+            login_token,
+            password_md5: PASSWORD_MD5,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(GameServerPackets::ConnectReply as u16)
+            .await?;
+        if !matches!(
+            PacketGameConnectionReply::try_from(&packet)?.result,
+            GameConnectResult::Ok
+        ) {
+            bail!("Failed to connect to game server");
+        }
+
+        let packet = self
+            .wait_for_packet(GameServerPackets::SelectCharacter as u16)
+            .await?;
+        Ok(PacketServerSelectCharacter::try_from(&packet)?)
+    }
+
+    /// Joins the zone of the selected character, returning our entity id.
+    pub async fn join_zone(&mut self) -> Result<ClientEntityId, anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientJoinZone {
+            weight_rate: 0,
+            z: 0,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(GameServerPackets::JoinZone as u16)
+            .await?;
+        let entity_id = PacketServerJoinZone::try_from(&packet)?.entity_id;
+        self.client_entity_id = Some(entity_id);
+        Ok(entity_id)
+    }
+
+    /// Moves our character and waits for the server to echo the movement.
+    pub async fn move_to(
+        &mut self,
+        x: f32,
+        y: f32,
+    ) -> Result<PacketServerMoveEntity, anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientMove {
+            target_entity_id: None,
+            x,
+            y,
+            z: 0,
+        }))
+        .await?;
+
+        loop {
+            let packet = self
+                .wait_for_packet(GameServerPackets::MoveEntity as u16)
+                .await?;
+            let move_entity = PacketServerMoveEntity::try_from(&packet)?;
+            if Some(move_entity.entity_id) == self.client_entity_id {
+                return Ok(move_entity);
+            }
+        }
+    }
+
+    /// Sends a local chat message and waits for the server to echo it back.
+    pub async fn chat(&mut self, text: &str) -> Result<(), anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientChat { text }))
+            .await?;
+
+        loop {
+            let packet = self
+                .wait_for_packet(GameServerPackets::LocalChat as u16)
+                .await?;
+            let local_chat = PacketServerLocalChat::try_from(&packet)?;
+            if Some(local_chat.entity_id) == self.client_entity_id && local_chat.text == text {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Sends a chat command, which unlike a chat message is not echoed back.
+    pub async fn chat_command(&mut self, text: &str) -> Result<(), anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientChat { text }))
+            .await
+    }
+
+    /// Waits for the server to whisper the given text.
+    pub async fn wait_for_server_whisper(&mut self, text: &str) -> Result<(), anyhow::Error> {
+        loop {
+            let packet = self
+                .wait_for_packet(GameServerPackets::Whisper as u16)
+                .await?;
+            let whisper = PacketServerWhisper::try_from(&packet)?;
+            if whisper.from == "SERVER" && whisper.text == text {
+                return Ok(());
+            }
+        }
+    }
+}
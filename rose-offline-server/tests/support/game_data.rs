@@ -0,0 +1,260 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bevy::math::{Vec2, Vec3};
+use enum_map::enum_map;
+
+use rose_data::{
+    AiDatabase, CharacterMotionDatabase, ItemDatabase, JobClassDatabase, MotionId, NpcDatabase,
+    QuestDatabase, SkillDatabase, StatusEffectDatabase, StatusEffectId, StringDatabase,
+    WarpGateDatabase, ZoneData, ZoneDatabase, ZoneGeometryDatabase, ZoneId, ZoneNavGridDatabase,
+};
+use rose_data_irose::{
+    encode_ability_type, encode_clan_member_position, encode_item_class,
+    encode_skill_target_filter, encode_skill_type, get_data_decoder, IroseSkillPageType,
+    SKILL_PAGE_SIZE,
+};
+use rose_file_readers::{RoseFile, RoseFileReader, StlFile, StlReadOptions};
+use rose_game_common::{
+    components::{CharacterGender, SkillPage},
+    data::DropTable,
+};
+use rose_game_irose::data::get_ability_value_calculator;
+use rose_offline_server::{
+    components::{
+        Achievements, BasicStats, CharacterInfo, DroppedItem, Equipment, ExperiencePoints,
+        HealthPoints, HonorPoints, Hotbar, Inventory, Level, ManaPoints, Position, QuestState,
+        Rebirth, SkillList, SkillPoints, Stamina, StatPoints, Statistics, UnionMembership,
+    },
+    storage::character::{CharacterCreator, CharacterCreatorError, CharacterStorage, SavedEffects},
+    GameData,
+};
+
+pub const STUB_ZONE_ID: u16 = 1;
+pub const STUB_START_POSITION: Vec3 = Vec3::new(80000.0, 80000.0, 0.0);
+
+// An NRST01 string table with no keys and no languages
+const EMPTY_STL: &[u8] = &[
+    6, b'N', b'R', b'S', b'T', b'0', b'1', 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+fn empty_stl() -> StlFile {
+    StlFile::read(RoseFileReader::from(EMPTY_STL), &StlReadOptions::default())
+        .expect("Failed to read empty string table")
+}
+
+struct StubCharacterCreator;
+
+impl CharacterCreator for StubCharacterCreator {
+    fn create(
+        &self,
+        name: String,
+        gender: CharacterGender,
+        birth_stone: u8,
+        face: u8,
+        hair: u8,
+    ) -> Result<CharacterStorage, CharacterCreatorError> {
+        let zone_id = ZoneId::new(STUB_ZONE_ID).unwrap();
+
+        Ok(CharacterStorage {
+            info: CharacterInfo {
+                name,
+                unique_id: 0,
+                gender,
+                race: 0,
+                birth_stone,
+                job: 0,
+                face,
+                hair,
+                revive_zone_id: zone_id,
+                revive_position: STUB_START_POSITION,
+                fame: 0,
+                fame_b: 0,
+                fame_g: 0,
+                rank: 0,
+            },
+            basic_stats: self.get_basic_stats(gender)?,
+            equipment: Equipment::default(),
+            inventory: Inventory::default(),
+            level: Level::new(1),
+            experience_points: ExperiencePoints::default(),
+            position: Position::new(STUB_START_POSITION, zone_id),
+            skill_list: SkillList {
+                pages: vec![
+                    SkillPage::new(IroseSkillPageType::Basic as usize, SKILL_PAGE_SIZE),
+                    SkillPage::new(IroseSkillPageType::Active as usize, SKILL_PAGE_SIZE),
+                    SkillPage::new(IroseSkillPageType::Passive as usize, SKILL_PAGE_SIZE),
+                    SkillPage::new(IroseSkillPageType::Clan as usize, SKILL_PAGE_SIZE),
+                ],
+            },
+            hotbar: Hotbar::default(),
+            delete_time: None,
+            health_points: HealthPoints::new(0),
+            mana_points: ManaPoints::new(0),
+            stat_points: StatPoints::default(),
+            skill_points: SkillPoints::default(),
+            quest_state: QuestState::default(),
+            union_membership: UnionMembership::default(),
+            stamina: Stamina::default(),
+            achievements: Achievements::default(),
+            statistics: Statistics::default(),
+            rebirth: Rebirth::default(),
+            honor_points: HonorPoints::default(),
+            saved_effects: SavedEffects::default(),
+            offline_vendor_proceeds: None,
+        })
+    }
+
+    fn get_basic_stats(
+        &self,
+        _gender: CharacterGender,
+    ) -> Result<BasicStats, CharacterCreatorError> {
+        Ok(BasicStats::default())
+    }
+}
+
+struct StubDropTable;
+
+impl DropTable for StubDropTable {
+    fn get_drop(
+        &self,
+        _world_drop_item_rate: i32,
+        _world_drop_money_rate: i32,
+        _npc_id: rose_data::NpcId,
+        _zone_id: ZoneId,
+        _level_difference: i32,
+        _character_drop_rate: i32,
+        _character_charm: i32,
+    ) -> Option<DroppedItem> {
+        None
+    }
+
+    fn get_npc_drop_items(&self, _npc_id: rose_data::NpcId) -> Vec<rose_data::ItemReference> {
+        Vec::new()
+    }
+}
+
+fn stub_zone() -> ZoneData {
+    ZoneData {
+        id: ZoneId::new(STUB_ZONE_ID).unwrap(),
+        name: Arc::from("Stub Zone"),
+        description: Arc::from(""),
+        sector_size: 10000,
+        grid_per_patch: 4.0,
+        grid_size: 250.0,
+        event_objects: Vec::new(),
+        monster_spawns: Vec::new(),
+        npcs: Vec::new(),
+        warp_gates: Vec::new(),
+        sectors_base_position: Vec2::ZERO,
+        num_sectors_x: 16,
+        num_sectors_y: 16,
+        start_position: STUB_START_POSITION,
+        revive_positions: vec![STUB_START_POSITION],
+        event_positions: HashMap::new(),
+        day_cycle: 160,
+        morning_time: 10,
+        day_time: 40,
+        evening_time: 120,
+        night_time: 150,
+        skybox_id: None,
+    }
+}
+
+/// Creates game data with a single empty zone and no items, npcs, quests or
+/// skills, which is enough for characters to be created and join the world
+/// without needing the irose client files.
+pub fn stub_game_data() -> GameData {
+    let string_database = Arc::new(StringDatabase {
+        language: 0,
+        fallback_language: 0,
+        languages: vec![0],
+        encode_ability_type,
+        encode_clan_member_position,
+        encode_item_class,
+        encode_skill_target_filter,
+        encode_skill_type,
+        ability: empty_stl(),
+        clan: empty_stl(),
+        client_strings: empty_stl(),
+        item: enum_map! { _ => empty_stl() },
+        item_prefix: empty_stl(),
+        item_class: empty_stl(),
+        job: empty_stl(),
+        job_class: empty_stl(),
+        npc: empty_stl(),
+        npc_store_tabs: empty_stl(),
+        planet: empty_stl(),
+        quest: empty_stl(),
+        skill: empty_stl(),
+        skill_target: empty_stl(),
+        skill_type: empty_stl(),
+        status_effect: empty_stl(),
+        union: empty_stl(),
+        zone: empty_stl(),
+    });
+    let item_database = Arc::new(ItemDatabase::new(
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+    ));
+    let npc_database = Arc::new(NpcDatabase::new(
+        Vec::new(),
+        HashMap::new(),
+        HashMap::new(),
+        enum_map! { _ => MotionId::new(0) },
+    ));
+    let skill_database = Arc::new(SkillDatabase::new(Vec::new()));
+
+    GameData {
+        character_creator: Box::new(StubCharacterCreator),
+        ability_value_calculator: get_ability_value_calculator(
+            item_database.clone(),
+            skill_database.clone(),
+            npc_database.clone(),
+        ),
+        data_decoder: get_data_decoder(),
+        drop_table: Box::new(StubDropTable),
+        ai: Arc::new(AiDatabase {
+            strings: HashMap::new(),
+            aips: HashMap::new(),
+        }),
+        items: item_database,
+        job_class: Arc::new(JobClassDatabase::new(Vec::new())),
+        motions: Arc::new(CharacterMotionDatabase::new(
+            1,
+            Vec::new(),
+            Vec::new(),
+            enum_map! { _ => MotionId::new(0) },
+            enum_map! { _ => 0 },
+        )),
+        npcs: npc_database,
+        quests: Arc::new(QuestDatabase {
+            quests: Vec::new(),
+            strings: HashMap::new(),
+            triggers: HashMap::new(),
+            triggers_by_hash: HashMap::new(),
+        }),
+        skills: skill_database,
+        status_effects: Arc::new(StatusEffectDatabase::new(
+            HashMap::new(),
+            StatusEffectId::new(43).unwrap(),
+        )),
+        string_database,
+        warp_gates: Arc::new(WarpGateDatabase::new(HashMap::new())),
+        zones: Arc::new(ZoneDatabase::new(vec![None, Some(stub_zone())])),
+        zone_geometry: Arc::new(ZoneGeometryDatabase::new(Vec::new())),
+        zone_nav_grids: Arc::new(ZoneNavGridDatabase::new(Vec::new())),
+    }
+}
@@ -0,0 +1,241 @@
+//! Shared helpers for the integration tests, which boot the server in-process
+//! with stub game data and drive it with a headless irose client.
+
+// Each integration test binary only uses some of these helpers
+#![allow(dead_code, unused_imports)]
+
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use simplelog::{Config, LevelFilter, SimpleLogger};
+use tokio::net::TcpListener;
+
+use rose_offline_server::{
+    storage, ExternalAddress, GameConfig, GameServer, GameWorld, LoginServer, PacketCodecSeeds,
+    ProtocolOptions, ProtocolType, RemoteControlClient, RemoteControlServer, WorldServer,
+};
+
+mod client;
+mod game_data;
+
+pub use client::{HeadlessClient, LoginFailed};
+pub use game_data::{stub_game_data, STUB_START_POSITION, STUB_ZONE_ID};
+
+/// Returns the storage directory used by the server, which is created empty
+/// in the cargo target temp directory. The server only reads the storage
+/// directory once, so it is shared by every test in the same test binary.
+pub fn storage_dir() -> &'static Path {
+    static STORAGE_DIR: OnceLock<PathBuf> = OnceLock::new();
+    STORAGE_DIR.get_or_init(|| {
+        let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+            .join(format!("storage-{}", std::process::id()));
+        std::fs::remove_dir_all(&path).ok();
+        std::fs::create_dir_all(&path).expect("Failed to create storage dir");
+        storage::set_local_storage_dir(path.clone()).expect("Failed to set storage dir");
+        path
+    })
+}
+
+/// The shared secret of the remote control connection used by
+/// `TestServer::start_with_remote_game_server`.
+pub const REMOTE_CONTROL_SECRET: &str = "test-remote-control-secret";
+
+/// A login, world and game server listening on ephemeral localhost ports.
+pub struct TestServer {
+    pub login_address: SocketAddr,
+}
+
+/// The game config used by `TestServer::start`, with spawns disabled.
+pub fn test_game_config() -> GameConfig {
+    GameConfig {
+        enable_npc_spawns: false,
+        enable_monster_spawns: false,
+        party_map_marker_interval: None,
+        item_drop_owner_duration: None,
+        reconnect_grace_period: None,
+        ..GameConfig::default()
+    }
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        Self::start_with_config(test_game_config()).await
+    }
+
+    pub async fn start_with_config(game_config: GameConfig) -> Self {
+        Self::start_with_options(game_config, None).await
+    }
+
+    /// Starts the servers writing a packet dump of every connection to
+    /// `packet_dump_dir`.
+    pub async fn start_with_packet_dump(packet_dump_dir: PathBuf) -> Self {
+        Self::start_with_options(test_game_config(), Some(packet_dump_dir)).await
+    }
+
+    async fn start_with_options(game_config: GameConfig, packet_dump_dir: Option<PathBuf>) -> Self {
+        SimpleLogger::init(LevelFilter::Warn, Config::default()).ok();
+
+        storage_dir();
+
+        let packet_codec_seeds = PacketCodecSeeds::new();
+        let protocols = ProtocolType::Irose.create_protocols(ProtocolOptions {
+            packet_dump_dir,
+            packet_codec_seeds: packet_codec_seeds.clone(),
+            strict_packet_codec: true,
+            geo_ip: None,
+            proxy_protocol: false,
+            trusted_proxies: Vec::new(),
+        });
+
+        let (game_control_tx, game_control_rx) = crossbeam_channel::unbounded();
+        std::thread::spawn(move || {
+            GameWorld::new(game_control_rx, packet_codec_seeds).run(game_config, stub_game_data());
+        });
+
+        let login_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let login_address = login_listener.local_addr().unwrap();
+        let mut login_server =
+            LoginServer::new(login_listener, protocols.login, game_control_tx.clone())
+                .await
+                .unwrap();
+
+        let mut world_server = WorldServer::new(
+            String::from("TestWorldServer"),
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+            &ExternalAddress::default(),
+            protocols.world,
+            game_control_tx.clone(),
+        )
+        .await
+        .unwrap();
+
+        let mut game_server = GameServer::new(
+            String::from("TestGameServer"),
+            world_server.get_entity(),
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+            &ExternalAddress::default(),
+            protocols.game,
+            game_control_tx,
+        )
+        .await
+        .unwrap();
+
+        tokio::spawn(async move {
+            game_server.run().await;
+        });
+
+        tokio::spawn(async move {
+            world_server.run().await;
+        });
+
+        tokio::spawn(async move {
+            login_server.run().await;
+        });
+
+        Self { login_address }
+    }
+
+    /// Starts the game world with a login and world server, and a game server
+    /// in a separate "process" which connects to it with the remote control
+    /// protocol.
+    pub async fn start_with_remote_game_server() -> Self {
+        SimpleLogger::init(LevelFilter::Warn, Config::default()).ok();
+
+        storage_dir();
+
+        let packet_codec_seeds = PacketCodecSeeds::new();
+        let protocols = ProtocolType::Irose.create_protocols(ProtocolOptions {
+            packet_dump_dir: None,
+            packet_codec_seeds: packet_codec_seeds.clone(),
+            strict_packet_codec: true,
+            geo_ip: None,
+            proxy_protocol: false,
+            trusted_proxies: Vec::new(),
+        });
+
+        let (game_control_tx, game_control_rx) = crossbeam_channel::unbounded();
+        let world_packet_codec_seeds = packet_codec_seeds.clone();
+        std::thread::spawn(move || {
+            GameWorld::new(game_control_rx, world_packet_codec_seeds)
+                .run(test_game_config(), stub_game_data());
+        });
+
+        let login_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let login_address = login_listener.local_addr().unwrap();
+        let mut login_server =
+            LoginServer::new(login_listener, protocols.login, game_control_tx.clone())
+                .await
+                .unwrap();
+
+        let mut world_server = WorldServer::new(
+            String::from("TestWorldServer"),
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+            &ExternalAddress::default(),
+            protocols.world,
+            game_control_tx.clone(),
+        )
+        .await
+        .unwrap();
+
+        let control_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let control_address = control_listener.local_addr().unwrap();
+        let mut remote_control_server = RemoteControlServer::new(
+            control_listener,
+            String::from(REMOTE_CONTROL_SECRET),
+            game_control_tx,
+            packet_codec_seeds,
+            Some(world_server.get_entity()),
+        );
+
+        tokio::spawn(async move {
+            remote_control_server.run().await;
+        });
+
+        tokio::spawn(async move {
+            world_server.run().await;
+        });
+
+        tokio::spawn(async move {
+            login_server.run().await;
+        });
+
+        // The game server process has its own packet codec seeds, which are
+        // kept in sync with the game world process
+        let remote_packet_codec_seeds = PacketCodecSeeds::new();
+        let remote_protocols = ProtocolType::Irose.create_protocols(ProtocolOptions {
+            packet_dump_dir: None,
+            packet_codec_seeds: remote_packet_codec_seeds.clone(),
+            strict_packet_codec: true,
+            geo_ip: None,
+            proxy_protocol: false,
+            trusted_proxies: Vec::new(),
+        });
+        let remote_control = RemoteControlClient::connect(
+            &control_address.to_string(),
+            REMOTE_CONTROL_SECRET,
+            remote_packet_codec_seeds,
+        )
+        .await
+        .unwrap();
+
+        let mut game_server = GameServer::new(
+            String::from("TestRemoteGameServer"),
+            remote_control.world_server.unwrap(),
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+            &ExternalAddress::default(),
+            remote_protocols.game,
+            remote_control.control_message_tx,
+        )
+        .await
+        .unwrap();
+
+        tokio::spawn(async move {
+            game_server.run().await;
+        });
+
+        Self { login_address }
+    }
+}
@@ -0,0 +1,144 @@
+use bytes::BytesMut;
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_tungstenite::tungstenite::Message;
+
+use rose_network_common::{WebSocketError, WebSocketStream};
+
+async fn listen() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind listener");
+    let address = listener.local_addr().unwrap().to_string();
+    (listener, address)
+}
+
+async fn accept(listener: TcpListener) -> Result<WebSocketStream, WebSocketError> {
+    let (stream, _) = listener.accept().await.expect("Failed to accept");
+    WebSocketStream::accept(stream).await
+}
+
+async fn read_http_response(stream: &mut TcpStream) -> String {
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).await.unwrap_or(0) == 0 {
+            break;
+        }
+        response.push(byte[0]);
+    }
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+#[tokio::test]
+async fn websocket_exchanges_binary_messages() {
+    let (listener, address) = listen().await;
+    let server = tokio::spawn(async move {
+        let mut stream = accept(listener).await.expect("Handshake failed");
+        let mut buffer = BytesMut::new();
+        stream
+            .read_buf(&mut buffer)
+            .await
+            .expect("Failed to read message");
+        stream
+            .write_all(&buffer)
+            .await
+            .expect("Failed to write message");
+        buffer
+    });
+
+    let socket = TcpStream::connect(&address).await.unwrap();
+    let (mut client, _) = tokio_tungstenite::client_async(format!("ws://{}/", address), socket)
+        .await
+        .expect("Client handshake failed");
+    client
+        .send(Message::Binary(vec![1, 2, 3, 4]))
+        .await
+        .unwrap();
+
+    let echoed = client.next().await.unwrap().unwrap();
+    assert_eq!(echoed, Message::Binary(vec![1, 2, 3, 4]));
+    assert_eq!(&server.await.unwrap()[..], &[1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn websocket_handshake_returns_accept_key() {
+    let (listener, address) = listen().await;
+    let server = tokio::spawn(accept(listener));
+
+    // Example key and accept value from RFC 6455 section 1.3
+    let mut socket = TcpStream::connect(&address).await.unwrap();
+    socket
+        .write_all(
+            b"GET / HTTP/1.1\r\n\
+              Host: localhost\r\n\
+              Upgrade: websocket\r\n\
+              Connection: Upgrade\r\n\
+              Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+              Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let response = read_http_response(&mut socket).await;
+    assert!(response.starts_with("HTTP/1.1 101"));
+    assert!(response.contains("s3pPLMBiTxaQ9kEOzzo4YWlbWxo="));
+    assert!(server.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn websocket_handshake_rejects_missing_headers() {
+    let (listener, address) = listen().await;
+    let server = tokio::spawn(accept(listener));
+
+    let mut socket = TcpStream::connect(&address).await.unwrap();
+    socket
+        .write_all(
+            b"GET / HTTP/1.1\r\n\
+              Host: localhost\r\n\
+              Connection: Upgrade\r\n\
+              Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        server.await.unwrap(),
+        Err(WebSocketError::InvalidHandshake)
+    ));
+}
+
+#[tokio::test]
+async fn websocket_rejects_unmasked_frames() {
+    let (listener, address) = listen().await;
+    let server = tokio::spawn(async move {
+        let mut stream = accept(listener).await.expect("Handshake failed");
+        let mut buffer = BytesMut::new();
+        stream.read_buf(&mut buffer).await
+    });
+
+    let mut socket = TcpStream::connect(&address).await.unwrap();
+    socket
+        .write_all(
+            b"GET / HTTP/1.1\r\n\
+              Host: localhost\r\n\
+              Upgrade: websocket\r\n\
+              Connection: Upgrade\r\n\
+              Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+              Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    read_http_response(&mut socket).await;
+
+    // Binary frame with the mask bit clear
+    socket.write_all(&[0x82, 0x03, 1, 2, 3]).await.unwrap();
+
+    assert!(matches!(
+        server.await.unwrap(),
+        Err(WebSocketError::InvalidFrame)
+    ));
+}
@@ -0,0 +1,417 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context};
+use tokio::net::TcpStream;
+
+use rose_game_common::{components::CharacterGender, messages::ClientEntityId};
+use rose_network_common::{Connection, Packet, PacketCodec};
+use rose_network_irose::{
+    game_client_packets::{
+        PacketClientAttack, PacketClientChat,
+        PacketClientConnectRequest as PacketClientGameConnectRequest, PacketClientJoinZone,
+        PacketClientMove,
+    },
+    game_server_packets::{
+        ConnectResult as GameConnectResult, PacketConnectionReply as PacketGameConnectionReply,
+        PacketServerAttackEntity, PacketServerJoinZone, PacketServerLocalChat,
+        PacketServerMoveEntity, PacketServerRemoveEntities, PacketServerSelectCharacter,
+        PacketServerSpawnEntityMonster, PacketServerStopMoveEntity,
+        ServerPackets as GameServerPackets,
+    },
+    login_client_packets::{
+        PacketClientChannelList, PacketClientConnect, PacketClientLoginRequest,
+        PacketClientSelectServer,
+    },
+    login_server_packets::{
+        LoginResult, PacketServerChannelList, PacketServerLoginReply, PacketServerSelectServer,
+        SelectServerResult, ServerPackets as LoginServerPackets,
+    },
+    world_client_packets::{
+        PacketClientConnectRequest as PacketClientWorldConnectRequest, PacketClientCreateCharacter,
+        PacketClientSelectCharacter,
+    },
+    world_server_packets::{
+        ConnectResult as WorldConnectResult, CreateCharacterResult,
+        PacketConnectionReply as PacketWorldConnectionReply, PacketServerCreateCharacterReply,
+        PacketServerMoveServer, ServerPackets as WorldServerPackets,
+    },
+    ClientPacketCodec, IROSE_112_TABLE,
+};
+
+/// How long to wait for a response before the client is considered failed
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The md5 hash of "password"
+const PASSWORD_MD5: &str = "5f4dcc3b5aa765d61d8327deb882cf99";
+
+fn leak_packet_codec(seed: Option<u32>) -> &'static (dyn PacketCodec + Send + Sync) {
+    // Connections borrow their packet codec, which lives for as long as the
+    // load test runs
+    Box::leak(Box::new(match seed {
+        Some(seed) => ClientPacketCodec::init(&IROSE_112_TABLE, seed),
+        None => ClientPacketCodec::default(&IROSE_112_TABLE),
+    }))
+}
+
+/// A simulated player which connects through the login, world and game
+/// servers like a real irose client.
+pub struct LoadTestClient {
+    account_name: String,
+    character_name: String,
+    connection: Option<Connection<'static>>,
+    world_connection: Option<Connection<'static>>,
+    login_token: u32,
+    packet_codec_seed: u32,
+    server_address: Option<SocketAddr>,
+    client_entity_id: Option<ClientEntityId>,
+    spawn_position: (f32, f32),
+    nearby_monsters: Vec<ClientEntityId>,
+}
+
+impl LoadTestClient {
+    pub fn new(account_name: String, character_name: String) -> Self {
+        Self {
+            account_name,
+            character_name,
+            connection: None,
+            world_connection: None,
+            login_token: 0,
+            packet_codec_seed: 0,
+            server_address: None,
+            client_entity_id: None,
+            spawn_position: (0.0, 0.0),
+            nearby_monsters: Vec::new(),
+        }
+    }
+
+    pub fn spawn_position(&self) -> (f32, f32) {
+        self.spawn_position
+    }
+
+    pub fn nearby_monsters(&self) -> impl Iterator<Item = &ClientEntityId> {
+        self.nearby_monsters.iter()
+    }
+
+    async fn connect(
+        &mut self,
+        address: SocketAddr,
+        packet_codec_seed: Option<u32>,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(mut connection) = self.connection.take() {
+            connection.shutdown().await;
+        }
+
+        let stream = TcpStream::connect(address)
+            .await
+            .with_context(|| format!("Failed to connect to {}", address))?;
+        self.connection = Some(Connection::new(
+            stream,
+            leak_packet_codec(packet_codec_seed),
+        ));
+        Ok(())
+    }
+
+    pub async fn disconnect(&mut self) {
+        if let Some(mut connection) = self.connection.take() {
+            connection.shutdown().await;
+        }
+        if let Some(mut connection) = self.world_connection.take() {
+            connection.shutdown().await;
+        }
+    }
+
+    async fn send_packet(&mut self, packet: Packet) -> Result<(), anyhow::Error> {
+        self.connection
+            .as_mut()
+            .ok_or_else(|| anyhow!("Client is not connected"))?
+            .write_packet(packet)
+            .await
+    }
+
+    /// Keeps track of the monsters the server tells us about, so there is
+    /// something to attack.
+    fn handle_packet(&mut self, packet: &Packet) {
+        if packet.command == GameServerPackets::SpawnEntityMonster as u16 {
+            if let Ok(spawn) = PacketServerSpawnEntityMonster::try_from(packet) {
+                if !self.nearby_monsters.contains(&spawn.entity_id) {
+                    self.nearby_monsters.push(spawn.entity_id);
+                }
+            }
+        } else if packet.command == GameServerPackets::RemoveEntities as u16 {
+            if let Ok(remove) = PacketServerRemoveEntities::try_from(packet) {
+                self.nearby_monsters
+                    .retain(|entity_id| !remove.entity_ids.contains(entity_id));
+            }
+        }
+    }
+
+    /// Reads packets until `is_response` returns true for one, any other
+    /// packets received whilst waiting are only used to track nearby monsters.
+    async fn wait_for_response(
+        &mut self,
+        mut is_response: impl FnMut(&Packet) -> bool,
+    ) -> Result<Packet, anyhow::Error> {
+        let mut connection = self
+            .connection
+            .take()
+            .ok_or_else(|| anyhow!("Client is not connected"))?;
+
+        let result = tokio::time::timeout(RESPONSE_TIMEOUT, async {
+            loop {
+                let packet = connection.read_packet().await?;
+                self.handle_packet(&packet);
+                if is_response(&packet) {
+                    return Ok(packet);
+                }
+            }
+        })
+        .await
+        .map_err(|_| anyhow!("Timed out waiting for response"));
+
+        self.connection = Some(connection);
+        result?
+    }
+
+    async fn wait_for_packet(&mut self, command: u16) -> Result<Packet, anyhow::Error> {
+        self.wait_for_response(|packet| packet.command == command)
+            .await
+    }
+
+    /// Logs in and selects the first channel of the first world server.
+    pub async fn login(&mut self, login_address: SocketAddr) -> Result<(), anyhow::Error> {
+        self.connect(login_address, None).await?;
+
+        self.send_packet(Packet::from(&PacketClientConnect)).await?;
+        self.wait_for_packet(LoginServerPackets::NetworkStatus as u16)
+            .await?;
+
+        let account_name = self.account_name.clone();
+        self.send_packet(Packet::from(&PacketClientLoginRequest {
+            username: &account_name,
+            password_md5: PASSWORD_MD5,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(LoginServerPackets::LoginReply as u16)
+            .await?;
+        let login_reply = PacketServerLoginReply::try_from(&packet)?;
+        if login_reply.result != LoginResult::Ok {
+            bail!("Login failed with result {:?}", login_reply.result);
+        }
+        let server_id = login_reply
+            .servers
+            .first()
+            .map(|(id, _)| *id as usize)
+            .ok_or_else(|| anyhow!("Login reply contained no servers"))?;
+
+        self.send_packet(Packet::from(&PacketClientChannelList { server_id }))
+            .await?;
+        let packet = self
+            .wait_for_packet(LoginServerPackets::ChannelList as u16)
+            .await?;
+        let channel_id = PacketServerChannelList::try_from(&packet)?
+            .channels
+            .first()
+            .map(|channel| channel.id as usize)
+            .ok_or_else(|| anyhow!("Channel list contained no channels"))?;
+
+        self.send_packet(Packet::from(&PacketClientSelectServer {
+            server_id,
+            channel_id,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(LoginServerPackets::SelectServer as u16)
+            .await?;
+        let select_server = PacketServerSelectServer::try_from(&packet)?;
+        if !matches!(select_server.result, SelectServerResult::Ok) {
+            bail!("Failed to select server");
+        }
+
+        self.login_token = select_server.login_token;
+        self.packet_codec_seed = select_server.packet_codec_seed;
+        self.server_address = Some(format!("{}:{}", select_server.ip, select_server.port).parse()?);
+        Ok(())
+    }
+
+    /// Connects to the world server and creates the load test character if
+    /// the account does not have it yet.
+    pub async fn connect_world(&mut self) -> Result<(), anyhow::Error> {
+        let world_address = self
+            .server_address
+            .ok_or_else(|| anyhow!("Must login before connecting to world server"))?;
+        self.connect(world_address, Some(self.packet_codec_seed))
+            .await?;
+
+        let login_token = self.login_token;
+        self.send_packet(Packet::from(&PacketClientWorldConnectRequest {
+            login_token,
+            password_md5: PASSWORD_MD5,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(WorldServerPackets::ConnectReply as u16)
+            .await?;
+        if !matches!(
+            PacketWorldConnectionReply::try_from(&packet)?.result,
+            WorldConnectResult::Ok
+        ) {
+            bail!("Failed to connect to world server");
+        }
+
+        let character_name = self.character_name.clone();
+        self.send_packet(Packet::from(&PacketClientCreateCharacter {
+            gender: CharacterGender::Male,
+            birth_stone: 0,
+            hair: 0,
+            face: 0,
+            weapon_type: 0,
+            start_point: 0,
+            name: &character_name,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(WorldServerPackets::CreateCharacterReply as u16)
+            .await?;
+        let reply = PacketServerCreateCharacterReply::try_from(&packet)?;
+        if !matches!(
+            reply.result,
+            CreateCharacterResult::Ok | CreateCharacterResult::NameAlreadyExists
+        ) {
+            bail!("Failed to create character with result {:?}", reply.result);
+        }
+
+        Ok(())
+    }
+
+    /// Selects the load test character and connects to the game server.
+    pub async fn connect_game(&mut self) -> Result<(), anyhow::Error> {
+        let character_name = self.character_name.clone();
+        self.send_packet(Packet::from(&PacketClientSelectCharacter {
+            slot: 0,
+            name: &character_name,
+        }))
+        .await?;
+
+        let packet = self
+            .wait_for_packet(WorldServerPackets::MoveServer as u16)
+            .await?;
+        let move_server = PacketServerMoveServer::try_from(&packet)?;
+        let login_token = move_server.login_token;
+        let packet_codec_seed = move_server.packet_codec_seed;
+        let game_address: SocketAddr =
+            format!("{}:{}", move_server.ip, move_server.port).parse()?;
+
+        // The world server connection must stay open whilst in game, otherwise
+        // the server will expire our login token
+        self.world_connection = self.connection.take();
+        self.connect(game_address, Some(packet_codec_seed)).await?;
+        self.send_packet(Packet::from(&PacketClientGameConnectRequest {
+            login_token,
+            password_md5: PASSWORD_MD5,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(GameServerPackets::ConnectReply as u16)
+            .await?;
+        if !matches!(
+            PacketGameConnectionReply::try_from(&packet)?.result,
+            GameConnectResult::Ok
+        ) {
+            bail!("Failed to connect to game server");
+        }
+
+        let packet = self
+            .wait_for_packet(GameServerPackets::SelectCharacter as u16)
+            .await?;
+        let select_character = PacketServerSelectCharacter::try_from(&packet)?;
+        self.spawn_position = (select_character.position.x, select_character.position.y);
+        Ok(())
+    }
+
+    pub async fn join_zone(&mut self) -> Result<(), anyhow::Error> {
+        self.send_packet(Packet::from(&PacketClientJoinZone {
+            weight_rate: 0,
+            z: 0,
+        }))
+        .await?;
+        let packet = self
+            .wait_for_packet(GameServerPackets::JoinZone as u16)
+            .await?;
+        self.client_entity_id = Some(PacketServerJoinZone::try_from(&packet)?.entity_id);
+        Ok(())
+    }
+
+    /// Moves our character, returning how long the server took to send the
+    /// movement back to us.
+    pub async fn move_to(&mut self, x: f32, y: f32) -> Result<Duration, anyhow::Error> {
+        let start = Instant::now();
+        self.send_packet(Packet::from(&PacketClientMove {
+            target_entity_id: None,
+            x,
+            y,
+            z: 0,
+        }))
+        .await?;
+
+        let client_entity_id = self.client_entity_id;
+        self.wait_for_response(|packet| {
+            packet.command == GameServerPackets::MoveEntity as u16
+                && PacketServerMoveEntity::try_from(packet).map_or(false, |move_entity| {
+                    Some(move_entity.entity_id) == client_entity_id
+                })
+        })
+        .await?;
+        Ok(start.elapsed())
+    }
+
+    /// Sends a local chat message, returning how long the server took to send
+    /// the message back to us.
+    pub async fn chat(&mut self, text: &str) -> Result<Duration, anyhow::Error> {
+        let start = Instant::now();
+        self.send_packet(Packet::from(&PacketClientChat { text }))
+            .await?;
+
+        let client_entity_id = self.client_entity_id;
+        self.wait_for_response(|packet| {
+            packet.command == GameServerPackets::LocalChat as u16
+                && PacketServerLocalChat::try_from(packet).map_or(false, |local_chat| {
+                    Some(local_chat.entity_id) == client_entity_id && local_chat.text == text
+                })
+        })
+        .await?;
+        Ok(start.elapsed())
+    }
+
+    /// Attacks a monster, returning how long the server took to tell us our
+    /// character has started the attack, or has stopped because the monster
+    /// could not be attacked.
+    pub async fn attack(
+        &mut self,
+        target_entity_id: ClientEntityId,
+    ) -> Result<Duration, anyhow::Error> {
+        let start = Instant::now();
+        self.send_packet(Packet::from(&PacketClientAttack { target_entity_id }))
+            .await?;
+
+        let client_entity_id = self.client_entity_id;
+        self.wait_for_response(|packet| {
+            if packet.command == GameServerPackets::AttackEntity as u16 {
+                PacketServerAttackEntity::try_from(packet).map_or(false, |attack_entity| {
+                    Some(attack_entity.entity_id) == client_entity_id
+                })
+            } else if packet.command == GameServerPackets::StopMoveEntity as u16 {
+                PacketServerStopMoveEntity::try_from(packet).map_or(false, |stop_move_entity| {
+                    Some(stop_move_entity.entity_id) == client_entity_id
+                })
+            } else {
+                false
+            }
+        })
+        .await?;
+        Ok(start.elapsed())
+    }
+}
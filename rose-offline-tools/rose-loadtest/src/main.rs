@@ -0,0 +1,338 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use clap::{Arg, Command};
+use rand::{seq::IteratorRandom, Rng};
+
+mod client;
+mod stats;
+
+use client::LoadTestClient;
+use stats::LoadTestStats;
+
+/// How long a client waits before logging in again after an error
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+struct LoadTestOptions {
+    login_address: SocketAddr,
+    account_prefix: String,
+    end_time: Instant,
+
+    /// Actions per minute of each client
+    move_rate: f32,
+    chat_rate: f32,
+    attack_rate: f32,
+
+    /// How far from its spawn position a client moves
+    move_radius: f32,
+}
+
+enum Action {
+    Move,
+    Chat,
+    Attack,
+}
+
+impl LoadTestOptions {
+    fn total_rate(&self) -> f32 {
+        self.move_rate + self.chat_rate + self.attack_rate
+    }
+
+    fn choose_action(&self, rng: &mut impl Rng) -> Action {
+        let roll = rng.gen_range(0.0..self.total_rate());
+        if roll < self.move_rate {
+            Action::Move
+        } else if roll < self.move_rate + self.chat_rate {
+            Action::Chat
+        } else {
+            Action::Attack
+        }
+    }
+}
+
+struct LoadTestState {
+    stats: Mutex<LoadTestStats>,
+    num_in_game: AtomicUsize,
+}
+
+fn record<T>(
+    state: &LoadTestState,
+    request: &'static str,
+    result: &Result<T, anyhow::Error>,
+    latency: impl FnOnce(&T) -> Duration,
+) {
+    let mut stats = state.stats.lock().unwrap();
+    match result {
+        Ok(value) => stats.add_latency(request, latency(value)),
+        Err(_) => stats.add_error(request),
+    }
+}
+
+/// Times a handshake step, which has no response latency of its own.
+async fn timed_step<F: std::future::Future<Output = Result<(), anyhow::Error>>>(
+    state: &LoadTestState,
+    request: &'static str,
+    step: F,
+) -> Result<(), anyhow::Error> {
+    let start = Instant::now();
+    let result = step.await.map(|_| start.elapsed());
+    record(state, request, &result, |latency| *latency);
+    result.map(|_| ())
+}
+
+async fn run_session(
+    client: &mut LoadTestClient,
+    index: usize,
+    options: &LoadTestOptions,
+    state: &LoadTestState,
+) -> Result<(), anyhow::Error> {
+    timed_step(state, "login", client.login(options.login_address)).await?;
+    timed_step(state, "world", client.connect_world()).await?;
+    timed_step(state, "game", client.connect_game()).await?;
+    timed_step(state, "join_zone", client.join_zone()).await?;
+
+    state.num_in_game.fetch_add(1, Ordering::Relaxed);
+    let result = run_actions(client, index, options, state).await;
+    state.num_in_game.fetch_sub(1, Ordering::Relaxed);
+    result
+}
+
+async fn run_actions(
+    client: &mut LoadTestClient,
+    index: usize,
+    options: &LoadTestOptions,
+    state: &LoadTestState,
+) -> Result<(), anyhow::Error> {
+    let mut chat_count = 0;
+
+    while Instant::now() < options.end_time {
+        // Randomise the delay between actions so the clients do not act in
+        // lock step with each other
+        let delay = {
+            let mut rng = rand::thread_rng();
+            Duration::from_secs_f32(rng.gen_range(0.5..1.5) * 60.0 / options.total_rate())
+        };
+        tokio::time::sleep(delay).await;
+
+        let action = options.choose_action(&mut rand::thread_rng());
+        match action {
+            Action::Move => {
+                let (x, y) = {
+                    let mut rng = rand::thread_rng();
+                    let (spawn_x, spawn_y) = client.spawn_position();
+                    (
+                        spawn_x + rng.gen_range(-options.move_radius..options.move_radius),
+                        spawn_y + rng.gen_range(-options.move_radius..options.move_radius),
+                    )
+                };
+                let result = client.move_to(x, y).await;
+                record(state, "move", &result, |latency| *latency);
+                result?;
+            }
+            Action::Chat => {
+                chat_count += 1;
+                let result = client
+                    .chat(&format!("Load test {} message {}", index, chat_count))
+                    .await;
+                record(state, "chat", &result, |latency| *latency);
+                result?;
+            }
+            Action::Attack => {
+                let target = client
+                    .nearby_monsters()
+                    .choose(&mut rand::thread_rng())
+                    .copied();
+                let Some(target) = target else {
+                    continue;
+                };
+
+                let result = client.attack(target).await;
+                record(state, "attack", &result, |latency| *latency);
+                result?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_client(index: usize, options: LoadTestOptions, state: Arc<LoadTestState>) {
+    while Instant::now() < options.end_time {
+        let mut client = LoadTestClient::new(
+            format!("{}{}", options.account_prefix, index),
+            format!("{}{}", options.account_prefix, index),
+        );
+
+        let result = run_session(&mut client, index, &options, &state).await;
+        client.disconnect().await;
+
+        if let Err(error) = result {
+            println!("Client {} failed: {:#}", index, error);
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(matches: &clap::ArgMatches, name: &str) -> T {
+    let value = matches.value_of(name).unwrap();
+    match value.parse() {
+        Ok(value) => value,
+        Err(_) => {
+            println!("Invalid value {} for --{}", value, name);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let command = Command::new("loadtest")
+        .about("Simulates many irose clients to measure server response latencies")
+        .arg(
+            Arg::new("login")
+                .long("login")
+                .takes_value(true)
+                .default_value("127.0.0.1:29000")
+                .help("Address of the login server."),
+        )
+        .arg(
+            Arg::new("clients")
+                .long("clients")
+                .takes_value(true)
+                .default_value("100")
+                .help("Number of clients to simulate."),
+        )
+        .arg(
+            Arg::new("connect-rate")
+                .long("connect-rate")
+                .takes_value(true)
+                .default_value("10")
+                .help("Number of clients which start connecting each second."),
+        )
+        .arg(
+            Arg::new("duration")
+                .long("duration")
+                .takes_value(true)
+                .default_value("60")
+                .help("How many seconds to run the load test for."),
+        )
+        .arg(
+            Arg::new("move-rate")
+                .long("move-rate")
+                .takes_value(true)
+                .default_value("20")
+                .help("Moves per minute of each client."),
+        )
+        .arg(
+            Arg::new("chat-rate")
+                .long("chat-rate")
+                .takes_value(true)
+                .default_value("5")
+                .help("Chat messages per minute of each client."),
+        )
+        .arg(
+            Arg::new("attack-rate")
+                .long("attack-rate")
+                .takes_value(true)
+                .default_value("10")
+                .help("Attacks on a nearby monster per minute of each client."),
+        )
+        .arg(
+            Arg::new("move-radius")
+                .long("move-radius")
+                .takes_value(true)
+                .default_value("2000")
+                .help("How far from their spawn position clients move."),
+        )
+        .arg(
+            Arg::new("account-prefix")
+                .long("account-prefix")
+                .takes_value(true)
+                .default_value("loadtest")
+                .help(
+                    "Prefix of the account and character names, which are created on first login.",
+                ),
+        )
+        .arg(
+            Arg::new("report-interval")
+                .long("report-interval")
+                .takes_value(true)
+                .default_value("10")
+                .help("How many seconds between each progress report."),
+        );
+    let matches = command.get_matches();
+
+    let num_clients: usize = parse_arg(&matches, "clients");
+    let connect_rate: f32 = parse_arg(&matches, "connect-rate");
+    let duration = Duration::from_secs(parse_arg(&matches, "duration"));
+    let report_interval = Duration::from_secs(parse_arg(&matches, "report-interval"));
+    let options = LoadTestOptions {
+        login_address: parse_arg(&matches, "login"),
+        account_prefix: matches.value_of("account-prefix").unwrap().to_string(),
+        end_time: Instant::now() + duration,
+        move_rate: parse_arg(&matches, "move-rate"),
+        chat_rate: parse_arg(&matches, "chat-rate"),
+        attack_rate: parse_arg(&matches, "attack-rate"),
+        move_radius: parse_arg(&matches, "move-radius"),
+    };
+    if options.total_rate() <= 0.0 || connect_rate <= 0.0 {
+        println!("The connect rate and at least one action rate must be above 0");
+        std::process::exit(1);
+    }
+
+    let state = Arc::new(LoadTestState {
+        stats: Mutex::new(LoadTestStats::default()),
+        num_in_game: AtomicUsize::new(0),
+    });
+
+    println!(
+        "Starting {} clients against {} for {} seconds",
+        num_clients,
+        options.login_address,
+        duration.as_secs()
+    );
+
+    let report_state = state.clone();
+    let report_end_time = options.end_time;
+    tokio::spawn(async move {
+        let start = Instant::now();
+        loop {
+            tokio::time::sleep(report_interval).await;
+            if Instant::now() >= report_end_time {
+                break;
+            }
+
+            println!(
+                "{}s: {} clients in game",
+                start.elapsed().as_secs(),
+                report_state.num_in_game.load(Ordering::Relaxed)
+            );
+            report_state.stats.lock().unwrap().print();
+        }
+    });
+
+    let mut tasks = Vec::with_capacity(num_clients);
+    let connect_interval = Duration::from_secs_f32(1.0 / connect_rate);
+    for index in 0..num_clients {
+        tasks.push(tokio::spawn(run_client(
+            index,
+            options.clone(),
+            state.clone(),
+        )));
+        tokio::time::sleep(connect_interval).await;
+    }
+
+    for task in tasks {
+        task.await.ok();
+    }
+
+    println!("Results for {} clients:", num_clients);
+    state.stats.lock().unwrap().print();
+}
@@ -0,0 +1,65 @@
+use std::{collections::BTreeMap, time::Duration};
+
+/// The server response latencies and errors of every client, grouped by the
+/// request which was made.
+#[derive(Default)]
+pub struct LoadTestStats {
+    latencies: BTreeMap<&'static str, Vec<Duration>>,
+    errors: BTreeMap<&'static str, usize>,
+}
+
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    let index = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+    sorted[index]
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl LoadTestStats {
+    pub fn add_latency(&mut self, request: &'static str, latency: Duration) {
+        self.latencies.entry(request).or_default().push(latency);
+    }
+
+    pub fn add_error(&mut self, request: &'static str) {
+        *self.errors.entry(request).or_default() += 1;
+    }
+
+    pub fn print(&self) {
+        println!(
+            "  {:<10} {:>8} {:>10} {:>10} {:>10} {:>8}",
+            "request", "count", "p50", "p99", "max", "errors"
+        );
+
+        let mut requests: Vec<&'static str> = self.latencies.keys().copied().collect();
+        for request in self.errors.keys() {
+            if !requests.contains(request) {
+                requests.push(request);
+            }
+        }
+
+        for request in requests {
+            let errors = self.errors.get(request).copied().unwrap_or(0);
+            let mut latencies = self.latencies.get(request).cloned().unwrap_or_default();
+            if latencies.is_empty() {
+                println!(
+                    "  {:<10} {:>8} {:>10} {:>10} {:>10} {:>8}",
+                    request, 0, "-", "-", "-", errors
+                );
+                continue;
+            }
+
+            latencies.sort();
+            println!(
+                "  {:<10} {:>8} {:>10} {:>10} {:>10} {:>8}",
+                request,
+                latencies.len(),
+                format_duration(percentile(&latencies, 0.5)),
+                format_duration(percentile(&latencies, 0.99)),
+                format_duration(*latencies.last().unwrap()),
+                errors
+            );
+        }
+    }
+}
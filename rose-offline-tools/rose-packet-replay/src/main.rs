@@ -0,0 +1,353 @@
+use std::path::Path;
+
+use clap::Command;
+use num_traits::FromPrimitive;
+
+use rose_network_common::{Packet, PacketDirection, PacketDumpEntry, PacketError};
+use rose_network_irose::{
+    game_client_packets, game_server_packets, login_client_packets, login_server_packets,
+    world_client_packets, world_server_packets,
+};
+
+/// Decodes a packet with the packet type for its opcode and encodes it again,
+/// returns None when the opcode has no packet type.
+macro_rules! reencode_packet {
+    ($packet:expr, $packets:ident, { $($opcode:ident => $packet_type:ident,)* }) => {
+        match $packets::from_u16($packet.command)? {
+            $($packets::$opcode => Some($packet_type::try_from($packet).map(|decoded| Packet::from(&decoded))),)*
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    };
+}
+
+fn reencode_login_client_packet(packet: &Packet) -> Option<Result<Packet, PacketError>> {
+    use login_client_packets::*;
+    reencode_packet!(packet, ClientPackets, {
+        LoginRequest => PacketClientLoginRequest,
+        ChannelList => PacketClientChannelList,
+        SelectServer => PacketClientSelectServer,
+    })
+}
+
+fn reencode_login_server_packet(packet: &Packet) -> Option<Result<Packet, PacketError>> {
+    use login_server_packets::*;
+    reencode_packet!(packet, ServerPackets, {
+        NetworkStatus => PacketConnectionReply,
+        LoginReply => PacketServerLoginReply,
+        ChannelList => PacketServerChannelList,
+        SelectServer => PacketServerSelectServer,
+    })
+}
+
+fn reencode_world_client_packet(packet: &Packet) -> Option<Result<Packet, PacketError>> {
+    use world_client_packets::*;
+    reencode_packet!(packet, ClientPackets, {
+        ConnectRequest => PacketClientConnectRequest,
+        CharacterListRequest => PacketClientCharacterList,
+        CreateCharacter => PacketClientCreateCharacter,
+        DeleteCharacter => PacketClientDeleteCharacter,
+        SelectCharacter => PacketClientSelectCharacter,
+        ClanCommand => PacketClientClanCommand,
+        SecondaryPin => PacketClientSecondaryPin,
+    })
+}
+
+fn reencode_world_server_packet(packet: &Packet) -> Option<Result<Packet, PacketError>> {
+    use world_server_packets::*;
+    reencode_packet!(packet, ServerPackets, {
+        ConnectReply => PacketConnectionReply,
+        CharacterListReply => PacketServerCharacterList,
+        CreateCharacterReply => PacketServerCreateCharacterReply,
+        DeleteCharacterReply => PacketServerDeleteCharacterReply,
+        MoveServer => PacketServerMoveServer,
+        SecondaryPinRequest => PacketServerSecondaryPinRequest,
+    })
+}
+
+fn reencode_game_client_packet(packet: &Packet) -> Option<Result<Packet, PacketError>> {
+    use game_client_packets::*;
+    reencode_packet!(packet, ClientPackets, {
+        ConnectRequest => PacketClientConnectRequest,
+        JoinZone => PacketClientJoinZone,
+        Move => PacketClientMove,
+        Attack => PacketClientAttack,
+        Chat => PacketClientChat,
+        ShoutChat => PacketClientShoutChat,
+        SetHotbarSlot => PacketClientSetHotbarSlot,
+        ChangeEquipment => PacketClientChangeEquipment,
+        ChangeVehiclePart => PacketClientChangeVehiclePart,
+        IncreaseBasicStat => PacketClientIncreaseBasicStat,
+        PickupItemDrop => PacketClientPickupItemDrop,
+        ReviveRequest => PacketClientReviveRequest,
+        SetReviveZone => PacketClientSetReviveZone,
+        QuestRequest => PacketClientQuestRequest,
+        PersonalStoreListItems => PacketClientPersonalStoreListItems,
+        PersonalStoreBuyItem => PacketClientPersonalStoreBuyItem,
+        PersonalStoreOpen => PacketClientPersonalStoreOpen,
+        RepairItemUsingItem => PacketClientRepairItemUsingItem,
+        RepairItemUsingNpc => PacketClientRepairItemUsingNpc,
+        DropItemFromInventory => PacketClientDropItemFromInventory,
+        UseItem => PacketClientUseItem,
+        LevelUpSkill => PacketClientLevelUpSkill,
+        CastSkillSelf => PacketClientCastSkillSelf,
+        CastSkillTargetEntity => PacketClientCastSkillTargetEntity,
+        CastSkillTargetPosition => PacketClientCastSkillTargetPosition,
+        NpcStoreTransaction => PacketClientNpcStoreTransaction,
+        ChangeAmmo => PacketClientChangeAmmo,
+        MoveToggle => PacketClientMoveToggle,
+        Emote => PacketClientEmote,
+        WarpGateRequest => PacketClientWarpGateRequest,
+        PartyRequest => PacketClientPartyRequest,
+        PartyReply => PacketClientPartyReply,
+        PartyUpdateRules => PacketClientPartyUpdateRules,
+        MoveCollision => PacketClientMoveCollision,
+        CraftItem => PacketClientCraftItem,
+        BankOpen => PacketClientBankOpen,
+        BankMoveItem => PacketClientBankMoveItem,
+        ClanCommand => PacketClientClanCommand,
+        ClientIntegrityResponse => PacketClientClientIntegrityResponse,
+    })
+}
+
+fn reencode_game_server_packet(packet: &Packet) -> Option<Result<Packet, PacketError>> {
+    use game_server_packets::*;
+    reencode_packet!(packet, ServerPackets, {
+        ConnectReply => PacketConnectionReply,
+        SelectCharacter => PacketServerSelectCharacter,
+        CharacterInventory => PacketServerCharacterInventory,
+        QuestData => PacketServerCharacterQuestData,
+        AttackEntity => PacketServerAttackEntity,
+        DamageEntity => PacketServerDamageEntity,
+        MoveEntity => PacketServerMoveEntity,
+        MoveEntityWithMoveMode => PacketServerMoveEntity,
+        JoinZone => PacketServerJoinZone,
+        LocalChat => PacketServerLocalChat,
+        ShoutChat => PacketServerShoutChat,
+        AnnounceChat => PacketServerAnnounceChat,
+        Whisper => PacketServerWhisper,
+        StopMoveEntity => PacketServerStopMoveEntity,
+        Teleport => PacketServerTeleport,
+        SetHotbarSlot => PacketServerSetHotbarSlot,
+        SpawnEntityItemDrop => PacketServerSpawnEntityItemDrop,
+        SpawnEntityNpc => PacketServerSpawnEntityNpc,
+        SpawnEntityMonster => PacketServerSpawnEntityMonster,
+        SpawnEntityCharacter => PacketServerSpawnEntityCharacter,
+        RemoveEntities => PacketServerRemoveEntities,
+        UpdateInventory => PacketServerUpdateInventory,
+        UpdateMoneyAndInventory => PacketServerUpdateInventory,
+        UpdateMoney => PacketServerUpdateMoney,
+        RewardItems => PacketServerRewardItems,
+        RewardMoney => PacketServerRewardMoney,
+        UpdateAmmo => PacketServerUpdateAmmo,
+        UpdateEquipment => PacketServerUpdateEquipment,
+        UpdateVehiclePart => PacketServerUpdateVehiclePart,
+        UpdateItemLife => PacketServerUpdateItemLife,
+        UpdateLevel => PacketServerUpdateLevel,
+        UpdateXpStamina => PacketServerUpdateXpStamina,
+        UpdateBasicStat => PacketServerUpdateBasicStat,
+        PickupItemDropResult => PacketServerPickupItemDropResult,
+        LogoutResult => PacketServerLogoutResult,
+        QuestResult => PacketServerQuestResult,
+        UpdateAbilityValueRewardAdd => PacketServerUpdateAbilityValue,
+        UpdateAbilityValueRewardSet => PacketServerUpdateAbilityValue,
+        LearnSkillResult => PacketServerLearnSkillResult,
+        LevelUpSkillResult => PacketServerLevelUpSkillResult,
+        RunNpcDeathTrigger => PacketServerRunNpcDeathTrigger,
+        OpenPersonalStore => PacketServerOpenPersonalStore,
+        ClosePersonalStore => PacketServerClosePersonalStore,
+        PersonalStoreItemList => PacketServerPersonalStoreItemList,
+        PersonalStoreTransactionUpdateMoneyAndInventory => PacketServerPersonalStoreTransactionUpdateMoneyAndInventory,
+        PersonalStoreTransactionResult => PacketServerPersonalStoreTransactionResult,
+        UseItem => PacketServerUseItem,
+        CastSkillSelf => PacketServerCastSkillSelf,
+        CastSkillTargetEntity => PacketServerCastSkillTargetEntity,
+        CastSkillTargetPosition => PacketServerCastSkillTargetPosition,
+        StartCastingSkill => PacketServerStartCastingSkill,
+        ApplySkillEffect => PacketServerApplySkillEffect,
+        ApplySkillDamage => PacketServerApplySkillDamage,
+        CancelCastingSkill => PacketServerCancelCastingSkill,
+        FinishCastingSkill => PacketServerFinishCastingSkill,
+        UpdateSpeed => PacketServerUpdateSpeed,
+        UpdateStatusEffects => PacketServerUpdateStatusEffects,
+        NpcStoreTransactionError => PacketServerNpcStoreTransactionError,
+        MoveToggle => PacketServerMoveToggle,
+        UseEmote => PacketServerUseEmote,
+        PartyRequest => PacketServerPartyRequest,
+        PartyReply => PacketServerPartyReply,
+        PartyMembers => PacketServerPartyMembers,
+        PartyMemberUpdateInfo => PacketServerPartyMemberUpdateInfo,
+        PartyMemberRewardItem => PacketServerPartyMemberRewardItem,
+        ChangeNpcId => PacketServerChangeNpcId,
+        PartyUpdateRules => PacketServerPartyUpdateRules,
+        AdjustPosition => PacketServerAdjustPosition,
+        CraftItem => PacketServerCraftItem,
+        BankOpen => PacketServerBankOpen,
+        BankTransaction => PacketServerBankTransaction,
+        RepairedItemUsingNpc => PacketServerRepairedItemUsingNpc,
+        ClanCommand => PacketServerClanCommand,
+        ClientIntegrityChallenge => PacketServerClientIntegrityChallenge,
+    })
+}
+fn get_packet_name(client_type: &str, direction: PacketDirection, command: u16) -> Option<String> {
+    match (client_type, direction) {
+        ("login", PacketDirection::Recv) => {
+            login_client_packets::ClientPackets::from_u16(command).map(|x| format!("{:?}", x))
+        }
+        ("login", PacketDirection::Send) => {
+            login_server_packets::ServerPackets::from_u16(command).map(|x| format!("{:?}", x))
+        }
+        ("world", PacketDirection::Recv) => {
+            world_client_packets::ClientPackets::from_u16(command).map(|x| format!("{:?}", x))
+        }
+        ("world", PacketDirection::Send) => {
+            world_server_packets::ServerPackets::from_u16(command).map(|x| format!("{:?}", x))
+        }
+        ("game", PacketDirection::Recv) => {
+            game_client_packets::ClientPackets::from_u16(command).map(|x| format!("{:?}", x))
+        }
+        ("game", PacketDirection::Send) => {
+            game_server_packets::ServerPackets::from_u16(command).map(|x| format!("{:?}", x))
+        }
+        _ => None,
+    }
+}
+
+fn reencode(
+    client_type: &str,
+    direction: PacketDirection,
+    packet: &Packet,
+) -> Option<Result<Packet, PacketError>> {
+    match (client_type, direction) {
+        ("login", PacketDirection::Recv) => reencode_login_client_packet(packet),
+        ("login", PacketDirection::Send) => reencode_login_server_packet(packet),
+        ("world", PacketDirection::Recv) => reencode_world_client_packet(packet),
+        ("world", PacketDirection::Send) => reencode_world_server_packet(packet),
+        ("game", PacketDirection::Recv) => reencode_game_client_packet(packet),
+        ("game", PacketDirection::Send) => reencode_game_server_packet(packet),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct ReplayResult {
+    num_checked: usize,
+    num_unchecked: usize,
+    num_failures: usize,
+}
+
+/// Decodes every packet in a dump with the irose packet definitions and
+/// checks it encodes back to the same bytes, which catches packets whose
+/// layout the server and the packet definitions disagree on.
+fn replay_packet_dump(path: &Path, verbose: bool) -> Result<ReplayResult, std::io::Error> {
+    let str = std::fs::read_to_string(path)?;
+
+    let mut client_type = "game";
+    for line in str.lines() {
+        if let Some(value) = PacketDumpEntry::parse_header(line, "client_type") {
+            client_type = value;
+        }
+    }
+
+    let mut result = ReplayResult::default();
+    for (line_number, line) in str.lines().enumerate() {
+        let entry = match PacketDumpEntry::parse(line) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => continue,
+            Err(error) => {
+                println!("{}:{}: {}", path.display(), line_number + 1, error);
+                result.num_failures += 1;
+                continue;
+            }
+        };
+
+        let packet = Packet::with_data(entry.command, entry.data.as_slice().into());
+        let packet_name = get_packet_name(client_type, entry.direction, entry.command)
+            .unwrap_or_else(|| String::from("Unknown"));
+        let failure = match reencode(client_type, entry.direction, &packet) {
+            None => {
+                result.num_unchecked += 1;
+                None
+            }
+            Some(Err(error)) => Some(format!("failed to decode: {}", error)),
+            Some(Ok(reencoded)) => {
+                result.num_checked += 1;
+                if reencoded.command != entry.command || reencoded.data[..] != entry.data[..] {
+                    Some(format!(
+                        "encodes differently after decoding: {:02x?}",
+                        &reencoded.data[..]
+                    ))
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let Some(failure) = failure {
+            println!(
+                "{}:{}: {} [{:03X}] {} {}",
+                path.display(),
+                line_number + 1,
+                entry.direction.as_str(),
+                entry.command,
+                packet_name,
+                failure
+            );
+            result.num_failures += 1;
+        } else if verbose {
+            println!(
+                "{} {} [{:03X}] {} {} bytes",
+                entry.time_ms,
+                entry.direction.as_str(),
+                entry.command,
+                packet_name,
+                entry.data.len()
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+fn main() {
+    let command = Command::new("rose-packet-replay")
+        .about("Checks packet dumps written by rose-offline-server --packet-dump decode and encode with the irose packet definitions")
+        .arg(
+            clap::Arg::new("verbose")
+                .long("verbose")
+                .help("Print every packet instead of only failures."),
+        )
+        .arg(
+            clap::Arg::new("files")
+                .help("Packet dump files to replay")
+                .takes_value(true)
+                .multiple_values(true)
+                .required(true),
+        );
+    let matches = command.get_matches();
+    let verbose = matches.is_present("verbose");
+
+    let mut total = ReplayResult::default();
+    for file in matches.values_of("files").unwrap() {
+        match replay_packet_dump(Path::new(file), verbose) {
+            Ok(result) => {
+                total.num_checked += result.num_checked;
+                total.num_unchecked += result.num_unchecked;
+                total.num_failures += result.num_failures;
+            }
+            Err(error) => {
+                println!("Failed to read {}: {}", file, error);
+                total.num_failures += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} packets checked, {} packets have no packet definition to check",
+        total.num_checked, total.num_unchecked
+    );
+    if total.num_failures > 0 {
+        println!("{} packets failed to replay", total.num_failures);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+};
+
+use bevy::ecs::prelude::Entity;
+use clap::Command;
+
+use rose_offline_server::{
+    components::{CommandCastSkillTarget, CommandData},
+    storage::zone_snapshot::{ZoneSnapshot, ZoneSnapshotEntity},
+};
+
+/// Entities which moved less than this distance are not reported by a diff
+const DIFF_MIN_DISTANCE: f32 = 1.0;
+
+fn command_name(command: &CommandData) -> String {
+    let debug = format!("{:?}", command);
+    debug
+        .split([' ', '{', '('])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn command_target(command: &CommandData) -> Option<Entity> {
+    match command {
+        CommandData::Move {
+            target: Some(target),
+            ..
+        }
+        | CommandData::Attack { target }
+        | CommandData::PickupItemDrop { target }
+        | CommandData::CastSkill {
+            skill_target: Some(CommandCastSkillTarget::Entity(target)),
+            ..
+        } => Some(*target),
+        _ => None,
+    }
+}
+
+fn entity_type_name(entity: &ZoneSnapshotEntity) -> String {
+    entity.entity_type.map_or_else(
+        || String::from("Other"),
+        |entity_type| format!("{:?}", entity_type),
+    )
+}
+
+fn format_entity(entity: &ZoneSnapshotEntity) -> String {
+    let mut text = format!(
+        "{:?} [{}] {} {} at ({:.0}, {:.0}, {:.0})",
+        entity.entity,
+        entity
+            .client_entity_id
+            .map_or_else(|| String::from("-"), |id| id.0.to_string()),
+        entity_type_name(entity),
+        entity.name.as_deref().unwrap_or("?"),
+        entity.position.x,
+        entity.position.y,
+        entity.position.z,
+    );
+
+    if let Some(health) = entity.health {
+        text += &format!(" hp {}/{}", health, entity.max_health.unwrap_or(0));
+    }
+    if let Some(level) = entity.level {
+        text += &format!(" level {}", level);
+    }
+    if let Some(team) = entity.team {
+        text += &format!(" team {}", team);
+    }
+    if let Some(command) = entity.command.as_ref() {
+        text += &format!(" command {}", command_name(&command.command));
+        if let Some(target) = command_target(&command.command) {
+            text += &format!(" -> {:?}", target);
+        }
+    }
+    if let Some(next_command) = entity.next_command.as_ref() {
+        text += &format!(" next {}", command_name(next_command));
+    }
+
+    text
+}
+
+/// Returns the problems found in the snapshot which commonly cause desyncs,
+/// such as commands targeting entities which are not in the zone.
+fn find_problems(snapshot: &ZoneSnapshot) -> Vec<String> {
+    let mut problems = Vec::new();
+    let entities: HashSet<Entity> = snapshot
+        .entities
+        .iter()
+        .map(|entity| entity.entity)
+        .collect();
+    let mut client_entity_ids = HashMap::new();
+
+    for entity in snapshot.entities.iter() {
+        if let Some(client_entity_id) = entity.client_entity_id {
+            if let Some(other) = client_entity_ids.insert(client_entity_id.0, entity.entity) {
+                problems.push(format!(
+                    "{:?} and {:?} share client entity id {}",
+                    other, entity.entity, client_entity_id.0
+                ));
+            }
+        }
+
+        let Some(command) = entity.command.as_ref() else {
+            continue;
+        };
+
+        if let Some(target) = command_target(&command.command) {
+            if !entities.contains(&target) {
+                problems.push(format!(
+                    "{:?} {} targets {:?} which is not in the zone",
+                    entity.entity,
+                    command_name(&command.command),
+                    target
+                ));
+            }
+        }
+
+        let is_dead = matches!(command.command, CommandData::Die { .. });
+        if entity.health == Some(0) && !is_dead {
+            problems.push(format!(
+                "{:?} has no health but its command is {}",
+                entity.entity,
+                command_name(&command.command)
+            ));
+        } else if entity.health.map_or(false, |health| health > 0) && is_dead {
+            problems.push(format!(
+                "{:?} is dying with {} health",
+                entity.entity,
+                entity.health.unwrap_or(0)
+            ));
+        }
+    }
+
+    problems
+}
+
+fn print_report(
+    snapshot: &ZoneSnapshot,
+    entity_type: Option<&str>,
+    client_entity_id: Option<usize>,
+) {
+    println!(
+        "Zone {} snapshot by {} at {}, {} entities",
+        snapshot.zone_id.get(),
+        snapshot.created_by,
+        snapshot.created,
+        snapshot.entities.len()
+    );
+
+    let mut type_counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut command_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entity in snapshot.entities.iter() {
+        *type_counts.entry(entity_type_name(entity)).or_default() += 1;
+        if let Some(command) = entity.command.as_ref() {
+            *command_counts
+                .entry(command_name(&command.command))
+                .or_default() += 1;
+        }
+    }
+    for (name, count) in type_counts {
+        println!("  {}: {}", name, count);
+    }
+    println!("Commands:");
+    for (name, count) in command_counts {
+        println!("  {}: {}", name, count);
+    }
+
+    let problems = find_problems(snapshot);
+    if !problems.is_empty() {
+        println!("Problems:");
+        for problem in problems {
+            println!("  {}", problem);
+        }
+    }
+
+    println!("Entities:");
+    for entity in snapshot.entities.iter() {
+        if entity_type.map_or(false, |entity_type| {
+            !entity_type_name(entity).eq_ignore_ascii_case(entity_type)
+        }) {
+            continue;
+        }
+        if client_entity_id.map_or(false, |id| {
+            entity
+                .client_entity_id
+                .map(|client_entity_id| client_entity_id.0)
+                != Some(id)
+        }) {
+            continue;
+        }
+
+        println!("  {}", format_entity(entity));
+    }
+}
+
+/// Prints the entities which were added, removed or changed between the two
+/// snapshots of the same zone.
+fn print_diff(before: &ZoneSnapshot, after: &ZoneSnapshot) {
+    println!(
+        "Zone {} changes from {} to {}",
+        after.zone_id.get(),
+        before.created,
+        after.created
+    );
+    if before.zone_id != after.zone_id {
+        println!(
+            "Warning: comparing snapshots of different zones {} and {}",
+            before.zone_id.get(),
+            after.zone_id.get()
+        );
+    }
+
+    let before_entities: HashMap<Entity, &ZoneSnapshotEntity> = before
+        .entities
+        .iter()
+        .map(|entity| (entity.entity, entity))
+        .collect();
+    let after_entities: HashMap<Entity, &ZoneSnapshotEntity> = after
+        .entities
+        .iter()
+        .map(|entity| (entity.entity, entity))
+        .collect();
+
+    for entity in before.entities.iter() {
+        if !after_entities.contains_key(&entity.entity) {
+            println!("- {}", format_entity(entity));
+        }
+    }
+
+    for entity in after.entities.iter() {
+        let Some(before_entity) = before_entities.get(&entity.entity) else {
+            println!("+ {}", format_entity(entity));
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        let distance = before_entity.position.distance(entity.position);
+        if distance >= DIFF_MIN_DISTANCE {
+            changes.push(format!("moved {:.0}", distance));
+        }
+        if before_entity.health != entity.health {
+            changes.push(format!(
+                "hp {} -> {}",
+                before_entity.health.unwrap_or(0),
+                entity.health.unwrap_or(0)
+            ));
+        }
+        let before_command = before_entity
+            .command
+            .as_ref()
+            .map(|command| command_name(&command.command));
+        let after_command = entity
+            .command
+            .as_ref()
+            .map(|command| command_name(&command.command));
+        if before_command != after_command {
+            changes.push(format!(
+                "command {} -> {}",
+                before_command.as_deref().unwrap_or("-"),
+                after_command.as_deref().unwrap_or("-")
+            ));
+        }
+
+        if !changes.is_empty() {
+            println!("~ {} {}", format_entity(entity), changes.join(", "));
+        }
+    }
+}
+
+fn main() {
+    let command = Command::new("rose-zone-snapshot")
+        .about("Reports on zone snapshots saved by the zonesnapshot chat command")
+        .arg(
+            clap::Arg::new("type")
+                .long("type")
+                .takes_value(true)
+                .possible_values(["character", "monster", "npc", "itemdrop", "other"])
+                .help("Only list entities of this type."),
+        )
+        .arg(
+            clap::Arg::new("entity")
+                .long("entity")
+                .takes_value(true)
+                .help("Only list the entity with this client entity id."),
+        )
+        .arg(
+            clap::Arg::new("diff")
+                .long("diff")
+                .takes_value(true)
+                .help("A later snapshot of the same zone to compare against."),
+        )
+        .arg(
+            clap::Arg::new("file")
+                .help("Zone snapshot file")
+                .takes_value(true)
+                .required(true),
+        );
+    let matches = command.get_matches();
+
+    let file = matches.value_of("file").unwrap();
+    let snapshot = match ZoneSnapshot::load(Path::new(file)) {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            println!("Failed to read {}: {:#}", file, error);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(diff_file) = matches.value_of("diff") {
+        match ZoneSnapshot::load(Path::new(diff_file)) {
+            Ok(after) => print_diff(&snapshot, &after),
+            Err(error) => {
+                println!("Failed to read {}: {:#}", diff_file, error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let client_entity_id = match matches.value_of("entity").map(str::parse::<usize>) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => {
+            println!("Invalid client entity id");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    print_report(&snapshot, matches.value_of("type"), client_entity_id);
+}